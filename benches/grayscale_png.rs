@@ -0,0 +1,96 @@
+//! Benchmarks for converting the grayscale PNGs this crate creates back into ASCII.
+//!
+//! Most images that are round-tripped were drawn from ASCII by this crate, so they are
+//! grayscale PNGs. Each one is converted along with the same pixels stored as an RGB PNG, which
+//! has to be turned into Luma first, and the average time of each conversion is printed:
+//!
+//! ```text
+//! cargo bench --bench grayscale_png
+//! ```
+//!
+//! Robert Peterson and Kelsey Werner 2023
+
+use ascii_art_converter::{
+    ascii_to_image_with_render_mode,
+    converter::{
+        options::{ConvertOptions, ImageRenderMode},
+        symbol_map::SymbolMap,
+    },
+    image_to_ascii_with_options, image_to_ascii_writer,
+};
+use image::{DynamicImage, ImageFormat};
+use std::{
+    hint::black_box,
+    io::Cursor,
+    time::{Duration, Instant},
+};
+
+/// The number of times each conversion is timed.
+const ITERATIONS: u32 = 10;
+
+/// Time `convert` over [ITERATIONS] runs and print the average.
+fn bench<T>(name: &str, mut convert: impl FnMut() -> T) {
+    // warm up the caches before timing
+    black_box(convert());
+
+    let mut total = Duration::ZERO;
+    for _ in 0..ITERATIONS {
+        let start = Instant::now();
+        black_box(convert());
+        total += start.elapsed();
+    }
+
+    println!("{name:<40} {:>10.2?}", total / ITERATIONS);
+}
+
+/// Convert the test image to ASCII that is 200 characters wide and draw it back into a grayscale
+/// PNG in the given [ImageRenderMode].
+fn round_trip_png(render_mode: ImageRenderMode) -> Vec<u8> {
+    // The idea to use "CARGO_MANIFEST_DIR" comes from StackOverflow:
+    // https://stackoverflow.com/questions/30003921/how-can-i-locate-resources-for-testing-with-cargo
+    let image_path = concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/test_assets/images/freakazoid-large.png"
+    );
+    let options = ConvertOptions {
+        width: Some(200),
+        ..Default::default()
+    };
+    let ascii = image_to_ascii_with_options(std::path::Path::new(image_path), options).unwrap();
+
+    ascii_to_image_with_render_mode(&ascii, SymbolMap::default(), render_mode)
+        .unwrap()
+        .into_inner()
+}
+
+/// Store the pixels of a grayscale PNG as an RGB PNG.
+fn to_rgb_png(png: &[u8]) -> Vec<u8> {
+    let img = image::load_from_memory(png).unwrap();
+    let mut rgb = Cursor::new(Vec::new());
+    DynamicImage::ImageRgb8(img.to_rgb8())
+        .write_to(&mut rgb, ImageFormat::Png)
+        .unwrap();
+    rgb.into_inner()
+}
+
+fn main() {
+    for (name, render_mode) in [
+        ("pixel", ImageRenderMode::Pixels),
+        ("glyph", ImageRenderMode::Glyphs),
+    ] {
+        let gray = round_trip_png(render_mode);
+        let rgb = to_rgb_png(&gray);
+
+        for (color, png) in [("grayscale", &gray), ("RGB", &rgb)] {
+            bench(&format!("{color} {name} PNG to ASCII"), || {
+                image_to_ascii_with_options(png.as_slice(), ConvertOptions::default()).unwrap()
+            });
+            bench(&format!("{color} {name} PNG to ASCII writer"), || {
+                let mut written = Vec::new();
+                image_to_ascii_writer(png.as_slice(), ConvertOptions::default(), &mut written)
+                    .unwrap();
+                written
+            });
+        }
+    }
+}
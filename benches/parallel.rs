@@ -0,0 +1,100 @@
+//! Benchmarks for the `parallel` feature.
+//!
+//! Large images are converted to ASCII and large ASCII is converted back to an image, and the
+//! average time of each conversion is printed. Run the benchmarks once without the feature
+//! and once with it to see the speedup:
+//!
+//! ```text
+//! cargo bench --bench parallel
+//! cargo bench --bench parallel --features parallel
+//! ```
+//!
+//! Robert Peterson and Kelsey Werner 2023
+
+use ascii_art_converter::{
+    ascii_to_image, ascii_to_image_with_render_mode,
+    converter::{
+        options::{ConvertOptions, ImageRenderMode},
+        strategy::EdgeStrategy,
+        symbol_map::SymbolMap,
+    },
+    image_to_ascii_with_options, image_to_ascii_with_strategy,
+};
+use image::{DynamicImage, ImageFormat, Luma};
+use std::{
+    hint::black_box,
+    io::Cursor,
+    time::{Duration, Instant},
+};
+
+/// The number of times each conversion is timed.
+const ITERATIONS: u32 = 10;
+
+/// The width in characters of the ASCII converted from the image.
+const ASCII_WIDTH: u32 = 1000;
+
+/// Time `convert` over [ITERATIONS] runs and print the average.
+fn bench<T>(name: &str, mut convert: impl FnMut() -> T) {
+    // warm up the thread pool and caches before timing
+    black_box(convert());
+
+    let mut total = Duration::ZERO;
+    for _ in 0..ITERATIONS {
+        let start = Instant::now();
+        black_box(convert());
+        total += start.elapsed();
+    }
+
+    println!("{name:<32} {:>10.2?}", total / ITERATIONS);
+}
+
+/// Encode a noisy gradient as a PNG that is big enough for the conversion to take a while.
+fn large_png() -> Vec<u8> {
+    let img = image::GrayImage::from_fn(2000, 2000, |x, y| {
+        Luma([((x * 7 + y * 13 + (x * y) % 97) % 256) as u8])
+    });
+
+    let mut png = Cursor::new(Vec::new());
+    DynamicImage::ImageLuma8(img)
+        .write_to(&mut png, ImageFormat::Png)
+        .unwrap();
+    png.into_inner()
+}
+
+fn main() {
+    println!(
+        "parallel feature: {}",
+        if cfg!(feature = "parallel") {
+            "on"
+        } else {
+            "off"
+        }
+    );
+
+    let png = large_png();
+    let options = ConvertOptions {
+        width: Some(ASCII_WIDTH),
+        ..Default::default()
+    };
+
+    bench("image to ASCII", || {
+        image_to_ascii_with_options(png.as_slice(), options.clone()).unwrap()
+    });
+    bench("image to ASCII with edges", || {
+        image_to_ascii_with_strategy(png.as_slice(), options.clone(), &EdgeStrategy::default())
+            .unwrap()
+    });
+
+    let ascii = image_to_ascii_with_options(png.as_slice(), options.clone()).unwrap();
+    let ascii: String = ascii
+        .lines()
+        .take(200)
+        .map(|line| format!("{}\n", &line[..300]))
+        .collect();
+
+    bench("ASCII to glyph image", || ascii_to_image(&ascii).unwrap());
+    bench("ASCII to pixel image", || {
+        ascii_to_image_with_render_mode(&ascii, SymbolMap::default(), ImageRenderMode::Pixels)
+            .unwrap()
+    });
+}
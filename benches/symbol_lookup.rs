@@ -0,0 +1,83 @@
+//! Benchmarks for looking up the brightness of ASCII symbols.
+//!
+//! A megabyte of ASCII is mapped back to brightness values with the lookup table of the
+//! [SymbolMap] and with a linear search of its symbols, which is how symbols used to be looked
+//! up, and the average time of each is printed:
+//!
+//! ```text
+//! cargo bench --bench symbol_lookup
+//! ```
+//!
+//! Robert Peterson and Kelsey Werner 2023
+
+use ascii_art_converter::{
+    converter::{options::ConvertOptions, symbol_map::SymbolMap},
+    image_to_ascii_with_options,
+};
+use std::{
+    hint::black_box,
+    time::{Duration, Instant},
+};
+
+/// The number of times each lookup is timed.
+const ITERATIONS: u32 = 10;
+
+/// The approximate size in bytes of the ASCII that is looked up.
+const ASCII_BYTES: usize = 1 << 20;
+
+/// Time `lookup` over [ITERATIONS] runs and print the average.
+fn bench<T>(name: &str, mut lookup: impl FnMut() -> T) {
+    // warm up the caches before timing
+    black_box(lookup());
+
+    let mut total = Duration::ZERO;
+    for _ in 0..ITERATIONS {
+        let start = Instant::now();
+        black_box(lookup());
+        total += start.elapsed();
+    }
+
+    println!("{name:<32} {:>10.2?}", total / ITERATIONS);
+}
+
+/// Convert the test image to ASCII and repeat it until it is about [ASCII_BYTES] long.
+fn large_ascii() -> String {
+    // The idea to use "CARGO_MANIFEST_DIR" comes from StackOverflow:
+    // https://stackoverflow.com/questions/30003921/how-can-i-locate-resources-for-testing-with-cargo
+    let image_path = concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/test_assets/images/freakazoid-large.png"
+    );
+    let options = ConvertOptions {
+        width: Some(200),
+        ..Default::default()
+    };
+    let ascii = image_to_ascii_with_options(std::path::Path::new(image_path), options).unwrap();
+
+    ascii.repeat(ASCII_BYTES / ascii.len() + 1)
+}
+
+fn main() {
+    let ascii = large_ascii();
+    let symbol_map = SymbolMap::default();
+    let symbols: Vec<char> = symbol_map.ramp().chars().collect();
+    let step = symbol_map.brightness_step();
+
+    bench("lookup table", || {
+        ascii
+            .chars()
+            .filter(|c| *c != '\n')
+            .map(|c| symbol_map.brightness_for_symbol(c).unwrap() as u64)
+            .sum::<u64>()
+    });
+    bench("linear search", || {
+        ascii
+            .chars()
+            .filter(|c| *c != '\n')
+            .map(|c| {
+                let idx = symbols.iter().position(|s| *s == c).unwrap();
+                (idx as f32 * step) as u8 as u64
+            })
+            .sum::<u64>()
+    });
+}
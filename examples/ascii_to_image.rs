@@ -23,7 +23,7 @@ fn main() {
                 "./output/{}.png",
                 &args[0]
                     .split('/')
-                    .last()
+                    .next_back()
                     .unwrap()
                     .split('.')
                     .next()
@@ -0,0 +1,1016 @@
+//! Command-line tool that transforms images into ASCII art and ASCII art into images.
+//!
+//! Example usage:
+//!
+//!     cargo run --bin ascii-art -- to-ascii test_assets/images/freakazoid-large.png --width 80
+//!     cargo run --bin ascii-art -- to-ascii test_assets/images/freakazoid-large.png --max-chars 2000
+//!     cargo run --bin ascii-art -- to-ascii test_assets/images/freakazoid-large.png --no-fit
+//!     cargo run --bin ascii-art -- to-image test_assets/ascii/castle.txt -o castle.png
+//!     cat castle.txt | cargo run --bin ascii-art -- to-image > castle.png
+//!     cargo run --bin ascii-art -- to-image drawings/ -o images/ --watch
+//!
+//! The input of both subcommands is read from standard input when it is left out or is `-`, and
+//! the output is written to standard output unless a path is given with `--output`, so the tool
+//! can be used in a pipe. Text written to a terminal is scaled to fit in it, unless `--no-fit` is
+//! given, and `--width` and `--height` take the place of the size of the terminal.
+//!
+//! With `--watch`, the tool keeps running and converts the input again every time it changes,
+//! which is handy for iterating on ASCII art in an editor. The input can then also be a
+//! directory, in which case every file in it is converted when it changes, and `--output` names
+//! the directory the results are written to.
+//!
+//! This application is packaged as the ascii-art binary and leverages the ascii_art_converter
+//! library crate and the clap crate.
+//!
+//! Robert Peterson and Kelsey Werner 2023
+
+use ascii_art_converter::{
+    brightness_histogram,
+    converter::{
+        ascii::Ascii,
+        histogram::BrightnessHistogram,
+        metadata::PngMetadata,
+        normalize::DEFAULT_TAB_WIDTH,
+        options::{ConvertOptions, RenderCharset, UnknownSymbolPolicy},
+        symbol_map::SymbolMap,
+        terminal::TerminalCapabilities,
+        ConvertError,
+    },
+    image_to_ascii_with_options,
+};
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use notify::{Event, EventKind, RecursiveMode, Watcher};
+use std::{
+    collections::BTreeSet,
+    fmt, fs,
+    io::{self, Read, Write},
+    path::{Path, PathBuf},
+    process::ExitCode,
+    sync::mpsc::{channel, Receiver, RecvTimeoutError},
+    time::Duration,
+};
+use terminal_size::{terminal_size, Height, Width};
+
+/// How long to wait for more changes after a change, since editors often save a file in several steps.
+const WATCH_DEBOUNCE: Duration = Duration::from_millis(100);
+
+/// Struct to store the command-line arguments of the ascii-art tool.
+#[derive(Parser)]
+#[command(version, about = "Convert images to ASCII art and ASCII art to images")]
+struct Cli {
+    /// [Command] stores the conversion to run.
+    #[command(subcommand)]
+    command: Command,
+    /// Keep running and convert the input again whenever it changes.
+    #[arg(long, global = true)]
+    watch: bool,
+}
+
+/// Represent the conversions the ascii-art tool can run.
+#[derive(Subcommand)]
+enum Command {
+    /// Convert an image into ASCII art text.
+    ToAscii(ToAsciiArgs),
+    /// Convert ASCII art text into a PNG image.
+    ToImage(ToImageArgs),
+}
+
+/// Struct to store the arguments of the `to-ascii` subcommand.
+#[derive(Args)]
+struct ToAsciiArgs {
+    /// The image to convert, or `-` to read it from standard input.
+    #[arg(default_value = "-")]
+    input: String,
+    /// The file to write the text to instead of standard output.
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+    /// The width of the text in characters, which defaults to the width of the terminal, or of the image when it doesn't fit.
+    #[arg(short, long)]
+    width: Option<u32>,
+    /// The most lines the text may have, which defaults to the height of the terminal less a line for the prompt.
+    #[arg(long)]
+    height: Option<u32>,
+    /// Don't scale the text to fit the terminal, so it is as large as the image unless a width or height is given.
+    #[arg(long)]
+    no_fit: bool,
+    /// The most characters the text may have, counting line breaks, which makes it narrower until it fits.
+    #[arg(long)]
+    max_chars: Option<u32>,
+    /// The characters the text is made of.
+    #[arg(short, long, value_enum, default_value_t = Charset::Ascii)]
+    charset: Charset,
+    /// Use dark symbols for light pixels, for light text on a dark background.
+    #[arg(short, long)]
+    invert: bool,
+    /// The symbols the text is made of when the charset is ascii.
+    #[arg(long, value_enum, default_value_t = Symbols::Classic)]
+    symbols: Symbols,
+    /// Keep the colors of the image with colored half blocks, which needs a terminal with 24-bit color.
+    #[arg(long, conflicts_with = "charset")]
+    color: bool,
+    /// Pick the charset, symbols and cell shape that suit the terminal the tool runs in, from its environment variables.
+    #[arg(long, conflicts_with_all = ["charset", "symbols", "color"])]
+    terminal: bool,
+    /// Convert the image the way its pixels are stored, instead of turning it upright with the orientation in its EXIF metadata.
+    #[arg(long)]
+    ignore_exif_orientation: bool,
+    /// Start the ASCII art with a comment block of the size of the image and the settings it was converted with.
+    #[arg(long)]
+    metadata: bool,
+    /// The columns and lines of the terminal the text is fit to, or [None] to not fit it to a terminal.
+    #[arg(skip)]
+    fit: Option<(u32, u32)>,
+}
+
+/// Struct to store the arguments of the `to-image` subcommand.
+#[derive(Args)]
+struct ToImageArgs {
+    /// The ASCII art to convert, or `-` to read it from standard input.
+    #[arg(default_value = "-")]
+    input: String,
+    /// The file to write the PNG to instead of standard output.
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+    /// Read dark symbols as light pixels, for ASCII art made with `to-ascii --invert`.
+    #[arg(short, long)]
+    invert: bool,
+    /// The symbols the ASCII art is read with.
+    #[arg(long, value_enum, default_value_t = Symbols::Classic)]
+    symbols: Symbols,
+    /// Match the brightness of the PNG to a reference image, such as the photo the ASCII art was made from.
+    #[arg(long, value_name = "IMAGE")]
+    match_histogram: Option<PathBuf>,
+    /// Match the brightness of the PNG to the standard curve of a well exposed photo.
+    #[arg(long, conflicts_with = "match_histogram")]
+    photographic: bool,
+    /// What to do with characters that are not in the symbol map.
+    #[arg(long, value_enum, default_value_t = UnknownSymbols::Error)]
+    unknown_symbols: UnknownSymbols,
+    /// The number of columns between the tab stops that tabs are expanded to.
+    #[arg(long, default_value_t = DEFAULT_TAB_WIDTH)]
+    tab_width: u32,
+    /// Remove the spaces at the end of each line, so the PNG is only as wide as the longest line of symbols.
+    #[arg(long)]
+    trim_trailing_whitespace: bool,
+    /// The title written into the text chunks of the PNG.
+    #[arg(long)]
+    title: Option<String>,
+    /// The author written into the text chunks of the PNG.
+    #[arg(long)]
+    author: Option<String>,
+    /// Write the hash of the ASCII art into the text chunks of the PNG, along with the title and author if given.
+    #[arg(long)]
+    metadata: bool,
+}
+
+/// Represent the policies for unknown characters that can be picked with `--unknown-symbols`.
+#[derive(Clone, Copy, ValueEnum)]
+enum UnknownSymbols {
+    /// Stop with an error that points at the first of them.
+    Error,
+    /// Draw them as spaces.
+    SkipAsSpace,
+    /// Draw them as the symbol that looks the most like them.
+    NearestMatch,
+}
+
+impl From<UnknownSymbols> for UnknownSymbolPolicy {
+    /// Map the command-line choice onto the [UnknownSymbolPolicy] of the library.
+    fn from(policy: UnknownSymbols) -> Self {
+        match policy {
+            UnknownSymbols::Error => UnknownSymbolPolicy::Error,
+            UnknownSymbols::SkipAsSpace => UnknownSymbolPolicy::SkipAsSpace,
+            UnknownSymbols::NearestMatch => UnknownSymbolPolicy::NearestMatch,
+        }
+    }
+}
+
+/// Represent the character sets that can be picked with `--charset`.
+#[derive(Clone, Copy, ValueEnum)]
+enum Charset {
+    /// Symbols from the ASCII symbol map.
+    Ascii,
+    /// Braille characters, which pack 2x4 pixels each.
+    Braille,
+}
+
+/// Represent the symbol maps that can be picked with `--symbols`.
+#[derive(Clone, Copy, ValueEnum)]
+enum Symbols {
+    /// The ramp of 70 symbols, spread evenly from dark to light.
+    Classic,
+    /// Every printable ASCII symbol, each as bright as its glyph looks.
+    Printable,
+    /// The ramp of 70 symbols, each as bright as its glyph looks.
+    Calibrated,
+}
+
+impl From<Charset> for RenderCharset {
+    /// Map the command-line choice onto the [RenderCharset] of the library.
+    fn from(charset: Charset) -> Self {
+        match charset {
+            Charset::Ascii => RenderCharset::Ascii,
+            Charset::Braille => RenderCharset::Braille,
+        }
+    }
+}
+
+/// Represent the errors that stop the ascii-art tool.
+#[derive(Debug)]
+enum CliError {
+    /// [CliError::Read] is used when the input can't be read.
+    Read(String, io::Error),
+    /// [CliError::Write] is used when the output can't be written.
+    Write(String, io::Error),
+    /// [CliError::Convert] is used when the library fails to convert the input.
+    Convert(ConvertError),
+    /// [CliError::WatchStandardInput] is used when `--watch` is given without an input to watch.
+    WatchStandardInput,
+    /// [CliError::Watch] is used when the input can't be watched for changes.
+    Watch(String, notify::Error),
+}
+
+impl fmt::Display for CliError {
+    /// Describe the error in a way that points at what went wrong.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CliError::Read(input, e) => write!(f, "can't read {}: {}", input, e),
+            CliError::Write(output, e) => write!(f, "can't write {}: {}", output, e),
+            CliError::Convert(ConvertError::UnknownASCIISymbol(c, None)) => {
+                write!(
+                    f,
+                    "the ASCII art contains an unsupported character: {:?}",
+                    c
+                )
+            }
+            CliError::Convert(ConvertError::UnknownASCIISymbol(c, Some(position))) => {
+                write!(
+                    f,
+                    "the ASCII art contains an unsupported character on {}: {:?}",
+                    position, c
+                )
+            }
+            CliError::Convert(ConvertError::ReadError(_) | ConvertError::DecodeError(_)) => {
+                write!(f, "the input is not an image in a supported format")
+            }
+            CliError::Convert(e) => write!(f, "{}", e),
+            CliError::WatchStandardInput => {
+                write!(f, "--watch needs an input file or directory to watch")
+            }
+            CliError::Watch(input, e) => write!(f, "can't watch {}: {}", input, e),
+        }
+    }
+}
+
+/// Build the [SymbolMap] of the `symbols` to convert with, which is reversed when `invert` is true.
+fn symbol_map(symbols: Symbols, invert: bool) -> Result<SymbolMap, CliError> {
+    let symbol_map = match symbols {
+        Symbols::Classic => SymbolMap::default(),
+        Symbols::Printable => SymbolMap::printable(),
+        Symbols::Calibrated => {
+            SymbolMap::calibrated(&SymbolMap::default().ramp()).map_err(CliError::Convert)?
+        }
+    };
+    if !invert {
+        return Ok(symbol_map);
+    }
+
+    Ok(inverted(symbol_map))
+}
+
+/// Reverse the brightness of the symbols of a [SymbolMap], so dark symbols are used for light pixels.
+fn inverted(symbol_map: SymbolMap) -> SymbolMap {
+    match symbol_map.levels() {
+        Some(levels) => {
+            let inverted: Vec<(char, u8)> = symbol_map
+                .ramp()
+                .chars()
+                .zip(levels.iter().map(|b| u8::MAX - b))
+                .collect();
+            SymbolMap::from_levels(&inverted).unwrap_or(symbol_map)
+        }
+        None => {
+            let ramp: String = symbol_map.ramp().chars().rev().collect();
+            SymbolMap::from_ramp(&ramp).unwrap_or(symbol_map)
+        }
+    }
+}
+
+/// Read all of the input, from standard input if the input is `-` or from a file otherwise.
+fn read_input<R: Read>(input: &str, stdin: &mut R) -> Result<Vec<u8>, CliError> {
+    if input == "-" {
+        let mut data = Vec::new();
+        stdin
+            .read_to_end(&mut data)
+            .map(|_| data)
+            .map_err(|e| CliError::Read("standard input".to_string(), e))
+    } else {
+        fs::read(input).map_err(|e| CliError::Read(input.to_string(), e))
+    }
+}
+
+/// Write all of the output, to a file if a path is given or to standard output otherwise.
+fn write_output<W: Write>(
+    output: &Option<PathBuf>,
+    data: &[u8],
+    stdout: &mut W,
+) -> Result<(), CliError> {
+    match output {
+        Some(path) => {
+            fs::write(path, data).map_err(|e| CliError::Write(path.display().to_string(), e))
+        }
+        None => stdout
+            .write_all(data)
+            .and_then(|_| stdout.flush())
+            .map_err(|e| CliError::Write("standard output".to_string(), e)),
+    }
+}
+
+impl Command {
+    /// The input of the command, which is `-` for standard input.
+    fn input(&self) -> &str {
+        match self {
+            Command::ToAscii(args) => &args.input,
+            Command::ToImage(args) => &args.input,
+        }
+    }
+
+    /// The output of the command, which is [None] for standard output.
+    fn output(&self) -> &Option<PathBuf> {
+        match self {
+            Command::ToAscii(args) => &args.output,
+            Command::ToImage(args) => &args.output,
+        }
+    }
+
+    /// The extension of the files the command writes when a directory is watched.
+    fn output_extension(&self) -> &'static str {
+        match self {
+            Command::ToAscii(_) => "txt",
+            Command::ToImage(_) => "png",
+        }
+    }
+
+    /// Convert the contents of the input into the contents of the output.
+    fn convert(&self, input: Vec<u8>) -> Result<Vec<u8>, CliError> {
+        match self {
+            Command::ToAscii(args) => {
+                let options = if args.terminal {
+                    let options = TerminalCapabilities::detect().recommended_options();
+                    ConvertOptions {
+                        width: args.width(),
+                        height: args.height(),
+                        max_characters: args.max_chars,
+                        symbol_map: if args.invert {
+                            inverted(options.symbol_map.clone())
+                        } else {
+                            options.symbol_map.clone()
+                        },
+                        ignore_exif_orientation: args.ignore_exif_orientation,
+                        metadata: args.metadata,
+                        ..options
+                    }
+                } else {
+                    ConvertOptions {
+                        width: args.width(),
+                        height: args.height(),
+                        max_characters: args.max_chars,
+                        charset: if args.color {
+                            RenderCharset::HalfBlock
+                        } else {
+                            args.charset.into()
+                        },
+                        symbol_map: symbol_map(args.symbols, args.invert)?,
+                        ignore_exif_orientation: args.ignore_exif_orientation,
+                        metadata: args.metadata,
+                        ..Default::default()
+                    }
+                };
+
+                image_to_ascii_with_options(input, options)
+                    .map(String::into_bytes)
+                    .map_err(CliError::Convert)
+            }
+            Command::ToImage(args) => {
+                let ascii = String::from_utf8_lossy(&input);
+                let symbol_map = symbol_map(args.symbols, args.invert)?;
+
+                let mut ascii = Ascii::with_symbol_map(&ascii, symbol_map)
+                    .with_unknown_symbol_policy(args.unknown_symbols.into())
+                    .with_tab_width(args.tab_width)
+                    .with_trailing_whitespace_trimmed(args.trim_trailing_whitespace);
+                if let Some(target) = args.histogram_target()? {
+                    ascii = ascii.with_histogram_match(target);
+                }
+                if args.metadata || args.title.is_some() || args.author.is_some() {
+                    ascii = ascii.with_metadata(PngMetadata {
+                        title: args.title.clone(),
+                        author: args.author.clone(),
+                    });
+                }
+                ascii
+                    .convert_to_image()
+                    .map(|png| png.into_inner())
+                    .map_err(CliError::Convert)
+            }
+        }
+    }
+}
+
+impl ToAsciiArgs {
+    /// The columns and lines of a terminal of the given size that the text is fit to, or [None] when the text is
+    /// written to a file, `--no-fit` is given, or standard output is not a terminal.
+    ///
+    /// A line is left for the prompt that follows the text, so the top of the text doesn't scroll out of view.
+    fn terminal_fit(&self, size: Option<(Width, Height)>) -> Option<(u32, u32)> {
+        if self.no_fit || self.output.is_some() {
+            return None;
+        }
+
+        size.map(|(Width(columns), Height(lines))| {
+            (
+                u32::from(columns),
+                u32::from(lines).saturating_sub(1).max(1),
+            )
+        })
+    }
+
+    /// The width of the text, which is the width that is given or else the width of the terminal.
+    fn width(&self) -> Option<u32> {
+        self.width.or(self.fit.map(|(columns, _)| columns))
+    }
+
+    /// The most lines of the text, which is the height that is given or else the height of the terminal.
+    fn height(&self) -> Option<u32> {
+        self.height.or(self.fit.map(|(_, lines)| lines))
+    }
+}
+
+impl ToImageArgs {
+    /// The histogram the brightness of the PNG is matched to, or [None] to keep the brightness of the symbols.
+    fn histogram_target(&self) -> Result<Option<BrightnessHistogram>, CliError> {
+        if self.photographic {
+            return Ok(Some(BrightnessHistogram::photographic()));
+        }
+
+        match &self.match_histogram {
+            Some(path) => {
+                let reference =
+                    fs::read(path).map_err(|e| CliError::Read(path.display().to_string(), e))?;
+                brightness_histogram(reference, ConvertOptions::default())
+                    .map(Some)
+                    .map_err(CliError::Convert)
+            }
+            None => Ok(None),
+        }
+    }
+}
+
+/// Run the command, reading from `stdin` and writing to `stdout` when no files are given.
+fn run<R: Read, W: Write>(cli: Cli, stdin: &mut R, stdout: &mut W) -> Result<(), CliError> {
+    if cli.watch {
+        return watch(&cli.command, stdout);
+    }
+
+    let input = read_input(cli.command.input(), stdin)?;
+    let output = cli.command.convert(input)?;
+    write_output(cli.command.output(), &output, stdout)
+}
+
+/// Convert the input every time it changes, until the input can no longer be watched.
+///
+/// A file is converted once right away, while a directory is only converted as its files change.
+fn watch<W: Write>(command: &Command, stdout: &mut W) -> Result<(), CliError> {
+    // The code for watching a path for changes references the notify documentation:
+    // https://docs.rs/notify/6.1.1/notify/index.html
+
+    let input = command.input();
+    if input == "-" {
+        return Err(CliError::WatchStandardInput);
+    }
+
+    let (sender, receiver) = channel();
+    let mut watcher =
+        notify::recommended_watcher(sender).map_err(|e| CliError::Watch(input.to_string(), e))?;
+    watcher
+        .watch(Path::new(input), RecursiveMode::NonRecursive)
+        .map_err(|e| CliError::Watch(input.to_string(), e))?;
+
+    if Path::new(input).is_file() {
+        convert_changed_file(command, Path::new(input), false, stdout);
+    }
+
+    watch_events(command, receiver, stdout)
+}
+
+/// Convert the files named by the change events, until there are no more events.
+///
+/// Changes that arrive within [WATCH_DEBOUNCE] of each other are handled together, so each
+/// changed file is only converted once.
+fn watch_events<W: Write>(
+    command: &Command,
+    events: Receiver<notify::Result<Event>>,
+    stdout: &mut W,
+) -> Result<(), CliError> {
+    let watching_directory = Path::new(command.input()).is_dir();
+
+    while let Ok(event) = events.recv() {
+        let mut changed = BTreeSet::new();
+        let mut next_event = event;
+        loop {
+            let event = next_event.map_err(|e| CliError::Watch(command.input().to_string(), e))?;
+            if matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_)) {
+                changed.extend(event.paths);
+            }
+
+            next_event = match events.recv_timeout(WATCH_DEBOUNCE) {
+                Ok(event) => event,
+                Err(RecvTimeoutError::Timeout | RecvTimeoutError::Disconnected) => break,
+            };
+        }
+
+        for path in changed.iter().filter(|path| path.is_file()) {
+            convert_changed_file(command, path, watching_directory, stdout);
+        }
+    }
+
+    Ok(())
+}
+
+/// Convert a file that changed, and report any error without stopping the watch.
+///
+/// When a directory is watched, the result is written to a file with the same name in the output
+/// directory. Files that the tool wrote itself are skipped, so that writing a result into the
+/// watched directory doesn't set off another conversion.
+fn convert_changed_file<W: Write>(
+    command: &Command,
+    path: &Path,
+    watching_directory: bool,
+    stdout: &mut W,
+) {
+    let output = match command.output() {
+        Some(output_directory) if watching_directory => path.file_stem().map(|stem| {
+            output_directory.join(format!(
+                "{}.{}",
+                stem.to_string_lossy(),
+                command.output_extension()
+            ))
+        }),
+        output => output.clone(),
+    };
+    if output.as_deref() == Some(path) {
+        return;
+    }
+
+    let result = fs::read(path)
+        .map_err(|e| CliError::Read(path.display().to_string(), e))
+        .and_then(|input| command.convert(input))
+        .and_then(|converted| write_output(&output, &converted, stdout));
+    if let Err(e) = result {
+        eprintln!("error: {}: {}", path.display(), e);
+    }
+}
+
+fn main() -> ExitCode {
+    let mut cli = Cli::parse();
+    if let Command::ToAscii(args) = &mut cli.command {
+        args.fit = args.terminal_fit(terminal_size());
+    }
+
+    match run(cli, &mut io::stdin().lock(), &mut io::stdout().lock()) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+// Tests
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ascii_art_converter::{compare_images, converter::compare::Tolerance};
+
+    // Runs the tool with the given arguments and standard input, and returns standard output
+    fn run_with(args: &[&str], stdin: &[u8]) -> Result<Vec<u8>, CliError> {
+        let cli = Cli::try_parse_from([&["ascii-art"], args].concat()).unwrap();
+        let mut stdout = Vec::new();
+        run(cli, &mut &stdin[..], &mut stdout).map(|_| stdout)
+    }
+
+    // Verifies that the command-line arguments are declared correctly
+    #[test]
+    fn test_cli() {
+        use clap::CommandFactory;
+        Cli::command().debug_assert();
+    }
+
+    // Verifies that an image file is converted into the same ASCII art as the library creates
+    #[test]
+    fn test_to_ascii() {
+        // The idea to use "CARGO_MANIFEST_DIR" comes from StackOverflow:
+        // https://stackoverflow.com/questions/30003921/how-can-i-locate-resources-for-testing-with-cargo
+        let image_path = concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/test_assets/images/freakazoid-small.png"
+        );
+        let ascii_path = concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/test_assets/ascii/freakazoid-small.txt"
+        );
+
+        let stdout = run_with(&["to-ascii", image_path], &[]).unwrap();
+
+        assert_eq!(
+            String::from_utf8(stdout).unwrap(),
+            fs::read_to_string(ascii_path).unwrap()
+        );
+    }
+
+    // Verifies that an image piped through standard input is converted with the width, charset and invert options
+    #[test]
+    fn test_to_ascii_stdin() {
+        // The idea to use "CARGO_MANIFEST_DIR" comes from StackOverflow:
+        // https://stackoverflow.com/questions/30003921/how-can-i-locate-resources-for-testing-with-cargo
+        let image_path = concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/test_assets/images/freakazoid-small.png"
+        );
+        let image = fs::read(image_path).unwrap();
+
+        let ascii = run_with(&["to-ascii", "-w", "20"], &image).unwrap();
+        let ascii = String::from_utf8(ascii).unwrap();
+        assert!(ascii.lines().all(|l| l.len() == 20));
+
+        // the lightest pixels become the darkest symbol
+        let inverted = run_with(&["to-ascii", "-w", "20", "--invert"], &image).unwrap();
+        let inverted = String::from_utf8(inverted).unwrap();
+        assert_eq!(ascii.matches(' ').count(), inverted.matches('$').count());
+
+        let braille = run_with(&["to-ascii", "-w", "20", "-c", "braille"], &image).unwrap();
+        let braille = String::from_utf8(braille).unwrap();
+        assert!(braille
+            .chars()
+            .all(|c| c == '\n' || ('\u{2800}'..='\u{28FF}').contains(&c)));
+
+        let color = run_with(&["to-ascii", "-w", "20", "--color"], &image).unwrap();
+        assert!(String::from_utf8(color).unwrap().contains("\x1b[38;2;"));
+
+        let budgeted = run_with(&["to-ascii", "--max-chars", "1000"], &image).unwrap();
+        assert!(String::from_utf8(budgeted).unwrap().chars().count() <= 1000);
+    }
+
+    // Verifies that ASCII art is converted into the same PNG as the library creates, and written to a file
+    #[test]
+    fn test_to_image() {
+        // The idea to use "CARGO_MANIFEST_DIR" comes from StackOverflow:
+        // https://stackoverflow.com/questions/30003921/how-can-i-locate-resources-for-testing-with-cargo
+        let ascii_path = concat!(env!("CARGO_MANIFEST_DIR"), "/test_assets/ascii/castle.txt");
+        let ascii = fs::read(ascii_path).unwrap();
+        let expected = ascii_art_converter::ascii_to_image(&String::from_utf8_lossy(&ascii))
+            .unwrap()
+            .into_inner();
+
+        let stdout = run_with(&["to-image"], &ascii).unwrap();
+        let comparison = compare_images(expected.as_slice(), stdout, &Tolerance::exact()).unwrap();
+        assert!(comparison.is_similar(&Tolerance::exact()));
+
+        let output = tempfile::NamedTempFile::new().unwrap();
+        let output_path = output.path().to_str().unwrap();
+        let stdout = run_with(&["to-image", ascii_path, "-o", output_path], &[]).unwrap();
+        assert!(stdout.is_empty());
+
+        let comparison =
+            compare_images(expected.as_slice(), output.path(), &Tolerance::exact()).unwrap();
+        assert!(comparison.is_similar(&Tolerance::exact()));
+    }
+
+    // Verifies that the brightness of the PNG is matched to a reference image or the photographic curve
+    #[test]
+    fn test_to_image_match_histogram() {
+        // The idea to use "CARGO_MANIFEST_DIR" comes from StackOverflow:
+        // https://stackoverflow.com/questions/30003921/how-can-i-locate-resources-for-testing-with-cargo
+        let ascii_path = concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/test_assets/ascii/freakazoid-small.txt"
+        );
+        let image_path = concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/test_assets/images/freakazoid-small.png"
+        );
+        let ascii = fs::read(ascii_path).unwrap();
+        let plain = run_with(&["to-image"], &ascii).unwrap();
+
+        let matched = run_with(&["to-image", "--match-histogram", image_path], &ascii).unwrap();
+        assert_ne!(matched, plain);
+
+        let photographic = run_with(&["to-image", "--photographic"], &ascii).unwrap();
+        assert_ne!(photographic, plain);
+
+        let result = run_with(
+            &["to-image", "--match-histogram", "/no/such/image.png"],
+            &ascii,
+        );
+        assert!(matches!(result, Err(CliError::Read(..))));
+    }
+
+    // Verifies that --symbols printable reads and writes every printable ASCII symbol
+    #[test]
+    fn test_printable_symbols() {
+        // The idea to use "CARGO_MANIFEST_DIR" comes from StackOverflow:
+        // https://stackoverflow.com/questions/30003921/how-can-i-locate-resources-for-testing-with-cargo
+        let image_path = concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/test_assets/images/freakazoid-small.png"
+        );
+        let ascii = run_with(&["to-ascii", image_path, "--symbols", "printable"], &[]).unwrap();
+        let ascii = String::from_utf8(ascii).unwrap();
+        let printable = SymbolMap::printable();
+        assert!(ascii
+            .lines()
+            .flat_map(str::chars)
+            .all(|c| printable.brightness_for_symbol(c).is_ok()));
+
+        assert!(run_with(&["to-image"], b"=V=").is_err());
+        assert!(run_with(&["to-image", "--symbols", "printable"], b"=V=").is_ok());
+        assert!(run_with(&["to-image", "--symbols", "printable", "--invert"], b"=V=").is_ok());
+    }
+
+    // Verifies that --terminal converts with the options recommended for the terminal the tests run in
+    #[test]
+    fn test_terminal() {
+        // The idea to use "CARGO_MANIFEST_DIR" comes from StackOverflow:
+        // https://stackoverflow.com/questions/30003921/how-can-i-locate-resources-for-testing-with-cargo
+        let image_path = concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/test_assets/images/freakazoid-small.png"
+        );
+        let options = ConvertOptions {
+            width: Some(20),
+            ..TerminalCapabilities::detect().recommended_options()
+        };
+
+        let ascii = run_with(&["to-ascii", image_path, "-w", "20", "--terminal"], &[]).unwrap();
+        assert_eq!(
+            String::from_utf8(ascii).unwrap(),
+            image_to_ascii_with_options(fs::read(image_path).unwrap(), options).unwrap()
+        );
+
+        assert!(Cli::try_parse_from(["ascii-art", "to-ascii", "--terminal", "--color"]).is_err());
+    }
+
+    // Verifies that --symbols calibrated picks the classic symbols by how bright their glyphs look
+    #[test]
+    fn test_calibrated_symbols() {
+        // The idea to use "CARGO_MANIFEST_DIR" comes from StackOverflow:
+        // https://stackoverflow.com/questions/30003921/how-can-i-locate-resources-for-testing-with-cargo
+        let image_path = concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/test_assets/images/freakazoid-small.png"
+        );
+        let classic = run_with(&["to-ascii", image_path], &[]).unwrap();
+        let calibrated =
+            run_with(&["to-ascii", image_path, "--symbols", "calibrated"], &[]).unwrap();
+        assert_ne!(classic, calibrated);
+
+        let ascii = String::from_utf8(calibrated).unwrap();
+        let symbol_map = SymbolMap::default();
+        assert!(ascii
+            .lines()
+            .flat_map(str::chars)
+            .all(|c| symbol_map.brightness_for_symbol(c).is_ok()));
+        assert!(run_with(
+            &["to-image", "--symbols", "calibrated", "--invert"],
+            ascii.as_bytes()
+        )
+        .is_ok());
+    }
+
+    // Verifies that characters that are not in the symbol map are drawn as --unknown-symbols says
+    #[test]
+    fn test_to_image_unknown_symbols() {
+        let result = run_with(&["to-image"], b"=V=");
+        assert!(matches!(
+            result,
+            Err(CliError::Convert(ConvertError::UnknownASCIISymbol('=', _)))
+        ));
+
+        let skipped =
+            run_with(&["to-image", "--unknown-symbols", "skip-as-space"], b"=V=").unwrap();
+        let spaces = run_with(&["to-image"], b"   ").unwrap();
+        let comparison = compare_images(skipped.as_slice(), spaces, &Tolerance::exact()).unwrap();
+        assert!(comparison.is_similar(&Tolerance::exact()));
+
+        assert!(run_with(&["to-image", "--unknown-symbols", "nearest-match"], b"=V=").is_ok());
+    }
+
+    // Verifies that Windows line endings and tabs are read like the spaces they stand for, and that
+    // --trim-trailing-whitespace drops the spaces at the end of lines
+    #[test]
+    fn test_to_image_whitespace() {
+        let same = |a: Vec<u8>, b: Vec<u8>| {
+            compare_images(a.as_slice(), b, &Tolerance::exact())
+                .unwrap()
+                .is_similar(&Tolerance::exact())
+        };
+        let spaces = run_with(&["to-image"], b"$  .\n.  $\n").unwrap();
+
+        let tabs = run_with(&["to-image", "--tab-width", "3"], b"$\t.\r\n.\t$\r\n").unwrap();
+        assert!(same(tabs, spaces.clone()));
+
+        let trimmed = run_with(
+            &["to-image", "--trim-trailing-whitespace"],
+            b"$  .  \n.  $\n",
+        )
+        .unwrap();
+        assert!(same(trimmed, spaces));
+    }
+
+    // Verifies that --metadata starts the ASCII art with a header, which to-image draws without, and
+    // that --title is written into the PNG
+    #[test]
+    fn test_metadata() {
+        let image_path = concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/test_assets/images/freakazoid-small.png"
+        );
+        let plain = run_with(&["to-ascii", image_path], &[]).unwrap();
+        let ascii = run_with(&["to-ascii", "--metadata", image_path], &[]).unwrap();
+        let ascii = String::from_utf8(ascii).unwrap();
+        assert!(ascii.starts_with("# ascii-art-converter\n# source: "));
+        assert!(ascii.ends_with(std::str::from_utf8(&plain).unwrap()));
+
+        let png = run_with(&["to-image", "--title", "Freakazoid"], ascii.as_bytes()).unwrap();
+        assert!(png.windows(16).any(|w| w == b"Title\0Freakazoid"));
+        let plain_png = run_with(&["to-image"], &plain).unwrap();
+        assert!(
+            compare_images(png.as_slice(), plain_png, &Tolerance::exact())
+                .unwrap()
+                .is_similar(&Tolerance::exact())
+        );
+    }
+
+    // Verifies that text written to a terminal is fit to its size, unless a width or height is given or fitting is
+    // turned off
+    #[test]
+    fn test_terminal_fit() {
+        let image_path = concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/test_assets/images/freakazoid-small.png"
+        );
+        let terminal = Some((Width(40), Height(11)));
+        let fit = |args: &[&str]| {
+            let mut cli = Cli::try_parse_from([&["ascii-art", "to-ascii"], args].concat()).unwrap();
+            let Command::ToAscii(to_ascii) = &mut cli.command else {
+                unreachable!()
+            };
+            to_ascii.fit = to_ascii.terminal_fit(terminal);
+            let mut stdout = Vec::new();
+            run(cli, &mut io::empty(), &mut stdout).unwrap();
+            String::from_utf8(stdout).unwrap()
+        };
+        let size = |text: &str| {
+            let columns = text.lines().map(|l| l.chars().count()).max().unwrap();
+            (columns, text.lines().count())
+        };
+
+        let (columns, lines) = size(&fit(&[image_path]));
+        assert!(columns <= 40 && lines <= 10);
+        assert!(columns == 40 || lines == 10);
+
+        let (columns, lines) = size(&fit(&["--width", "20", image_path]));
+        assert!(columns == 20 && lines <= 10);
+        let (_, lines) = size(&fit(&["--height", "30", image_path]));
+        assert!(lines > 10 && lines <= 30);
+
+        let unfit = String::from_utf8(run_with(&["to-ascii", image_path], &[]).unwrap()).unwrap();
+        assert_eq!(fit(&["--no-fit", image_path]), unfit);
+
+        let cli = Cli::try_parse_from(["ascii-art", "to-ascii", "-o", "art.txt"]).unwrap();
+        let Command::ToAscii(to_ascii) = cli.command else {
+            unreachable!()
+        };
+        assert_eq!(to_ascii.terminal_fit(terminal), None);
+        assert_eq!(to_ascii.terminal_fit(None), None);
+    }
+
+    // Verifies that errors are reported with a message that points at what went wrong
+    #[test]
+    fn test_errors() {
+        let result = run_with(&["to-ascii", "/no/such/image.png"], &[]);
+        assert!(matches!(result, Err(CliError::Read(..))));
+
+        let result = run_with(&["to-ascii"], b"not an image");
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "the input is not an image in a supported format"
+        );
+
+        let result = run_with(&["to-image"], "ƒ".as_bytes());
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "the ASCII art contains an unsupported character on line 1, column 1: 'ƒ'"
+        );
+
+        assert!(
+            Cli::try_parse_from(["ascii-art", "to-ascii", "--color", "-c", "braille"]).is_err()
+        );
+    }
+
+    // Verifies that --watch is rejected when the input is standard input
+    #[test]
+    fn test_watch_standard_input() {
+        let result = run_with(&["to-ascii", "--watch"], &[]);
+        assert!(matches!(result, Err(CliError::WatchStandardInput)));
+    }
+
+    // Verifies that the files of a watched directory are converted into the output directory as they change
+    #[test]
+    fn test_watch_events() {
+        // The idea to use "CARGO_MANIFEST_DIR" comes from StackOverflow:
+        // https://stackoverflow.com/questions/30003921/how-can-i-locate-resources-for-testing-with-cargo
+        let ascii_path = concat!(env!("CARGO_MANIFEST_DIR"), "/test_assets/ascii/castle.txt");
+        let input = tempfile::tempdir().unwrap();
+        let output = tempfile::tempdir().unwrap();
+        let castle_path = input.path().join("castle.txt");
+        let deleted_path = input.path().join("deleted.txt");
+        fs::copy(ascii_path, &castle_path).unwrap();
+
+        let cli = Cli::try_parse_from([
+            "ascii-art",
+            "to-image",
+            input.path().to_str().unwrap(),
+            "-o",
+            output.path().to_str().unwrap(),
+            "--watch",
+        ])
+        .unwrap();
+        let (sender, receiver) = channel();
+        for path in [&castle_path, &castle_path, &deleted_path] {
+            let event = Event::new(EventKind::Modify(notify::event::ModifyKind::Any));
+            sender.send(Ok(event.add_path(path.clone()))).unwrap();
+        }
+        drop(sender);
+
+        let mut stdout = Vec::new();
+        watch_events(&cli.command, receiver, &mut stdout).unwrap();
+
+        assert!(stdout.is_empty());
+        let expected =
+            ascii_art_converter::ascii_to_image(&fs::read_to_string(ascii_path).unwrap())
+                .unwrap()
+                .into_inner();
+        let comparison = compare_images(
+            expected.as_slice(),
+            output.path().join("castle.png").as_path(),
+            &Tolerance::exact(),
+        )
+        .unwrap();
+        assert!(comparison.is_similar(&Tolerance::exact()));
+        assert_eq!(fs::read_dir(output.path()).unwrap().count(), 1);
+    }
+
+    // Verifies that a watched file is converted right away and again after it is saved
+    #[test]
+    fn test_watch_file() {
+        let input = tempfile::tempdir().unwrap();
+        let ascii_path = input.path().join("art.txt");
+        let output_path = input.path().join("art.png");
+        fs::write(&ascii_path, "$").unwrap();
+
+        let cli = Cli::try_parse_from([
+            "ascii-art",
+            "to-image",
+            ascii_path.to_str().unwrap(),
+            "-o",
+            output_path.to_str().unwrap(),
+            "--watch",
+        ])
+        .unwrap();
+        // the watch never ends, so it is left running when the test finishes
+        std::thread::spawn(move || run(cli, &mut io::empty(), &mut io::sink()));
+
+        let wait_for_output = |expected: &[u8]| {
+            for _ in 0..100 {
+                if fs::read(&output_path).is_ok_and(|png| png == expected) {
+                    return true;
+                }
+                std::thread::sleep(Duration::from_millis(50));
+            }
+            false
+        };
+
+        let small = ascii_art_converter::ascii_to_image("$")
+            .unwrap()
+            .into_inner();
+        assert!(wait_for_output(&small));
+
+        fs::write(&ascii_path, "$$\n$$").unwrap();
+        let large = ascii_art_converter::ascii_to_image("$$\n$$")
+            .unwrap()
+            .into_inner();
+        assert!(wait_for_output(&large));
+    }
+}
@@ -1,25 +1,394 @@
 //! Convert ASCII to images and images to ASCII.
 //!
 //! The submodules contain the logic to do all the ASCII <-> Image conversion.
-//! The [ConvertError] enum is shared amongst the submodules.
+//! The [ConvertError] enum is shared amongst the submodules. It implements [std::error::Error],
+//! so it can be used with `?` in functions that return other errors, and it keeps the
+//! [ErrorSource] of errors that were caused by the [image] crate or by reading and writing.
 //!
 //! Robert Peterson and Kelsey Werner 2023
 
+pub mod animation;
 pub mod ascii;
+pub mod asciicast;
+pub mod banner;
+pub mod batch;
+pub mod codec;
+pub mod collage;
+pub mod compare;
 pub mod dimension;
+pub mod dither;
+pub mod histogram;
 pub mod image;
+pub mod manifest;
+pub mod metadata;
+pub mod normalize;
+pub mod options;
+pub mod pipeline;
+pub mod png_text;
+#[cfg(feature = "qr")]
+pub mod qr;
+mod resize;
+pub mod ruler;
+pub mod service;
+pub mod source;
+pub mod steganography;
+pub mod strategy;
 pub mod symbol_map;
+pub mod terminal;
+pub mod testing;
+pub mod usage;
+#[cfg(feature = "video")]
+pub mod video;
+pub mod warning;
+
+use options::{MAX_BRIGHTNESS, MAX_CONTRAST_ADJUSTMENT};
+use std::{error::Error, fmt, sync::Arc};
 
 /// Represent the various errors that can happen during conversion.
 #[derive(Debug, PartialEq)]
 pub enum ConvertError {
-    /// [ConvertError::ReadError] is used when the [image] crate can't guess what format the image is.
-    ReadError,
-    /// [ConvertError::WriteError] is used when the [image] crate fails to write the final PNG data to a buffer.
-    WriteError,
-    /// [ConvertError::DecodeError] is used when the [image] crate can't parse the image.
-    DecodeError,
+    /// [ConvertError::ReadError] is used when the image can't be read, or the [image] crate can't
+    /// guess what format the image is. The [ErrorSource] is the error that stopped the read.
+    ReadError(ErrorSource),
+    /// [ConvertError::WriteError] is used when the [image] crate fails to write the final PNG data to a buffer,
+    /// or the text can't be written. The [ErrorSource] is the error that stopped the write.
+    WriteError(ErrorSource),
+    /// [ConvertError::DecodeError] is used when the [image] crate can't parse the image. The
+    /// [ErrorSource] is the error the image was rejected with.
+    DecodeError(ErrorSource),
     /// [ConvertError::UnknownASCIISymbol] is used when a user tries to turn ASCII
-    /// into an image but the ASCII contains a [char] that is not in the symbol map.
-    UnknownASCIISymbol(char),
+    /// into an image but the ASCII contains a [char] that is not in the symbol map. The
+    /// [SymbolPosition] is where the [char] is in the ASCII, or [None] when a single symbol
+    /// was looked up in the symbol map.
+    UnknownASCIISymbol(char, Option<SymbolPosition>),
+    /// [ConvertError::EmptyInput] is used when a user tries to turn ASCII into an image but the
+    /// ASCII is empty or only has line breaks, which would give an image with no pixels.
+    EmptyInput,
+    /// [ConvertError::TooLarge] is used when an image or a piece of ASCII is bigger than the
+    /// [SizeLimits](options::SizeLimits) of the conversion. The width and height are the size
+    /// that was too large, in pixels for images and in characters for text.
+    TooLarge { width: u32, height: u32 },
+    /// [ConvertError::EmptySymbolRamp] is used when a custom symbol ramp has no symbols.
+    EmptySymbolRamp,
+    /// [ConvertError::DuplicateSymbol] is used when a custom symbol ramp contains the same [char] more than once.
+    DuplicateSymbol(char),
+    /// [ConvertError::InvalidWidth] is used when a user asks for ASCII that is zero characters wide.
+    InvalidWidth,
+    /// [ConvertError::InvalidHeight] is used when a user asks for ASCII that is zero lines tall.
+    InvalidHeight,
+    /// [ConvertError::InvalidBudget] is used when a user asks for ASCII that fits in zero characters.
+    InvalidBudget,
+    /// [ConvertError::InvalidGamma] is used when a user asks for a gamma that is not a positive number.
+    InvalidGamma,
+    /// [ConvertError::InvalidCellAspect] is used when a user asks for a cell aspect ratio that is not a positive number.
+    InvalidCellAspect,
+    /// [ConvertError::InvalidAdjustment] is used when a user asks for a brightness or contrast adjustment that is
+    /// out of range.
+    InvalidAdjustment,
+    /// [ConvertError::InvalidCrop] is used when the region of the image to convert is empty or reaches past the
+    /// edge of the image.
+    InvalidCrop,
+    /// [ConvertError::InvalidPipeline] is used when the stages of a pipeline can't be applied,
+    /// such as a crop that reaches past the edge of the image.
+    InvalidPipeline,
+    /// [ConvertError::FontError] is used when the font used to draw ASCII characters can't be read.
+    FontError,
+    /// [ConvertError::InvalidManifest] is used when a conversion manifest is not valid JSON, has
+    /// settings that don't exist, or was written by a newer version of the converter.
+    InvalidManifest,
+    /// [ConvertError::DimensionMismatch] is used when two images that are compared are not the same size.
+    DimensionMismatch,
+    /// [ConvertError::CarrierTooSmall] is used when ASCII is hidden in an image that doesn't have
+    /// enough pixels to hold it.
+    CarrierTooSmall,
+    /// [ConvertError::NoHiddenText] is used when an image that is read for hidden ASCII doesn't
+    /// have any.
+    NoHiddenText,
+    /// [ConvertError::InvalidFontSize] is used when a user asks for a banner whose letters are zero lines tall.
+    InvalidFontSize,
+    /// [ConvertError::QrDataTooLong] is used when the text of a QR code has more bytes than the largest QR code holds.
+    QrDataTooLong,
+    /// [ConvertError::InvalidQrCode] is used when ASCII that is read as a QR code is not a square of modules
+    /// that is the size of a QR code.
+    InvalidQrCode,
+}
+
+impl ConvertError {
+    /// Add the [SymbolPosition] of a symbol to a [ConvertError::UnknownASCIISymbol] error.
+    ///
+    /// `x` is the column of the symbol and `y` is its line, both counted from 0. Other errors
+    /// are returned as they are.
+    pub(crate) fn at(self, x: u32, y: u32) -> ConvertError {
+        match self {
+            ConvertError::UnknownASCIISymbol(symbol, _) => {
+                ConvertError::UnknownASCIISymbol(symbol, Some(SymbolPosition { x, y }))
+            }
+            error => error,
+        }
+    }
+
+    /// The name of the variant of the error, such as `"TooLarge"`, without the values it holds.
+    ///
+    /// Errors of the same kind have the same name, so it can be used to count errors by kind.
+    pub fn name(&self) -> &'static str {
+        match self {
+            ConvertError::ReadError(_) => "ReadError",
+            ConvertError::WriteError(_) => "WriteError",
+            ConvertError::DecodeError(_) => "DecodeError",
+            ConvertError::UnknownASCIISymbol(..) => "UnknownASCIISymbol",
+            ConvertError::EmptyInput => "EmptyInput",
+            ConvertError::TooLarge { .. } => "TooLarge",
+            ConvertError::EmptySymbolRamp => "EmptySymbolRamp",
+            ConvertError::DuplicateSymbol(_) => "DuplicateSymbol",
+            ConvertError::InvalidWidth => "InvalidWidth",
+            ConvertError::InvalidHeight => "InvalidHeight",
+            ConvertError::InvalidBudget => "InvalidBudget",
+            ConvertError::InvalidGamma => "InvalidGamma",
+            ConvertError::InvalidCellAspect => "InvalidCellAspect",
+            ConvertError::InvalidAdjustment => "InvalidAdjustment",
+            ConvertError::InvalidCrop => "InvalidCrop",
+            ConvertError::InvalidPipeline => "InvalidPipeline",
+            ConvertError::FontError => "FontError",
+            ConvertError::InvalidManifest => "InvalidManifest",
+            ConvertError::DimensionMismatch => "DimensionMismatch",
+            ConvertError::CarrierTooSmall => "CarrierTooSmall",
+            ConvertError::NoHiddenText => "NoHiddenText",
+            ConvertError::InvalidFontSize => "InvalidFontSize",
+            ConvertError::QrDataTooLong => "QrDataTooLong",
+            ConvertError::InvalidQrCode => "InvalidQrCode",
+        }
+    }
+}
+
+impl fmt::Display for ConvertError {
+    /// Describe the error in a way that can be shown to the user of the converter.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ConvertError::ReadError(source) => write!(f, "the image can't be read: {}", source),
+            ConvertError::WriteError(source) => {
+                write!(f, "the result can't be written: {}", source)
+            }
+            ConvertError::DecodeError(source) => {
+                write!(f, "the image can't be decoded: {}", source)
+            }
+            ConvertError::UnknownASCIISymbol(symbol, None) => {
+                write!(f, "the symbol {:?} is not in the symbol map", symbol)
+            }
+            ConvertError::UnknownASCIISymbol(symbol, Some(position)) => write!(
+                f,
+                "the symbol {:?} at {} is not in the symbol map",
+                symbol, position
+            ),
+            ConvertError::EmptyInput => write!(f, "the ASCII has no characters to draw"),
+            ConvertError::TooLarge { width, height } => {
+                write!(
+                    f,
+                    "the size {}x{} is larger than the converter allows",
+                    width, height
+                )
+            }
+            ConvertError::EmptySymbolRamp => write!(f, "the symbol ramp has no symbols"),
+            ConvertError::DuplicateSymbol(symbol) => {
+                write!(
+                    f,
+                    "the symbol {:?} is in the symbol ramp more than once",
+                    symbol
+                )
+            }
+            ConvertError::InvalidWidth => write!(f, "the width must be at least one character"),
+            ConvertError::InvalidHeight => write!(f, "the height must be at least one line"),
+            ConvertError::InvalidBudget => {
+                write!(f, "the character budget must be at least one character")
+            }
+            ConvertError::InvalidGamma => write!(f, "the gamma must be a positive number"),
+            ConvertError::InvalidCellAspect => {
+                write!(f, "the cell aspect ratio must be a positive number")
+            }
+            ConvertError::InvalidAdjustment => {
+                write!(
+                    f,
+                    "the brightness must be from -{} to {} and the contrast from -{} to {} percent",
+                    MAX_BRIGHTNESS,
+                    MAX_BRIGHTNESS,
+                    MAX_CONTRAST_ADJUSTMENT,
+                    MAX_CONTRAST_ADJUSTMENT
+                )
+            }
+            ConvertError::InvalidCrop => {
+                write!(f, "the cropped region must be inside of the image")
+            }
+            ConvertError::InvalidPipeline => {
+                write!(
+                    f,
+                    "the stages of the pipeline can't be applied to the image"
+                )
+            }
+            ConvertError::FontError => write!(f, "the font can't be read"),
+            ConvertError::InvalidManifest => write!(f, "the manifest is not valid"),
+            ConvertError::DimensionMismatch => write!(f, "the images are not the same size"),
+            ConvertError::CarrierTooSmall => {
+                write!(f, "the image is too small to hide the ASCII in")
+            }
+            ConvertError::NoHiddenText => write!(f, "the image has no hidden ASCII"),
+            ConvertError::InvalidFontSize => write!(f, "the font size must be at least one line"),
+            ConvertError::QrDataTooLong => write!(f, "the text is too long to fit in a QR code"),
+            ConvertError::InvalidQrCode => write!(f, "the ASCII is not a QR code"),
+        }
+    }
+}
+
+impl Error for ConvertError {
+    /// The error that caused a read, write or decode error.
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ConvertError::ReadError(source)
+            | ConvertError::WriteError(source)
+            | ConvertError::DecodeError(source) => Some(source.error()),
+            _ => None,
+        }
+    }
+}
+
+/// [ErrorSource] is a struct that holds the underlying error that caused a [ConvertError], such
+/// as an [image::ImageError] or an [std::io::Error].
+///
+/// The underlying errors can't be cloned or compared, so the error is shared and two sources
+/// are equal when they describe the same error.
+#[derive(Debug, Clone)]
+pub struct ErrorSource(Arc<dyn Error + Send + Sync>);
+
+impl ErrorSource {
+    /// Create a new [ErrorSource] from the underlying error.
+    pub fn new<E: Error + Send + Sync + 'static>(error: E) -> ErrorSource {
+        ErrorSource(Arc::new(error))
+    }
+
+    /// Create a new [ErrorSource] from a message, for problems that are found by the converter
+    /// itself rather than by the [image] crate.
+    pub(crate) fn message(message: &str) -> ErrorSource {
+        ErrorSource(Arc::new(Message(message.to_string())))
+    }
+
+    /// The underlying error.
+    pub fn error(&self) -> &(dyn Error + Send + Sync + 'static) {
+        self.0.as_ref()
+    }
+}
+
+impl fmt::Display for ErrorSource {
+    /// Describe the underlying error.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl PartialEq for ErrorSource {
+    /// Compare the descriptions of the underlying errors.
+    fn eq(&self, other: &ErrorSource) -> bool {
+        self.to_string() == other.to_string()
+    }
+}
+
+/// [Message] is a struct that holds an error found by the converter itself.
+#[derive(Debug)]
+struct Message(String);
+
+impl fmt::Display for Message {
+    /// Write the message.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl Error for Message {}
+
+/// [SymbolPosition] is a struct that holds where a symbol is in a piece of ASCII.
+///
+/// The position is also the pixel the symbol becomes in [options::ImageRenderMode::Pixels].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SymbolPosition {
+    /// The column of the symbol, counted in characters from 0.
+    pub x: u32,
+    /// The line of the symbol, counted from 0.
+    pub y: u32,
+}
+
+impl fmt::Display for SymbolPosition {
+    /// Describe the position the way text editors do, with lines and columns counted from 1.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}, column {}", self.y + 1, self.x + 1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{self, Cursor};
+
+    // Test that errors of the [image] crate are kept as the source of a [ConvertError].
+    #[test]
+    fn test_error_source() {
+        let error = compare::decode(&mut Cursor::new(vec![0; 10])).unwrap_err();
+
+        assert!(matches!(error, ConvertError::DecodeError(_)));
+        assert!(error.source().is_some());
+        assert!(error
+            .to_string()
+            .starts_with("the image can't be decoded: "));
+        assert!(ConvertError::InvalidGamma.source().is_none());
+
+        // sources are compared by their descriptions
+        assert_eq!(
+            ErrorSource::new(io::Error::other("disk full")),
+            ErrorSource::message("disk full")
+        );
+        assert_ne!(
+            ErrorSource::message("disk full"),
+            ErrorSource::message("no such file")
+        );
+    }
+
+    // Test that the position of an unknown symbol is added to the error and described from 1.
+    #[test]
+    fn test_unknown_symbol_position() {
+        let error = ConvertError::UnknownASCIISymbol('P', None).at(2, 0);
+
+        assert_eq!(
+            error,
+            ConvertError::UnknownASCIISymbol('P', Some(SymbolPosition { x: 2, y: 0 }))
+        );
+        assert_eq!(
+            error.to_string(),
+            "the symbol 'P' at line 1, column 3 is not in the symbol map"
+        );
+        assert_eq!(
+            ConvertError::UnknownASCIISymbol('P', None).to_string(),
+            "the symbol 'P' is not in the symbol map"
+        );
+        assert_eq!(
+            ConvertError::InvalidWidth.at(2, 0),
+            ConvertError::InvalidWidth
+        );
+    }
+
+    // Test that errors are named after their variant, whatever values they hold.
+    #[test]
+    fn test_error_name() {
+        assert_eq!(
+            ConvertError::UnknownASCIISymbol('P', None).name(),
+            "UnknownASCIISymbol"
+        );
+        assert_eq!(
+            ConvertError::TooLarge {
+                width: 1,
+                height: 2
+            }
+            .name(),
+            "TooLarge"
+        );
+        assert_eq!(
+            ConvertError::DecodeError(ErrorSource::message("bad")).name(),
+            "DecodeError"
+        );
+    }
 }
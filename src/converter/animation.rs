@@ -0,0 +1,170 @@
+//! Animated GIF to ASCII converter.
+//!
+//! Each frame of an animated GIF is converted into ASCII just like a still image, using the same
+//! [ConvertOptions]. The frames are decoded and converted one at a time by [AsciiFrames], so a
+//! long animation can be played while it is still being converted. Each [AsciiFrame] keeps how
+//! long it should be shown for. GIFs often ask for delays that are too short to be shown, so
+//! the delays are limited the way browsers limit them, which keeps the frame rate the same as
+//! in a browser.
+//!
+//! Robert Peterson and Kelsey Werner 2023
+
+use crate::converter::{
+    dimension::Dimension,
+    image::{convert_decoded_image, validate_options},
+    options::ConvertOptions,
+    source::ImageSource,
+    ConvertError, ErrorSource,
+};
+use image::{codecs::gif::GifDecoder, AnimationDecoder, DynamicImage, Frames, ImageDecoder};
+use std::time::Duration;
+
+/// Frames that ask for a shorter delay than this are shown for [DEFAULT_FRAME_DELAY] instead.
+pub const MIN_FRAME_DELAY: Duration = Duration::from_millis(20);
+
+/// The delay of frames that ask for a delay shorter than [MIN_FRAME_DELAY], which is what
+/// browsers use: <https://bugzilla.mozilla.org/show_bug.cgi?id=232769>
+pub const DEFAULT_FRAME_DELAY: Duration = Duration::from_millis(100);
+
+/// [AsciiFrame] is a struct that holds a single converted frame of an animation.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AsciiFrame {
+    /// The ASCII of the frame.
+    pub ascii: String,
+    /// How long the frame is shown before the next frame.
+    pub delay: Duration,
+}
+
+/// [AsciiFrames] is a struct that converts the frames of an animated GIF into ASCII as they are
+/// read.
+pub struct AsciiFrames<'a> {
+    /// The frames of the GIF that have not been converted yet.
+    frames: Frames<'a>,
+    /// The options every frame is converted with.
+    options: ConvertOptions,
+}
+
+impl<'a> AsciiFrames<'a> {
+    /// Start reading the frames of an animated GIF from an image source.
+    ///
+    /// [ConvertError::DecodeError] is returned if the source is not a GIF,
+    /// [ConvertError::TooLarge] is returned if its frames have more pixels than the limit of the
+    /// options, and the options are checked like [crate::converter::image::Image::convert_to_ascii] checks them.
+    pub fn new<S>(source: S, options: ConvertOptions) -> Result<AsciiFrames<'a>, ConvertError>
+    where
+        S: ImageSource,
+        S::Buffer: 'a,
+    {
+        validate_options(&options)?;
+
+        let decoder = GifDecoder::new(source.open()?)
+            .map_err(|e| ConvertError::DecodeError(ErrorSource::new(e)))?;
+        options
+            .limits
+            .check_pixels(&Dimension::from(decoder.dimensions()))?;
+        Ok(AsciiFrames {
+            frames: decoder.into_frames(),
+            options,
+        })
+    }
+}
+
+impl<'a> Iterator for AsciiFrames<'a> {
+    type Item = Result<AsciiFrame, ConvertError>;
+
+    /// Decode and convert the next frame of the GIF.
+    ///
+    /// [ConvertError::DecodeError] is returned if the frame can't be decoded, and
+    /// [ConvertError::InvalidPipeline] is returned if a crop of the pipeline does not fit.
+    fn next(&mut self) -> Option<Self::Item> {
+        let frame = match self.frames.next()? {
+            Ok(frame) => frame,
+            Err(e) => return Some(Err(ConvertError::DecodeError(ErrorSource::new(e)))),
+        };
+
+        let (numer, denom) = frame.delay().numer_denom_ms();
+        let delay = Duration::from_millis(u64::from(numer / denom.max(1)));
+        let delay = if delay < MIN_FRAME_DELAY {
+            DEFAULT_FRAME_DELAY
+        } else {
+            delay
+        };
+
+        let img = DynamicImage::ImageRgba8(frame.into_buffer());
+        Some(self.options.apply_geometry(img).map(|img| AsciiFrame {
+            ascii: convert_decoded_image(img, &self.options, &self.options.symbol_map),
+            delay,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{codecs::gif::GifEncoder, Delay, Frame, Rgba, RgbaImage};
+
+    // Encodes a GIF that alternates between black and white frames with the given delays
+    fn blinking_gif(delays: &[u32]) -> Vec<u8> {
+        let mut gif = Vec::new();
+        {
+            let mut encoder = GifEncoder::new(&mut gif);
+            for (i, delay) in delays.iter().enumerate() {
+                let shade = if i % 2 == 0 { 0 } else { 255 };
+                let buffer = RgbaImage::from_pixel(8, 8, Rgba([shade, shade, shade, 255]));
+                let delay = Delay::from_numer_denom_ms(*delay, 1);
+                encoder
+                    .encode_frame(Frame::from_parts(buffer, 0, 0, delay))
+                    .unwrap();
+            }
+        }
+        gif
+    }
+
+    // Test that every frame of a GIF is converted in order with its delay.
+    #[test]
+    fn test_ascii_frames() {
+        let gif = blinking_gif(&[50, 200, 0]);
+        let options = ConvertOptions {
+            width: Some(4),
+            ..Default::default()
+        };
+
+        let frames: Vec<AsciiFrame> = AsciiFrames::new(gif, options)
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+
+        assert_eq!(frames.len(), 3);
+        assert_eq!(frames[0].ascii, "$$$$\n$$$$\n");
+        assert_eq!(frames[1].ascii, "    \n    \n");
+        assert_eq!(frames[0].delay, Duration::from_millis(50));
+        assert_eq!(frames[1].delay, Duration::from_millis(200));
+        // too short to be shown, so the delay browsers use is used instead
+        assert_eq!(frames[2].delay, DEFAULT_FRAME_DELAY);
+    }
+
+    // Test that images that are not GIFs and invalid options are rejected.
+    #[test]
+    fn test_ascii_frames_errors() {
+        // The idea to use "CARGO_MANIFEST_DIR" comes from StackOverflow:
+        // https://stackoverflow.com/questions/30003921/how-can-i-locate-resources-for-testing-with-cargo
+        let png_path = concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/test_assets/images/freakazoid-small.png"
+        );
+
+        assert!(matches!(
+            AsciiFrames::new(std::path::Path::new(png_path), ConvertOptions::default()),
+            Err(ConvertError::DecodeError(_))
+        ));
+
+        let options = ConvertOptions {
+            width: Some(0),
+            ..Default::default()
+        };
+        assert!(matches!(
+            AsciiFrames::new(blinking_gif(&[50]), options),
+            Err(ConvertError::InvalidWidth)
+        ));
+    }
+}
@@ -1,13 +1,47 @@
 //! ASCII to image converter.
 //!
 //! This module is responsible for converting ASCII to a PNG. It uses the [image] crate to
-//! create the PNG.
+//! draw the image, and writes the PNG with an [ImageCodec] that [Ascii::with_codec] can change.
+//! By default the characters are drawn with the DejaVu Sans Mono font using the [ab_glyph] crate, and the [ImageRenderMode] can be changed to map each character to a
+//! gray pixel instead. ASCII can also be converted to an SVG, which stays crisp at any zoom.
+//! Images can be drawn at a larger scale with [Ascii::with_scale], which gives sharper images on
+//! high-DPI screens than stretching the image would. The brightness of the PNG can be matched to
+//! a [BrightnessHistogram] with [Ascii::with_histogram_match], which makes the few brightness
+//! levels of the symbols look more like a photo. Characters that are not in the [SymbolMap] stop
+//! the conversion unless another [UnknownSymbolPolicy] is picked with
+//! [Ascii::with_unknown_symbol_policy]. The drawn PNG can be turned with [Ascii::with_rotation]
+//! and mirrored with [Ascii::with_flip]. Line endings and tabs are normalized before the ASCII is
+//! measured, and [Ascii::with_tab_width] and [Ascii::with_trailing_whitespace_trimmed] change how.
+//! ASCII that starts with a metadata header is drawn without it, and [Ascii::with_metadata]
+//! writes a title, an author and the hash of the ASCII into the text chunks of the PNG.
+//! [Ascii::with_canonical_output] writes the PNG with the fixed encoder settings of
+//! [encode_canonical_png], so the same ASCII always gives the same bytes.
 //!
 //! Robert Peterson and Kelsey Werner 2023
 
-use crate::converter::{dimension::Dimension, symbol_map::brightness_for_symbol, ConvertError};
-use image::{imageops, GrayImage, ImageOutputFormat, Luma};
-use std::io::Cursor;
+use crate::converter::{
+    codec::{default_codec, ImageCodec},
+    dimension::Dimension,
+    histogram::BrightnessHistogram,
+    metadata::{strip_metadata_header, PngMetadata},
+    normalize::{normalize_whitespace, WhitespaceOptions},
+    options::{ImageRenderMode, SizeLimits, UnknownSymbolPolicy},
+    pipeline::{Flip, Pipeline, Rotation},
+    symbol_map::SymbolMap,
+    testing::encode_canonical_png,
+    ConvertError,
+};
+use ab_glyph::{point, Font, FontRef, PxScale, ScaleFont};
+use image::{imageops, DynamicImage, GrayImage, Luma};
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+use std::{
+    borrow::Cow,
+    collections::{BTreeSet, HashMap},
+    fmt::Write,
+    io::Cursor,
+    ops::Range,
+};
 
 /// The min image size in pixels.
 ///
@@ -15,29 +49,283 @@ use std::io::Cursor;
 /// than their default 1-char-to-1-pixel ratio.
 const MIN_IMAGE_DIMENSION: u32 = 500;
 
+/// The monospace font used to draw the characters of the ASCII.
+///
+/// DejaVu Sans Mono is from <https://dejavu-fonts.github.io/> and its license is next to
+/// the font file.
+const FONT_DATA: &[u8] = include_bytes!("../../assets/fonts/DejaVuSansMono.ttf");
+
+/// Parse the monospace font used to draw the characters of the ASCII.
+///
+/// [ConvertError::FontError] is returned if the font can't be read.
+pub(crate) fn load_font() -> Result<FontRef<'static>, ConvertError> {
+    FontRef::try_from_slice(FONT_DATA).map_err(|_| ConvertError::FontError)
+}
+
+/// The height in pixels that characters are drawn at.
+const GLYPH_SCALE: f32 = 16.0;
+
 /// [Ascii] is a struct that contains the ASCII data that will be converted to an image.
 pub struct Ascii<'a> {
     /// A reference to the ASCII string that will be converted to an image.
     data: &'a str,
+    /// The map used to turn each symbol of the ASCII into a brightness.
+    symbol_map: SymbolMap,
+    /// How the ASCII is drawn when it is converted into an image.
+    render_mode: ImageRenderMode,
+    /// The font the characters are drawn with, or [None] to load it when it is needed.
+    font: Option<FontRef<'static>>,
+    /// How many times larger than normal the image is drawn.
+    scale: u32,
+    /// [Option] stores the histogram the brightness of the image is matched to, or [None] to
+    /// keep the brightness of the symbols.
+    histogram_target: Option<BrightnessHistogram>,
+    /// What happens to the characters that are not in the [SymbolMap].
+    on_unknown_symbol: UnknownSymbolPolicy,
+    /// The rotate and flip stages applied to the PNG once it is drawn.
+    orientation: Pipeline,
+    /// How the line endings, tabs and trailing spaces of the ASCII are normalized.
+    whitespace: WhitespaceOptions,
+    /// The most lines and columns the ASCII may have, and the most pixels it may be drawn with.
+    limits: SizeLimits,
+    /// [Option] stores the text written into the PNG, or [None] to write a PNG without text chunks.
+    metadata: Option<PngMetadata>,
+    /// Whether the PNG is written with the fixed settings of [encode_canonical_png].
+    canonical: bool,
+    /// The [ImageCodec] the PNG is written with.
+    codec: &'a dyn ImageCodec,
 }
 
 impl<'a> Ascii<'a> {
+    /// Create a new [Ascii] that uses the default [SymbolMap].
     pub fn new(data: &'a str) -> Ascii<'a> {
-        Ascii { data }
+        Self::with_symbol_map(data, SymbolMap::default())
+    }
+
+    /// Create a new [Ascii] that uses the given [SymbolMap].
+    pub fn with_symbol_map(data: &'a str, symbol_map: SymbolMap) -> Ascii<'a> {
+        Ascii {
+            data,
+            symbol_map,
+            render_mode: ImageRenderMode::default(),
+            font: None,
+            scale: 1,
+            histogram_target: None,
+            on_unknown_symbol: UnknownSymbolPolicy::default(),
+            orientation: Pipeline::new(),
+            whitespace: WhitespaceOptions::default(),
+            limits: SizeLimits::default(),
+            metadata: None,
+            canonical: false,
+            codec: default_codec(),
+        }
+    }
+
+    /// Draw the characters with a font that has already been loaded by [load_font].
+    pub(crate) fn with_font(mut self, font: FontRef<'static>) -> Ascii<'a> {
+        self.font = Some(font);
+        self
+    }
+
+    /// Change the [ImageRenderMode] used when the [Ascii] is converted into an image.
+    pub fn with_render_mode(mut self, render_mode: ImageRenderMode) -> Ascii<'a> {
+        self.render_mode = render_mode;
+        self
+    }
+
+    /// Change how many times larger than normal the image is drawn, such as 2 for high-DPI screens.
+    ///
+    /// The image is exactly `scale` times as wide and as tall as the normal image, and the
+    /// characters are drawn at that size rather than stretched, so the image stays sharp. A
+    /// scale of 0 is treated as 1.
+    pub fn with_scale(mut self, scale: u32) -> Ascii<'a> {
+        self.scale = scale.max(1);
+        self
+    }
+
+    /// Match the brightness of the PNG to a [BrightnessHistogram], such as the histogram of a
+    /// reference photo or [BrightnessHistogram::photographic].
+    ///
+    /// With [ImageRenderMode::Pixels], the pixels of the symbols are matched before the image is
+    /// scaled up, so the brightness levels of the symbols are spread out to follow the target.
+    /// With [ImageRenderMode::Glyphs], the drawn characters are matched. The SVG from
+    /// [Ascii::convert_to_svg] is not changed.
+    pub fn with_histogram_match(mut self, target: BrightnessHistogram) -> Ascii<'a> {
+        self.histogram_target = Some(target);
+        self
+    }
+
+    /// Change what happens to the characters of the ASCII that are not in the [SymbolMap].
+    ///
+    /// With [UnknownSymbolPolicy::Error], converting the [Ascii] fails on the first of them. The
+    /// other policies draw each of them as a symbol of the map instead, in the PNG and the SVG.
+    pub fn with_unknown_symbol_policy(mut self, policy: UnknownSymbolPolicy) -> Ascii<'a> {
+        self.on_unknown_symbol = policy;
+        self
+    }
+
+    /// Turn the PNG by a [Rotation] once it is drawn.
+    ///
+    /// Rotations and flips are applied in the order they were added. The SVG from
+    /// [Ascii::convert_to_svg] is not changed.
+    pub fn with_rotation(mut self, rotation: Rotation) -> Ascii<'a> {
+        self.orientation = self.orientation.rotate(rotation);
+        self
+    }
+
+    /// Mirror the PNG with a [Flip] once it is drawn.
+    ///
+    /// Rotations and flips are applied in the order they were added. The SVG from
+    /// [Ascii::convert_to_svg] is not changed.
+    pub fn with_flip(mut self, flip: Flip) -> Ascii<'a> {
+        self.orientation = self.orientation.flip(flip);
+        self
+    }
+
+    /// Change the number of columns between the tab stops that tabs in the ASCII are expanded to.
+    ///
+    /// Tabs are expanded to every [DEFAULT_TAB_WIDTH] columns unless another width is picked, and
+    /// are removed with a width of 0.
+    ///
+    /// [DEFAULT_TAB_WIDTH]: crate::converter::normalize::DEFAULT_TAB_WIDTH
+    pub fn with_tab_width(mut self, tab_width: u32) -> Ascii<'a> {
+        self.whitespace.tab_width = tab_width;
+        self
+    }
+
+    /// Remove the spaces at the end of each line of the ASCII before it is converted.
+    ///
+    /// The image is then only as wide as the longest line of symbols, instead of the longest line
+    /// with its trailing spaces.
+    pub fn with_trailing_whitespace_trimmed(mut self, trim: bool) -> Ascii<'a> {
+        self.whitespace.trim_trailing = trim;
+        self
+    }
+
+    /// Change the [SizeLimits] the ASCII is checked against before it is drawn.
+    ///
+    /// The number of columns and lines of the ASCII are checked against the limits, and so is
+    /// the number of pixels of an image drawn with [ImageRenderMode::Glyphs].
+    pub fn with_size_limits(mut self, limits: SizeLimits) -> Ascii<'a> {
+        self.limits = limits;
+        self
+    }
+
+    /// Write the title and author of the [PngMetadata], and the SHA-256 hash of the ASCII, into
+    /// text chunks of the PNG.
+    ///
+    /// The hash is of the ASCII exactly as it was given, so the PNG can be matched with the file
+    /// it was drawn from. The SVG has no text chunks, so it is drawn without them.
+    pub fn with_metadata(mut self, metadata: PngMetadata) -> Ascii<'a> {
+        self.metadata = Some(metadata);
+        self
+    }
+
+    /// Write the PNG with the fixed encoder settings of [encode_canonical_png] instead of the
+    /// defaults of the [image] crate, which can change between its versions.
+    ///
+    /// The pixels are the same either way, only the bytes of the PNG can differ.
+    pub fn with_canonical_output(mut self, canonical: bool) -> Ascii<'a> {
+        self.canonical = canonical;
+        self
+    }
+
+    /// Write the PNG with the given [ImageCodec] instead of the [default_codec].
+    ///
+    /// Canonical output is always written with [encode_canonical_png], whatever the codec.
+    pub fn with_codec(mut self, codec: &'a dyn ImageCodec) -> Ascii<'a> {
+        self.codec = codec;
+        self
+    }
+
+    /// The ASCII with its metadata header removed and its line endings, tabs and trailing spaces
+    /// normalized, which is what is drawn, and its [Dimension] in characters.
+    ///
+    /// [ConvertError::EmptyInput] is returned if the ASCII only has line breaks, since it would be
+    /// drawn as an image without pixels. Lines of spaces are drawn as a blank image.
+    /// [ConvertError::TooLarge] is returned if the ASCII has more columns or lines than the
+    /// [SizeLimits] allow.
+    fn measure(&self) -> Result<(Cow<'a, str>, Dimension), ConvertError> {
+        let art = strip_metadata_header(self.data).unwrap_or(self.data);
+        let text = normalize_whitespace(art, self.whitespace);
+        if text.lines().all(str::is_empty) {
+            return Err(ConvertError::EmptyInput);
+        }
+
+        let dimension = Self::get_dimensions(&text);
+        self.limits.check_text(dimension.width, dimension.height)?;
+        Ok((text, dimension))
+    }
+
+    /// Turn and mirror a drawn image with the rotations and flips of the [Ascii].
+    fn orient(&self, img: DynamicImage) -> Result<DynamicImage, ConvertError> {
+        // rotations and flips fit any image, so only a crop could fail
+        self.orientation.apply_geometry(img)
+    }
+
+    /// Pick the symbol each character of the ASCII that is not in the [SymbolMap] is drawn as,
+    /// following the [UnknownSymbolPolicy].
+    ///
+    /// The map is empty with [UnknownSymbolPolicy::Error], so those characters are still
+    /// reported. [ConvertError::FontError] is returned if the font is needed but can't be read.
+    fn substitutes(&self, text: &str) -> Result<HashMap<char, char>, ConvertError> {
+        let unknown: BTreeSet<char> = text
+            .lines()
+            .flat_map(str::chars)
+            .filter(|c| self.symbol_map.brightness_for_symbol(*c).is_err())
+            .collect();
+        if unknown.is_empty() || self.on_unknown_symbol == UnknownSymbolPolicy::Error {
+            return Ok(HashMap::new());
+        }
+
+        let blank = match self.symbol_map.brightness_for_symbol(' ') {
+            Ok(_) => ' ',
+            Err(_) => self.symbol_map.symbol_for_brightness(u8::MAX),
+        };
+        if self.on_unknown_symbol == UnknownSymbolPolicy::SkipAsSpace {
+            return Ok(unknown.into_iter().map(|c| (c, blank)).collect());
+        }
+
+        let font = match &self.font {
+            Some(font) => font.clone(),
+            None => load_font()?,
+        };
+        let symbols: Vec<(char, Vec<f32>)> = self
+            .symbol_map
+            .ramp()
+            .chars()
+            .filter_map(|s| glyph_cell(&font, s).map(|cell| (s, cell)))
+            .collect();
+        // the sum of the squared differences of the ink of each pixel of two cells
+        let distance =
+            |a: &[f32], b: &[f32]| -> f32 { a.iter().zip(b).map(|(a, b)| (a - b) * (a - b)).sum() };
+
+        Ok(unknown
+            .into_iter()
+            .map(|c| {
+                let nearest = glyph_cell(&font, c).and_then(|cell| {
+                    symbols
+                        .iter()
+                        .min_by(|a, b| distance(&a.1, &cell).total_cmp(&distance(&b.1, &cell)))
+                        .map(|(s, _)| *s)
+                });
+                (c, nearest.unwrap_or(blank))
+            })
+            .collect())
     }
 
     /// Determine the square dimensions of an ASCII string
     ///
     /// The square dimensions of the ASCII input are used to construct the image
     /// size of the generated image. The width of the image is the width of the
-    /// largest ASCII line. The height of the image is the line height of the ASCII
-    /// input.
-    fn get_dimensions(&self) -> Dimension {
+    /// largest ASCII line, counted in characters. The height of the image is the line
+    /// height of the ASCII input.
+    fn get_dimensions(text: &str) -> Dimension {
         let mut dimension = Dimension::new();
 
-        for l in self.data.lines() {
+        for l in text.lines() {
             dimension.height += 1;
-            let w = l.len() as u32;
+            let w = l.chars().count() as u32;
             // the longest line in the string is the width of the square
             if w > dimension.width {
                 dimension.width = w;
@@ -49,47 +337,309 @@ impl<'a> Ascii<'a> {
 
     /// Convert [Ascii] to a PNG image.
     ///
-    /// The PNG binary data is returned as a [Cursor]. If there is any problem
-    /// reading the ASCII or generating the [Cursor], a [ConvertError] is returned.
+    /// The [ImageRenderMode] decides whether the characters are drawn with a font or as gray
+    /// pixels. Either way, every character must be in the [SymbolMap].
+    /// The PNG binary data is returned as a [Cursor], with the text chunks of the [PngMetadata] if
+    /// there is any. If there is any problem reading the ASCII or generating the [Cursor], a
+    /// [ConvertError] is returned.
     pub fn convert_to_image(&self) -> Result<Cursor<Vec<u8>>, ConvertError> {
+        let img = self.draw()?;
+        let png = if self.canonical {
+            encode_canonical_png(&img)?
+        } else {
+            self.codec.encode_png(&img)?
+        };
+        match &self.metadata {
+            Some(metadata) => Ok(Cursor::new(metadata.add_to_png(png.get_ref(), self.data)?)),
+            None => Ok(png),
+        }
+    }
+
+    /// Draw [Ascii] into an image the way [Ascii::convert_to_image] does, without writing it as
+    /// a PNG.
+    pub(crate) fn draw(&self) -> Result<DynamicImage, ConvertError> {
+        match self.render_mode {
+            ImageRenderMode::Glyphs => self.draw_glyph_image(),
+            ImageRenderMode::Pixels => self.draw_pixel_image(),
+        }
+    }
+
+    /// Draw [Ascii] into an image with one gray pixel per character.
+    fn draw_pixel_image(&self) -> Result<DynamicImage, ConvertError> {
         // find dimensions of ASCII string
-        let mut dimension = self.get_dimensions();
+        let (text, dimension) = self.measure()?;
 
         // create empty [ImageBuffer] of recently determined dimensions
         let mut img = GrayImage::new(dimension.width, dimension.height);
 
-        // traverse ascii to fill out [ImageBuffer]
-        for (h, line) in (0_u32..).zip(self.data.lines()) {
+        // traverse ascii to fill out [ImageBuffer], one row of pixels per line
+        let substitutes = self.substitutes(&text)?;
+        let lines: Vec<&str> = text.lines().collect();
+        rows_mut(&mut img).try_for_each(|(h, row)| {
+            for ((w, pixel), c) in (0_u32..).zip(row.iter_mut()).zip(lines[h].chars()) {
+                *pixel = self
+                    .symbol_map
+                    .brightness_for_symbol(substitutes.get(&c).copied().unwrap_or(c))
+                    .map_err(|e| e.at(w, h as u32))?;
+            }
+            Ok(())
+        })?;
+
+        if let Some(target) = &self.histogram_target {
+            BrightnessHistogram::match_image(&mut img, target);
+        }
+
+        self.orient(scale_pixels(DynamicImage::ImageLuma8(img), self.scale))
+    }
+
+    /// Draw [Ascii] into an image by drawing each character with a monospace font.
+    ///
+    /// The characters are drawn in black on a white background, so dense characters such as
+    /// `$` are dark and the space is light, just like in the [SymbolMap].
+    fn draw_glyph_image(&self) -> Result<DynamicImage, ConvertError> {
+        let font = match &self.font {
+            Some(font) => font.clone(),
+            None => load_font()?,
+        };
+        let normal_font = font.as_scaled(PxScale::from(GLYPH_SCALE));
+        let scaled_font = font.as_scaled(PxScale::from(GLYPH_SCALE * self.scale as f32));
+
+        // every glyph of a monospace font has the same advance, and the cells are measured at
+        // the normal size so a scaled image is exactly `scale` times as large
+        let cell_width = normal_font.h_advance(font.glyph_id('M')).ceil() as u32 * self.scale;
+        let cell_height =
+            (normal_font.height() + normal_font.line_gap()).ceil() as u32 * self.scale;
+
+        let (text, dimension) = self.measure()?;
+        let drawn = Dimension::from((
+            dimension.width.saturating_mul(cell_width),
+            dimension.height.saturating_mul(cell_height),
+        ));
+        self.limits.check_pixels(&drawn)?;
+        let mut img = GrayImage::from_pixel(drawn.width, drawn.height, Luma([u8::MAX]));
+
+        // make sure the ASCII is valid even though the brightness is not needed
+        let substitutes = self.substitutes(&text)?;
+        for (h, line) in (0_u32..).zip(text.lines()) {
             for (w, c) in (0_u32..).zip(line.chars()) {
-                let brightness = brightness_for_symbol(c)?;
-                img.put_pixel(w, h, Luma([brightness]));
+                self.symbol_map
+                    .brightness_for_symbol(substitutes.get(&c).copied().unwrap_or(c))
+                    .map_err(|e| e.at(w, h))?;
             }
         }
 
-        dimension.scale_up(MIN_IMAGE_DIMENSION);
+        let width = img.width() as i64;
+        let lines: Vec<&str> = text.lines().collect();
+
+        // draw a range of lines into a strip of rows of the image that starts at `top`
+        let draw_lines = |strip: &mut [u8], top: i64, drawn: Range<usize>| {
+            let bottom = top + strip.len() as i64 / width;
+
+            for (h, line) in drawn.clone().zip(&lines[drawn]) {
+                for (w, c) in (0_u32..).zip(line.chars()) {
+                    let c = substitutes.get(&c).copied().unwrap_or(c);
+                    let position = point(
+                        (w * cell_width) as f32,
+                        (h as u32 * cell_height) as f32 + scaled_font.ascent(),
+                    );
+                    let mut glyph = scaled_font.scaled_glyph(c);
+                    glyph.position = position;
 
-        let newimg = imageops::resize(
-            &img,
-            // account for fonts displaying ASCII art with more height than width
-            dimension.width / 2,
-            dimension.height,
-            imageops::FilterType::Triangle,
+                    if let Some(outline) = scaled_font.outline_glyph(glyph) {
+                        let bounds = outline.px_bounds();
+                        if bounds.max.y as i64 <= top || bounds.min.y as i64 >= bottom {
+                            continue;
+                        }
+
+                        outline.draw(|x, y, coverage| {
+                            let px = bounds.min.x as i64 + x as i64;
+                            let py = bounds.min.y as i64 + y as i64;
+                            if px < 0 || py < top || px >= width || py >= bottom {
+                                return;
+                            }
+
+                            // darken the pixel by how much of it the glyph covers
+                            let pixel = &mut strip[((py - top) * width + px) as usize];
+                            let ink = (u8::MAX as f32 * (1.0 - coverage.min(1.0))) as u8;
+                            *pixel = (*pixel).min(ink);
+                        });
+                    }
+                }
+            }
+        };
+
+        // each line gets its own strip of the image, and since some glyphs reach a few pixels
+        // past the top or bottom of their line, the lines next to it are drawn into it too
+        #[cfg(feature = "parallel")]
+        img.par_chunks_mut((width as usize * cell_height as usize).max(1))
+            .enumerate()
+            .for_each(|(h, strip)| {
+                let top = (h as u32 * cell_height) as i64;
+                draw_lines(strip, top, h.saturating_sub(1)..(h + 2).min(lines.len()));
+            });
+        #[cfg(not(feature = "parallel"))]
+        draw_lines(&mut img, 0, 0..lines.len());
+
+        if let Some(target) = &self.histogram_target {
+            BrightnessHistogram::match_image(&mut img, target);
+        }
+
+        self.orient(DynamicImage::ImageLuma8(img))
+    }
+
+    /// Convert [Ascii] to an SVG image.
+    ///
+    /// Each symbol is drawn as a gray rectangle with the brightness of the symbol, so the SVG
+    /// looks like the PNG from [Ascii::convert_to_image] without the blur of scaling. Runs
+    /// of symbols with the same brightness are merged into a single rectangle to keep the SVG
+    /// small. If there is any problem reading the ASCII, a [ConvertError] is returned.
+    pub fn convert_to_svg(&self) -> Result<String, ConvertError> {
+        let (text, dimension) = self.measure()?;
+        let mut size = Dimension::from((dimension.width, dimension.height));
+        size.scale_up(MIN_IMAGE_DIMENSION);
+
+        // Each symbol is twice as tall as it is wide to account for fonts displaying ASCII
+        // art with more height than width. Writing to a [String] can not fail.
+        let mut svg = String::new();
+        let _ = writeln!(
+            svg,
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" viewBox=\"0 0 {} {}\" shape-rendering=\"crispEdges\">",
+            size.width / 2,
+            size.height,
+            dimension.width,
+            dimension.height * 2,
         );
+        // missing symbols at the end of short lines are black, just like in the PNG
+        svg.push_str("<rect width=\"100%\" height=\"100%\" fill=\"rgb(0,0,0)\"/>\n");
+
+        let substitutes = self.substitutes(&text)?;
+        for (h, line) in (0_u32..).zip(text.lines()) {
+            let mut runs: Vec<(u32, u32, u8)> = Vec::new();
 
-        // write image to a [Cursor]
-        let mut buffer: Cursor<Vec<u8>> = Cursor::new(Vec::new());
-        let write = newimg.write_to(&mut buffer, ImageOutputFormat::Png);
+            for (w, c) in (0_u32..).zip(line.chars()) {
+                let brightness = self
+                    .symbol_map
+                    .brightness_for_symbol(substitutes.get(&c).copied().unwrap_or(c))
+                    .map_err(|e| e.at(w, h))?;
+                match runs.last_mut() {
+                    Some((_, length, b)) if *b == brightness => *length += 1,
+                    _ => runs.push((w, 1, brightness)),
+                }
+            }
 
-        match write {
-            Ok(_) => Ok(buffer),
-            Err(_) => Err(ConvertError::WriteError),
+            for (x, length, b) in runs.into_iter().filter(|(_, _, b)| *b > 0) {
+                let _ = writeln!(
+                    svg,
+                    "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"2\" fill=\"rgb({b},{b},{b})\"/>",
+                    x,
+                    h * 2,
+                    length,
+                );
+            }
         }
+
+        svg.push_str("</svg>\n");
+        Ok(svg)
     }
 }
 
+/// Draw the glyph of a character into its cell at the normal size, giving how much ink covers
+/// each pixel of the cell from 0.0 to 1.0, row by row.
+///
+/// Characters without a glyph in the font return [None], and characters with an empty glyph,
+/// like the space, return a cell without ink.
+fn glyph_cell(font: &FontRef, c: char) -> Option<Vec<f32>> {
+    // glyph 0 is the box that fonts draw for characters they don't have
+    if font.glyph_id(c).0 == 0 {
+        return None;
+    }
+
+    let scaled_font = font.as_scaled(PxScale::from(GLYPH_SCALE));
+    let cell_width = scaled_font.h_advance(font.glyph_id('M')).ceil() as i64;
+    let cell_height = (scaled_font.height() + scaled_font.line_gap()).ceil() as i64;
+
+    let mut cell = vec![0.0; (cell_width * cell_height) as usize];
+    let mut glyph = scaled_font.scaled_glyph(c);
+    glyph.position = point(0.0, scaled_font.ascent());
+    if let Some(outline) = scaled_font.outline_glyph(glyph) {
+        let bounds = outline.px_bounds();
+        outline.draw(|x, y, coverage| {
+            let px = bounds.min.x as i64 + x as i64;
+            let py = bounds.min.y as i64 + y as i64;
+            if (0..cell_width).contains(&px) && (0..cell_height).contains(&py) {
+                cell[(py * cell_width + px) as usize] += coverage.min(1.0);
+            }
+        });
+    }
+
+    Some(cell)
+}
+
+/// Measure how much of its cell the glyph of a character covers with ink, from 0.0 for a blank
+/// cell to 1.0 for a cell that is filled in.
+///
+/// Characters without a glyph in the font return [None].
+pub(crate) fn ink_coverage(font: &FontRef, c: char) -> Option<f32> {
+    glyph_cell(font, c).map(|cell| cell.iter().sum::<f32>() / cell.len() as f32)
+}
+
+/// Split a [GrayImage] into its rows of pixels, numbered from the top.
+///
+/// With the `parallel` feature, the rows are filled in on the rayon thread pool, so when
+/// several lines of ASCII have unknown symbols, the error may come from any of them.
+#[cfg(feature = "parallel")]
+fn rows_mut(img: &mut GrayImage) -> impl ParallelIterator<Item = (usize, &mut [u8])> {
+    let width = (img.width() as usize).max(1);
+    img.par_chunks_mut(width).enumerate()
+}
+
+/// Split a [GrayImage] into its rows of pixels, numbered from the top.
+///
+/// With the `parallel` feature, the rows are filled in on the rayon thread pool, so when
+/// several lines of ASCII have unknown symbols, the error may come from any of them.
+#[cfg(not(feature = "parallel"))]
+fn rows_mut(img: &mut GrayImage) -> impl Iterator<Item = (usize, &mut [u8])> {
+    let width = (img.width() as usize).max(1);
+    img.chunks_mut(width).enumerate()
+}
+
+/// Scale up an image with one pixel per ASCII symbol and write it as a PNG.
+///
+/// The image is scaled so that it is at least [MIN_IMAGE_DIMENSION] pixels in size, and it
+/// is squeezed horizontally to account for fonts displaying ASCII art with more height than
+/// width. The image is then made `scale` times as large and written with the [ImageCodec]. The
+/// PNG binary data is returned as a [Cursor], or [ConvertError::WriteError] if the PNG could not
+/// be written.
+pub(crate) fn render_png(
+    img: DynamicImage,
+    scale: u32,
+    codec: &dyn ImageCodec,
+) -> Result<Cursor<Vec<u8>>, ConvertError> {
+    codec.encode_png(&scale_pixels(img, scale))
+}
+
+/// Scale an image with one pixel per character up to a size that can be looked at, `scale`
+/// times larger than normal.
+fn scale_pixels(img: DynamicImage, scale: u32) -> DynamicImage {
+    let mut dimension = Dimension::from((img.width(), img.height()));
+    dimension.scale_up(MIN_IMAGE_DIMENSION);
+
+    img.resize_exact(
+        // account for fonts displaying ASCII art with more height than width
+        dimension.width / 2 * scale,
+        dimension.height * scale,
+        imageops::FilterType::Triangle,
+    )
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::converter::{
+        compare::Tolerance,
+        testing::{assert_golden, canonicalize_png},
+        SymbolPosition,
+    };
     use std::fs;
 
     // Tests to check that dimensions are properly created from various ASCII input.
@@ -97,10 +647,71 @@ mod tests {
     fn test_ascii_dimensions() {
         assert_eq!(
             Dimension::from((3, 4)),
-            Ascii::new("1\n1\n123\n1").get_dimensions()
+            Ascii::get_dimensions("1\n1\n123\n1")
+        );
+        assert_eq!(Dimension::from((1, 1)), Ascii::get_dimensions("a"));
+        assert_eq!(Dimension::from((3, 1)), Ascii::get_dimensions("bbb"));
+        // characters that take up more than one byte are a single column
+        assert_eq!(
+            Dimension::from((2, 1)),
+            Ascii::get_dimensions("\u{2588}\u{2591}")
+        );
+    }
+
+    // Test that Windows line endings, tabs and trailing spaces are normalized before the ASCII is
+    // measured and drawn.
+    #[test]
+    fn test_convert_to_image_whitespace() {
+        let pixels = |ascii: Ascii| {
+            let png = ascii
+                .with_render_mode(ImageRenderMode::Pixels)
+                .convert_to_image()
+                .unwrap();
+            image::load_from_memory(png.get_ref()).unwrap().into_luma8()
+        };
+
+        let unix = pixels(Ascii::new("@   .\n@@  \n"));
+        assert_eq!(
+            pixels(Ascii::new("@\t.\r\n@@  \r\n").with_tab_width(4)),
+            unix
+        );
+        assert_eq!(pixels(Ascii::new("@\t.\r@@\t").with_tab_width(4)), unix);
+        assert_eq!(
+            pixels(Ascii::new("@\t.").with_tab_width(2)),
+            pixels(Ascii::new("@ ."))
+        );
+        assert_eq!(pixels(Ascii::new("@\t.")), pixels(Ascii::new("@       .")));
+
+        let trimmed = pixels(Ascii::new("@@  \r\n.   \n").with_trailing_whitespace_trimmed(true));
+        assert_eq!(trimmed, pixels(Ascii::new("@@\n.\n")));
+        assert_ne!(trimmed, pixels(Ascii::new("@@  \n.   \n")));
+    }
+
+    // Test that a metadata header is not drawn, and that the text chunks of the metadata are
+    // written into the PNG without changing its pixels.
+    #[test]
+    fn test_convert_to_image_with_metadata() {
+        let art = "@@..\n.@@.\n";
+        let with_header = format!(
+            "{}\n# source: 4x2 pixels\n\n{}",
+            crate::converter::metadata::HEADER_START,
+            art
+        );
+        let png = |ascii: Ascii| ascii.convert_to_image().unwrap().into_inner();
+
+        let plain = png(Ascii::new(art));
+        assert_eq!(png(Ascii::new(&with_header)), plain);
+
+        let metadata = PngMetadata {
+            title: Some("Wave".to_string()),
+            author: Some("Kelsey".to_string()),
+        };
+        let tagged = png(Ascii::new(art).with_metadata(metadata.clone()));
+        assert_eq!(tagged, metadata.add_to_png(&plain, art).unwrap());
+        assert_eq!(
+            image::load_from_memory(&tagged).unwrap(),
+            image::load_from_memory(&plain).unwrap()
         );
-        assert_eq!(Dimension::from((1, 1)), Ascii::new("a").get_dimensions());
-        assert_eq!(Dimension::from((3, 1)), Ascii::new("bbb").get_dimensions());
     }
 
     // Test to check for a bug that was uncovered during manual testing.
@@ -110,12 +721,335 @@ mod tests {
         assert!(img.is_ok());
     }
 
+    // Test that ASCII with nothing but line breaks is rejected instead of drawn as an image
+    // without pixels.
+    #[test]
+    fn test_convert_empty_input() {
+        for ascii in ["", "\n\n\n", "\r\n\r"] {
+            assert_eq!(
+                Ascii::new(ascii).convert_to_image(),
+                Err(ConvertError::EmptyInput)
+            );
+            assert_eq!(
+                Ascii::new(ascii)
+                    .with_render_mode(ImageRenderMode::Pixels)
+                    .convert_to_image(),
+                Err(ConvertError::EmptyInput)
+            );
+            assert_eq!(
+                Ascii::new(ascii).convert_to_svg(),
+                Err(ConvertError::EmptyInput)
+            );
+        }
+        assert!(Ascii::new("\n.\n").convert_to_image().is_ok());
+        assert!(Ascii::new(" \t\n").convert_to_image().is_ok());
+        assert_eq!(
+            Ascii::new("  \t\n ")
+                .with_trailing_whitespace_trimmed(true)
+                .convert_to_image(),
+            Err(ConvertError::EmptyInput)
+        );
+    }
+
+    // Test that ASCII with more lines or columns than the size limits, or that would be drawn
+    // with more pixels than the limit, is rejected.
+    #[test]
+    fn test_convert_size_limits() {
+        let limits = SizeLimits {
+            max_columns: 3,
+            max_rows: 2,
+            ..Default::default()
+        };
+        assert!(Ascii::new("...\n...")
+            .with_size_limits(limits)
+            .convert_to_image()
+            .is_ok());
+        assert_eq!(
+            Ascii::new("....\n...")
+                .with_size_limits(limits)
+                .convert_to_image(),
+            Err(ConvertError::TooLarge {
+                width: 4,
+                height: 2
+            })
+        );
+        assert_eq!(
+            Ascii::new(".\n.\n.")
+                .with_size_limits(limits)
+                .with_render_mode(ImageRenderMode::Pixels)
+                .convert_to_image(),
+            Err(ConvertError::TooLarge {
+                width: 1,
+                height: 3
+            })
+        );
+        assert_eq!(
+            Ascii::new(".\n.\n.")
+                .with_size_limits(limits)
+                .convert_to_svg(),
+            Err(ConvertError::TooLarge {
+                width: 1,
+                height: 3
+            })
+        );
+
+        let limits = SizeLimits {
+            max_pixels: 100,
+            ..Default::default()
+        };
+        assert!(matches!(
+            Ascii::new("..").with_size_limits(limits).convert_to_image(),
+            Err(ConvertError::TooLarge { .. })
+        ));
+        assert!(Ascii::new("..")
+            .with_size_limits(limits)
+            .with_render_mode(ImageRenderMode::Pixels)
+            .convert_to_image()
+            .is_ok());
+    }
+
     // Test to check that unsupported ASCII fails in an expected way.
     #[test]
     fn test_convert_to_image_unknown_ascii() {
         let image = Ascii::new("P").convert_to_image();
         assert!(image.is_err());
-        assert_eq!(image, Err(ConvertError::UnknownASCIISymbol('P')));
+        assert_eq!(
+            image,
+            Err(ConvertError::UnknownASCIISymbol(
+                'P',
+                Some(SymbolPosition { x: 0, y: 0 })
+            ))
+        );
+    }
+
+    // Test to check that a custom [SymbolMap] is used to read the ASCII.
+    #[test]
+    fn test_convert_to_image_with_symbol_map() {
+        let symbol_map = SymbolMap::from_ramp("XO").unwrap();
+
+        assert!(Ascii::with_symbol_map("XO\nOX", symbol_map.clone())
+            .convert_to_image()
+            .is_ok());
+        assert_eq!(
+            Ascii::with_symbol_map("@", symbol_map).convert_to_image(),
+            Err(ConvertError::UnknownASCIISymbol(
+                '@',
+                Some(SymbolPosition { x: 0, y: 0 })
+            ))
+        );
+    }
+
+    // Test to check that characters that are not in the symbol map follow the policy.
+    #[test]
+    fn test_convert_to_image_with_unknown_symbol_policy() {
+        let error = Ascii::new("=V")
+            .with_unknown_symbol_policy(UnknownSymbolPolicy::Error)
+            .convert_to_image();
+        assert_eq!(
+            error,
+            Err(ConvertError::UnknownASCIISymbol(
+                '=',
+                Some(SymbolPosition { x: 0, y: 0 })
+            ))
+        );
+
+        let skipped = Ascii::new("=V@")
+            .with_unknown_symbol_policy(UnknownSymbolPolicy::SkipAsSpace)
+            .convert_to_image()
+            .unwrap();
+        assert_eq!(skipped, Ascii::new("  @").convert_to_image().unwrap());
+
+        let nearest = Ascii::new("=V")
+            .with_unknown_symbol_policy(UnknownSymbolPolicy::NearestMatch)
+            .substitutes("=V")
+            .unwrap();
+        assert_eq!(nearest.get(&'V'), Some(&'v'));
+        assert!(nearest
+            .values()
+            .all(|s| SymbolMap::default().brightness_for_symbol(*s).is_ok()));
+        assert!(Ascii::new("=V")
+            .with_unknown_symbol_policy(UnknownSymbolPolicy::NearestMatch)
+            .convert_to_image()
+            .is_ok());
+
+        // a map without a space skips characters as its lightest symbol
+        let symbol_map = SymbolMap::from_ramp("@.").unwrap();
+        let svg = Ascii::with_symbol_map("P.", symbol_map.clone())
+            .with_unknown_symbol_policy(UnknownSymbolPolicy::SkipAsSpace)
+            .convert_to_svg()
+            .unwrap();
+        assert_eq!(
+            svg,
+            Ascii::with_symbol_map("..", symbol_map)
+                .convert_to_svg()
+                .unwrap()
+        );
+    }
+
+    // Test to check that glyphs are drawn in black on a white background in a grid of cells.
+    #[test]
+    fn test_convert_to_glyph_image() {
+        let blank = Ascii::new("  \n  ").convert_to_image().unwrap();
+        let blank = image::load_from_memory(blank.get_ref())
+            .unwrap()
+            .into_luma8();
+
+        assert!(blank.pixels().all(|p| p[0] == u8::MAX));
+
+        let dark = Ascii::new("$@\n$@").convert_to_image().unwrap();
+        let dark = image::load_from_memory(dark.get_ref())
+            .unwrap()
+            .into_luma8();
+
+        assert_eq!(dark.dimensions(), blank.dimensions());
+        assert!(dark.pixels().any(|p| p[0] < 64));
+
+        // both rendering modes reject the same symbols
+        assert_eq!(
+            Ascii::new("P").convert_to_image(),
+            Err(ConvertError::UnknownASCIISymbol(
+                'P',
+                Some(SymbolPosition { x: 0, y: 0 })
+            ))
+        );
+    }
+
+    // Test to check that a scaled image is exactly twice as large and still has the same shapes.
+    #[test]
+    fn test_convert_to_image_with_scale() {
+        for render_mode in [ImageRenderMode::Glyphs, ImageRenderMode::Pixels] {
+            let ascii = Ascii::new("$@ \n .:").with_render_mode(render_mode);
+            let normal = ascii.convert_to_image().unwrap();
+            let normal = image::load_from_memory(normal.get_ref())
+                .unwrap()
+                .into_luma8();
+            let double = ascii.with_scale(2).convert_to_image().unwrap();
+            let double = image::load_from_memory(double.get_ref())
+                .unwrap()
+                .into_luma8();
+
+            assert_eq!(
+                double.dimensions(),
+                (normal.width() * 2, normal.height() * 2)
+            );
+
+            let shrunk = imageops::resize(
+                &double,
+                normal.width(),
+                normal.height(),
+                imageops::FilterType::Triangle,
+            );
+            let difference = normal
+                .pixels()
+                .zip(shrunk.pixels())
+                .map(|(a, b)| a[0].abs_diff(b[0]) as u64)
+                .sum::<u64>()
+                / normal.pixels().len() as u64;
+
+            assert!(difference < 16);
+        }
+
+        assert_eq!(
+            Ascii::new("$").with_scale(0).convert_to_image(),
+            Ascii::new("$").convert_to_image()
+        );
+    }
+
+    // Test that the PNG is turned and mirrored once it is drawn.
+    #[test]
+    fn test_convert_to_image_with_orientation() {
+        let decode = |ascii: Ascii| {
+            let png = ascii.convert_to_image().unwrap();
+            image::load_from_memory(png.get_ref()).unwrap()
+        };
+
+        for render_mode in [ImageRenderMode::Glyphs, ImageRenderMode::Pixels] {
+            let ascii = || Ascii::new("$@ \n .:").with_render_mode(render_mode);
+            let upright = decode(ascii());
+
+            assert_eq!(
+                decode(ascii().with_rotation(Rotation::Clockwise90)),
+                upright.rotate90()
+            );
+            assert_eq!(decode(ascii().with_flip(Flip::Horizontal)), upright.fliph());
+            // the rotations and flips are applied in order
+            assert_eq!(
+                decode(
+                    ascii()
+                        .with_rotation(Rotation::Clockwise270)
+                        .with_flip(Flip::Vertical)
+                ),
+                upright.rotate270().flipv()
+            );
+        }
+    }
+
+    // Test that the brightness of the PNG is matched to a histogram.
+    #[test]
+    fn test_convert_to_image_with_histogram_match() {
+        let symbol_map = SymbolMap::from_ramp("@ ").unwrap();
+        let ascii = "@@@@   \n".repeat(4);
+        let gradient = image::ImageBuffer::from_fn(256, 1, |x, _| Luma([x as u8]));
+        let convert = |target: Option<BrightnessHistogram>| {
+            let ascii = Ascii::with_symbol_map(&ascii, symbol_map.clone())
+                .with_render_mode(ImageRenderMode::Pixels);
+            let ascii = match target {
+                Some(target) => ascii.with_histogram_match(target),
+                None => ascii,
+            };
+            let png = ascii.convert_to_image().unwrap();
+            image::load_from_memory(png.get_ref()).unwrap().into_luma8()
+        };
+
+        let plain = convert(None);
+        let matched = convert(Some(BrightnessHistogram::from_image(&gradient)));
+
+        // the two symbols are spread out to the middle of their share of the gradient
+        let (dark, light) = (plain.width() * 2 / 7, plain.width() * 11 / 14);
+        assert_eq!(plain.get_pixel(dark, 0)[0], 0);
+        assert!((50..90).contains(&matched.get_pixel(dark, 0)[0]));
+        assert_eq!(
+            plain.get_pixel(light, 0)[0],
+            symbol_map.brightness_for_symbol(' ').unwrap()
+        );
+        assert!((170..220).contains(&matched.get_pixel(light, 0)[0]));
+
+        // glyphs are matched too
+        let glyphs = Ascii::new("$ ").convert_to_image().unwrap();
+        let matched_glyphs = Ascii::new("$ ")
+            .with_histogram_match(BrightnessHistogram::photographic())
+            .convert_to_image()
+            .unwrap();
+        assert_ne!(glyphs.get_ref(), matched_glyphs.get_ref());
+    }
+
+    // Test to check that ASCII is properly turned into an SVG.
+    #[test]
+    fn test_convert_to_svg() {
+        let symbol_map = SymbolMap::from_ramp("@. ").unwrap();
+        let svg = Ascii::with_symbol_map("  .\n@", symbol_map.clone())
+            .convert_to_svg()
+            .unwrap();
+
+        assert!(svg.starts_with("<svg "));
+        assert!(svg.contains("viewBox=\"0 0 3 4\""));
+        assert!(svg.ends_with("</svg>\n"));
+        // the two spaces are merged, and the dark "@" is left to the background
+        assert!(svg.contains(
+            "<rect x=\"0\" y=\"0\" width=\"2\" height=\"2\" fill=\"rgb(170,170,170)\"/>"
+        ));
+        assert!(
+            svg.contains("<rect x=\"2\" y=\"0\" width=\"1\" height=\"2\" fill=\"rgb(85,85,85)\"/>")
+        );
+        assert_eq!(svg.matches("<rect").count(), 3);
+
+        assert_eq!(
+            Ascii::with_symbol_map("P", symbol_map).convert_to_svg(),
+            Err(ConvertError::UnknownASCIISymbol(
+                'P',
+                Some(SymbolPosition { x: 0, y: 0 })
+            ))
+        );
     }
 
     // Test to check that ASCII is properly turned into a PNG.
@@ -127,7 +1061,9 @@ mod tests {
         let ascii_file =
             fs::read_to_string(ascii_path).expect("Should have been able to read ASCII file.");
 
-        let image = Ascii::new(&ascii_file).convert_to_image();
+        let image = Ascii::new(&ascii_file)
+            .with_render_mode(ImageRenderMode::Pixels)
+            .convert_to_image();
 
         assert!(image.is_ok());
 
@@ -137,8 +1073,24 @@ mod tests {
             env!("CARGO_MANIFEST_DIR"),
             "/test_assets/converted_images/castle.png"
         );
-        let image_file = fs::read(image_path).expect("Should have been able to read image file.");
+        assert_golden(image_path, image.unwrap().get_ref(), &Tolerance::exact());
+    }
+
+    // Test that canonical output has the same pixels as the default output, and always the same bytes.
+    #[test]
+    fn test_convert_to_image_with_canonical_output() {
+        let ascii = "$$$\n. .\n";
+        let default = Ascii::new(ascii).convert_to_image().unwrap().into_inner();
+        let canonical = Ascii::new(ascii)
+            .with_canonical_output(true)
+            .convert_to_image()
+            .unwrap()
+            .into_inner();
 
-        assert_eq!(image.unwrap().into_inner(), image_file);
+        assert_eq!(canonical, canonicalize_png(&default).unwrap());
+        assert_eq!(
+            image::load_from_memory(&canonical).unwrap(),
+            image::load_from_memory(&default).unwrap()
+        );
     }
 }
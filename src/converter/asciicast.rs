@@ -0,0 +1,135 @@
+//! ASCII animation to asciinema cast converter.
+//!
+//! An asciinema cast records what was written to a terminal and when, so converted animations
+//! can be played back in a terminal with `asciinema play` or in the asciinema web player. The
+//! casts are written in version 2 of the format: a JSON header that sizes the terminal, followed
+//! by one JSON array for each [AsciiFrame] that clears the screen and writes the frame at the
+//! time it is shown.
+//!
+//! Robert Peterson and Kelsey Werner 2023
+
+use crate::converter::animation::AsciiFrame;
+use serde_json::json;
+use std::time::Duration;
+
+/// The version of the asciinema cast format written by [frames_to_cast]:
+/// <https://docs.asciinema.org/manual/asciicast/v2/>
+pub const CAST_VERSION: u32 = 2;
+
+/// The media type of asciinema casts, which browsers use to offer a cast as a download.
+pub const CAST_MIME_TYPE: &str = "application/x-asciicast";
+
+/// The escape codes that move the cursor to the top left corner of the terminal and clear it,
+/// which are written before each frame of a cast.
+const CLEAR_SCREEN: &str = "\x1b[H\x1b[2J";
+
+/// Write [AsciiFrame]s as an asciinema cast that plays them at `fps` frames per second.
+///
+/// The cast starts with a header that sizes the terminal to the longest line and the tallest
+/// frame, followed by one line for each frame. Each frame clears the screen and is written once
+/// the frames before it have been shown. With [None] as the frame rate, each frame is shown for
+/// its own delay, which keeps the timing of an animated GIF. A frame rate of 0 is treated as 1.
+pub fn frames_to_cast(frames: &[AsciiFrame], fps: Option<u32>) -> String {
+    let width = frames
+        .iter()
+        .flat_map(|frame| frame.ascii.lines())
+        .map(|line| line.chars().count())
+        .max()
+        .unwrap_or(0);
+    let height = frames
+        .iter()
+        .map(|frame| frame.ascii.lines().count())
+        .max()
+        .unwrap_or(0);
+
+    let mut cast = json!({"version": CAST_VERSION, "width": width, "height": height}).to_string();
+    cast.push('\n');
+
+    let frame_delay = fps.map(|fps| Duration::from_secs(1) / fps.max(1));
+    let mut time = Duration::ZERO;
+    for frame in frames {
+        // terminals in raw mode need a carriage return to go back to the start of the line
+        let output = format!("{}{}", CLEAR_SCREEN, frame.ascii.replace('\n', "\r\n"));
+        cast.push_str(&json!([time.as_secs_f64(), "o", output]).to_string());
+        cast.push('\n');
+        time += frame_delay.unwrap_or(frame.delay);
+    }
+
+    cast
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Test that frames are written as an asciinema cast that clears the screen before each frame.
+    #[test]
+    fn test_frames_to_cast() {
+        let frames = [
+            AsciiFrame {
+                ascii: "$$$\n$$$\n".to_string(),
+                delay: Duration::from_millis(500),
+            },
+            AsciiFrame {
+                ascii: "..\n".to_string(),
+                delay: Duration::from_millis(250),
+            },
+            AsciiFrame {
+                ascii: "$\n".to_string(),
+                delay: Duration::from_millis(500),
+            },
+        ];
+
+        assert_eq!(
+            frames_to_cast(&frames, None),
+            concat!(
+                "{\"height\":2,\"version\":2,\"width\":3}\n",
+                "[0.0,\"o\",\"\\u001b[H\\u001b[2J$$$\\r\\n$$$\\r\\n\"]\n",
+                "[0.5,\"o\",\"\\u001b[H\\u001b[2J..\\r\\n\"]\n",
+                "[0.75,\"o\",\"\\u001b[H\\u001b[2J$\\r\\n\"]\n",
+            )
+        );
+    }
+
+    // Test that a frame rate shows every frame for the same time, whatever their delays.
+    #[test]
+    fn test_frames_to_cast_fps() {
+        let frames = [
+            AsciiFrame {
+                ascii: "$\n".to_string(),
+                delay: Duration::from_millis(500),
+            },
+            AsciiFrame {
+                ascii: ".\n".to_string(),
+                delay: Duration::from_millis(100),
+            },
+            AsciiFrame {
+                ascii: "$\n".to_string(),
+                delay: Duration::from_millis(100),
+            },
+        ];
+        let times = |fps| -> Vec<f64> {
+            frames_to_cast(&frames, fps)
+                .lines()
+                .skip(1)
+                .map(|line| {
+                    serde_json::from_str::<serde_json::Value>(line).unwrap()[0]
+                        .as_f64()
+                        .unwrap()
+                })
+                .collect()
+        };
+        assert_eq!(times(Some(4)), [0.0, 0.25, 0.5]);
+        assert_eq!(times(Some(0)), [0.0, 1.0, 2.0]);
+        assert_eq!(times(None), [0.0, 0.5, 0.6]);
+    }
+
+    // Test that a cast without frames only has its header.
+    #[test]
+    fn test_frames_to_cast_empty() {
+        assert_eq!(
+            frames_to_cast(&[], Some(10)),
+            "{\"height\":0,\"version\":2,\"width\":0}\n"
+        );
+    }
+}
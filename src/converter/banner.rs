@@ -0,0 +1,176 @@
+//! Text to ASCII banner converter.
+//!
+//! A banner is short text written in large letters made of ASCII symbols, like the lettering of
+//! `figlet`. The text is drawn in black on white with the DejaVu Sans Mono font, and the drawing
+//! is converted like any other image, so the letters are made of the same symbol ramp and
+//! charsets as converted photos. The size of the letters is given in lines of text.
+//!
+//! Robert Peterson and Kelsey Werner 2023
+
+use crate::converter::{
+    ascii::load_font, dimension::Dimension, image::PreparedImage, options::ConvertOptions,
+    ConvertError,
+};
+use ab_glyph::{point, Font, PxScale, ScaleFont};
+use image::{DynamicImage, GrayImage, Luma};
+
+/// The number of lines each line of a banner is tall by default.
+pub const DEFAULT_FONT_SIZE: u32 = 8;
+
+/// Draw text in black on white, with each line of the text `line_height` pixels tall.
+///
+/// [ConvertError::TooLarge] is returned if the drawing has more pixels than the options allow.
+fn draw_text(
+    text: &str,
+    line_height: u32,
+    options: &ConvertOptions,
+) -> Result<GrayImage, ConvertError> {
+    let font = load_font()?;
+    let scaled_font = font.as_scaled(PxScale::from(line_height as f32));
+    let advance = scaled_font.h_advance(font.glyph_id('M'));
+
+    let lines: Vec<&str> = text.lines().collect();
+    let columns = lines
+        .iter()
+        .map(|line| line.chars().count())
+        .max()
+        .unwrap_or(0);
+    let drawn = Dimension::from((
+        (columns as f32 * advance).ceil() as u32,
+        (lines.len() as u32).saturating_mul(line_height),
+    ));
+    options.limits.check_pixels(&drawn)?;
+
+    let mut img = GrayImage::from_pixel(drawn.width, drawn.height, Luma([u8::MAX]));
+    for (h, line) in (0_u32..).zip(&lines) {
+        for (w, c) in (0_u32..).zip(line.chars()) {
+            let mut glyph = scaled_font.scaled_glyph(c);
+            glyph.position = point(
+                w as f32 * advance,
+                (h * line_height) as f32 + scaled_font.ascent(),
+            );
+
+            if let Some(outline) = scaled_font.outline_glyph(glyph) {
+                let bounds = outline.px_bounds();
+                outline.draw(|x, y, coverage| {
+                    let px = bounds.min.x as i64 + x as i64;
+                    let py = bounds.min.y as i64 + y as i64;
+                    if px < 0 || py < 0 || px >= drawn.width as i64 || py >= drawn.height as i64 {
+                        return;
+                    }
+
+                    // darken the pixel by how much of it the glyph covers
+                    let pixel = img.get_pixel_mut(px as u32, py as u32);
+                    let ink = (u8::MAX as f32 * (1.0 - coverage.min(1.0))) as u8;
+                    pixel[0] = pixel[0].min(ink);
+                });
+            }
+        }
+    }
+
+    Ok(img)
+}
+
+/// Convert text into a banner of large letters made of symbols, with each line of the text
+/// `font_size` lines of ASCII tall.
+///
+/// The drawing of the text is converted with the given [ConvertOptions], except that the width
+/// is picked to fit the letters, so the width of the options is ignored. The charset, symbol map,
+/// dithering and other settings are used as they are.
+/// [ConvertError::InvalidFontSize] is returned if the font size is zero, and
+/// [ConvertError::EmptyInput] if the text only has whitespace.
+pub fn text_to_banner(
+    text: &str,
+    font_size: u32,
+    options: &ConvertOptions,
+) -> Result<String, ConvertError> {
+    if font_size == 0 {
+        return Err(ConvertError::InvalidFontSize);
+    }
+    if text.trim().is_empty() {
+        return Err(ConvertError::EmptyInput);
+    }
+
+    // each line of ASCII covers `1 / cell_aspect` rows of pixels when the pixels are converted
+    // one column to one character, which also gives every charset the same number of lines
+    let line_height = (font_size as f32 / options.cell_aspect()).ceil() as u32;
+    let img = draw_text(text, line_height, options)?;
+
+    let options = ConvertOptions {
+        width: Some(img.width().max(1)),
+        ..options.clone()
+    };
+    PreparedImage::from_image(DynamicImage::ImageLuma8(img)).convert(&options)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::converter::{
+        options::{RenderCharset, SizeLimits},
+        symbol_map::SymbolMap,
+    };
+
+    // Test that text converts to a banner that is as tall as the font size and made of symbols.
+    #[test]
+    fn test_text_to_banner() {
+        let banner = text_to_banner("Hi", 8, &ConvertOptions::default()).unwrap();
+
+        assert_eq!(banner.lines().count(), 8);
+        assert!(banner.contains('$'));
+        // the letters are drawn with the symbols of the ramp on a blank background
+        let symbol_map = SymbolMap::default();
+        assert!(banner
+            .chars()
+            .all(|c| c == '\n' || symbol_map.brightness_for_symbol(c).is_ok()));
+        assert!(banner.lines().last().unwrap().trim().is_empty());
+
+        let two_lines = text_to_banner("Hi\nHi", 8, &ConvertOptions::default()).unwrap();
+        assert_eq!(two_lines.lines().count(), 16);
+
+        let larger = text_to_banner("Hi", 16, &ConvertOptions::default()).unwrap();
+        assert_eq!(larger.lines().count(), 16);
+        assert!(larger.lines().next().unwrap().len() > banner.lines().next().unwrap().len());
+    }
+
+    // Test that a banner is made of the charset of the options.
+    #[test]
+    fn test_text_to_banner_charset() {
+        let options = ConvertOptions {
+            charset: RenderCharset::Braille,
+            ..Default::default()
+        };
+        let banner = text_to_banner("Hi", 8, &options).unwrap();
+
+        assert!(banner
+            .chars()
+            .all(|c| c == '\n' || ('\u{2800}'..='\u{28ff}').contains(&c)));
+    }
+
+    // Test that invalid font sizes, empty text and banners that are too large are rejected.
+    #[test]
+    fn test_text_to_banner_errors() {
+        let options = ConvertOptions::default();
+
+        assert_eq!(
+            text_to_banner("Hi", 0, &options),
+            Err(ConvertError::InvalidFontSize)
+        );
+        assert_eq!(
+            text_to_banner(" \n\t", 8, &options),
+            Err(ConvertError::EmptyInput)
+        );
+
+        let limited = ConvertOptions {
+            limits: SizeLimits {
+                max_pixels: 500,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        assert!(matches!(
+            text_to_banner("Hello", 8, &limited),
+            Err(ConvertError::TooLarge { .. })
+        ));
+    }
+}
@@ -0,0 +1,75 @@
+//! Batch image to ASCII conversion.
+//!
+//! Converting many images is spread over scoped threads, one per available CPU, which take the
+//! next image to convert from a shared queue until every image is converted. The results are
+//! put back in the order of the images, so the result of each image can be found by its
+//! position no matter which thread converted it or how long it took.
+//!
+//! Robert Peterson and Kelsey Werner 2023
+
+use std::{num::NonZeroUsize, panic, sync::Mutex, thread};
+
+/// Apply `convert` to every item on as many threads as there are CPUs, and return the results in
+/// the order of the items.
+pub(crate) fn map_in_order<T, R, F>(items: Vec<T>, convert: F) -> Vec<R>
+where
+    T: Send,
+    R: Send,
+    F: Fn(T) -> R + Sync,
+{
+    let workers = thread::available_parallelism()
+        .map_or(1, NonZeroUsize::get)
+        .min(items.len());
+    if workers <= 1 {
+        return items.into_iter().map(convert).collect();
+    }
+
+    let queue = Mutex::new(items.into_iter().enumerate());
+    let mut results: Vec<(usize, R)> = thread::scope(|scope| {
+        let handles: Vec<_> = (0..workers)
+            .map(|_| {
+                scope.spawn(|| {
+                    let mut converted = Vec::new();
+                    // the lock is released before the item is converted
+                    while let Some((i, item)) = queue.lock().map_or(None, |mut queue| queue.next())
+                    {
+                        converted.push((i, convert(item)));
+                    }
+                    converted
+                })
+            })
+            .collect();
+
+        handles
+            .into_iter()
+            .flat_map(|handle| {
+                handle
+                    .join()
+                    .unwrap_or_else(|panic| panic::resume_unwind(panic))
+            })
+            .collect()
+    });
+
+    results.sort_unstable_by_key(|(i, _)| *i);
+    results.into_iter().map(|(_, result)| result).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    // Test that the results are in the order of the items even when later items finish first.
+    #[test]
+    fn test_map_in_order() {
+        let items: Vec<u64> = (0..20).collect();
+
+        let results = map_in_order(items, |i| {
+            thread::sleep(Duration::from_millis(20 - i));
+            i * 2
+        });
+
+        assert_eq!(results, (0..20).map(|i| i * 2).collect::<Vec<_>>());
+        assert_eq!(map_in_order(Vec::<u64>::new(), |i| i), Vec::<u64>::new());
+    }
+}
@@ -0,0 +1,277 @@
+//! Pluggable image encoding and decoding.
+//!
+//! [Image] reads images and [Ascii] writes PNGs through an [ImageCodec], so the library that
+//! parses and writes image files can be swapped out without touching the conversions. The
+//! [ImageCrateCodec] uses the [image] crate for both, and is the [default_codec] unless a feature
+//! picks another backend. With the `minimal-png` feature the [default_codec] is the
+//! `MinimalPngCodec`, which writes PNGs with a small encoder of its own on top of the zlib
+//! streams of the flate2 crate, and still reads images with the [image] crate. A different codec
+//! is picked for a single conversion with [Image::with_codec] and [Ascii::with_codec].
+//!
+//! [Image]: crate::converter::image::Image
+//! [Image::with_codec]: crate::converter::image::Image::with_codec
+//! [Ascii]: crate::converter::ascii::Ascii
+//! [Ascii::with_codec]: crate::converter::ascii::Ascii::with_codec
+//!
+//! Robert Peterson and Kelsey Werner 2023
+
+use crate::converter::{
+    dimension::Dimension,
+    image::{AsciiImageBuffer, ImageInfo},
+    ConvertError, ErrorSource,
+};
+use image::{io::Reader, DynamicImage, ImageOutputFormat};
+use std::io::Cursor;
+
+/// [ImageCodec] is a trait for the backends that decode images and encode PNGs.
+pub trait ImageCodec: Send + Sync {
+    /// Read the [ImageInfo] of the image in a buffer from its header, without decoding the pixels.
+    ///
+    /// The buffer doesn't need to be rewound afterwards, since the caller does that.
+    /// [ConvertError::ReadError] is returned if the buffer can't be read, and
+    /// [ConvertError::DecodeError] is returned if the format or header can't be parsed.
+    fn inspect(&self, file: &mut dyn AsciiImageBuffer) -> Result<ImageInfo, ConvertError>;
+
+    /// Decode the image in a buffer into a [DynamicImage], guessing its format from its header.
+    ///
+    /// [ConvertError::ReadError] is returned if the format can't be guessed, and
+    /// [ConvertError::DecodeError] if the image can't be parsed.
+    fn decode(&self, file: &mut dyn AsciiImageBuffer) -> Result<DynamicImage, ConvertError>;
+
+    /// Write an image as a PNG.
+    ///
+    /// [ConvertError::WriteError] is returned if the PNG can't be written.
+    fn encode_png(&self, img: &DynamicImage) -> Result<Cursor<Vec<u8>>, ConvertError>;
+
+    /// Whether PNG images decode the same way as with the PNG decoder of the [image] crate, so
+    /// that they can be streamed through it one row at a time instead of calling
+    /// [ImageCodec::decode].
+    fn streams_png_rows(&self) -> bool {
+        false
+    }
+}
+
+/// [ImageCrateCodec] is the [ImageCodec] that decodes and encodes with the [image] crate.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct ImageCrateCodec;
+
+impl ImageCodec for ImageCrateCodec {
+    fn inspect(&self, file: &mut dyn AsciiImageBuffer) -> Result<ImageInfo, ConvertError> {
+        match Reader::new(file).with_guessed_format() {
+            Ok(image_with_format) => match image_with_format.format() {
+                Some(format) => match image_with_format.into_dimensions() {
+                    Ok(dimensions) => Ok(ImageInfo {
+                        dimension: Dimension::from(dimensions),
+                        format,
+                    }),
+                    Err(e) => Err(ConvertError::DecodeError(ErrorSource::new(e))),
+                },
+                None => Err(ConvertError::DecodeError(ErrorSource::message(
+                    "the format of the image is not supported",
+                ))),
+            },
+            Err(e) => Err(ConvertError::ReadError(ErrorSource::new(e))),
+        }
+    }
+
+    fn decode(&self, file: &mut dyn AsciiImageBuffer) -> Result<DynamicImage, ConvertError> {
+        match Reader::new(file).with_guessed_format() {
+            Ok(image_with_format) => image_with_format
+                .decode()
+                .map_err(|e| ConvertError::DecodeError(ErrorSource::new(e))),
+            Err(e) => Err(ConvertError::ReadError(ErrorSource::new(e))),
+        }
+    }
+
+    fn encode_png(&self, img: &DynamicImage) -> Result<Cursor<Vec<u8>>, ConvertError> {
+        // write image to a [Cursor]
+        let mut buffer: Cursor<Vec<u8>> = Cursor::new(Vec::new());
+        let write = img.write_to(&mut buffer, ImageOutputFormat::Png);
+
+        match write {
+            Ok(_) => Ok(buffer),
+            Err(e) => Err(ConvertError::WriteError(ErrorSource::new(e))),
+        }
+    }
+
+    fn streams_png_rows(&self) -> bool {
+        true
+    }
+}
+
+/// [MinimalPngCodec] is the [ImageCodec] that writes PNGs with a minimal encoder of its own and
+/// reads images with the [image] crate.
+///
+/// Every row is stored without a filter and the pixels are compressed with the zlib encoder of
+/// the flate2 crate, so the PNG encoder of the [image] crate isn't needed. The PNGs are larger
+/// than the ones the [ImageCrateCodec] writes, but have the same pixels.
+#[cfg(feature = "minimal-png")]
+#[derive(Debug, Default, Clone, Copy)]
+pub struct MinimalPngCodec;
+
+#[cfg(feature = "minimal-png")]
+impl ImageCodec for MinimalPngCodec {
+    fn inspect(&self, file: &mut dyn AsciiImageBuffer) -> Result<ImageInfo, ConvertError> {
+        ImageCrateCodec.inspect(file)
+    }
+
+    fn decode(&self, file: &mut dyn AsciiImageBuffer) -> Result<DynamicImage, ConvertError> {
+        ImageCrateCodec.decode(file)
+    }
+
+    fn encode_png(&self, img: &DynamicImage) -> Result<Cursor<Vec<u8>>, ConvertError> {
+        use crate::converter::png_text::{encode_chunk, PNG_SIGNATURE};
+        use flate2::{write::ZlibEncoder, Compression};
+        use std::io::Write;
+
+        // the color types of the PNG specification for 8-bit gray, RGB, gray with alpha and RGBA
+        let converted;
+        let (img, color_type) = match img {
+            DynamicImage::ImageLuma8(_) => (img, 0),
+            DynamicImage::ImageRgb8(_) => (img, 2),
+            DynamicImage::ImageLumaA8(_) => (img, 4),
+            DynamicImage::ImageRgba8(_) => (img, 6),
+            _ => {
+                converted = DynamicImage::ImageRgba8(img.to_rgba8());
+                (&converted, 6)
+            }
+        };
+
+        let mut header = Vec::with_capacity(13);
+        header.extend_from_slice(&img.width().to_be_bytes());
+        header.extend_from_slice(&img.height().to_be_bytes());
+        // 8 bits per channel, deflate compression, adaptive filtering and no interlacing
+        header.extend_from_slice(&[8, color_type, 0, 0, 0]);
+
+        let row_length = img.width() as usize * usize::from(img.color().bytes_per_pixel());
+        let mut pixels = ZlibEncoder::new(Vec::new(), Compression::default());
+        let write_error = |e| ConvertError::WriteError(ErrorSource::new(e));
+        if row_length > 0 {
+            for row in img.as_bytes().chunks(row_length) {
+                // each row starts with the type of its filter, where 0 is no filter
+                pixels.write_all(&[0]).map_err(write_error)?;
+                pixels.write_all(row).map_err(write_error)?;
+            }
+        }
+        let pixels = pixels.finish().map_err(write_error)?;
+
+        let mut png = PNG_SIGNATURE.to_vec();
+        png.extend(encode_chunk(b"IHDR", &header));
+        png.extend(encode_chunk(b"IDAT", &pixels));
+        png.extend(encode_chunk(b"IEND", &[]));
+        Ok(Cursor::new(png))
+    }
+
+    fn streams_png_rows(&self) -> bool {
+        true
+    }
+}
+
+/// The [ImageCodec] used by conversions that don't pick one, which is chosen by the features
+/// the library is built with.
+pub fn default_codec() -> &'static dyn ImageCodec {
+    #[cfg(feature = "minimal-png")]
+    return &MinimalPngCodec;
+    #[cfg(not(feature = "minimal-png"))]
+    return &ImageCrateCodec;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::converter::{ascii::Ascii, image::Image, options::ImageRenderMode};
+    use image::{ImageBuffer, ImageFormat, Rgb};
+
+    // Creates an image with a gradient in each channel
+    fn gradient() -> DynamicImage {
+        DynamicImage::ImageRgb8(ImageBuffer::from_fn(7, 5, |x, y| {
+            Rgb([(x * 36) as u8, (y * 50) as u8, ((x + y) * 20) as u8])
+        }))
+    }
+
+    // Test that the image crate codec reads the PNGs it writes.
+    #[test]
+    fn test_image_crate_codec() {
+        let png = ImageCrateCodec
+            .encode_png(&gradient())
+            .unwrap()
+            .into_inner();
+
+        let info = ImageCrateCodec.inspect(&mut Cursor::new(&png)).unwrap();
+        assert_eq!(info.dimension, Dimension::from((7, 5)));
+        assert_eq!(info.format, ImageFormat::Png);
+        assert_eq!(
+            ImageCrateCodec.decode(&mut Cursor::new(&png)).unwrap(),
+            gradient()
+        );
+
+        assert!(matches!(
+            ImageCrateCodec.decode(&mut Cursor::new(b"not an image".to_vec())),
+            Err(ConvertError::DecodeError(_))
+        ));
+    }
+
+    // A codec that reads every image as a white square and writes PNGs as their size
+    struct WhiteCodec;
+
+    impl ImageCodec for WhiteCodec {
+        fn inspect(&self, _: &mut dyn AsciiImageBuffer) -> Result<ImageInfo, ConvertError> {
+            Ok(ImageInfo {
+                dimension: Dimension::from((8, 8)),
+                format: ImageFormat::Png,
+            })
+        }
+
+        fn decode(&self, _: &mut dyn AsciiImageBuffer) -> Result<DynamicImage, ConvertError> {
+            Ok(DynamicImage::ImageLuma8(ImageBuffer::from_pixel(
+                8,
+                8,
+                image::Luma([u8::MAX]),
+            )))
+        }
+
+        fn encode_png(&self, img: &DynamicImage) -> Result<Cursor<Vec<u8>>, ConvertError> {
+            Ok(Cursor::new(
+                format!("{}x{}", img.width(), img.height()).into_bytes(),
+            ))
+        }
+    }
+
+    // Test that images are read and PNGs are written with the codec the conversion picks.
+    #[test]
+    fn test_with_codec() {
+        let mut buff = Cursor::new(b"anything".to_vec());
+        let ascii = Image::with_width(&mut buff, 4)
+            .with_codec(&WhiteCodec)
+            .convert_to_ascii()
+            .unwrap();
+        assert_eq!(ascii, "    \n    \n");
+
+        let png = Ascii::new("$$\n$$\n")
+            .with_render_mode(ImageRenderMode::Pixels)
+            .with_codec(&WhiteCodec)
+            .convert_to_image()
+            .unwrap();
+        assert_eq!(String::from_utf8(png.into_inner()).unwrap(), "250x500");
+    }
+
+    // Test that the minimal PNGs have the same pixels as the images they were written from.
+    #[cfg(feature = "minimal-png")]
+    #[test]
+    fn test_minimal_png_codec() {
+        let gray_alpha = DynamicImage::ImageLumaA8(ImageBuffer::from_fn(3, 4, |x, y| {
+            image::LumaA([(x * 80) as u8, (y * 60) as u8])
+        }));
+        for img in [gradient(), gray_alpha, DynamicImage::new_luma8(1, 1)] {
+            let png = MinimalPngCodec.encode_png(&img).unwrap().into_inner();
+            assert_eq!(ImageCrateCodec.decode(&mut Cursor::new(&png)).unwrap(), img);
+        }
+
+        let wide = DynamicImage::new_rgb16(2, 2);
+        let png = MinimalPngCodec.encode_png(&wide).unwrap().into_inner();
+        assert_eq!(
+            ImageCrateCodec.decode(&mut Cursor::new(&png)).unwrap(),
+            DynamicImage::ImageRgba8(wide.to_rgba8())
+        );
+    }
+}
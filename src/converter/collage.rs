@@ -0,0 +1,178 @@
+//! Collage of ASCII art.
+//!
+//! A collage stitches the ASCII art of a few images into a single piece, either side by side on
+//! the same lines or in a grid. The blocks of ASCII art are padded so they line up: every line of
+//! a block is padded with spaces to the width of the widest block in its column, and shorter
+//! blocks are padded with blank lines above and below to the height of the tallest block in their
+//! row. The blocks are separated by a [Collage] column separator, and the rows of a grid by a
+//! line of a row separator.
+//!
+//! Robert Peterson and Kelsey Werner 2023
+
+use crate::converter::ConvertError;
+use std::iter::repeat_n;
+
+/// The separator put between the blocks on the same lines by default.
+pub const DEFAULT_COLUMN_SEPARATOR: &str = " ";
+
+/// [CollageLayout] is an enum that picks how the blocks of a [Collage] are arranged.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CollageLayout {
+    /// Every block is put on the same lines, from left to right.
+    #[default]
+    SideBySide,
+    /// The blocks are put in rows of a square grid, from left to right and top to bottom, so
+    /// two to four blocks are put in two columns.
+    Grid,
+}
+
+impl CollageLayout {
+    /// The number of blocks put on the same lines when a collage of `blocks` blocks is stitched.
+    fn columns(&self, blocks: usize) -> usize {
+        match self {
+            CollageLayout::SideBySide => blocks,
+            CollageLayout::Grid => (1..).find(|columns| columns * columns >= blocks).unwrap(),
+        }
+    }
+}
+
+/// [Collage] is a struct that stitches blocks of ASCII art into a single piece.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Collage {
+    layout: CollageLayout,
+    column_separator: String,
+    row_separator: Option<char>,
+}
+
+impl Collage {
+    /// Create a [Collage] with the given [CollageLayout], which separates the blocks on the same
+    /// lines with the [DEFAULT_COLUMN_SEPARATOR] and puts the rows of a grid right below each other.
+    pub fn new(layout: CollageLayout) -> Self {
+        Collage {
+            layout,
+            column_separator: DEFAULT_COLUMN_SEPARATOR.to_string(),
+            row_separator: None,
+        }
+    }
+
+    /// Set the text put between the blocks on the same lines, such as `" | "`.
+    pub fn with_column_separator(mut self, separator: &str) -> Self {
+        self.column_separator = separator.to_string();
+        self
+    }
+
+    /// Set the character repeated across a line between the rows of a grid, such as `'-'`, or
+    /// [None] to put the rows right below each other.
+    pub fn with_row_separator(mut self, separator: Option<char>) -> Self {
+        self.row_separator = separator;
+        self
+    }
+
+    /// Stitch the blocks of ASCII art into a single piece, in the order they are given.
+    ///
+    /// Every line of the collage is equally long and ends with a line break.
+    /// [ConvertError::EmptyInput] is returned if there are no blocks.
+    pub fn stitch<S: AsRef<str>>(&self, blocks: &[S]) -> Result<String, ConvertError> {
+        if blocks.is_empty() {
+            return Err(ConvertError::EmptyInput);
+        }
+
+        let blocks: Vec<Vec<&str>> = blocks
+            .iter()
+            .map(|block| block.as_ref().lines().collect())
+            .collect();
+        let columns = self.layout.columns(blocks.len());
+        let widths: Vec<usize> = (0..columns)
+            .map(|column| {
+                blocks
+                    .iter()
+                    .skip(column)
+                    .step_by(columns)
+                    .flatten()
+                    .map(|line| line.chars().count())
+                    .max()
+                    .unwrap_or(0)
+            })
+            .collect();
+        let width =
+            widths.iter().sum::<usize>() + self.column_separator.chars().count() * (columns - 1);
+
+        let mut collage = String::new();
+        for (row, blocks) in blocks.chunks(columns).enumerate() {
+            if let Some(separator) = self.row_separator.filter(|_| row > 0) {
+                collage.extend(repeat_n(separator, width));
+                collage.push('\n');
+            }
+
+            let height = blocks.iter().map(Vec::len).max().unwrap_or(0);
+            for line in 0..height {
+                for (column, width) in widths.iter().enumerate() {
+                    if column > 0 {
+                        collage.push_str(&self.column_separator);
+                    }
+                    // shorter blocks are centered, and the missing blocks of the last row of a grid are left blank
+                    let text = blocks
+                        .get(column)
+                        .and_then(|lines| {
+                            let top = (height - lines.len()) / 2;
+                            lines.get(line.checked_sub(top)?)
+                        })
+                        .copied()
+                        .unwrap_or("");
+                    collage.push_str(text);
+                    collage.extend(repeat_n(' ', width - text.chars().count()));
+                }
+                collage.push('\n');
+            }
+        }
+
+        Ok(collage)
+    }
+}
+
+impl Default for Collage {
+    fn default() -> Self {
+        Collage::new(CollageLayout::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Test that blocks are put side by side, padded to the same width and centered to the same height.
+    #[test]
+    fn test_side_by_side() {
+        let collage = Collage::default()
+            .stitch(&["$$\n$$\n$$\n", "@\n", "%%%\n%\n"])
+            .unwrap();
+
+        assert_eq!(collage, "$$   %%%\n$$ @ %  \n$$      \n");
+    }
+
+    // Test that blocks are put in a grid with the separators between them.
+    #[test]
+    fn test_grid() {
+        let collage = Collage::new(CollageLayout::Grid)
+            .with_column_separator(" | ")
+            .with_row_separator(Some('-'))
+            .stitch(&["$$\n", "@\n@\n", "%%%\n"])
+            .unwrap();
+
+        assert_eq!(collage, "$$  | @\n    | @\n-------\n%%% |  \n");
+
+        let square = Collage::new(CollageLayout::Grid)
+            .stitch(&["1", "2", "3", "4"])
+            .unwrap();
+        assert_eq!(square, "1 2\n3 4\n");
+    }
+
+    // Test that a collage without blocks is rejected.
+    #[test]
+    fn test_empty_collage() {
+        assert_eq!(
+            Collage::default().stitch::<&str>(&[]),
+            Err(ConvertError::EmptyInput)
+        );
+    }
+}
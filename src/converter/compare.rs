@@ -0,0 +1,289 @@
+//! Perceptual comparison of images.
+//!
+//! Comparing the bytes of two PNGs breaks whenever the encoder of the [image] crate changes,
+//! even though the pixels are the same. [compare] looks at the decoded pixels instead. It counts
+//! the pixels that differ by more than a [Tolerance], finds the largest difference of any color
+//! channel, and measures the structural similarity (SSIM) of the brightness of the two images:
+//! <https://en.wikipedia.org/wiki/Structural_similarity>. The resulting [ImageComparison] is
+//! checked against a [Tolerance] with [ImageComparison::is_similar], which is how the golden
+//! images of the tests are compared.
+//!
+//! Robert Peterson and Kelsey Werner 2023
+
+use crate::converter::{image::AsciiImageBuffer, ConvertError, ErrorSource};
+use image::{io::Reader, DynamicImage, GrayImage};
+
+/// The width and height in pixels of the windows the SSIM is measured over.
+const SSIM_WINDOW: u32 = 8;
+
+/// The constant that keeps the SSIM stable when both windows are almost black, which is
+/// `(0.01 * 255)^2` as in the original paper.
+const SSIM_C1: f64 = 6.5025;
+
+/// The constant that keeps the SSIM stable when both windows are almost flat, which is
+/// `(0.03 * 255)^2` as in the original paper.
+const SSIM_C2: f64 = 58.5225;
+
+/// [Tolerance] is a struct that holds how different two images can be and still be similar.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Tolerance {
+    /// The largest difference of any color channel for a pixel to still count as the same.
+    pub pixel_difference: u8,
+    /// The largest fraction (0.0 to 1.0) of the pixels that can differ.
+    pub differing_fraction: f64,
+    /// The smallest SSIM (-1.0 to 1.0) the images can have.
+    pub min_ssim: f64,
+}
+
+impl Tolerance {
+    /// A [Tolerance] that only allows images with exactly the same pixels.
+    pub fn exact() -> Tolerance {
+        Tolerance {
+            pixel_difference: 0,
+            differing_fraction: 0.0,
+            min_ssim: 1.0,
+        }
+    }
+}
+
+impl Default for Tolerance {
+    /// The default [Tolerance] allows small rounding differences in a few pixels, which is
+    /// what changing the filters or the color conversions of an image library can cause.
+    fn default() -> Self {
+        Tolerance {
+            pixel_difference: 2,
+            differing_fraction: 0.001,
+            min_ssim: 0.99,
+        }
+    }
+}
+
+/// [ImageComparison] is a struct that holds how different two images of the same size are.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ImageComparison {
+    /// The number of pixels compared.
+    pub pixels: u64,
+    /// The largest difference of any color channel of any pixel.
+    pub max_difference: u8,
+    /// The number of pixels with a color channel that differs by more than the
+    /// [Tolerance::pixel_difference] used for the comparison.
+    pub differing_pixels: u64,
+    /// The mean SSIM of the brightness of the images, where 1.0 is identical.
+    pub ssim: f64,
+}
+
+impl ImageComparison {
+    /// The fraction (0.0 to 1.0) of the pixels that differ.
+    pub fn differing_fraction(&self) -> f64 {
+        if self.pixels == 0 {
+            return 0.0;
+        }
+        self.differing_pixels as f64 / self.pixels as f64
+    }
+
+    /// Check if the images are similar enough for the [Tolerance].
+    ///
+    /// The [Tolerance] should be the one the comparison was made with, since it decides which
+    /// pixels were counted as differing.
+    pub fn is_similar(&self, tolerance: &Tolerance) -> bool {
+        // identical pixels are always similar, even if rounding leaves the SSIM just under 1.0
+        self.differing_fraction() <= tolerance.differing_fraction
+            && (self.max_difference == 0 || self.ssim >= tolerance.min_ssim)
+    }
+}
+
+/// Decode an image from a buffer in any of the formats the [image] crate can guess.
+///
+/// [ConvertError::ReadError] is returned if the buffer can't be read, and
+/// [ConvertError::DecodeError] is returned if the image can't be parsed.
+pub fn decode<T: AsciiImageBuffer>(buffer: &mut T) -> Result<DynamicImage, ConvertError> {
+    match Reader::new(buffer).with_guessed_format() {
+        Ok(image_with_format) => image_with_format
+            .decode()
+            .map_err(|e| ConvertError::DecodeError(ErrorSource::new(e))),
+        Err(e) => Err(ConvertError::ReadError(ErrorSource::new(e))),
+    }
+}
+
+/// Compare the pixels of two images with a [Tolerance].
+///
+/// Both images are compared as 8-bit RGBA, so an image compares the same no matter which color
+/// type it was saved with. [ConvertError::DimensionMismatch] is returned if the images are not
+/// the same size.
+pub fn compare(
+    expected: &DynamicImage,
+    actual: &DynamicImage,
+    tolerance: &Tolerance,
+) -> Result<ImageComparison, ConvertError> {
+    if expected.width() != actual.width() || expected.height() != actual.height() {
+        return Err(ConvertError::DimensionMismatch);
+    }
+
+    let expected_rgba = expected.to_rgba8();
+    let actual_rgba = actual.to_rgba8();
+
+    let mut max_difference = 0;
+    let mut differing_pixels = 0;
+    for (e, a) in expected_rgba.pixels().zip(actual_rgba.pixels()) {
+        let difference = e.0.iter().zip(a.0).map(|(e, a)| e.abs_diff(a)).max();
+        let difference = difference.unwrap_or(0);
+
+        max_difference = max_difference.max(difference);
+        if difference > tolerance.pixel_difference {
+            differing_pixels += 1;
+        }
+    }
+
+    Ok(ImageComparison {
+        pixels: u64::from(expected.width()) * u64::from(expected.height()),
+        max_difference,
+        differing_pixels,
+        ssim: ssim(&expected.to_luma8(), &actual.to_luma8()),
+    })
+}
+
+/// Measure the mean SSIM of two Luma images of the same size.
+///
+/// The SSIM is measured over windows of [SSIM_WINDOW] pixels that don't overlap, and the
+/// windows at the right and bottom edges are cut short to fit inside of the images.
+fn ssim(expected: &GrayImage, actual: &GrayImage) -> f64 {
+    let (width, height) = expected.dimensions();
+    if width == 0 || height == 0 {
+        return 1.0;
+    }
+
+    let mut total = 0.0;
+    let mut windows = 0;
+    for top in (0..height).step_by(SSIM_WINDOW as usize) {
+        for left in (0..width).step_by(SSIM_WINDOW as usize) {
+            let right = (left + SSIM_WINDOW).min(width);
+            let bottom = (top + SSIM_WINDOW).min(height);
+
+            let pairs: Vec<(f64, f64)> = (top..bottom)
+                .flat_map(|y| (left..right).map(move |x| (x, y)))
+                .map(|(x, y)| {
+                    (
+                        f64::from(expected.get_pixel(x, y)[0]),
+                        f64::from(actual.get_pixel(x, y)[0]),
+                    )
+                })
+                .collect();
+            let n = pairs.len() as f64;
+
+            let mean_e = pairs.iter().map(|(e, _)| e).sum::<f64>() / n;
+            let mean_a = pairs.iter().map(|(_, a)| a).sum::<f64>() / n;
+            let (mut var_e, mut var_a, mut covariance) = (0.0, 0.0, 0.0);
+            for (e, a) in &pairs {
+                var_e += (e - mean_e).powi(2);
+                var_a += (a - mean_a).powi(2);
+                covariance += (e - mean_e) * (a - mean_a);
+            }
+            var_e /= n;
+            var_a /= n;
+            covariance /= n;
+
+            total += ((2.0 * mean_e * mean_a + SSIM_C1) * (2.0 * covariance + SSIM_C2))
+                / ((mean_e.powi(2) + mean_a.powi(2) + SSIM_C1) * (var_e + var_a + SSIM_C2));
+            windows += 1;
+        }
+    }
+
+    total / f64::from(windows)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{ImageBuffer, Luma, Rgb};
+
+    // Creates a gradient that has structure in every window
+    fn gradient() -> DynamicImage {
+        DynamicImage::ImageLuma8(ImageBuffer::from_fn(20, 12, |x, y| {
+            Luma([(x * 12 + y * 3) as u8])
+        }))
+    }
+
+    // Test that identical images are exactly similar, whatever color type they are stored as.
+    #[test]
+    fn test_compare_identical() {
+        let img = gradient();
+        let comparison = compare(
+            &img,
+            &DynamicImage::ImageRgb8(img.to_rgb8()),
+            &Tolerance::exact(),
+        )
+        .unwrap();
+
+        assert_eq!(comparison.pixels, 240);
+        assert_eq!(comparison.max_difference, 0);
+        assert_eq!(comparison.differing_pixels, 0);
+        assert!((comparison.ssim - 1.0).abs() < 1e-9);
+        assert!(comparison.is_similar(&Tolerance::exact()));
+    }
+
+    // Test that small rounding differences are within the default tolerance but not exact.
+    #[test]
+    fn test_compare_rounding() {
+        let img = gradient();
+        let mut rounded = img.to_luma8();
+        rounded.pixels_mut().step_by(7).for_each(|p| p[0] ^= 1);
+        let rounded = DynamicImage::ImageLuma8(rounded);
+
+        let comparison = compare(&img, &rounded, &Tolerance::default()).unwrap();
+        assert_eq!(comparison.max_difference, 1);
+        assert_eq!(comparison.differing_pixels, 0);
+        assert!(comparison.is_similar(&Tolerance::default()));
+
+        let comparison = compare(&img, &rounded, &Tolerance::exact()).unwrap();
+        assert_eq!(comparison.differing_pixels, 35);
+        assert!(!comparison.is_similar(&Tolerance::exact()));
+    }
+
+    // Test that images with different content are not similar.
+    #[test]
+    fn test_compare_different() {
+        let img = gradient();
+        let mut inverted = img.clone();
+        inverted.invert();
+
+        let comparison = compare(&img, &inverted, &Tolerance::default()).unwrap();
+        assert_eq!(comparison.max_difference, 255);
+        assert!(comparison.differing_fraction() > 0.9);
+        assert!(comparison.ssim < 0.0);
+        assert!(!comparison.is_similar(&Tolerance::default()));
+
+        // a single color change is found even if the brightness stays close
+        let red = DynamicImage::ImageRgb8(ImageBuffer::from_pixel(2, 2, Rgb([200, 0, 0])));
+        let mut blue = red.to_rgb8();
+        blue.put_pixel(0, 0, Rgb([0, 0, 200]));
+        let comparison = compare(&red, &DynamicImage::ImageRgb8(blue), &Tolerance::default());
+        assert_eq!(comparison.unwrap().differing_pixels, 1);
+    }
+
+    // Test that images of different sizes can't be compared.
+    #[test]
+    fn test_compare_dimension_mismatch() {
+        let img = gradient();
+
+        assert_eq!(
+            compare(&img, &img.rotate90(), &Tolerance::default()),
+            Err(ConvertError::DimensionMismatch)
+        );
+    }
+
+    // Test that images are decoded from any format the [image] crate guesses.
+    #[test]
+    fn test_decode() {
+        let mut png = std::io::Cursor::new(Vec::new());
+        gradient()
+            .write_to(&mut png, image::ImageFormat::Png)
+            .unwrap();
+        png.set_position(0);
+
+        assert_eq!(decode(&mut png), Ok(gradient()));
+        assert!(matches!(
+            decode(&mut std::io::Cursor::new(vec![0; 10])),
+            Err(ConvertError::DecodeError(_))
+        ));
+    }
+}
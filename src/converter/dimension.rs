@@ -2,10 +2,13 @@
 //!
 //! The [Dimension] struct is used to represent the dimension of the ASCII input
 //! and the image. Dimensions will be scaled up or down in order to generate ASCII
-//! and images that are of a reasonable size for humans to view.
+//! and images that are of a reasonable size for humans to view. The [Rect] struct is used
+//! to represent a region of an image, such as the part of it that is converted.
 //!
 //! Robert Peterson and Kelsey Werner 2023
 
+use serde::{Deserialize, Serialize};
+
 /// [Dimension] is a struct that holds image dimension information.
 #[derive(Debug, PartialEq)]
 pub struct Dimension {
@@ -52,6 +55,19 @@ impl Dimension {
         self.scale(min);
     }
 
+    /// Scale the dimensions so the width is exactly `width`.
+    ///
+    /// Scaling the dimensions will preserve the dimension ratio. The height will
+    /// never be scaled below 1 so that very wide items still have a visible row.
+    pub fn scale_to_width(&mut self, width: u32) {
+        if self.width == 0 {
+            return;
+        }
+        let ratio = self.height as f32 / self.width as f32;
+        self.height = ((width as f32 * ratio) as u32).max(1);
+        self.width = width;
+    }
+
     /// Scale the dimensions by a given `factor`.
     ///
     /// Scaling the dimensions will preserve the dimension ratio.
@@ -83,6 +99,49 @@ impl Default for Dimension {
     }
 }
 
+/// [Rect] is a struct that holds a region of an image, in pixels.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Rect {
+    /// The column of the left edge of the region.
+    pub x: u32,
+    /// The row of the top edge of the region.
+    pub y: u32,
+    /// The width of the region.
+    pub width: u32,
+    /// The height of the region.
+    pub height: u32,
+}
+
+impl Rect {
+    /// Create a new [Rect] whose top left corner is at `x`, `y`.
+    pub fn new(x: u32, y: u32, width: u32, height: u32) -> Self {
+        Rect {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    /// Check if the region has no pixels.
+    pub fn is_empty(&self) -> bool {
+        self.width == 0 || self.height == 0
+    }
+
+    /// Check if the region is not empty and lies inside an item of the given [Dimension].
+    pub fn fits(&self, dimension: &Dimension) -> bool {
+        let fits = |start: u32, length: u32, max: u32| {
+            length > 0 && start.checked_add(length).is_some_and(|end| end <= max)
+        };
+        fits(self.x, self.width, dimension.width) && fits(self.y, self.height, dimension.height)
+    }
+
+    /// The [Dimension] of the region.
+    pub fn dimension(&self) -> Dimension {
+        Dimension::from((self.width, self.height))
+    }
+}
+
 // Test that a [Dimension] can be created from a tuple.
 #[test]
 fn test_from() {
@@ -107,6 +166,43 @@ fn test_scale_down() {
     assert_eq!(190, d2.height);
 }
 
+// Test that the [Dimension] can be scaled to an exact width.
+#[test]
+fn test_scale_to_width() {
+    // scales down
+    let mut d1 = Dimension::from((300, 90));
+    d1.scale_to_width(100);
+    assert_eq!(100, d1.width);
+    assert_eq!(30, d1.height);
+
+    // scales up
+    let mut d2 = Dimension::from((40, 80));
+    d2.scale_to_width(80);
+    assert_eq!(80, d2.width);
+    assert_eq!(160, d2.height);
+
+    // never collapses the height
+    let mut d3 = Dimension::from((1000, 1));
+    d3.scale_to_width(40);
+    assert_eq!(40, d3.width);
+    assert_eq!(1, d3.height);
+}
+
+// Test that a [Rect] only fits inside a [Dimension] when none of it is past the edges.
+#[test]
+fn test_rect_fits() {
+    let d = Dimension::from((100, 50));
+    assert!(Rect::new(0, 0, 100, 50).fits(&d));
+    assert!(Rect::new(10, 20, 30, 30).fits(&d));
+    assert!(!Rect::new(10, 20, 30, 31).fits(&d));
+    assert!(!Rect::new(100, 0, 1, 1).fits(&d));
+    assert!(!Rect::new(u32::MAX, 0, 2, 1).fits(&d));
+
+    let empty = Rect::new(0, 0, 0, 10);
+    assert!(empty.is_empty());
+    assert!(!empty.fits(&d));
+}
+
 // Test that the [Dimension] can be scaled up.
 #[test]
 fn test_scape_down() {
@@ -0,0 +1,128 @@
+//! Dithering for image to ASCII conversion.
+//!
+//! Quantizing a smooth gradient into a small number of symbols makes harsh bands appear
+//! in the ASCII. Dithering hides the bands by spreading the quantization error of each
+//! pixel over its neighbors ([DitherMode::FloydSteinberg]) or by nudging each pixel with a
+//! repeating threshold pattern ([DitherMode::Bayer]).
+//!
+//! Robert Peterson and Kelsey Werner 2023
+
+use crate::converter::options::DitherMode;
+use image::GrayImage;
+
+/// The 4x4 Bayer threshold matrix used for ordered dithering.
+///
+/// The matrix is from <https://en.wikipedia.org/wiki/Ordered_dithering>
+const BAYER_MATRIX: [[u8; 4]; 4] = [[0, 8, 2, 10], [12, 4, 14, 6], [3, 11, 1, 9], [15, 7, 13, 5]];
+
+/// Dither a [GrayImage] in place.
+///
+/// `quantize` maps a brightness to the brightness of the level it will be rendered as, and
+/// `step` is the distance between two neighboring levels. After dithering, quantizing each
+/// pixel of the image gives the dithered level for that pixel.
+pub fn dither<F>(img: &mut GrayImage, mode: DitherMode, step: f32, quantize: F)
+where
+    F: Fn(u8) -> u8,
+{
+    match mode {
+        DitherMode::None => {}
+        DitherMode::FloydSteinberg => floyd_steinberg(img, quantize),
+        DitherMode::Bayer => bayer(img, step),
+    }
+}
+
+/// Dither a [GrayImage] using Floyd–Steinberg error diffusion.
+///
+/// The error between each pixel and its quantized level is pushed onto the pixels to the
+/// right and below it. The weights of the algorithm were found here:
+/// <https://en.wikipedia.org/wiki/Floyd%E2%80%93Steinberg_dithering>
+fn floyd_steinberg<F>(img: &mut GrayImage, quantize: F)
+where
+    F: Fn(u8) -> u8,
+{
+    let (width, height) = img.dimensions();
+    let (w, h) = (width as usize, height as usize);
+    let mut errors = vec![0.0_f32; w * h];
+
+    for y in 0..h {
+        for x in 0..w {
+            let pixel = img.get_pixel_mut(x as u32, y as u32);
+            let value = (pixel[0] as f32 + errors[y * w + x]).clamp(0.0, 255.0);
+            pixel[0] = value.round() as u8;
+            let error = value - quantize(pixel[0]) as f32;
+
+            if x + 1 < w {
+                errors[y * w + x + 1] += error * 7.0 / 16.0;
+            }
+            if y + 1 < h {
+                if x > 0 {
+                    errors[(y + 1) * w + x - 1] += error * 3.0 / 16.0;
+                }
+                errors[(y + 1) * w + x] += error * 5.0 / 16.0;
+                if x + 1 < w {
+                    errors[(y + 1) * w + x + 1] += error / 16.0;
+                }
+            }
+        }
+    }
+}
+
+/// Dither a [GrayImage] using a 4x4 Bayer matrix.
+///
+/// Each pixel is offset by up to half a `step` in either direction depending on its
+/// position in the repeating [BAYER_MATRIX].
+fn bayer(img: &mut GrayImage, step: f32) {
+    for (x, y, pixel) in img.enumerate_pixels_mut() {
+        let threshold = (BAYER_MATRIX[(y % 4) as usize][(x % 4) as usize] as f32 + 0.5) / 16.0;
+        let value = pixel[0] as f32 + (threshold - 0.5) * step;
+        pixel[0] = value.round().clamp(0.0, 255.0) as u8;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::Luma;
+
+    // Quantize to black or white.
+    fn two_tone(brightness: u8) -> u8 {
+        if brightness < 128 {
+            0
+        } else {
+            255
+        }
+    }
+
+    // Test that dithering can be turned off.
+    #[test]
+    fn test_no_dither() {
+        let mut img = GrayImage::from_fn(8, 8, |x, _| Luma([x as u8 * 32]));
+        let original = img.clone();
+        dither(&mut img, DitherMode::None, 255.0, two_tone);
+
+        assert_eq!(img, original);
+    }
+
+    // Test that Floyd–Steinberg dithering of flat gray gives a mix of black and white
+    // that averages out to the original gray.
+    #[test]
+    fn test_floyd_steinberg() {
+        let mut img = GrayImage::from_pixel(16, 16, Luma([128]));
+        dither(&mut img, DitherMode::FloydSteinberg, 255.0, two_tone);
+
+        let white = img.pixels().filter(|p| two_tone(p[0]) == 255).count();
+        assert!((100..=156).contains(&white));
+    }
+
+    // Test that Bayer dithering of flat gray gives a repeating mix of black and white.
+    #[test]
+    fn test_bayer() {
+        let mut img = GrayImage::from_pixel(8, 8, Luma([128]));
+        dither(&mut img, DitherMode::Bayer, 255.0, two_tone);
+
+        let white = img.pixels().filter(|p| two_tone(p[0]) == 255).count();
+        assert_eq!(white, 32);
+        // the pattern repeats every 4 pixels
+        assert_eq!(img.get_pixel(0, 0), img.get_pixel(4, 4));
+    }
+}
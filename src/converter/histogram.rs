@@ -0,0 +1,278 @@
+//! Brightness histogram of an image.
+//!
+//! The [BrightnessHistogram] counts how many pixels of an image have each Luma brightness.
+//! It is computed from the pixels exactly as the converter sees them, after the image is
+//! scaled but before its contrast is adjusted. This can be used to explain why an image
+//! converted poorly, for example because all of its pixels are too dark, and to suggest a
+//! [ContrastMode] that fixes it.
+//!
+//! A histogram can also be the target that the brightness of another image is matched to. This
+//! is used when ASCII is converted back into an image, where the few brightness levels of the
+//! symbols are spread out to follow the histogram of a reference photo, or the standard curve
+//! of [BrightnessHistogram::photographic], so the image looks less like a poster.
+//!
+//! Robert Peterson and Kelsey Werner 2023
+
+use crate::converter::options::ContrastMode;
+use image::GrayImage;
+
+/// Images where the middle 98% of the pixels cover fewer brightness values than this are
+/// considered low contrast.
+const LOW_CONTRAST_RANGE: u8 = 96;
+
+/// Images with a mean brightness below this are considered too dark.
+const DARK_MEAN: f32 = 64.0;
+
+/// Images with a mean brightness above this are considered too light.
+const LIGHT_MEAN: f32 = 192.0;
+
+/// The brightness the standard photographic curve is centered on.
+const PHOTOGRAPHIC_MEAN: f32 = 128.0;
+
+/// The standard deviation of the brightness of the standard photographic curve.
+const PHOTOGRAPHIC_DEVIATION: f32 = 56.0;
+
+/// The number of pixels the standard photographic curve has at its peak.
+const PHOTOGRAPHIC_PEAK: f32 = 10_000.0;
+
+/// [BrightnessHistogram] is a struct that holds the number of pixels of each Luma brightness.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BrightnessHistogram {
+    /// The number of pixels for each brightness from 0 (darkest) to 255 (lightest).
+    counts: [u64; 256],
+}
+
+impl BrightnessHistogram {
+    /// Count the pixels of each brightness in a [GrayImage].
+    pub fn from_image(img: &GrayImage) -> BrightnessHistogram {
+        let mut counts = [0_u64; 256];
+        for p in img.pixels() {
+            counts[p[0] as usize] += 1;
+        }
+
+        BrightnessHistogram { counts }
+    }
+
+    /// Create the standard histogram of a well exposed photo, which can be matched to when no
+    /// reference image is at hand.
+    ///
+    /// The histogram is a bell curve centered on middle gray that reaches into the shadows and
+    /// the highlights without clipping.
+    pub fn photographic() -> BrightnessHistogram {
+        let mut counts = [0_u64; 256];
+        for (b, count) in counts.iter_mut().enumerate() {
+            let distance = (b as f32 - PHOTOGRAPHIC_MEAN) / PHOTOGRAPHIC_DEVIATION;
+            *count = (PHOTOGRAPHIC_PEAK * (-distance * distance / 2.0).exp()).round() as u64;
+        }
+
+        BrightnessHistogram { counts }
+    }
+
+    /// The number of pixels for each brightness from 0 (darkest) to 255 (lightest).
+    pub fn counts(&self) -> &[u64; 256] {
+        &self.counts
+    }
+
+    /// The total number of pixels counted.
+    pub fn total(&self) -> u64 {
+        self.counts.iter().sum()
+    }
+
+    /// The cumulative number of pixels with each brightness or darker.
+    pub fn cumulative(&self) -> [u64; 256] {
+        let mut total = 0;
+        self.counts.map(|count| {
+            total += count;
+            total
+        })
+    }
+
+    /// The mean brightness of the pixels, or [None] if no pixels were counted.
+    pub fn mean(&self) -> Option<f32> {
+        let total = self.total();
+        if total == 0 {
+            return None;
+        }
+
+        let sum: u64 = (0_u64..).zip(self.counts).map(|(b, c)| b * c).sum();
+        Some(sum as f32 / total as f32)
+    }
+
+    /// The darkest brightness that at least `fraction` (from 0.0 to 1.0) of the pixels are at
+    /// or below, or [None] if no pixels were counted.
+    pub fn percentile(&self, fraction: f32) -> Option<u8> {
+        let total = self.total();
+        if total == 0 {
+            return None;
+        }
+
+        let target = (fraction.clamp(0.0, 1.0) * total as f32).ceil().max(1.0) as u64;
+        (0_u8..=u8::MAX)
+            .zip(self.cumulative())
+            .find(|(_, count)| *count >= target)
+            .map(|(b, _)| b)
+    }
+
+    /// The number of brightness values covered by the middle 98% of the pixels.
+    ///
+    /// The darkest and lightest 1% of the pixels are ignored so that a few stray pixels do
+    /// not hide how washed out the rest of the image is.
+    pub fn dynamic_range(&self) -> u8 {
+        match (self.percentile(0.01), self.percentile(0.99)) {
+            (Some(low), Some(high)) => high - low,
+            _ => 0,
+        }
+    }
+
+    /// Map each brightness of this histogram onto the brightness of the `target` histogram that
+    /// the same share of pixels is darker than.
+    ///
+    /// Applying the mapping to the image this histogram was counted from gives it roughly the
+    /// histogram of the target. Each brightness is mapped from the middle of its own pixels, so
+    /// an image with only a few brightness levels has them spread out over the target rather
+    /// than pushed to its extremes. If either histogram has no pixels, every brightness is kept.
+    ///
+    /// The algorithm is the histogram matching described here:
+    /// <https://en.wikipedia.org/wiki/Histogram_matching>
+    pub fn matching_lookup(&self, target: &BrightnessHistogram) -> [u8; 256] {
+        let identity = std::array::from_fn(|b| b as u8);
+        let (total, target_total) = (self.total(), target.total());
+        if total == 0 || target_total == 0 {
+            return identity;
+        }
+
+        let target_cumulative = target.cumulative();
+        let mut lookup = identity;
+        for (b, cumulative) in self.cumulative().into_iter().enumerate() {
+            // the share of pixels darker than the middle of this brightness
+            let share = (cumulative as f64 - self.counts[b] as f64 / 2.0) / total as f64;
+            let position = target_cumulative
+                .partition_point(|count| (*count as f64 / target_total as f64) < share);
+            lookup[b] = position.min(u8::MAX as usize) as u8;
+        }
+
+        lookup
+    }
+
+    /// Change the brightness of every pixel of a [GrayImage] so its histogram matches the
+    /// `target` histogram.
+    ///
+    /// See [BrightnessHistogram::matching_lookup] for how the brightness is mapped.
+    pub fn match_image(img: &mut GrayImage, target: &BrightnessHistogram) {
+        let lookup = BrightnessHistogram::from_image(img).matching_lookup(target);
+        for p in img.pixels_mut() {
+            p[0] = lookup[p[0] as usize];
+        }
+    }
+
+    /// Suggest the [ContrastMode] that would make the image convert best.
+    ///
+    /// Low contrast images are equalized, images that are too dark or too light are gamma
+    /// corrected, and every other image is left as is.
+    pub fn suggested_contrast(&self) -> ContrastMode {
+        let mean = match self.mean() {
+            Some(mean) => mean,
+            None => return ContrastMode::Linear,
+        };
+
+        if self.dynamic_range() < LOW_CONTRAST_RANGE {
+            ContrastMode::Equalize
+        } else if mean < DARK_MEAN {
+            ContrastMode::Gamma(0.5)
+        } else if mean > LIGHT_MEAN {
+            ContrastMode::Gamma(2.0)
+        } else {
+            ContrastMode::Linear
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{ImageBuffer, Luma};
+
+    // Test that pixels are counted by brightness.
+    #[test]
+    fn test_from_image() {
+        let img = ImageBuffer::from_fn(4, 2, |x, _| Luma([x as u8 * 10]));
+        let histogram = BrightnessHistogram::from_image(&img);
+
+        assert_eq!(histogram.total(), 8);
+        assert_eq!(histogram.counts()[0], 2);
+        assert_eq!(histogram.counts()[30], 2);
+        assert_eq!(histogram.counts()[5], 0);
+        assert_eq!(histogram.cumulative()[10], 4);
+        assert_eq!(histogram.cumulative()[255], 8);
+    }
+
+    // Test the statistics of a histogram.
+    #[test]
+    fn test_statistics() {
+        let img = ImageBuffer::from_fn(100, 1, |x, _| Luma([x as u8]));
+        let histogram = BrightnessHistogram::from_image(&img);
+
+        assert_eq!(histogram.mean(), Some(49.5));
+        assert_eq!(histogram.percentile(0.0), Some(0));
+        assert_eq!(histogram.percentile(0.5), Some(49));
+        assert_eq!(histogram.percentile(1.0), Some(99));
+        assert_eq!(histogram.dynamic_range(), 98);
+    }
+
+    // Test that an empty histogram has no statistics.
+    #[test]
+    fn test_empty() {
+        let histogram = BrightnessHistogram::from_image(&GrayImage::new(0, 0));
+
+        assert_eq!(histogram.mean(), None);
+        assert_eq!(histogram.percentile(0.5), None);
+        assert_eq!(histogram.dynamic_range(), 0);
+        assert_eq!(histogram.suggested_contrast(), ContrastMode::Linear);
+    }
+
+    // Test that a contrast option is suggested for poorly exposed images.
+    #[test]
+    fn test_suggested_contrast() {
+        let suggest = |low: u8, span: u8| {
+            let img = ImageBuffer::from_fn(256, 1, |x, _| {
+                Luma([low + (x as f32 / 255.0 * span as f32) as u8])
+            });
+            BrightnessHistogram::from_image(&img).suggested_contrast()
+        };
+
+        assert_eq!(suggest(0, 255), ContrastMode::Linear);
+        assert_eq!(suggest(100, 40), ContrastMode::Equalize);
+        assert_eq!(suggest(0, 110), ContrastMode::Gamma(0.5));
+        assert_eq!(suggest(145, 110), ContrastMode::Gamma(2.0));
+    }
+
+    // Test that the standard photographic curve is centered on middle gray and well exposed.
+    #[test]
+    fn test_photographic() {
+        let histogram = BrightnessHistogram::photographic();
+
+        assert_eq!(histogram.percentile(0.5), Some(128));
+        assert!(histogram.counts()[0] > 0);
+        assert!(histogram.counts()[128] > histogram.counts()[64]);
+        assert_eq!(histogram.suggested_contrast(), ContrastMode::Linear);
+    }
+
+    // Test that an image takes on the histogram it is matched to.
+    #[test]
+    fn test_match_image() {
+        // a poster with only three brightness levels
+        let mut img = ImageBuffer::from_fn(300, 1, |x, _| Luma([[0, 100, 255][x as usize / 100]]));
+        let target = ImageBuffer::from_fn(256, 1, |x, _| Luma([x as u8]));
+        BrightnessHistogram::match_image(&mut img, &BrightnessHistogram::from_image(&target));
+
+        // each level lands in the middle of its share of the target
+        assert_eq!(img.get_pixel(0, 0)[0], 42);
+        assert_eq!(img.get_pixel(150, 0)[0], 127);
+        assert_eq!(img.get_pixel(299, 0)[0], 213);
+
+        // nothing changes when there is no target
+        let lookup = BrightnessHistogram::from_image(&img)
+            .matching_lookup(&BrightnessHistogram::from_image(&GrayImage::new(0, 0)));
+        assert!((0..=u8::MAX).all(|b| lookup[b as usize] == b));
+    }
+}
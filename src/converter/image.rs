@@ -1,13 +1,70 @@
 //! Image to ASCII converter.
 //!
-//! This module is responsible for converting images to ASCII. It uses the [image] crate to
-//! read the PNG.
+//! This module is responsible for converting images to ASCII. It reads the image through an
+//! [ImageCodec], which uses the [image] crate by default, and the image can be any of the
+//! [SUPPORTED_FORMATS]. Images can also be rendered
+//! with braille characters or colored half blocks instead of ASCII by selecting a
+//! [RenderCharset] in the [ConvertOptions]. The half block path works on the RGB pixels of
+//! the image rather than the Luma symbol map. [Image::convert_to_ascii_writer] writes the text
+//! line by line, and decodes and scales PNG images one row at a time, so very large images can
+//! be converted without holding them in memory. [Image::convert_to_ascii_with_progress] reports
+//! how many lines have been converted, so long conversions can show their progress.
+//! [Image::prepare] decodes an image once into a [PreparedImage], which converts it again with
+//! new options without decoding it each time. Photos are turned upright with the orientation in
+//! their EXIF metadata, which is read with the kamadak-exif crate, unless the options ignore it.
 //!
 //! Robert Peterson and Kelsey Werner 2023
 
-use crate::converter::{dimension::Dimension, symbol_map::symbol_for_brightness, ConvertError};
-use image::{imageops, io::Reader, ImageBuffer, Luma};
-use std::io::{BufRead, Read, Seek};
+use crate::converter::{
+    ascii::render_png,
+    codec::{default_codec, ImageCodec},
+    dimension::Dimension,
+    dither::dither,
+    histogram::BrightnessHistogram,
+    metadata::with_metadata,
+    options::{
+        ContrastMode, ConvertOptions, RenderCharset, MAX_BRIGHTNESS, MAX_CONTRAST_ADJUSTMENT,
+    },
+    pipeline::{Flip, Pipeline, Rotation, Stage},
+    resize::RowResizer,
+    ruler::{add_rulers, gutter_width, GUTTER_SEPARATOR, RULER_LINES},
+    strategy::{Cell, SymbolStrategy},
+    usage::{ResourceUsage, Stopwatch},
+    warning::ConversionWarning,
+    ConvertError, ErrorSource,
+};
+use image::{
+    codecs::png::PngDecoder, imageops, ColorType, DynamicImage, GrayImage, ImageBuffer,
+    ImageDecoder, ImageFormat, Pixel, Rgb, RgbImage,
+};
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+use std::{
+    borrow::Cow,
+    fmt::Write,
+    io::{self, BufRead, Cursor, Read, Seek, SeekFrom},
+    sync::{
+        atomic::{AtomicU32, Ordering},
+        OnceLock,
+    },
+};
+
+/// The image formats that the converter is tested against.
+///
+/// The [image] crate can guess the format of each of these from the header of the file.
+pub const SUPPORTED_FORMATS: [ImageFormat; 5] = [
+    ImageFormat::Jpeg,
+    ImageFormat::Png,
+    ImageFormat::WebP,
+    ImageFormat::Bmp,
+    ImageFormat::Tiff,
+];
+
+/// A callback that is told how many rows of text have been converted, out of the total number of rows.
+///
+/// With the `parallel` feature, rows are converted on the rayon thread pool, so the callback may be called from several
+/// threads and the counts may arrive slightly out of order.
+pub type ProgressCallback<'a> = &'a (dyn Fn(u32, u32) + Sync);
 
 /// The max image size in pixels.
 ///
@@ -15,170 +72,3017 @@ use std::io::{BufRead, Read, Seek};
 /// than 200x200 characters.
 const MAX_ASCII_DIMENSION: u32 = 200;
 
-/// Convert a [ImageBuffer] into an ASCII [String].
-///
-/// ASCII is generated by looking at each pixel of the image. To generate ASCII of reasonable
-/// size, the image is first scaled down if it is too big. The [image] crate is used to scale
-/// the image and to iterate over each pixel of the image.
-fn convert_image_to_ascii(img: ImageBuffer<Luma<u8>, Vec<u8>>) -> String {
-    let mut dimension = Dimension::from(img.dimensions());
+/// The number of pixel columns packed into a single braille character.
+const BRAILLE_CELL_WIDTH: u32 = 2;
+
+/// The number of pixel rows packed into a single braille character.
+const BRAILLE_CELL_HEIGHT: u32 = 4;
+
+/// The first character of the braille Unicode block, which has no raised dots.
+const BRAILLE_BLANK: u32 = 0x2800;
+
+/// Pixels darker than this Luma brightness raise a dot in a braille character.
+const BRAILLE_THRESHOLD: u8 = 128;
+
+/// The bit for each dot of a braille character, indexed by `[y][x]` within the 2x4 cell.
+///
+/// The dot numbering of the Unicode braille block is described here:
+/// <https://en.wikipedia.org/wiki/Braille_Patterns>
+const BRAILLE_DOTS: [[u32; 2]; 4] = [[0x01, 0x08], [0x02, 0x10], [0x04, 0x20], [0x40, 0x80]];
+
+/// The most bytes a single colored half block can take up.
+///
+/// This is two 24-bit ANSI color escape sequences (`\x1b[38;2;255;255;255m` is 19 bytes)
+/// followed by the 3 byte `▀` character.
+const MAX_HALF_BLOCK_BYTES: usize = 19 * 2 + 3;
+
+/// Scale the [Dimension] of an image to the size in pixels it is resized to before it is
+/// turned into text.
+///
+/// When a width is given in the [ConvertOptions], the image is scaled so the output is exactly
+/// that many characters wide. Otherwise the image is scaled down if it is too big. Braille
+/// characters cover two pixels per character, so braille images are scaled twice as wide.
+/// When a budget of characters or a height is given, the image is scaled down further until the
+/// text fits.
+fn scaled_dimension(dimension: Dimension, options: &ConvertOptions) -> Dimension {
+    let pixels_per_character = match options.charset {
+        RenderCharset::Braille => BRAILLE_CELL_WIDTH,
+        RenderCharset::Ascii | RenderCharset::HalfBlock => 1,
+    };
+    let original = (dimension.width, dimension.height);
+
+    let mut dimension = dimension;
+    match options.width {
+        Some(w) => dimension.scale_to_width(w * pixels_per_character),
+        None => dimension.scale_down(MAX_ASCII_DIMENSION * pixels_per_character),
+    }
+
+    let width = dimension.width.div_ceil(pixels_per_character).max(1);
+    let mut fitting = width;
+    if let Some(budget) = options.max_characters {
+        fitting = fitting_width(options, fitting, |options| {
+            estimate_characters(Dimension::from(original), options) <= budget as usize
+        });
+    }
+    if let Some(lines) = options.height {
+        fitting = fitting_width(options, fitting, |options| {
+            estimate_size(Dimension::from(original), options).rows <= lines
+        });
+    }
+    if fitting < width {
+        dimension = Dimension::from(original);
+        dimension.scale_to_width(fitting * pixels_per_character);
+    }
+
+    dimension
+}
+
+/// Find the largest width, from 1 up to `max_width` characters, whose text `fits` when the image
+/// is converted with the [ConvertOptions] at that width.
+///
+/// The text grows with the width, so the widths are binary searched. If not even a single
+/// character per line fits, the text is one character wide.
+fn fitting_width<F>(options: &ConvertOptions, max_width: u32, fits: F) -> u32
+where
+    F: Fn(&ConvertOptions) -> bool,
+{
+    let fits = |width: u32| {
+        fits(&ConvertOptions {
+            width: Some(width),
+            height: None,
+            max_characters: None,
+            ..options.clone()
+        })
+    };
+
+    let (mut low, mut high) = (1, max_width);
+    while low < high {
+        let middle = low + (high - low).div_ceil(2);
+        if fits(middle) {
+            low = middle;
+        } else {
+            high = middle - 1;
+        }
+    }
+
+    low
+}
+
+/// List the [ConversionWarning]s about how an image of the given [Dimension] is scaled when it is
+/// converted with the [ConvertOptions].
+fn scaling_warnings(dimension: Dimension, options: &ConvertOptions) -> Vec<ConversionWarning> {
+    let original = (dimension.width, dimension.height);
+    let unbudgeted = ConvertOptions {
+        max_characters: None,
+        ..options.clone()
+    };
+    let columns = estimate_size(Dimension::from(original), &unbudgeted).columns;
+
+    let mut warnings = Vec::new();
+    if options.width.is_none()
+        && scaled_dimension(Dimension::from(original), &unbudgeted) != dimension
+    {
+        warnings.push(ConversionWarning::ImageDownscaled {
+            from: dimension.width,
+            to: columns,
+        });
+    }
+
+    if let Some(budget) = options.max_characters {
+        let width = estimate_size(dimension, options).columns;
+        if width < columns {
+            warnings.push(ConversionWarning::WidthReducedToBudget { budget, width });
+        }
+    }
+
+    warnings
+}
+
+/// Predict the most characters the text an image of the given [Dimension] converts into can
+/// have, counting line breaks.
+///
+/// Every symbol and braille character is a single character, while each colored half block
+/// also counts the characters of its color escape sequences.
+fn estimate_characters(dimension: Dimension, options: &ConvertOptions) -> usize {
+    let estimate = estimate_size(dimension, options);
+    let (characters_per_column, characters_per_row) = match options.charset {
+        // the `▀` character is 3 bytes but a single character
+        RenderCharset::HalfBlock => (MAX_HALF_BLOCK_BYTES - 2, ANSI_RESET.len() + 1),
+        RenderCharset::Ascii | RenderCharset::Braille => (1, 1),
+    };
+
+    estimate.rows as usize
+        * (estimate.columns as usize * characters_per_column + characters_per_row)
+}
+
+/// Predict the [SizeEstimate] of the text an image of the given [Dimension] converts into.
+///
+/// The estimate follows the same scaling as the conversion itself, so the number of columns
+/// and rows is exact. The number of bytes is an upper bound, since symbols and color escape
+/// sequences can have different lengths.
+fn estimate_size(dimension: Dimension, options: &ConvertOptions) -> SizeEstimate {
+    let scaled = scaled_dimension(dimension, options);
+
+    let (columns, rows, bytes_per_character, bytes_per_row) = match options.charset {
+        RenderCharset::Ascii => (
+            scaled.width,
+            ascii_rows(scaled.height, options.cell_aspect()),
+            options.symbol_map.max_symbol_bytes(),
+            1,
+        ),
+        RenderCharset::Braille => (
+            scaled.width.div_ceil(BRAILLE_CELL_WIDTH),
+            scaled.height.div_ceil(BRAILLE_CELL_HEIGHT),
+            3,
+            1,
+        ),
+        RenderCharset::HalfBlock => (
+            scaled.width,
+            scaled.height.div_ceil(2),
+            MAX_HALF_BLOCK_BYTES,
+            ANSI_RESET.len() + 1,
+        ),
+    };
+
+    let bytes = rows as usize * (columns as usize * bytes_per_character + bytes_per_row);
+
+    if !options.rulers {
+        return SizeEstimate {
+            columns,
+            rows,
+            bytes,
+        };
+    }
+
+    // the rulers add a gutter in front of every line and a few lines above the text
+    let gutter = (gutter_width(rows as usize) + GUTTER_SEPARATOR.len()) as u32;
+    let ruled_rows = rows + RULER_LINES as u32;
+    SizeEstimate {
+        columns: columns + gutter,
+        rows: ruled_rows,
+        bytes: bytes + (ruled_rows * gutter) as usize + RULER_LINES * (columns as usize + 1),
+    }
+}
+
+/// Adjust the brightness and contrast of a [GrayImage] in place by the amounts of the
+/// [ConvertOptions].
+///
+/// The brightness is added to each pixel first, and then the pixels are moved away from (or
+/// toward) middle gray by the contrast adjustment.
+fn adjust_brightness_contrast(img: &mut GrayImage, brightness: i16, contrast_adjustment: f32) {
+    if brightness == 0 && contrast_adjustment == 0.0 {
+        return;
+    }
+
+    let max = u8::MAX as f32;
+    let factor = ((100.0 + contrast_adjustment) / 100.0).powi(2);
+    let lookup: [u8; 256] = std::array::from_fn(|b| {
+        let brightened = (b as i16 + brightness).clamp(0, u8::MAX as i16) as f32 / max;
+        (((brightened - 0.5) * factor + 0.5) * max)
+            .round()
+            .clamp(0.0, max) as u8
+    });
+
+    for p in img.pixels_mut() {
+        p[0] = lookup[p[0] as usize];
+    }
+}
+
+/// The threshold used by [ContrastMode::Threshold] for images that [otsu_threshold] can't split.
+const OTSU_FALLBACK_THRESHOLD: u8 = 128;
+
+/// Adjust the brightness of a [GrayImage] in place using the given [ContrastMode].
+fn adjust_contrast(img: &mut GrayImage, mode: ContrastMode) {
+    let lookup = match mode {
+        ContrastMode::Linear => return,
+        ContrastMode::Gamma(gamma) => gamma_lookup(gamma),
+        ContrastMode::Equalize => equalize_lookup(img),
+        ContrastMode::Threshold(threshold) => {
+            threshold_lookup(threshold.unwrap_or_else(|| otsu_threshold(img)))
+        }
+    };
+
+    for p in img.pixels_mut() {
+        p[0] = lookup[p[0] as usize];
+    }
+}
+
+/// Build a table that maps each brightness to its gamma corrected brightness.
+fn gamma_lookup(gamma: f32) -> [u8; 256] {
+    let max = u8::MAX as f32;
+    std::array::from_fn(|b| ((b as f32 / max).powf(gamma) * max).round() as u8)
+}
+
+/// Build a table that maps each brightness of a [GrayImage] to its equalized brightness.
+///
+/// Each brightness is mapped to its position in the cumulative histogram of the image, so
+/// that every brightness ends up used by about the same number of pixels. The formula was
+/// found here: <https://en.wikipedia.org/wiki/Histogram_equalization>
+fn equalize_lookup(img: &GrayImage) -> [u8; 256] {
+    let cdf = BrightnessHistogram::from_image(img).cumulative();
+    let total = cdf[255];
+
+    // the number of pixels that have the darkest brightness in the image
+    let cdf_min = cdf.iter().copied().find(|c| *c > 0).unwrap_or(0);
+
+    std::array::from_fn(|b| {
+        // an image with a single brightness has no contrast to stretch
+        if total == cdf_min {
+            b as u8
+        } else {
+            let scaled = cdf[b].saturating_sub(cdf_min) as f32 / (total - cdf_min) as f32;
+            (scaled * u8::MAX as f32).round() as u8
+        }
+    })
+}
+
+/// Build a table that maps each brightness below `threshold` to black and the rest to white.
+fn threshold_lookup(threshold: u8) -> [u8; 256] {
+    std::array::from_fn(|b| if b < threshold as usize { 0 } else { u8::MAX })
+}
+
+/// Pick the threshold that splits the pixels of a [GrayImage] into a dark and a light group
+/// whose brightnesses are the most different.
+///
+/// This is Otsu's method, which picks the split with the largest variance between the
+/// groups: <https://en.wikipedia.org/wiki/Otsu%27s_method>. Images with a single brightness
+/// can't be split, and are thresholded at the middle of the brightness range.
+fn otsu_threshold(img: &GrayImage) -> u8 {
+    let histogram = BrightnessHistogram::from_image(img);
+    let counts = histogram.counts();
+    let total = histogram.total() as f64;
+    let brightness_sum: f64 = counts
+        .iter()
+        .enumerate()
+        .map(|(b, count)| b as f64 * *count as f64)
+        .sum();
+
+    let (mut dark_count, mut dark_sum) = (0.0, 0.0);
+    let (mut best_threshold, mut best_variance) = (OTSU_FALLBACK_THRESHOLD, 0.0);
+    for (b, count) in counts.iter().enumerate() {
+        dark_count += *count as f64;
+        dark_sum += b as f64 * *count as f64;
+        let light_count = total - dark_count;
+        if dark_count == 0.0 || light_count == 0.0 {
+            continue;
+        }
+
+        let mean_difference = dark_sum / dark_count - (brightness_sum - dark_sum) / light_count;
+        let variance = dark_count * light_count * mean_difference * mean_difference;
+        if variance > best_variance {
+            // the dark group is every brightness up to b, so the light group starts right after it
+            (best_threshold, best_variance) = (b as u8 + 1, variance);
+        }
+    }
+
+    best_threshold
+}
+
+/// Adjust the contrast of a scaled [GrayImage] in place and dither it.
+///
+/// The brightness and contrast adjustment of the [ConvertOptions] are applied first, then the
+/// contrast and dither stages of the [Pipeline] in order, followed by the contrast and dither of
+/// the [ConvertOptions]. `quantize` and `step` describe the levels the
+/// pixels are dithered to, as in [dither]. When `dithered` is false, only the contrast is
+/// adjusted.
+///
+/// [Pipeline]: crate::converter::pipeline::Pipeline
+fn adjust_tone<F>(
+    img: &mut GrayImage,
+    options: &ConvertOptions,
+    step: f32,
+    quantize: F,
+    dithered: bool,
+) where
+    F: Fn(u8) -> u8,
+{
+    adjust_brightness_contrast(img, options.brightness, options.contrast_adjustment);
+
+    let stages = options.pipeline.pixel_stages().chain([
+        Stage::Contrast(options.contrast),
+        Stage::Dither(options.dither),
+    ]);
+
+    for stage in stages {
+        match stage {
+            Stage::Contrast(mode) => adjust_contrast(img, mode),
+            Stage::Dither(mode) if dithered => dither(img, mode, step, &quantize),
+            _ => {}
+        }
+    }
+}
+
+/// Scale a [GrayImage] so it has one pixel per character of the [RenderCharset].
+///
+/// When a width is given in the [ConvertOptions], the image is scaled so the text is exactly
+/// that many characters wide. Otherwise, to generate text of reasonable size, the image is
+/// scaled down if it is too big. The [image] crate is used to scale the image. For
+/// [RenderCharset::Ascii], a row of pixels is sampled for each line of text by [sample_rows].
+///
+/// Images that are already the right size, such as the grayscale PNGs this crate creates from
+/// ASCII, are sampled straight from `img` without being copied by the [image] crate first.
+fn scaled_luma(img: &GrayImage, options: &ConvertOptions) -> GrayImage {
+    let dimension = scaled_dimension(Dimension::from(img.dimensions()), options);
+
+    if (dimension.width, dimension.height) == img.dimensions() {
+        return match options.charset {
+            RenderCharset::Ascii => sample_rows(img, options.cell_aspect()),
+            RenderCharset::Braille | RenderCharset::HalfBlock => img.clone(),
+        };
+    }
+
+    let newimg = imageops::resize(
+        img,
+        dimension.width,
+        dimension.height,
+        imageops::FilterType::Triangle,
+    );
+
+    match options.charset {
+        RenderCharset::Ascii => sample_rows(&newimg, options.cell_aspect()),
+        RenderCharset::Braille | RenderCharset::HalfBlock => newimg,
+    }
+}
+
+/// Scale, sample and dither a [GrayImage] into one brightness per ASCII symbol.
+///
+/// The image is scaled by [scaled_luma], and then the contrast of the pixels is adjusted and
+/// they are dithered using the [SymbolMap] of the options by [adjust_tone].
+///
+/// [SymbolMap]: crate::converter::symbol_map::SymbolMap
+fn sample_ascii_brightness(img: &GrayImage, options: &ConvertOptions) -> GrayImage {
+    adjust_ascii_tone(scaled_luma(img, options), options)
+}
+
+/// Adjust the contrast of a scaled and sampled [GrayImage] and dither it to the levels of the
+/// [SymbolMap] of the options.
+///
+/// [SymbolMap]: crate::converter::symbol_map::SymbolMap
+fn adjust_ascii_tone(mut sampled: GrayImage, options: &ConvertOptions) -> GrayImage {
+    let symbol_map = &options.symbol_map;
+    adjust_tone(
+        &mut sampled,
+        options,
+        symbol_map.brightness_step(),
+        |b| symbol_map.quantize(b),
+        true,
+    );
+
+    sampled
+}
+
+/// Count the lines of ASCII that cover `height` rows of pixels when each character cell is
+/// `cell_aspect` times as wide as it is tall.
+fn ascii_rows(height: u32, cell_aspect: f32) -> u32 {
+    (height as f32 * cell_aspect) as u32
+}
+
+/// Sample a row of pixels for each line of ASCII to account for typefaces making the ascii
+/// taller than wider in relation to the original image, so with the [DEFAULT_CELL_ASPECT]
+/// vertical pixels are skipped half the time: <http://paulbourke.net/dataformats/asciiart/>
+///
+/// The row in the middle of the pixels covered by each line is sampled.
+///
+/// [DEFAULT_CELL_ASPECT]: crate::converter::options::DEFAULT_CELL_ASPECT
+fn sample_rows<P: Pixel>(
+    img: &ImageBuffer<P, Vec<P::Subpixel>>,
+    cell_aspect: f32,
+) -> ImageBuffer<P, Vec<P::Subpixel>> {
+    let last_row = img.height().saturating_sub(1);
+    ImageBuffer::from_fn(
+        img.width(),
+        ascii_rows(img.height(), cell_aspect),
+        |x, y| {
+            let row = ((y as f32 + 0.5) / cell_aspect) as u32;
+            *img.get_pixel(x, row.min(last_row))
+        },
+    )
+}
+
+/// Scale a [GrayImage] so each ASCII symbol covers a cell of `cell_width` by `cell_height` pixels.
+///
+/// The ASCII has the same number of columns and rows as it would with one pixel per symbol, so
+/// the image is scaled straight to that size instead of skipping rows. The contrast of the
+/// pixels is adjusted, but they are not dithered since the levels of the [SymbolMap] do not
+/// apply to cells.
+///
+/// [SymbolMap]: crate::converter::symbol_map::SymbolMap
+fn sample_ascii_cells(
+    img: &GrayImage,
+    options: &ConvertOptions,
+    cell_width: u32,
+    cell_height: u32,
+) -> GrayImage {
+    let dimension = scaled_dimension(Dimension::from(img.dimensions()), options);
+
+    let mut sampled = imageops::resize(
+        img,
+        dimension.width * cell_width,
+        ascii_rows(dimension.height, options.cell_aspect()) * cell_height,
+        imageops::FilterType::Triangle,
+    );
+    adjust_tone(&mut sampled, options, 1.0, |b| b, false);
+
+    sampled
+}
+
+/// Convert a [ImageBuffer] into an ASCII [String].
+///
+/// ASCII is generated by looking at each cell of pixels of the image. When the
+/// [SymbolStrategy] covers a single pixel per symbol, the image is scaled and dithered by
+/// [sample_ascii_brightness]. Otherwise the image is scaled by [sample_ascii_cells]. Then
+/// each cell is turned into a symbol by the [SymbolStrategy]. With the `parallel` feature, the
+/// rows of cells are turned into lines on the rayon thread pool. The progress callback is called
+/// after each line.
+fn convert_image_to_ascii<S>(
+    img: &GrayImage,
+    options: &ConvertOptions,
+    strategy: &S,
+    progress: ProgressCallback,
+) -> String
+where
+    S: SymbolStrategy + ?Sized,
+{
+    let (cell_width, cell_height) = strategy.cell_size();
+    let (cell_width, cell_height) = (cell_width.max(1), cell_height.max(1));
+
+    let sampled = if (cell_width, cell_height) == (1, 1) {
+        sample_ascii_brightness(img, options)
+    } else {
+        sample_ascii_cells(img, options, cell_width, cell_height)
+    };
+
+    // generate ascii, one line per row of cells
+    let total = sampled.height() / cell_height;
+    let done = AtomicU32::new(0);
+    #[cfg(feature = "parallel")]
+    let rows = (0..total).into_par_iter();
+    #[cfg(not(feature = "parallel"))]
+    let rows = 0..total;
+
+    rows.map(|row| {
+        let mut line = String::new();
+        push_ascii_line(
+            &mut line,
+            &sampled,
+            row,
+            (cell_width, cell_height),
+            strategy,
+        );
+        progress(done.fetch_add(1, Ordering::Relaxed) + 1, total);
+        line
+    })
+    .collect()
+}
+
+/// Push the symbols of one row of cells of a sampled [GrayImage] onto `line`, followed by a
+/// newline.
+fn push_ascii_line<S>(
+    line: &mut String,
+    sampled: &GrayImage,
+    row: u32,
+    (cell_width, cell_height): (u32, u32),
+    strategy: &S,
+) where
+    S: SymbolStrategy + ?Sized,
+{
+    for column in 0..sampled.width() / cell_width {
+        let cell = Cell::new(
+            sampled,
+            column * cell_width,
+            row * cell_height,
+            cell_width,
+            cell_height,
+        );
+        line.push(strategy.symbol_for_cell(&cell));
+    }
+    line.push('\n');
+}
+
+/// Decode a PNG one row at a time, scaling it for [RenderCharset::Ascii] as the rows are read.
+///
+/// Each row is converted to Luma exactly like [DynamicImage::into_luma8] and pushed into a
+/// [RowResizer], so the scaled image is identical to the one [scaled_luma] creates, but the
+/// whole decoded image is never held in memory. Interlaced PNGs can't be read in order, so the
+/// [image] crate decodes them whole. Rows of grayscale PNGs are used as they are read, and rows
+/// of PNGs that don't need to be scaled skip the [RowResizer]. [ConvertError::DecodeError] is
+/// returned if the PNG can't be decoded.
+fn stream_scaled_png<R: Read>(
+    reader: R,
+    options: &ConvertOptions,
+) -> Result<GrayImage, ConvertError> {
+    let decoder =
+        PngDecoder::new(reader).map_err(|e| ConvertError::DecodeError(ErrorSource::new(e)))?;
+    let (width, height) = decoder.dimensions();
+    let color = decoder.color_type();
+    let dimension = scaled_dimension(Dimension::from((width, height)), options);
+    let unscaled = (dimension.width, dimension.height) == (width, height);
+
+    let mut resizer = RowResizer::new(width, height, dimension.width, dimension.height);
+    let mut pixels = Vec::with_capacity(dimension.width as usize * dimension.height as usize);
+    let mut row = vec![0; color.bytes_per_pixel() as usize * width as usize];
+
+    let mut rows = decoder
+        .into_reader()
+        .map_err(|e| ConvertError::DecodeError(ErrorSource::new(e)))?;
+    for _ in 0..height {
+        rows.read_exact(&mut row)
+            .map_err(|e| ConvertError::DecodeError(ErrorSource::new(e)))?;
+        let luma = png_row_to_luma(color, width, &row)?;
+        if unscaled {
+            pixels.extend_from_slice(&luma);
+            continue;
+        }
+        for line in resizer.push_row(&luma) {
+            pixels.extend(line);
+        }
+    }
+
+    let scaled = GrayImage::from_raw(dimension.width, dimension.height, pixels)
+        .ok_or_else(|| ConvertError::DecodeError(ErrorSource::message("the PNG ended early")))?;
+    Ok(sample_rows(&scaled, options.cell_aspect()))
+}
+
+/// Convert a single row of pixels read from a PNG into Luma.
+///
+/// Rows of [ColorType::L8] pixels are already Luma, so they are borrowed instead of converted.
+/// PNGs store 16-bit channels as big endian. [ConvertError::DecodeError] is returned for a
+/// [ColorType] that PNGs can't have.
+fn png_row_to_luma(
+    color: ColorType,
+    width: u32,
+    row: &[u8],
+) -> Result<Cow<'_, [u8]>, ConvertError> {
+    let wide = || -> Vec<u16> {
+        row.chunks_exact(2)
+            .map(|c| u16::from_be_bytes([c[0], c[1]]))
+            .collect()
+    };
+
+    let img = match color {
+        ColorType::L8 => return Ok(Cow::Borrowed(row)),
+        ColorType::La8 => {
+            ImageBuffer::from_raw(width, 1, row.to_vec()).map(DynamicImage::ImageLumaA8)
+        }
+        ColorType::Rgb8 => {
+            ImageBuffer::from_raw(width, 1, row.to_vec()).map(DynamicImage::ImageRgb8)
+        }
+        ColorType::Rgba8 => {
+            ImageBuffer::from_raw(width, 1, row.to_vec()).map(DynamicImage::ImageRgba8)
+        }
+        ColorType::L16 => ImageBuffer::from_raw(width, 1, wide()).map(DynamicImage::ImageLuma16),
+        ColorType::La16 => ImageBuffer::from_raw(width, 1, wide()).map(DynamicImage::ImageLumaA16),
+        ColorType::Rgb16 => ImageBuffer::from_raw(width, 1, wide()).map(DynamicImage::ImageRgb16),
+        ColorType::Rgba16 => ImageBuffer::from_raw(width, 1, wide()).map(DynamicImage::ImageRgba16),
+        _ => None,
+    };
+
+    img.map(|img| Cow::Owned(img.into_luma8().into_raw()))
+        .ok_or_else(|| {
+            ConvertError::DecodeError(ErrorSource::message("the PNG has an unknown color type"))
+        })
+}
+
+/// Convert a [DynamicImage] into a PNG that looks like its ASCII art.
+///
+/// The image is sampled exactly like [convert_image_to_ascii], but instead of writing a symbol
+/// for each pixel, the pixel is given the brightness that the symbol maps back to. This is the
+/// same image that converting the ASCII back with [Ascii] in [ImageRenderMode::Pixels] would
+/// create, without having to write and read the ASCII in between. When `colorize` is true, each pixel keeps the color of
+/// the original image, darkened to the brightness of its symbol.
+///
+/// [Ascii]: crate::converter::ascii::Ascii
+/// [ImageRenderMode::Pixels]: crate::converter::options::ImageRenderMode::Pixels
+fn convert_image_to_ascii_image(
+    img: DynamicImage,
+    options: &ConvertOptions,
+    colorize: bool,
+    codec: &dyn ImageCodec,
+) -> Result<Cursor<Vec<u8>>, ConvertError> {
+    let symbol_map = &options.symbol_map;
+    // grayscale images are sampled without being copied into a new buffer
+    let luma = match img.as_luma8() {
+        Some(luma) => Cow::Borrowed(luma),
+        None => Cow::Owned(img.to_luma8()),
+    };
+    let mut sampled = sample_ascii_brightness(&luma, options);
+    for p in sampled.pixels_mut() {
+        p[0] = symbol_map.quantize(p[0]);
+    }
+
+    if !colorize {
+        return render_png(DynamicImage::ImageLuma8(sampled), 1, codec);
+    }
+
+    let colors = sample_ascii_colors(&img, options);
+    let colored = RgbImage::from_fn(sampled.width(), sampled.height(), |x, y| {
+        let brightness = sampled.get_pixel(x, y)[0] as u16;
+        let Rgb(color) = *colors.get_pixel(x, y);
+        Rgb(color.map(|c| (c as u16 * brightness / 255) as u8))
+    });
+
+    render_png(DynamicImage::ImageRgb8(colored), 1, codec)
+}
+
+/// Scale and sample the colors of a [DynamicImage] the same way [sample_ascii_brightness] scales
+/// and samples its brightness, so there is one color for each ASCII symbol.
+fn sample_ascii_colors(img: &DynamicImage, options: &ConvertOptions) -> RgbImage {
+    let dimension = scaled_dimension(Dimension::from((img.width(), img.height())), options);
+    let colors = imageops::resize(
+        &img.to_rgb8(),
+        dimension.width,
+        dimension.height,
+        imageops::FilterType::Triangle,
+    );
+    sample_rows(&colors, options.cell_aspect())
+}
+
+/// Convert a [DynamicImage] into ASCII art wrapped in an HTML `<pre>` element.
+///
+/// The symbols are picked exactly like [convert_image_to_ascii] picks them with the [SymbolMap]
+/// of the options, and are escaped so that any symbol can be put in a page. When `colorize` is
+/// true, each run of symbols of the same color is wrapped in a `<span style="color:#rrggbb">`
+/// with the color of the original image under it.
+///
+/// [SymbolMap]: crate::converter::symbol_map::SymbolMap
+fn convert_image_to_ascii_html(
+    img: DynamicImage,
+    options: &ConvertOptions,
+    colorize: bool,
+) -> String {
+    let symbol_map = &options.symbol_map;
+    let luma = match img.as_luma8() {
+        Some(luma) => Cow::Borrowed(luma),
+        None => Cow::Owned(img.to_luma8()),
+    };
+    let sampled = sample_ascii_brightness(&luma, options);
+    let colors = colorize.then(|| sample_ascii_colors(&img, options));
+
+    let mut html = String::from("<pre>");
+    for y in 0..sampled.height() {
+        let mut span_color = None;
+        for x in 0..sampled.width() {
+            if let Some(colors) = &colors {
+                let color = *colors.get_pixel(x, y);
+                if span_color != Some(color) {
+                    if span_color.is_some() {
+                        html.push_str("</span>");
+                    }
+                    let Rgb([r, g, b]) = color;
+                    // writing to a String can't fail
+                    let _ = write!(html, "<span style=\"color:#{:02x}{:02x}{:02x}\">", r, g, b);
+                    span_color = Some(color);
+                }
+            }
+            push_html_escaped(
+                &mut html,
+                symbol_map.symbol_for_brightness(sampled.get_pixel(x, y)[0]),
+            );
+        }
+        if span_color.is_some() {
+            html.push_str("</span>");
+        }
+        html.push('\n');
+    }
+    html.push_str("</pre>\n");
+
+    html
+}
+
+/// Push a symbol onto `html`, escaping the characters that HTML gives a meaning to.
+fn push_html_escaped(html: &mut String, symbol: char) {
+    match symbol {
+        '<' => html.push_str("&lt;"),
+        '>' => html.push_str("&gt;"),
+        '&' => html.push_str("&amp;"),
+        '"' => html.push_str("&quot;"),
+        '\'' => html.push_str("&#39;"),
+        _ => html.push(symbol),
+    }
+}
+
+/// Convert a [ImageBuffer] into a braille [String].
+///
+/// Each braille character covers a 2x4 block of pixels, and a dot is raised for every dark
+/// pixel in the block. Since a braille cell is about twice as tall as it is wide, the dots are
+/// roughly square and no vertical pixels need to be skipped. When a width is given in the
+/// [ConvertOptions], the image is scaled so the output is exactly that many characters wide.
+/// The contrast of the pixels is adjusted and they are dithered to black and white before
+/// the dots are raised. The progress callback is called after each line.
+fn convert_image_to_braille(
+    img: &GrayImage,
+    options: &ConvertOptions,
+    progress: ProgressCallback,
+) -> String {
+    let dimension = scaled_dimension(Dimension::from(img.dimensions()), options);
+    let (width, height) = (dimension.width, dimension.height);
+
+    let mut newimg = imageops::resize(img, width, height, imageops::FilterType::Triangle);
+
+    adjust_tone(
+        &mut newimg,
+        options,
+        u8::MAX as f32,
+        |b| if b < BRAILLE_THRESHOLD { 0 } else { u8::MAX },
+        true,
+    );
+
+    let columns = width.div_ceil(BRAILLE_CELL_WIDTH);
+    let rows = height.div_ceil(BRAILLE_CELL_HEIGHT);
+
+    let mut braille: String = Default::default();
+
+    for row in 0..rows {
+        for column in 0..columns {
+            let mut dots = 0;
+            for (dy, row_dots) in (0_u32..).zip(BRAILLE_DOTS) {
+                for (dx, dot) in (0_u32..).zip(row_dots) {
+                    let x = column * BRAILLE_CELL_WIDTH + dx;
+                    let y = row * BRAILLE_CELL_HEIGHT + dy;
+                    // pixels past the edge of the image are treated as blank
+                    if x < width && y < height && newimg.get_pixel(x, y)[0] < BRAILLE_THRESHOLD {
+                        dots |= dot;
+                    }
+                }
+            }
+            // every value in 0x2800..=0x28FF is a valid braille character
+            braille.push(char::from_u32(BRAILLE_BLANK + dots).unwrap_or(' '));
+        }
+        braille.push('\n');
+        progress(row + 1, rows);
+    }
+
+    braille
+}
+
+/// The upper half block character. Its foreground color paints the top pixel of a pair.
+const UPPER_HALF_BLOCK: char = '\u{2580}';
+
+/// The ANSI escape sequence that resets the terminal colors.
+const ANSI_RESET: &str = "\x1b[0m";
+
+/// Convert a [RgbImage] into a [String] of colored half block characters.
+///
+/// Each character covers a vertical pair of pixels: the `▀` character is printed with the
+/// top pixel as the 24-bit ANSI foreground color and the bottom pixel as the background
+/// color. A pixel pair is about as tall as a character is wide, so no vertical pixels need
+/// to be skipped. If the image has an odd height, the last row only sets the foreground.
+/// When a width is given in the [ConvertOptions], the image is scaled so the output is exactly
+/// that many characters wide. The progress callback is called after each line.
+fn convert_image_to_half_blocks(
+    img: &RgbImage,
+    options: &ConvertOptions,
+    progress: ProgressCallback,
+) -> String {
+    let dimension = scaled_dimension(Dimension::from(img.dimensions()), options);
+
+    let newimg = imageops::resize(
+        img,
+        dimension.width,
+        dimension.height,
+        imageops::FilterType::Triangle,
+    );
+
+    let mut blocks: String = Default::default();
+    let rows = dimension.height.div_ceil(2);
+
+    for y in (0..dimension.height).step_by(2) {
+        for x in 0..dimension.width {
+            let Rgb([r, g, b]) = *newimg.get_pixel(x, y);
+            // writing to a [String] can not fail
+            let _ = write!(blocks, "\x1b[38;2;{};{};{}m", r, g, b);
+            if y + 1 < dimension.height {
+                let Rgb([r, g, b]) = *newimg.get_pixel(x, y + 1);
+                let _ = write!(blocks, "\x1b[48;2;{};{};{}m", r, g, b);
+            }
+            blocks.push(UPPER_HALF_BLOCK);
+        }
+        blocks.push_str(ANSI_RESET);
+        blocks.push('\n');
+        progress(y / 2 + 1, rows);
+    }
+
+    blocks
+}
+
+/// [AsciiImageBuffer] is a trait that represents a buffer that implements
+/// the [Read], [Seek], and [BufRead] traits. This trait is used as a trait alias.
+///
+/// The idea to make a new trait to be used as a trait alias comes from StackOverflow:
+/// <https://stackoverflow.com/questions/26070559/is-there-any-way-to-create-a-type-alias-for-multiple-traits>
+pub trait AsciiImageBuffer: Read + Seek + BufRead {}
+impl<T: Read + Seek + BufRead> AsciiImageBuffer for T {}
+
+/// [ImageInfo] is a struct that holds the metadata found in the header of an image.
+#[derive(Debug, PartialEq)]
+pub struct ImageInfo {
+    /// The size of the image in pixels.
+    pub dimension: Dimension,
+    /// The format of the image, such as PNG or JPEG.
+    pub format: ImageFormat,
+}
+
+/// [SizeEstimate] is a struct that holds the predicted size of the text an image converts into.
+#[derive(Debug, PartialEq)]
+pub struct SizeEstimate {
+    /// The number of characters per line.
+    pub columns: u32,
+    /// The number of lines.
+    pub rows: u32,
+    /// The approximate number of bytes of the text, which is never less than the actual size.
+    pub bytes: usize,
+}
+
+/// Check that the [ConvertOptions] can be used to convert an image.
+///
+/// See [Image::decode] for the errors that can be returned.
+pub(crate) fn validate_options(options: &ConvertOptions) -> Result<(), ConvertError> {
+    if options.width == Some(0) {
+        return Err(ConvertError::InvalidWidth);
+    }
+
+    if options.height == Some(0) {
+        return Err(ConvertError::InvalidHeight);
+    }
+
+    if options.max_characters == Some(0) {
+        return Err(ConvertError::InvalidBudget);
+    }
+
+    if let ContrastMode::Gamma(gamma) = options.contrast {
+        if !(gamma.is_finite() && gamma > 0.0) {
+            return Err(ConvertError::InvalidGamma);
+        }
+    }
+
+    if !(-MAX_BRIGHTNESS..=MAX_BRIGHTNESS).contains(&options.brightness)
+        || !(-MAX_CONTRAST_ADJUSTMENT..=MAX_CONTRAST_ADJUSTMENT)
+            .contains(&options.contrast_adjustment)
+    {
+        return Err(ConvertError::InvalidAdjustment);
+    }
+
+    if options.crop.is_some_and(|rect| rect.is_empty()) {
+        return Err(ConvertError::InvalidCrop);
+    }
+
+    if let Some(aspect) = options.cell_aspect {
+        if !(aspect.is_finite() && aspect > 0.0) {
+            return Err(ConvertError::InvalidCellAspect);
+        }
+    }
+
+    options.pipeline.validate()
+}
+
+/// Convert a decoded [DynamicImage], whose crop, rotate and flip stages have already been
+/// applied, into text.
+///
+/// The [RenderCharset] in the options decides which characters the text is made of, and the
+/// strategy picks the symbols of [RenderCharset::Ascii]. Line numbers and column rulers are
+/// added if the options ask for them.
+pub(crate) fn convert_decoded_image<S>(
+    img: DynamicImage,
+    options: &ConvertOptions,
+    strategy: &S,
+) -> String
+where
+    S: SymbolStrategy + ?Sized,
+{
+    convert_decoded_image_with_progress(img, options, strategy, &|_, _| {})
+}
+
+/// Convert a decoded [DynamicImage] into text like [convert_decoded_image], calling the
+/// progress callback after each line of text is converted.
+fn convert_decoded_image_with_progress<S>(
+    img: DynamicImage,
+    options: &ConvertOptions,
+    strategy: &S,
+    progress: ProgressCallback,
+) -> String
+where
+    S: SymbolStrategy + ?Sized,
+{
+    let text = match options.charset {
+        RenderCharset::Ascii => {
+            convert_image_to_ascii(&img.into_luma8(), options, strategy, progress)
+        }
+        RenderCharset::Braille => convert_image_to_braille(&img.into_luma8(), options, progress),
+        RenderCharset::HalfBlock => {
+            convert_image_to_half_blocks(&img.into_rgb8(), options, progress)
+        }
+    };
+
+    with_rulers(text, options)
+}
+
+/// Add line numbers and column rulers to the text if the [ConvertOptions] ask for them.
+fn with_rulers(text: String, options: &ConvertOptions) -> String {
+    if options.rulers {
+        add_rulers(&text)
+    } else {
+        text
+    }
+}
+
+/// Read which way is up from the EXIF metadata of the image in a buffer, as the rotate and flip
+/// stages that turn the image upright.
+///
+/// Images without EXIF metadata or without an orientation in it get no stages. The buffer is
+/// rewound afterwards, and [ConvertError::ReadError] is returned if that fails.
+fn read_orientation<R: BufRead + Seek>(file: &mut R) -> Result<Pipeline, ConvertError> {
+    let start = file
+        .stream_position()
+        .map_err(|e| ConvertError::ReadError(ErrorSource::new(e)))?;
+
+    let orientation = exif::Reader::new()
+        .read_from_container(file)
+        .ok()
+        .and_then(|exif| {
+            exif.get_field(exif::Tag::Orientation, exif::In::PRIMARY)
+                .and_then(|field| field.value.get_uint(0))
+        });
+
+    file.seek(SeekFrom::Start(start))
+        .map_err(|e| ConvertError::ReadError(ErrorSource::new(e)))?;
+
+    // the values are the ones of the Orientation tag in the EXIF specification, where 1 is upright
+    let pipeline = Pipeline::new();
+    Ok(match orientation {
+        Some(2) => pipeline.flip(Flip::Horizontal),
+        Some(3) => pipeline.rotate(Rotation::Clockwise180),
+        Some(4) => pipeline.flip(Flip::Vertical),
+        Some(5) => pipeline
+            .rotate(Rotation::Clockwise90)
+            .flip(Flip::Horizontal),
+        Some(6) => pipeline.rotate(Rotation::Clockwise90),
+        Some(7) => pipeline
+            .rotate(Rotation::Clockwise270)
+            .flip(Flip::Horizontal),
+        Some(8) => pipeline.rotate(Rotation::Clockwise270),
+        _ => pipeline,
+    })
+}
+
+/// [PreparedImage] is a struct that holds a decoded image, so that it can be converted into text
+/// many times without being read and decoded again.
+///
+/// The grayscale pixels used by [RenderCharset::Ascii] and [RenderCharset::Braille] are kept
+/// from the start, and the RGB pixels used by [RenderCharset::HalfBlock] are kept once they are
+/// first needed. Only the scaling, the contrast, the dithering and the picking of symbols are run
+/// again for new options, which makes trying out a different charset, symbol map or width on the
+/// same image respond right away.
+pub struct PreparedImage {
+    /// The decoded image, turned upright but before any stages of a pipeline are applied.
+    image: DynamicImage,
+    /// The grayscale pixels of the image.
+    luma: GrayImage,
+    /// The RGB pixels of the image, once a conversion has needed them.
+    rgb: OnceLock<RgbImage>,
+}
+
+impl PreparedImage {
+    /// Create a new [PreparedImage] from an image that has already been decoded.
+    pub fn from_image(image: DynamicImage) -> PreparedImage {
+        PreparedImage {
+            luma: image.to_luma8(),
+            image,
+            rgb: OnceLock::new(),
+        }
+    }
+
+    /// The [Dimension] of the decoded image.
+    pub fn dimension(&self) -> Dimension {
+        Dimension::from(self.luma.dimensions())
+    }
+
+    /// Convert the image into text with the [ConvertOptions], picking ASCII symbols with the
+    /// [SymbolMap] of the options.
+    ///
+    /// The text is the same as [Image::convert_to_ascii] creates with the same options. See
+    /// [Image::decode] for the errors caused by invalid options.
+    ///
+    /// [SymbolMap]: crate::converter::symbol_map::SymbolMap
+    pub fn convert(&self, options: &ConvertOptions) -> Result<String, ConvertError> {
+        self.convert_with_strategy(options, &options.symbol_map)
+    }
+
+    /// Convert the image into text with the [ConvertOptions], picking the symbols of
+    /// [RenderCharset::Ascii] with a [SymbolStrategy].
+    ///
+    /// The kept pixels are converted as they are, unless the pipeline of the options crops or
+    /// rotates the image. Those stages are applied to a copy of the decoded image first.
+    /// [ConvertError::TooLarge] is returned if the text has more columns or rows than the limits
+    /// of the options.
+    pub fn convert_with_strategy<S>(
+        &self,
+        options: &ConvertOptions,
+        strategy: &S,
+    ) -> Result<String, ConvertError>
+    where
+        S: SymbolStrategy + ?Sized,
+    {
+        validate_options(options)?;
+        let dimension = options.transformed_dimension(self.dimension())?;
+        let estimate = estimate_size(dimension, options);
+        options.limits.check_text(estimate.columns, estimate.rows)?;
+
+        if options.changes_geometry() {
+            let img = options.apply_geometry(self.image.clone())?;
+            let text = convert_decoded_image(img, options, strategy);
+            return Ok(with_metadata(text, self.dimension(), options));
+        }
+
+        let progress = &|_, _| {};
+        let text = match options.charset {
+            RenderCharset::Ascii => convert_image_to_ascii(&self.luma, options, strategy, progress),
+            RenderCharset::Braille => convert_image_to_braille(&self.luma, options, progress),
+            RenderCharset::HalfBlock => {
+                let rgb = self.rgb.get_or_init(|| self.image.to_rgb8());
+                convert_image_to_half_blocks(rgb, options, progress)
+            }
+        };
+
+        Ok(with_metadata(
+            with_rulers(text, options),
+            self.dimension(),
+            options,
+        ))
+    }
+}
+
+/// [Image] is a struct that holds the [AsciiImageBuffer] that will be turned into an ASCII [String].
+pub struct Image<'a, T>
+where
+    T: AsciiImageBuffer,
+{
+    /// A reference to the image that will be turned into an ASCII.
+    file: &'a mut T,
+    /// The settings used to convert the image.
+    options: ConvertOptions,
+    /// The [ImageCodec] the image is read with.
+    codec: &'a dyn ImageCodec,
+}
+
+impl<'a, T> Image<'a, T>
+where
+    T: AsciiImageBuffer,
+{
+    /// Create a new [Image].
+    pub fn new(file: &'a mut T) -> Image<'a, T> {
+        Self::with_options(file, ConvertOptions::default())
+    }
+
+    /// Create a new [Image] that will be converted into ASCII that is `width` characters wide.
+    pub fn with_width(file: &'a mut T, width: u32) -> Image<'a, T> {
+        Self::with_options(
+            file,
+            ConvertOptions {
+                width: Some(width),
+                ..Default::default()
+            },
+        )
+    }
+
+    /// Create a new [Image] that will be converted using the given [ConvertOptions].
+    pub fn with_options(file: &'a mut T, options: ConvertOptions) -> Image<'a, T> {
+        Image {
+            file,
+            options,
+            codec: default_codec(),
+        }
+    }
+
+    /// Read the image with the given [ImageCodec] instead of the [default_codec].
+    ///
+    /// PNG images are only decoded one row at a time by [Image::convert_to_ascii_writer] when the
+    /// codec [streams its PNG rows](ImageCodec::streams_png_rows).
+    pub fn with_codec(mut self, codec: &'a dyn ImageCodec) -> Image<'a, T> {
+        self.codec = codec;
+        self
+    }
+
+    /// Read the [ImageInfo] of the [Image] without decoding the pixels.
+    ///
+    /// Only the header of the image is read, which makes this much cheaper than a full
+    /// conversion. The buffer is rewound afterwards so the [Image] can still be converted.
+    /// [ConvertError::ReadError] is returned if the buffer can't be read, and
+    /// [ConvertError::DecodeError] is returned if the format or header can't be parsed.
+    pub fn inspect(&mut self) -> Result<ImageInfo, ConvertError> {
+        let start = self
+            .file
+            .stream_position()
+            .map_err(|e| ConvertError::ReadError(ErrorSource::new(e)))?;
+
+        let info = self.codec.inspect(&mut *self.file);
+
+        self.file
+            .seek(SeekFrom::Start(start))
+            .map_err(|e| ConvertError::ReadError(ErrorSource::new(e)))?;
+
+        info
+    }
+
+    /// Read the [Dimension] of the [Image] from its header.
+    ///
+    /// See [Image::inspect] for the errors that can be returned.
+    pub fn dimensions(&mut self) -> Result<Dimension, ConvertError> {
+        self.inspect().map(|info| info.dimension)
+    }
+
+    /// Read the [ImageFormat] of the [Image] from its header.
+    ///
+    /// See [Image::inspect] for the errors that can be returned.
+    pub fn format(&mut self) -> Result<ImageFormat, ConvertError> {
+        self.inspect().map(|info| info.format)
+    }
+
+    /// Predict the [SizeEstimate] of the text the [Image] converts into without converting it.
+    ///
+    /// Only the header of the image is read. See [Image::inspect] for the errors that can be
+    /// returned, [ConvertError::InvalidWidth] is returned if the requested width is zero,
+    /// [ConvertError::InvalidHeight] is returned if the requested height is zero,
+    /// [ConvertError::InvalidBudget] is returned if the budget of characters is zero, and
+    /// [ConvertError::InvalidPipeline] is returned if a crop of the pipeline does not fit.
+    pub fn estimate(&mut self) -> Result<SizeEstimate, ConvertError> {
+        if self.options.width == Some(0) {
+            return Err(ConvertError::InvalidWidth);
+        }
+
+        if self.options.height == Some(0) {
+            return Err(ConvertError::InvalidHeight);
+        }
+
+        if self.options.max_characters == Some(0) {
+            return Err(ConvertError::InvalidBudget);
+        }
+
+        let dimension = self.dimensions()?;
+        let dimension = self.oriented_options()?.transformed_dimension(dimension)?;
+        Ok(estimate_size(dimension, &self.options))
+    }
+
+    /// List the [ConversionWarning]s about how the [Image] is scaled without converting it.
+    ///
+    /// Only the header of the image is read. A warning is returned when the image is too large
+    /// to convert at its own size and no width was requested, and when the text is made narrower
+    /// to fit the budget of characters. See [Image::estimate] for the errors that can be returned.
+    pub fn warnings(&mut self) -> Result<Vec<ConversionWarning>, ConvertError> {
+        self.estimate()?;
+
+        let dimension = self.dimensions()?;
+        let dimension = self.oriented_options()?.transformed_dimension(dimension)?;
+        Ok(scaling_warnings(dimension, &self.options))
+    }
+
+    /// Read the rotate and flip stages that turn the [Image] upright from its EXIF metadata, or
+    /// no stages when the options ignore the EXIF orientation.
+    ///
+    /// See [read_orientation] for the errors that can be returned.
+    fn orientation(&mut self) -> Result<Pipeline, ConvertError> {
+        if self.options.ignore_exif_orientation {
+            return Ok(Pipeline::new());
+        }
+
+        read_orientation(&mut self.file)
+    }
+
+    /// The [ConvertOptions] of the [Image] with the stages of [Image::orientation] applied right
+    /// after the region is cut out, so the region stays in the pixels as they are stored.
+    fn oriented_options(&mut self) -> Result<ConvertOptions, ConvertError> {
+        let orientation = self.orientation()?;
+        Ok(self.options.with_orientation(&orientation))
+    }
+
+    /// Check the size in the header of the [Image] against the [SizeLimits] of the options
+    /// before the image is decoded.
+    ///
+    /// [ConvertError::TooLarge] is returned if the image has more pixels than the limit, or if
+    /// the text it converts into has more columns or rows than the limits. The options must be
+    /// valid, since the size of the text is predicted from them.
+    ///
+    /// [SizeLimits]: crate::converter::options::SizeLimits
+    fn check_size(&mut self) -> Result<(), ConvertError> {
+        let dimension = self.dimensions()?;
+        self.options.limits.check_pixels(&dimension)?;
+
+        let dimension = self.oriented_options()?.transformed_dimension(dimension)?;
+        let estimate = estimate_size(dimension, &self.options);
+        self.options
+            .limits
+            .check_text(estimate.columns, estimate.rows)
+    }
+
+    /// Decode the [Image] into a [DynamicImage].
+    ///
+    /// The [image] crate is used to parse the image into a readable buffer, and then the region
+    /// is cut out, the image is turned upright with its EXIF orientation, and the crop, rotate
+    /// and flip stages of the pipeline are applied. If the requested width is zero,
+    /// [ConvertError::InvalidWidth] is returned, if the budget of characters is zero,
+    /// [ConvertError::InvalidBudget] is returned, and if a requested gamma is not a positive
+    /// number, [ConvertError::InvalidGamma] is returned. If the brightness or contrast adjustment
+    /// is out of range, [ConvertError::InvalidAdjustment] is returned. If the stages of the
+    /// pipeline are out of order or a crop does not fit, [ConvertError::InvalidPipeline] is
+    /// returned. If the image or its text is larger than the limits of the options,
+    /// [ConvertError::TooLarge] is returned without decoding the pixels.
+    fn decode(&mut self) -> Result<DynamicImage, ConvertError> {
+        self.decode_measured().map(|(img, _)| img)
+    }
+
+    /// Decode the [Image] like [Image::decode], also counting the pixels and bytes of the image
+    /// as it was decoded, before its geometry was changed.
+    fn decode_measured(&mut self) -> Result<(DynamicImage, ResourceUsage), ConvertError> {
+        validate_options(&self.options)?;
+        self.check_size()?;
+
+        let options = self.oriented_options()?;
+        let img = self.codec.decode(&mut *self.file)?;
+        let decoded = ResourceUsage {
+            pixels_processed: u64::from(img.width()) * u64::from(img.height()),
+            peak_memory_bytes: img.as_bytes().len() as u64,
+            ..Default::default()
+        };
+
+        Ok((options.apply_geometry(img)?, decoded))
+    }
+
+    /// Decode the [Image] once into a [PreparedImage], which can then be converted again and
+    /// again with different options.
+    ///
+    /// The image is turned upright with its EXIF orientation unless the options of the [Image]
+    /// ignore it, so regions to convert are in the pixels of the upright image. See
+    /// [Image::inspect] for the errors that can be returned, and [ConvertError::TooLarge] is
+    /// returned if the image has more pixels than the limit of the options.
+    pub fn prepare(&mut self) -> Result<PreparedImage, ConvertError> {
+        let dimension = self.dimensions()?;
+        self.options.limits.check_pixels(&dimension)?;
+
+        let orientation = self.orientation()?;
+        let img = self.codec.decode(&mut *self.file)?;
+        orientation
+            .apply_geometry(img)
+            .map(PreparedImage::from_image)
+    }
+
+    /// Convert a [Image] to an ASCII [String].
+    ///
+    /// The [image] crate is used to parse the image into a readable buffer. The
+    /// [RenderCharset] in the options decides which characters the text is made of, and
+    /// line numbers, column rulers and a metadata header are added if the options ask for them.
+    /// ASCII symbols are picked with the [SymbolMap] of the options.
+    /// See [Image::decode] for the errors caused by invalid options.
+    ///
+    /// [SymbolMap]: crate::converter::symbol_map::SymbolMap
+    pub fn convert_to_ascii(&mut self) -> Result<String, ConvertError> {
+        let symbol_map = self.options.symbol_map.clone();
+        self.convert_to_ascii_with_strategy(&symbol_map)
+    }
+
+    /// Convert a [Image] to an ASCII [String], picking the symbols with a [SymbolStrategy].
+    ///
+    /// The strategy is only used by [RenderCharset::Ascii]. Otherwise this works just like
+    /// [Image::convert_to_ascii].
+    pub fn convert_to_ascii_with_strategy<S>(
+        &mut self,
+        strategy: &S,
+    ) -> Result<String, ConvertError>
+    where
+        S: SymbolStrategy + ?Sized,
+    {
+        // the options are checked before the header is read, so they are reported first
+        validate_options(&self.options)?;
+        let source = self.dimensions()?;
+        let img = self.decode()?;
+        let text = convert_decoded_image(img, &self.options, strategy);
+        Ok(with_metadata(text, source, &self.options))
+    }
+
+    /// Convert a [Image] to an ASCII [String], reporting how far along the conversion is.
+    ///
+    /// The text is the same as [Image::convert_to_ascii]. After each line of text is converted,
+    /// `progress` is called with the number of lines converted so far and the total number of
+    /// lines, so a long conversion can show how much of it is done. Decoding the image happens
+    /// before the first call. See [ProgressCallback] for how the calls are made with the
+    /// `parallel` feature, and [Image::decode] for the errors caused by invalid options.
+    pub fn convert_to_ascii_with_progress<F>(&mut self, progress: F) -> Result<String, ConvertError>
+    where
+        F: Fn(u32, u32) + Sync,
+    {
+        self.convert_to_ascii_with_usage(progress)
+            .map(|(text, _)| text)
+    }
+
+    /// Convert a [Image] to an ASCII [String] like [Image::convert_to_ascii_with_progress], also
+    /// measuring the [ResourceUsage] of the conversion.
+    ///
+    /// The peak memory is estimated from the size of the decoded image, of the pixels the
+    /// characters are picked from, which are gray for ASCII and braille and RGB for half blocks,
+    /// and of the text.
+    pub fn convert_to_ascii_with_usage<F>(
+        &mut self,
+        progress: F,
+    ) -> Result<(String, ResourceUsage), ConvertError>
+    where
+        F: Fn(u32, u32) + Sync,
+    {
+        let stopwatch = Stopwatch::start();
+        validate_options(&self.options)?;
+        let source = self.dimensions()?;
+        let (img, decoded) = self.decode_measured()?;
+
+        let bytes_per_pixel = match self.options.charset {
+            RenderCharset::HalfBlock => 3,
+            RenderCharset::Ascii | RenderCharset::Braille => 1,
+        };
+        let converted_bytes = u64::from(img.width()) * u64::from(img.height()) * bytes_per_pixel;
+
+        let symbol_map = self.options.symbol_map.clone();
+        let text = convert_decoded_image_with_progress(img, &self.options, &symbol_map, &progress);
+        let text = with_metadata(text, source, &self.options);
+
+        let usage = ResourceUsage {
+            peak_memory_bytes: decoded.peak_memory_bytes + converted_bytes + text.len() as u64,
+            conversion_time: stopwatch.elapsed(),
+            ..decoded
+        };
+        Ok((text, usage))
+    }
+
+    /// Convert a [Image] to ASCII, writing each line to `writer` as soon as it is ready.
+    ///
+    /// The text is the same as [Image::convert_to_ascii], but it is never held in memory as a
+    /// whole. For [RenderCharset::Ascii], PNG images without a region to crop, an EXIF
+    /// orientation, or crop, rotate or flip stages in the pipeline are also decoded one row at a
+    /// time and scaled as they are read, so a multi-megapixel image doesn't have to be decoded
+    /// into memory all at once. Other formats
+    /// are decoded whole. Braille, half blocks, rulers and metadata headers need the whole text
+    /// before it can be written, so they are converted by [Image::convert_to_ascii] first. See [Image::decode]
+    /// for the errors caused by invalid options, and [ConvertError::WriteError] is returned if
+    /// the writer fails.
+    pub fn convert_to_ascii_writer<W: io::Write>(
+        &mut self,
+        writer: &mut W,
+    ) -> Result<(), ConvertError> {
+        if self.options.charset != RenderCharset::Ascii
+            || self.options.rulers
+            || self.options.metadata
+        {
+            let text = self.convert_to_ascii()?;
+            return writer
+                .write_all(text.as_bytes())
+                .map_err(|e| ConvertError::WriteError(ErrorSource::new(e)));
+        }
+
+        let streamed = self.codec.streams_png_rows()
+            && self.format()? == ImageFormat::Png
+            && !self.oriented_options()?.changes_geometry();
+
+        let scaled = if streamed {
+            validate_options(&self.options)?;
+            self.check_size()?;
+            stream_scaled_png(&mut *self.file, &self.options)?
+        } else {
+            scaled_luma(&self.decode()?.into_luma8(), &self.options)
+        };
+        let sampled = adjust_ascii_tone(scaled, &self.options);
+
+        let mut line = String::new();
+        for row in 0..sampled.height() {
+            line.clear();
+            push_ascii_line(&mut line, &sampled, row, (1, 1), &self.options.symbol_map);
+            writer
+                .write_all(line.as_bytes())
+                .map_err(|e| ConvertError::WriteError(ErrorSource::new(e)))?;
+        }
+
+        Ok(())
+    }
+
+    /// Convert a [Image] to a PNG that looks like its ASCII art.
+    ///
+    /// The image is decoded once and rendered straight back into a PNG, as if it were
+    /// converted to ASCII and the ASCII converted back to an image. The [SymbolMap] of the
+    /// options is always used, so the [RenderCharset] is ignored. When `colorize` is true,
+    /// the PNG keeps the colors of the original image.
+    ///
+    /// [SymbolMap]: crate::converter::symbol_map::SymbolMap
+    pub fn convert_to_ascii_image(
+        &mut self,
+        colorize: bool,
+    ) -> Result<Cursor<Vec<u8>>, ConvertError> {
+        let img = self.decode()?;
+        let options = ConvertOptions {
+            charset: RenderCharset::Ascii,
+            ..self.options.clone()
+        };
+        convert_image_to_ascii_image(img, &options, colorize, self.codec)
+    }
+
+    /// Convert a [Image] to ASCII art wrapped in an HTML `<pre>` element, ready to be put in a
+    /// page.
+    ///
+    /// The symbols are the same as [Image::convert_to_ascii] picks with the [SymbolMap] of the
+    /// options, so the [RenderCharset] is ignored, and no line numbers or rulers are added. When
+    /// `colorize` is true, the symbols are wrapped in `<span>` tags that color them like the
+    /// original image. See [Image::decode] for the errors caused by invalid options.
+    ///
+    /// [SymbolMap]: crate::converter::symbol_map::SymbolMap
+    pub fn convert_to_ascii_html(&mut self, colorize: bool) -> Result<String, ConvertError> {
+        let img = self.decode()?;
+        let options = ConvertOptions {
+            charset: RenderCharset::Ascii,
+            ..self.options.clone()
+        };
+        Ok(convert_image_to_ascii_html(img, &options, colorize))
+    }
+
+    /// Compute the [BrightnessHistogram] of the [Image].
+    ///
+    /// The histogram counts the pixels exactly as the conversion sees them, after the image is
+    /// scaled for the [RenderCharset] of the options but before its contrast is adjusted. See
+    /// [Image::decode] for the errors that can be returned.
+    pub fn histogram(&mut self) -> Result<BrightnessHistogram, ConvertError> {
+        let img = self.decode()?;
+        let scaled = scaled_luma(&img.into_luma8(), &self.options);
+        Ok(BrightnessHistogram::from_image(&scaled))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::converter::{
+        ascii::Ascii,
+        dimension::Rect,
+        options::{DitherMode, ImageRenderMode, SizeLimits, DEFAULT_MAX_COLUMNS},
+        pipeline::{Pipeline, Rotation},
+        strategy::QuadrantBlocks,
+        symbol_map::SymbolMap,
+    };
+    use image::Luma;
+    use std::{
+        fs,
+        fs::File,
+        io::{BufReader, Cursor, Error, SeekFrom},
+    };
+
+    // I was trying to figure out how to make [Seek] fail in order to raise an error
+    // when I call `.with_guessed_format()` inside of the `.convert_to_ascii()` function.
+    // I looked around for ways to utilize dependency injection of some sort, and I found
+    // this StackOverflow comment:
+    // https://stackoverflow.com/questions/72817819/how-do-i-use-rust-traits-to-abstract-http-call-for-tests
+    //
+    // The comment helped me realize that I can implement the [AsciiImageBuffer] trait requirements
+    // in a way that makes them fail every time they are called. And that's exactly what I did for
+    // the [BadImage] struct and the traits it implements.
+    struct BadImage;
+
+    impl Seek for BadImage {
+        fn seek(&mut self, _pos: SeekFrom) -> std::io::Result<u64> {
+            Err(Error::other("Bad Seek.seek!"))
+        }
+    }
+
+    impl Read for BadImage {
+        fn read(&mut self, _buf: &mut [u8]) -> std::io::Result<usize> {
+            Err(Error::other("Bad Read.read!"))
+        }
+    }
+
+    impl BufRead for BadImage {
+        fn fill_buf(&mut self) -> std::io::Result<&[u8]> {
+            Err(Error::other("Bad BufRead.fill_buf!"))
+        }
+
+        fn consume(&mut self, _amt: usize) {}
+    }
+
+    // Test the [ConvertError::ReadError] failure state of `convert_to_ascii()`.
+    #[test]
+    fn test_read_error() {
+        let mut bad_image = BadImage;
+        let mut img = Image::new(&mut bad_image);
+
+        assert!(matches!(
+            img.convert_to_ascii(),
+            Err(ConvertError::ReadError(_))
+        ));
+    }
+
+    // Test the [ConvertError::DecodeError] failure state of `convert_to_ascii()`.
+    #[test]
+    fn test_decode_error() {
+        let mut buff = Cursor::new(String::new());
+        let mut img = Image::new(&mut buff);
+
+        assert!(matches!(
+            img.convert_to_ascii(),
+            Err(ConvertError::DecodeError(_))
+        ));
+    }
+
+    // Test the successful conversion of an image into ASCII.
+    #[test]
+    fn test_convert_to_ascii() {
+        // The idea to use "CARGO_MANIFEST_DIR" comes from StackOverflow:
+        // https://stackoverflow.com/questions/30003921/how-can-i-locate-resources-for-testing-with-cargo
+        let img_path = concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/test_assets/images/freakazoid-small.png"
+        );
+        let img_file = File::open(img_path).unwrap();
+        let mut img_reader = BufReader::new(img_file);
+        let mut img = Image::new(&mut img_reader);
+        let ascii = img.convert_to_ascii();
+
+        assert!(ascii.is_ok());
+
+        let ascii = ascii.unwrap();
+
+        // The idea to use "CARGO_MANIFEST_DIR" comes from StackOverflow:
+        // https://stackoverflow.com/questions/30003921/how-can-i-locate-resources-for-testing-with-cargo
+        let ascii_path = concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/test_assets/ascii/freakazoid-small.txt"
+        );
+        let ascii_file =
+            fs::read_to_string(ascii_path).expect("Should have been able to read ASCII file.");
+
+        assert_eq!(ascii, ascii_file);
+    }
+
+    // Test that a requested width controls the number of characters per line.
+    #[test]
+    fn test_convert_to_ascii_with_width() {
+        // The idea to use "CARGO_MANIFEST_DIR" comes from StackOverflow:
+        // https://stackoverflow.com/questions/30003921/how-can-i-locate-resources-for-testing-with-cargo
+        let img_path = concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/test_assets/images/freakazoid-small.png"
+        );
+
+        for width in [40, 80, 120, 200] {
+            let img_file = File::open(img_path).unwrap();
+            let mut img_reader = BufReader::new(img_file);
+            let ascii = Image::with_width(&mut img_reader, width)
+                .convert_to_ascii()
+                .unwrap();
+
+            assert!(ascii.lines().all(|l| l.len() == width as usize));
+        }
+    }
+
+    // Test that a budget of characters picks the widest text that fits in it.
+    #[test]
+    fn test_convert_to_ascii_with_budget() {
+        let img_path = concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/test_assets/images/freakazoid-small.png"
+        );
+
+        for (charset, budget) in [
+            (RenderCharset::Ascii, 2_000),
+            (RenderCharset::Ascii, 4_000),
+            (RenderCharset::Braille, 500),
+        ] {
+            let convert = |width, max_characters| {
+                let mut img_reader = BufReader::new(File::open(img_path).unwrap());
+                Image::with_options(
+                    &mut img_reader,
+                    ConvertOptions {
+                        width,
+                        max_characters,
+                        charset,
+                        ..Default::default()
+                    },
+                )
+                .convert_to_ascii()
+                .unwrap()
+            };
+
+            let text = convert(None, Some(budget));
+            let width = text.lines().next().unwrap().chars().count() as u32;
+
+            assert!(text.chars().count() <= budget as usize);
+            assert!(convert(Some(width + 1), None).chars().count() > budget as usize);
+            assert_eq!(convert(Some(1000), Some(budget)), text);
+            // a budget never makes the text larger than it would be otherwise
+            assert_eq!(convert(None, Some(u32::MAX)), convert(None, None));
+        }
+    }
+
+    // Test that a height picks the widest text that has at most that many lines, within the width.
+    #[test]
+    fn test_convert_to_ascii_with_height() {
+        let img_path = concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/test_assets/images/freakazoid-small.png"
+        );
+
+        for (charset, lines) in [
+            (RenderCharset::Ascii, 10),
+            (RenderCharset::Ascii, 24),
+            (RenderCharset::Braille, 10),
+        ] {
+            let convert = |width, height| {
+                let mut img_reader = BufReader::new(File::open(img_path).unwrap());
+                Image::with_options(
+                    &mut img_reader,
+                    ConvertOptions {
+                        width,
+                        height,
+                        charset,
+                        ..Default::default()
+                    },
+                )
+                .convert_to_ascii()
+                .unwrap()
+            };
+            let columns = |text: &str| text.lines().next().unwrap().chars().count() as u32;
+
+            let text = convert(None, Some(lines));
+            let width = columns(&text);
+            assert!(text.lines().count() <= lines as usize);
+            assert!(convert(Some(width + 1), None).lines().count() > lines as usize);
+            assert_eq!(convert(Some(1000), Some(lines)), text);
+
+            // the text stays as narrow as the width when that already fits
+            assert_eq!(
+                convert(Some(width / 2), Some(lines)),
+                convert(Some(width / 2), None)
+            );
+            assert_eq!(convert(None, Some(u32::MAX)), convert(None, None));
+        }
+    }
+
+    // Test the [ConvertError::InvalidHeight] failure state of `convert_to_ascii()` and `estimate()`.
+    #[test]
+    fn test_invalid_height() {
+        let options = ConvertOptions {
+            height: Some(0),
+            ..Default::default()
+        };
+
+        let mut buff = Cursor::new(String::new());
+        let mut img = Image::with_options(&mut buff, options);
+
+        assert_eq!(img.convert_to_ascii(), Err(ConvertError::InvalidHeight));
+        assert_eq!(img.estimate(), Err(ConvertError::InvalidHeight));
+    }
+
+    // Test that 2x4 pixel blocks are packed into the expected braille characters.
+    #[test]
+    fn test_convert_image_to_braille() {
+        // left column dark, right column light
+        let img = ImageBuffer::from_fn(2, 4, |x, _| if x == 0 { Luma([0]) } else { Luma([255]) });
+        assert_eq!(
+            convert_image_to_braille(&img, &ConvertOptions::default(), &|_, _| {}),
+            "\u{2847}\n"
+        );
+
+        // all dark
+        let img = ImageBuffer::from_pixel(2, 4, Luma([0]));
+        assert_eq!(
+            convert_image_to_braille(&img, &ConvertOptions::default(), &|_, _| {}),
+            "\u{28FF}\n"
+        );
+
+        // all light
+        let img = ImageBuffer::from_pixel(2, 4, Luma([255]));
+        assert_eq!(
+            convert_image_to_braille(&img, &ConvertOptions::default(), &|_, _| {}),
+            "\u{2800}\n"
+        );
+    }
+
+    // Test that pixel pairs are turned into colored half blocks.
+    #[test]
+    fn test_convert_image_to_half_blocks() {
+        // red on top of blue
+        let img = ImageBuffer::from_fn(1, 2, |_, y| {
+            if y == 0 {
+                Rgb([255, 0, 0])
+            } else {
+                Rgb([0, 0, 255])
+            }
+        });
+        assert_eq!(
+            convert_image_to_half_blocks(&img, &ConvertOptions::default(), &|_, _| {}),
+            "\x1b[38;2;255;0;0m\x1b[48;2;0;0;255m\u{2580}\x1b[0m\n"
+        );
+
+        // odd heights only set the foreground of the last row
+        let img = ImageBuffer::from_pixel(2, 1, Rgb([0, 255, 0]));
+        assert_eq!(
+            convert_image_to_half_blocks(&img, &ConvertOptions::default(), &|_, _| {}),
+            "\x1b[38;2;0;255;0m\u{2580}\x1b[38;2;0;255;0m\u{2580}\x1b[0m\n"
+        );
+    }
+
+    // Test that dithering a gradient uses more symbols per row than plain quantization.
+    #[test]
+    fn test_convert_image_to_ascii_dither() {
+        // a soft gradient that only covers a couple of symbols of a short ramp
+        let img = ImageBuffer::from_fn(64, 8, |x, _| Luma([100 + (x / 4) as u8]));
+        let mut options = ConvertOptions {
+            symbol_map: SymbolMap::from_ramp("@#+. ").unwrap(),
+            ..Default::default()
+        };
+        let plain = convert_image_to_ascii(&img, &options, &options.symbol_map, &|_, _| {});
+
+        for mode in [DitherMode::FloydSteinberg, DitherMode::Bayer] {
+            options.dither = mode;
+            let dithered = convert_image_to_ascii(&img, &options, &options.symbol_map, &|_, _| {});
+
+            assert_eq!(dithered.lines().count(), plain.lines().count());
+            assert_ne!(dithered, plain);
+            // the dithered ASCII mixes the neighboring symbols instead of one hard band
+            let first_row = dithered.lines().next().unwrap();
+            assert!(first_row.contains('#') && first_row.contains('+'));
+        }
+    }
+
+    // Test that braille output of a real image has the requested width.
+    #[test]
+    fn test_convert_to_braille_with_width() {
+        // The idea to use "CARGO_MANIFEST_DIR" comes from StackOverflow:
+        // https://stackoverflow.com/questions/30003921/how-can-i-locate-resources-for-testing-with-cargo
+        let img_path = concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/test_assets/images/freakazoid-small.png"
+        );
+        let img_file = File::open(img_path).unwrap();
+        let mut img_reader = BufReader::new(img_file);
+        let options = ConvertOptions {
+            width: Some(80),
+            charset: RenderCharset::Braille,
+            ..Default::default()
+        };
+        let braille = Image::with_options(&mut img_reader, options)
+            .convert_to_ascii()
+            .unwrap();
+
+        assert!(braille.lines().all(|l| l.chars().count() == 80
+            && l.chars().all(|c| ('\u{2800}'..='\u{28FF}').contains(&c))));
+    }
+
+    // Test that a custom [SymbolMap] is used to generate ASCII.
+    #[test]
+    fn test_convert_to_ascii_with_symbol_map() {
+        // The idea to use "CARGO_MANIFEST_DIR" comes from StackOverflow:
+        // https://stackoverflow.com/questions/30003921/how-can-i-locate-resources-for-testing-with-cargo
+        let img_path = concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/test_assets/images/freakazoid-small.png"
+        );
+        let img_file = File::open(img_path).unwrap();
+        let mut img_reader = BufReader::new(img_file);
+        let options = ConvertOptions {
+            symbol_map: SymbolMap::from_ramp("@%#*+=-:. ").unwrap(),
+            ..Default::default()
+        };
+        let ascii = Image::with_options(&mut img_reader, options)
+            .convert_to_ascii()
+            .unwrap();
+
+        assert!(ascii.chars().all(|c| "@%#*+=-:. \n".contains(c)));
+    }
+
+    // Test that the header of an image can be inspected without converting it.
+    #[test]
+    fn test_inspect() {
+        // The idea to use "CARGO_MANIFEST_DIR" comes from StackOverflow:
+        // https://stackoverflow.com/questions/30003921/how-can-i-locate-resources-for-testing-with-cargo
+        let img_path = concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/test_assets/images/freakazoid-small.png"
+        );
+        let img_file = File::open(img_path).unwrap();
+        let mut img_reader = BufReader::new(img_file);
+        let mut img = Image::new(&mut img_reader);
+
+        let info = img.inspect().unwrap();
+        assert_eq!(info.format, ImageFormat::Png);
+        assert_eq!(img.format(), Ok(ImageFormat::Png));
+        assert_eq!(img.dimensions(), Ok(info.dimension));
+
+        // the image can still be converted after it has been inspected
+        let ascii_path = concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/test_assets/ascii/freakazoid-small.txt"
+        );
+        let ascii_file =
+            fs::read_to_string(ascii_path).expect("Should have been able to read ASCII file.");
+        assert_eq!(img.convert_to_ascii(), Ok(ascii_file));
+    }
+
+    // Test that a JPEG header can be inspected.
+    #[test]
+    fn test_inspect_jpeg() {
+        // The idea to use "CARGO_MANIFEST_DIR" comes from StackOverflow:
+        // https://stackoverflow.com/questions/30003921/how-can-i-locate-resources-for-testing-with-cargo
+        let img_path = concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/test_assets/images/goldfish.jpeg"
+        );
+        let img_file = File::open(img_path).unwrap();
+        let mut img_reader = BufReader::new(img_file);
+
+        assert_eq!(Image::new(&mut img_reader).format(), Ok(ImageFormat::Jpeg));
+    }
+
+    // Test that a WebP image can be inspected and converted.
+    #[test]
+    fn test_webp() {
+        // The idea to use "CARGO_MANIFEST_DIR" comes from StackOverflow:
+        // https://stackoverflow.com/questions/30003921/how-can-i-locate-resources-for-testing-with-cargo
+        let img_path = concat!(env!("CARGO_MANIFEST_DIR"), "/test_assets/images/pixel.webp");
+        let img_file = File::open(img_path).unwrap();
+        let mut img_reader = BufReader::new(img_file);
+        let mut img = Image::new(&mut img_reader);
+
+        assert_eq!(img.format(), Ok(ImageFormat::WebP));
+        assert_eq!(img.dimensions(), Ok(Dimension::from((1, 1))));
+        assert!(img.convert_to_ascii().is_ok());
+    }
+
+    // Test that BMP and TIFF images convert into the same ASCII as the PNG they were made from.
+    #[test]
+    fn test_bmp_and_tiff() {
+        // The idea to use "CARGO_MANIFEST_DIR" comes from StackOverflow:
+        // https://stackoverflow.com/questions/30003921/how-can-i-locate-resources-for-testing-with-cargo
+        let img_path = concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/test_assets/images/freakazoid-small.png"
+        );
+        let ascii_path = concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/test_assets/ascii/freakazoid-small.txt"
+        );
+        let ascii_file =
+            fs::read_to_string(ascii_path).expect("Should have been able to read ASCII file.");
+        let png = image::open(img_path).unwrap();
+
+        for format in [ImageFormat::Bmp, ImageFormat::Tiff] {
+            let mut buff = Cursor::new(Vec::new());
+            png.write_to(&mut buff, format).unwrap();
+            buff.rewind().unwrap();
+            let mut img = Image::new(&mut buff);
+
+            assert_eq!(img.format(), Ok(format));
+            assert_eq!(img.convert_to_ascii(), Ok(ascii_file.clone()));
+        }
+    }
+
+    // Test the failure states of `inspect()`.
+    #[test]
+    fn test_inspect_errors() {
+        let mut bad_image = BadImage;
+        assert!(matches!(
+            Image::new(&mut bad_image).inspect(),
+            Err(ConvertError::ReadError(_))
+        ));
+
+        let mut buff = Cursor::new(String::new());
+        assert!(matches!(
+            Image::new(&mut buff).inspect(),
+            Err(ConvertError::DecodeError(_))
+        ));
+    }
+
+    // Test that the estimated size matches the size of the actual conversion.
+    #[test]
+    fn test_estimate() {
+        // The idea to use "CARGO_MANIFEST_DIR" comes from StackOverflow:
+        // https://stackoverflow.com/questions/30003921/how-can-i-locate-resources-for-testing-with-cargo
+        let img_path = concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/test_assets/images/freakazoid-large.png"
+        );
+
+        for charset in [
+            RenderCharset::Ascii,
+            RenderCharset::Braille,
+            RenderCharset::HalfBlock,
+        ] {
+            for (width, rulers) in [(None, false), (Some(40), false), (Some(120), true)] {
+                let img_file = File::open(img_path).unwrap();
+                let mut img_reader = BufReader::new(img_file);
+                let options = ConvertOptions {
+                    width,
+                    charset,
+                    rulers,
+                    ..Default::default()
+                };
+                let mut img = Image::with_options(&mut img_reader, options);
+
+                let estimate = img.estimate().unwrap();
+                let text = img.convert_to_ascii().unwrap();
+
+                assert_eq!(estimate.rows as usize, text.lines().count());
+                assert!(estimate.bytes >= text.len());
+                if charset != RenderCharset::HalfBlock {
+                    // the tens ruler is shorter than the rest of the lines
+                    let skipped = if rulers { 1 } else { 0 };
+                    assert!(text
+                        .lines()
+                        .skip(skipped)
+                        .all(|l| l.chars().count() == estimate.columns as usize));
+                    if !rulers {
+                        assert_eq!(estimate.bytes, text.len());
+                    }
+                }
+            }
+        }
+    }
+
+    // Test that rulers are added to the converted text when they are asked for.
+    #[test]
+    fn test_convert_to_ascii_with_rulers() {
+        // The idea to use "CARGO_MANIFEST_DIR" comes from StackOverflow:
+        // https://stackoverflow.com/questions/30003921/how-can-i-locate-resources-for-testing-with-cargo
+        let img_path = concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/test_assets/images/freakazoid-small.png"
+        );
+        let img_file = File::open(img_path).unwrap();
+        let mut img_reader = BufReader::new(img_file);
+        let ascii = Image::new(&mut img_reader).convert_to_ascii().unwrap();
+
+        img_reader.rewind().unwrap();
+        let options = ConvertOptions {
+            rulers: true,
+            ..Default::default()
+        };
+        let ruled = Image::with_options(&mut img_reader, options)
+            .convert_to_ascii()
+            .unwrap();
+
+        assert_eq!(ruled.lines().count(), ascii.lines().count() + RULER_LINES);
+        assert_eq!(crate::converter::ruler::strip_rulers(&ruled), Some(ascii));
+    }
+
+    // Test that a metadata header with the size of the image is put in front of the converted text when it is asked
+    // for, the same way by every way of converting.
+    #[test]
+    fn test_convert_to_ascii_with_metadata() {
+        // The idea to use "CARGO_MANIFEST_DIR" comes from StackOverflow:
+        // https://stackoverflow.com/questions/30003921/how-can-i-locate-resources-for-testing-with-cargo
+        let img_path = concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/test_assets/images/freakazoid-small.png"
+        );
+        let img_file = File::open(img_path).unwrap();
+        let mut img_reader = BufReader::new(img_file);
+        let ascii = Image::new(&mut img_reader).convert_to_ascii().unwrap();
+
+        img_reader.rewind().unwrap();
+        let options = ConvertOptions {
+            metadata: true,
+            ..Default::default()
+        };
+        let mut image = Image::with_options(&mut img_reader, options.clone());
+        let source = image.dimensions().unwrap();
+        let with_header = image.convert_to_ascii().unwrap();
+
+        assert_eq!(
+            with_header,
+            crate::converter::metadata::metadata_header(source, &options) + &ascii
+        );
+        assert_eq!(
+            crate::converter::metadata::strip_metadata_header(&with_header),
+            Some(ascii.as_str())
+        );
+
+        img_reader.rewind().unwrap();
+        let prepared = Image::new(&mut img_reader).prepare().unwrap();
+        assert_eq!(prepared.convert(&options).unwrap(), with_header);
+
+        img_reader.rewind().unwrap();
+        let mut written = Vec::new();
+        Image::with_options(&mut img_reader, options)
+            .convert_to_ascii_writer(&mut written)
+            .unwrap();
+        assert_eq!(String::from_utf8(written).unwrap(), with_header);
+    }
+
+    // Test that a [SymbolStrategy] with bigger cells keeps the size of the ASCII.
+    #[test]
+    fn test_convert_to_ascii_with_strategy() {
+        // The idea to use "CARGO_MANIFEST_DIR" comes from StackOverflow:
+        // https://stackoverflow.com/questions/30003921/how-can-i-locate-resources-for-testing-with-cargo
+        let img_path = concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/test_assets/images/freakazoid-small.png"
+        );
+        let img_file = File::open(img_path).unwrap();
+        let mut img_reader = BufReader::new(img_file);
+        let ascii = Image::new(&mut img_reader).convert_to_ascii().unwrap();
+
+        img_reader.rewind().unwrap();
+        let blocks = Image::new(&mut img_reader)
+            .convert_to_ascii_with_strategy(&QuadrantBlocks)
+            .unwrap();
+
+        assert_eq!(blocks.lines().count(), ascii.lines().count());
+        assert!(blocks
+            .lines()
+            .zip(ascii.lines())
+            .all(|(b, a)| b.chars().count() == a.chars().count()));
+
+        // a closure can pick the symbols too
+        img_reader.rewind().unwrap();
+        let strategy = |cell: &Cell| if cell.brightness() < 128 { '#' } else { '.' };
+        let shapes = Image::new(&mut img_reader)
+            .convert_to_ascii_with_strategy(&strategy)
+            .unwrap();
+
+        assert!(shapes.chars().all(|c| c == '#' || c == '.' || c == '\n'));
+    }
+
+    // Test that progress is reported once per line of every charset, and that the text is the same without it.
+    #[test]
+    fn test_convert_to_ascii_with_progress() {
+        // The idea to use "CARGO_MANIFEST_DIR" comes from StackOverflow:
+        // https://stackoverflow.com/questions/30003921/how-can-i-locate-resources-for-testing-with-cargo
+        let img_path = concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/test_assets/images/freakazoid-small.png"
+        );
+        let img_file = File::open(img_path).unwrap();
+        let mut img_reader = BufReader::new(img_file);
+
+        for charset in [
+            RenderCharset::Ascii,
+            RenderCharset::Braille,
+            RenderCharset::HalfBlock,
+        ] {
+            let options = ConvertOptions {
+                charset,
+                ..Default::default()
+            };
+            img_reader.rewind().unwrap();
+            let text = Image::with_options(&mut img_reader, options.clone())
+                .convert_to_ascii()
+                .unwrap();
+
+            let calls = std::sync::Mutex::new(Vec::new());
+            img_reader.rewind().unwrap();
+            let progress_text = Image::with_options(&mut img_reader, options)
+                .convert_to_ascii_with_progress(|done, total| {
+                    calls.lock().unwrap().push((done, total))
+                })
+                .unwrap();
+
+            let mut calls = calls.into_inner().unwrap();
+            calls.sort();
+            let lines = text.lines().count() as u32;
+
+            assert_eq!(progress_text, text);
+            assert_eq!(
+                calls,
+                (1..=lines).map(|done| (done, lines)).collect::<Vec<_>>()
+            );
+        }
+    }
+
+    // Test the failure states of `estimate()`.
+    #[test]
+    fn test_estimate_errors() {
+        let mut buff = Cursor::new(String::new());
+        assert!(matches!(
+            Image::new(&mut buff).estimate(),
+            Err(ConvertError::DecodeError(_))
+        ));
+
+        let mut buff = Cursor::new(String::new());
+        assert_eq!(
+            Image::with_width(&mut buff, 0).estimate(),
+            Err(ConvertError::InvalidWidth)
+        );
+    }
+
+    // Test that the ASCII image is the same image that converting the ASCII back creates.
+    #[test]
+    fn test_convert_to_ascii_image() {
+        // The idea to use "CARGO_MANIFEST_DIR" comes from StackOverflow:
+        // https://stackoverflow.com/questions/30003921/how-can-i-locate-resources-for-testing-with-cargo
+        let img_path = concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/test_assets/images/freakazoid-small.png"
+        );
+        let img_file = File::open(img_path).unwrap();
+        let mut img_reader = BufReader::new(img_file);
+        let ascii = Image::new(&mut img_reader).convert_to_ascii().unwrap();
+
+        img_reader.rewind().unwrap();
+        let png = Image::new(&mut img_reader)
+            .convert_to_ascii_image(false)
+            .unwrap();
+
+        let expected = Ascii::new(&ascii)
+            .with_render_mode(ImageRenderMode::Pixels)
+            .convert_to_image()
+            .unwrap();
+
+        assert_eq!(png, expected);
+    }
+
+    // Test that a colorized ASCII image keeps the color of the original image.
+    #[test]
+    fn test_convert_to_ascii_image_colorized() {
+        let mut source = Cursor::new(Vec::new());
+        RgbImage::from_pixel(40, 40, Rgb([255, 0, 0]))
+            .write_to(&mut source, ImageFormat::Png)
+            .unwrap();
+        source.rewind().unwrap();
+
+        let mut png = Image::new(&mut source)
+            .convert_to_ascii_image(true)
+            .unwrap();
+        png.rewind().unwrap();
+        let rendered = image::load(png, ImageFormat::Png).unwrap().into_rgb8();
+
+        assert!(rendered
+            .pixels()
+            .all(|Rgb([r, g, b])| *r > 0 && *g == 0 && *b == 0));
+    }
+
+    // Test that the HTML has the same symbols as the ASCII, escaped and wrapped in a pre element.
+    #[test]
+    fn test_convert_to_ascii_html() {
+        let mut source = Cursor::new(Vec::new());
+        GrayImage::from_fn(40, 40, |x, _| Luma([x as u8 * 6]))
+            .write_to(&mut source, ImageFormat::Png)
+            .unwrap();
+        let options = ConvertOptions {
+            symbol_map: SymbolMap::from_ramp("<&o ").unwrap(),
+            ..Default::default()
+        };
+
+        source.rewind().unwrap();
+        let ascii = Image::with_options(&mut source, options.clone())
+            .convert_to_ascii()
+            .unwrap();
+        source.rewind().unwrap();
+        let html = Image::with_options(&mut source, options)
+            .convert_to_ascii_html(false)
+            .unwrap();
+
+        let escaped = ascii.replace('&', "&amp;").replace('<', "&lt;");
+        assert_eq!(html, format!("<pre>{}</pre>\n", escaped));
+    }
+
+    // Test that colorized HTML wraps each run of symbols of the same color in a single span.
+    #[test]
+    fn test_convert_to_ascii_html_colorized() {
+        let mut source = Cursor::new(Vec::new());
+        RgbImage::from_fn(4, 4, |x, _| {
+            if x < 2 {
+                Rgb([255, 0, 0])
+            } else {
+                Rgb([0, 0, 255])
+            }
+        })
+        .write_to(&mut source, ImageFormat::Png)
+        .unwrap();
+        source.rewind().unwrap();
+
+        let html = Image::with_width(&mut source, 4)
+            .convert_to_ascii_html(true)
+            .unwrap();
+        let lines: Vec<&str> = html.lines().collect();
+
+        assert!(lines[0].starts_with("<pre><span style=\"color:#ff0000\">"));
+        assert!(lines[1].starts_with("<span style=\"color:#ff0000\">"));
+        assert_eq!(lines[1].matches("<span").count(), 2);
+        assert!(lines[1].contains("</span><span style=\"color:#0000ff\">"));
+        assert!(lines[1].ends_with("</span>"));
+        assert_eq!(lines.last(), Some(&"</pre>"));
+    }
+
+    // Test that gamma correction brightens or darkens the middle of the brightness range.
+    #[test]
+    fn test_gamma_lookup() {
+        assert_eq!(gamma_lookup(1.0), std::array::from_fn(|b| b as u8));
+
+        let brighten = gamma_lookup(0.5);
+        let darken = gamma_lookup(2.0);
+        assert_eq!((brighten[0], brighten[255]), (0, 255));
+        assert_eq!((darken[0], darken[255]), (0, 255));
+        assert!(brighten[128] > 128);
+        assert!(darken[128] < 128);
+    }
+
+    // Test that equalization stretches a low contrast image over the whole brightness range.
+    #[test]
+    fn test_adjust_contrast_equalize() {
+        let mut img = ImageBuffer::from_fn(4, 1, |x, _| Luma([100 + x as u8 * 10]));
+        adjust_contrast(&mut img, ContrastMode::Equalize);
+
+        let values: Vec<u8> = img.pixels().map(|p| p[0]).collect();
+        assert_eq!(values, vec![0, 85, 170, 255]);
+
+        // an image with a single brightness is left as is
+        let mut img = ImageBuffer::from_pixel(4, 4, Luma([100]));
+        adjust_contrast(&mut img, ContrastMode::Equalize);
+        assert!(img.pixels().all(|p| p[0] == 100));
+    }
+
+    // Test that thresholding leaves only black and white pixels, split at the given threshold or
+    // at the one picked by Otsu's method.
+    #[test]
+    fn test_adjust_contrast_threshold() {
+        let gradient = ImageBuffer::from_fn(4, 1, |x, _| Luma([x as u8 * 80]));
+        let thresholded = |threshold| {
+            let mut img = gradient.clone();
+            adjust_contrast(&mut img, ContrastMode::Threshold(threshold));
+            img.pixels().map(|p| p[0]).collect::<Vec<u8>>()
+        };
 
-    dimension.scale_down(MAX_ASCII_DIMENSION);
+        assert_eq!(thresholded(Some(80)), vec![0, 255, 255, 255]);
+        assert_eq!(thresholded(Some(200)), vec![0, 0, 0, 255]);
+        assert_eq!(thresholded(Some(0)), vec![255, 255, 255, 255]);
+        assert_eq!(thresholded(None), vec![0, 0, 255, 255]);
+    }
 
-    let newimg = imageops::resize(
-        &img,
-        dimension.width,
-        dimension.height,
-        imageops::FilterType::Triangle,
-    );
+    // Test that Otsu's method picks a threshold between the two groups of brightnesses of an image.
+    #[test]
+    fn test_otsu_threshold() {
+        // dark ink around 30 on a light page around 220
+        let drawing = ImageBuffer::from_fn(10, 10, |x, y| {
+            Luma([if x == y {
+                25 + x as u8 % 3 * 5
+            } else {
+                215 + y as u8 % 3 * 5
+            }])
+        });
+        let threshold = otsu_threshold(&drawing);
+        assert!(threshold > 35 && threshold <= 215);
 
-    // generate ascii
-    let mut ascii: String = Default::default();
+        assert_eq!(
+            otsu_threshold(&ImageBuffer::from_pixel(4, 4, Luma([90]))),
+            128
+        );
+        assert_eq!(
+            otsu_threshold(&ImageBuffer::from_fn(2, 1, |x, _| Luma([x as u8 * 255]))),
+            1
+        );
+    }
 
-    for y in 0..dimension.height {
-        // Skip vertical pixels half the time to account for typefaces making
-        // the ascii much taller than wider in relation to the original image:
-        // http://paulbourke.net/dataformats/asciiart/
-        if y % 2 == 0 {
-            continue;
-        }
-        for x in 0..dimension.width {
-            let p = *newimg.get_pixel(x, y);
-            let brightness: u8 = p[0];
-            let symbol = symbol_for_brightness(brightness);
-            ascii.push(symbol);
-        }
-        ascii.push('\n');
+    // Test that the brightness is added to each pixel and the contrast adjustment moves the pixels
+    // away from or toward middle gray.
+    #[test]
+    fn test_adjust_brightness_contrast() {
+        let gradient = ImageBuffer::from_fn(3, 1, |x, _| Luma([64 + x as u8 * 64]));
+        let adjusted = |brightness, contrast_adjustment| {
+            let mut img = gradient.clone();
+            adjust_brightness_contrast(&mut img, brightness, contrast_adjustment);
+            img.pixels().map(|p| p[0]).collect::<Vec<u8>>()
+        };
+
+        assert_eq!(adjusted(0, 0.0), vec![64, 128, 192]);
+        assert_eq!(adjusted(50, 0.0), vec![114, 178, 242]);
+        assert_eq!(adjusted(-100, 0.0), vec![0, 28, 92]);
+        assert_eq!(adjusted(MAX_BRIGHTNESS, 0.0), vec![255, 255, 255]);
+
+        let raised = adjusted(0, 50.0);
+        assert!(raised[0] < 64 && raised[2] > 192);
+        let lowered = adjusted(0, -50.0);
+        assert!(lowered[0] > 64 && lowered[2] < 192);
+        assert_eq!(adjusted(0, -MAX_CONTRAST_ADJUSTMENT), vec![128, 128, 128]);
     }
 
-    ascii
-}
+    // Test that a dark, low contrast image uses more symbols once its contrast is adjusted.
+    #[test]
+    fn test_convert_image_to_ascii_contrast() {
+        let img = ImageBuffer::from_fn(64, 8, |x, _| Luma([10 + (x / 8) as u8]));
+        let count_symbols = |contrast| {
+            let options = ConvertOptions {
+                width: Some(64),
+                contrast,
+                ..Default::default()
+            };
+            let mut ascii: Vec<char> =
+                convert_image_to_ascii(&img, &options, &options.symbol_map, &|_, _| {})
+                    .chars()
+                    .collect();
+            ascii.sort();
+            ascii.dedup();
+            ascii.len()
+        };
 
-/// [AsciiImageBuffer] is a trait that represents a buffer that implements
-/// the [Read], [Seek], and [BufRead] traits. This trait is used as a trait alias.
-///
-/// The idea to make a new trait to be used as a trait alias comes from StackOverflow:
-/// <https://stackoverflow.com/questions/26070559/is-there-any-way-to-create-a-type-alias-for-multiple-traits>
-pub trait AsciiImageBuffer: Read + Seek + BufRead {}
-impl<T: Read + Seek + BufRead> AsciiImageBuffer for T {}
+        let linear = count_symbols(ContrastMode::Linear);
+        assert!(count_symbols(ContrastMode::Equalize) > linear);
+        assert!(count_symbols(ContrastMode::Gamma(0.5)) > linear);
+    }
 
-/// [Image] is a struct that holds the [AsciiImageBuffer] that will be turned into an ASCII [String].
-pub struct Image<'a, T>
-where
-    T: AsciiImageBuffer,
-{
-    /// A reference to the image that will be turned into an ASCII.
-    file: &'a mut T,
-}
+    // Test that the histogram counts one pixel per character of the converted text.
+    #[test]
+    fn test_histogram() {
+        // The idea to use "CARGO_MANIFEST_DIR" comes from StackOverflow:
+        // https://stackoverflow.com/questions/30003921/how-can-i-locate-resources-for-testing-with-cargo
+        let img_path = concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/test_assets/images/freakazoid-small.png"
+        );
+        let img_file = File::open(img_path).unwrap();
+        let mut img_reader = BufReader::new(img_file);
+        let ascii = Image::new(&mut img_reader).convert_to_ascii().unwrap();
 
-impl<'a, T> Image<'a, T>
-where
-    T: AsciiImageBuffer,
-{
-    /// Create a new [Image].
-    pub fn new(file: &'a mut T) -> Image<'a, T> {
-        Image { file }
+        img_reader.rewind().unwrap();
+        let histogram = Image::new(&mut img_reader).histogram().unwrap();
+
+        assert_eq!(
+            histogram.total() as usize,
+            ascii.chars().filter(|c| *c != '\n').count()
+        );
     }
 
-    /// Convert a [Image] to an ASCII [String].
-    ///
-    /// The [image] crate is used to parse the image into a readable buffer.
-    pub fn convert_to_ascii(&mut self) -> Result<String, ConvertError> {
-        match Reader::new(&mut self.file).with_guessed_format() {
-            Ok(image_with_format) => match image_with_format.decode() {
-                Ok(img) => Ok(convert_image_to_ascii(img.into_luma8())),
-                Err(_) => Err(ConvertError::DecodeError),
-            },
-            Err(_) => Err(ConvertError::ReadError),
+    // Test the [ConvertError::InvalidGamma] failure state of `convert_to_ascii()`.
+    #[test]
+    fn test_invalid_gamma() {
+        for gamma in [0.0, -1.0, f32::NAN, f32::INFINITY] {
+            let mut buff = Cursor::new(String::new());
+            let options = ConvertOptions {
+                contrast: ContrastMode::Gamma(gamma),
+                ..Default::default()
+            };
+            let mut img = Image::with_options(&mut buff, options);
+
+            assert_eq!(img.convert_to_ascii(), Err(ConvertError::InvalidGamma));
         }
     }
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use std::{
-        fs,
-        fs::File,
-        io::{BufReader, Cursor, Error, ErrorKind, SeekFrom},
-    };
 
-    // I was trying to figure out how to make [Seek] fail in order to raise an error
-    // when I call `.with_guessed_format()` inside of the `.convert_to_ascii()` function.
-    // I looked around for ways to utilize dependency injection of some sort, and I found
-    // this StackOverflow comment:
-    // https://stackoverflow.com/questions/72817819/how-do-i-use-rust-traits-to-abstract-http-call-for-tests
-    //
-    // The comment helped me realize that I can implement the [AsciiImageBuffer] trait requirements
-    // in a way that makes them fail every time they are called. And that's exactly what I did for
-    // the [BadImage] struct and the traits it implements.
-    struct BadImage;
+    // Test the [ConvertError::InvalidCellAspect] failure state of `convert_to_ascii()`.
+    #[test]
+    fn test_invalid_cell_aspect() {
+        for aspect in [0.0, -0.5, f32::NAN, f32::INFINITY] {
+            let mut buff = Cursor::new(String::new());
+            let options = ConvertOptions {
+                cell_aspect: Some(aspect),
+                ..Default::default()
+            };
+            let mut img = Image::with_options(&mut buff, options);
 
-    impl Seek for BadImage {
-        fn seek(&mut self, _pos: SeekFrom) -> std::io::Result<u64> {
-            Err(Error::new(ErrorKind::Other, "Bad Seek.seek!"))
+            assert_eq!(img.convert_to_ascii(), Err(ConvertError::InvalidCellAspect));
         }
     }
 
-    impl Read for BadImage {
-        fn read(&mut self, _buf: &mut [u8]) -> std::io::Result<usize> {
-            Err(Error::new(ErrorKind::Other, "Bad Read.read!"))
+    // Test the [ConvertError::InvalidAdjustment] failure state of `convert_to_ascii()`.
+    #[test]
+    fn test_invalid_adjustment() {
+        for (brightness, contrast_adjustment) in [
+            (MAX_BRIGHTNESS + 1, 0.0),
+            (i16::MIN, 0.0),
+            (0, MAX_CONTRAST_ADJUSTMENT + 0.5),
+            (0, f32::NAN),
+        ] {
+            let mut buff = Cursor::new(String::new());
+            let options = ConvertOptions {
+                brightness,
+                contrast_adjustment,
+                ..Default::default()
+            };
+            let mut img = Image::with_options(&mut buff, options);
+
+            assert_eq!(img.convert_to_ascii(), Err(ConvertError::InvalidAdjustment));
         }
     }
 
-    impl BufRead for BadImage {
-        fn fill_buf(&mut self) -> std::io::Result<&[u8]> {
-            Err(Error::new(ErrorKind::Other, "Bad BufRead.fill_buf!"))
+    // Test that only the region of the image is converted, and that a region outside of the image
+    // is rejected with [ConvertError::InvalidCrop].
+    #[test]
+    fn test_crop() {
+        // The idea to use "CARGO_MANIFEST_DIR" comes from StackOverflow:
+        // https://stackoverflow.com/questions/30003921/how-can-i-locate-resources-for-testing-with-cargo
+        let img_path = concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/test_assets/images/freakazoid-small.png"
+        );
+        let convert = |crop: Option<Rect>| {
+            let img_file = File::open(img_path).unwrap();
+            let mut img_reader = BufReader::new(img_file);
+            let options = ConvertOptions {
+                crop,
+                ..Default::default()
+            };
+            let mut img = Image::with_options(&mut img_reader, options);
+            (img.estimate(), img.convert_to_ascii())
+        };
+
+        let (estimate, text) = convert(Some(Rect::new(4, 6, 20, 10)));
+        let text = text.unwrap();
+        assert_eq!(text.lines().count(), 5);
+        assert!(text.lines().all(|line| line.len() == 20));
+        assert_eq!(estimate.unwrap().columns, 20);
+
+        // cropping is the same as the first stage of the pipeline cropping
+        let img_file = File::open(img_path).unwrap();
+        let mut img_reader = BufReader::new(img_file);
+        let options = ConvertOptions {
+            pipeline: Pipeline::new().crop(4, 6, 20, 10),
+            ..Default::default()
+        };
+        assert_eq!(
+            Image::with_options(&mut img_reader, options).convert_to_ascii(),
+            Ok(text)
+        );
+
+        for crop in [Rect::new(0, 0, 0, 10), Rect::new(0, 0, 100_000, 1)] {
+            let (estimate, text) = convert(Some(crop));
+            assert_eq!(estimate, Err(ConvertError::InvalidCrop));
+            assert_eq!(text, Err(ConvertError::InvalidCrop));
         }
+    }
 
-        fn consume(&mut self, _amt: usize) {}
+    // Encode a JPEG of a wide image, whose left half is black, with an EXIF orientation.
+    fn oriented_jpeg(orientation: u16) -> Vec<u8> {
+        let img = GrayImage::from_fn(64, 32, |x, _| Luma([if x < 32 { 0 } else { 255 }]));
+        let mut jpeg = Cursor::new(Vec::new());
+        DynamicImage::ImageLuma8(img)
+            .write_to(&mut jpeg, image::ImageOutputFormat::Jpeg(90))
+            .unwrap();
+        let jpeg = jpeg.into_inner();
+
+        // an APP1 segment with a big-endian TIFF header and one IFD that only holds the orientation
+        let mut exif = b"Exif\0\0MM\0\x2a\0\0\0\x08\0\x01\x01\x12\0\x03\0\0\0\x01".to_vec();
+        exif.extend_from_slice(&orientation.to_be_bytes());
+        exif.extend_from_slice(&[0; 6]);
+
+        let mut tagged = vec![0xFF, 0xD8, 0xFF, 0xE1];
+        tagged.extend_from_slice(&(exif.len() as u16 + 2).to_be_bytes());
+        tagged.extend(exif);
+        tagged.extend_from_slice(&jpeg[2..]);
+        tagged
     }
 
-    // Test the [ConvertError::ReadError] failure state of `convert_to_ascii()`.
+    // Test that the orientation in the EXIF metadata is read as the stages that turn the image upright.
     #[test]
-    fn test_read_error() {
-        let mut bad_image = BadImage;
-        let mut img = Image::new(&mut bad_image);
+    fn test_read_orientation() {
+        let read = |jpeg: Vec<u8>| {
+            let mut reader = Cursor::new(jpeg);
+            let orientation = read_orientation(&mut reader).unwrap();
+            assert_eq!(reader.position(), 0);
+            orientation
+        };
+
+        assert_eq!(read(oriented_jpeg(1)), Pipeline::new());
+        assert_eq!(
+            read(oriented_jpeg(6)),
+            Pipeline::new().rotate(Rotation::Clockwise90)
+        );
+        assert_eq!(
+            read(oriented_jpeg(7)),
+            Pipeline::new()
+                .rotate(Rotation::Clockwise270)
+                .flip(Flip::Horizontal)
+        );
+        assert_eq!(read(oriented_jpeg(9)), Pipeline::new());
+        assert_eq!(read(b"not an image".to_vec()), Pipeline::new());
+    }
+
+    // Test that photos are turned upright with their EXIF orientation, unless the options ignore it.
+    #[test]
+    fn test_exif_orientation() {
+        let jpeg = oriented_jpeg(6);
+        let convert = |ignore_exif_orientation: bool| {
+            let options = ConvertOptions {
+                width: Some(8),
+                ignore_exif_orientation,
+                ..Default::default()
+            };
+            // decoding reads the image to its end, so each call gets its own buffer
+            let image = || Cursor::new(jpeg.clone());
+            (
+                Image::with_options(&mut image(), options.clone())
+                    .estimate()
+                    .unwrap()
+                    .rows,
+                Image::with_options(&mut image(), options.clone())
+                    .prepare()
+                    .unwrap()
+                    .dimension(),
+                Image::with_options(&mut image(), options)
+                    .convert_to_ascii()
+                    .unwrap(),
+            )
+        };
 
-        assert_eq!(img.convert_to_ascii(), Err(ConvertError::ReadError));
+        // a quarter turn clockwise makes the image tall, with its black half on top
+        let (rows, dimension, text) = convert(false);
+        assert_eq!(rows, 8);
+        assert_eq!(dimension, Dimension::from((32, 64)));
+        assert_eq!(text.lines().count(), 8);
+        assert_ne!(text.lines().next(), text.lines().last());
+
+        let (rows, dimension, text) = convert(true);
+        assert_eq!(rows, 2);
+        assert_eq!(dimension, Dimension::from((64, 32)));
+        assert_eq!(text.lines().count(), 2);
     }
 
-    // Test the [ConvertError::DecodeError] failure state of `convert_to_ascii()`.
+    // Test that the cell aspect ratio decides how many lines of ASCII an image converts into.
     #[test]
-    fn test_decode_error() {
+    fn test_cell_aspect() {
+        // The idea to use "CARGO_MANIFEST_DIR" comes from StackOverflow:
+        // https://stackoverflow.com/questions/30003921/how-can-i-locate-resources-for-testing-with-cargo
+        let img_path = concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/test_assets/images/freakazoid-small.png"
+        );
+        let img = image::open(img_path).unwrap();
+        let (width, height) = (img.width(), img.height());
+
+        for (aspect, rows) in [
+            (None, height / 2),
+            (Some(0.5), height / 2),
+            (Some(1.0), height),
+            (Some(0.25), height / 4),
+        ] {
+            let img_file = File::open(img_path).unwrap();
+            let mut img_reader = BufReader::new(img_file);
+            let options = ConvertOptions {
+                width: Some(width),
+                cell_aspect: aspect,
+                ..Default::default()
+            };
+            let mut img = Image::with_options(&mut img_reader, options);
+
+            let estimate = img.estimate().unwrap();
+            let text = img.convert_to_ascii().unwrap();
+
+            assert_eq!(estimate.rows, rows);
+            assert_eq!(text.lines().count() as u32, rows);
+            assert!(text.lines().all(|line| line.len() == width as usize));
+        }
+    }
+
+    // Test the [ConvertError::InvalidWidth] failure state of `convert_to_ascii()`.
+    #[test]
+    fn test_invalid_width() {
         let mut buff = Cursor::new(String::new());
-        let mut img = Image::new(&mut buff);
+        let mut img = Image::with_width(&mut buff, 0);
 
-        assert_eq!(img.convert_to_ascii(), Err(ConvertError::DecodeError));
+        assert_eq!(img.convert_to_ascii(), Err(ConvertError::InvalidWidth));
     }
 
-    // Test the successful conversion of an image into ASCII.
+    // Test the [ConvertError::InvalidBudget] failure state of `convert_to_ascii()` and `estimate()`.
     #[test]
-    fn test_convert_to_ascii() {
+    fn test_invalid_budget() {
+        let options = ConvertOptions {
+            max_characters: Some(0),
+            ..Default::default()
+        };
+
+        let mut buff = Cursor::new(String::new());
+        let mut img = Image::with_options(&mut buff, options);
+
+        assert_eq!(img.convert_to_ascii(), Err(ConvertError::InvalidBudget));
+        assert_eq!(img.estimate(), Err(ConvertError::InvalidBudget));
+        assert_eq!(img.warnings(), Err(ConvertError::InvalidBudget));
+    }
+
+    // Test that scaling a large image down and narrowing the text to fit a budget are warned about.
+    #[test]
+    fn test_warnings() {
+        let warnings = |name, width, max_characters| {
+            let img_path = format!("{}/test_assets/images/{}", env!("CARGO_MANIFEST_DIR"), name);
+            let mut img_reader = BufReader::new(File::open(img_path).unwrap());
+            let options = ConvertOptions {
+                width,
+                max_characters,
+                ..Default::default()
+            };
+            let mut img = Image::with_options(&mut img_reader, options);
+            let warnings = img.warnings().unwrap();
+            let text = img.convert_to_ascii().unwrap();
+            (warnings, text.lines().next().unwrap().len() as u32)
+        };
+
+        let (found, width) = warnings("freakazoid-large.png", None, None);
+        assert_eq!(
+            found,
+            vec![ConversionWarning::ImageDownscaled {
+                from: 580,
+                to: width
+            }]
+        );
+
+        // asking for a width is not a surprise
+        assert_eq!(warnings("freakazoid-large.png", Some(40), None).0, vec![]);
+        assert_eq!(warnings("freakazoid-small.png", None, None).0, vec![]);
+
+        let (found, width) = warnings("freakazoid-small.png", None, Some(2_000));
+        assert_eq!(
+            found,
+            vec![ConversionWarning::WidthReducedToBudget {
+                budget: 2_000,
+                width
+            }]
+        );
+        assert_eq!(
+            warnings("freakazoid-small.png", None, Some(u32::MAX)).0,
+            vec![]
+        );
+    }
+
+    // Test that the crop and rotate stages of a pipeline change the image before it is converted.
+    #[test]
+    fn test_convert_to_ascii_with_pipeline() {
         // The idea to use "CARGO_MANIFEST_DIR" comes from StackOverflow:
         // https://stackoverflow.com/questions/30003921/how-can-i-locate-resources-for-testing-with-cargo
         let img_path = concat!(
             env!("CARGO_MANIFEST_DIR"),
             "/test_assets/images/freakazoid-small.png"
         );
+        let ascii_path = concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/test_assets/ascii/freakazoid-small.txt"
+        );
+        let ascii_file =
+            fs::read_to_string(ascii_path).expect("Should have been able to read ASCII file.");
+        let (width, height) = image::image_dimensions(img_path).unwrap();
+
+        // turning the image all the way around and cropping nothing gives the same ASCII
+        let pipeline = Pipeline::new()
+            .rotate(Rotation::Clockwise180)
+            .crop(0, 0, width, height)
+            .rotate(Rotation::Clockwise180);
         let img_file = File::open(img_path).unwrap();
         let mut img_reader = BufReader::new(img_file);
-        let mut img = Image::new(&mut img_reader);
-        let ascii = img.convert_to_ascii();
+        let options = ConvertOptions {
+            pipeline,
+            ..Default::default()
+        };
+        assert_eq!(
+            Image::with_options(&mut img_reader, options).convert_to_ascii(),
+            Ok(ascii_file)
+        );
 
-        assert!(ascii.is_ok());
+        // a quarter turn is estimated and converted with the width and height swapped
+        let pipeline = Pipeline::new()
+            .crop(0, 0, width / 2, height)
+            .rotate(Rotation::Clockwise90);
+        let img_file = File::open(img_path).unwrap();
+        let mut img_reader = BufReader::new(img_file);
+        let options = ConvertOptions {
+            pipeline,
+            ..Default::default()
+        };
+        let mut img = Image::with_options(&mut img_reader, options);
+        let estimate = img.estimate().unwrap();
+        let ascii = img.convert_to_ascii().unwrap();
 
-        let ascii = ascii.unwrap();
+        assert_eq!(estimate.columns, height);
+        assert_eq!(estimate.rows, width / 2 / 2);
+        assert_eq!(ascii.lines().count(), estimate.rows as usize);
+        assert!(ascii.lines().all(|l| l.len() == estimate.columns as usize));
+    }
+
+    // Test that the contrast and dither stages of a pipeline are applied like the options.
+    #[test]
+    fn test_convert_to_ascii_with_pipeline_pixel_stages() {
+        let img = GrayImage::from_fn(64, 32, |x, _| Luma([(x * 2) as u8]));
+        let convert = |options: &ConvertOptions| {
+            let mut buff = Cursor::new(Vec::new());
+            DynamicImage::ImageLuma8(img.clone())
+                .write_to(&mut buff, ImageFormat::Png)
+                .unwrap();
+            buff.rewind().unwrap();
+            Image::with_options(&mut buff, options.clone()).convert_to_ascii()
+        };
+
+        let with_options = ConvertOptions {
+            contrast: ContrastMode::Equalize,
+            dither: DitherMode::Bayer,
+            ..Default::default()
+        };
+        let with_pipeline = ConvertOptions {
+            pipeline: Pipeline::new()
+                .contrast(ContrastMode::Equalize)
+                .dither(DitherMode::Bayer),
+            ..Default::default()
+        };
+
+        assert_eq!(convert(&with_options), convert(&with_pipeline));
+        assert_eq!(
+            with_options.applied_stages(),
+            with_pipeline.applied_stages()
+        );
+    }
+
+    // Test the [ConvertError::InvalidPipeline] failure state of `convert_to_ascii()`.
+    #[test]
+    fn test_invalid_pipeline() {
+        let mut buff = Cursor::new(String::new());
+        let options = ConvertOptions {
+            pipeline: Pipeline::new()
+                .dither(DitherMode::Bayer)
+                .rotate(Rotation::Clockwise90),
+            ..Default::default()
+        };
+        let mut img = Image::with_options(&mut buff, options);
+
+        assert_eq!(img.convert_to_ascii(), Err(ConvertError::InvalidPipeline));
+
+        // The idea to use "CARGO_MANIFEST_DIR" comes from StackOverflow:
+        // https://stackoverflow.com/questions/30003921/how-can-i-locate-resources-for-testing-with-cargo
+        let img_path = concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/test_assets/images/freakazoid-small.png"
+        );
+        let img_file = File::open(img_path).unwrap();
+        let mut img_reader = BufReader::new(img_file);
+        let options = ConvertOptions {
+            pipeline: Pipeline::new().crop(0, 0, 100_000, 1),
+            ..Default::default()
+        };
+        let mut img = Image::with_options(&mut img_reader, options);
+
+        assert_eq!(img.estimate(), Err(ConvertError::InvalidPipeline));
+        assert_eq!(img.convert_to_ascii(), Err(ConvertError::InvalidPipeline));
+    }
+
+    // Converts an image file into text with both `convert_to_ascii()` and `convert_to_ascii_writer()`
+    fn convert_both_ways(path: &str, options: ConvertOptions) -> (String, String) {
+        let mut img_reader = BufReader::new(File::open(path).unwrap());
+        let ascii = Image::with_options(&mut img_reader, options.clone())
+            .convert_to_ascii()
+            .unwrap();
+
+        img_reader.rewind().unwrap();
+        let mut written = Vec::new();
+        Image::with_options(&mut img_reader, options)
+            .convert_to_ascii_writer(&mut written)
+            .unwrap();
+
+        (ascii, String::from_utf8(written).unwrap())
+    }
+
+    // Test that writing the ASCII gives the same text as converting it to a [String].
+    #[test]
+    fn test_convert_to_ascii_writer() {
+        // The idea to use "CARGO_MANIFEST_DIR" comes from StackOverflow:
+        // https://stackoverflow.com/questions/30003921/how-can-i-locate-resources-for-testing-with-cargo
+        let png_path = concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/test_assets/images/freakazoid-large.png"
+        );
+        let jpeg_path = concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/test_assets/images/goldfish.jpeg"
+        );
+
+        let options = [
+            ConvertOptions::default(),
+            ConvertOptions {
+                width: Some(57),
+                contrast: ContrastMode::Equalize,
+                dither: DitherMode::FloydSteinberg,
+                ..Default::default()
+            },
+            ConvertOptions {
+                pipeline: Pipeline::new()
+                    .crop(10, 20, 300, 200)
+                    .rotate(Rotation::Clockwise90),
+                ..Default::default()
+            },
+            ConvertOptions {
+                charset: RenderCharset::Braille,
+                rulers: true,
+                ..Default::default()
+            },
+        ];
+
+        for path in [png_path, jpeg_path] {
+            for options in options.clone() {
+                let (ascii, written) = convert_both_ways(path, options);
+                assert_eq!(written, ascii);
+            }
+        }
+    }
 
+    // Test that a PNG streamed row by row still matches the expected ASCII.
+    #[test]
+    fn test_convert_to_ascii_writer_streams_png() {
         // The idea to use "CARGO_MANIFEST_DIR" comes from StackOverflow:
         // https://stackoverflow.com/questions/30003921/how-can-i-locate-resources-for-testing-with-cargo
+        let img_path = concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/test_assets/images/freakazoid-small.png"
+        );
         let ascii_path = concat!(
             env!("CARGO_MANIFEST_DIR"),
             "/test_assets/ascii/freakazoid-small.txt"
         );
-        let ascii_file =
-            fs::read_to_string(ascii_path).expect("Should have been able to read ASCII file.");
 
-        assert_eq!(ascii, ascii_file);
+        let (_, written) = convert_both_ways(img_path, ConvertOptions::default());
+
+        assert_eq!(written, fs::read_to_string(ascii_path).unwrap());
+
+        // 16-bit pixels are read as big endian
+        let img = ImageBuffer::from_fn(30, 20, |x, y| Luma([(x * 2000 + y * 900) as u16]));
+        let mut png = Cursor::new(Vec::new());
+        DynamicImage::ImageLuma16(img)
+            .write_to(&mut png, ImageFormat::Png)
+            .unwrap();
+
+        png.rewind().unwrap();
+        let ascii = Image::new(&mut png).convert_to_ascii().unwrap();
+        png.rewind().unwrap();
+        let mut written = Vec::new();
+        Image::new(&mut png)
+            .convert_to_ascii_writer(&mut written)
+            .unwrap();
+
+        assert_eq!(String::from_utf8(written).unwrap(), ascii);
+    }
+
+    // Test that grayscale PNGs convert into the same text as the same pixels stored as RGB, with
+    // and without scaling.
+    #[test]
+    fn test_grayscale_png_fast_path() {
+        let gray = GrayImage::from_fn(60, 40, |x, y| Luma([((x * 37 + y * 11) % 256) as u8]));
+        let encode = |img: DynamicImage| {
+            let mut png = Cursor::new(Vec::new());
+            img.write_to(&mut png, ImageFormat::Png).unwrap();
+            png.rewind().unwrap();
+            png
+        };
+        let convert = |png: &Cursor<Vec<u8>>, options: &ConvertOptions| {
+            let ascii = Image::with_options(&mut png.clone(), options.clone())
+                .convert_to_ascii()
+                .unwrap();
+            let mut written = Vec::new();
+            Image::with_options(&mut png.clone(), options.clone())
+                .convert_to_ascii_writer(&mut written)
+                .unwrap();
+            assert_eq!(String::from_utf8(written).unwrap(), ascii);
+            ascii
+        };
+
+        let luma_png = encode(DynamicImage::ImageLuma8(gray.clone()));
+        let rgb_png = encode(DynamicImage::ImageRgb8(
+            DynamicImage::ImageLuma8(gray).to_rgb8(),
+        ));
+
+        for options in [
+            ConvertOptions::default(),
+            ConvertOptions {
+                width: Some(23),
+                ..Default::default()
+            },
+            ConvertOptions {
+                charset: RenderCharset::Braille,
+                ..Default::default()
+            },
+        ] {
+            assert_eq!(convert(&luma_png, &options), convert(&rgb_png, &options));
+        }
+
+        let luma_image = Image::new(&mut luma_png.clone())
+            .convert_to_ascii_image(false)
+            .unwrap();
+        let rgb_image = Image::new(&mut rgb_png.clone())
+            .convert_to_ascii_image(false)
+            .unwrap();
+        assert_eq!(luma_image.into_inner(), rgb_image.into_inner());
+    }
+
+    // Test the failure states of `convert_to_ascii_writer()`.
+    #[test]
+    fn test_convert_to_ascii_writer_errors() {
+        // The idea to use "CARGO_MANIFEST_DIR" comes from StackOverflow:
+        // https://stackoverflow.com/questions/30003921/how-can-i-locate-resources-for-testing-with-cargo
+        let img_path = concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/test_assets/images/freakazoid-small.png"
+        );
+        let mut img_reader = BufReader::new(File::open(img_path).unwrap());
+        let mut full = [0_u8; 8];
+
+        assert!(matches!(
+            Image::new(&mut img_reader).convert_to_ascii_writer(&mut &mut full[..]),
+            Err(ConvertError::WriteError(_))
+        ));
+
+        img_reader.rewind().unwrap();
+        assert_eq!(
+            Image::with_width(&mut img_reader, 0).convert_to_ascii_writer(&mut Vec::new()),
+            Err(ConvertError::InvalidWidth)
+        );
+
+        let mut bad_image = BadImage;
+        assert!(matches!(
+            Image::new(&mut bad_image).convert_to_ascii_writer(&mut Vec::new()),
+            Err(ConvertError::ReadError(_))
+        ));
+    }
+
+    // Test that images and text larger than the size limits are rejected before the image is
+    // decoded.
+    #[test]
+    fn test_size_limits() {
+        // The idea to use "CARGO_MANIFEST_DIR" comes from StackOverflow:
+        // https://stackoverflow.com/questions/30003921/how-can-i-locate-resources-for-testing-with-cargo
+        let img_path = concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/test_assets/images/freakazoid-small.png"
+        );
+        let mut img_reader = BufReader::new(File::open(img_path).unwrap());
+        let dimension = Image::new(&mut img_reader).dimensions().unwrap();
+        let too_large = ConvertError::TooLarge {
+            width: dimension.width,
+            height: dimension.height,
+        };
+
+        let options = ConvertOptions {
+            limits: SizeLimits {
+                max_pixels: u64::from(dimension.width) * u64::from(dimension.height) - 1,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        assert_eq!(
+            Image::with_options(&mut img_reader, options.clone())
+                .convert_to_ascii()
+                .unwrap_err(),
+            too_large
+        );
+        assert_eq!(
+            Image::with_options(&mut img_reader, options.clone())
+                .convert_to_ascii_writer(&mut Vec::new())
+                .unwrap_err(),
+            too_large
+        );
+        assert_eq!(
+            Image::with_options(&mut img_reader, options)
+                .prepare()
+                .map(|prepared| prepared.dimension())
+                .unwrap_err(),
+            too_large
+        );
+
+        let options = ConvertOptions {
+            width: Some(DEFAULT_MAX_COLUMNS + 1),
+            ..Default::default()
+        };
+        let rows = Image::with_options(&mut img_reader, options.clone())
+            .estimate()
+            .unwrap()
+            .rows;
+        let too_wide = ConvertError::TooLarge {
+            width: DEFAULT_MAX_COLUMNS + 1,
+            height: rows,
+        };
+        assert_eq!(
+            Image::with_options(&mut img_reader, options.clone())
+                .convert_to_ascii()
+                .unwrap_err(),
+            too_wide
+        );
+        let prepared = Image::new(&mut img_reader).prepare().unwrap();
+        assert_eq!(prepared.convert(&options).unwrap_err(), too_wide);
     }
 }
@@ -0,0 +1,287 @@
+//! Conversion manifests.
+//!
+//! A [Manifest] records every setting of a [ConvertOptions] as a small JSON document, so a
+//! conversion can be shared and run again with exactly the same settings. The symbol map is
+//! stored as its brightness ramp, along with the measured brightness of each symbol for maps
+//! such as [SymbolMap::printable], and settings that are left out of a manifest keep their
+//! default values. Each manifest records the [MANIFEST_VERSION] it was written with, and
+//! manifests from a newer version are rejected rather than silently converted differently.
+//!
+//! An example manifest:
+//!
+//! ```json
+//! {"version":1,"width":80,"charset":"ascii","symbols":"@%#*+=-:. ","contrast":"equalize",
+//!  "dither":"none","rulers":false,"pipeline":[{"rotate":"clockwise90"}]}
+//! ```
+//!
+//! Robert Peterson and Kelsey Werner 2023
+
+use crate::converter::{
+    dimension::Rect,
+    options::{ContrastMode, ConvertOptions, DitherMode, RenderCharset, SizeLimits},
+    pipeline::Pipeline,
+    symbol_map::SymbolMap,
+    ConvertError,
+};
+use serde::{Deserialize, Serialize};
+
+/// The version of the manifest format written by this version of the converter.
+pub const MANIFEST_VERSION: u32 = 1;
+
+/// [Manifest] is a struct that holds every setting of a conversion in a form that can be
+/// written as JSON.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Manifest {
+    /// The version of the manifest format.
+    pub version: u32,
+    /// The number of characters per line, or [None] to let the converter pick.
+    pub width: Option<u32>,
+    /// The most lines the text may have, or [None] for no limit.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub height: Option<u32>,
+    /// The most characters the text may have, or [None] for no limit.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_characters: Option<u32>,
+    /// The set of characters used to render the image.
+    pub charset: RenderCharset,
+    /// The brightness ramp of the [SymbolMap], from darkest to lightest.
+    pub symbols: String,
+    /// The measured brightness of each of the symbols, or [None] when they are spread evenly.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub levels: Option<Vec<u8>>,
+    /// The amount added to the brightness of each pixel.
+    #[serde(skip_serializing_if = "is_default")]
+    pub brightness: i16,
+    /// The percent the contrast of the pixels is raised by.
+    #[serde(skip_serializing_if = "is_default")]
+    pub contrast_adjustment: f32,
+    /// The brightness adjustment applied before pixels are mapped to characters.
+    pub contrast: ContrastMode,
+    /// The dithering applied before pixels are mapped to characters.
+    pub dither: DitherMode,
+    /// The width of a character cell divided by its height, or [None] for the default.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cell_aspect: Option<f32>,
+    /// Whether line numbers and column rulers are added.
+    pub rulers: bool,
+    /// Whether the text starts with a header of metadata.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub metadata: bool,
+    /// Whether the orientation in the EXIF metadata of the image is ignored.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub ignore_exif_orientation: bool,
+    /// The region of the image that is converted, or [None] for the whole image.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub crop: Option<Rect>,
+    /// The stages applied to the image before the contrast and dither.
+    pub pipeline: Pipeline,
+}
+
+impl Manifest {
+    /// Read a [Manifest] from JSON.
+    ///
+    /// [ConvertError::InvalidManifest] is returned if the JSON can't be parsed, has settings
+    /// that don't exist, or is from a newer [MANIFEST_VERSION].
+    pub fn from_json(json: &str) -> Result<Manifest, ConvertError> {
+        let manifest: Manifest =
+            serde_json::from_str(json).map_err(|_| ConvertError::InvalidManifest)?;
+
+        if manifest.version > MANIFEST_VERSION {
+            return Err(ConvertError::InvalidManifest);
+        }
+
+        Ok(manifest)
+    }
+
+    /// Write the [Manifest] as compact JSON.
+    pub fn to_json(&self) -> String {
+        // every setting of a manifest can be written as JSON
+        serde_json::to_string(self).unwrap_or_default()
+    }
+
+    /// Turn the [Manifest] back into the [ConvertOptions] it records.
+    ///
+    /// [ConvertError::EmptySymbolRamp] or [ConvertError::DuplicateSymbol] is returned if the
+    /// symbols can't be made into a [SymbolMap], and [ConvertError::InvalidManifest] if there
+    /// isn't a level for each symbol. The rest of the settings are checked when the image is
+    /// converted.
+    pub fn to_options(&self) -> Result<ConvertOptions, ConvertError> {
+        let symbol_map = match &self.levels {
+            Some(levels) if levels.len() == self.symbols.chars().count() => {
+                let levels: Vec<(char, u8)> =
+                    self.symbols.chars().zip(levels.iter().copied()).collect();
+                SymbolMap::from_levels(&levels)?
+            }
+            Some(_) => return Err(ConvertError::InvalidManifest),
+            None => SymbolMap::from_ramp(&self.symbols)?,
+        };
+
+        Ok(ConvertOptions {
+            width: self.width,
+            height: self.height,
+            max_characters: self.max_characters,
+            charset: self.charset,
+            symbol_map,
+            brightness: self.brightness,
+            contrast_adjustment: self.contrast_adjustment,
+            contrast: self.contrast,
+            dither: self.dither,
+            cell_aspect: self.cell_aspect,
+            rulers: self.rulers,
+            metadata: self.metadata,
+            ignore_exif_orientation: self.ignore_exif_orientation,
+            crop: self.crop,
+            pipeline: self.pipeline.clone(),
+            limits: SizeLimits::default(),
+        })
+    }
+}
+
+/// Check if a setting has its default value, so that it is left out of the JSON.
+fn is_default<T: Default + PartialEq>(value: &T) -> bool {
+    *value == T::default()
+}
+
+impl Default for Manifest {
+    /// The default [Manifest] records the default [ConvertOptions].
+    fn default() -> Self {
+        Manifest::from(&ConvertOptions::default())
+    }
+}
+
+impl From<&ConvertOptions> for Manifest {
+    /// Record the settings of a [ConvertOptions].
+    fn from(options: &ConvertOptions) -> Self {
+        Manifest {
+            version: MANIFEST_VERSION,
+            width: options.width,
+            height: options.height,
+            max_characters: options.max_characters,
+            charset: options.charset,
+            symbols: options.symbol_map.ramp(),
+            levels: options.symbol_map.levels().map(<[u8]>::to_vec),
+            brightness: options.brightness,
+            contrast_adjustment: options.contrast_adjustment,
+            contrast: options.contrast,
+            dither: options.dither,
+            cell_aspect: options.cell_aspect,
+            rulers: options.rulers,
+            metadata: options.metadata,
+            ignore_exif_orientation: options.ignore_exif_orientation,
+            crop: options.crop,
+            pipeline: options.pipeline.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::converter::pipeline::Rotation;
+
+    // Test that options written to a manifest are read back the same.
+    #[test]
+    fn test_round_trip() {
+        let options = ConvertOptions {
+            width: Some(80),
+            height: Some(24),
+            max_characters: None,
+            charset: RenderCharset::Braille,
+            symbol_map: SymbolMap::from_ramp("@%#*+=-:. ").unwrap(),
+            brightness: -20,
+            contrast_adjustment: 35.5,
+            contrast: ContrastMode::Gamma(0.5),
+            dither: DitherMode::Bayer,
+            cell_aspect: Some(0.25),
+            rulers: true,
+            metadata: true,
+            ignore_exif_orientation: true,
+            crop: Some(Rect::new(2, 4, 16, 12)),
+            pipeline: Pipeline::new()
+                .crop(0, 0, 8, 8)
+                .rotate(Rotation::Clockwise90),
+            limits: SizeLimits::default(),
+        };
+
+        let json = Manifest::from(&options).to_json();
+        assert_eq!(
+            json,
+            r#"{"version":1,"width":80,"height":24,"charset":"braille","symbols":"@%#*+=-:. ","brightness":-20,"contrast_adjustment":35.5,"contrast":{"gamma":0.5},"dither":"bayer","cell_aspect":0.25,"rulers":true,"metadata":true,"ignore_exif_orientation":true,"crop":{"x":2,"y":4,"width":16,"height":12},"pipeline":[{"crop":{"x":0,"y":0,"width":8,"height":8}},{"rotate":"clockwise90"}]}"#
+        );
+        assert_eq!(
+            Manifest::from_json(&json).unwrap().to_options(),
+            Ok(options)
+        );
+    }
+
+    // Test that the measured brightness of the symbols is written and read back.
+    #[test]
+    fn test_round_trip_levels() {
+        let options = ConvertOptions {
+            symbol_map: SymbolMap::from_levels(&[('@', 0), ('=', 141), (' ', 255)]).unwrap(),
+            ..Default::default()
+        };
+
+        let json = Manifest::from(&options).to_json();
+        assert!(json.contains(r#""symbols":"@= ","levels":[0,141,255]"#));
+        assert_eq!(
+            Manifest::from_json(&json).unwrap().to_options(),
+            Ok(options)
+        );
+
+        let printable = ConvertOptions {
+            symbol_map: SymbolMap::printable(),
+            ..Default::default()
+        };
+        assert_eq!(Manifest::from(&printable).to_options(), Ok(printable));
+    }
+
+    // Test that settings left out of a manifest keep their defaults.
+    #[test]
+    fn test_defaults() {
+        let manifest = Manifest::from_json(r#"{"width":40}"#).unwrap();
+
+        assert_eq!(
+            manifest.to_options(),
+            Ok(ConvertOptions {
+                width: Some(40),
+                ..Default::default()
+            })
+        );
+        assert_eq!(
+            Manifest::from_json("{}").unwrap().to_options(),
+            Ok(ConvertOptions::default())
+        );
+
+        let manifest = Manifest::from_json(r#"{"max_characters":10000}"#).unwrap();
+        assert_eq!(manifest.max_characters, Some(10_000));
+        assert_eq!(Manifest::from_json(&manifest.to_json()).unwrap(), manifest);
+    }
+
+    // Test that invalid manifests are rejected.
+    #[test]
+    fn test_invalid() {
+        for json in [
+            "",
+            "true",
+            r#"{"width":"wide"}"#,
+            r#"{"colour":true}"#,
+            r#"{"version":2}"#,
+        ] {
+            assert_eq!(
+                Manifest::from_json(json),
+                Err(ConvertError::InvalidManifest)
+            );
+        }
+
+        let manifest = Manifest::from_json(r#"{"symbols":"@@"}"#).unwrap();
+        assert_eq!(
+            manifest.to_options(),
+            Err(ConvertError::DuplicateSymbol('@'))
+        );
+
+        let manifest = Manifest::from_json(r#"{"symbols":"@. ","levels":[0,255]}"#).unwrap();
+        assert_eq!(manifest.to_options(), Err(ConvertError::InvalidManifest));
+    }
+}
@@ -0,0 +1,214 @@
+//! Metadata about where converted text and images came from.
+//!
+//! Text converted from an image with the `metadata` option of [ConvertOptions] starts with a
+//! header comment block that records the size of the image and the settings of the conversion:
+//!
+//! ```text
+//! # ascii-art-converter
+//! # source: 640x480 pixels
+//! # settings: {"version":1,"width":80,"charset":"ascii",...}
+//!
+//! ```
+//!
+//! Each line of the header starts with [COMMENT_PREFIX], the settings are the JSON of a
+//! [Manifest] so that the conversion can be run again, and a blank line separates the header
+//! from the art. [strip_metadata_header] gives back the art, and ASCII that starts with a header
+//! is drawn into an image without it.
+//!
+//! In the other direction, the [PngMetadata] of ASCII that is drawn into a PNG is written into
+//! the text chunks of the PNG, along with the SHA-256 hash of the ASCII it was drawn from, so the
+//! PNG can be matched with its source.
+//!
+//! Robert Peterson and Kelsey Werner 2023
+
+use crate::converter::{
+    dimension::Dimension, manifest::Manifest, options::ConvertOptions, png_text::add_text_chunks,
+    ConvertError,
+};
+use sha2::{Digest, Sha256};
+
+/// The first line of a metadata header, which marks the text as having one.
+pub const HEADER_START: &str = "# ascii-art-converter";
+
+/// The characters every line of a metadata header starts with.
+pub const COMMENT_PREFIX: &str = "# ";
+
+/// The keyword of the PNG text chunk that holds the hash of the ASCII the PNG was drawn from.
+pub const SOURCE_HASH_KEYWORD: &str = "Source ASCII SHA-256";
+
+/// Write the metadata header of text converted from an image that is `source` pixels, with the
+/// given [ConvertOptions].
+///
+/// The header ends with the blank line that separates it from the art.
+pub fn metadata_header(source: Dimension, options: &ConvertOptions) -> String {
+    format!(
+        "{}\n{}source: {}x{} pixels\n{}settings: {}\n\n",
+        HEADER_START,
+        COMMENT_PREFIX,
+        source.width,
+        source.height,
+        COMMENT_PREFIX,
+        Manifest::from(options).to_json()
+    )
+}
+
+/// Put the metadata header in front of text converted from an image that is `source` pixels, if
+/// the [ConvertOptions] ask for it.
+pub(crate) fn with_metadata(text: String, source: Dimension, options: &ConvertOptions) -> String {
+    if options.metadata {
+        metadata_header(source, options) + &text
+    } else {
+        text
+    }
+}
+
+/// Remove the metadata header that [metadata_header] wrote from the start of `text`.
+///
+/// Returns [None] if `text` does not start with a header. Headers written with Windows line
+/// endings are removed as well.
+pub fn strip_metadata_header(text: &str) -> Option<&str> {
+    let mut lines = text.split_inclusive('\n');
+    let first = lines.next()?;
+    if first.trim_end_matches(['\r', '\n']) != HEADER_START {
+        return None;
+    }
+
+    let mut offset = first.len();
+    for line in lines {
+        offset += line.len();
+        let line = line.trim_end_matches(['\r', '\n']);
+        if line.is_empty() {
+            return Some(&text[offset..]);
+        }
+        if !line.starts_with(COMMENT_PREFIX) {
+            return None;
+        }
+    }
+
+    None
+}
+
+/// Calculate the SHA-256 hash of ASCII as 64 lowercase hex digits.
+pub fn ascii_hash(ascii: &str) -> String {
+    Sha256::digest(ascii.as_bytes())
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+/// [PngMetadata] is a struct that holds the text written into a PNG drawn from ASCII.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct PngMetadata {
+    /// The title of the art, or [None] to leave it out.
+    pub title: Option<String>,
+    /// The author of the art, or [None] to leave it out.
+    pub author: Option<String>,
+}
+
+impl PngMetadata {
+    /// Write the title and author into text chunks of the encoded PNG in `png`, along with the
+    /// hash of the `ascii` it was drawn from under [SOURCE_HASH_KEYWORD].
+    ///
+    /// Returns [ConvertError::DecodeError] when `png` is not a PNG file.
+    pub fn add_to_png(&self, png: &[u8], ascii: &str) -> Result<Vec<u8>, ConvertError> {
+        let hash = ascii_hash(ascii);
+        let mut entries: Vec<(&str, &str)> = Vec::new();
+        if let Some(title) = &self.title {
+            entries.push(("Title", title));
+        }
+        if let Some(author) = &self.author {
+            entries.push(("Author", author));
+        }
+        entries.push((SOURCE_HASH_KEYWORD, &hash));
+
+        add_text_chunks(png, &entries)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::converter::options::RenderCharset;
+
+    // Test that the header records the size of the image and the settings as a manifest.
+    #[test]
+    fn test_metadata_header() {
+        let options = ConvertOptions {
+            width: Some(40),
+            charset: RenderCharset::Braille,
+            metadata: true,
+            ..Default::default()
+        };
+        let header = metadata_header(Dimension::from((640, 480)), &options);
+        let lines: Vec<&str> = header.lines().collect();
+
+        assert_eq!(lines[0], HEADER_START);
+        assert_eq!(lines[1], "# source: 640x480 pixels");
+        let settings = lines[2].strip_prefix("# settings: ").unwrap();
+        assert_eq!(
+            Manifest::from_json(settings).unwrap().to_options(),
+            Ok(options)
+        );
+        assert_eq!(lines[3], "");
+        assert_eq!(lines.len(), 4);
+    }
+
+    // Test that the header is only added when the options ask for it, and that it can be removed again.
+    #[test]
+    fn test_strip_metadata_header() {
+        let art = "$$$\n. .\n";
+        let options = ConvertOptions {
+            metadata: true,
+            ..Default::default()
+        };
+
+        let with_header = with_metadata(art.to_string(), Dimension::from((3, 4)), &options);
+        assert_eq!(strip_metadata_header(&with_header), Some(art));
+        assert_eq!(
+            strip_metadata_header(&with_header.replace('\n', "\r\n")),
+            Some(art.replace('\n', "\r\n").as_str())
+        );
+        assert_eq!(
+            with_metadata(
+                art.to_string(),
+                Dimension::from((3, 4)),
+                &Default::default()
+            ),
+            art
+        );
+
+        assert_eq!(strip_metadata_header(art), None);
+        assert_eq!(strip_metadata_header("# ascii-art-converter\n$$$\n"), None);
+        assert_eq!(
+            strip_metadata_header("# ascii-art-converter\n# source"),
+            None
+        );
+    }
+
+    // Test that the title, author and hash of the ASCII are written into text chunks of a PNG.
+    #[test]
+    fn test_add_to_png() {
+        let png = crate::ascii_to_image("$$$\n").unwrap().into_inner();
+        let metadata = PngMetadata {
+            title: Some("Castle".to_string()),
+            author: None,
+        };
+        let tagged = metadata.add_to_png(&png, "$$$\n").unwrap();
+
+        let contains = |needle: &[u8]| tagged.windows(needle.len()).any(|w| w == needle);
+        assert!(contains(b"tEXtTitle\0Castle"));
+        assert!(!contains(b"Author"));
+        assert!(contains(
+            format!("tEXt{}\0{}", SOURCE_HASH_KEYWORD, ascii_hash("$$$\n")).as_bytes()
+        ));
+        assert_eq!(
+            ascii_hash(""),
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
+
+        assert!(matches!(
+            metadata.add_to_png(b"not a png", "$$$\n"),
+            Err(ConvertError::DecodeError(_))
+        ));
+    }
+}
@@ -0,0 +1,335 @@
+//! Normalization of pasted ASCII art.
+//!
+//! ASCII art copied from web pages, word processors, or email often picks up a byte-order
+//! mark or Unicode whitespace such as non-breaking spaces. These characters look like
+//! ordinary spaces but are not ASCII, so this module maps them back to their ASCII
+//! equivalents before the ASCII is converted. Email clients and browsers may also
+//! soft-wrap long lines of ASCII art, which this module can detect and repair.
+//! [normalize_ascii_with_warnings] also reports what was changed as [ConversionWarning]s.
+//! [normalize_whitespace] turns Windows line endings and tabs into the line breaks and spaces
+//! that take up one row and one column each, so the lines of the art line up when it is drawn.
+//!
+//! Robert Peterson and Kelsey Werner 2023
+
+use crate::converter::warning::ConversionWarning;
+use std::borrow::Cow;
+
+/// The byte-order mark, which is also used as a zero-width no-break space.
+const BYTE_ORDER_MARK: char = '\u{FEFF}';
+
+/// Invisible characters that are dropped entirely during normalization.
+///
+/// These are not considered whitespace by [char::is_whitespace] but are commonly
+/// inserted by editors and browsers.
+const ZERO_WIDTH: [char; 4] = [BYTE_ORDER_MARK, '\u{200B}', '\u{200C}', '\u{200D}'];
+
+/// Unicode line breaks that are turned into `\n` during normalization.
+const LINE_BREAKS: [char; 3] = ['\u{0085}', '\u{2028}', '\u{2029}'];
+
+/// Normalize a pasted ASCII string.
+///
+/// The byte-order mark and other zero-width characters are removed, Unicode line breaks are
+/// turned into `\n`, and every other non-ASCII whitespace character (for example U+00A0, the
+/// non-breaking space) is turned into an ASCII space. All other characters are left alone.
+pub fn normalize_ascii(ascii: &str) -> String {
+    normalize_ascii_with_warnings(ascii).0
+}
+
+/// Normalize a pasted ASCII string like [normalize_ascii], along with a [ConversionWarning] for
+/// each kind of character that was removed or replaced.
+///
+/// No warnings are returned when the ASCII did not need to be changed.
+pub fn normalize_ascii_with_warnings(ascii: &str) -> (String, Vec<ConversionWarning>) {
+    let (mut removed, mut line_breaks, mut spaces) = (0, 0, 0);
+    let normalized = ascii
+        .chars()
+        .filter(|c| {
+            let invisible = ZERO_WIDTH.contains(c);
+            removed += invisible as usize;
+            !invisible
+        })
+        .map(|c| {
+            if c.is_ascii() || !c.is_whitespace() {
+                c
+            } else if LINE_BREAKS.contains(&c) {
+                line_breaks += 1;
+                '\n'
+            } else {
+                spaces += 1;
+                ' '
+            }
+        })
+        .collect();
+
+    let warnings = [
+        (
+            removed,
+            ConversionWarning::InvisibleCharactersRemoved(removed),
+        ),
+        (
+            line_breaks,
+            ConversionWarning::LineBreaksReplaced(line_breaks),
+        ),
+        (spaces, ConversionWarning::SpacesReplaced(spaces)),
+    ]
+    .into_iter()
+    .filter(|(count, _)| *count > 0)
+    .map(|(_, warning)| warning)
+    .collect();
+
+    (normalized, warnings)
+}
+
+/// The number of columns between tab stops that tabs are expanded to, unless another is picked.
+pub const DEFAULT_TAB_WIDTH: u32 = 8;
+
+/// [WhitespaceOptions] is a struct that holds how the line endings, tabs and trailing spaces of
+/// ASCII art are normalized by [normalize_whitespace].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WhitespaceOptions {
+    /// The number of columns between tab stops. Each tab is expanded into the spaces up to the
+    /// next tab stop, and tabs are removed when it is 0.
+    pub tab_width: u32,
+    /// Remove the spaces at the end of each line, which makes the art as narrow as its longest
+    /// line of symbols.
+    pub trim_trailing: bool,
+}
+
+impl Default for WhitespaceOptions {
+    fn default() -> WhitespaceOptions {
+        WhitespaceOptions {
+            tab_width: DEFAULT_TAB_WIDTH,
+            trim_trailing: false,
+        }
+    }
+}
+
+/// Normalize the line endings, tabs and trailing spaces of ASCII art.
+///
+/// Windows (`\r\n`) and old Mac (`\r`) line endings are turned into `\n`, tabs are expanded
+/// into spaces up to the next tab stop, and the spaces at the end of each line are removed if the
+/// [WhitespaceOptions] ask for it. Afterwards every character of a line takes up exactly one
+/// column. ASCII that doesn't need to be changed is borrowed as is.
+pub fn normalize_whitespace(ascii: &str, options: WhitespaceOptions) -> Cow<'_, str> {
+    let trailing_spaces = options.trim_trailing && ascii.lines().any(|line| line.ends_with(' '));
+    if !trailing_spaces && !ascii.contains(['\r', '\t']) {
+        return Cow::Borrowed(ascii);
+    }
+
+    let ascii = ascii.replace("\r\n", "\n").replace('\r', "\n");
+    let lines: Vec<String> = ascii
+        .split('\n')
+        .map(|line| {
+            let mut expanded = String::with_capacity(line.len());
+            let mut column = 0;
+            for c in line.chars() {
+                if c != '\t' {
+                    expanded.push(c);
+                    column += 1;
+                } else if options.tab_width > 0 {
+                    let spaces = options.tab_width - column % options.tab_width;
+                    expanded.extend(std::iter::repeat_n(' ', spaces as usize));
+                    column += spaces;
+                }
+            }
+            if options.trim_trailing {
+                expanded.truncate(expanded.trim_end_matches(' ').len());
+            }
+            expanded
+        })
+        .collect();
+
+    Cow::Owned(lines.join("\n"))
+}
+
+/// The smallest line width that is considered to be the result of soft-wrapping.
+///
+/// Soft-wrapping happens at widths like 72 or 80 characters, so short lines are never
+/// treated as wrapped. This keeps small pieces of art from being "repaired" by accident.
+const MIN_SOFT_WRAP_WIDTH: usize = 20;
+
+/// The smallest number of wrapped line pairs needed before soft-wrapping is detected.
+const MIN_SOFT_WRAP_PAIRS: usize = 2;
+
+/// Detect ASCII art that was soft-wrapped and rejoin the wrapped lines.
+///
+/// Soft-wrapped art has a distinctive pattern of alternating long and short lines: every
+/// original line was split into a line that is exactly the wrap width long, followed by
+/// a shorter line holding the rest. When every pair of lines follows this pattern, the
+/// pairs are rejoined and the repaired ASCII is returned. Otherwise [None] is returned and
+/// the ASCII should be used as is.
+pub fn rejoin_soft_wraps(ascii: &str) -> Option<String> {
+    let lines: Vec<&str> = ascii.lines().collect();
+    let wrap_width = lines.first()?.len();
+
+    if wrap_width < MIN_SOFT_WRAP_WIDTH || lines.len() < MIN_SOFT_WRAP_PAIRS * 2 {
+        return None;
+    }
+
+    let is_wrapped = lines.chunks(2).all(|pair| match pair {
+        [long, short] => long.len() == wrap_width && !short.is_empty() && short.len() < wrap_width,
+        // a last line that is not part of a pair must fit within the wrap width
+        [last] => last.len() <= wrap_width,
+        _ => false,
+    });
+
+    if !is_wrapped {
+        return None;
+    }
+
+    let mut repaired = lines
+        .chunks(2)
+        .map(|pair| pair.concat())
+        .collect::<Vec<_>>()
+        .join("\n");
+    if ascii.ends_with('\n') {
+        repaired.push('\n');
+    }
+
+    Some(repaired)
+}
+
+// Test that a leading byte-order mark is removed.
+#[test]
+fn test_strip_byte_order_mark() {
+    assert_eq!(normalize_ascii("\u{FEFF}@@\n.."), "@@\n..");
+}
+
+// Test that Unicode spaces are mapped to ASCII spaces.
+#[test]
+fn test_unicode_spaces() {
+    assert_eq!(normalize_ascii("@\u{00A0}@\u{2003}@\u{3000}@"), "@ @ @ @");
+}
+
+// Test that Unicode line breaks and zero-width characters are handled.
+#[test]
+fn test_line_breaks_and_zero_width() {
+    assert_eq!(normalize_ascii("@\u{2028}.\u{200B}."), "@\n..");
+}
+
+// Test that ASCII and other non-ASCII characters are not changed.
+#[test]
+fn test_untouched_characters() {
+    assert_eq!(normalize_ascii("$@B%8&\r\n\t "), "$@B%8&\r\n\t ");
+    assert_eq!(normalize_ascii("£¥€"), "£¥€");
+}
+
+// Test that the characters that were removed or replaced are counted.
+#[test]
+fn test_normalize_ascii_with_warnings() {
+    assert_eq!(
+        normalize_ascii_with_warnings("\u{FEFF}@\u{00A0}@\u{2003}\u{2028}.\u{200B}."),
+        (
+            "@ @ \n..".to_string(),
+            vec![
+                ConversionWarning::InvisibleCharactersRemoved(2),
+                ConversionWarning::LineBreaksReplaced(1),
+                ConversionWarning::SpacesReplaced(2),
+            ]
+        )
+    );
+    assert_eq!(
+        normalize_ascii_with_warnings("@@\n.."),
+        ("@@\n..".to_string(), vec![])
+    );
+}
+
+// Test that line endings become `\n` and tabs are expanded up to the next tab stop.
+#[test]
+fn test_normalize_whitespace() {
+    let options = WhitespaceOptions::default();
+    assert_eq!(
+        normalize_whitespace("@@\r\n..\r$$\r\n", options),
+        "@@\n..\n$$\n"
+    );
+    assert_eq!(
+        normalize_whitespace("\t@\n.\t@", options),
+        "        @\n.       @"
+    );
+    assert_eq!(
+        normalize_whitespace(
+            "@\t.\t",
+            WhitespaceOptions {
+                tab_width: 4,
+                ..options
+            }
+        ),
+        "@   .   "
+    );
+    assert_eq!(
+        normalize_whitespace(
+            "@\t.",
+            WhitespaceOptions {
+                tab_width: 0,
+                ..options
+            }
+        ),
+        "@."
+    );
+    assert!(matches!(
+        normalize_whitespace("@@ \n..", options),
+        Cow::Borrowed("@@ \n..")
+    ));
+}
+
+// Test that trailing spaces, including expanded tabs, are only removed when asked for.
+#[test]
+fn test_normalize_whitespace_trim_trailing() {
+    let options = WhitespaceOptions {
+        trim_trailing: true,
+        ..Default::default()
+    };
+    assert_eq!(normalize_whitespace("@@  \r\n .\t\n", options), "@@\n .\n");
+    assert_eq!(normalize_whitespace(" @ \n", options), " @\n");
+    assert_eq!(
+        normalize_whitespace("@@  \n", WhitespaceOptions::default()),
+        "@@  \n"
+    );
+}
+
+// Test that soft-wrapped lines are rejoined.
+#[test]
+fn test_rejoin_soft_wraps() {
+    let long = "@".repeat(MIN_SOFT_WRAP_WIDTH);
+    let wrapped = format!("{long}\n..\n{long}\n.\n{long}\n...", long = long);
+    assert_eq!(
+        rejoin_soft_wraps(&wrapped),
+        Some(format!("{long}..\n{long}.\n{long}...", long = long))
+    );
+
+    // a last line that was not wrapped is kept
+    let wrapped = format!("{long}\n..\n{long}\n.\n..", long = long);
+    assert_eq!(
+        rejoin_soft_wraps(&wrapped),
+        Some(format!("{long}..\n{long}.\n..", long = long))
+    );
+
+    // a trailing newline is kept
+    let wrapped = format!("{long}\n..\n{long}\n.\n", long = long);
+    assert_eq!(
+        rejoin_soft_wraps(&wrapped),
+        Some(format!("{long}..\n{long}.\n", long = long))
+    );
+}
+
+// Test that art which was not soft-wrapped is left alone.
+#[test]
+fn test_rejoin_soft_wraps_not_wrapped() {
+    let long = "@".repeat(MIN_SOFT_WRAP_WIDTH);
+
+    // lines are all the same length
+    assert_eq!(
+        rejoin_soft_wraps(&format!("{long}\n{long}\n{long}\n{long}", long = long)),
+        None
+    );
+    // lines are too short to be soft-wrapped
+    assert_eq!(rejoin_soft_wraps("@@@\n.\n@@@\n."), None);
+    // only a single pair of lines
+    assert_eq!(rejoin_soft_wraps(&format!("{}\n.", long)), None);
+    // a short line is followed by another short line
+    assert_eq!(
+        rejoin_soft_wraps(&format!("{long}\n.\n..\n.", long = long)),
+        None
+    );
+    assert_eq!(rejoin_soft_wraps(""), None);
+}
@@ -0,0 +1,335 @@
+//! Options for image to ASCII conversion.
+//!
+//! The [ImageRenderMode] picks how ASCII is drawn when it is converted back into an image, and
+//! the [UnknownSymbolPolicy] picks what happens to characters that are not in the [SymbolMap].
+//! The [ConvertOptions] struct collects the settings that control how an image is
+//! turned into text, such as the width of the output or a budget of characters it
+//! has to fit in, the [RenderCharset] used
+//! to draw it, the [SymbolMap] used by the ASCII charset, the shape of its character cells,
+//! a brightness and contrast adjustment, the [ContrastMode], and the [DitherMode]. A region of the image to convert and a [Pipeline]
+//! of preprocessing stages can be given as well, along with the [SizeLimits] that guard the
+//! converter against images that decode to gigantic pixel buffers.
+//!
+//! Robert Peterson and Kelsey Werner 2023
+
+use crate::converter::{
+    dimension::{Dimension, Rect},
+    pipeline::{Pipeline, Stage},
+    symbol_map::SymbolMap,
+    ConvertError,
+};
+use image::DynamicImage;
+use serde::{Deserialize, Serialize};
+
+/// Represent the sets of characters an image can be rendered with.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RenderCharset {
+    /// [RenderCharset::Ascii] maps each pixel to a symbol from the ASCII symbol map.
+    #[default]
+    Ascii,
+    /// [RenderCharset::Braille] packs 2x4 pixel blocks into a single braille character
+    /// from the U+2800 Unicode block, giving much higher effective resolution.
+    Braille,
+    /// [RenderCharset::HalfBlock] renders each vertical pair of pixels as a `▀` character
+    /// with 24-bit ANSI foreground and background colors. The output is meant for terminals.
+    HalfBlock,
+}
+
+/// Represent the ways an image can be dithered before it is turned into text.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DitherMode {
+    /// [DitherMode::None] maps each pixel straight to its nearest level.
+    #[default]
+    None,
+    /// [DitherMode::FloydSteinberg] spreads the error of each pixel onto its neighbors.
+    FloydSteinberg,
+    /// [DitherMode::Bayer] offsets each pixel using a repeating 4x4 threshold pattern.
+    Bayer,
+}
+
+/// Represent the ways the brightness of an image can be adjusted before it is mapped to characters.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ContrastMode {
+    /// [ContrastMode::Linear] maps the brightness of each pixel to characters as is.
+    #[default]
+    Linear,
+    /// [ContrastMode::Gamma] raises the brightness of each pixel (from 0.0 to 1.0) to the
+    /// given power. Values below 1.0 brighten the dark parts of the image and values above
+    /// 1.0 darken the light parts of the image.
+    Gamma(f32),
+    /// [ContrastMode::Equalize] spreads the brightness of the pixels evenly over the whole
+    /// brightness range, which stretches the contrast of washed out or dark photos.
+    Equalize,
+    /// [ContrastMode::Threshold] turns pixels at least as bright as the threshold white and the
+    /// rest black, so the image is drawn with only the darkest and lightest symbols. When no
+    /// threshold is given, it is picked with Otsu's method to best split the pixels of the image
+    /// into two groups. This suits logos and line drawings.
+    Threshold(Option<u8>),
+}
+
+/// Represent the ways ASCII can be drawn when it is converted into an image.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum ImageRenderMode {
+    /// [ImageRenderMode::Glyphs] draws the characters of the ASCII with a monospace font, so
+    /// the image looks like the ASCII art.
+    #[default]
+    Glyphs,
+    /// [ImageRenderMode::Pixels] maps each character to a single gray pixel using the
+    /// [SymbolMap] and scales the result up.
+    Pixels,
+}
+
+/// Represent what happens to the characters of ASCII that are not in the [SymbolMap] when the
+/// ASCII is converted into an image.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UnknownSymbolPolicy {
+    /// [UnknownSymbolPolicy::Error] stops the conversion with [ConvertError::UnknownASCIISymbol].
+    ///
+    /// [ConvertError::UnknownASCIISymbol]: crate::converter::ConvertError::UnknownASCIISymbol
+    #[default]
+    Error,
+    /// [UnknownSymbolPolicy::SkipAsSpace] draws the character as a space, or as the lightest
+    /// symbol when the [SymbolMap] has no space.
+    SkipAsSpace,
+    /// [UnknownSymbolPolicy::NearestMatch] draws the character as the symbol whose glyph looks
+    /// the most like it, comparing the ink of each pixel of their cells, so `V` is drawn like `v`
+    /// and `E` like `C`. Characters
+    /// the font can't draw are treated like [UnknownSymbolPolicy::SkipAsSpace].
+    NearestMatch,
+}
+
+/// The width of a character cell divided by its height used when [ConvertOptions] don't set one.
+///
+/// Characters of monospace fonts are about twice as tall as they are wide, so each line of ASCII
+/// covers two rows of pixels: <http://paulbourke.net/dataformats/asciiart/>
+pub const DEFAULT_CELL_ASPECT: f32 = 0.5;
+
+/// The most the brightness of each pixel can be raised or lowered by, which turns every pixel white
+/// or black.
+pub const MAX_BRIGHTNESS: i16 = 255;
+
+/// The most the contrast of an image can be raised or lowered by, in percent. Lowering it by this
+/// much turns every pixel middle gray.
+pub const MAX_CONTRAST_ADJUSTMENT: f32 = 100.0;
+
+/// The most pixels an image may decode to when [SizeLimits] don't set a limit, which is about
+/// 400 MB of RGBA pixels.
+pub const DEFAULT_MAX_PIXELS: u64 = 100_000_000;
+
+/// The most characters a line of text may have when [SizeLimits] don't set a limit.
+pub const DEFAULT_MAX_COLUMNS: u32 = 4096;
+
+/// The most lines a piece of text may have when [SizeLimits] don't set a limit.
+pub const DEFAULT_MAX_ROWS: u32 = 4096;
+
+/// [SizeLimits] is a struct that holds the largest images and text the converter works with.
+///
+/// A small compressed image can decode to a gigantic pixel buffer, so the size in the header of
+/// an image is checked against the limits before its pixels are decoded. Sizes past a limit are
+/// rejected with [ConvertError::TooLarge].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SizeLimits {
+    /// The most pixels an image may have, counting both images that are decoded and images that
+    /// ASCII is drawn into.
+    pub max_pixels: u64,
+    /// The most characters a line of text may have.
+    pub max_columns: u32,
+    /// The most lines a piece of text may have.
+    pub max_rows: u32,
+}
+
+impl SizeLimits {
+    /// Check that an image of the given [Dimension] has no more pixels than the limit.
+    pub fn check_pixels(&self, dimension: &Dimension) -> Result<(), ConvertError> {
+        if u64::from(dimension.width) * u64::from(dimension.height) > self.max_pixels {
+            return Err(ConvertError::TooLarge {
+                width: dimension.width,
+                height: dimension.height,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Check that text with the given number of columns and rows fits in the limits.
+    pub fn check_text(&self, columns: u32, rows: u32) -> Result<(), ConvertError> {
+        if columns > self.max_columns || rows > self.max_rows {
+            return Err(ConvertError::TooLarge {
+                width: columns,
+                height: rows,
+            });
+        }
+
+        Ok(())
+    }
+}
+
+impl Default for SizeLimits {
+    /// Create [SizeLimits] with the [DEFAULT_MAX_PIXELS], [DEFAULT_MAX_COLUMNS] and
+    /// [DEFAULT_MAX_ROWS].
+    fn default() -> SizeLimits {
+        SizeLimits {
+            max_pixels: DEFAULT_MAX_PIXELS,
+            max_columns: DEFAULT_MAX_COLUMNS,
+            max_rows: DEFAULT_MAX_ROWS,
+        }
+    }
+}
+
+/// [ConvertOptions] is a struct that holds the settings used to convert an image into text.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct ConvertOptions {
+    /// The number of characters per line of the generated text, or [None] to let
+    /// the converter pick a reasonable size.
+    pub width: Option<u32>,
+    /// The most lines the generated text may have, or [None] for no limit.
+    ///
+    /// The converter picks the largest width, up to the one it would use otherwise, whose text
+    /// has at most this many lines, so together with the width the text fits in a box such as
+    /// the window of a terminal.
+    pub height: Option<u32>,
+    /// The most characters the whole text may have, counting line breaks, or [None] for
+    /// no limit.
+    ///
+    /// The converter picks the largest width, up to the one it would use otherwise, whose
+    /// text fits the budget. The height follows the aspect ratio of the image and the
+    /// [RenderCharset], so tall images get narrower text than wide ones.
+    pub max_characters: Option<u32>,
+    /// The set of characters used to render the image.
+    pub charset: RenderCharset,
+    /// The map of brightness to symbols used by [RenderCharset::Ascii], which can be one of the
+    /// built-in maps of a [SymbolSet](crate::converter::symbol_map::SymbolSet).
+    pub symbol_map: SymbolMap,
+    /// The amount added to the brightness of each pixel, from -[MAX_BRIGHTNESS] to
+    /// [MAX_BRIGHTNESS].
+    ///
+    /// The brightness and the contrast adjustment below are applied to the scaled pixels before
+    /// the stages of the pipeline, and are used by [RenderCharset::Ascii] and
+    /// [RenderCharset::Braille].
+    pub brightness: i16,
+    /// The percent the contrast of the pixels is raised by, from -[MAX_CONTRAST_ADJUSTMENT] to
+    /// [MAX_CONTRAST_ADJUSTMENT]. Negative numbers lower it and zero keeps it as is.
+    ///
+    /// Pixels are moved away from middle gray by `((100 + contrast_adjustment) / 100)²` times
+    /// their distance from it, the same formula as [image::imageops::contrast].
+    pub contrast_adjustment: f32,
+    /// The brightness adjustment applied before pixels are mapped to characters.
+    ///
+    /// Contrast is used by [RenderCharset::Ascii] and [RenderCharset::Braille].
+    pub contrast: ContrastMode,
+    /// The dithering applied before pixels are mapped to characters.
+    ///
+    /// Dithering is used by [RenderCharset::Ascii] and [RenderCharset::Braille].
+    pub dither: DitherMode,
+    /// The width of a character cell divided by its height, or [None] for the
+    /// [DEFAULT_CELL_ASPECT].
+    ///
+    /// [RenderCharset::Ascii] covers `1 / cell_aspect` rows of pixels with each line of text, so
+    /// the text isn't stretched vertically when it is shown in a font with cells of that shape.
+    pub cell_aspect: Option<f32>,
+    /// Prefix each line of the text with its line number and put column rulers above it.
+    pub rulers: bool,
+    /// Start the text with a header comment block that records the size of the image and the
+    /// settings of the conversion.
+    ///
+    /// The header is described in the [metadata](crate::converter::metadata) module. It is not
+    /// counted by the size estimates or the text limits, since it doesn't grow with the image.
+    pub metadata: bool,
+    /// Convert the image the way its pixels are stored, instead of turning it upright with the
+    /// orientation in its EXIF metadata.
+    ///
+    /// Phones store photos sideways and record which way is up in EXIF, so the orientation is
+    /// honored unless this is set.
+    pub ignore_exif_orientation: bool,
+    /// The region of the image that is converted, or [None] to convert the whole image.
+    ///
+    /// The region is cut out of the decoded image before the stages of the pipeline are applied,
+    /// so it is in the pixels of the image as it was uploaded.
+    pub crop: Option<Rect>,
+    /// The stages applied to the image before the contrast and dither above.
+    ///
+    /// Crop, rotate and flip stages are used by every [RenderCharset], and contrast and dither
+    /// stages are used by [RenderCharset::Ascii] and [RenderCharset::Braille].
+    pub pipeline: Pipeline,
+    /// The largest images and text the conversion works with.
+    pub limits: SizeLimits,
+}
+
+impl ConvertOptions {
+    /// The width of a character cell divided by its height that the options convert images with.
+    pub fn cell_aspect(&self) -> f32 {
+        self.cell_aspect.unwrap_or(DEFAULT_CELL_ASPECT)
+    }
+
+    /// List every [Stage] the options apply to an image, in the order they are applied.
+    ///
+    /// These are the stages of the [Pipeline] followed by the contrast and dither of the
+    /// options, which are left out when they don't change the image.
+    pub fn applied_stages(&self) -> Vec<Stage> {
+        let mut stages = self.pipeline.stages().to_vec();
+        if self.contrast != ContrastMode::Linear {
+            stages.push(Stage::Contrast(self.contrast));
+        }
+        if self.dither != DitherMode::None {
+            stages.push(Stage::Dither(self.dither));
+        }
+
+        stages
+    }
+
+    /// The [Dimension] of an image of the given size after it is cropped to the region of the
+    /// options and the crop, rotate and flip stages of the pipeline are applied.
+    ///
+    /// [ConvertError::InvalidCrop] is returned if the region is empty or reaches past the edge
+    /// of the image, and [ConvertError::InvalidPipeline] if a crop of the pipeline does.
+    pub fn transformed_dimension(&self, dimension: Dimension) -> Result<Dimension, ConvertError> {
+        let dimension = match self.crop {
+            Some(rect) if rect.fits(&dimension) => rect.dimension(),
+            Some(_) => return Err(ConvertError::InvalidCrop),
+            None => dimension,
+        };
+
+        self.pipeline.transformed_dimension(dimension)
+    }
+
+    /// Copy the options with the rotate and flip stages that turn an image upright put in front
+    /// of the stages of the pipeline, so they are applied right after the region is cut out.
+    pub(crate) fn with_orientation(&self, orientation: &Pipeline) -> ConvertOptions {
+        let pipeline = orientation
+            .stages()
+            .iter()
+            .chain(self.pipeline.stages())
+            .fold(Pipeline::new(), |pipeline, stage| pipeline.stage(*stage));
+
+        ConvertOptions {
+            pipeline,
+            ..self.clone()
+        }
+    }
+
+    /// Check if the options change the shape of the decoded image, with a region to convert or
+    /// with crop, rotate and flip stages.
+    pub(crate) fn changes_geometry(&self) -> bool {
+        self.crop.is_some() || self.pipeline.stages().iter().any(Stage::is_geometric)
+    }
+
+    /// Crop a decoded image to the region of the options, and then apply the crop, rotate and flip
+    /// stages of the pipeline.
+    ///
+    /// See [ConvertOptions::transformed_dimension] for the errors that can be returned.
+    pub(crate) fn apply_geometry(&self, img: DynamicImage) -> Result<DynamicImage, ConvertError> {
+        let img = match self.crop {
+            Some(rect) if rect.fits(&Dimension::from((img.width(), img.height()))) => {
+                img.crop_imm(rect.x, rect.y, rect.width, rect.height)
+            }
+            Some(_) => return Err(ConvertError::InvalidCrop),
+            None => img,
+        };
+
+        self.pipeline.apply_geometry(img)
+    }
+}
@@ -0,0 +1,449 @@
+//! Ordered preprocessing stages for image to ASCII conversion.
+//!
+//! A [Pipeline] lists the [Stage]s that are applied to an image before it is turned into text,
+//! in the order they are applied. Crop, rotate and flip stages change the shape of the decoded image,
+//! and contrast and dither stages change the brightness of the pixels once the image has been
+//! scaled to one pixel per character. Pipelines can be serialized with [serde] so that the
+//! exact transformations used for a conversion can be shown to a user and replayed later.
+//!
+//! Robert Peterson and Kelsey Werner 2023
+
+use crate::converter::{
+    dimension::Dimension,
+    options::{ContrastMode, DitherMode},
+    ConvertError,
+};
+use image::DynamicImage;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Represent the ways an image can be rotated.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Rotation {
+    /// [Rotation::Clockwise90] turns the image a quarter turn clockwise.
+    Clockwise90,
+    /// [Rotation::Clockwise180] turns the image upside down.
+    Clockwise180,
+    /// [Rotation::Clockwise270] turns the image a quarter turn counterclockwise.
+    Clockwise270,
+}
+
+/// Represent the ways an image can be mirrored.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Flip {
+    /// [Flip::Horizontal] swaps the left and right sides of the image.
+    Horizontal,
+    /// [Flip::Vertical] swaps the top and bottom of the image.
+    Vertical,
+}
+
+/// Represent a single preprocessing step of a [Pipeline].
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Stage {
+    /// [Stage::Crop] keeps the `width` by `height` pixels whose top left corner is at `x`, `y`.
+    Crop {
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+    },
+    /// [Stage::Rotate] turns the image by the given [Rotation].
+    Rotate(Rotation),
+    /// [Stage::Flip] mirrors the image with the given [Flip].
+    Flip(Flip),
+    /// [Stage::Contrast] adjusts the brightness of the pixels with the given [ContrastMode].
+    Contrast(ContrastMode),
+    /// [Stage::Dither] dithers the pixels with the given [DitherMode].
+    Dither(DitherMode),
+}
+
+impl Stage {
+    /// Check if the stage changes the shape of the decoded image rather than its pixels.
+    pub fn is_geometric(&self) -> bool {
+        matches!(self, Stage::Crop { .. } | Stage::Rotate(_) | Stage::Flip(_))
+    }
+}
+
+impl fmt::Display for Stage {
+    /// Describe the stage in words that can be shown to a user.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Stage::Crop {
+                x,
+                y,
+                width,
+                height,
+            } => write!(f, "Crop to {}x{} pixels at ({}, {})", width, height, x, y),
+            Stage::Rotate(Rotation::Clockwise90) => write!(f, "Rotate 90° clockwise"),
+            Stage::Rotate(Rotation::Clockwise180) => write!(f, "Rotate 180°"),
+            Stage::Rotate(Rotation::Clockwise270) => write!(f, "Rotate 90° counterclockwise"),
+            Stage::Flip(Flip::Horizontal) => write!(f, "Flip horizontally"),
+            Stage::Flip(Flip::Vertical) => write!(f, "Flip vertically"),
+            Stage::Contrast(ContrastMode::Linear) => write!(f, "Keep the contrast as is"),
+            Stage::Contrast(ContrastMode::Gamma(gamma)) => {
+                write!(f, "Adjust the contrast with a gamma of {}", gamma)
+            }
+            Stage::Contrast(ContrastMode::Equalize) => write!(f, "Equalize the contrast"),
+            Stage::Contrast(ContrastMode::Threshold(Some(threshold))) => {
+                write!(f, "Turn black and white at a brightness of {}", threshold)
+            }
+            Stage::Contrast(ContrastMode::Threshold(None)) => {
+                write!(
+                    f,
+                    "Turn black and white at the best brightness for the image"
+                )
+            }
+            Stage::Dither(DitherMode::None) => write!(f, "Do not dither"),
+            Stage::Dither(DitherMode::FloydSteinberg) => write!(f, "Dither with Floyd–Steinberg"),
+            Stage::Dither(DitherMode::Bayer) => write!(f, "Dither with a Bayer matrix"),
+        }
+    }
+}
+
+/// [Pipeline] is a struct that holds the ordered [Stage]s applied to an image before it is
+/// turned into text.
+///
+/// Stages are added with the builder methods and applied in the order they were added. All
+/// crop, rotate and flip stages have to come before the contrast and dither stages, since they
+/// work on the image before it is scaled.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Pipeline {
+    /// The stages in the order they are applied.
+    stages: Vec<Stage>,
+}
+
+impl Pipeline {
+    /// Create a new [Pipeline] without any stages.
+    pub fn new() -> Self {
+        Pipeline { stages: Vec::new() }
+    }
+
+    /// Add a [Stage] to the end of the pipeline.
+    pub fn stage(mut self, stage: Stage) -> Self {
+        self.stages.push(stage);
+        self
+    }
+
+    /// Add a [Stage::Crop] to the end of the pipeline.
+    pub fn crop(self, x: u32, y: u32, width: u32, height: u32) -> Self {
+        self.stage(Stage::Crop {
+            x,
+            y,
+            width,
+            height,
+        })
+    }
+
+    /// Add a [Stage::Rotate] to the end of the pipeline.
+    pub fn rotate(self, rotation: Rotation) -> Self {
+        self.stage(Stage::Rotate(rotation))
+    }
+
+    /// Add a [Stage::Flip] to the end of the pipeline.
+    pub fn flip(self, flip: Flip) -> Self {
+        self.stage(Stage::Flip(flip))
+    }
+
+    /// Add a [Stage::Contrast] to the end of the pipeline.
+    pub fn contrast(self, mode: ContrastMode) -> Self {
+        self.stage(Stage::Contrast(mode))
+    }
+
+    /// Add a [Stage::Dither] to the end of the pipeline.
+    pub fn dither(self, mode: DitherMode) -> Self {
+        self.stage(Stage::Dither(mode))
+    }
+
+    /// The stages in the order they are applied.
+    pub fn stages(&self) -> &[Stage] {
+        &self.stages
+    }
+
+    /// Check if the pipeline has no stages.
+    pub fn is_empty(&self) -> bool {
+        self.stages.is_empty()
+    }
+
+    /// The contrast and dither stages in the order they are applied.
+    pub(crate) fn pixel_stages(&self) -> impl Iterator<Item = Stage> + '_ {
+        self.stages.iter().copied().filter(|s| !s.is_geometric())
+    }
+
+    /// Check that the stages can be applied.
+    ///
+    /// [ConvertError::InvalidPipeline] is returned if a crop, rotate or flip stage comes after a
+    /// contrast or dither stage, and [ConvertError::InvalidGamma] is returned if a contrast
+    /// stage has a gamma that is not a positive number.
+    pub fn validate(&self) -> Result<(), ConvertError> {
+        let first_pixel_stage = self.stages.iter().position(|s| !s.is_geometric());
+        if let Some(first) = first_pixel_stage {
+            if self.stages[first..].iter().any(Stage::is_geometric) {
+                return Err(ConvertError::InvalidPipeline);
+            }
+        }
+
+        for stage in &self.stages {
+            if let Stage::Contrast(ContrastMode::Gamma(gamma)) = stage {
+                if !(gamma.is_finite() && *gamma > 0.0) {
+                    return Err(ConvertError::InvalidGamma);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// The [Dimension] of an image of the given size after the crop, rotate and flip stages.
+    ///
+    /// [ConvertError::InvalidPipeline] is returned if a crop is empty or reaches past the
+    /// edge of the image.
+    pub fn transformed_dimension(&self, dimension: Dimension) -> Result<Dimension, ConvertError> {
+        let (mut width, mut height) = (dimension.width, dimension.height);
+
+        for stage in &self.stages {
+            match *stage {
+                Stage::Crop {
+                    x,
+                    y,
+                    width: crop_width,
+                    height: crop_height,
+                } => {
+                    let fits = |start: u32, length: u32, max: u32| {
+                        length > 0 && start.checked_add(length).is_some_and(|end| end <= max)
+                    };
+                    if !fits(x, crop_width, width) || !fits(y, crop_height, height) {
+                        return Err(ConvertError::InvalidPipeline);
+                    }
+                    (width, height) = (crop_width, crop_height);
+                }
+                Stage::Rotate(Rotation::Clockwise90 | Rotation::Clockwise270) => {
+                    (width, height) = (height, width);
+                }
+                _ => {}
+            }
+        }
+
+        Ok(Dimension { width, height })
+    }
+
+    /// Apply the crop, rotate and flip stages to a decoded image.
+    ///
+    /// See [Pipeline::transformed_dimension] for the errors that can be returned.
+    pub(crate) fn apply_geometry(&self, img: DynamicImage) -> Result<DynamicImage, ConvertError> {
+        self.transformed_dimension(Dimension::from((img.width(), img.height())))?;
+
+        Ok(self.stages.iter().fold(img, |img, stage| match *stage {
+            Stage::Crop {
+                x,
+                y,
+                width,
+                height,
+            } => img.crop_imm(x, y, width, height),
+            Stage::Rotate(Rotation::Clockwise90) => img.rotate90(),
+            Stage::Rotate(Rotation::Clockwise180) => img.rotate180(),
+            Stage::Rotate(Rotation::Clockwise270) => img.rotate270(),
+            Stage::Flip(Flip::Horizontal) => img.fliph(),
+            Stage::Flip(Flip::Vertical) => img.flipv(),
+            Stage::Contrast(_) | Stage::Dither(_) => img,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{GrayImage, Luma};
+
+    // Test that the builder keeps the stages in the order they were added.
+    #[test]
+    fn test_builder() {
+        let pipeline = Pipeline::new()
+            .crop(1, 2, 3, 4)
+            .rotate(Rotation::Clockwise90)
+            .contrast(ContrastMode::Equalize)
+            .dither(DitherMode::Bayer);
+
+        assert_eq!(
+            pipeline.stages(),
+            [
+                Stage::Crop {
+                    x: 1,
+                    y: 2,
+                    width: 3,
+                    height: 4
+                },
+                Stage::Rotate(Rotation::Clockwise90),
+                Stage::Contrast(ContrastMode::Equalize),
+                Stage::Dither(DitherMode::Bayer),
+            ]
+        );
+        assert_eq!(
+            pipeline.pixel_stages().collect::<Vec<_>>(),
+            [
+                Stage::Contrast(ContrastMode::Equalize),
+                Stage::Dither(DitherMode::Bayer)
+            ]
+        );
+        assert!(Pipeline::new().is_empty());
+    }
+
+    // Test that stages must be in an order that can be applied.
+    #[test]
+    fn test_validate() {
+        assert_eq!(
+            Pipeline::new()
+                .rotate(Rotation::Clockwise180)
+                .contrast(ContrastMode::Gamma(0.5))
+                .validate(),
+            Ok(())
+        );
+        assert_eq!(
+            Pipeline::new()
+                .dither(DitherMode::Bayer)
+                .rotate(Rotation::Clockwise180)
+                .validate(),
+            Err(ConvertError::InvalidPipeline)
+        );
+        assert_eq!(
+            Pipeline::new()
+                .contrast(ContrastMode::Gamma(-1.0))
+                .validate(),
+            Err(ConvertError::InvalidGamma)
+        );
+    }
+
+    // Test that crops and rotations change the size of the image.
+    #[test]
+    fn test_transformed_dimension() {
+        let pipeline = Pipeline::new()
+            .crop(10, 0, 30, 20)
+            .rotate(Rotation::Clockwise90);
+
+        assert_eq!(
+            pipeline.transformed_dimension(Dimension::from((40, 20))),
+            Ok(Dimension::from((20, 30)))
+        );
+        assert_eq!(
+            pipeline.transformed_dimension(Dimension::from((39, 20))),
+            Err(ConvertError::InvalidPipeline)
+        );
+        assert_eq!(
+            Pipeline::new()
+                .crop(0, 0, 0, 1)
+                .transformed_dimension(Dimension::from((1, 1))),
+            Err(ConvertError::InvalidPipeline)
+        );
+    }
+
+    // Test that thresholding is described with its threshold or as picking one for the image.
+    #[test]
+    fn test_display_threshold() {
+        assert_eq!(
+            Stage::Contrast(ContrastMode::Threshold(Some(100))).to_string(),
+            "Turn black and white at a brightness of 100"
+        );
+        assert_eq!(
+            Stage::Contrast(ContrastMode::Threshold(None)).to_string(),
+            "Turn black and white at the best brightness for the image"
+        );
+        assert_eq!(
+            serde_json::to_string(&Pipeline::new().contrast(ContrastMode::Threshold(None)))
+                .unwrap(),
+            r#"[{"contrast":{"threshold":null}}]"#
+        );
+    }
+
+    // Test that the crop and rotate stages are applied to the image in order.
+    #[test]
+    fn test_apply_geometry() {
+        let img = GrayImage::from_fn(3, 2, |x, y| Luma([(x + y * 3) as u8]));
+        let pipeline = Pipeline::new()
+            .crop(1, 0, 2, 2)
+            .rotate(Rotation::Clockwise90)
+            .contrast(ContrastMode::Equalize);
+
+        let transformed = pipeline
+            .apply_geometry(DynamicImage::ImageLuma8(img))
+            .unwrap()
+            .into_luma8();
+
+        // the cropped pixels [1, 2] over [4, 5] turned a quarter clockwise
+        assert_eq!(transformed.dimensions(), (2, 2));
+        assert_eq!(transformed.into_raw(), vec![4, 1, 5, 2]);
+    }
+
+    // Test that flips mirror the image without changing its size.
+    #[test]
+    fn test_flip() {
+        let img =
+            DynamicImage::ImageLuma8(GrayImage::from_fn(3, 2, |x, y| Luma([(x + y * 3) as u8])));
+
+        let flipped = |flip| {
+            Pipeline::new()
+                .flip(flip)
+                .apply_geometry(img.clone())
+                .unwrap()
+                .into_luma8()
+                .into_raw()
+        };
+        assert_eq!(flipped(Flip::Horizontal), vec![2, 1, 0, 5, 4, 3]);
+        assert_eq!(flipped(Flip::Vertical), vec![3, 4, 5, 0, 1, 2]);
+
+        assert_eq!(
+            Pipeline::new()
+                .flip(Flip::Horizontal)
+                .transformed_dimension(Dimension::from((3, 2))),
+            Ok(Dimension::from((3, 2)))
+        );
+        assert_eq!(Stage::Flip(Flip::Vertical).to_string(), "Flip vertically");
+    }
+
+    // Test that a pipeline can be serialized and read back.
+    #[test]
+    fn test_serialize() {
+        let pipeline = Pipeline::new()
+            .crop(0, 0, 8, 8)
+            .rotate(Rotation::Clockwise270)
+            .flip(Flip::Horizontal)
+            .contrast(ContrastMode::Gamma(0.5))
+            .dither(DitherMode::FloydSteinberg);
+
+        let json = serde_json::to_string(&pipeline).unwrap();
+        assert_eq!(
+            json,
+            r#"[{"crop":{"x":0,"y":0,"width":8,"height":8}},{"rotate":"clockwise270"},{"flip":"horizontal"},{"contrast":{"gamma":0.5}},{"dither":"floyd_steinberg"}]"#
+        );
+        assert_eq!(serde_json::from_str::<Pipeline>(&json).unwrap(), pipeline);
+    }
+
+    // Test that each stage is described in words.
+    #[test]
+    fn test_display() {
+        assert_eq!(
+            Stage::Crop {
+                x: 1,
+                y: 2,
+                width: 3,
+                height: 4
+            }
+            .to_string(),
+            "Crop to 3x4 pixels at (1, 2)"
+        );
+        assert_eq!(
+            Stage::Rotate(Rotation::Clockwise90).to_string(),
+            "Rotate 90° clockwise"
+        );
+        assert_eq!(
+            Stage::Contrast(ContrastMode::Gamma(0.5)).to_string(),
+            "Adjust the contrast with a gamma of 0.5"
+        );
+        assert_eq!(
+            Stage::Dither(DitherMode::FloydSteinberg).to_string(),
+            "Dither with Floyd–Steinberg"
+        );
+    }
+}
@@ -0,0 +1,121 @@
+//! Text metadata for PNG images.
+//!
+//! PNG files can carry text, such as the author of the image, in chunks that image viewers show
+//! as the properties of the file. [add_text_chunks] writes each entry into a tEXt chunk, or an
+//! iTXt chunk when the text has characters that Latin-1 can't store, right after the header of
+//! an encoded PNG so the text travels with the image wherever it is downloaded to.
+//!
+//! Robert Peterson and Kelsey Werner 2023
+
+use crate::converter::{ConvertError, ErrorSource};
+use flate2::Crc;
+
+/// The 8 bytes every PNG file starts with.
+pub(crate) const PNG_SIGNATURE: [u8; 8] = [137, 80, 78, 71, 13, 10, 26, 10];
+
+/// The length of the signature and the IHDR chunk, which must come first in a PNG file.
+const HEADER_LENGTH: usize = 8 + 4 + 4 + 13 + 4;
+
+/// Encode a chunk with its length, type, data and CRC.
+pub(crate) fn encode_chunk(chunk_type: &[u8; 4], data: &[u8]) -> Vec<u8> {
+    let mut crc = Crc::new();
+    crc.update(chunk_type);
+    crc.update(data);
+
+    let mut chunk = Vec::with_capacity(data.len() + 12);
+    chunk.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    chunk.extend_from_slice(chunk_type);
+    chunk.extend_from_slice(data);
+    chunk.extend_from_slice(&crc.sum().to_be_bytes());
+    chunk
+}
+
+/// Encode a text entry as a tEXt chunk when the text is Latin-1 and as an uncompressed iTXt chunk otherwise.
+fn encode_text_chunk(keyword: &str, text: &str) -> Vec<u8> {
+    let mut data = keyword.as_bytes().to_vec();
+    data.push(0);
+
+    if text.chars().all(|c| (c as u32) < 256) {
+        data.extend(text.chars().map(|c| c as u8));
+        encode_chunk(b"tEXt", &data)
+    } else {
+        // no compression, then an empty language tag and translated keyword
+        data.extend_from_slice(&[0, 0, 0, 0]);
+        data.extend_from_slice(text.as_bytes());
+        encode_chunk(b"iTXt", &data)
+    }
+}
+
+/// Add a text chunk for each `(keyword, text)` entry to the encoded PNG in `png`.
+///
+/// The keywords should be one of the keywords in the PNG specification, such as "Author" or
+/// "Copyright". The chunks are added right after the IHDR chunk. Returns
+/// [ConvertError::DecodeError] when `png` is not a PNG file.
+pub fn add_text_chunks(png: &[u8], entries: &[(&str, &str)]) -> Result<Vec<u8>, ConvertError> {
+    if png.len() < HEADER_LENGTH || png[..8] != PNG_SIGNATURE || &png[12..16] != b"IHDR" {
+        return Err(ConvertError::DecodeError(ErrorSource::message(
+            "the data is not a PNG",
+        )));
+    }
+
+    let mut tagged = png[..HEADER_LENGTH].to_vec();
+    for (keyword, text) in entries {
+        tagged.extend(encode_text_chunk(keyword, text));
+    }
+    tagged.extend_from_slice(&png[HEADER_LENGTH..]);
+
+    Ok(tagged)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{ImageBuffer, ImageOutputFormat, Luma};
+    use std::io::Cursor;
+
+    // Encode a small gray PNG.
+    fn test_png() -> Vec<u8> {
+        let img: ImageBuffer<Luma<u8>, Vec<u8>> = ImageBuffer::new(4, 2);
+        let mut png = Cursor::new(Vec::new());
+        img.write_to(&mut png, ImageOutputFormat::Png).unwrap();
+        png.into_inner()
+    }
+
+    // Find out if `needle` is somewhere in `haystack`.
+    fn contains(haystack: &[u8], needle: &[u8]) -> bool {
+        haystack
+            .windows(needle.len())
+            .any(|window| window == needle)
+    }
+
+    // Test that Latin-1 text is added in tEXt chunks and the image can still be read.
+    #[test]
+    fn test_add_text_chunks() {
+        let png = test_png();
+
+        let tagged = add_text_chunks(&png, &[("Author", "Zoë"), ("Copyright", "CC0 1.0")]).unwrap();
+
+        assert!(contains(&tagged, b"tEXtAuthor\0Zo\xEB"));
+        assert!(contains(&tagged, b"tEXtCopyright\0CC0 1.0"));
+        assert_eq!(tagged.len(), png.len() + 12 + 10 + 12 + 17);
+        assert_eq!(image::load_from_memory(&tagged).unwrap().width(), 4);
+    }
+
+    // Test that text that isn't Latin-1 is added in an iTXt chunk as UTF-8.
+    #[test]
+    fn test_add_text_chunks_unicode() {
+        let tagged = add_text_chunks(&test_png(), &[("Author", "ロバート")]).unwrap();
+
+        assert!(contains(&tagged, "iTXtAuthor\0\0\0\0\0ロバート".as_bytes()));
+        assert!(image::load_from_memory(&tagged).is_ok());
+    }
+
+    // Test that data that isn't a PNG is rejected.
+    #[test]
+    fn test_add_text_chunks_not_png() {
+        assert!(matches!(
+            add_text_chunks(b"GIF89a", &[("Author", "Robert")]),
+            Err(ConvertError::DecodeError(_))
+        ));
+    }
+}
@@ -0,0 +1,256 @@
+//! QR code to ASCII converter.
+//!
+//! A QR code is drawn with the block characters `▀`, `▄` and `█`, where each character holds two
+//! modules (the squares of the code) stacked on top of each other. A character is about twice as
+//! tall as it is wide, so the modules come out square and the code can be scanned right off a
+//! terminal or a page. A code drawn this way can be pasted back in and rasterized into a PNG
+//! with [qr_ascii_to_png], so it can be scanned from a printout or a photo.
+//!
+//! This module is only built with the `qr` feature.
+//!
+//! Robert Peterson and Kelsey Werner 2023
+
+use crate::converter::{codec::default_codec, ConvertError};
+use image::{DynamicImage, GrayImage, Luma};
+use qrcode::{Color, QrCode};
+
+/// The number of light modules around a QR code, which scanners need to find its edges.
+pub const QUIET_ZONE: usize = 4;
+
+/// The number of pixels on each side of a module in a rasterized QR code.
+pub const MODULE_PIXELS: u32 = 8;
+
+/// The number of modules on each side of the smallest QR code. Each larger version of a QR code
+/// adds 4 modules to each side, up to the largest.
+const MIN_QR_WIDTH: usize = 21;
+const MAX_QR_WIDTH: usize = 177;
+
+/// The block character of two stacked modules, given whether the top and the bottom module is dark.
+fn block(top: bool, bottom: bool) -> char {
+    match (top, bottom) {
+        (true, true) => '█',
+        (true, false) => '▀',
+        (false, true) => '▄',
+        (false, false) => ' ',
+    }
+}
+
+/// Whether the top and the bottom module of a block character are dark, or [None] if the
+/// character is not one of the blocks a QR code is drawn with.
+fn modules(symbol: char) -> Option<(bool, bool)> {
+    match symbol {
+        '█' => Some((true, true)),
+        '▀' => Some((true, false)),
+        '▄' => Some((false, true)),
+        ' ' => Some((false, false)),
+        _ => None,
+    }
+}
+
+/// Draw a QR code of the given text, such as a URL, with block characters.
+///
+/// The code is surrounded by a quiet zone of [QUIET_ZONE] light modules, and each line of the
+/// ASCII ends with a line break.
+/// [ConvertError::EmptyInput] is returned if the text is empty, and [ConvertError::QrDataTooLong]
+/// if the text doesn't fit in the largest QR code.
+pub fn text_to_qr_ascii(text: &str) -> Result<String, ConvertError> {
+    if text.is_empty() {
+        return Err(ConvertError::EmptyInput);
+    }
+
+    // any bytes can be encoded, so only the length of the text can be rejected
+    let code = QrCode::new(text.as_bytes()).map_err(|_| ConvertError::QrDataTooLong)?;
+    let width = code.width();
+    let colors = code.to_colors();
+    let is_dark = |x: usize, y: usize| {
+        (QUIET_ZONE..QUIET_ZONE + width).contains(&x)
+            && (QUIET_ZONE..QUIET_ZONE + width).contains(&y)
+            && colors[(y - QUIET_ZONE) * width + x - QUIET_ZONE] == Color::Dark
+    };
+
+    // the code is an odd number of modules tall, so the bottom half of the last line is left light
+    let padded = width + 2 * QUIET_ZONE;
+    let mut ascii = String::new();
+    for y in (0..padded).step_by(2) {
+        ascii.extend((0..padded).map(|x| block(is_dark(x, y), is_dark(x, y + 1))));
+        ascii.push('\n');
+    }
+
+    Ok(ascii)
+}
+
+/// Read the modules of a QR code drawn with block characters, with the quiet zone around it removed.
+///
+/// The quiet zone can be any size and trailing spaces can be missing from the lines, such as when
+/// the code was copied out of a terminal, since the code is found by its dark modules.
+fn read_modules(ascii: &str) -> Result<Vec<Vec<bool>>, ConvertError> {
+    let mut rows: Vec<Vec<bool>> = Vec::new();
+    for (y, line) in (0_u32..).zip(ascii.lines()) {
+        let mut top = Vec::new();
+        let mut bottom = Vec::new();
+        for (x, symbol) in (0_u32..).zip(line.chars()) {
+            let (top_module, bottom_module) = modules(symbol)
+                .ok_or_else(|| ConvertError::UnknownASCIISymbol(symbol, None).at(x, y))?;
+            top.push(top_module);
+            bottom.push(bottom_module);
+        }
+        rows.push(top);
+        rows.push(bottom);
+    }
+
+    let has_dark = |row: &Vec<bool>| row.contains(&true);
+    let (Some(first), Some(last)) = (
+        rows.iter().position(has_dark),
+        rows.iter().rposition(has_dark),
+    ) else {
+        return Err(ConvertError::EmptyInput);
+    };
+    let rows = &rows[first..=last];
+    let left = rows
+        .iter()
+        .filter_map(|row| row.iter().position(|&dark| dark))
+        .min();
+    let right = rows
+        .iter()
+        .filter_map(|row| row.iter().rposition(|&dark| dark))
+        .max();
+    let (Some(left), Some(right)) = (left, right) else {
+        return Err(ConvertError::EmptyInput);
+    };
+
+    // the finder patterns in three of the corners reach every edge of a QR code, so the dark
+    // modules span a square that is exactly the size of the code
+    let width = right - left + 1;
+    if width != rows.len()
+        || !(MIN_QR_WIDTH..=MAX_QR_WIDTH).contains(&width)
+        || !(width - MIN_QR_WIDTH).is_multiple_of(4)
+    {
+        return Err(ConvertError::InvalidQrCode);
+    }
+
+    Ok(rows
+        .iter()
+        .map(|row| {
+            (left..=right)
+                .map(|x| row.get(x).copied().unwrap_or(false))
+                .collect()
+        })
+        .collect())
+}
+
+/// Rasterize a QR code drawn with block characters, such as one drawn by [text_to_qr_ascii], into
+/// a black and white PNG that can be scanned.
+///
+/// Each module is drawn as a square of [MODULE_PIXELS] pixels, and the code is surrounded by a
+/// quiet zone of [QUIET_ZONE] light modules no matter how much of it was in the ASCII.
+/// [ConvertError::UnknownASCIISymbol] is returned if the ASCII has a character that is not a block
+/// or a space, [ConvertError::EmptyInput] if it has no dark modules, and
+/// [ConvertError::InvalidQrCode] if its modules are not the size of a QR code.
+pub fn qr_ascii_to_png(ascii: &str) -> Result<Vec<u8>, ConvertError> {
+    let modules = read_modules(ascii)?;
+    let side = (modules.len() + 2 * QUIET_ZONE) as u32 * MODULE_PIXELS;
+    let img = GrayImage::from_fn(side, side, |x, y| {
+        let (x, y) = ((x / MODULE_PIXELS) as usize, (y / MODULE_PIXELS) as usize);
+        let dark = x >= QUIET_ZONE
+            && y >= QUIET_ZONE
+            && modules
+                .get(y - QUIET_ZONE)
+                .and_then(|row| row.get(x - QUIET_ZONE))
+                .is_some_and(|&dark| dark);
+        Luma([if dark { 0 } else { u8::MAX }])
+    });
+
+    Ok(default_codec()
+        .encode_png(&DynamicImage::ImageLuma8(img))?
+        .into_inner())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::converter::SymbolPosition;
+
+    const URL: &str = "https://github.com/rawburt/ascii-art-converter-website";
+
+    // Scan the QR code in a PNG and return its text.
+    fn scan(png: &[u8]) -> String {
+        let img = image::load_from_memory(png).unwrap().to_luma8();
+        let mut prepared = rqrr::PreparedImage::prepare_from_greyscale(
+            img.width() as usize,
+            img.height() as usize,
+            |x, y| img.get_pixel(x as u32, y as u32)[0],
+        );
+        let grids = prepared.detect_grids();
+        assert_eq!(grids.len(), 1);
+
+        grids[0].decode().unwrap().1
+    }
+
+    // Test that a QR code is drawn as a square of blocks surrounded by a quiet zone.
+    #[test]
+    fn test_text_to_qr_ascii() {
+        let ascii = text_to_qr_ascii(URL).unwrap();
+        let lines: Vec<&str> = ascii.lines().collect();
+        let width = lines[0].chars().count();
+
+        // two modules are drawn in each line, so the lines are half as many as the modules across
+        assert_eq!(lines.len() * 2 - 1, width);
+        assert!(lines.iter().all(|line| line.chars().count() == width));
+        assert!(ascii.chars().all(|c| c == '\n' || modules(c).is_some()));
+        assert!(lines[0].trim().is_empty());
+        assert!(lines[lines.len() - 1].trim().is_empty());
+        assert!(lines[QUIET_ZONE / 2].contains('█'));
+    }
+
+    // Test that empty text and text that is too long for a QR code are rejected.
+    #[test]
+    fn test_text_to_qr_ascii_errors() {
+        assert_eq!(text_to_qr_ascii(""), Err(ConvertError::EmptyInput));
+        assert_eq!(
+            text_to_qr_ascii(&"a".repeat(3000)),
+            Err(ConvertError::QrDataTooLong)
+        );
+    }
+
+    // Test that a drawn QR code is rasterized into a PNG that scans as the text it was drawn from,
+    // even when the quiet zone was trimmed off.
+    #[test]
+    fn test_qr_ascii_to_png() {
+        let ascii = text_to_qr_ascii(URL).unwrap();
+        let png = qr_ascii_to_png(&ascii).unwrap();
+        let width = ascii.lines().next().unwrap().chars().count() as u32;
+
+        assert_eq!(
+            image::load_from_memory(&png).unwrap().width(),
+            width * MODULE_PIXELS
+        );
+        assert_eq!(scan(&png), URL);
+
+        let trimmed: Vec<&str> = ascii
+            .lines()
+            .map(str::trim_end)
+            .filter(|line| !line.is_empty())
+            .collect();
+        assert_eq!(scan(&qr_ascii_to_png(&trimmed.join("\r\n")).unwrap()), URL);
+    }
+
+    // Test that ASCII that is not a QR code drawn with blocks is rejected.
+    #[test]
+    fn test_qr_ascii_to_png_errors() {
+        assert_eq!(
+            qr_ascii_to_png(" ▀\n █x"),
+            Err(ConvertError::UnknownASCIISymbol(
+                'x',
+                Some(SymbolPosition { x: 2, y: 1 })
+            ))
+        );
+        assert_eq!(qr_ascii_to_png("   \n\n  "), Err(ConvertError::EmptyInput));
+        assert_eq!(qr_ascii_to_png("██\n██"), Err(ConvertError::InvalidQrCode));
+
+        // a QR code that lost its last line is no longer square
+        let ascii = text_to_qr_ascii(URL).unwrap();
+        let lines: Vec<&str> = ascii.lines().collect();
+        let cut = lines[..lines.len() - QUIET_ZONE / 2 - 1].join("\n");
+        assert_eq!(qr_ascii_to_png(&cut), Err(ConvertError::InvalidQrCode));
+    }
+}
@@ -0,0 +1,186 @@
+//! Row by row image scaling.
+//!
+//! [imageops::resize] needs the whole image in memory before it can scale it. [RowResizer]
+//! scales a Luma image with the same triangle filter while its rows are pushed in one at a
+//! time, so an image can be scaled while it is still being decoded. Only the rows of the
+//! scaled image that are still being summed are kept in memory. The weights and the order the
+//! pixels are summed in follow the [image] crate exactly, so the scaled image is identical to
+//! the one [imageops::resize] creates with [FilterType::Triangle].
+//!
+//! [imageops::resize]: image::imageops::resize
+//! [FilterType::Triangle]: image::imageops::FilterType::Triangle
+//!
+//! Robert Peterson and Kelsey Werner 2023
+
+use std::collections::VecDeque;
+
+/// [Window] is a struct that holds the weights of the input pixels summed into one output pixel.
+struct Window {
+    /// The first input pixel in the window.
+    left: u32,
+    /// The input pixel just past the end of the window.
+    right: u32,
+    /// The weight of each input pixel in the window, which add up to one.
+    weights: Vec<f32>,
+}
+
+/// Calculate the triangle function, which is the filter the converter scales images with.
+fn triangle_kernel(x: f32) -> f32 {
+    if x.abs() < 1.0 {
+        1.0 - x.abs()
+    } else {
+        0.0
+    }
+}
+
+/// Build the [Window] of every output pixel when scaling `length` pixels to `new_length`.
+///
+/// The math follows the samplers of the [image] crate, which are explained here:
+/// <https://github.com/image-rs/image/blob/v0.24.6/src/imageops/sample.rs>
+fn windows(length: u32, new_length: u32) -> Vec<Window> {
+    let ratio = length as f32 / new_length as f32;
+    let scale = ratio.max(1.0);
+    let support = scale;
+    let last = i64::from(length);
+
+    (0..new_length)
+        .map(|output| {
+            let input = (output as f32 + 0.5) * ratio;
+
+            let left = ((input - support).floor() as i64).clamp(0, last - 1);
+            let right = ((input + support).ceil() as i64).clamp(left + 1, last);
+
+            // the kernel treats the center of a pixel as zero
+            let input = input - 0.5;
+
+            let mut weights: Vec<f32> = (left..right)
+                .map(|i| triangle_kernel((i as f32 - input) / scale))
+                .collect();
+            let sum = weights.iter().fold(0.0, |sum, w| sum + w);
+            weights.iter_mut().for_each(|w| *w /= sum);
+
+            Window {
+                left: left as u32,
+                right: right as u32,
+                weights,
+            }
+        })
+        .collect()
+}
+
+/// [RowResizer] is a struct that scales a Luma image as its rows are pushed in from top to bottom.
+pub(crate) struct RowResizer {
+    /// The window of input rows summed into each output row.
+    rows: Vec<Window>,
+    /// The window of input columns summed into each output column.
+    columns: Vec<Window>,
+    /// The width of the input image.
+    width: usize,
+    /// The index of the next input row.
+    next_row: u32,
+    /// The index of the first output row that hasn't been finished.
+    next_output: usize,
+    /// The running sums of the output rows that have been started but not finished.
+    sums: VecDeque<Vec<f32>>,
+}
+
+impl RowResizer {
+    /// Create a new [RowResizer] that scales a `width` by `height` image to `new_width` by
+    /// `new_height`.
+    pub(crate) fn new(width: u32, height: u32, new_width: u32, new_height: u32) -> RowResizer {
+        RowResizer {
+            rows: windows(height, new_height),
+            columns: windows(width, new_width),
+            width: width as usize,
+            next_row: 0,
+            next_output: 0,
+            sums: VecDeque::new(),
+        }
+    }
+
+    /// Push the next row of Luma pixels of the input image.
+    ///
+    /// The rows of the scaled image that are finished by this row are returned in order. Once
+    /// every row of the input image has been pushed, every row of the scaled image has been
+    /// returned.
+    pub(crate) fn push_row(&mut self, row: &[u8]) -> Vec<Vec<u8>> {
+        let y = self.next_row;
+        self.next_row += 1;
+
+        // start summing the output rows whose window begins at this row
+        while let Some(window) = self.rows.get(self.next_output + self.sums.len()) {
+            if window.left > y {
+                break;
+            }
+            self.sums.push_back(vec![0.0; self.width]);
+        }
+
+        for (sum, window) in self.sums.iter_mut().zip(&self.rows[self.next_output..]) {
+            if y < window.right {
+                let weight = window.weights[(y - window.left) as usize];
+                for (s, p) in sum.iter_mut().zip(row) {
+                    *s += *p as f32 * weight;
+                }
+            }
+        }
+
+        // every output row whose window ends at this row is finished
+        let mut finished = Vec::new();
+        while let Some(window) = self.rows.get(self.next_output) {
+            if window.right != y + 1 {
+                break;
+            }
+            if let Some(sum) = self.sums.pop_front() {
+                finished.push(self.sample_columns(&sum));
+            }
+            self.next_output += 1;
+        }
+
+        finished
+    }
+
+    /// Scale a summed row to the new width and round it back to Luma pixels.
+    fn sample_columns(&self, sum: &[f32]) -> Vec<u8> {
+        self.columns
+            .iter()
+            .map(|window| {
+                let t = sum[window.left as usize..window.right as usize]
+                    .iter()
+                    .zip(&window.weights)
+                    .fold(0.0, |t, (s, w)| t + s * w);
+                t.clamp(0.0, u8::MAX as f32).round() as u8
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{imageops, GrayImage, Luma};
+
+    // Scales an image by pushing its rows into a [RowResizer] one at a time
+    fn resize_rows(img: &GrayImage, new_width: u32, new_height: u32) -> GrayImage {
+        let mut resizer = RowResizer::new(img.width(), img.height(), new_width, new_height);
+        let mut pixels = Vec::new();
+        for row in img.as_raw().chunks(img.width() as usize) {
+            for line in resizer.push_row(row) {
+                pixels.extend(line);
+            }
+        }
+        GrayImage::from_raw(new_width, new_height, pixels).unwrap()
+    }
+
+    // Test that scaling row by row matches the [image] crate when shrinking and growing.
+    #[test]
+    fn test_push_row_matches_resize() {
+        let img = GrayImage::from_fn(37, 23, |x, y| Luma([((x * 31 + y * 17) % 256) as u8]));
+
+        for (new_width, new_height) in [(10, 5), (37, 23), (80, 60), (37, 4), (1, 1), (5, 40)] {
+            let expected =
+                imageops::resize(&img, new_width, new_height, imageops::FilterType::Triangle);
+
+            assert_eq!(resize_rows(&img, new_width, new_height), expected);
+        }
+    }
+}
@@ -0,0 +1,165 @@
+//! Line numbers and column rulers for generated text.
+//!
+//! Art editors that align hand-edits against generated output need to know which line and
+//! column each character is on. [add_rulers] prefixes every line of the text with its line
+//! number and puts column rulers above the text. The gutter is separated from the art by
+//! [GUTTER_SEPARATOR] so that [strip_rulers] can give back the original text.
+//!
+//! Robert Peterson and Kelsey Werner 2023
+
+/// The separator between the line number gutter and the art.
+pub const GUTTER_SEPARATOR: &str = " | ";
+
+/// The number of lines [add_rulers] puts above the art.
+pub const RULER_LINES: usize = 3;
+
+/// Count the characters of a line that take up a column, skipping ANSI escape sequences.
+fn visible_width(line: &str) -> usize {
+    let mut width = 0;
+    let mut chars = line.chars();
+
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            // skip everything up to and including the final letter of the sequence
+            for c in chars.by_ref() {
+                if c.is_ascii_alphabetic() {
+                    break;
+                }
+            }
+        } else {
+            width += 1;
+        }
+    }
+
+    width
+}
+
+/// The number of characters needed for the line numbers of text with `lines` lines.
+pub fn gutter_width(lines: usize) -> usize {
+    lines.max(1).to_string().len()
+}
+
+/// Prefix each line of `text` with its line number and put column rulers above it.
+///
+/// The first ruler line has the tens digit of every tenth column and the second ruler line
+/// has the ones digit of every column. Lines and columns are counted from 1.
+pub fn add_rulers(text: &str) -> String {
+    let lines: Vec<&str> = text.lines().collect();
+    let gutter = gutter_width(lines.len());
+    let columns = lines.iter().map(|l| visible_width(l)).max().unwrap_or(0);
+
+    let tens: String = (1..=columns)
+        .map(|c| {
+            if c % 10 == 0 {
+                char::from(b'0' + (c / 10 % 10) as u8)
+            } else {
+                ' '
+            }
+        })
+        .collect();
+    let ones: String = (1..=columns)
+        .map(|c| char::from(b'0' + (c % 10) as u8))
+        .collect();
+
+    let blank_gutter = " ".repeat(gutter);
+    let mut ruled = format!(
+        "{}{}{}\n{}{}{}\n{}-+-{}\n",
+        blank_gutter,
+        GUTTER_SEPARATOR,
+        tens.trim_end(),
+        blank_gutter,
+        GUTTER_SEPARATOR,
+        ones,
+        "-".repeat(gutter),
+        "-".repeat(columns),
+    );
+
+    for (number, line) in (1_usize..).zip(lines) {
+        ruled.push_str(&format!(
+            "{:>width$}{}{}\n",
+            number,
+            GUTTER_SEPARATOR,
+            line,
+            width = gutter
+        ));
+    }
+
+    ruled
+}
+
+/// Remove the line numbers and column rulers that [add_rulers] added to `text`.
+///
+/// Returns [None] if `text` does not look like it has rulers.
+pub fn strip_rulers(text: &str) -> Option<String> {
+    let mut lines = text.lines();
+
+    // the two ruler lines are followed by the separator line
+    for _ in 0..RULER_LINES - 1 {
+        lines.next()?.split_once(GUTTER_SEPARATOR)?;
+    }
+    let separator = lines.next()?;
+    if !separator.contains("-+-") || separator.chars().any(|c| c != '-' && c != '+') {
+        return None;
+    }
+
+    let mut stripped = String::new();
+    for line in lines {
+        let (number, art) = line.split_once(GUTTER_SEPARATOR)?;
+        number.trim_start().parse::<usize>().ok()?;
+        stripped.push_str(art);
+        stripped.push('\n');
+    }
+
+    Some(stripped)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Test that rulers and line numbers are added above and in front of the text.
+    #[test]
+    fn test_add_rulers() {
+        let text = format!("{}\n{}\n", "@".repeat(12), ".".repeat(3));
+        let expected = [
+            "  |          1",
+            "  | 123456789012",
+            "--+-------------",
+            "1 | @@@@@@@@@@@@",
+            "2 | ...",
+        ]
+        .map(|l| format!("{}\n", l))
+        .concat();
+
+        assert_eq!(add_rulers(&text), expected);
+    }
+
+    // Test that the gutter is as wide as the largest line number.
+    #[test]
+    fn test_add_rulers_wide_gutter() {
+        let text = "#\n".repeat(10);
+        let ruled = add_rulers(&text);
+        let lines: Vec<&str> = ruled.lines().collect();
+
+        assert_eq!(lines[2], "---+--");
+        assert_eq!(lines[3], " 1 | #");
+        assert_eq!(lines[12], "10 | #");
+    }
+
+    // Test that ANSI escape sequences do not count as columns.
+    #[test]
+    fn test_add_rulers_ansi() {
+        let ruled = add_rulers("\x1b[38;2;1;2;3m\u{2580}\x1b[0m\n");
+        assert_eq!(ruled.lines().nth(1), Some("  | 1"));
+    }
+
+    // Test that stripping the rulers gives back the original text.
+    #[test]
+    fn test_strip_rulers() {
+        let text = format!("{}\n {} \n\n", "@".repeat(25), ".".repeat(3));
+
+        assert_eq!(strip_rulers(&add_rulers(&text)), Some(text));
+        assert_eq!(strip_rulers("@@@\n...\n"), None);
+        assert_eq!(strip_rulers(""), None);
+    }
+}
@@ -0,0 +1,367 @@
+//! Long-lived conversion service.
+//!
+//! The public functions of the library set up everything a conversion needs each time they are
+//! called: the [SymbolMap] is built from its ramp along with its lookup table, and the font the
+//! ASCII is drawn with is parsed. A web server runs many conversions with the same settings, so
+//! a [Converter] does this once and keeps it warm between conversions. It holds a registry of
+//! named charsets, which are ready to use [SymbolMap]s, and the parsed font. A [Converter] is
+//! [Send] and [Sync], so one can be shared by all of the workers of a server, such as in an
+//! actix `web::Data`.
+//!
+//! Robert Peterson and Kelsey Werner 2023
+
+use crate::converter::{
+    animation::AsciiFrames,
+    ascii::{load_font, Ascii},
+    batch::map_in_order,
+    image::Image,
+    options::{ConvertOptions, ImageRenderMode, UnknownSymbolPolicy},
+    pipeline::{Pipeline, Stage},
+    source::ImageSource,
+    symbol_map::SymbolMap,
+    usage::ResourceUsage,
+    ConvertError,
+};
+use ab_glyph::FontRef;
+use std::{
+    collections::HashMap,
+    io::{self, Cursor},
+};
+
+/// The name of the charset that every [Converter] has, which is the default [SymbolMap].
+pub const DEFAULT_CHARSET: &str = "default";
+
+/// The name of the charset that every [Converter] has for [SymbolMap::printable], which reads
+/// every printable ASCII symbol.
+pub const PRINTABLE_CHARSET: &str = "printable";
+
+/// [Converter] is a struct that holds the state shared by many conversions.
+pub struct Converter {
+    /// The registered charsets by name.
+    charsets: HashMap<String, SymbolMap>,
+    /// The font the characters of ASCII are drawn with.
+    font: FontRef<'static>,
+}
+
+impl Converter {
+    /// Create a new [Converter] with the [DEFAULT_CHARSET] and [PRINTABLE_CHARSET] registered.
+    ///
+    /// [ConvertError::FontError] is returned if the font can't be read.
+    pub fn new() -> Result<Converter, ConvertError> {
+        Ok(Converter {
+            charsets: HashMap::from([
+                (DEFAULT_CHARSET.to_string(), SymbolMap::default()),
+                (PRINTABLE_CHARSET.to_string(), SymbolMap::printable()),
+            ]),
+            font: load_font()?,
+        })
+    }
+
+    /// Register a charset built from a brightness ramp under a name.
+    ///
+    /// A charset that was registered under the same name before is replaced. See
+    /// [SymbolMap::from_ramp] for the errors that can be returned.
+    pub fn with_charset(mut self, name: &str, ramp: &str) -> Result<Converter, ConvertError> {
+        self.charsets
+            .insert(name.to_string(), SymbolMap::from_ramp(ramp)?);
+        Ok(self)
+    }
+
+    /// Register a charset built from a brightness ramp under a name, with the brightness of each
+    /// symbol calibrated from its glyph.
+    ///
+    /// A charset that was registered under the same name before is replaced. See
+    /// [SymbolMap::calibrated] for the errors that can be returned.
+    pub fn with_calibrated_charset(
+        mut self,
+        name: &str,
+        ramp: &str,
+    ) -> Result<Converter, ConvertError> {
+        self.charsets
+            .insert(name.to_string(), SymbolMap::calibrated(ramp)?);
+        Ok(self)
+    }
+
+    /// The [SymbolMap] of the charset registered under a name, or [None] if there isn't one.
+    pub fn charset(&self, name: &str) -> Option<&SymbolMap> {
+        self.charsets.get(name)
+    }
+
+    /// Convert an image source into ASCII using the given [ConvertOptions].
+    ///
+    /// This gives the same text as [crate::image_to_ascii_with_options].
+    pub fn image_to_ascii<S: ImageSource>(
+        &self,
+        source: S,
+        options: ConvertOptions,
+    ) -> Result<String, ConvertError> {
+        Image::with_options(&mut source.open()?, options).convert_to_ascii()
+    }
+
+    /// Convert an image source into ASCII using the given [ConvertOptions], reporting how far
+    /// along the conversion is.
+    ///
+    /// See [Image::convert_to_ascii_with_progress] for how `progress` is called.
+    pub fn image_to_ascii_with_progress<S, F>(
+        &self,
+        source: S,
+        options: ConvertOptions,
+        progress: F,
+    ) -> Result<String, ConvertError>
+    where
+        S: ImageSource,
+        F: Fn(u32, u32) + Sync,
+    {
+        Image::with_options(&mut source.open()?, options).convert_to_ascii_with_progress(progress)
+    }
+
+    /// Convert an image source into ASCII using the given [ConvertOptions], reporting how far
+    /// along the conversion is and measuring the [ResourceUsage] of the conversion.
+    ///
+    /// See [Image::convert_to_ascii_with_usage] for how the resources are measured.
+    pub fn image_to_ascii_with_usage<S, F>(
+        &self,
+        source: S,
+        options: ConvertOptions,
+        progress: F,
+    ) -> Result<(String, ResourceUsage), ConvertError>
+    where
+        S: ImageSource,
+        F: Fn(u32, u32) + Sync,
+    {
+        Image::with_options(&mut source.open()?, options).convert_to_ascii_with_usage(progress)
+    }
+
+    /// Convert an image source into ASCII using the given [ConvertOptions], writing each line to
+    /// `writer` as soon as it is ready.
+    ///
+    /// This gives the same text as [Converter::image_to_ascii]. See
+    /// [Image::convert_to_ascii_writer] for which images are streamed and the errors that can be
+    /// returned.
+    pub fn image_to_ascii_writer<S, W>(
+        &self,
+        source: S,
+        options: ConvertOptions,
+        writer: &mut W,
+    ) -> Result<(), ConvertError>
+    where
+        S: ImageSource,
+        W: io::Write,
+    {
+        Image::with_options(&mut source.open()?, options).convert_to_ascii_writer(writer)
+    }
+
+    /// Convert many image sources into ASCII at the same time using the same [ConvertOptions].
+    ///
+    /// This gives the same results, in the same order, as [crate::convert_batch].
+    pub fn convert_batch<I>(
+        &self,
+        sources: I,
+        options: &ConvertOptions,
+    ) -> Vec<Result<String, ConvertError>>
+    where
+        I: IntoIterator,
+        I::Item: ImageSource + Send,
+    {
+        map_in_order(sources.into_iter().collect(), |source| {
+            self.image_to_ascii(source, options.clone())
+        })
+    }
+
+    /// Start converting the frames of an animated GIF into ASCII using the given [ConvertOptions].
+    ///
+    /// See [AsciiFrames::new] for the errors that can be returned.
+    pub fn animation_to_ascii<'a, S>(
+        &self,
+        source: S,
+        options: ConvertOptions,
+    ) -> Result<AsciiFrames<'a>, ConvertError>
+    where
+        S: ImageSource,
+        S::Buffer: 'a,
+    {
+        AsciiFrames::new(source, options)
+    }
+
+    /// Convert ASCII into a PNG using a [SymbolMap] and [ImageRenderMode], drawing the
+    /// characters that are not in the map as the [UnknownSymbolPolicy] says.
+    ///
+    /// This gives the same image as [crate::ascii_to_image_with_unknown_symbols], but the
+    /// characters are drawn with the font the [Converter] already parsed.
+    pub fn ascii_to_image(
+        &self,
+        ascii: &str,
+        symbol_map: &SymbolMap,
+        render_mode: ImageRenderMode,
+        policy: UnknownSymbolPolicy,
+    ) -> Result<Cursor<Vec<u8>>, ConvertError> {
+        self.ascii_to_scaled_image(ascii, symbol_map, render_mode, policy, 1)
+    }
+
+    /// Convert ASCII into a PNG using a [SymbolMap], [ImageRenderMode] and
+    /// [UnknownSymbolPolicy], drawn `scale` times as large as normal.
+    ///
+    /// See [Ascii::with_scale] for how the image is scaled.
+    pub fn ascii_to_scaled_image(
+        &self,
+        ascii: &str,
+        symbol_map: &SymbolMap,
+        render_mode: ImageRenderMode,
+        policy: UnknownSymbolPolicy,
+        scale: u32,
+    ) -> Result<Cursor<Vec<u8>>, ConvertError> {
+        self.ascii_to_oriented_image(
+            ascii,
+            symbol_map,
+            render_mode,
+            policy,
+            scale,
+            &Pipeline::new(),
+        )
+    }
+
+    /// Convert ASCII into a PNG using a [SymbolMap], [ImageRenderMode] and
+    /// [UnknownSymbolPolicy], drawn `scale` times as large as normal and then turned and mirrored
+    /// by the rotate and flip stages of the `orientation` [Pipeline].
+    ///
+    /// The other stages of the pipeline are ignored. See [Ascii::with_rotation] and
+    /// [Ascii::with_flip] for how the image is turned and mirrored.
+    pub fn ascii_to_oriented_image(
+        &self,
+        ascii: &str,
+        symbol_map: &SymbolMap,
+        render_mode: ImageRenderMode,
+        policy: UnknownSymbolPolicy,
+        scale: u32,
+        orientation: &Pipeline,
+    ) -> Result<Cursor<Vec<u8>>, ConvertError> {
+        let ascii = Ascii::with_symbol_map(ascii, symbol_map.clone())
+            .with_render_mode(render_mode)
+            .with_unknown_symbol_policy(policy)
+            .with_scale(scale)
+            .with_font(self.font.clone());
+
+        orientation
+            .stages()
+            .iter()
+            .fold(ascii, |ascii, stage| match *stage {
+                Stage::Rotate(rotation) => ascii.with_rotation(rotation),
+                Stage::Flip(flip) => ascii.with_flip(flip),
+                _ => ascii,
+            })
+            .convert_to_image()
+    }
+
+    /// Convert ASCII into an SVG using a [SymbolMap] and [UnknownSymbolPolicy].
+    ///
+    /// This gives the same SVG as [crate::ascii_to_svg] when the default [SymbolMap] and
+    /// [UnknownSymbolPolicy] are used.
+    pub fn ascii_to_svg(
+        &self,
+        ascii: &str,
+        symbol_map: &SymbolMap,
+        policy: UnknownSymbolPolicy,
+    ) -> Result<String, ConvertError> {
+        Ascii::with_symbol_map(ascii, symbol_map.clone())
+            .with_unknown_symbol_policy(policy)
+            .with_font(self.font.clone())
+            .convert_to_svg()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{fs, path::Path};
+
+    // Test that a [Converter] can be shared between threads.
+    #[test]
+    fn test_send_sync() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<Converter>();
+    }
+
+    // Test that charsets are registered by name.
+    #[test]
+    fn test_charsets() {
+        let converter = Converter::new()
+            .unwrap()
+            .with_charset("blocks", "█▓▒░ ")
+            .unwrap()
+            .with_calibrated_charset("calibrated", "@%#*+=-:. ")
+            .unwrap();
+
+        assert_eq!(
+            converter.charset(DEFAULT_CHARSET),
+            Some(&SymbolMap::default())
+        );
+        assert_eq!(
+            converter.charset(PRINTABLE_CHARSET),
+            Some(&SymbolMap::printable())
+        );
+        assert_eq!(
+            converter.charset("blocks"),
+            SymbolMap::from_ramp("█▓▒░ ").ok().as_ref()
+        );
+        assert_eq!(
+            converter.charset("calibrated"),
+            SymbolMap::calibrated("@%#*+=-:. ").ok().as_ref()
+        );
+        assert_eq!(converter.charset("missing"), None);
+        assert!(matches!(
+            Converter::new().unwrap().with_charset("empty", ""),
+            Err(ConvertError::EmptySymbolRamp)
+        ));
+    }
+
+    // Test that a [Converter] shared by several threads gives the same results as the public functions.
+    #[test]
+    fn test_shared_conversions() {
+        // The idea to use "CARGO_MANIFEST_DIR" comes from StackOverflow:
+        // https://stackoverflow.com/questions/30003921/how-can-i-locate-resources-for-testing-with-cargo
+        let image_path = concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/test_assets/images/freakazoid-small.png"
+        );
+        let ascii_path = concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/test_assets/ascii/freakazoid-small.txt"
+        );
+        let ascii = fs::read_to_string(ascii_path).unwrap();
+        let converter = Converter::new().unwrap();
+        let symbol_map = converter.charset(DEFAULT_CHARSET).unwrap();
+
+        std::thread::scope(|scope| {
+            for _ in 0..4 {
+                scope.spawn(|| {
+                    assert_eq!(
+                        converter.image_to_ascii(Path::new(image_path), ConvertOptions::default()),
+                        Ok(ascii.clone())
+                    );
+                    let mut written = Vec::new();
+                    converter
+                        .image_to_ascii_writer(
+                            Path::new(image_path),
+                            ConvertOptions::default(),
+                            &mut written,
+                        )
+                        .unwrap();
+                    assert_eq!(String::from_utf8(written).unwrap(), ascii);
+                    assert_eq!(
+                        converter.ascii_to_image(
+                            &ascii,
+                            symbol_map,
+                            ImageRenderMode::Glyphs,
+                            UnknownSymbolPolicy::Error
+                        ),
+                        crate::ascii_to_image(&ascii)
+                    );
+                    assert_eq!(
+                        converter.ascii_to_svg(&ascii, symbol_map, UnknownSymbolPolicy::Error),
+                        crate::ascii_to_svg(&ascii)
+                    );
+                });
+            }
+        });
+    }
+}
@@ -0,0 +1,177 @@
+//! Sources of images to convert.
+//!
+//! The converter reads images from an [AsciiImageBuffer], which has to be seekable so that the
+//! format of the image can be guessed from its header. The [ImageSource] trait opens anything
+//! that holds an image as such a buffer: buffers that are already open, byte slices and vectors
+//! that are already in memory, and files on disk, except in WebAssembly, where there is no file
+//! system. With the `async` feature, [read_stream]
+//! collects an asynchronous stream of bytes, such as an upload or a download, into a buffer
+//! without going through a temporary file.
+//!
+//! Robert Peterson and Kelsey Werner 2023
+
+#[cfg(any(feature = "async", not(target_arch = "wasm32")))]
+use crate::converter::ErrorSource;
+use crate::converter::{image::AsciiImageBuffer, ConvertError};
+use std::io::Cursor;
+#[cfg(not(target_arch = "wasm32"))]
+use std::{
+    fs::File,
+    io::BufReader,
+    path::{Path, PathBuf},
+};
+
+/// [ImageSource] is a trait for anything that an image can be read from.
+pub trait ImageSource {
+    /// The buffer the image is read from once the source is opened.
+    type Buffer: AsciiImageBuffer;
+
+    /// Open the source for reading.
+    ///
+    /// [ConvertError::ReadError] is returned if the source can't be opened.
+    fn open(self) -> Result<Self::Buffer, ConvertError>;
+}
+
+impl<'a, T> ImageSource for &'a mut T
+where
+    T: AsciiImageBuffer,
+{
+    type Buffer = &'a mut T;
+
+    /// A buffer that is already open is read as is.
+    fn open(self) -> Result<Self::Buffer, ConvertError> {
+        Ok(self)
+    }
+}
+
+impl<'a> ImageSource for &'a [u8] {
+    type Buffer = Cursor<&'a [u8]>;
+
+    /// The bytes are read in place without being copied.
+    fn open(self) -> Result<Self::Buffer, ConvertError> {
+        Ok(Cursor::new(self))
+    }
+}
+
+impl ImageSource for Vec<u8> {
+    type Buffer = Cursor<Vec<u8>>;
+
+    /// The bytes are read in place without being copied.
+    fn open(self) -> Result<Self::Buffer, ConvertError> {
+        Ok(Cursor::new(self))
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl ImageSource for &Path {
+    type Buffer = BufReader<File>;
+
+    /// The file at the path is opened for buffered reading.
+    fn open(self) -> Result<Self::Buffer, ConvertError> {
+        File::open(self)
+            .map(BufReader::new)
+            .map_err(|e| ConvertError::ReadError(ErrorSource::new(e)))
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl ImageSource for PathBuf {
+    type Buffer = BufReader<File>;
+
+    /// The file at the path is opened for buffered reading.
+    fn open(self) -> Result<Self::Buffer, ConvertError> {
+        self.as_path().open()
+    }
+}
+
+/// Collect an asynchronous stream of byte chunks into a buffer that can be used as an
+/// [ImageSource].
+///
+/// Images can't be decoded until their header has been guessed, which needs the whole image, so
+/// the stream is read to the end before anything is converted. [ConvertError::ReadError] is
+/// returned with the error of the stream if the stream returns an error.
+#[cfg(feature = "async")]
+pub async fn read_stream<S, B, E>(stream: S) -> Result<Cursor<Vec<u8>>, ConvertError>
+where
+    S: futures_util::Stream<Item = Result<B, E>>,
+    B: AsRef<[u8]>,
+    E: std::error::Error + Send + Sync + 'static,
+{
+    use futures_util::TryStreamExt;
+
+    stream
+        .map_err(|e| ConvertError::ReadError(ErrorSource::new(e)))
+        .try_fold(Vec::new(), |mut bytes, chunk| async move {
+            bytes.extend_from_slice(chunk.as_ref());
+            Ok(bytes)
+        })
+        .await
+        .map(Cursor::new)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{fs, io::Read};
+
+    // The idea to use "CARGO_MANIFEST_DIR" comes from StackOverflow:
+    // https://stackoverflow.com/questions/30003921/how-can-i-locate-resources-for-testing-with-cargo
+    const IMAGE_PATH: &str = concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/test_assets/images/freakazoid-small.png"
+    );
+
+    // Reads a source to the end
+    fn read_source<S: ImageSource>(source: S) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        source.open().unwrap().read_to_end(&mut bytes).unwrap();
+        bytes
+    }
+
+    // Test that each kind of source reads the same bytes.
+    #[test]
+    fn test_open() {
+        let expected = fs::read(IMAGE_PATH).unwrap();
+
+        assert_eq!(read_source(expected.as_slice()), expected);
+        assert_eq!(read_source(expected.clone()), expected);
+        assert_eq!(read_source(Path::new(IMAGE_PATH)), expected);
+        assert_eq!(read_source(PathBuf::from(IMAGE_PATH)), expected);
+        assert_eq!(read_source(&mut Cursor::new(expected.clone())), expected);
+    }
+
+    // Test that a missing file can't be opened.
+    #[test]
+    fn test_open_missing_file() {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("test_assets/images/missing.png");
+
+        assert!(matches!(
+            path.open().err(),
+            Some(ConvertError::ReadError(_))
+        ));
+    }
+
+    // Test that a stream of chunks is collected in order.
+    #[cfg(feature = "async")]
+    #[actix_web::test]
+    async fn test_read_stream() {
+        let expected = fs::read(IMAGE_PATH).unwrap();
+        let chunks = expected
+            .chunks(100)
+            .map(|chunk| Ok::<_, std::io::Error>(chunk.to_vec()));
+        let buffer = read_stream(futures_util::stream::iter(chunks))
+            .await
+            .unwrap();
+
+        assert_eq!(buffer.into_inner(), expected);
+
+        let failing = futures_util::stream::iter([
+            Ok(vec![0_u8]),
+            Err(std::io::Error::from(std::io::ErrorKind::Other)),
+        ]);
+        assert!(matches!(
+            read_stream(failing).await.err(),
+            Some(ConvertError::ReadError(_))
+        ));
+    }
+}
@@ -0,0 +1,149 @@
+//! ASCII hidden in the pixels of a photo.
+//!
+//! The text chunks of [super::png_text] are lost when a site strips the metadata of an upload.
+//! [hide] keeps ASCII in the pixels themselves instead: each bit of the text replaces the least
+//! significant bit of a red, green or blue channel of the carrier image, which changes the color
+//! of a pixel by at most one step and can't be seen. The text starts with [MAGIC] and its length
+//! so that [reveal] can tell an image with hidden ASCII from any other image. The alpha channel
+//! is left alone, and the result must be saved in a lossless format such as PNG, because lossy
+//! compression scrambles the lowest bits of the colors.
+//!
+//! Robert Peterson and Kelsey Werner 2023
+
+use crate::converter::{dimension::Dimension, ConvertError};
+use image::{DynamicImage, RgbaImage};
+
+/// The bytes hidden before the text so that images with hidden ASCII can be recognized.
+pub const MAGIC: [u8; 4] = *b"ASCI";
+
+/// The number of bytes hidden before the text, which are the [MAGIC] and the length of the text.
+const HEADER_LENGTH: usize = MAGIC.len() + 4;
+
+/// The number of color channels of each pixel that carry bits of the text.
+const CHANNELS_PER_PIXEL: usize = 3;
+
+/// The number of bytes of text that can be hidden in a carrier image of a [Dimension].
+pub fn capacity(dimension: Dimension) -> usize {
+    let bits = dimension.width as usize * dimension.height as usize * CHANNELS_PER_PIXEL;
+    (bits / 8).saturating_sub(HEADER_LENGTH)
+}
+
+/// Hide ASCII in the least significant bits of the colors of a carrier image.
+///
+/// The carrier is returned as 8-bit RGBA with the text hidden in it. Returns
+/// [ConvertError::CarrierTooSmall] when the text is longer than the [capacity] of the carrier.
+pub fn hide(carrier: &DynamicImage, ascii: &str) -> Result<RgbaImage, ConvertError> {
+    let dimension = Dimension::from((carrier.width(), carrier.height()));
+    if ascii.len() > capacity(dimension) {
+        return Err(ConvertError::CarrierTooSmall);
+    }
+
+    let mut bytes = Vec::with_capacity(HEADER_LENGTH + ascii.len());
+    bytes.extend_from_slice(&MAGIC);
+    bytes.extend_from_slice(&(ascii.len() as u32).to_be_bytes());
+    bytes.extend_from_slice(ascii.as_bytes());
+
+    let mut img = carrier.to_rgba8();
+    let bits = bytes
+        .iter()
+        .flat_map(|byte| (0..8).rev().map(move |shift| (byte >> shift) & 1));
+    for (channel, bit) in color_channels_mut(&mut img).zip(bits) {
+        *channel = (*channel & !1) | bit;
+    }
+
+    Ok(img)
+}
+
+/// Read the ASCII that [hide] hid in an image.
+///
+/// Returns [ConvertError::NoHiddenText] when the image doesn't start with the [MAGIC], when
+/// the length of the text is more than the image can hold, or when the text is not UTF-8.
+pub fn reveal(img: &DynamicImage) -> Result<String, ConvertError> {
+    let img = img.to_rgba8();
+    let mut bytes = img
+        .pixels()
+        .flat_map(|pixel| pixel.0.into_iter().take(CHANNELS_PER_PIXEL))
+        .map(|channel| channel & 1)
+        .collect::<Vec<u8>>()
+        .chunks_exact(8)
+        .map(|bits| bits.iter().fold(0, |byte, bit| (byte << 1) | bit))
+        .collect::<Vec<u8>>();
+
+    if bytes.len() < HEADER_LENGTH || bytes[..MAGIC.len()] != MAGIC {
+        return Err(ConvertError::NoHiddenText);
+    }
+    let mut length = [0; 4];
+    length.copy_from_slice(&bytes[MAGIC.len()..HEADER_LENGTH]);
+    let length = u32::from_be_bytes(length) as usize;
+    if length > bytes.len() - HEADER_LENGTH {
+        return Err(ConvertError::NoHiddenText);
+    }
+
+    bytes.truncate(HEADER_LENGTH + length);
+    String::from_utf8(bytes.split_off(HEADER_LENGTH)).map_err(|_| ConvertError::NoHiddenText)
+}
+
+/// The red, green and blue channels of every pixel of an image, in order.
+fn color_channels_mut(img: &mut RgbaImage) -> impl Iterator<Item = &mut u8> {
+    img.pixels_mut()
+        .flat_map(|pixel| pixel.0[..CHANNELS_PER_PIXEL].iter_mut())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{Rgba, RgbaImage};
+
+    // Create a carrier image with a different color at every pixel.
+    fn test_carrier(width: u32, height: u32) -> DynamicImage {
+        DynamicImage::ImageRgba8(RgbaImage::from_fn(width, height, |x, y| {
+            Rgba([(x * 7) as u8, (y * 13) as u8, (x + y) as u8, 200])
+        }))
+    }
+
+    // Test that hidden ASCII is revealed again and the carrier barely changes.
+    #[test]
+    fn test_hide_and_reveal() {
+        let carrier = test_carrier(16, 16);
+        let ascii = "  /\\_/\\\n ( o.o )\n  > ^ <";
+
+        let hidden = hide(&carrier, ascii).unwrap();
+
+        assert_eq!(
+            reveal(&DynamicImage::ImageRgba8(hidden.clone())),
+            Ok(ascii.to_string())
+        );
+        for (before, after) in carrier.to_rgba8().pixels().zip(hidden.pixels()) {
+            assert!(before.0[..3]
+                .iter()
+                .zip(&after.0[..3])
+                .all(|(before, after)| before.abs_diff(*after) <= 1));
+            assert_eq!(before.0[3], after.0[3]);
+        }
+    }
+
+    // Test that text longer than the capacity of the carrier is rejected.
+    #[test]
+    fn test_hide_carrier_too_small() {
+        // 8 by 8 pixels hold 24 bytes, 8 of which are the header
+        let carrier = test_carrier(8, 8);
+        let capacity = capacity(Dimension::from((8, 8)));
+        assert_eq!(capacity, 16);
+
+        assert!(hide(&carrier, &"#".repeat(capacity)).is_ok());
+        assert_eq!(
+            hide(&carrier, &"#".repeat(capacity + 1)),
+            Err(ConvertError::CarrierTooSmall)
+        );
+    }
+
+    // Test that an image without hidden ASCII is recognized.
+    #[test]
+    fn test_reveal_no_hidden_text() {
+        assert_eq!(
+            reveal(&test_carrier(16, 16)),
+            Err(ConvertError::NoHiddenText)
+        );
+        assert_eq!(reveal(&test_carrier(1, 1)), Err(ConvertError::NoHiddenText));
+    }
+}
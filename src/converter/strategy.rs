@@ -0,0 +1,299 @@
+//! Strategies for mapping pixels to symbols.
+//!
+//! Converting an image to ASCII comes down to picking a symbol for each cell of pixels. The
+//! [SymbolStrategy] trait is that choice, so new conversion modes can be added, or injected by
+//! users of the library, without changing the converter. A [SymbolMap] is the default brightness
+//! ramp strategy, [EdgeStrategy] draws the outlines of the image with line symbols,
+//! [QuadrantBlocks] packs 2x2 pixels into Unicode block characters, and any closure that takes
+//! a [Cell] and returns a [char] is a strategy too.
+//!
+//! Robert Peterson and Kelsey Werner 2023
+
+use crate::converter::symbol_map::SymbolMap;
+use image::GrayImage;
+
+/// Pixels darker than this are treated as filled by [QuadrantBlocks].
+const QUADRANT_THRESHOLD: u8 = 128;
+
+/// The Unicode block characters for each combination of filled quadrants.
+///
+/// The index is made of one bit per quadrant: 1 is the upper left, 2 is the upper right,
+/// 4 is the lower left, and 8 is the lower right.
+const QUADRANT_SYMBOLS: [char; 16] = [
+    ' ', '\u{2598}', '\u{259D}', '\u{2580}', '\u{2596}', '\u{258C}', '\u{259E}', '\u{259B}',
+    '\u{2597}', '\u{259A}', '\u{2590}', '\u{259C}', '\u{2584}', '\u{2599}', '\u{259F}', '\u{2588}',
+];
+
+/// The default gradient strength above which [EdgeStrategy] draws an edge.
+const EDGE_THRESHOLD: f32 = 96.0;
+
+/// [Cell] is a struct that holds the block of pixels that is turned into a single symbol.
+///
+/// The pixels around the cell can be read too, which is needed to find edges.
+pub struct Cell<'a> {
+    /// The scaled image the cell is part of.
+    image: &'a GrayImage,
+    /// The column of the leftmost pixel of the cell.
+    x: u32,
+    /// The row of the topmost pixel of the cell.
+    y: u32,
+    /// The width of the cell in pixels.
+    width: u32,
+    /// The height of the cell in pixels.
+    height: u32,
+}
+
+impl<'a> Cell<'a> {
+    /// Create a new [Cell] of the given size at a pixel position of an image.
+    pub fn new(image: &'a GrayImage, x: u32, y: u32, width: u32, height: u32) -> Cell<'a> {
+        Cell {
+            image,
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    /// The width and height of the cell in pixels.
+    pub fn size(&self) -> (u32, u32) {
+        (self.width, self.height)
+    }
+
+    /// The brightness of a pixel relative to the top left of the cell.
+    ///
+    /// The offsets can reach outside of the cell. Pixels past the edge of the image take the
+    /// brightness of the closest pixel in the image.
+    pub fn pixel(&self, dx: i64, dy: i64) -> u8 {
+        let (width, height) = self.image.dimensions();
+        if width == 0 || height == 0 {
+            return u8::MAX;
+        }
+
+        let x = (self.x as i64 + dx).clamp(0, width as i64 - 1) as u32;
+        let y = (self.y as i64 + dy).clamp(0, height as i64 - 1) as u32;
+        self.image.get_pixel(x, y)[0]
+    }
+
+    /// The mean brightness of the pixels in the cell.
+    pub fn brightness(&self) -> u8 {
+        let mut total = 0_u32;
+        for dy in 0..self.height {
+            for dx in 0..self.width {
+                total += self.pixel(dx as i64, dy as i64) as u32;
+            }
+        }
+
+        (total / (self.width * self.height).max(1)) as u8
+    }
+}
+
+/// [ThreadSafe] is a trait that is used as a trait alias for [Sync] when the `parallel` feature
+/// is enabled, since the rows of an image are then converted on several threads at once.
+/// Without the feature, every type is [ThreadSafe].
+#[cfg(feature = "parallel")]
+pub trait ThreadSafe: Sync {}
+#[cfg(feature = "parallel")]
+impl<T: Sync + ?Sized> ThreadSafe for T {}
+
+/// [ThreadSafe] is a trait that is used as a trait alias for [Sync] when the `parallel` feature
+/// is enabled, since the rows of an image are then converted on several threads at once.
+/// Without the feature, every type is [ThreadSafe].
+#[cfg(not(feature = "parallel"))]
+pub trait ThreadSafe {}
+#[cfg(not(feature = "parallel"))]
+impl<T: ?Sized> ThreadSafe for T {}
+
+/// [SymbolStrategy] is a trait that picks the symbol used for a [Cell] of an image.
+///
+/// With the `parallel` feature, strategies must be [Sync] so that rows of cells can be
+/// converted at the same time.
+pub trait SymbolStrategy: ThreadSafe {
+    /// The width and height in pixels of the cell that each symbol covers.
+    ///
+    /// Most strategies look at a single pixel per symbol, which is the default.
+    fn cell_size(&self) -> (u32, u32) {
+        (1, 1)
+    }
+
+    /// Pick the symbol for a [Cell].
+    fn symbol_for_cell(&self, cell: &Cell) -> char;
+}
+
+impl SymbolStrategy for SymbolMap {
+    /// Map the brightness of the cell onto the ramp of the [SymbolMap].
+    fn symbol_for_cell(&self, cell: &Cell) -> char {
+        self.symbol_for_brightness(cell.brightness())
+    }
+}
+
+impl<F> SymbolStrategy for F
+where
+    F: Fn(&Cell) -> char + ThreadSafe,
+{
+    /// Call the closure with the cell.
+    fn symbol_for_cell(&self, cell: &Cell) -> char {
+        self(cell)
+    }
+}
+
+/// [EdgeStrategy] is a struct that draws the edges of an image with line symbols.
+///
+/// The strength and direction of the edge at each pixel is found with the Sobel operator:
+/// <https://en.wikipedia.org/wiki/Sobel_operator>. Pixels without a strong edge are drawn
+/// with the [SymbolMap].
+#[derive(Debug, Clone, PartialEq)]
+pub struct EdgeStrategy {
+    /// The map used for pixels that are not on an edge.
+    pub symbol_map: SymbolMap,
+    /// The gradient strength above which an edge is drawn.
+    pub threshold: f32,
+}
+
+impl Default for EdgeStrategy {
+    /// The default [EdgeStrategy] fills in the image with the default [SymbolMap].
+    fn default() -> Self {
+        EdgeStrategy {
+            symbol_map: SymbolMap::default(),
+            threshold: EDGE_THRESHOLD,
+        }
+    }
+}
+
+impl SymbolStrategy for EdgeStrategy {
+    /// Draw an edge symbol that follows the edge through the cell, or fall back to the
+    /// [SymbolMap] if there is no edge.
+    fn symbol_for_cell(&self, cell: &Cell) -> char {
+        let p = |dx, dy| cell.pixel(dx, dy) as f32;
+
+        // the change in brightness from left to right and from top to bottom
+        let gx = p(1, -1) + 2.0 * p(1, 0) + p(1, 1) - p(-1, -1) - 2.0 * p(-1, 0) - p(-1, 1);
+        let gy = p(-1, 1) + 2.0 * p(0, 1) + p(1, 1) - p(-1, -1) - 2.0 * p(0, -1) - p(1, -1);
+
+        if gx.hypot(gy) <= self.threshold {
+            return self.symbol_map.symbol_for_brightness(cell.brightness());
+        }
+
+        // the edge runs across the direction the brightness changes in, and rows grow
+        // downwards, so the angle is flipped to match how the symbols look
+        let angle = (-gy).atan2(gx).to_degrees().rem_euclid(180.0);
+        match angle {
+            a if !(22.5..157.5).contains(&a) => '|',
+            a if a < 67.5 => '\\',
+            a if a < 112.5 => '-',
+            _ => '/',
+        }
+    }
+}
+
+/// [QuadrantBlocks] is a struct that packs 2x2 pixels into a Unicode quadrant block character.
+///
+/// Each quadrant of the character is filled if its pixel is dark, which doubles the
+/// resolution of the image in both directions.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct QuadrantBlocks;
+
+impl SymbolStrategy for QuadrantBlocks {
+    /// Each quadrant block covers 2x2 pixels.
+    fn cell_size(&self) -> (u32, u32) {
+        (2, 2)
+    }
+
+    /// Fill the quadrants of the character whose pixels are dark.
+    fn symbol_for_cell(&self, cell: &Cell) -> char {
+        let mut quadrants = 0;
+        for (bit, (dx, dy)) in [(0, 0), (1, 0), (0, 1), (1, 1)].into_iter().enumerate() {
+            if cell.pixel(dx, dy) < QUADRANT_THRESHOLD {
+                quadrants |= 1 << bit;
+            }
+        }
+
+        QUADRANT_SYMBOLS[quadrants]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{ImageBuffer, Luma};
+
+    // Test that a cell reads its pixels and clamps reads past the edge of the image.
+    #[test]
+    fn test_cell() {
+        let img = ImageBuffer::from_fn(4, 4, |x, y| Luma([(x + y * 4) as u8 * 10]));
+        let cell = Cell::new(&img, 2, 2, 2, 2);
+
+        assert_eq!(cell.size(), (2, 2));
+        assert_eq!(cell.pixel(0, 0), 100);
+        assert_eq!(cell.pixel(1, 1), 150);
+        assert_eq!(cell.pixel(5, 5), 150);
+        assert_eq!(cell.pixel(-5, 0), 80);
+        assert_eq!(cell.brightness(), 125);
+    }
+
+    // Test that a [SymbolMap] maps the brightness of the cell onto its ramp.
+    #[test]
+    fn test_symbol_map_strategy() {
+        let img = ImageBuffer::from_pixel(1, 1, Luma([255]));
+        let symbol_map = SymbolMap::from_ramp("@. ").unwrap();
+
+        assert_eq!(symbol_map.cell_size(), (1, 1));
+        assert_eq!(
+            symbol_map.symbol_for_cell(&Cell::new(&img, 0, 0, 1, 1)),
+            ' '
+        );
+    }
+
+    // Test that a closure can be used as a strategy.
+    #[test]
+    fn test_closure_strategy() {
+        let img = ImageBuffer::from_pixel(1, 1, Luma([10]));
+        let strategy = |cell: &Cell| if cell.brightness() < 128 { '#' } else { ' ' };
+
+        assert_eq!(strategy.symbol_for_cell(&Cell::new(&img, 0, 0, 1, 1)), '#');
+    }
+
+    // Test that edges are drawn with symbols that follow them.
+    #[test]
+    fn test_edge_strategy() {
+        let strategy = EdgeStrategy::default();
+
+        // dark on the left, light on the right
+        let img = ImageBuffer::from_fn(3, 3, |x, _| Luma([if x == 0 { 0 } else { 255 }]));
+        assert_eq!(strategy.symbol_for_cell(&Cell::new(&img, 1, 1, 1, 1)), '|');
+
+        // dark on the top, light on the bottom
+        let img = ImageBuffer::from_fn(3, 3, |_, y| Luma([if y == 0 { 0 } else { 255 }]));
+        assert_eq!(strategy.symbol_for_cell(&Cell::new(&img, 1, 1, 1, 1)), '-');
+
+        // dark in the upper left corner
+        let img = ImageBuffer::from_fn(3, 3, |x, y| Luma([if x + y < 2 { 0 } else { 255 }]));
+        assert_eq!(strategy.symbol_for_cell(&Cell::new(&img, 1, 1, 1, 1)), '/');
+
+        // dark in the upper right corner
+        let img = ImageBuffer::from_fn(3, 3, |x, y| Luma([if x > y { 0 } else { 255 }]));
+        assert_eq!(strategy.symbol_for_cell(&Cell::new(&img, 1, 1, 1, 1)), '\\');
+
+        // no edge
+        let img = ImageBuffer::from_pixel(3, 3, Luma([255]));
+        assert_eq!(strategy.symbol_for_cell(&Cell::new(&img, 1, 1, 1, 1)), ' ');
+    }
+
+    // Test that each combination of dark quadrants is packed into the matching block.
+    #[test]
+    fn test_quadrant_blocks() {
+        let img = ImageBuffer::from_fn(4, 2, |x, y| {
+            Luma([if (x < 2 && y == 0) || x == 3 { 0 } else { 255 }])
+        });
+
+        assert_eq!(QuadrantBlocks.cell_size(), (2, 2));
+        assert_eq!(
+            QuadrantBlocks.symbol_for_cell(&Cell::new(&img, 0, 0, 2, 2)),
+            '\u{2580}'
+        );
+        assert_eq!(
+            QuadrantBlocks.symbol_for_cell(&Cell::new(&img, 2, 0, 2, 2)),
+            '\u{2590}'
+        );
+    }
+}
@@ -2,11 +2,29 @@
 //!
 //! To convert an image to ASCII, the Luma brightness of a pixel in an image is mapped
 //! to an ASCII value. To convert ASCII to an image, the ASCII is mapped to a Luma brightness
-//! value. This module contains the mapping and the logic to search the mapping.
+//! value. This module contains the [SymbolMap] that holds the mapping and the logic to search
+//! the mapping. A [SymbolMap] can be built from a custom brightness ramp, or the default
+//! 70 [SYMBOLS] can be used. [SymbolMap::printable] covers every printable ASCII symbol
+//! instead, with the brightness of each measured from its glyph, so that pasted ASCII art that
+//! uses symbols outside of the ramp can still be read. The brightness of every ASCII symbol is kept in a lookup table,
+//! so converting large ASCII inputs doesn't search the symbols for every character.
+//!
+//! Any ramp can be calibrated with [SymbolMap::calibrated] too, which draws each symbol with the
+//! font and measures its ink, so the brightness of the symbols follows how dark they look
+//! instead of their place in the ramp.
+//!
+//! A [SymbolSet] picks one of the built-in maps. Each quantizes brightness its own way: the
+//! ramps of [SymbolSet::Standard70] and [SymbolSet::Minimal10] are spread evenly across the
+//! brightness range, the shade characters of [SymbolSet::Blocks] sit at the share of their
+//! cell they fill, and [SymbolSet::Binary] splits the range in half with a threshold.
 //!
 //! Robert Peterson and Kelsey Werner 2023
 
-use crate::converter::ConvertError;
+use crate::converter::{
+    ascii::{ink_coverage, load_font},
+    ConvertError,
+};
+use serde::{Deserialize, Serialize};
 
 /// ASCII symbols used for Luma brightness mapping
 ///
@@ -18,46 +36,496 @@ const SYMBOLS: [char; 70] = [
     'i', '!', 'l', 'I', ';', ':', ',', '\"', '^', '`', '\'', '.', ' ',
 ];
 
-/// Divide ASCII number range (0-255) into 70 parts.
+/// The 10 ASCII symbols of [SymbolSet::Minimal10], from darkest to lightest.
+///
+/// The suggested [char]s are from <http://paulbourke.net/dataformats/asciiart/>
+const MINIMAL_SYMBOLS: [char; 10] = ['@', '%', '#', '*', '+', '=', '-', ':', '.', ' '];
+
+/// The shade characters of [SymbolSet::Blocks] with the brightness of the share of their cell
+/// they leave blank, from darkest to lightest.
+const BLOCK_SYMBOLS: [(char, u8); 5] = [
+    ('\u{2588}', 0),
+    ('\u{2593}', 64),
+    ('\u{2592}', 128),
+    ('\u{2591}', 191),
+    (' ', 255),
+];
+
+/// The two symbols of [SymbolSet::Binary], which sit at the ends of the brightness range so
+/// that every pixel below the middle is mapped to the first one.
+const BINARY_SYMBOLS: [(char, u8); 2] = [('#', 0), (' ', 255)];
+
+/// Every printable ASCII symbol with its measured brightness, from darkest to lightest.
 ///
-/// This allows us to map [u8] to [SYMBOLS] and [SYMBOLS] indexes to [u8].
-const BRIGHT_DIV: f32 = 3.65;
+/// The brightness is measured from how much of its cell the glyph of the symbol leaves blank
+/// when it is drawn with the font used for ASCII to image conversion, and is scaled so the
+/// inkiest symbol is 0 and the space is 255. These are the levels [SymbolMap::calibrated] gives
+/// the printable symbols, which are kept here so the map can be built without drawing them.
+const PRINTABLE_SYMBOLS: [(char, u8); 95] = [
+    ('M', 0),
+    ('N', 1),
+    ('@', 2),
+    ('B', 2),
+    ('W', 6),
+    ('R', 23),
+    ('Q', 24),
+    ('8', 26),
+    ('#', 30),
+    ('D', 32),
+    ('0', 34),
+    ('H', 34),
+    ('g', 37),
+    ('K', 41),
+    ('O', 42),
+    ('&', 45),
+    ('9', 46),
+    ('6', 46),
+    ('E', 51),
+    ('U', 52),
+    ('A', 54),
+    ('m', 55),
+    ('d', 55),
+    ('b', 55),
+    ('q', 56),
+    ('p', 56),
+    ('G', 58),
+    ('$', 59),
+    ('P', 62),
+    ('X', 70),
+    ('S', 71),
+    ('4', 71),
+    ('5', 72),
+    ('Z', 72),
+    ('k', 78),
+    ('a', 79),
+    ('h', 80),
+    ('V', 80),
+    ('3', 81),
+    ('%', 81),
+    ('w', 81),
+    ('e', 85),
+    ('2', 87),
+    ('F', 91),
+    ('I', 92),
+    ('o', 94),
+    ('y', 96),
+    ('n', 105),
+    ('C', 105),
+    ('u', 105),
+    ('1', 108),
+    ('J', 109),
+    ('T', 109),
+    ('Y', 111),
+    ('{', 116),
+    ('s', 116),
+    ('}', 117),
+    ('x', 120),
+    ('f', 120),
+    ('7', 121),
+    ('L', 121),
+    ('j', 122),
+    ('z', 123),
+    ('t', 124),
+    ('v', 127),
+    ('[', 128),
+    (']', 128),
+    ('i', 131),
+    ('l', 137),
+    ('c', 137),
+    ('?', 140),
+    ('=', 141),
+    ('|', 144),
+    ('<', 146),
+    ('>', 146),
+    (')', 151),
+    ('(', 151),
+    ('+', 151),
+    ('/', 154),
+    ('\\', 154),
+    ('r', 157),
+    ('*', 166),
+    ('!', 176),
+    ('^', 190),
+    (';', 190),
+    ('"', 194),
+    ('~', 199),
+    (':', 207),
+    (',', 214),
+    ('_', 224),
+    ('\'', 225),
+    ('-', 228),
+    ('.', 231),
+    ('`', 234),
+    (' ', 255),
+];
 
-/// Map a [u8] into a [char] from the symbol map.
-pub fn symbol_for_brightness(brightness: u8) -> char {
-    // dividing by 26 gives us 10 different results across the u8 range
-    // which allows us to map to the 10 different brightnesses in SYMBOLS
-    let idx = (brightness as f32 / BRIGHT_DIV) as usize;
-    SYMBOLS[idx]
+/// The span of the Luma brightness range (0-255) that is divided between the symbols.
+///
+/// Dividing this span by the 70 [SYMBOLS] gives 3.65, which allows us to map [u8] to
+/// symbol indexes and symbol indexes to [u8].
+const BRIGHT_SPAN: f32 = 255.5;
+
+/// The number of ASCII [char]s, which all have a slot in the lookup table of a [SymbolMap].
+const ASCII_CHARS: usize = 128;
+
+/// [SymbolMap] is a struct that maps Luma brightness values to symbols and back.
+///
+/// The symbols are ordered from the darkest brightness (0) to the lightest brightness (255).
+#[derive(Debug, Clone, PartialEq)]
+pub struct SymbolMap {
+    /// The symbols of the map, ordered from darkest to lightest.
+    symbols: Vec<char>,
+    /// The size of the brightness range that maps to a single symbol.
+    bright_div: f32,
+    /// The brightness of each ASCII [char], indexed by its code, or [None] if it isn't a symbol.
+    ascii_brightness: [Option<u8>; ASCII_CHARS],
+    /// The measured brightness of the symbols, or [None] when they are spread evenly across
+    /// the brightness range.
+    levels: Option<Levels>,
+}
+
+/// [Levels] is a struct that holds the measured brightness of the symbols of a [SymbolMap].
+#[derive(Debug, Clone, PartialEq)]
+struct Levels {
+    /// The brightness of each symbol, in the order of the symbols.
+    brightness: Vec<u8>,
+    /// The index of the symbol whose brightness is nearest to each [u8], so that mapping a
+    /// pixel to a symbol doesn't search the symbols.
+    nearest: Vec<usize>,
 }
 
-/// Map a [char] in the symbol map into a [u8].
+/// Represent the built-in sets of symbols an image can be converted to ASCII with.
 ///
-/// This function returns [ConvertError::UnknownASCIISymbol] if [char] does not exist in the symbol map.
-pub fn brightness_for_symbol(symbol: char) -> Result<u8, ConvertError> {
-    let b = SYMBOLS
-        .into_iter()
-        .position(|c| c == symbol)
-        .map(|s| s as f32 * BRIGHT_DIV);
+/// A [SymbolSet] is picked for a conversion by putting its [SymbolMap] in the options, such as
+/// `ConvertOptions { symbol_map: SymbolSet::Blocks.into(), ..Default::default() }`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SymbolSet {
+    /// [SymbolSet::Standard70] spreads the 70 [SYMBOLS] of the default map evenly across the
+    /// brightness range.
+    #[default]
+    Standard70,
+    /// [SymbolSet::Minimal10] spreads the 10 [MINIMAL_SYMBOLS] evenly across the brightness
+    /// range, which gives bolder steps that read better at small widths.
+    Minimal10,
+    /// [SymbolSet::Blocks] maps each pixel to the nearest of the shade characters `█▓▒░` and
+    /// the space, which are not ASCII and need a font that has them.
+    Blocks,
+    /// [SymbolSet::Binary] maps pixels darker than the middle of the brightness range to `#`
+    /// and the others to a space.
+    Binary,
+}
+
+impl SymbolSet {
+    /// Every [SymbolSet], in the order they are offered.
+    pub const ALL: [SymbolSet; 4] = [
+        SymbolSet::Standard70,
+        SymbolSet::Minimal10,
+        SymbolSet::Blocks,
+        SymbolSet::Binary,
+    ];
+
+    /// The name of the set, such as `"minimal10"`, which is how it is written in JSON.
+    pub fn name(self) -> &'static str {
+        match self {
+            SymbolSet::Standard70 => "standard70",
+            SymbolSet::Minimal10 => "minimal10",
+            SymbolSet::Blocks => "blocks",
+            SymbolSet::Binary => "binary",
+        }
+    }
+
+    /// Find the set with a [SymbolSet::name], or [None] if there isn't one.
+    pub fn from_name(name: &str) -> Option<SymbolSet> {
+        Self::ALL.into_iter().find(|set| set.name() == name)
+    }
+}
+
+impl From<SymbolSet> for SymbolMap {
+    /// Build the [SymbolMap] of a [SymbolSet].
+    fn from(set: SymbolSet) -> SymbolMap {
+        match set {
+            SymbolSet::Standard70 => SymbolMap::default(),
+            SymbolSet::Minimal10 => SymbolMap::from_symbols(MINIMAL_SYMBOLS.to_vec()),
+            SymbolSet::Blocks => SymbolMap::from_sorted_levels(BLOCK_SYMBOLS.to_vec()),
+            SymbolSet::Binary => SymbolMap::from_sorted_levels(BINARY_SYMBOLS.to_vec()),
+        }
+    }
+}
+
+impl SymbolMap {
+    /// Create a new [SymbolMap] from a brightness ramp such as `"@%#*+=-:. "`.
+    ///
+    /// The first [char] of the ramp is used for the darkest pixels and the last [char] is
+    /// used for the lightest pixels. This function returns [ConvertError::EmptySymbolRamp]
+    /// if the ramp has no symbols and [ConvertError::DuplicateSymbol] if a symbol appears
+    /// more than once, since that symbol could not be mapped back into a single brightness.
+    pub fn from_ramp(ramp: &str) -> Result<SymbolMap, ConvertError> {
+        let mut symbols: Vec<char> = Vec::new();
+
+        for c in ramp.chars() {
+            if symbols.contains(&c) {
+                return Err(ConvertError::DuplicateSymbol(c));
+            }
+            symbols.push(c);
+        }
+
+        if symbols.is_empty() {
+            return Err(ConvertError::EmptySymbolRamp);
+        }
+
+        Ok(Self::from_symbols(symbols))
+    }
+
+    /// Create a new [SymbolMap] from symbols that are known to be unique.
+    fn from_symbols(symbols: Vec<char>) -> SymbolMap {
+        let bright_div = BRIGHT_SPAN / symbols.len() as f32;
+
+        let mut ascii_brightness = [None; ASCII_CHARS];
+        for (idx, c) in symbols.iter().enumerate() {
+            if c.is_ascii() {
+                ascii_brightness[*c as usize] = Some((idx as f32 * bright_div) as u8);
+            }
+        }
+
+        SymbolMap {
+            symbols,
+            bright_div,
+            ascii_brightness,
+            levels: None,
+        }
+    }
+
+    /// Create a new [SymbolMap] from symbols with a measured brightness each, such as
+    /// `[('@', 0), ('=', 141), (' ', 255)]`.
+    ///
+    /// Unlike a ramp, the symbols are not spread evenly across the brightness range. Each pixel
+    /// is mapped to the symbol whose brightness is nearest to it, and each symbol is mapped back
+    /// to its own brightness. The symbols are sorted from darkest to lightest. This function
+    /// returns [ConvertError::EmptySymbolRamp] if there are no symbols and
+    /// [ConvertError::DuplicateSymbol] if a symbol appears more than once.
+    pub fn from_levels(levels: &[(char, u8)]) -> Result<SymbolMap, ConvertError> {
+        let mut levels = levels.to_vec();
+        for (idx, (c, _)) in levels.iter().enumerate() {
+            if levels[..idx].iter().any(|(s, _)| s == c) {
+                return Err(ConvertError::DuplicateSymbol(*c));
+            }
+        }
+
+        if levels.is_empty() {
+            return Err(ConvertError::EmptySymbolRamp);
+        }
+
+        // the sort is stable, so symbols with the same brightness keep their order
+        levels.sort_by_key(|(_, brightness)| *brightness);
+        Ok(Self::from_sorted_levels(levels))
+    }
+
+    /// Create a new [SymbolMap] from unique symbols with a measured brightness each, which are
+    /// known to be sorted from darkest to lightest.
+    fn from_sorted_levels(levels: Vec<(char, u8)>) -> SymbolMap {
+        let (symbols, brightness): (Vec<char>, Vec<u8>) = levels.into_iter().unzip();
+
+        let mut ascii_brightness = [None; ASCII_CHARS];
+        for (c, b) in symbols.iter().zip(&brightness) {
+            if c.is_ascii() {
+                ascii_brightness[*c as usize] = Some(*b);
+            }
+        }
+
+        // the first of the symbols that are as near as each other is picked
+        let nearest = (0..=u8::MAX)
+            .map(|b| {
+                let distance = |idx: &usize| brightness[*idx].abs_diff(b);
+                (0..brightness.len()).min_by_key(distance).unwrap_or(0)
+            })
+            .collect();
+
+        SymbolMap {
+            bright_div: BRIGHT_SPAN / symbols.len() as f32,
+            symbols,
+            ascii_brightness,
+            levels: Some(Levels {
+                brightness,
+                nearest,
+            }),
+        }
+    }
+
+    /// Create a new [SymbolMap] from a brightness ramp, with the brightness of each symbol
+    /// calibrated from its glyph instead of spread evenly across the brightness range.
+    ///
+    /// Each symbol is drawn with the font used for ASCII to image conversion and the share of its
+    /// cell that is covered with ink is measured. The inkiest symbol gets brightness 0, the symbol
+    /// with the least ink gets 255, and the others sit between them by how much ink they have, so
+    /// symbols that look alike get brightnesses that are alike. Since the symbols are sorted by
+    /// their brightness, the order of the ramp doesn't matter. This function returns the errors of
+    /// [SymbolMap::from_ramp], [ConvertError::FontError] if the font can't be read, and
+    /// [ConvertError::UnknownASCIISymbol] if the font has no glyph for a symbol.
+    pub fn calibrated(ramp: &str) -> Result<SymbolMap, ConvertError> {
+        let symbols = Self::from_ramp(ramp)?.symbols;
+        let font = load_font()?;
+
+        let mut coverage = Vec::with_capacity(symbols.len());
+        for c in &symbols {
+            let ink = ink_coverage(&font, *c).ok_or(ConvertError::UnknownASCIISymbol(*c, None))?;
+            coverage.push((*c, ink));
+        }
+
+        let most_ink = coverage.iter().map(|(_, ink)| *ink).fold(0.0, f32::max);
+        let least_ink = coverage
+            .iter()
+            .map(|(_, ink)| *ink)
+            .fold(most_ink, f32::min);
+        // a ramp whose symbols all have the same ink is only as bright as its darkest symbol
+        let span = (most_ink - least_ink).max(f32::EPSILON);
+        let levels: Vec<(char, u8)> = coverage
+            .into_iter()
+            .map(|(c, ink)| (c, ((most_ink - ink) / span * u8::MAX as f32).round() as u8))
+            .collect();
+
+        Self::from_levels(&levels)
+    }
+
+    /// Create a new [SymbolMap] of every printable ASCII symbol, with the brightness measured
+    /// from the glyph of each symbol.
+    ///
+    /// The default map only has 70 symbols, so ASCII art that uses others, such as `=`, `V` or
+    /// `P`, can't be read with it. This map reads all 95 of them, and each is drawn as about as
+    /// bright as its glyph looks.
+    pub fn printable() -> SymbolMap {
+        Self::from_sorted_levels(PRINTABLE_SYMBOLS.to_vec())
+    }
+
+    /// Create a copy of the [SymbolMap] that uses `background` as its lightest symbol.
+    ///
+    /// The lightest symbol is used for the "empty" parts of an image, which is a space in the
+    /// default map. Some destinations strip leading and trailing spaces and ruin the alignment
+    /// of the ASCII, so a visible symbol such as `.` can be used instead. If `background` is
+    /// already one of the other symbols, it is removed from its old spot so that it still maps
+    /// to a single brightness.
+    pub fn with_background(&self, background: char) -> SymbolMap {
+        // the old lightest symbol is always replaced by the background
+        let lightest = self.symbols.len() - 1;
+
+        if let Some(levels) = &self.levels {
+            let mut kept: Vec<(char, u8)> = self.symbols[..lightest]
+                .iter()
+                .copied()
+                .zip(levels.brightness.iter().copied())
+                .filter(|(c, _)| *c != background)
+                .collect();
+            kept.push((background, levels.brightness[lightest]));
+            return Self::from_sorted_levels(kept);
+        }
+
+        let mut symbols: Vec<char> = self.symbols[..lightest]
+            .iter()
+            .copied()
+            .filter(|c| *c != background)
+            .collect();
+        symbols.push(background);
+
+        Self::from_symbols(symbols)
+    }
+
+    /// The brightness ramp of the map, which gives back the same map when passed to
+    /// [SymbolMap::from_ramp] unless the map has [SymbolMap::levels].
+    pub fn ramp(&self) -> String {
+        self.symbols.iter().collect()
+    }
+
+    /// The measured brightness of each symbol of the [SymbolMap::ramp], or [None] when the
+    /// symbols are spread evenly across the brightness range.
+    ///
+    /// Zipped with the ramp, the levels give back the same map when passed to
+    /// [SymbolMap::from_levels].
+    pub fn levels(&self) -> Option<&[u8]> {
+        self.levels
+            .as_ref()
+            .map(|levels| levels.brightness.as_slice())
+    }
+
+    /// Map a [u8] into a [char] from the symbol map.
+    pub fn symbol_for_brightness(&self, brightness: u8) -> char {
+        self.symbols[self.symbol_index(brightness)]
+    }
+
+    /// Find the index of the symbol a [u8] is mapped to.
+    fn symbol_index(&self, brightness: u8) -> usize {
+        match &self.levels {
+            Some(levels) => levels.nearest[brightness as usize],
+            // dividing by the brightness span of a single symbol gives us one result per
+            // symbol across the u8 range
+            None => (brightness as f32 / self.bright_div) as usize,
+        }
+    }
+
+    /// The brightness of the symbol at an index of the map.
+    fn brightness_at(&self, idx: usize) -> u8 {
+        match &self.levels {
+            Some(levels) => levels.brightness[idx],
+            None => (idx as f32 * self.bright_div) as u8,
+        }
+    }
+
+    /// The size of the brightness range that maps to a single symbol, on average for a map
+    /// with [SymbolMap::levels].
+    pub fn brightness_step(&self) -> f32 {
+        self.bright_div
+    }
+
+    /// The most bytes any symbol of the map takes up when encoded as UTF-8.
+    pub fn max_symbol_bytes(&self) -> usize {
+        self.symbols.iter().map(|c| c.len_utf8()).max().unwrap_or(1)
+    }
+
+    /// Map a [u8] into the brightness of the symbol it will be rendered as.
+    ///
+    /// This is the brightness that [SymbolMap::brightness_for_symbol] gives back for the
+    /// symbol chosen by [SymbolMap::symbol_for_brightness].
+    pub fn quantize(&self, brightness: u8) -> u8 {
+        self.brightness_at(self.symbol_index(brightness))
+    }
+
+    /// Map a [char] in the symbol map into a [u8].
+    ///
+    /// ASCII symbols are read from the lookup table, and only symbols outside of ASCII are
+    /// searched for. This function returns [ConvertError::UnknownASCIISymbol] if [char] does
+    /// not exist in the symbol map.
+    pub fn brightness_for_symbol(&self, symbol: char) -> Result<u8, ConvertError> {
+        if symbol.is_ascii() {
+            return self.ascii_brightness[symbol as usize]
+                .ok_or(ConvertError::UnknownASCIISymbol(symbol, None));
+        }
+
+        match self.symbols.iter().position(|c| *c == symbol) {
+            Some(idx) => Ok(self.brightness_at(idx)),
+            None => Err(ConvertError::UnknownASCIISymbol(symbol, None)),
+        }
+    }
+}
 
-    match b {
-        Some(brightness) => Ok(brightness as u8),
-        None => Err(ConvertError::UnknownASCIISymbol(symbol)),
+impl Default for SymbolMap {
+    /// The default construction of a [SymbolMap] uses the 70 [SYMBOLS].
+    fn default() -> Self {
+        Self::from_symbols(SYMBOLS.to_vec())
     }
 }
 
 // Test that all symbols can properly generate a brightness.
 #[test]
 fn test_all_symbols_have_brightness() {
+    let symbol_map = SymbolMap::default();
     for s in SYMBOLS {
-        assert!(brightness_for_symbol(s).is_ok());
+        assert!(symbol_map.brightness_for_symbol(s).is_ok());
     }
 }
 
 // Test that there are invalid symbols.
 #[test]
 fn test_brightness_for_symbol_bad() {
-    assert!(brightness_for_symbol('P').is_err());
+    assert!(SymbolMap::default().brightness_for_symbol('P').is_err());
 }
 
 // Test that every possible [u8] can generate a symbol, and that there are only
@@ -66,11 +534,266 @@ fn test_brightness_for_symbol_bad() {
 fn test_symbol_for_brightness() {
     use std::collections::BTreeSet;
 
+    let symbol_map = SymbolMap::default();
     let mut b = BTreeSet::new();
 
     for i in 0..=255 {
-        b.insert(symbol_for_brightness(i));
+        b.insert(symbol_map.symbol_for_brightness(i));
     }
 
     assert_eq!(b.len(), 70);
 }
+
+// Test that a custom ramp maps the full brightness range onto its symbols.
+#[test]
+fn test_from_ramp() {
+    let symbol_map = SymbolMap::from_ramp("@%#*+=-:. ").unwrap();
+
+    assert_eq!(symbol_map.symbol_for_brightness(0), '@');
+    assert_eq!(symbol_map.symbol_for_brightness(255), ' ');
+    assert_eq!(symbol_map.brightness_for_symbol('@'), Ok(0));
+    assert_eq!(symbol_map.brightness_for_symbol(' '), Ok(229));
+    assert_eq!(
+        symbol_map.brightness_for_symbol('$'),
+        Err(ConvertError::UnknownASCIISymbol('$', None))
+    );
+
+    let mut b = std::collections::BTreeSet::new();
+    for i in 0..=255 {
+        b.insert(symbol_map.symbol_for_brightness(i));
+    }
+    assert_eq!(b.len(), 10);
+}
+
+// Test that quantizing a brightness matches the round trip through a symbol.
+#[test]
+fn test_quantize() {
+    let symbol_map = SymbolMap::default();
+    for i in 0..=255 {
+        let symbol = symbol_map.symbol_for_brightness(i);
+        assert_eq!(
+            Ok(symbol_map.quantize(i)),
+            symbol_map.brightness_for_symbol(symbol)
+        );
+    }
+}
+
+// Test that the byte length of the widest symbol is found.
+#[test]
+fn test_max_symbol_bytes() {
+    assert_eq!(SymbolMap::default().max_symbol_bytes(), 1);
+    assert_eq!(SymbolMap::from_ramp("█▓▒░ ").unwrap().max_symbol_bytes(), 3);
+}
+
+// Test that the lightest symbol can be replaced with a background symbol.
+#[test]
+fn test_with_background() {
+    let symbol_map = SymbolMap::from_ramp("@%#*+=-:. ").unwrap();
+
+    let custom = symbol_map.with_background('_');
+    assert_eq!(custom, SymbolMap::from_ramp("@%#*+=-:._").unwrap());
+    assert_eq!(custom.symbol_for_brightness(255), '_');
+
+    // a symbol already in the ramp is moved to the lightest spot
+    let dot = symbol_map.with_background('.');
+    assert_eq!(dot, SymbolMap::from_ramp("@%#*+=-:.").unwrap());
+    assert_eq!(dot.symbol_for_brightness(255), '.');
+
+    // the lightest symbol is left as is
+    assert_eq!(symbol_map.with_background(' '), symbol_map);
+}
+
+// Test that a single symbol ramp is allowed.
+#[test]
+fn test_from_ramp_single_symbol() {
+    let symbol_map = SymbolMap::from_ramp("#").unwrap();
+
+    assert_eq!(symbol_map.symbol_for_brightness(0), '#');
+    assert_eq!(symbol_map.symbol_for_brightness(255), '#');
+}
+
+// Test that invalid ramps are rejected.
+#[test]
+fn test_from_ramp_errors() {
+    assert_eq!(SymbolMap::from_ramp(""), Err(ConvertError::EmptySymbolRamp));
+    assert_eq!(
+        SymbolMap::from_ramp("@#@"),
+        Err(ConvertError::DuplicateSymbol('@'))
+    );
+}
+
+// Test that the default map is the same as a ramp built from [SYMBOLS].
+#[test]
+fn test_default_matches_symbols_ramp() {
+    let ramp: String = SYMBOLS.iter().collect();
+    assert_eq!(SymbolMap::from_ramp(&ramp), Ok(SymbolMap::default()));
+}
+
+// Test that the ramp of a map builds the same map.
+#[test]
+fn test_ramp() {
+    let symbol_map = SymbolMap::from_ramp("@%#*+=-:. ").unwrap();
+
+    assert_eq!(symbol_map.ramp(), "@%#*+=-:. ");
+    assert_eq!(SymbolMap::from_ramp(&symbol_map.ramp()), Ok(symbol_map));
+}
+
+// Test that the lookup table gives the same brightness as searching the symbols.
+#[test]
+fn test_brightness_lookup_matches_search() {
+    for symbol_map in [
+        SymbolMap::default(),
+        SymbolMap::from_ramp("█▓▒░ .").unwrap(),
+        SymbolMap::default().with_background('_'),
+        SymbolMap::printable(),
+        SymbolMap::from_levels(&[('█', 0), ('▒', 128), (' ', 255)]).unwrap(),
+    ] {
+        for c in (0..=255_u8).map(char::from).chain("█▓▒░é".chars()) {
+            let searched = symbol_map
+                .symbols
+                .iter()
+                .position(|s| *s == c)
+                .map(|idx| symbol_map.brightness_at(idx))
+                .ok_or(ConvertError::UnknownASCIISymbol(c, None));
+
+            assert_eq!(symbol_map.brightness_for_symbol(c), searched);
+        }
+    }
+}
+
+// Test that the printable map reads every printable ASCII symbol and maps each pixel to the
+// symbol that is nearest in brightness.
+#[test]
+fn test_printable() {
+    let symbol_map = SymbolMap::printable();
+
+    for c in (0x20..0x7f_u8).map(char::from) {
+        assert!(symbol_map.brightness_for_symbol(c).is_ok());
+    }
+    assert!(symbol_map.brightness_for_symbol('\t').is_err());
+    assert_eq!(symbol_map.ramp().len(), 95);
+
+    assert_eq!(symbol_map.symbol_for_brightness(0), 'M');
+    assert_eq!(symbol_map.symbol_for_brightness(255), ' ');
+    assert_eq!(symbol_map.brightness_for_symbol('='), Ok(141));
+    assert_eq!(symbol_map.symbol_for_brightness(142), '=');
+
+    for i in 0..=255 {
+        let symbol = symbol_map.symbol_for_brightness(i);
+        assert_eq!(
+            Ok(symbol_map.quantize(i)),
+            symbol_map.brightness_for_symbol(symbol)
+        );
+    }
+}
+
+// Test that measured levels are sorted, checked, and give back the same map.
+#[test]
+fn test_from_levels() {
+    let symbol_map = SymbolMap::from_levels(&[(' ', 255), ('@', 0), ('=', 141)]).unwrap();
+
+    assert_eq!(symbol_map.ramp(), "@= ");
+    assert_eq!(symbol_map.levels(), Some(&[0, 141, 255][..]));
+    assert_eq!(symbol_map.symbol_for_brightness(60), '@');
+    assert_eq!(symbol_map.symbol_for_brightness(80), '=');
+    assert_eq!(SymbolMap::default().levels(), None);
+
+    let levels: Vec<(char, u8)> = SymbolMap::printable()
+        .ramp()
+        .chars()
+        .zip(SymbolMap::printable().levels().unwrap().iter().copied())
+        .collect();
+    assert_eq!(SymbolMap::from_levels(&levels), Ok(SymbolMap::printable()));
+
+    assert_eq!(
+        SymbolMap::from_levels(&[]),
+        Err(ConvertError::EmptySymbolRamp)
+    );
+    assert_eq!(
+        SymbolMap::from_levels(&[('@', 0), ('@', 255)]),
+        Err(ConvertError::DuplicateSymbol('@'))
+    );
+}
+
+// Test that the background of a measured map takes the brightness of the lightest symbol.
+#[test]
+fn test_with_background_levels() {
+    let symbol_map = SymbolMap::from_levels(&[('@', 0), ('.', 230), (' ', 255)]).unwrap();
+
+    let dot = symbol_map.with_background('.');
+    assert_eq!(
+        dot,
+        SymbolMap::from_levels(&[('@', 0), ('.', 255)]).unwrap()
+    );
+    assert_eq!(dot.symbol_for_brightness(255), '.');
+}
+
+// Test that each symbol set quantizes brightness its own way.
+#[test]
+fn test_symbol_sets() {
+    assert_eq!(SymbolMap::from(SymbolSet::Standard70), SymbolMap::default());
+
+    let minimal = SymbolMap::from(SymbolSet::Minimal10);
+    assert_eq!(minimal.ramp(), "@%#*+=-:. ");
+    assert_eq!(minimal.levels(), None);
+    assert_eq!(minimal.symbol_for_brightness(0), '@');
+    assert_eq!(minimal.symbol_for_brightness(128), '=');
+    assert_eq!(minimal.symbol_for_brightness(255), ' ');
+
+    let blocks = SymbolMap::from(SymbolSet::Blocks);
+    assert_eq!(blocks.ramp(), "\u{2588}\u{2593}\u{2592}\u{2591} ");
+    assert_eq!(blocks.symbol_for_brightness(100), '\u{2592}');
+    assert_eq!(blocks.brightness_for_symbol('\u{2591}'), Ok(191));
+    assert_eq!(blocks.max_symbol_bytes(), 3);
+
+    let binary = SymbolMap::from(SymbolSet::Binary);
+    assert_eq!(binary.symbol_for_brightness(127), '#');
+    assert_eq!(binary.symbol_for_brightness(128), ' ');
+    assert_eq!(binary.quantize(200), 255);
+
+    for set in SymbolSet::ALL {
+        assert_eq!(SymbolSet::from_name(set.name()), Some(set));
+        assert_eq!(
+            serde_json::to_string(&set).unwrap(),
+            format!("\"{}\"", set.name())
+        );
+    }
+    assert_eq!(SymbolSet::from_name("printable"), None);
+}
+
+// Test that a calibrated map is sorted by the ink of the glyphs of its symbols.
+#[test]
+fn test_calibrated() {
+    let symbol_map = SymbolMap::calibrated(" .:@").unwrap();
+
+    assert_eq!(symbol_map.ramp(), "@:. ");
+    let levels = symbol_map.levels().unwrap();
+    assert_eq!((levels[0], levels[3]), (0, 255));
+    assert!(levels[1] < levels[2]);
+    // a period has much less ink than an at sign, so it is much closer to a space than an even
+    // ramp would put it
+    assert!(levels[2] > 200);
+
+    let calibrated = SymbolMap::calibrated(&SymbolMap::printable().ramp()).unwrap();
+    for c in SymbolMap::printable().ramp().chars() {
+        let measured = SymbolMap::printable().brightness_for_symbol(c).unwrap();
+        let brightness = calibrated.brightness_for_symbol(c).unwrap();
+        assert!(
+            measured.abs_diff(brightness) <= 2,
+            "{:?} is {} but was measured as {}",
+            c,
+            brightness,
+            measured
+        );
+    }
+
+    assert_eq!(SymbolMap::calibrated("@").unwrap().levels(), Some(&[0][..]));
+    assert_eq!(
+        SymbolMap::calibrated("@@"),
+        Err(ConvertError::DuplicateSymbol('@'))
+    );
+    assert_eq!(
+        SymbolMap::calibrated("@\u{e000}"),
+        Err(ConvertError::UnknownASCIISymbol('\u{e000}', None))
+    );
+}
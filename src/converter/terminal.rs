@@ -0,0 +1,206 @@
+//! Detection of what the terminal the text is shown in can display.
+//!
+//! Text meant for a terminal looks best when it uses everything the terminal can show: colored
+//! half blocks need 24-bit color, block shades need Unicode, and the height of the text depends
+//! on the shape of the character cells. [TerminalCapabilities] reads these from the environment
+//! variables terminals and their users set, and [TerminalCapabilities::recommended_options] turns
+//! them into [ConvertOptions] for converting an image for that terminal.
+//!
+//! Robert Peterson and Kelsey Werner 2023
+
+use crate::converter::{
+    options::{ConvertOptions, RenderCharset, DEFAULT_CELL_ASPECT},
+    symbol_map::SymbolSet,
+};
+use std::env;
+
+/// The environment variable that sets the width of a character cell of the terminal divided by
+/// its height, for fonts that are not about twice as tall as they are wide.
+pub const CELL_ASPECT_VARIABLE: &str = "ASCII_ART_CELL_ASPECT";
+
+/// Represent the number of colors a terminal can display.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ColorDepth {
+    /// [ColorDepth::Monochrome] is used when the terminal shows no colors, or the user asked for
+    /// none with `NO_COLOR`.
+    Monochrome,
+    /// [ColorDepth::Ansi16] is used for terminals that show the 16 basic ANSI colors.
+    Ansi16,
+    /// [ColorDepth::Ansi256] is used for terminals that show the 256 colors of the xterm palette.
+    Ansi256,
+    /// [ColorDepth::TrueColor] is used for terminals that show 24-bit colors.
+    TrueColor,
+}
+
+/// [TerminalCapabilities] is a struct that holds what a terminal can display.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TerminalCapabilities {
+    /// The number of colors the terminal can display.
+    pub color_depth: ColorDepth,
+    /// Whether the terminal displays Unicode characters, such as block shades and half blocks.
+    pub unicode: bool,
+    /// The width of a character cell of the terminal divided by its height.
+    pub cell_aspect: f32,
+}
+
+impl TerminalCapabilities {
+    /// Detect the [TerminalCapabilities] of the terminal the process runs in from its
+    /// environment variables.
+    pub fn detect() -> TerminalCapabilities {
+        TerminalCapabilities::from_env(|name| env::var(name).ok())
+    }
+
+    /// Detect the [TerminalCapabilities] of a terminal, where `variable` finds the value of one of
+    /// its environment variables.
+    ///
+    /// The color depth follows `NO_COLOR`, `COLORTERM` and `TERM`, Unicode is supported when the
+    /// locale of `LC_ALL`, `LC_CTYPE` or `LANG` uses UTF-8, and the cell aspect ratio is read from
+    /// [CELL_ASPECT_VARIABLE]. A cell aspect ratio that is not a positive number is ignored, and
+    /// the [DEFAULT_CELL_ASPECT] is used instead.
+    pub fn from_env<F: Fn(&str) -> Option<String>>(variable: F) -> TerminalCapabilities {
+        let set = |name: &str| variable(name).filter(|value| !value.is_empty());
+
+        let term = set("TERM").unwrap_or_default();
+        let color_depth = if set("NO_COLOR").is_some() {
+            ColorDepth::Monochrome
+        } else if set("COLORTERM")
+            .is_some_and(|colorterm| colorterm == "truecolor" || colorterm == "24bit")
+        {
+            ColorDepth::TrueColor
+        } else if term.contains("256color") {
+            ColorDepth::Ansi256
+        } else if term.is_empty() || term == "dumb" {
+            ColorDepth::Monochrome
+        } else {
+            ColorDepth::Ansi16
+        };
+
+        // the first locale variable that is set decides the character encoding
+        let unicode = ["LC_ALL", "LC_CTYPE", "LANG"]
+            .into_iter()
+            .find_map(set)
+            .is_some_and(|locale| {
+                let locale = locale.to_lowercase();
+                locale.contains("utf-8") || locale.contains("utf8")
+            });
+
+        let cell_aspect = set(CELL_ASPECT_VARIABLE)
+            .and_then(|aspect| aspect.trim().parse::<f32>().ok())
+            .filter(|aspect| aspect.is_finite() && *aspect > 0.0)
+            .unwrap_or(DEFAULT_CELL_ASPECT);
+
+        TerminalCapabilities {
+            color_depth,
+            unicode,
+            cell_aspect,
+        }
+    }
+
+    /// Recommend the [ConvertOptions] to convert an image into text for the terminal.
+    ///
+    /// Terminals with 24-bit color and Unicode get colored half blocks, other terminals with
+    /// Unicode get the block shades of [SymbolSet::Blocks], and the rest get the standard ASCII
+    /// symbols. The cell aspect ratio of the terminal is kept in the options.
+    pub fn recommended_options(&self) -> ConvertOptions {
+        let (charset, symbol_set) = match (self.color_depth, self.unicode) {
+            (ColorDepth::TrueColor, true) => (RenderCharset::HalfBlock, SymbolSet::default()),
+            (_, true) => (RenderCharset::Ascii, SymbolSet::Blocks),
+            (_, false) => (RenderCharset::Ascii, SymbolSet::default()),
+        };
+
+        ConvertOptions {
+            charset,
+            symbol_map: symbol_set.into(),
+            cell_aspect: (self.cell_aspect != DEFAULT_CELL_ASPECT).then_some(self.cell_aspect),
+            ..Default::default()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    // Detect the capabilities of a terminal with the given environment variables.
+    fn detect_with(variables: &[(&str, &str)]) -> TerminalCapabilities {
+        let variables: HashMap<&str, &str> = variables.iter().copied().collect();
+        TerminalCapabilities::from_env(|name| variables.get(name).map(|value| value.to_string()))
+    }
+
+    // Test that the color depth, Unicode support and cell aspect ratio are read from the environment.
+    #[test]
+    fn test_from_env() {
+        assert_eq!(
+            detect_with(&[]),
+            TerminalCapabilities {
+                color_depth: ColorDepth::Monochrome,
+                unicode: false,
+                cell_aspect: DEFAULT_CELL_ASPECT,
+            }
+        );
+        assert_eq!(
+            detect_with(&[
+                ("TERM", "xterm-256color"),
+                ("COLORTERM", "truecolor"),
+                ("LANG", "en_US.UTF-8"),
+                (CELL_ASPECT_VARIABLE, "0.6"),
+            ]),
+            TerminalCapabilities {
+                color_depth: ColorDepth::TrueColor,
+                unicode: true,
+                cell_aspect: 0.6,
+            }
+        );
+
+        let depth = |variables: &[(&str, &str)]| detect_with(variables).color_depth;
+        assert_eq!(depth(&[("TERM", "xterm-256color")]), ColorDepth::Ansi256);
+        assert_eq!(depth(&[("TERM", "xterm")]), ColorDepth::Ansi16);
+        assert_eq!(depth(&[("TERM", "dumb")]), ColorDepth::Monochrome);
+        assert_eq!(
+            depth(&[("TERM", "xterm-256color"), ("NO_COLOR", "1")]),
+            ColorDepth::Monochrome
+        );
+
+        // the first locale variable that is set wins
+        assert!(!detect_with(&[("LC_ALL", "C"), ("LANG", "en_US.UTF-8")]).unicode);
+        assert!(detect_with(&[("LC_ALL", ""), ("LC_CTYPE", "de_DE.utf8")]).unicode);
+
+        for aspect in ["0", "-1", "tall", "inf"] {
+            assert_eq!(
+                detect_with(&[(CELL_ASPECT_VARIABLE, aspect)]).cell_aspect,
+                DEFAULT_CELL_ASPECT
+            );
+        }
+    }
+
+    // Test that the recommended options use what the terminal can display.
+    #[test]
+    fn test_recommended_options() {
+        let capabilities = TerminalCapabilities {
+            color_depth: ColorDepth::TrueColor,
+            unicode: true,
+            cell_aspect: DEFAULT_CELL_ASPECT,
+        };
+        let options = capabilities.recommended_options();
+        assert_eq!(options.charset, RenderCharset::HalfBlock);
+        assert_eq!(options.cell_aspect, None);
+
+        let options = TerminalCapabilities {
+            color_depth: ColorDepth::Ansi256,
+            cell_aspect: 0.6,
+            ..capabilities
+        }
+        .recommended_options();
+        assert_eq!(options.charset, RenderCharset::Ascii);
+        assert_eq!(options.symbol_map, SymbolSet::Blocks.into());
+        assert_eq!(options.cell_aspect, Some(0.6));
+
+        let options = TerminalCapabilities {
+            unicode: false,
+            ..capabilities
+        }
+        .recommended_options();
+        assert_eq!(options, ConvertOptions::default());
+    }
+}
@@ -0,0 +1,234 @@
+//! Deterministic output and golden file checks.
+//!
+//! The bytes of a PNG depend on the compression level and row filters the encoder picks, and the
+//! defaults of the [image] crate change from version to version, so comparing PNGs byte for byte
+//! breaks whenever the crate is updated. [encode_canonical_png] writes a PNG with fixed encoder
+//! settings instead, which [Ascii::with_canonical_output] uses for the PNGs it draws, and
+//! [canonicalize_png] rewrites any PNG the same way.
+//!
+//! Golden files are checked by their decoded pixels rather than their bytes. [check_golden]
+//! compares a PNG with the golden image it should match using [compare], and
+//! [assert_golden] panics with the [ImageComparison] when it doesn't. Setting the
+//! [UPDATE_GOLDEN_VAR] environment variable writes the canonical PNG over the golden file instead,
+//! so changed output can be reviewed in the diff:
+//!
+//! ```text
+//! UPDATE_GOLDEN=1 cargo test
+//! ```
+//!
+//! [Ascii::with_canonical_output]: crate::converter::ascii::Ascii::with_canonical_output
+//!
+//! Robert Peterson and Kelsey Werner 2023
+
+use crate::converter::{
+    compare::{compare, decode, ImageComparison, Tolerance},
+    ConvertError, ErrorSource,
+};
+use image::{
+    codecs::png::{CompressionType, FilterType, PngEncoder},
+    DynamicImage, ImageEncoder,
+};
+use std::{env, fs, io::Cursor, path::Path};
+
+/// The environment variable that makes [check_golden] write golden files instead of checking them.
+pub const UPDATE_GOLDEN_VAR: &str = "UPDATE_GOLDEN";
+
+/// The compression level of a canonical PNG.
+const CANONICAL_COMPRESSION: CompressionType = CompressionType::Best;
+
+/// The filter of every row of a canonical PNG.
+const CANONICAL_FILTER: FilterType = FilterType::NoFilter;
+
+/// Write an image as a PNG with fixed encoder settings, so the same pixels always give the same
+/// bytes.
+///
+/// Gray, gray with alpha, RGB and RGBA images with 8 bits per channel keep their color type, and
+/// any other image is written as 8-bit RGBA.
+/// [ConvertError::WriteError] is returned if the PNG can't be written.
+pub fn encode_canonical_png(img: &DynamicImage) -> Result<Cursor<Vec<u8>>, ConvertError> {
+    let converted;
+    let img = match img {
+        DynamicImage::ImageLuma8(_)
+        | DynamicImage::ImageLumaA8(_)
+        | DynamicImage::ImageRgb8(_)
+        | DynamicImage::ImageRgba8(_) => img,
+        _ => {
+            converted = DynamicImage::ImageRgba8(img.to_rgba8());
+            &converted
+        }
+    };
+
+    let mut buffer = Cursor::new(Vec::new());
+    PngEncoder::new_with_quality(&mut buffer, CANONICAL_COMPRESSION, CANONICAL_FILTER)
+        .write_image(img.as_bytes(), img.width(), img.height(), img.color())
+        .map_err(|e| ConvertError::WriteError(ErrorSource::new(e)))?;
+
+    Ok(buffer)
+}
+
+/// Decode an image and write it again with [encode_canonical_png].
+///
+/// Only the pixels are kept, so text chunks and other metadata are dropped.
+/// See [decode] for the errors that can be returned when the image can't be read.
+pub fn canonicalize_png(image: &[u8]) -> Result<Vec<u8>, ConvertError> {
+    let img = decode(&mut Cursor::new(image))?;
+    Ok(encode_canonical_png(&img)?.into_inner())
+}
+
+/// [GoldenCheck] is an enum that holds the outcome of checking a PNG against a golden file.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GoldenCheck {
+    /// The pixels of the PNG are similar to the golden image.
+    Matched(ImageComparison),
+    /// The pixels of the PNG differ from the golden image by more than the [Tolerance].
+    Differed(ImageComparison),
+    /// The golden file was written from the PNG because [UPDATE_GOLDEN_VAR] is set.
+    Updated,
+}
+
+/// Check the pixels of the PNG in `actual` against the golden image at `path`.
+///
+/// When the [UPDATE_GOLDEN_VAR] environment variable is set to anything but an empty string, the
+/// PNG is written to `path` with [canonicalize_png] and [GoldenCheck::Updated] is returned.
+/// [ConvertError::ReadError] is returned if the golden file can't be read,
+/// [ConvertError::WriteError] if it can't be written, [ConvertError::DecodeError] if either image can't be decoded, and
+/// [ConvertError::DimensionMismatch] if the images are not the same size.
+pub fn check_golden<P: AsRef<Path>>(
+    path: P,
+    actual: &[u8],
+    tolerance: &Tolerance,
+) -> Result<GoldenCheck, ConvertError> {
+    let update = env::var_os(UPDATE_GOLDEN_VAR).is_some_and(|value| !value.is_empty());
+    check_golden_with(path.as_ref(), actual, tolerance, update)
+}
+
+/// Check a PNG against a golden file like [check_golden], writing the golden file instead if
+/// `update` is set.
+fn check_golden_with(
+    path: &Path,
+    actual: &[u8],
+    tolerance: &Tolerance,
+    update: bool,
+) -> Result<GoldenCheck, ConvertError> {
+    if update {
+        fs::write(path, canonicalize_png(actual)?)
+            .map_err(|e| ConvertError::WriteError(ErrorSource::new(e)))?;
+        return Ok(GoldenCheck::Updated);
+    }
+
+    let golden = fs::read(path).map_err(|e| ConvertError::ReadError(ErrorSource::new(e)))?;
+    let expected = decode(&mut Cursor::new(golden))?;
+    let actual = decode(&mut Cursor::new(actual))?;
+
+    let comparison = compare(&expected, &actual, tolerance)?;
+    if comparison.is_similar(tolerance) {
+        Ok(GoldenCheck::Matched(comparison))
+    } else {
+        Ok(GoldenCheck::Differed(comparison))
+    }
+}
+
+/// Assert that the PNG in `actual` matches the golden image at `path` within the [Tolerance].
+///
+/// Panics with the [ImageComparison] when the pixels differ, and with the [ConvertError] when the
+/// images can't be compared. See [check_golden] for how the golden file is updated instead.
+#[track_caller]
+pub fn assert_golden<P: AsRef<Path>>(path: P, actual: &[u8], tolerance: &Tolerance) {
+    let path = path.as_ref();
+    match check_golden(path, actual, tolerance) {
+        Ok(GoldenCheck::Matched(_) | GoldenCheck::Updated) => {}
+        Ok(GoldenCheck::Differed(comparison)) => panic!(
+            "{} doesn't match the golden image: {:?}; rerun with {}=1 to update it",
+            path.display(),
+            comparison,
+            UPDATE_GOLDEN_VAR
+        ),
+        Err(e) => panic!("{} can't be compared: {}", path.display(), e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{ImageBuffer, Luma, Rgb};
+
+    // Creates an image with a gradient in each channel
+    fn gradient() -> DynamicImage {
+        DynamicImage::ImageRgb8(ImageBuffer::from_fn(16, 12, |x, y| {
+            Rgb([(x * 16) as u8, (y * 20) as u8, ((x + y) * 8) as u8])
+        }))
+    }
+
+    // Writes an image as a PNG with the default settings of the encoder
+    fn png(img: &DynamicImage) -> Vec<u8> {
+        let mut png = Cursor::new(Vec::new());
+        img.write_to(&mut png, image::ImageOutputFormat::Png)
+            .unwrap();
+        png.into_inner()
+    }
+
+    // Test that the same pixels always give the same bytes, whatever encoder wrote the PNG before.
+    #[test]
+    fn test_canonicalize_png() {
+        let img = gradient();
+        let canonical = encode_canonical_png(&img).unwrap().into_inner();
+
+        let mut fast = Cursor::new(Vec::new());
+        PngEncoder::new_with_quality(&mut fast, CompressionType::Fast, FilterType::Paeth)
+            .write_image(img.as_bytes(), img.width(), img.height(), img.color())
+            .unwrap();
+        assert_ne!(fast.get_ref(), &canonical);
+        assert_eq!(canonicalize_png(fast.get_ref()).unwrap(), canonical);
+
+        let decoded = image::load_from_memory(&canonical).unwrap();
+        assert_eq!(decoded, img);
+
+        let wide = DynamicImage::ImageLuma16(ImageBuffer::from_pixel(2, 2, Luma([u16::MAX])));
+        let decoded = image::load_from_memory(encode_canonical_png(&wide).unwrap().get_ref());
+        assert_eq!(decoded.unwrap().color(), image::ColorType::Rgba8);
+
+        assert!(matches!(
+            canonicalize_png(b"not a png"),
+            Err(ConvertError::DecodeError(_))
+        ));
+    }
+
+    // Test that a PNG is checked against a golden file by its pixels rather than its bytes.
+    #[test]
+    fn test_check_golden() {
+        let golden = tempfile::NamedTempFile::new().unwrap();
+        fs::write(golden.path(), canonicalize_png(&png(&gradient())).unwrap()).unwrap();
+
+        let check = |actual: &[u8], update| {
+            check_golden_with(golden.path(), actual, &Tolerance::exact(), update)
+        };
+
+        let matched = check(&png(&gradient()), false);
+        assert!(matches!(matched, Ok(GoldenCheck::Matched(_))));
+
+        let mut changed = gradient().to_rgb8();
+        changed.put_pixel(3, 3, Rgb([255, 0, 0]));
+        let changed = png(&DynamicImage::ImageRgb8(changed));
+        let differed = check(&changed, false);
+        assert!(matches!(differed, Ok(GoldenCheck::Differed(c)) if c.differing_pixels == 1));
+
+        let small = png(&DynamicImage::ImageRgb8(ImageBuffer::new(2, 2)));
+        assert_eq!(check(&small, false), Err(ConvertError::DimensionMismatch));
+
+        // updating the golden file writes the new pixels, which match from then on
+        assert_eq!(check(&changed, true), Ok(GoldenCheck::Updated));
+        assert_eq!(
+            fs::read(golden.path()).unwrap(),
+            canonicalize_png(&changed).unwrap()
+        );
+        assert!(matches!(
+            check(&changed, false),
+            Ok(GoldenCheck::Matched(_))
+        ));
+
+        assert!(matches!(
+            check_golden("/no/such/golden.png", &changed, &Tolerance::exact()),
+            Err(ConvertError::ReadError(_))
+        ));
+    }
+}
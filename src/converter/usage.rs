@@ -0,0 +1,61 @@
+//! Resources used by a conversion.
+//!
+//! Servers that convert images for many users need to know what each conversion costs, both to
+//! account for the users and to plan how large their machines must be. [ResourceUsage] records
+//! how many pixels a conversion decoded, an estimate of the most memory it held at once, and how
+//! long it took. It is measured by [Image::convert_to_ascii_with_usage].
+//!
+//! [Image::convert_to_ascii_with_usage]: crate::converter::image::Image::convert_to_ascii_with_usage
+//!
+//! Robert Peterson and Kelsey Werner 2023
+
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::Instant;
+
+/// [ResourceUsage] is a struct that holds the resources a single conversion used.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ResourceUsage {
+    /// The number of pixels of the decoded image, before it was cropped or scaled.
+    pub pixels_processed: u64,
+    /// An estimate of the most bytes the conversion held in memory at once.
+    ///
+    /// This counts the decoded image, the copy of it the characters are picked from and the
+    /// text, which are the buffers that grow with the size of the image.
+    pub peak_memory_bytes: u64,
+    /// The time the conversion took, from reading the image to the last line of text.
+    ///
+    /// This is measured on the clock, so it is the CPU time of the conversion when it runs on
+    /// a single thread that doesn't wait, and more than it when the thread waits. It is always
+    /// zero in WebAssembly, which has no clock to measure it with.
+    pub conversion_time: Duration,
+}
+
+/// [Stopwatch] is a struct that measures how long a conversion takes.
+///
+/// WebAssembly in a browser has no clock that [std::time::Instant] can read, so there the
+/// stopwatch always reads zero instead of stopping the conversion.
+pub(crate) struct Stopwatch {
+    /// The time the stopwatch was started at.
+    #[cfg(not(target_arch = "wasm32"))]
+    start: Instant,
+}
+
+impl Stopwatch {
+    /// Start a new [Stopwatch].
+    pub(crate) fn start() -> Stopwatch {
+        Stopwatch {
+            #[cfg(not(target_arch = "wasm32"))]
+            start: Instant::now(),
+        }
+    }
+
+    /// The time since the [Stopwatch] was started.
+    pub(crate) fn elapsed(&self) -> Duration {
+        #[cfg(not(target_arch = "wasm32"))]
+        return self.start.elapsed();
+        #[cfg(target_arch = "wasm32")]
+        return Duration::ZERO;
+    }
+}
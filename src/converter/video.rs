@@ -0,0 +1,264 @@
+//! Video clip to ASCII converter.
+//!
+//! Videos are converted from frames that were already extracted from the clip, such as the PNGs
+//! written by `ffmpeg -i clip.mp4 -vf fps=10 frames/%04d.png`, so no video decoder has to be
+//! built into the converter. Each frame is converted into ASCII just like a still image, using
+//! the same [ConvertOptions], and every [AsciiFrame] is shown for the same delay. The frames can
+//! then be played back as an animated GIF made by [frames_to_gif], or in a terminal as an
+//! asciinema cast made by [frames_to_cast]. [clip_to_cast] does all of it for a directory of
+//! extracted frames.
+//!
+//! This module is only built with the `video` feature.
+//!
+//! Robert Peterson and Kelsey Werner 2023
+
+use crate::converter::{
+    animation::AsciiFrame,
+    ascii::{load_font, Ascii},
+    asciicast::frames_to_cast,
+    image::{Image, SUPPORTED_FORMATS},
+    options::{ConvertOptions, ImageRenderMode},
+    source::ImageSource,
+    symbol_map::SymbolMap,
+    ConvertError, ErrorSource,
+};
+use image::{
+    codecs::gif::{GifEncoder, Repeat},
+    Delay, Frame, ImageFormat,
+};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+/// List the frames that were extracted from a video clip into a directory, in the order they are
+/// played.
+///
+/// The frames are the files whose extension is one of the [SUPPORTED_FORMATS], sorted by name,
+/// so frames that are numbered with leading zeros are played in order. Other files are skipped.
+/// [ConvertError::ReadError] is returned if the directory can't be read.
+pub fn frame_paths(dir: &Path) -> Result<Vec<PathBuf>, ConvertError> {
+    let entries = fs::read_dir(dir).map_err(|e| ConvertError::ReadError(ErrorSource::new(e)))?;
+
+    let mut paths = Vec::new();
+    for entry in entries {
+        let path = entry
+            .map_err(|e| ConvertError::ReadError(ErrorSource::new(e)))?
+            .path();
+        if ImageFormat::from_path(&path).is_ok_and(|format| SUPPORTED_FORMATS.contains(&format)) {
+            paths.push(path);
+        }
+    }
+
+    paths.sort();
+    Ok(paths)
+}
+
+/// Convert the frames of a video clip into [AsciiFrame]s that are each shown for `frame_delay`,
+/// which is one second divided by the frame rate the frames were extracted at.
+///
+/// Each frame is converted like [Image::convert_to_ascii] converts a still image, and the first
+/// frame that can't be converted stops the conversion with its error.
+pub fn convert_frames<I>(
+    frames: I,
+    frame_delay: Duration,
+    options: &ConvertOptions,
+) -> Result<Vec<AsciiFrame>, ConvertError>
+where
+    I: IntoIterator,
+    I::Item: ImageSource,
+{
+    frames
+        .into_iter()
+        .map(|source| {
+            let ascii =
+                Image::with_options(&mut source.open()?, options.clone()).convert_to_ascii()?;
+            Ok(AsciiFrame {
+                ascii,
+                delay: frame_delay,
+            })
+        })
+        .collect()
+}
+
+/// Draw [AsciiFrame]s into an animated GIF that loops forever, showing each frame for its delay.
+///
+/// The ASCII of each frame is drawn like [Ascii::convert_to_image] draws it with the given
+/// [SymbolMap] and [ImageRenderMode], so every symbol of the frames must be in the map. The GIF
+/// is as large as the first frame. [ConvertError::WriteError] is returned if the GIF can't be
+/// written.
+pub fn frames_to_gif(
+    frames: &[AsciiFrame],
+    symbol_map: &SymbolMap,
+    render_mode: ImageRenderMode,
+) -> Result<Vec<u8>, ConvertError> {
+    let font = load_font()?;
+
+    let mut gif = Vec::new();
+    {
+        let mut encoder = GifEncoder::new(&mut gif);
+        encoder
+            .set_repeat(Repeat::Infinite)
+            .map_err(|e| ConvertError::WriteError(ErrorSource::new(e)))?;
+
+        for frame in frames {
+            let img = Ascii::with_symbol_map(&frame.ascii, symbol_map.clone())
+                .with_font(font.clone())
+                .with_render_mode(render_mode)
+                .draw()?;
+            let delay = Delay::from_saturating_duration(frame.delay);
+            encoder
+                .encode_frame(Frame::from_parts(img.to_rgba8(), 0, 0, delay))
+                .map_err(|e| ConvertError::WriteError(ErrorSource::new(e)))?;
+        }
+    }
+
+    Ok(gif)
+}
+
+/// Convert the frames that were extracted from a video clip into a directory at `fps` frames per
+/// second into an asciinema cast that plays them back at the same rate.
+///
+/// The frames are listed by [frame_paths] and converted by [convert_frames], and the first error
+/// of either stops the conversion. A frame rate of 0 is treated as 1.
+pub fn clip_to_cast(
+    dir: &Path,
+    fps: u32,
+    options: &ConvertOptions,
+) -> Result<String, ConvertError> {
+    let frame_delay = Duration::from_secs(1) / fps.max(1);
+    let frames = convert_frames(frame_paths(dir)?, frame_delay, options)?;
+
+    Ok(frames_to_cast(&frames, Some(fps)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{codecs::gif::GifDecoder, AnimationDecoder, DynamicImage, Luma};
+    use std::io::Cursor;
+
+    // Encodes a gray PNG of the given size and shade
+    fn gray_png(width: u32, height: u32, shade: u8) -> Vec<u8> {
+        let mut png = Cursor::new(Vec::new());
+        DynamicImage::ImageLuma8(image::ImageBuffer::from_pixel(width, height, Luma([shade])))
+            .write_to(&mut png, ImageFormat::Png)
+            .unwrap();
+        png.into_inner()
+    }
+
+    // Test that every frame of a clip is converted in order with the same delay.
+    #[test]
+    fn test_convert_frames() {
+        let options = ConvertOptions {
+            width: Some(4),
+            ..Default::default()
+        };
+        let frames = convert_frames(
+            vec![gray_png(8, 8, 0), gray_png(8, 8, 255)],
+            Duration::from_millis(100),
+            &options,
+        )
+        .unwrap();
+
+        assert_eq!(frames.len(), 2);
+        assert_eq!(frames[0].ascii, "$$$$\n$$$$\n");
+        assert_eq!(frames[1].ascii, "    \n    \n");
+        assert!(frames
+            .iter()
+            .all(|frame| frame.delay == Duration::from_millis(100)));
+
+        assert!(matches!(
+            convert_frames(
+                vec![gray_png(8, 8, 0), b"not an image".to_vec()],
+                Duration::from_millis(100),
+                &options,
+            ),
+            Err(ConvertError::DecodeError(_))
+        ));
+    }
+
+    // Test that the frames of a directory are listed in order, and other files are skipped.
+    #[test]
+    fn test_frame_paths() {
+        let dir = tempfile::tempdir().unwrap();
+        for name in ["0002.png", "0001.png", "0010.jpg", "notes.txt"] {
+            fs::write(dir.path().join(name), b"").unwrap();
+        }
+
+        let names: Vec<_> = frame_paths(dir.path())
+            .unwrap()
+            .iter()
+            .map(|path| path.file_name().unwrap().to_string_lossy().into_owned())
+            .collect();
+        assert_eq!(names, ["0001.png", "0002.png", "0010.jpg"]);
+
+        assert!(matches!(
+            frame_paths(&dir.path().join("missing")),
+            Err(ConvertError::ReadError(_))
+        ));
+    }
+
+    // Test that frames are drawn into a GIF with their delays.
+    #[test]
+    fn test_frames_to_gif() {
+        let frames = [
+            AsciiFrame {
+                ascii: "$$\n$$\n".to_string(),
+                delay: Duration::from_millis(100),
+            },
+            AsciiFrame {
+                ascii: "  \n  \n".to_string(),
+                delay: Duration::from_millis(200),
+            },
+        ];
+
+        let gif = frames_to_gif(&frames, &SymbolMap::default(), ImageRenderMode::Pixels).unwrap();
+        let decoded: Vec<Frame> = GifDecoder::new(Cursor::new(gif))
+            .unwrap()
+            .into_frames()
+            .collect::<Result<_, _>>()
+            .unwrap();
+
+        assert_eq!(decoded.len(), 2);
+        assert_eq!(decoded[0].delay(), Delay::from_numer_denom_ms(100, 1));
+        assert_eq!(decoded[1].delay(), Delay::from_numer_denom_ms(200, 1));
+        // the dark symbols are drawn darker than the spaces
+        assert!(decoded[0].buffer().get_pixel(0, 0)[0] < decoded[1].buffer().get_pixel(0, 0)[0]);
+
+        let unknown = [AsciiFrame {
+            ascii: "P".to_string(),
+            delay: Duration::from_millis(100),
+        }];
+        assert!(matches!(
+            frames_to_gif(&unknown, &SymbolMap::default(), ImageRenderMode::Pixels),
+            Err(ConvertError::UnknownASCIISymbol(..))
+        ));
+    }
+
+    // Test that the frames of a directory are converted into a cast that plays them at the frame rate.
+    #[test]
+    fn test_clip_to_cast() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("0001.png"), gray_png(8, 8, 0)).unwrap();
+        fs::write(dir.path().join("0002.png"), gray_png(8, 8, 255)).unwrap();
+        let options = ConvertOptions {
+            width: Some(4),
+            ..Default::default()
+        };
+
+        let cast = clip_to_cast(dir.path(), 4, &options).unwrap();
+        let lines: Vec<&str> = cast.lines().collect();
+
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0], "{\"height\":2,\"version\":2,\"width\":4}");
+        assert!(lines[1].starts_with("[0.0,\"o\","));
+        assert!(lines[2].starts_with("[0.25,\"o\","));
+
+        assert!(matches!(
+            clip_to_cast(&dir.path().join("missing"), 4, &options),
+            Err(ConvertError::ReadError(_))
+        ));
+    }
+}
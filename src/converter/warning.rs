@@ -0,0 +1,105 @@
+//! Warnings about the changes a conversion made on its own.
+//!
+//! Some conversions quietly change their input so that it can be converted, for example by
+//! removing invisible characters from pasted ASCII or by scaling a very large image down. A
+//! [ConversionWarning] describes one of these changes, so it can be shown next to the result
+//! instead of leaving the user to wonder why the result looks different from what they expected.
+//!
+//! Robert Peterson and Kelsey Werner 2023
+
+use std::fmt;
+
+/// Represent the changes a conversion can make to its input without being asked to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConversionWarning {
+    /// [ConversionWarning::InvisibleCharactersRemoved] is used when byte-order marks or other
+    /// zero-width characters were removed from the ASCII. It holds how many were removed.
+    InvisibleCharactersRemoved(usize),
+    /// [ConversionWarning::LineBreaksReplaced] is used when Unicode line breaks in the ASCII were
+    /// replaced with `\n`. It holds how many were replaced.
+    LineBreaksReplaced(usize),
+    /// [ConversionWarning::SpacesReplaced] is used when Unicode whitespace in the ASCII, such as
+    /// non-breaking spaces, was replaced with ASCII spaces. It holds how many were replaced.
+    SpacesReplaced(usize),
+    /// [ConversionWarning::ImageDownscaled] is used when no width was requested and the image was
+    /// too large to convert at its own size. It holds the width of the image in pixels and the
+    /// width of the text it was scaled down to in characters.
+    ImageDownscaled { from: u32, to: u32 },
+    /// [ConversionWarning::WidthReducedToBudget] is used when the text was made narrower than it
+    /// would otherwise be to fit in a budget of characters. It holds the budget and the width of
+    /// the text in characters.
+    WidthReducedToBudget { budget: u32, width: u32 },
+}
+
+impl fmt::Display for ConversionWarning {
+    /// Describe the change in a way that can be shown to the user of the converter.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ConversionWarning::InvisibleCharactersRemoved(count) => write!(
+                f,
+                "{} removed",
+                count_of(*count, "invisible character", "invisible characters")
+            ),
+            ConversionWarning::LineBreaksReplaced(count) => write!(
+                f,
+                "{} replaced with newlines",
+                count_of(*count, "Unicode line break", "Unicode line breaks")
+            ),
+            ConversionWarning::SpacesReplaced(count) => write!(
+                f,
+                "{} replaced with ASCII spaces",
+                count_of(*count, "Unicode space", "Unicode spaces")
+            ),
+            ConversionWarning::ImageDownscaled { from, to } => write!(
+                f,
+                "image downscaled from {}px to {} wide",
+                from,
+                count_of(*to as usize, "character", "characters")
+            ),
+            ConversionWarning::WidthReducedToBudget { budget, width } => write!(
+                f,
+                "width reduced to {} to fit the budget of {}",
+                count_of(*width as usize, "character", "characters"),
+                count_of(*budget as usize, "character", "characters")
+            ),
+        }
+    }
+}
+
+/// Write a `count` along with the `singular` or `plural` name of what is counted.
+fn count_of(count: usize, singular: &str, plural: &str) -> String {
+    if count == 1 {
+        format!("{} {}", count, singular)
+    } else {
+        format!("{} {}", count, plural)
+    }
+}
+
+// Test that warnings are described with the right plural.
+#[test]
+fn test_display() {
+    assert_eq!(
+        ConversionWarning::InvisibleCharactersRemoved(1).to_string(),
+        "1 invisible character removed"
+    );
+    assert_eq!(
+        ConversionWarning::SpacesReplaced(5).to_string(),
+        "5 Unicode spaces replaced with ASCII spaces"
+    );
+    assert_eq!(
+        ConversionWarning::ImageDownscaled {
+            from: 8000,
+            to: 200
+        }
+        .to_string(),
+        "image downscaled from 8000px to 200 characters wide"
+    );
+    assert_eq!(
+        ConversionWarning::WidthReducedToBudget {
+            budget: 4000,
+            width: 40
+        }
+        .to_string(),
+        "width reduced to 40 characters to fit the budget of 4000 characters"
+    );
+}
@@ -1,35 +1,476 @@
 //! Convert ASCII to images and images to ASCII.
 //!
 //! This module contains the public interface for converting images to ASCII via [image_to_ascii]
-//! and for converting ASCII to images via [ascii_to_image]. Internally, the [image] crate is used
-//! to read and write images.
+//! (or [image_to_ascii_with_width] to pick the output width), for converting images to braille
+//! via [image_to_braille], and for converting ASCII to images via [ascii_to_image] (or
+//! [ascii_to_svg] for a vector image).
+//! [image_to_ascii_with_options] combines all of the image conversion settings, and
+//! [image_to_ascii_with_strategy] also picks how pixels are mapped to symbols. A custom
+//! [SymbolMap] can be used in both directions via [ConvertOptions] and [ascii_to_image_with_symbol_map],
+//! and [ascii_to_image_with_render_mode] picks how the characters are drawn.
+//! [ascii_to_image_with_histogram] matches the brightness of the PNG to a reference photo or a
+//! standard curve, which gives more photographic reconstructions of round-tripped art.
+//! [ascii_to_image_with_unknown_symbols] picks an [UnknownSymbolPolicy] for characters that are
+//! not in the [SymbolMap], so pasted art that uses them can still be converted.
+//! [ascii_to_image_with_metadata] writes a title, an author and the hash of the ASCII into text
+//! chunks of the PNG, and the `metadata` option of [ConvertOptions] starts converted text with a
+//! header comment block of the size of the image and the settings, which [strip_metadata_header]
+//! removes again.
+//! [image_to_ascii_image] does both directions in one call to make a photo look like ASCII art.
+//! [ascii_to_image_data_uri] returns the PNG as a data URI to put inline in a page.
+//! [image_to_ascii_html] wraps the ASCII in HTML, optionally colored like the photo, to put in a page.
+//! The size and format of an image can be read without converting it via [inspect], and the
+//! size of the resulting text can be predicted via [estimate_output]. [conversion_warnings] lists the
+//! [ConversionWarning]s about how an image will be scaled, and [normalize_ascii_with_warnings] those
+//! about how pasted ASCII was cleaned up, so that these changes can be shown to the user. The brightness of an image
+//! can be examined via [brightness_histogram]. Crop, rotate, contrast and dither stages can be
+//! composed in order with a [Pipeline] in the [ConvertOptions]. Images can be read from any
+//! [ImageSource], such as an open buffer, bytes in memory or a file path, and
+//! [image_to_ascii_writer] writes the text to any [Write] as it is converted, and
+//! [image_to_ascii_with_usage] measures the [ResourceUsage] of a conversion. The settings of a
+//! conversion can be saved as a JSON [Manifest] and run again via [image_to_ascii_with_manifest].
+//! Generated images can be checked against expected images via [compare_images], which
+//! compares their pixels instead of their encoded bytes, and the `converter::testing` module
+//! checks them against golden files and writes PNGs with fixed encoder settings. Servers that run many conversions can
+//! share a single [Converter], which keeps its charsets and font loaded between conversions.
+//! Many images can be converted at the same time via [convert_batch], and a few images can be
+//! stitched into a single piece of ASCII art via [images_to_ascii_collage]. Short text is written
+//! in large letters made of symbols, like a `figlet` banner, via [text_to_ascii_banner].
+//! The frames of an animated GIF are converted one at a time, with their delays, by [AsciiFrames].
+//! With the `video` feature, the frames extracted from a video clip are converted by the
+//! `converter::video` module, which plays them back as an animated GIF or an asciinema cast.
+//! With the `qr` feature, which is on by default, the `converter::qr` module draws a QR code of a
+//! link with block characters, and rasterizes a drawn QR code back into a PNG that can be scanned.
+//! Frames of ASCII played at a steady frame rate are written as an asciinema cast via
+//! [ascii_frames_to_asciicast], so they can be played back in a terminal.
+//! An image decoded once with [prepare_image] can be converted again and again with new options,
+//! which only reruns the scaling and the picking of symbols.
+//! The options that suit the terminal the text is shown in are recommended by [TerminalCapabilities].
+//! ASCII can be hidden in the pixels of a photo via [hide_ascii_in_image] and read back via
+//! [reveal_ascii_in_image], which keeps it even when the metadata of the photo is stripped.
+//! The library also builds for WebAssembly, and with the `wasm` feature the `wasm` module exports
+//! the conversions to JavaScript, so a page can preview them without a server round trip.
+//! Internally, the [image] crate is used to read and write images, through an [ImageCodec] that
+//! the `minimal-png` feature or a custom backend can replace.
+//!
+//! [ImageCodec]: crate::converter::codec::ImageCodec
+//! [Pipeline]: crate::converter::pipeline::Pipeline
+//! [Converter]: crate::converter::service::Converter
+//! [AsciiFrames]: crate::converter::animation::AsciiFrames
+//! [TerminalCapabilities]: crate::converter::terminal::TerminalCapabilities
+//! [ConversionWarning]: crate::converter::warning::ConversionWarning
+//! [normalize_ascii_with_warnings]: crate::converter::normalize::normalize_ascii_with_warnings
+//! [strip_metadata_header]: crate::converter::metadata::strip_metadata_header
 //!
 //! Robert Peterson and Kelsey Werner 2023
 
 pub mod converter;
+#[cfg(feature = "wasm")]
+pub mod wasm;
 
 use crate::converter::{
+    animation::AsciiFrame,
     ascii::Ascii,
-    image::{AsciiImageBuffer, Image},
+    asciicast::frames_to_cast,
+    banner::text_to_banner,
+    batch::map_in_order,
+    codec::default_codec,
+    collage::Collage,
+    compare::{compare, decode, ImageComparison, Tolerance},
+    histogram::BrightnessHistogram,
+    image::{Image, ImageInfo, PreparedImage, SizeEstimate},
+    manifest::Manifest,
+    metadata::PngMetadata,
+    options::{ConvertOptions, ImageRenderMode, RenderCharset, UnknownSymbolPolicy},
+    source::ImageSource,
+    steganography::{hide, reveal},
+    strategy::SymbolStrategy,
+    symbol_map::SymbolMap,
+    usage::ResourceUsage,
+    warning::ConversionWarning,
     ConvertError,
 };
-use std::io::Cursor;
+use base64::{engine::general_purpose::STANDARD, Engine};
+use std::{
+    io::{Cursor, Write},
+    time::Duration,
+};
 
-/// Public interface to convert a given file path into an ASCII [String]
-pub fn image_to_ascii<T: AsciiImageBuffer>(file: &mut T) -> Result<String, ConvertError> {
-    Image::new(file).convert_to_ascii()
+/// Public interface to convert a given image source into an ASCII [String]
+pub fn image_to_ascii<S: ImageSource>(source: S) -> Result<String, ConvertError> {
+    Image::new(&mut source.open()?).convert_to_ascii()
 }
 
-/// Public interface to convert a given ASCII string into a PNG.
+/// Public interface to convert a given image source into an ASCII [String] that is `width` characters wide.
+pub fn image_to_ascii_with_width<S: ImageSource>(
+    source: S,
+    width: u32,
+) -> Result<String, ConvertError> {
+    Image::with_width(&mut source.open()?, width).convert_to_ascii()
+}
+
+/// Public interface to read the [ImageInfo] (size and format) of a given image source.
+///
+/// Only the header of the image is read, so this can be used to check an image before
+/// doing the full conversion.
+pub fn inspect<S: ImageSource>(source: S) -> Result<ImageInfo, ConvertError> {
+    Image::new(&mut source.open()?).inspect()
+}
+
+/// Public interface to decode a given image source once into a [PreparedImage].
+///
+/// The [PreparedImage] can then be converted with different [ConvertOptions] without reading
+/// and decoding the image again, such as while the options are tweaked in a live preview.
+pub fn prepare_image<S: ImageSource>(source: S) -> Result<PreparedImage, ConvertError> {
+    Image::new(&mut source.open()?).prepare()
+}
+
+/// Public interface to predict the [SizeEstimate] of the text a given image source converts into
+/// with the given [ConvertOptions].
 ///
+/// Only the header of the image is read, so this can be used to warn about enormous output
+/// before doing the full conversion.
+pub fn estimate_output<S: ImageSource>(
+    source: S,
+    options: ConvertOptions,
+) -> Result<SizeEstimate, ConvertError> {
+    Image::with_options(&mut source.open()?, options).estimate()
+}
+
+/// Public interface to list the [ConversionWarning]s about how a given image source is scaled when it
+/// is converted with the given [ConvertOptions].
+///
+/// Only the header of the image is read, so the warnings can be shown next to the result of a
+/// conversion without converting the image twice.
+pub fn conversion_warnings<S: ImageSource>(
+    source: S,
+    options: ConvertOptions,
+) -> Result<Vec<ConversionWarning>, ConvertError> {
+    Image::with_options(&mut source.open()?, options).warnings()
+}
+
+/// Public interface to compute the [BrightnessHistogram] of a given image source as it is seen
+/// when converting with the given [ConvertOptions].
+///
+/// The histogram can be used to explain why an image converted poorly, and
+/// [BrightnessHistogram::suggested_contrast] picks a [ContrastMode] that may fix it.
+///
+/// [ContrastMode]: crate::converter::options::ContrastMode
+pub fn brightness_histogram<S: ImageSource>(
+    source: S,
+    options: ConvertOptions,
+) -> Result<BrightnessHistogram, ConvertError> {
+    Image::with_options(&mut source.open()?, options).histogram()
+}
+
+/// Public interface to convert a given image source into a [String] of braille characters.
+///
+/// Each braille character packs a 2x4 block of pixels, so the result has a much higher
+/// effective resolution than [image_to_ascii].
+pub fn image_to_braille<S: ImageSource>(source: S) -> Result<String, ConvertError> {
+    let options = ConvertOptions {
+        charset: RenderCharset::Braille,
+        ..Default::default()
+    };
+    Image::with_options(&mut source.open()?, options).convert_to_ascii()
+}
+
+/// Public interface to convert a given image source into a [String] using the given [ConvertOptions].
+pub fn image_to_ascii_with_options<S: ImageSource>(
+    source: S,
+    options: ConvertOptions,
+) -> Result<String, ConvertError> {
+    Image::with_options(&mut source.open()?, options).convert_to_ascii()
+}
+
+/// Public interface to convert a given image source into a [String] using the settings recorded
+/// in a JSON [Manifest].
+///
+/// A manifest can be made from the [ConvertOptions] of an earlier conversion, so that the same
+/// conversion can be run again or shared. [ConvertError::InvalidManifest] is returned if the
+/// manifest can't be read.
+pub fn image_to_ascii_with_manifest<S: ImageSource>(
+    source: S,
+    manifest: &str,
+) -> Result<String, ConvertError> {
+    let options = Manifest::from_json(manifest)?.to_options()?;
+    image_to_ascii_with_options(source, options)
+}
+
+/// Public interface to convert a given image source into an ASCII [String] using the given
+/// [ConvertOptions], along with the [ResourceUsage] of the conversion.
+///
+/// The text is the same as [image_to_ascii_with_options]. The usage tells how many pixels were
+/// decoded, about how much memory the conversion needed and how long it took, which servers can
+/// use to account for their users.
+pub fn image_to_ascii_with_usage<S: ImageSource>(
+    source: S,
+    options: ConvertOptions,
+) -> Result<(String, ResourceUsage), ConvertError> {
+    Image::with_options(&mut source.open()?, options).convert_to_ascii_with_usage(|_, _| {})
+}
+
+/// Public interface to convert a given image source using the given [ConvertOptions], writing
+/// the text to `writer` line by line instead of returning a [String].
+///
+/// Large PNG images are decoded and scaled one row at a time, so this is the way to convert
+/// multi-megapixel images without holding the whole decoded image or the whole text in memory.
+/// [ConvertError::WriteError] is returned if the writer fails.
+pub fn image_to_ascii_writer<S: ImageSource, W: Write>(
+    source: S,
+    options: ConvertOptions,
+    writer: &mut W,
+) -> Result<(), ConvertError> {
+    Image::with_options(&mut source.open()?, options).convert_to_ascii_writer(writer)
+}
+
+/// Public interface to convert a given image source into an ASCII [String] using the given
+/// [ConvertOptions], with the symbols picked by a [SymbolStrategy].
+///
+/// Strategies such as [EdgeStrategy] and [QuadrantBlocks] come with the library, and any
+/// closure that takes a [Cell] and returns a [char] can be used as well.
+///
+/// [EdgeStrategy]: crate::converter::strategy::EdgeStrategy
+/// [QuadrantBlocks]: crate::converter::strategy::QuadrantBlocks
+/// [Cell]: crate::converter::strategy::Cell
+pub fn image_to_ascii_with_strategy<I: ImageSource, S: SymbolStrategy + ?Sized>(
+    source: I,
+    options: ConvertOptions,
+    strategy: &S,
+) -> Result<String, ConvertError> {
+    Image::with_options(&mut source.open()?, options).convert_to_ascii_with_strategy(strategy)
+}
+
+/// Public interface to convert a given image source into a PNG that looks like its ASCII art.
+///
+/// This gives the same image as converting the file with [image_to_ascii_with_options] and
+/// the ASCII back with [ascii_to_image_with_render_mode] in [ImageRenderMode::Pixels], but the
+/// image is only decoded and encoded once. When `colorize` is true, the PNG keeps the colors of the original image.
 /// PNG data is written to a [Cursor].
+pub fn image_to_ascii_image<S: ImageSource>(
+    source: S,
+    options: ConvertOptions,
+    colorize: bool,
+) -> Result<Cursor<Vec<u8>>, ConvertError> {
+    Image::with_options(&mut source.open()?, options).convert_to_ascii_image(colorize)
+}
+
+/// Public interface to convert a given image source into ASCII art wrapped in an HTML `<pre>`
+/// element.
+///
+/// The symbols are the same as [image_to_ascii_with_options] gives, escaped so they can be put
+/// straight into a page. When `colorize` is true, each run of symbols is wrapped in a `<span>`
+/// with the color of the original image under it.
+pub fn image_to_ascii_html<S: ImageSource>(
+    source: S,
+    options: ConvertOptions,
+    colorize: bool,
+) -> Result<String, ConvertError> {
+    Image::with_options(&mut source.open()?, options).convert_to_ascii_html(colorize)
+}
+
+/// Public interface to convert a given ASCII string into a PNG.
+///
+/// The characters are drawn with a monospace font. PNG data is written to a [Cursor].
 pub fn ascii_to_image(ascii: &str) -> Result<Cursor<Vec<u8>>, ConvertError> {
     Ascii::new(ascii).convert_to_image()
 }
 
+/// Public interface to convert a given ASCII string into a PNG in a `data:image/png;base64,...` URI, which can be put
+/// straight into the `src` of an `<img>` without writing the PNG to a file.
+pub fn ascii_to_image_data_uri(ascii: &str) -> Result<String, ConvertError> {
+    let png = ascii_to_image(ascii)?.into_inner();
+    Ok(format!("data:image/png;base64,{}", STANDARD.encode(png)))
+}
+
+/// Public interface to convert a given ASCII string into an SVG.
+///
+/// Unlike the PNG from [ascii_to_image], the SVG is made of vector shapes, so it stays
+/// crisp at any zoom.
+pub fn ascii_to_svg(ascii: &str) -> Result<String, ConvertError> {
+    Ascii::new(ascii).convert_to_svg()
+}
+
+/// Public interface to convert a given ASCII string into a PNG using a custom [SymbolMap].
+///
+/// PNG data is written to a [Cursor].
+pub fn ascii_to_image_with_symbol_map(
+    ascii: &str,
+    symbol_map: SymbolMap,
+) -> Result<Cursor<Vec<u8>>, ConvertError> {
+    Ascii::with_symbol_map(ascii, symbol_map).convert_to_image()
+}
+
+/// Public interface to convert a given ASCII string into a PNG using a custom [SymbolMap]
+/// and [ImageRenderMode].
+///
+/// PNG data is written to a [Cursor].
+pub fn ascii_to_image_with_render_mode(
+    ascii: &str,
+    symbol_map: SymbolMap,
+    render_mode: ImageRenderMode,
+) -> Result<Cursor<Vec<u8>>, ConvertError> {
+    Ascii::with_symbol_map(ascii, symbol_map)
+        .with_render_mode(render_mode)
+        .convert_to_image()
+}
+
+/// Public interface to convert a given ASCII string into a PNG using a custom [SymbolMap]
+/// and [ImageRenderMode], with its brightness matched to a [BrightnessHistogram].
+///
+/// The target can be the histogram of a reference photo from [brightness_histogram] or the
+/// standard curve of [BrightnessHistogram::photographic]. See [Ascii::with_histogram_match]
+/// for how the brightness is matched. PNG data is written to a [Cursor].
+pub fn ascii_to_image_with_histogram(
+    ascii: &str,
+    symbol_map: SymbolMap,
+    render_mode: ImageRenderMode,
+    target: BrightnessHistogram,
+) -> Result<Cursor<Vec<u8>>, ConvertError> {
+    Ascii::with_symbol_map(ascii, symbol_map)
+        .with_render_mode(render_mode)
+        .with_histogram_match(target)
+        .convert_to_image()
+}
+
+/// Public interface to convert a given ASCII string into a PNG using a custom [SymbolMap]
+/// and [ImageRenderMode], drawing the characters that are not in the map as the
+/// [UnknownSymbolPolicy] says.
+///
+/// PNG data is written to a [Cursor].
+pub fn ascii_to_image_with_unknown_symbols(
+    ascii: &str,
+    symbol_map: SymbolMap,
+    render_mode: ImageRenderMode,
+    policy: UnknownSymbolPolicy,
+) -> Result<Cursor<Vec<u8>>, ConvertError> {
+    Ascii::with_symbol_map(ascii, symbol_map)
+        .with_render_mode(render_mode)
+        .with_unknown_symbol_policy(policy)
+        .convert_to_image()
+}
+
+/// Public interface to convert a given ASCII string into a PNG with [PngMetadata] written into
+/// its text chunks.
+///
+/// The SHA-256 hash of the ASCII is written along with the title and author, so the PNG can be
+/// matched with its source. PNG data is written to a [Cursor].
+pub fn ascii_to_image_with_metadata(
+    ascii: &str,
+    metadata: PngMetadata,
+) -> Result<Cursor<Vec<u8>>, ConvertError> {
+    Ascii::new(ascii).with_metadata(metadata).convert_to_image()
+}
+
+/// Public interface to convert many given image sources into ASCII [String]s using the same
+/// [ConvertOptions].
+///
+/// The images are converted at the same time on as many threads as there are CPUs. The results
+/// are returned in the order of the sources, and an image that fails to convert doesn't stop the
+/// others from being converted.
+pub fn convert_batch<I>(sources: I, options: &ConvertOptions) -> Vec<Result<String, ConvertError>>
+where
+    I: IntoIterator,
+    I::Item: ImageSource + Send,
+{
+    map_in_order(sources.into_iter().collect(), |source| {
+        image_to_ascii_with_options(source, options.clone())
+    })
+}
+
+/// Public interface to convert the given image sources into ASCII with the same [ConvertOptions] and stitch the
+/// ASCII of every image into a single piece with a [Collage].
+///
+/// The first image that fails to convert stops the collage with its error, and [ConvertError::EmptyInput] is
+/// returned if there are no sources.
+pub fn images_to_ascii_collage<I>(
+    sources: I,
+    collage: &Collage,
+    options: ConvertOptions,
+) -> Result<String, ConvertError>
+where
+    I: IntoIterator,
+    I::Item: ImageSource,
+{
+    let blocks = sources
+        .into_iter()
+        .map(|source| image_to_ascii_with_options(source, options.clone()))
+        .collect::<Result<Vec<String>, ConvertError>>()?;
+    collage.stitch(&blocks)
+}
+
+/// Public interface to write the given text as a banner of large letters made of ASCII symbols, with each line of
+/// the text `font_size` lines of ASCII tall.
+///
+/// [ConvertError::InvalidFontSize] is returned if the font size is zero, and [ConvertError::EmptyInput] if the
+/// text only has whitespace.
+pub fn text_to_ascii_banner(text: &str, font_size: u32) -> Result<String, ConvertError> {
+    text_to_banner(text, font_size, &ConvertOptions::default())
+}
+
+/// Public interface to compare the pixels of two given image sources with a [Tolerance].
+///
+/// The images can be in any format and color type, so a PNG compares the same after it is
+/// encoded again by a different encoder. [ImageComparison::is_similar] checks the result
+/// against the [Tolerance].
+pub fn compare_images<A: ImageSource, B: ImageSource>(
+    expected: A,
+    actual: B,
+    tolerance: &Tolerance,
+) -> Result<ImageComparison, ConvertError> {
+    let expected = decode(&mut expected.open()?)?;
+    let actual = decode(&mut actual.open()?)?;
+    compare(&expected, &actual, tolerance)
+}
+
+/// Public interface to hide a given ASCII string in the pixels of a given carrier image source.
+///
+/// Unlike the text chunks of [add_text_chunks], the ASCII is still there after the metadata of
+/// the image is stripped, as long as the image is kept as a PNG. See [steganography] for how it
+/// is hidden. PNG data is written to a [Cursor].
+///
+/// [add_text_chunks]: crate::converter::png_text::add_text_chunks
+/// [steganography]: crate::converter::steganography
+pub fn hide_ascii_in_image<S: ImageSource>(
+    carrier: S,
+    ascii: &str,
+) -> Result<Cursor<Vec<u8>>, ConvertError> {
+    let carrier = decode(&mut carrier.open()?)?;
+    default_codec().encode_png(&image::DynamicImage::ImageRgba8(hide(&carrier, ascii)?))
+}
+
+/// Public interface to read the ASCII string that [hide_ascii_in_image] hid in a given image source.
+pub fn reveal_ascii_in_image<S: ImageSource>(source: S) -> Result<String, ConvertError> {
+    reveal(&decode(&mut source.open()?)?)
+}
+
+/// Public interface to write frames of ASCII as an asciinema version 2 cast that plays them at
+/// `fps` frames per second.
+///
+/// The cast can be played back in a terminal with `asciinema play` or embedded with the
+/// asciinema player. A frame rate of 0 is treated as 1. Frames with their own delays, such as
+/// the [AsciiFrame]s of an animated GIF, are written with [frames_to_cast] without a frame rate
+/// instead.
+pub fn ascii_frames_to_asciicast<S: AsRef<str>>(frames: &[S], fps: u32) -> String {
+    let frames: Vec<AsciiFrame> = frames
+        .iter()
+        .map(|ascii| AsciiFrame {
+            ascii: ascii.as_ref().to_string(),
+            delay: Duration::ZERO,
+        })
+        .collect();
+
+    frames_to_cast(&frames, Some(fps))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::converter::{
+        collage::CollageLayout,
+        pipeline::{Pipeline, Rotation},
+        SymbolPosition,
+    };
     use std::{fs, fs::File, io::BufReader};
 
     // Test that ASCII converts to the proper PNG.
@@ -41,7 +482,11 @@ mod tests {
         let ascii_file =
             fs::read_to_string(ascii_path).expect("Should have been able to read ASCII file.");
 
-        let image = ascii_to_image(&ascii_file);
+        let image = ascii_to_image_with_render_mode(
+            &ascii_file,
+            SymbolMap::default(),
+            ImageRenderMode::Pixels,
+        );
 
         assert!(image.is_ok());
 
@@ -51,9 +496,526 @@ mod tests {
             env!("CARGO_MANIFEST_DIR"),
             "/test_assets/converted_images/castle.png"
         );
-        let image_file = fs::read(image_path).expect("Should have been able to read image file.");
+        let comparison = compare_images(
+            std::path::Path::new(image_path),
+            image.unwrap().into_inner(),
+            &Tolerance::exact(),
+        )
+        .unwrap();
+
+        assert!(comparison.is_similar(&Tolerance::exact()));
+    }
+
+    // Test that images are compared by their pixels instead of their encoded bytes.
+    #[test]
+    fn test_compare_images() {
+        // The idea to use "CARGO_MANIFEST_DIR" comes from StackOverflow:
+        // https://stackoverflow.com/questions/30003921/how-can-i-locate-resources-for-testing-with-cargo
+        let image_path = concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/test_assets/images/freakazoid-small.png"
+        );
+        let image_file = fs::read(image_path).unwrap();
+
+        // the same pixels saved in a different format are identical
+        let mut bmp = Cursor::new(Vec::new());
+        image::load_from_memory(&image_file)
+            .unwrap()
+            .write_to(&mut bmp, image::ImageFormat::Bmp)
+            .unwrap();
+        let comparison =
+            compare_images(image_file.as_slice(), bmp.into_inner(), &Tolerance::exact()).unwrap();
+        assert!(comparison.is_similar(&Tolerance::exact()));
+
+        let castle_path = concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/test_assets/converted_images/castle.png"
+        );
+        assert_eq!(
+            compare_images(
+                image_file.as_slice(),
+                std::path::Path::new(castle_path),
+                &Tolerance::default()
+            ),
+            Err(ConvertError::DimensionMismatch)
+        );
+    }
+
+    // Test that ASCII hidden in a photo survives having its metadata stripped.
+    #[test]
+    fn test_hide_ascii_in_image() {
+        // The idea to use "CARGO_MANIFEST_DIR" comes from StackOverflow:
+        // https://stackoverflow.com/questions/30003921/how-can-i-locate-resources-for-testing-with-cargo
+        let image_path = concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/test_assets/images/freakazoid-small.png"
+        );
+        let ascii = image_to_ascii_with_width(std::path::Path::new(image_path), 40).unwrap();
+
+        let png = hide_ascii_in_image(std::path::Path::new(image_path), &ascii).unwrap();
+        // decoding and encoding the image again drops any metadata but keeps the pixels
+        let mut stripped = Cursor::new(Vec::new());
+        image::load_from_memory(png.get_ref())
+            .unwrap()
+            .write_to(&mut stripped, image::ImageFormat::Png)
+            .unwrap();
+
+        assert_eq!(reveal_ascii_in_image(stripped.into_inner()), Ok(ascii));
+        assert_eq!(
+            reveal_ascii_in_image(std::path::Path::new(image_path)),
+            Err(ConvertError::NoHiddenText)
+        );
+    }
+
+    // Test that the resources of a conversion are measured along with the same text.
+    #[test]
+    fn test_image_to_ascii_with_usage() {
+        // The idea to use "CARGO_MANIFEST_DIR" comes from StackOverflow:
+        // https://stackoverflow.com/questions/30003921/how-can-i-locate-resources-for-testing-with-cargo
+        let image_path = std::path::Path::new(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/test_assets/images/freakazoid-small.png"
+        ));
+        let (width, height) = image::image_dimensions(image_path).unwrap();
+        let options = ConvertOptions {
+            width: Some(40),
+            ..Default::default()
+        };
+
+        let (ascii, usage) = image_to_ascii_with_usage(image_path, options.clone()).unwrap();
+
+        assert_eq!(
+            Ok(ascii.clone()),
+            image_to_ascii_with_options(image_path, options)
+        );
+        assert_eq!(usage.pixels_processed, u64::from(width * height));
+        assert!(usage.peak_memory_bytes > usage.pixels_processed + ascii.len() as u64);
+        assert!(usage.conversion_time > std::time::Duration::ZERO);
+    }
+
+    // Test that the edges of an image can be drawn with a strategy.
+    #[test]
+    fn test_image_to_ascii_with_strategy() {
+        // The idea to use "CARGO_MANIFEST_DIR" comes from StackOverflow:
+        // https://stackoverflow.com/questions/30003921/how-can-i-locate-resources-for-testing-with-cargo
+        let img_path = concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/test_assets/images/freakazoid-large.png"
+        );
+        let img_file = File::open(img_path).unwrap();
+        let mut img_reader = BufReader::new(img_file);
+        let options = ConvertOptions {
+            width: Some(80),
+            ..Default::default()
+        };
+        let strategy = converter::strategy::EdgeStrategy::default();
+        let ascii = image_to_ascii_with_strategy(&mut img_reader, options, &strategy).unwrap();
+
+        assert!(ascii.lines().all(|l| l.len() == 80));
+        assert!(ascii.contains(['|', '-', '/', '\\']));
+    }
 
-        assert_eq!(image.unwrap().into_inner(), image_file);
+    // Test that an image converts into the same ASCII from a path and from bytes in memory.
+    #[test]
+    fn test_image_to_ascii_from_sources() {
+        // The idea to use "CARGO_MANIFEST_DIR" comes from StackOverflow:
+        // https://stackoverflow.com/questions/30003921/how-can-i-locate-resources-for-testing-with-cargo
+        let img_path = concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/test_assets/images/freakazoid-small.png"
+        );
+        let ascii_path = concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/test_assets/ascii/freakazoid-small.txt"
+        );
+        let ascii_file =
+            fs::read_to_string(ascii_path).expect("Should have been able to read ASCII file.");
+        let img_bytes = fs::read(img_path).unwrap();
+
+        assert_eq!(
+            image_to_ascii(std::path::Path::new(img_path)),
+            Ok(ascii_file.clone())
+        );
+        assert_eq!(image_to_ascii(img_bytes.as_slice()), Ok(ascii_file.clone()));
+        assert_eq!(image_to_ascii(img_bytes), Ok(ascii_file));
+    }
+
+    // Test that a manifest of the options converts an image the same way as the options.
+    #[test]
+    fn test_image_to_ascii_with_manifest() {
+        // The idea to use "CARGO_MANIFEST_DIR" comes from StackOverflow:
+        // https://stackoverflow.com/questions/30003921/how-can-i-locate-resources-for-testing-with-cargo
+        let img_path = concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/test_assets/images/freakazoid-small.png"
+        );
+        let options = ConvertOptions {
+            width: Some(40),
+            symbol_map: SymbolMap::from_ramp("@%#*+=-:. ").unwrap(),
+            rulers: true,
+            ..Default::default()
+        };
+        let manifest = Manifest::from(&options).to_json();
+
+        assert_eq!(
+            image_to_ascii_with_manifest(std::path::Path::new(img_path), &manifest),
+            image_to_ascii_with_options(std::path::Path::new(img_path), options)
+        );
+        assert_eq!(
+            image_to_ascii_with_manifest(std::path::Path::new(img_path), "{"),
+            Err(ConvertError::InvalidManifest)
+        );
+    }
+
+    // Test that writing the ASCII of an image gives the same text as converting it.
+    #[test]
+    fn test_image_to_ascii_writer() {
+        // The idea to use "CARGO_MANIFEST_DIR" comes from StackOverflow:
+        // https://stackoverflow.com/questions/30003921/how-can-i-locate-resources-for-testing-with-cargo
+        let img_path = concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/test_assets/images/freakazoid-large.png"
+        );
+        let options = ConvertOptions {
+            width: Some(120),
+            ..Default::default()
+        };
+
+        let mut written = Vec::new();
+        image_to_ascii_writer(
+            std::path::Path::new(img_path),
+            options.clone(),
+            &mut written,
+        )
+        .unwrap();
+
+        assert_eq!(
+            String::from_utf8(written),
+            Ok(image_to_ascii_with_options(std::path::Path::new(img_path), options).unwrap())
+        );
+    }
+
+    // Test that ASCII converts to a PNG with the characters drawn by default.
+    #[test]
+    fn test_ascii_to_image_glyphs() {
+        let glyphs = ascii_to_image("$$$\n. .").unwrap().into_inner();
+        let pixels = ascii_to_image_with_render_mode(
+            "$$$\n. .",
+            SymbolMap::default(),
+            ImageRenderMode::Pixels,
+        )
+        .unwrap()
+        .into_inner();
+
+        assert_ne!(glyphs, pixels);
+        assert!(image::load_from_memory(&glyphs).is_ok());
+    }
+
+    // Test that ASCII converts to a data URI of the same PNG.
+    #[test]
+    fn test_ascii_to_image_data_uri() {
+        let uri = ascii_to_image_data_uri("$$$\n. .").unwrap();
+        let png = uri.strip_prefix("data:image/png;base64,").unwrap();
+
+        assert_eq!(
+            STANDARD.decode(png).unwrap(),
+            ascii_to_image("$$$\n. .").unwrap().into_inner()
+        );
+        assert!(matches!(
+            ascii_to_image_data_uri("\n\n"),
+            Err(ConvertError::EmptyInput)
+        ));
+    }
+
+    // Test that an image converts to braille characters.
+    #[test]
+    fn test_image_to_braille() {
+        // The idea to use "CARGO_MANIFEST_DIR" comes from StackOverflow:
+        // https://stackoverflow.com/questions/30003921/how-can-i-locate-resources-for-testing-with-cargo
+        let img_path = concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/test_assets/images/freakazoid-small.png"
+        );
+        let img_file = File::open(img_path).unwrap();
+        let mut img_reader = BufReader::new(img_file);
+        let braille = image_to_braille(&mut img_reader).unwrap();
+
+        assert!(!braille.is_empty());
+        assert!(braille
+            .chars()
+            .all(|c| c == '\n' || ('\u{2800}'..='\u{28FF}').contains(&c)));
+    }
+
+    // Test that a custom ramp can be used to convert an image to ASCII and back again.
+    #[test]
+    fn test_custom_ramp_round_trip() {
+        // The idea to use "CARGO_MANIFEST_DIR" comes from StackOverflow:
+        // https://stackoverflow.com/questions/30003921/how-can-i-locate-resources-for-testing-with-cargo
+        let img_path = concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/test_assets/images/freakazoid-small.png"
+        );
+        let img_file = File::open(img_path).unwrap();
+        let mut img_reader = BufReader::new(img_file);
+        let symbol_map = SymbolMap::from_ramp("@%#*+=-:. ").unwrap();
+        let options = ConvertOptions {
+            symbol_map: symbol_map.clone(),
+            ..Default::default()
+        };
+        let ascii = image_to_ascii_with_options(&mut img_reader, options).unwrap();
+
+        assert!(ascii_to_image_with_symbol_map(&ascii, symbol_map).is_ok());
+        assert!(ascii_to_image(&ascii).is_err());
+    }
+
+    // Test that an image can be inspected.
+    #[test]
+    fn test_inspect() {
+        // The idea to use "CARGO_MANIFEST_DIR" comes from StackOverflow:
+        // https://stackoverflow.com/questions/30003921/how-can-i-locate-resources-for-testing-with-cargo
+        let img_path = concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/test_assets/images/freakazoid-large.png"
+        );
+        let img_file = File::open(img_path).unwrap();
+        let mut img_reader = BufReader::new(img_file);
+        let info = inspect(&mut img_reader).unwrap();
+
+        assert_eq!(info.format, image::ImageFormat::Png);
+        assert!(info.dimension.width > 0 && info.dimension.height > 0);
+    }
+
+    // Test that a prepared image converts into the same text as the image with each of the options.
+    #[test]
+    fn test_prepare_image() {
+        // The idea to use "CARGO_MANIFEST_DIR" comes from StackOverflow:
+        // https://stackoverflow.com/questions/30003921/how-can-i-locate-resources-for-testing-with-cargo
+        let img_path = concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/test_assets/images/freakazoid-small.png"
+        );
+        let prepared = prepare_image(std::path::Path::new(img_path)).unwrap();
+
+        let inverted: String = SymbolMap::default().ramp().chars().rev().collect();
+        for options in [
+            ConvertOptions::default(),
+            ConvertOptions {
+                width: Some(20),
+                symbol_map: SymbolMap::from_ramp(&inverted).unwrap(),
+                ..Default::default()
+            },
+            ConvertOptions {
+                charset: RenderCharset::Braille,
+                rulers: true,
+                ..Default::default()
+            },
+            ConvertOptions {
+                charset: RenderCharset::HalfBlock,
+                width: Some(30),
+                ..Default::default()
+            },
+            ConvertOptions {
+                pipeline: Pipeline::new()
+                    .crop(0, 0, 8, 8)
+                    .rotate(Rotation::Clockwise90),
+                ..Default::default()
+            },
+        ] {
+            assert_eq!(
+                prepared.convert(&options),
+                image_to_ascii_with_options(std::path::Path::new(img_path), options.clone())
+            );
+        }
+
+        assert_eq!(
+            prepared.convert(&ConvertOptions {
+                width: Some(0),
+                ..Default::default()
+            }),
+            Err(ConvertError::InvalidWidth)
+        );
+    }
+
+    // Test that the estimated output size matches the converted ASCII.
+    #[test]
+    fn test_estimate_output() {
+        // The idea to use "CARGO_MANIFEST_DIR" comes from StackOverflow:
+        // https://stackoverflow.com/questions/30003921/how-can-i-locate-resources-for-testing-with-cargo
+        let img_path = concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/test_assets/images/freakazoid-large.png"
+        );
+        let img_file = File::open(img_path).unwrap();
+        let mut img_reader = BufReader::new(img_file);
+        let options = ConvertOptions {
+            width: Some(80),
+            ..Default::default()
+        };
+        let estimate = estimate_output(&mut img_reader, options.clone()).unwrap();
+        let ascii = image_to_ascii_with_options(&mut img_reader, options).unwrap();
+
+        assert_eq!(estimate.columns, 80);
+        assert_eq!(estimate.rows as usize, ascii.lines().count());
+        assert_eq!(estimate.bytes, ascii.len());
+    }
+
+    // Test that a large image converted without a width warns that it was scaled down.
+    #[test]
+    fn test_conversion_warnings() {
+        let img_path = std::path::Path::new(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/test_assets/images/freakazoid-large.png"
+        ));
+        let warnings = conversion_warnings(img_path, ConvertOptions::default()).unwrap();
+        let ascii = image_to_ascii(img_path).unwrap();
+
+        assert_eq!(
+            warnings,
+            vec![ConversionWarning::ImageDownscaled {
+                from: 580,
+                to: ascii.lines().next().unwrap().len() as u32
+            }]
+        );
+    }
+
+    // Test that the combined conversion gives the same PNG as converting in two steps.
+    #[test]
+    fn test_image_to_ascii_image() {
+        // The idea to use "CARGO_MANIFEST_DIR" comes from StackOverflow:
+        // https://stackoverflow.com/questions/30003921/how-can-i-locate-resources-for-testing-with-cargo
+        let img_path = concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/test_assets/images/freakazoid-large.png"
+        );
+        let symbol_map = SymbolMap::from_ramp("@%#*+=-:. ").unwrap();
+        let options = ConvertOptions {
+            width: Some(80),
+            symbol_map: symbol_map.clone(),
+            ..Default::default()
+        };
+
+        let img_file = File::open(img_path).unwrap();
+        let mut img_reader = BufReader::new(img_file);
+        let ascii = image_to_ascii_with_options(&mut img_reader, options.clone()).unwrap();
+        let expected =
+            ascii_to_image_with_render_mode(&ascii, symbol_map, ImageRenderMode::Pixels).unwrap();
+
+        let img_file = File::open(img_path).unwrap();
+        let mut img_reader = BufReader::new(img_file);
+        let png = image_to_ascii_image(&mut img_reader, options.clone(), false).unwrap();
+
+        assert_eq!(png, expected);
+
+        let img_file = File::open(img_path).unwrap();
+        let mut img_reader = BufReader::new(img_file);
+        assert!(image_to_ascii_image(&mut img_reader, options, true).is_ok());
+    }
+
+    // Test that the HTML holds the same ASCII as the plain conversion.
+    #[test]
+    fn test_image_to_ascii_html() {
+        // The idea to use "CARGO_MANIFEST_DIR" comes from StackOverflow:
+        // https://stackoverflow.com/questions/30003921/how-can-i-locate-resources-for-testing-with-cargo
+        let img_path = concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/test_assets/images/freakazoid-large.png"
+        );
+        let options = ConvertOptions {
+            width: Some(80),
+            ..Default::default()
+        };
+
+        let ascii =
+            image_to_ascii_with_options(std::path::Path::new(img_path), options.clone()).unwrap();
+        let html =
+            image_to_ascii_html(std::path::Path::new(img_path), options.clone(), false).unwrap();
+        let unescaped = html
+            .replace("&lt;", "<")
+            .replace("&gt;", ">")
+            .replace("&quot;", "\"")
+            .replace("&#39;", "'")
+            .replace("&amp;", "&");
+        assert_eq!(unescaped, format!("<pre>{}</pre>\n", ascii));
+
+        let colored = image_to_ascii_html(std::path::Path::new(img_path), options, true).unwrap();
+        assert!(colored.starts_with("<pre><span style=\"color:#"));
+        assert_eq!(colored.lines().count(), ascii.lines().count() + 1);
+    }
+
+    // Test that the histogram of an image can be computed.
+    #[test]
+    fn test_brightness_histogram() {
+        // The idea to use "CARGO_MANIFEST_DIR" comes from StackOverflow:
+        // https://stackoverflow.com/questions/30003921/how-can-i-locate-resources-for-testing-with-cargo
+        let img_path = concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/test_assets/images/freakazoid-large.png"
+        );
+        let img_file = File::open(img_path).unwrap();
+        let mut img_reader = BufReader::new(img_file);
+        let options = ConvertOptions {
+            width: Some(40),
+            ..Default::default()
+        };
+        let histogram = brightness_histogram(&mut img_reader, options).unwrap();
+
+        assert_eq!(histogram.total() % 40, 0);
+        assert!(histogram.mean().is_some());
+    }
+
+    // Test that ASCII converted back with the histogram of the original image gets closer to its brightness.
+    #[test]
+    fn test_ascii_to_image_with_histogram() {
+        // The idea to use "CARGO_MANIFEST_DIR" comes from StackOverflow:
+        // https://stackoverflow.com/questions/30003921/how-can-i-locate-resources-for-testing-with-cargo
+        let img_path = concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/test_assets/images/freakazoid-small.png"
+        );
+        let ascii = image_to_ascii(std::path::Path::new(img_path)).unwrap();
+        let original =
+            brightness_histogram(std::path::Path::new(img_path), ConvertOptions::default())
+                .unwrap();
+        let mean = |png: Cursor<Vec<u8>>| {
+            let img = image::load_from_memory(png.get_ref()).unwrap().into_luma8();
+            BrightnessHistogram::from_image(&img).mean().unwrap()
+        };
+
+        let plain =
+            ascii_to_image_with_render_mode(&ascii, SymbolMap::default(), ImageRenderMode::Pixels)
+                .unwrap();
+        let matched = ascii_to_image_with_histogram(
+            &ascii,
+            SymbolMap::default(),
+            ImageRenderMode::Pixels,
+            original.clone(),
+        )
+        .unwrap();
+
+        let target = original.mean().unwrap();
+        assert!((mean(matched) - target).abs() < (mean(plain) - target).abs());
+    }
+
+    // Test that ASCII converts to an SVG.
+    #[test]
+    fn test_ascii_to_svg() {
+        // The idea to use "CARGO_MANIFEST_DIR" comes from StackOverflow:
+        // https://stackoverflow.com/questions/30003921/how-can-i-locate-resources-for-testing-with-cargo
+        let ascii_path = concat!(env!("CARGO_MANIFEST_DIR"), "/test_assets/ascii/castle.txt");
+        let ascii_file =
+            fs::read_to_string(ascii_path).expect("Should have been able to read ASCII file.");
+
+        let svg = ascii_to_svg(&ascii_file).unwrap();
+
+        assert!(svg.starts_with("<svg "));
+        assert!(svg.ends_with("</svg>\n"));
+        assert_eq!(
+            ascii_to_svg("P"),
+            Err(ConvertError::UnknownASCIISymbol(
+                'P',
+                Some(SymbolPosition { x: 0, y: 0 })
+            ))
+        );
     }
 
     // Test that bugs found during manual testing to not reoccur.
@@ -63,6 +1025,43 @@ mod tests {
         assert!(ascii_to_image("@#$....").is_ok());
     }
 
+    // Test that pasted ASCII with unsupported characters converts unless the policy is to fail.
+    #[test]
+    fn test_ascii_to_image_with_unknown_symbols() {
+        let convert = |policy| {
+            ascii_to_image_with_unknown_symbols(
+                "/=\\
+|V|",
+                SymbolMap::default(),
+                ImageRenderMode::Glyphs,
+                policy,
+            )
+        };
+
+        assert!(convert(UnknownSymbolPolicy::Error).is_err());
+        assert!(convert(UnknownSymbolPolicy::SkipAsSpace).is_ok());
+        assert!(convert(UnknownSymbolPolicy::NearestMatch).is_ok());
+    }
+
+    // Test that the metadata is written into the PNG without changing its pixels.
+    #[test]
+    fn test_ascii_to_image_with_metadata() {
+        let metadata = PngMetadata {
+            title: Some("Castle".to_string()),
+            author: Some("Kelsey".to_string()),
+        };
+        let png = ascii_to_image_with_metadata("$$$\n. .\n", metadata).unwrap();
+        let plain = ascii_to_image("$$$\n. .\n").unwrap();
+
+        let bytes = png.get_ref();
+        assert!(bytes.windows(12).any(|w| w == b"Title\0Castle"));
+        assert!(bytes.windows(13).any(|w| w == b"Author\0Kelsey"));
+        assert_eq!(
+            image::load_from_memory(bytes).unwrap(),
+            image::load_from_memory(plain.get_ref()).unwrap()
+        );
+    }
+
     // Test that an image converts to the proper ASCII.
     #[test]
     fn test_image_to_ascii() {
@@ -91,4 +1090,108 @@ mod tests {
 
         assert_eq!(ascii, ascii_file);
     }
+
+    // Test that a batch of images converts in order, with failures kept in their place.
+    #[test]
+    fn test_convert_batch() {
+        // The idea to use "CARGO_MANIFEST_DIR" comes from StackOverflow:
+        // https://stackoverflow.com/questions/30003921/how-can-i-locate-resources-for-testing-with-cargo
+        let small_path = concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/test_assets/images/freakazoid-small.png"
+        );
+        let large_path = concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/test_assets/images/freakazoid-large.png"
+        );
+        let small = fs::read(small_path).unwrap();
+        let large = fs::read(large_path).unwrap();
+        let options = ConvertOptions {
+            width: Some(40),
+            ..Default::default()
+        };
+
+        let results = convert_batch(
+            [&large[..], b"not an image", &small[..], &large[..]],
+            &options,
+        );
+
+        assert_eq!(results.len(), 4);
+        assert_eq!(
+            results[0],
+            image_to_ascii_with_options(&large[..], options.clone())
+        );
+        assert!(matches!(results[1], Err(ConvertError::DecodeError(_))));
+        assert_eq!(
+            results[2],
+            image_to_ascii_with_options(&small[..], options.clone())
+        );
+        assert_eq!(results[3], results[0]);
+    }
+
+    // Test that images convert to a collage of their ASCII side by side.
+    #[test]
+    fn test_images_to_ascii_collage() {
+        // The idea to use "CARGO_MANIFEST_DIR" comes from StackOverflow:
+        // https://stackoverflow.com/questions/30003921/how-can-i-locate-resources-for-testing-with-cargo
+        let img_path = concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/test_assets/images/freakazoid-small.png"
+        );
+        let img = fs::read(img_path).unwrap();
+        let options = ConvertOptions {
+            width: Some(20),
+            ..Default::default()
+        };
+
+        let ascii = image_to_ascii_with_options(&img[..], options.clone()).unwrap();
+        let collage = Collage::new(CollageLayout::SideBySide).with_column_separator("|");
+        let stitched =
+            images_to_ascii_collage([&img[..], &img[..]], &collage, options.clone()).unwrap();
+
+        assert_eq!(stitched.lines().count(), ascii.lines().count());
+        assert!(stitched
+            .lines()
+            .zip(ascii.lines())
+            .all(|(stitched, line)| stitched == format!("{}|{}", line, line)));
+        assert!(matches!(
+            images_to_ascii_collage([&img[..], b"not an image"], &collage, options),
+            Err(ConvertError::DecodeError(_))
+        ));
+    }
+
+    // Test that text is written as a banner as tall as the font size.
+    #[test]
+    fn test_text_to_ascii_banner() {
+        let banner = text_to_ascii_banner("ASCII", 4).unwrap();
+
+        assert_eq!(banner.lines().count(), 4);
+        assert!(!banner.trim().is_empty());
+        assert_eq!(
+            text_to_ascii_banner("ASCII", 0),
+            Err(ConvertError::InvalidFontSize)
+        );
+    }
+
+    // Test that frames of ASCII are written as a cast that shows them at the frame rate.
+    #[test]
+    fn test_ascii_frames_to_asciicast() {
+        let cast = ascii_frames_to_asciicast(&["$$\n", "..\n", "$.\n"], 4);
+        let times: Vec<f64> = cast
+            .lines()
+            .skip(1)
+            .map(|line| {
+                serde_json::from_str::<serde_json::Value>(line).unwrap()[0]
+                    .as_f64()
+                    .unwrap()
+            })
+            .collect();
+
+        assert!(cast.starts_with("{\"height\":1,\"version\":2,\"width\":2}\n"));
+        assert_eq!(times, [0.0, 0.25, 0.5]);
+        assert_eq!(
+            ascii_frames_to_asciicast(&["$$\n", "..\n"], 0),
+            ascii_frames_to_asciicast(&["$$\n", "..\n"], 1)
+        );
+    }
 }
@@ -0,0 +1,80 @@
+//! WebAssembly bindings of the converter.
+//!
+//! With the `wasm` feature, the library can be built for `wasm32-unknown-unknown` and these
+//! functions are exported to JavaScript with [wasm_bindgen], so a page can preview a conversion
+//! in the browser without sending the image to a server. Browsers have no file system, so images
+//! are passed in as the bytes of the file, such as the contents of an `<input type="file">`, and
+//! PNGs are returned as bytes rather than in a [Cursor](std::io::Cursor). The settings of a
+//! conversion are passed as the JSON of a [Manifest], the same way they are shared by the website.
+//! Errors are thrown as JavaScript errors with the message of the [ConvertError].
+//!
+//! Robert Peterson and Kelsey Werner 2023
+
+use crate::converter::{
+    ascii::Ascii, image::Image, manifest::Manifest, options::ConvertOptions, ConvertError,
+};
+use std::io::Cursor;
+use wasm_bindgen::prelude::*;
+
+/// Read the [ConvertOptions] of a JSON [Manifest], or the default options when there is none.
+///
+/// [ConvertError::InvalidManifest] is returned if the manifest can't be read.
+fn manifest_options(manifest: Option<String>) -> Result<ConvertOptions, ConvertError> {
+    match manifest {
+        Some(json) => Manifest::from_json(&json)?.to_options(),
+        None => Ok(ConvertOptions::default()),
+    }
+}
+
+/// Convert the bytes of an image into ASCII with the settings of a JSON [Manifest], or the
+/// default settings when the manifest is left out.
+#[wasm_bindgen(js_name = imageToAscii)]
+pub fn image_to_ascii(image: &[u8], manifest: Option<String>) -> Result<String, JsError> {
+    let options = manifest_options(manifest)?;
+    Ok(Image::with_options(&mut Cursor::new(image), options).convert_to_ascii()?)
+}
+
+/// Convert the bytes of an image into ASCII wrapped in an HTML `<pre>` element, which can be put
+/// straight into the page. When `colorize` is true, the symbols are colored like the image.
+#[wasm_bindgen(js_name = imageToAsciiHtml)]
+pub fn image_to_ascii_html(
+    image: &[u8],
+    manifest: Option<String>,
+    colorize: bool,
+) -> Result<String, JsError> {
+    let options = manifest_options(manifest)?;
+    Ok(Image::with_options(&mut Cursor::new(image), options).convert_to_ascii_html(colorize)?)
+}
+
+/// Convert ASCII into the bytes of a PNG, drawing the characters with a monospace font.
+#[wasm_bindgen(js_name = asciiToImage)]
+pub fn ascii_to_image(ascii: &str) -> Result<Vec<u8>, JsError> {
+    Ok(Ascii::new(ascii).convert_to_image()?.into_inner())
+}
+
+/// Convert ASCII into an SVG, which stays crisp at any zoom.
+#[wasm_bindgen(js_name = asciiToSvg)]
+pub fn ascii_to_svg(ascii: &str) -> Result<String, JsError> {
+    Ok(Ascii::new(ascii).convert_to_svg()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Test that the bindings convert bytes in both directions with the settings of a manifest.
+    // Only successful conversions are tested, as errors can only be thrown in WebAssembly.
+    #[test]
+    fn test_bindings() {
+        let png = ascii_to_image("$$\n$$\n").unwrap();
+        assert!(png.starts_with(b"\x89PNG"));
+
+        let ascii = image_to_ascii(&png, Some(r#"{"width": 2}"#.to_string())).unwrap();
+        assert_eq!(ascii.lines().next().unwrap().chars().count(), 2);
+        assert!(image_to_ascii(&png, None).is_ok());
+        assert!(image_to_ascii_html(&png, None, true)
+            .unwrap()
+            .starts_with("<pre"));
+        assert!(ascii_to_svg("$$\n").unwrap().contains("<svg"));
+    }
+}
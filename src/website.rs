@@ -2,7 +2,42 @@
 //!
 //! Robert Peterson and Kelsey Werner 2023
 
+pub mod accounting;
+pub mod admin;
+pub mod animation_form_params;
+pub mod api_docs;
+pub mod api_keys;
+pub mod art_store;
 pub mod ascii_form_params;
+pub mod attribution;
+pub mod auth;
+pub mod batch_form_params;
+pub mod cleanup;
+pub mod collage_form_params;
+pub mod config;
+pub mod config_check;
+pub mod conversion_defaults;
+pub mod export;
+pub mod formatting;
+pub mod frame_stream;
+pub mod gallery;
+pub mod health;
 pub mod html_template;
 pub mod image_form_params;
+pub mod import;
 pub mod input_processors;
+pub mod jobs;
+pub mod line_stream;
+pub mod metrics_store;
+pub mod moderation;
+pub mod operator_auth;
+#[cfg(feature = "qr")]
+pub mod qr_form_params;
+pub mod rate_limit;
+pub mod result_store;
+pub mod results;
+pub mod security_headers;
+pub mod self_test;
+pub mod text_form_params;
+pub mod tls;
+pub mod user;
@@ -0,0 +1,101 @@
+//! Module to account for the resources used by conversions.
+//!
+//! Every image the website converts into ASCII art measures its [ResourceUsage], which is saved in the
+//! [super::results::ResultRecord] of the result and added to the totals kept here by [record_usage]. Operators read the
+//! totals in the metrics of the website, which tells them how much work the converter does and how large the server
+//! must be to do it.
+//!
+//! Robert Peterson and Kelsey Werner 2023
+
+use ascii_art_converter::converter::usage::ResourceUsage;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// The number of conversions whose resources were measured since the server started.
+static MEASURED_CONVERSIONS: AtomicU64 = AtomicU64::new(0);
+
+/// The number of pixels decoded by the measured conversions.
+static PIXELS_PROCESSED: AtomicU64 = AtomicU64::new(0);
+
+/// The time the measured conversions took, in microseconds.
+static CONVERSION_MICROSECONDS: AtomicU64 = AtomicU64::new(0);
+
+/// The largest estimate of the memory a single measured conversion held at once, in bytes.
+static PEAK_MEMORY_BYTES: AtomicU64 = AtomicU64::new(0);
+
+/// Function to add the [ResourceUsage] of a conversion to the totals of the website.
+pub fn record_usage(usage: &ResourceUsage) {
+    MEASURED_CONVERSIONS.fetch_add(1, Ordering::Relaxed);
+    PIXELS_PROCESSED.fetch_add(usage.pixels_processed, Ordering::Relaxed);
+    CONVERSION_MICROSECONDS.fetch_add(
+        u64::try_from(usage.conversion_time.as_micros()).unwrap_or(u64::MAX),
+        Ordering::Relaxed,
+    );
+    PEAK_MEMORY_BYTES.fetch_max(usage.peak_memory_bytes, Ordering::Relaxed);
+}
+
+/// Function to write the totals of the resources used by conversions in the plain text format that Prometheus scrapes.
+pub fn usage_metrics() -> String {
+    format!(
+        "# HELP conversions_measured_total Conversions whose resources were measured.\n\
+         # TYPE conversions_measured_total counter\n\
+         conversions_measured_total {}\n\
+         # HELP conversion_pixels_processed_total Pixels of the images decoded by conversions.\n\
+         # TYPE conversion_pixels_processed_total counter\n\
+         conversion_pixels_processed_total {}\n\
+         # HELP conversion_seconds_total Time spent converting.\n\
+         # TYPE conversion_seconds_total counter\n\
+         conversion_seconds_total {}\n\
+         # HELP conversion_peak_memory_bytes Largest estimate of the memory a single conversion held at once.\n\
+         # TYPE conversion_peak_memory_bytes gauge\n\
+         conversion_peak_memory_bytes {}\n",
+        MEASURED_CONVERSIONS.load(Ordering::Relaxed),
+        PIXELS_PROCESSED.load(Ordering::Relaxed),
+        CONVERSION_MICROSECONDS.load(Ordering::Relaxed) as f64 / 1_000_000.0,
+        PEAK_MEMORY_BYTES.load(Ordering::Relaxed),
+    )
+}
+
+// Tests
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    // Finds the value of a metric in the text of the metrics.
+    fn metric(metrics: &str, name: &str) -> f64 {
+        metrics
+            .lines()
+            .find_map(|line| line.strip_prefix(&format!("{} ", name)))
+            .and_then(|value| value.parse().ok())
+            .unwrap()
+    }
+
+    // Verifies that the usage of a conversion is added to the totals of the metrics
+    #[test]
+    fn test_record_usage() {
+        let before = usage_metrics();
+
+        record_usage(&ResourceUsage {
+            pixels_processed: 1_000,
+            peak_memory_bytes: 5_000,
+            conversion_time: Duration::from_millis(250),
+        });
+        let after = usage_metrics();
+
+        // other tests may convert at the same time, so the totals only have to grow by at least this conversion
+        assert!(
+            metric(&after, "conversions_measured_total")
+                >= metric(&before, "conversions_measured_total") + 1.0
+        );
+        assert!(
+            metric(&after, "conversion_pixels_processed_total")
+                >= metric(&before, "conversion_pixels_processed_total") + 1_000.0
+        );
+        assert!(
+            metric(&after, "conversion_seconds_total")
+                >= metric(&before, "conversion_seconds_total") + 0.249
+        );
+        assert!(metric(&after, "conversion_peak_memory_bytes") >= 5_000.0);
+    }
+}
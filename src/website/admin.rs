@@ -0,0 +1,130 @@
+//! Module for the dashboard operators open in their browser at GET /admin.
+//!
+//! The dashboard shows the totals of the conversions kept by the [super::metrics_store::MetricsStore], the failed
+//! conversions by the kind of [ascii_art_converter::converter::ConvertError], and the space the results of conversions
+//! take up in the [ResultStore], as measured by [disk_usage]. Its purge form removes the results that were stored longer
+//! ago than the number of hours in the [PurgeFormParams], like the cleanup of old results does on its own.
+//!
+//! Operators sign in to the dashboard with basic authentication, using the operator token as the password. Browsers send
+//! the credentials with forms posted from other websites too, so the purge is refused when [is_cross_site] finds that it
+//! was posted from somewhere else.
+//!
+//! Robert Peterson and Kelsey Werner 2023
+
+use super::result_store::ResultStore;
+use actix_web::{http::header, HttpRequest};
+use serde::{Deserialize, Serialize};
+use std::io;
+
+/// Struct to store the space the results of conversions take up.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+pub struct DiskUsage {
+    /// The number of stored result files.
+    pub files: u64,
+    /// The number of bytes of every stored result file together.
+    pub bytes: u64,
+}
+
+/// Struct to store the query parameters of the dashboard.
+///
+/// Actix Web populates [AdminQuery] with the query of the GET /admin page, which is redirected to after a purge.
+#[derive(Serialize, Deserialize)]
+pub struct AdminQuery {
+    /// [Option] stores the number of result files the last purge removed, or [None] if the page wasn't opened after one.
+    pub purged: Option<u64>,
+}
+
+/// Struct to store the form data of the purge form of the dashboard.
+///
+/// Actix Web populates [PurgeFormParams] with the form data of the POST /admin/purge request.
+#[derive(Serialize, Deserialize)]
+pub struct PurgeFormParams {
+    /// The age in hours past which stored results are removed, where 0 removes every result.
+    pub older_than_hours: u64,
+}
+
+/// Function to measure the space the results of conversions in a [ResultStore] take up.
+///
+/// Results that are removed while they are measured are skipped.
+pub fn disk_usage(results: &dyn ResultStore) -> io::Result<DiskUsage> {
+    let mut usage = DiskUsage::default();
+
+    for name in results.names()? {
+        if let Some(size) = results.size(&name)? {
+            usage.files += 1;
+            usage.bytes += size;
+        }
+    }
+
+    Ok(usage)
+}
+
+/// Function to check if a request was sent by a page of another website.
+///
+/// Browsers tell where a request comes from in the `Sec-Fetch-Site` header, and older browsers send the `Origin` of forms,
+/// which must then have the same host as the request. Requests with neither header, such as those made with curl, are
+/// not cross-site.
+pub fn is_cross_site(request: &HttpRequest) -> bool {
+    let headers = request.headers();
+    if let Some(site) = headers.get("sec-fetch-site") {
+        return site != "same-origin" && site != "none";
+    }
+
+    match headers
+        .get(header::ORIGIN)
+        .and_then(|origin| origin.to_str().ok())
+    {
+        Some(origin) => {
+            let origin_host = origin.split_once("://").map_or(origin, |(_, host)| host);
+            origin_host != request.connection_info().host()
+        }
+        None => false,
+    }
+}
+
+// Tests
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::website::result_store::MemoryResultStore;
+    use actix_web::test::TestRequest;
+
+    // Verifies that disk_usage() counts every stored result and adds up their sizes
+    #[test]
+    fn test_disk_usage() {
+        let results = MemoryResultStore::new();
+
+        assert_eq!(disk_usage(&results).unwrap(), DiskUsage::default());
+
+        results.write("a.png", b"png").unwrap();
+        results.write("a.txt", b"$$").unwrap();
+
+        assert_eq!(
+            disk_usage(&results).unwrap(),
+            DiskUsage { files: 2, bytes: 5 }
+        );
+    }
+
+    // Verifies that only requests that say they come from another website are cross-site
+    #[test]
+    fn test_is_cross_site() {
+        for (name, value, expected) in [
+            ("sec-fetch-site", "same-origin", false),
+            ("sec-fetch-site", "none", false),
+            ("sec-fetch-site", "cross-site", true),
+            ("sec-fetch-site", "same-site", true),
+            ("origin", "http://localhost:8080", false),
+            ("origin", "https://example.com", true),
+        ] {
+            let request = TestRequest::post()
+                .insert_header((header::HOST, "localhost:8080"))
+                .insert_header((name, value))
+                .to_http_request();
+
+            assert_eq!(is_cross_site(&request), expected, "{}: {}", name, value);
+        }
+
+        assert!(!is_cross_site(&TestRequest::post().to_http_request()));
+    }
+}
@@ -0,0 +1,163 @@
+//! Module to store and sanitize animation input.
+//!
+//! The animation input is provided by the user in a multipart form to the POST /stream-ascii-animation endpoint,
+//! such as with `curl -N -F "animation_input=@animation.gif" http://127.0.0.1:8080/stream-ascii-animation`, or to the
+//! POST /ascii-animation.cast endpoint to download the animation as an asciinema cast.
+//!
+//! Robert Peterson and Kelsey Werner 2023
+
+use super::image_form_params::parse_ascii_width;
+use actix_multipart::form::{bytes::Bytes, text::Text, MultipartForm};
+use ascii_art_converter::inspect;
+use image::ImageFormat;
+
+/// Struct to store an animation.
+///
+/// Actix Web populates [AnimationFormParams] with user-submitted form data.
+#[derive(MultipartForm)]
+pub struct AnimationFormParams {
+    /// [Option] stores an animated GIF as [Bytes] or [None] if no animation submitted.
+    pub animation_input: Option<Bytes>,
+    /// [Option] stores the selected ASCII art width as [Text] or [None] if no width submitted.
+    pub ascii_width: Option<Text<String>>,
+}
+
+/// Enum to store the possible error states that can be detected when sanitizing animation input.
+///
+/// The different enum variants are used to identify the specific cause of an error.
+#[derive(PartialEq, Debug)]
+pub enum AnimationInputError {
+    /// [AnimationInputError::EmptyInput] error is caused when the form is submitted without being populated with an animation.
+    EmptyInput,
+    /// [AnimationInputError::UnsupportedAnimationType] error is caused when the form is submitted with a file that is not a GIF.
+    UnsupportedAnimationType,
+    /// [AnimationInputError::UnsupportedWidth] error is caused when the form is submitted with an ASCII art width that is not one of the [SUPPORTED_ASCII_WIDTHS](super::image_form_params::SUPPORTED_ASCII_WIDTHS).
+    UnsupportedWidth,
+}
+
+impl AnimationFormParams {
+    /// Function to verify if animation form input is valid.
+    ///
+    /// When the input animation passes valiation, function returns `Ok(&Bytes)` where [Bytes] is the input GIF file.
+    /// Returns `Err(AnimationInputError::EmptyInput)` when an empty form is submitted.
+    /// Returns `Err(AnimationInputError::UnsupportedAnimationType)` when a file that is not a GIF, or whose contents are not a GIF, is submitted.
+    pub fn validate_animation_input(&self) -> Result<&Bytes, AnimationInputError> {
+        let animation_file = match &self.animation_input {
+            Some(animation_file) if animation_file.data.is_empty() => {
+                return Err(AnimationInputError::EmptyInput)
+            }
+            Some(animation_file) => animation_file,
+            None => return Err(AnimationInputError::EmptyInput),
+        };
+
+        let declared_gif = animation_file
+            .content_type
+            .as_ref()
+            .and_then(|mime_type| ImageFormat::from_mime_type(mime_type.essence_str()))
+            == Some(ImageFormat::Gif);
+
+        match inspect(&animation_file.data[..]) {
+            Ok(info) if declared_gif && info.format == ImageFormat::Gif => Ok(animation_file),
+            _ => Err(AnimationInputError::UnsupportedAnimationType),
+        }
+    }
+
+    /// Function to verify if the ASCII art width form input is valid, the same way as on the image to ASCII form.
+    ///
+    /// Returns `Err(AnimationInputError::UnsupportedWidth)` when the width is rejected by [parse_ascii_width].
+    pub fn validate_ascii_width(&self) -> Result<Option<u32>, AnimationInputError> {
+        parse_ascii_width(&self.ascii_width).map_err(|_| AnimationInputError::UnsupportedWidth)
+    }
+}
+
+// Tests
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::web;
+    use std::fs;
+
+    // Verifies that empty input accurately detected by AnimationFormParams::validate_animation_input() and error returned
+    #[test]
+    fn test_empty_input() {
+        let mut input = AnimationFormParams {
+            animation_input: None,
+            ascii_width: None,
+        };
+
+        assert_eq!(
+            input.validate_animation_input().unwrap_err(),
+            AnimationInputError::EmptyInput
+        );
+
+        input.animation_input = Some(Bytes {
+            data: web::Bytes::new(),
+            content_type: Some(mime::IMAGE_GIF),
+            file_name: Some("test_file.gif".to_string()),
+        });
+
+        assert_eq!(
+            input.validate_animation_input().unwrap_err(),
+            AnimationInputError::EmptyInput
+        );
+    }
+
+    // Verifies that a GIF is accepted by AnimationFormParams::validate_animation_input() and other files are rejected
+    #[test]
+    fn test_animation_type() {
+        // The idea to use "CARGO_MANIFEST_DIR" comes from StackOverflow:
+        // https://stackoverflow.com/questions/30003921/how-can-i-locate-resources-for-testing-with-cargo
+        let gif_path = concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/test_assets/images/moving-bar.gif"
+        );
+        let png_path = concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/test_assets/images/freakazoid-small.png"
+        );
+
+        for (path, content_type, expected) in [
+            (gif_path, mime::IMAGE_GIF, Ok(())),
+            (
+                gif_path,
+                mime::IMAGE_PNG,
+                Err(AnimationInputError::UnsupportedAnimationType),
+            ),
+            (
+                png_path,
+                mime::IMAGE_GIF,
+                Err(AnimationInputError::UnsupportedAnimationType),
+            ),
+        ] {
+            let input = AnimationFormParams {
+                animation_input: Some(Bytes {
+                    data: web::Bytes::from(fs::read(path).unwrap()),
+                    content_type: Some(content_type),
+                    file_name: Some("test_file.gif".to_string()),
+                }),
+                ascii_width: None,
+            };
+
+            assert_eq!(input.validate_animation_input().map(|_| ()), expected);
+        }
+    }
+
+    // Verifies that the ASCII art width is checked by AnimationFormParams::validate_ascii_width()
+    #[test]
+    fn test_ascii_width() {
+        for (width, expected) in [
+            (None, Ok(None)),
+            (Some(""), Ok(None)),
+            (Some("80"), Ok(Some(80))),
+            (Some("81"), Err(AnimationInputError::UnsupportedWidth)),
+        ] {
+            let input = AnimationFormParams {
+                animation_input: None,
+                ascii_width: width.map(|w| Text(w.to_string())),
+            };
+
+            assert_eq!(input.validate_ascii_width(), expected);
+        }
+    }
+}
@@ -0,0 +1,110 @@
+//! Module for the OpenAPI document of the JSON API, which programs read to learn how to call it.
+//!
+//! The document is derived with utoipa from the handlers of the `/api/v1` endpoints and of the API keys of operators,
+//! and the schemas of their requests and responses are derived from the same structs the handlers use, such as
+//! [CollageFormParams] and [ApiErrorBody], so the document can't drift from the code. It is served at
+//! GET /api/docs/openapi.json, along with Swagger UI at GET /api/docs/ to try the endpoints out in a browser.
+//!
+//! Robert Peterson and Kelsey Werner 2023
+
+use super::{
+    api_keys::{ApiErrorBody, ApiKeyInfo, ApiKeyRequest, IssuedApiKey, API_KEY_HEADER},
+    batch_form_params::BatchFormParams,
+    collage_form_params::CollageFormParams,
+};
+use utoipa::{
+    openapi::security::{ApiKey, ApiKeyValue, Http, HttpAuthScheme, SecurityScheme},
+    Modify, OpenApi,
+};
+
+/// The address Swagger UI is served under.
+pub const DOCS_PATH: &str = "/api/docs/{_:.*}";
+
+/// The address the OpenAPI document is served at.
+pub const OPENAPI_PATH: &str = "/api/docs/openapi.json";
+
+/// Struct to store the OpenAPI document of the JSON API.
+#[derive(OpenApi)]
+#[openapi(
+    info(
+        title = "ASCII Art Converter API",
+        description = "Converts images into ASCII art. The `/api/v1` endpoints need an API key when the website requires one."
+    ),
+    paths(
+        crate::batch_image_to_ascii,
+        crate::collage_to_ascii,
+        crate::issue_api_key,
+        crate::api_keys_list,
+        crate::revoke_api_key,
+    ),
+    components(schemas(
+        BatchFormParams,
+        CollageFormParams,
+        ApiKeyRequest,
+        IssuedApiKey,
+        ApiKeyInfo,
+        ApiErrorBody,
+    )),
+    modifiers(&SecuritySchemes),
+    tags(
+        (name = "conversions", description = "Converting images into ASCII art."),
+        (name = "operators", description = "Managing API keys with the operator token.")
+    )
+)]
+pub struct ApiDoc;
+
+/// Struct to add the ways the JSON API is authenticated to the OpenAPI document.
+struct SecuritySchemes;
+
+impl Modify for SecuritySchemes {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        let components = openapi.components.get_or_insert_with(Default::default);
+        components.add_security_scheme(
+            "api_key",
+            SecurityScheme::ApiKey(ApiKey::Header(ApiKeyValue::new(API_KEY_HEADER))),
+        );
+        components.add_security_scheme(
+            "operator_token",
+            SecurityScheme::Http(Http::new(HttpAuthScheme::Bearer)),
+        );
+    }
+}
+
+// Tests
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Verifies that the document has every endpoint of the JSON API, the schemas they use and the ways to authenticate
+    #[test]
+    fn test_api_doc() {
+        let document = serde_json::to_value(ApiDoc::openapi()).unwrap();
+
+        for path in [
+            "/api/v1/batch/image-to-ascii",
+            "/api/v1/collage",
+            "/api/operator/keys",
+            "/api/operator/keys/{id}",
+        ] {
+            assert!(document["paths"].get(path).is_some(), "{}", path);
+        }
+        for schema in ["CollageFormParams", "ApiErrorBody", "IssuedApiKey"] {
+            assert!(
+                document["components"]["schemas"].get(schema).is_some(),
+                "{}",
+                schema
+            );
+        }
+
+        assert_eq!(
+            document["components"]["securitySchemes"]["api_key"]["name"],
+            API_KEY_HEADER
+        );
+        assert_eq!(
+            document["components"]["schemas"]["CollageFormParams"]["properties"]["images"]["items"]
+                ["format"],
+            "binary"
+        );
+    }
+}
@@ -0,0 +1,538 @@
+//! Module for the API keys that programs send to use the JSON API under `/api/v1`.
+//!
+//! Operators issue keys with POST /api/operator/keys, which returns the key once; only its [content_hash] is stored in
+//! the `api_keys` table of the gallery database. Every key may make a number of requests a day, counted in the
+//! `api_key_usage` table by the day in UTC, which is `api_daily_quota` in the [super::config::AppConfig] unless the key
+//! was issued with its own quota.
+//!
+//! When `api_keys_required` is set, the [ApiKeyCheck] middleware wraps the `/api/v1` endpoints and only lets requests
+//! through that send a key in an `X-API-Key` header, such as with
+//! `curl -H "X-API-Key: $KEY" -F "images=@cat.png" -F "images=@dog.png" http://127.0.0.1:8080/api/v1/collage`.
+//! Other requests are answered with an [ApiKeyError] as a JSON object with an `error` code and a `message`.
+//!
+//! Robert Peterson and Kelsey Werner 2023
+
+use super::art_store::content_hash;
+use actix_web::{
+    body::{EitherBody, MessageBody},
+    dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform},
+    http::{header, StatusCode},
+    Error, HttpResponse, ResponseError,
+};
+use serde::{Deserialize, Serialize};
+use sqlx::{
+    sqlite::{SqliteConnectOptions, SqlitePool, SqlitePoolOptions},
+    Executor,
+};
+use std::{
+    fmt,
+    future::{ready, Future, Ready},
+    pin::Pin,
+    rc::Rc,
+    str::FromStr,
+    time::SystemTime,
+};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+/// The header that API keys are sent in.
+pub const API_KEY_HEADER: &str = "X-API-Key";
+
+/// The number of seconds in a day.
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+/// The SQL that creates the tables of API keys and their usage when they don't exist yet.
+const API_KEYS_SCHEMA: &str = "CREATE TABLE IF NOT EXISTS api_keys (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    key_hash TEXT NOT NULL UNIQUE,
+    name TEXT NOT NULL,
+    daily_quota INTEGER NOT NULL,
+    revoked INTEGER NOT NULL DEFAULT 0,
+    created_at TEXT NOT NULL DEFAULT (datetime('now'))
+);
+CREATE TABLE IF NOT EXISTS api_key_usage (
+    key_id INTEGER NOT NULL REFERENCES api_keys (id),
+    day TEXT NOT NULL,
+    requests INTEGER NOT NULL,
+    PRIMARY KEY (key_id, day)
+)";
+
+/// Struct to store the JSON body of a request to issue an API key.
+///
+/// Actix Web populates [ApiKeyRequest] with the JSON body of the POST /api/operator/keys request.
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct ApiKeyRequest {
+    /// The name that says who the key is for.
+    #[schema(example = "Photo app")]
+    pub name: String,
+    /// [Option] stores the number of requests the key may make a day, or [None] to use the quota of the website.
+    pub daily_quota: Option<u32>,
+}
+
+/// Struct to store an API key that was just issued, which is the only time the key itself is known.
+#[derive(Debug, Clone, PartialEq, Serialize, ToSchema)]
+pub struct IssuedApiKey {
+    /// The id of the key, which it is revoked by.
+    pub id: i64,
+    /// The name that says who the key is for.
+    pub name: String,
+    /// The key that is sent in the [API_KEY_HEADER] header.
+    pub key: String,
+    /// The number of requests the key may make a day.
+    pub daily_quota: u32,
+}
+
+/// Struct to store an issued API key as operators see it, without the key itself.
+#[derive(Debug, Clone, PartialEq, Serialize, sqlx::FromRow, ToSchema)]
+pub struct ApiKeyInfo {
+    /// The id of the key.
+    pub id: i64,
+    /// The name that says who the key is for.
+    pub name: String,
+    /// The number of requests the key may make a day.
+    pub daily_quota: i64,
+    /// The number of requests the key made today.
+    pub requests_today: i64,
+    /// [bool] that is `true` when the key was revoked and can't be used anymore.
+    pub revoked: bool,
+    /// The time the key was issued, in UTC.
+    pub created_at: String,
+}
+
+/// Struct to store the JSON object that errors of the JSON API are answered with.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct ApiErrorBody {
+    /// The code of the error that programs can check, such as "invalid_api_key".
+    #[schema(example = "quota_exceeded")]
+    pub error: String,
+    /// The message that explains the error to people.
+    pub message: String,
+}
+
+/// Enum to store whether an API key may make a request.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum KeyUse {
+    /// [KeyUse::Allowed] is used when the key is valid and the request was counted towards its quota.
+    Allowed,
+    /// [KeyUse::Invalid] is used when the key was never issued or was revoked.
+    Invalid,
+    /// [KeyUse::QuotaExceeded] is used when the key made all of the requests it may make today.
+    QuotaExceeded {
+        /// The number of requests the key may make a day.
+        daily_quota: i64,
+    },
+}
+
+/// Struct to store the API keys and their usage in a SQLite database.
+///
+/// The [ApiKeyStore] can be cloned cheaply, since every clone shares the same pool of connections.
+#[derive(Clone)]
+pub struct ApiKeyStore {
+    /// The pool of connections to the database.
+    pool: SqlitePool,
+}
+
+impl ApiKeyStore {
+    /// Function to open the API keys stored in the database at `url`, which is created if it doesn't exist yet.
+    ///
+    /// The connections are only made once the store is used, and the tables are created on each new connection if they
+    /// don't exist yet. Returns an error if the url is not a SQLite url.
+    pub fn new(url: &str) -> Result<ApiKeyStore, sqlx::Error> {
+        let options = SqliteConnectOptions::from_str(url)?.create_if_missing(true);
+        let pool = SqlitePoolOptions::new()
+            .after_connect(|connection, _| {
+                Box::pin(async move {
+                    connection.execute(API_KEYS_SCHEMA).await?;
+                    Ok(())
+                })
+            })
+            .connect_lazy_with(options);
+
+        Ok(ApiKeyStore { pool })
+    }
+
+    /// Function to issue a new API key named `name` that may make `daily_quota` requests a day.
+    ///
+    /// The key is two random UUIDs, and only its hash is stored.
+    pub async fn issue(&self, name: &str, daily_quota: u32) -> Result<IssuedApiKey, sqlx::Error> {
+        let key = format!("{}{}", Uuid::new_v4().simple(), Uuid::new_v4().simple());
+        let id = sqlx::query("INSERT INTO api_keys (key_hash, name, daily_quota) VALUES (?, ?, ?)")
+            .bind(content_hash(&key))
+            .bind(name)
+            .bind(daily_quota)
+            .execute(&self.pool)
+            .await?
+            .last_insert_rowid();
+
+        Ok(IssuedApiKey {
+            id,
+            name: name.to_string(),
+            key,
+            daily_quota,
+        })
+    }
+
+    /// Function to get every issued API key along with the number of requests it made today, the oldest first.
+    pub async fn keys(&self) -> Result<Vec<ApiKeyInfo>, sqlx::Error> {
+        sqlx::query_as(
+            "SELECT api_keys.id, name, daily_quota, COALESCE(requests, 0) AS requests_today, revoked, created_at
+            FROM api_keys
+            LEFT JOIN api_key_usage ON api_key_usage.key_id = api_keys.id AND api_key_usage.day = date('now')
+            ORDER BY api_keys.id",
+        )
+        .fetch_all(&self.pool)
+        .await
+    }
+
+    /// Function to revoke the API key with `id`, so it can't be used anymore.
+    ///
+    /// Returns `false` when there is no key with the id that wasn't revoked already.
+    pub async fn revoke(&self, id: i64) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query("UPDATE api_keys SET revoked = 1 WHERE id = ? AND revoked = 0")
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Function to count a request made with `key` towards its quota of today.
+    ///
+    /// The request is only counted when it is [KeyUse::Allowed], and the check and the count are a single statement so
+    /// requests made at the same time can't go over the quota together.
+    pub async fn use_key(&self, key: &str) -> Result<KeyUse, sqlx::Error> {
+        let found: Option<(i64, i64)> = sqlx::query_as(
+            "SELECT id, daily_quota FROM api_keys WHERE key_hash = ? AND revoked = 0",
+        )
+        .bind(content_hash(key.trim()))
+        .fetch_optional(&self.pool)
+        .await?;
+        let (id, daily_quota) = match found {
+            Some(found) => found,
+            None => return Ok(KeyUse::Invalid),
+        };
+
+        let result = sqlx::query(
+            "INSERT INTO api_key_usage (key_id, day, requests) SELECT ?, date('now'), 1 WHERE ? > 0
+            ON CONFLICT (key_id, day) DO UPDATE SET requests = requests + 1 WHERE requests < ?",
+        )
+        .bind(id)
+        .bind(daily_quota)
+        .bind(daily_quota)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(if result.rows_affected() > 0 {
+            KeyUse::Allowed
+        } else {
+            KeyUse::QuotaExceeded { daily_quota }
+        })
+    }
+}
+
+/// Function to count the seconds from `now` until the next day in UTC, when the quotas of every key start over.
+pub fn seconds_until_tomorrow(now: SystemTime) -> u64 {
+    let seconds = now
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map_or(0, |since_epoch| since_epoch.as_secs());
+    SECONDS_PER_DAY - seconds % SECONDS_PER_DAY
+}
+
+/// Enum to store the reasons a request may not use the JSON API.
+///
+/// The different enum variants are used to identify the specific cause of an error.
+#[derive(Debug, PartialEq)]
+pub enum ApiKeyError {
+    /// [ApiKeyError::Missing] error is caused when the request didn't send an API key.
+    Missing,
+    /// [ApiKeyError::Invalid] error is caused when the API key was never issued or was revoked.
+    Invalid,
+    /// [ApiKeyError::QuotaExceeded] error is caused when the API key made all of the requests it may make today.
+    QuotaExceeded {
+        /// The number of requests the key may make a day.
+        daily_quota: i64,
+        /// The number of seconds until the quota starts over.
+        retry_after: u64,
+    },
+    /// [ApiKeyError::Unavailable] error is caused when the API keys can't be read from the database.
+    Unavailable,
+}
+
+impl ApiKeyError {
+    /// Function to get the code of the error that programs can check, such as "invalid_api_key".
+    pub fn code(&self) -> &'static str {
+        match self {
+            ApiKeyError::Missing => "missing_api_key",
+            ApiKeyError::Invalid => "invalid_api_key",
+            ApiKeyError::QuotaExceeded { .. } => "quota_exceeded",
+            ApiKeyError::Unavailable => "unavailable",
+        }
+    }
+}
+
+impl fmt::Display for ApiKeyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ApiKeyError::Missing => write!(f, "It looks like you didn't send an API key! Be sure to send it in an \"{}\" header.", API_KEY_HEADER),
+            ApiKeyError::Invalid => write!(f, "It looks like that API key doesn't exist or was revoked! Ask an operator of the website for a new one."),
+            ApiKeyError::QuotaExceeded { daily_quota, retry_after } => write!(f, "You've made the {} requests your API key can make today! Please try again in {} seconds.", daily_quota, retry_after),
+            ApiKeyError::Unavailable => write!(f, "It looks like we ran into an issue with checking your API key! Try it one more time."),
+        }
+    }
+}
+
+impl ResponseError for ApiKeyError {
+    /// Returns `401 Unauthorized` for a missing or invalid key, `429 Too Many Requests` for a key that made all of its
+    /// requests today, and `503 Service Unavailable` when the keys can't be read.
+    fn status_code(&self) -> StatusCode {
+        match self {
+            ApiKeyError::Missing | ApiKeyError::Invalid => StatusCode::UNAUTHORIZED,
+            ApiKeyError::QuotaExceeded { .. } => StatusCode::TOO_MANY_REQUESTS,
+            ApiKeyError::Unavailable => StatusCode::SERVICE_UNAVAILABLE,
+        }
+    }
+
+    /// Returns the JSON object of the error, along with the `Retry-After` header when the quota was exceeded.
+    fn error_response(&self) -> HttpResponse {
+        let mut response = HttpResponse::build(self.status_code());
+        if let ApiKeyError::QuotaExceeded { retry_after, .. } = self {
+            response.insert_header((header::RETRY_AFTER, *retry_after));
+        }
+        response.json(ApiErrorBody {
+            error: self.code().to_string(),
+            message: self.to_string(),
+        })
+    }
+}
+
+/// Function to check the API key sent with a request, and count the request towards its quota.
+pub async fn check_api_key(store: &ApiKeyStore, key: Option<&str>) -> Result<(), ApiKeyError> {
+    let key = key
+        .filter(|key| !key.trim().is_empty())
+        .ok_or(ApiKeyError::Missing)?;
+
+    match store.use_key(key).await {
+        Ok(KeyUse::Allowed) => Ok(()),
+        Ok(KeyUse::Invalid) => Err(ApiKeyError::Invalid),
+        Ok(KeyUse::QuotaExceeded { daily_quota }) => Err(ApiKeyError::QuotaExceeded {
+            daily_quota,
+            retry_after: seconds_until_tomorrow(SystemTime::now()),
+        }),
+        Err(error) => {
+            log::warn!("Checking an API key failed: {}", error);
+            Err(ApiKeyError::Unavailable)
+        }
+    }
+}
+
+/// Struct to store the middleware that only lets requests with a valid API key through to the requests it wraps.
+#[derive(Clone)]
+pub struct ApiKeyCheck {
+    /// [Option] stores the [ApiKeyStore] the keys are checked with, or [None] when API keys aren't required.
+    store: Option<ApiKeyStore>,
+}
+
+impl ApiKeyCheck {
+    /// Function to create the middleware from an [ApiKeyStore], where [None] lets every request through.
+    pub fn new(store: Option<ApiKeyStore>) -> ApiKeyCheck {
+        ApiKeyCheck { store }
+    }
+}
+
+// The middleware follows the example in the actix-web middleware documentation:
+// https://actix.rs/docs/middleware/
+impl<S, B> Transform<S, ServiceRequest> for ApiKeyCheck
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: MessageBody + 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Transform = ApiKeyCheckMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(ApiKeyCheckMiddleware {
+            service: Rc::new(service),
+            store: self.store.clone(),
+        }))
+    }
+}
+
+/// Struct to store the service the [ApiKeyCheck] middleware wraps.
+pub struct ApiKeyCheckMiddleware<S> {
+    /// The wrapped service, which handles the requests that are let through.
+    service: Rc<S>,
+    /// [Option] stores the [ApiKeyStore] the keys are checked with, or [None] when API keys aren't required.
+    store: Option<ApiKeyStore>,
+}
+
+impl<S, B> Service<ServiceRequest> for ApiKeyCheckMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: MessageBody + 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>>>>;
+
+    forward_ready!(service);
+
+    fn call(&self, request: ServiceRequest) -> Self::Future {
+        let service = Rc::clone(&self.service);
+        let store = self.store.clone();
+
+        Box::pin(async move {
+            if let Some(store) = store {
+                let key = request
+                    .headers()
+                    .get(API_KEY_HEADER)
+                    .and_then(|value| value.to_str().ok());
+                if let Err(error) = check_api_key(&store, key).await {
+                    let response = error.error_response();
+                    return Ok(request.into_response(response).map_into_right_body());
+                }
+            }
+
+            service
+                .call(request)
+                .await
+                .map(ServiceResponse::map_into_left_body)
+        })
+    }
+}
+
+// Tests
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::{
+        test::{call_service, init_service, read_body, TestRequest},
+        web, App,
+    };
+    use std::time::Duration;
+
+    // Opens an API key store in a new database in a temporary directory
+    fn temporary_store() -> (tempfile::TempDir, ApiKeyStore) {
+        let directory = tempfile::tempdir().unwrap();
+        let url = format!("sqlite://{}", directory.path().join("keys.db").display());
+        let store = ApiKeyStore::new(&url).unwrap();
+        (directory, store)
+    }
+
+    // Verifies that an issued key can make its quota of requests, that other keys have their own quotas, and that a
+    // revoked key can't be used
+    #[actix_web::test]
+    async fn test_use_key() {
+        let (_directory, store) = temporary_store();
+        let issued = store.issue("Robot", 2).await.unwrap();
+        let other = store.issue("Other robot", 1).await.unwrap();
+
+        assert_eq!(issued.name, "Robot");
+        assert_eq!(issued.daily_quota, 2);
+        assert_ne!(issued.key, other.key);
+
+        assert_eq!(store.use_key(&issued.key).await.unwrap(), KeyUse::Allowed);
+        assert_eq!(store.use_key(&issued.key).await.unwrap(), KeyUse::Allowed);
+        assert_eq!(
+            store.use_key(&issued.key).await.unwrap(),
+            KeyUse::QuotaExceeded { daily_quota: 2 }
+        );
+        assert_eq!(store.use_key(&other.key).await.unwrap(), KeyUse::Allowed);
+        assert_eq!(store.use_key("unknown").await.unwrap(), KeyUse::Invalid);
+
+        let keys = store.keys().await.unwrap();
+
+        assert_eq!(keys.len(), 2);
+        assert_eq!(keys[0].name, "Robot");
+        assert_eq!(keys[0].requests_today, 2);
+        assert_eq!(keys[1].requests_today, 1);
+        assert!(!keys[0].revoked);
+
+        assert!(store.revoke(other.id).await.unwrap());
+        assert!(!store.revoke(other.id).await.unwrap());
+        assert_eq!(store.use_key(&other.key).await.unwrap(), KeyUse::Invalid);
+        assert!(store.keys().await.unwrap()[1].revoked);
+    }
+
+    // Verifies that the quotas start over at midnight in UTC
+    #[test]
+    fn test_seconds_until_tomorrow() {
+        let midnight = SystemTime::UNIX_EPOCH + Duration::from_secs(SECONDS_PER_DAY * 19_000);
+
+        assert_eq!(seconds_until_tomorrow(midnight), SECONDS_PER_DAY);
+        assert_eq!(
+            seconds_until_tomorrow(midnight + Duration::from_secs(SECONDS_PER_DAY - 90)),
+            90
+        );
+    }
+
+    // Verifies that the middleware answers requests without a valid key, or with a key that used its quota, with a
+    // JSON error, and lets every request through when API keys aren't required
+    #[actix_web::test]
+    async fn test_api_key_middleware() {
+        let (_directory, store) = temporary_store();
+        let issued = store.issue("Robot", 1).await.unwrap();
+        let app = init_service(
+            App::new()
+                .service(
+                    web::resource("/checked")
+                        .wrap(ApiKeyCheck::new(Some(store)))
+                        .to(HttpResponse::Ok),
+                )
+                .service(
+                    web::resource("/open")
+                        .wrap(ApiKeyCheck::new(None))
+                        .to(HttpResponse::Ok),
+                ),
+        )
+        .await;
+
+        for (key, status, code) in [
+            (None, StatusCode::UNAUTHORIZED, "missing_api_key"),
+            (Some("unknown"), StatusCode::UNAUTHORIZED, "invalid_api_key"),
+        ] {
+            let mut request = TestRequest::get().uri("/checked");
+            if let Some(key) = key {
+                request = request.insert_header((API_KEY_HEADER, key));
+            }
+            let response = call_service(&app, request.to_request()).await;
+
+            assert_eq!(response.status(), status);
+
+            let error: serde_json::Value =
+                serde_json::from_slice(&read_body(response).await).unwrap();
+
+            assert_eq!(error["error"], code);
+        }
+
+        let request = TestRequest::get()
+            .uri("/checked")
+            .insert_header((API_KEY_HEADER, issued.key.as_str()))
+            .to_request();
+        let response = call_service(&app, request).await;
+
+        assert!(response.status().is_success());
+
+        let request = TestRequest::get()
+            .uri("/checked")
+            .insert_header((API_KEY_HEADER, issued.key.as_str()))
+            .to_request();
+        let response = call_service(&app, request).await;
+
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert!(response.headers().contains_key(header::RETRY_AFTER));
+
+        let error: serde_json::Value = serde_json::from_slice(&read_body(response).await).unwrap();
+
+        assert_eq!(error["error"], "quota_exceeded");
+        assert!(error["message"].as_str().unwrap().contains("1 requests"));
+
+        let request = TestRequest::get().uri("/open").to_request();
+        let response = call_service(&app, request).await;
+
+        assert!(response.status().is_success());
+    }
+}
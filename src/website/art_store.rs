@@ -0,0 +1,143 @@
+//! Module to store published ASCII art by the hash of its contents.
+//!
+//! Each piece of ASCII art is saved in a file named after the SHA-256 hash of its text, so the same art is only stored once
+//! and the art behind a hash can never change. This lets other tools pull art from the GET /api/art/{hash} endpoint
+//! and cache it forever.
+//!
+//! Robert Peterson and Kelsey Werner 2023
+
+use sha2::{Digest, Sha256};
+use std::{fs, io, path::PathBuf};
+use tempfile::NamedTempFile;
+
+/// The directory the web application stores published ASCII art in.
+pub const ART_STORE_DIRECTORY: &str = "./art_store/";
+
+/// Function to calculate the hash ASCII art is stored under, which is its SHA-256 hash as 64 lowercase hex digits.
+///
+/// Any other content, such as an image, can be hashed the same way.
+pub fn content_hash<T: AsRef<[u8]>>(content: T) -> String {
+    Sha256::digest(content.as_ref())
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+/// Function to check if a hash looks like one made by [content_hash].
+///
+/// Only these hashes are used as file names, so a hash can't point outside of the store.
+pub fn is_content_hash(hash: &str) -> bool {
+    hash.len() == 64
+        && hash
+            .bytes()
+            .all(|byte| byte.is_ascii_digit() || (b'a'..=b'f').contains(&byte))
+}
+
+/// Struct to store ASCII art in a directory by the hash of its contents.
+pub struct ArtStore {
+    /// The directory the ASCII art files are stored in.
+    root: PathBuf,
+}
+
+impl ArtStore {
+    /// Function to open the store in a directory, which is created if it doesn't exist yet.
+    pub fn new<P: Into<PathBuf>>(root: P) -> io::Result<ArtStore> {
+        let root = root.into();
+        fs::create_dir_all(&root)?;
+        Ok(ArtStore { root })
+    }
+
+    /// Function to get the path of the file that ASCII art with the given hash is stored in.
+    fn path_of(&self, hash: &str) -> PathBuf {
+        self.root.join(format!("{}.txt", hash))
+    }
+
+    /// Function to store ASCII art under its [content_hash].
+    ///
+    /// Returns `Ok((hash, true))` when the art is stored for the first time and `Ok((hash, false))` when it was already stored.
+    /// The file is written under a temporary name and then renamed, so art is never read while it is half written.
+    pub fn publish(&self, ascii: &str) -> io::Result<(String, bool)> {
+        let hash = content_hash(ascii);
+        let path = self.path_of(&hash);
+        if path.exists() {
+            return Ok((hash, false));
+        }
+
+        let mut file = NamedTempFile::new_in(&self.root)?;
+        io::Write::write_all(&mut file, ascii.as_bytes())?;
+        file.persist(&path).map_err(|e| e.error)?;
+        Ok((hash, true))
+    }
+
+    /// Function to list the hashes of every stored ASCII art, sorted by hash.
+    pub fn hashes(&self) -> io::Result<Vec<String>> {
+        let mut hashes = Vec::new();
+        for entry in fs::read_dir(&self.root)? {
+            let path = entry?.path();
+            // the temporary files of art that is being published are skipped
+            let hash = match (path.file_stem(), path.extension()) {
+                (Some(stem), Some(extension)) if extension == "txt" => stem.to_string_lossy(),
+                _ => continue,
+            };
+            if is_content_hash(&hash) {
+                hashes.push(hash.to_string());
+            }
+        }
+        hashes.sort_unstable();
+        Ok(hashes)
+    }
+
+    /// Function to get the ASCII art stored under a hash.
+    ///
+    /// Returns `Ok(None)` when nothing is stored under the hash, or when the hash is not one made by [content_hash].
+    pub fn get(&self, hash: &str) -> io::Result<Option<String>> {
+        if !is_content_hash(hash) {
+            return Ok(None);
+        }
+
+        match fs::read_to_string(self.path_of(hash)) {
+            Ok(ascii) => Ok(Some(ascii)),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+}
+
+// Tests
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Verifies that content_hash() returns the SHA-256 hash of the text as lowercase hex digits
+    #[test]
+    fn test_content_hash() {
+        assert_eq!(
+            content_hash("abc"),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+        assert!(is_content_hash(&content_hash("$$$")));
+        assert!(!is_content_hash("../../etc/passwd"));
+        assert!(!is_content_hash(&content_hash("$$$").to_uppercase()));
+    }
+
+    // Verifies that published ASCII art can be read back by its hash, and is only stored once
+    #[test]
+    fn test_publish_and_get() {
+        let root = tempfile::tempdir().unwrap();
+        let art_directory = root.path().join("art");
+        let store = ArtStore::new(&art_directory).unwrap();
+
+        let (hash, created) = store.publish("$$\n..\n").unwrap();
+
+        assert!(created);
+        assert_eq!(hash, content_hash("$$\n..\n"));
+        assert_eq!(store.get(&hash).unwrap(), Some("$$\n..\n".to_string()));
+        assert_eq!(store.publish("$$\n..\n").unwrap(), (hash, false));
+        assert_eq!(fs::read_dir(&art_directory).unwrap().count(), 1);
+        assert_eq!(store.hashes().unwrap(), vec![content_hash("$$\n..\n")]);
+
+        assert_eq!(store.get(&content_hash("missing")).unwrap(), None);
+        assert_eq!(store.get("not-a-hash").unwrap(), None);
+    }
+}
@@ -4,7 +4,20 @@
 //!
 //! Robert Peterson and Kelsey Werner 2023
 
+use super::{
+    attribution::{clean_author, Attribution, License},
+    image_form_params::parse_orientation,
+};
+use ascii_art_converter::converter::{
+    normalize::{normalize_ascii_with_warnings, rejoin_soft_wraps},
+    pipeline::Stage,
+    warning::ConversionWarning,
+};
 use serde::{Deserialize, Serialize};
+use unicode_segmentation::UnicodeSegmentation;
+
+/// The max number of non-ASCII characters that are reported back to the user.
+pub const MAX_REPORTED_NON_ASCII: usize = 5;
 
 /// Struct to store ASCII art text.
 ///
@@ -13,6 +26,78 @@ use serde::{Deserialize, Serialize};
 pub struct AsciiFormParams {
     /// [String] to store ASCII art text.
     pub ascii_input: String,
+    /// [bool] that is `true` when the user asked for the ASCII art text to be used exactly as pasted.
+    ///
+    /// Unchecked checkboxes are not submitted with the form, so this defaults to `false`.
+    #[serde(default)]
+    pub skip_normalization: bool,
+    /// [bool] that is `true` when the user asked for soft-wrapped lines to be left alone.
+    ///
+    /// Unchecked checkboxes are not submitted with the form, so this defaults to `false`.
+    #[serde(default)]
+    pub skip_soft_wrap_repair: bool,
+    /// [bool] that is `true` when the user asked for characters that are not supported to be drawn as the supported
+    /// symbol that looks the most like them, instead of being reported as an error.
+    ///
+    /// Unchecked checkboxes are not submitted with the form, so this defaults to `false`.
+    #[serde(default)]
+    pub replace_unknown_symbols: bool,
+    /// [Option] stores `true` when the user asked for each character to be drawn as a gray pixel instead of with a font,
+    /// `false` when the user asked for a font, or [None] if neither was submitted so that the default theme of the website is used.
+    #[serde(default)]
+    pub render_pixels: Option<bool>,
+    /// [Option] stores the name of the selected rotation or flip (such as "rotate90") or [None] if no orientation submitted.
+    #[serde(default)]
+    pub orientation: Option<String>,
+    /// [Option] stores the title the conversion is shown with in the gallery or [None] if no title submitted.
+    #[serde(default)]
+    pub title: Option<String>,
+    /// [Option] stores the name of the author of the conversion or [None] if no author submitted.
+    #[serde(default)]
+    pub author: Option<String>,
+    /// [Option] stores the code of the selected [License] or [None] if no license submitted.
+    #[serde(default)]
+    pub license: Option<String>,
+    /// [bool] that is `true` when the user asked for the images to be returned inline in the page as data URLs,
+    /// instead of being stored as files on the server. Inline results have no permalink and are not added to the gallery.
+    ///
+    /// Unchecked checkboxes are not submitted with the form, so this defaults to `false`.
+    #[serde(default)]
+    pub inline_result: bool,
+}
+
+/// Struct to store the location of a non-ASCII character found in ASCII art text input.
+///
+/// Characters are grapheme clusters (user-perceived characters), so an emoji made of several
+/// Unicode code points or a letter with combining accents is reported once, at the column the user sees it.
+#[derive(PartialEq, Debug)]
+pub struct NonAsciiCharacter {
+    /// [String] that stores the grapheme cluster that is not ASCII.
+    pub grapheme: String,
+    /// The line the character was found on, starting at 1.
+    pub line: usize,
+    /// The column the character was found in, starting at 1 and counted in grapheme clusters.
+    pub column: usize,
+}
+
+impl NonAsciiCharacter {
+    /// Function to describe the character and its position in a way that can be displayed to the user.
+    ///
+    /// The Unicode code points of the character are included since many non-ASCII characters
+    /// (like non-breaking spaces) look identical to ASCII characters.
+    pub fn describe(&self) -> String {
+        let code_points = self
+            .grapheme
+            .chars()
+            .map(|c| format!("U+{:04X}", c as u32))
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        format!(
+            "Line {}, column {}: \"{}\" ({})",
+            self.line, self.column, self.grapheme, code_points
+        )
+    }
 }
 
 /// Enum to store the possible error states that can be detected when sanitizing ASCII art text input.
@@ -23,24 +108,126 @@ pub enum AsciiInputError {
     /// [AsciiInputError::EmptyInput] error is caused when the form is submitted without being populated with text input.
     EmptyInput,
     /// [AsciiInputError::NotAsciiInput] error is caused when the form is submitted with text input that is not valid ASCII.
-    NotAsciiInput,
+    ///
+    /// This variant stores the first [MAX_REPORTED_NON_ASCII] offending characters and the total number of offending characters.
+    NotAsciiInput {
+        offenders: Vec<NonAsciiCharacter>,
+        total: usize,
+    },
+    /// [AsciiInputError::UnsupportedLicense] error is caused when the form is submitted with a license that is not one of the [License] codes.
+    UnsupportedLicense,
+    /// [AsciiInputError::UnsupportedOrientation] error is caused when the form is submitted with an orientation that is not one of the
+    /// [super::image_form_params::SUPPORTED_ORIENTATIONS].
+    UnsupportedOrientation,
 }
 
 impl AsciiFormParams {
+    /// Function to normalize the ASCII art text input before it is validated.
+    ///
+    /// Pasted text often contains a byte-order mark or non-breaking spaces that look like ASCII
+    /// but are not. Unless [AsciiFormParams::skip_normalization] is set, these characters are
+    /// removed or mapped to their ASCII equivalents using [normalize_ascii_with_warnings].
+    /// Returns a [ConversionWarning] for each kind of character that was changed so that the user
+    /// can be told about the changes.
+    pub fn normalize_ascii_input(&mut self) -> Vec<ConversionWarning> {
+        if self.skip_normalization {
+            return Vec::new();
+        }
+
+        let (normalized, warnings) = normalize_ascii_with_warnings(&self.ascii_input);
+        self.ascii_input = normalized;
+        warnings
+    }
+
+    /// Function to repair ASCII art text input that was soft-wrapped by an email client or browser.
+    ///
+    /// Unless [AsciiFormParams::skip_soft_wrap_repair] is set, the wrapped lines are detected and
+    /// rejoined using [rejoin_soft_wraps]. Returns `true` when the input was repaired so that the
+    /// user can be told about the repair.
+    pub fn repair_soft_wrapped_input(&mut self) -> bool {
+        if self.skip_soft_wrap_repair {
+            return false;
+        }
+
+        match rejoin_soft_wraps(&self.ascii_input) {
+            Some(repaired) => {
+                self.ascii_input = repaired;
+                true
+            }
+            None => false,
+        }
+    }
+
     /// Function to verify if ASCII art form input is valid.
     ///
     /// Returns `Ok(())` when the input is valid ASCII text.
     /// Returns `Err(AsciiInputError::EmptyInput)` when an empty form is submitted.
-    /// Returns `Err(AsciiInputError::NotAsciiInput)` when invalid ASCII text is submitted.
+    /// Returns `Err(AsciiInputError::NotAsciiInput)` with the position of the first few offending characters when invalid ASCII text is submitted.
     pub fn validate_ascii_input(&self) -> Result<(), AsciiInputError> {
         if self.ascii_input.is_empty() {
             Err(AsciiInputError::EmptyInput)
         } else if !self.ascii_input.is_ascii() {
-            Err(AsciiInputError::NotAsciiInput)
+            let mut offenders = self.find_non_ascii_characters();
+            let total = offenders.len();
+            offenders.truncate(MAX_REPORTED_NON_ASCII);
+
+            Err(AsciiInputError::NotAsciiInput { offenders, total })
         } else {
             Ok(())
         }
     }
+
+    /// Function to verify if the author and license form input is valid.
+    ///
+    /// Returns `Ok(Attribution)` with the cleaned up author name and the selected [License], which are [None] when left blank.
+    /// Returns `Err(AsciiInputError::UnsupportedLicense)` when a license that is not one of the [License] codes is submitted.
+    pub fn validate_attribution(&self) -> Result<Attribution, AsciiInputError> {
+        let license = match self.license.as_deref() {
+            None | Some("") => None,
+            Some(code) => {
+                Some(License::from_code(code).ok_or(AsciiInputError::UnsupportedLicense)?)
+            }
+        };
+
+        Ok(Attribution {
+            author: self.author.as_deref().and_then(clean_author),
+            license,
+        })
+    }
+
+    /// Function to verify if the orientation form input is valid.
+    ///
+    /// Returns `Ok(None)` when no orientation is submitted so that the image is drawn upright.
+    /// Returns `Ok(Some(Stage))` with the rotate or flip stage of one of the [super::image_form_params::SUPPORTED_ORIENTATIONS].
+    /// Returns `Err(AsciiInputError::UnsupportedOrientation)` when any other orientation is submitted.
+    pub fn validate_orientation(&self) -> Result<Option<Stage>, AsciiInputError> {
+        match self.orientation.as_deref() {
+            None => Ok(None),
+            Some(name) => parse_orientation(name).ok_or(AsciiInputError::UnsupportedOrientation),
+        }
+    }
+
+    /// Function to find every grapheme cluster in the ASCII art text input that is not ASCII.
+    ///
+    /// The grapheme clusters are found using the unicode-segmentation crate so that
+    /// positions match what the user sees in the text box.
+    fn find_non_ascii_characters(&self) -> Vec<NonAsciiCharacter> {
+        let mut offenders = Vec::new();
+
+        for (line_index, line) in self.ascii_input.lines().enumerate() {
+            for (column_index, grapheme) in line.graphemes(true).enumerate() {
+                if !grapheme.is_ascii() {
+                    offenders.push(NonAsciiCharacter {
+                        grapheme: grapheme.to_string(),
+                        line: line_index + 1,
+                        column: column_index + 1,
+                    });
+                }
+            }
+        }
+
+        offenders
+    }
 }
 
 // Tests
@@ -50,6 +237,15 @@ impl AsciiFormParams {
 fn test_empty_input() {
     let input = AsciiFormParams {
         ascii_input: "".to_string(),
+        skip_normalization: false,
+        skip_soft_wrap_repair: false,
+        replace_unknown_symbols: false,
+        render_pixels: Some(false),
+        orientation: None,
+        title: None,
+        author: None,
+        license: None,
+        inline_result: false,
     };
     let result = input.validate_ascii_input();
     assert_eq!(result, Err(AsciiInputError::EmptyInput));
@@ -60,17 +256,148 @@ fn test_empty_input() {
 fn test_not_ascii_input() {
     let mut input = AsciiFormParams {
         ascii_input: "😄".to_string(),
+        skip_normalization: false,
+        skip_soft_wrap_repair: false,
+        replace_unknown_symbols: false,
+        render_pixels: Some(false),
+        orientation: None,
+        title: None,
+        author: None,
+        license: None,
+        inline_result: false,
     };
     let mut result = input.validate_ascii_input();
 
-    assert_eq!(result, Err(AsciiInputError::NotAsciiInput));
+    assert_eq!(
+        result,
+        Err(AsciiInputError::NotAsciiInput {
+            offenders: vec![NonAsciiCharacter {
+                grapheme: "😄".to_string(),
+                line: 1,
+                column: 1,
+            }],
+            total: 1,
+        })
+    );
 
     input = AsciiFormParams {
-        ascii_input: "£¥€¢abc".to_string(),
+        ascii_input: "ab\n£¥€¢abc".to_string(),
+        skip_normalization: false,
+        skip_soft_wrap_repair: false,
+        replace_unknown_symbols: false,
+        render_pixels: Some(false),
+        orientation: None,
+        title: None,
+        author: None,
+        license: None,
+        inline_result: false,
     };
     result = input.validate_ascii_input();
 
-    assert_eq!(result, Err(AsciiInputError::NotAsciiInput));
+    assert_eq!(
+        result,
+        Err(AsciiInputError::NotAsciiInput {
+            offenders: vec![
+                NonAsciiCharacter {
+                    grapheme: "£".to_string(),
+                    line: 2,
+                    column: 1,
+                },
+                NonAsciiCharacter {
+                    grapheme: "¥".to_string(),
+                    line: 2,
+                    column: 2,
+                },
+                NonAsciiCharacter {
+                    grapheme: "€".to_string(),
+                    line: 2,
+                    column: 3,
+                },
+                NonAsciiCharacter {
+                    grapheme: "¢".to_string(),
+                    line: 2,
+                    column: 4,
+                },
+            ],
+            total: 4,
+        })
+    );
+}
+
+// Verifies that AsciiFormParams::validate_ascii_input() reports multi-code-point characters once and at the column the user sees
+#[test]
+fn test_not_ascii_input_graphemes() {
+    let input = AsciiFormParams {
+        ascii_input: "e\u{0301}👩\u{200D}💻@".to_string(),
+        skip_normalization: false,
+        skip_soft_wrap_repair: false,
+        replace_unknown_symbols: false,
+        render_pixels: Some(false),
+        orientation: None,
+        title: None,
+        author: None,
+        license: None,
+        inline_result: false,
+    };
+    let result = input.validate_ascii_input();
+
+    assert_eq!(
+        result,
+        Err(AsciiInputError::NotAsciiInput {
+            offenders: vec![
+                NonAsciiCharacter {
+                    grapheme: "e\u{0301}".to_string(),
+                    line: 1,
+                    column: 1,
+                },
+                NonAsciiCharacter {
+                    grapheme: "👩\u{200D}💻".to_string(),
+                    line: 1,
+                    column: 2,
+                },
+            ],
+            total: 2,
+        })
+    );
+}
+
+// Verifies that AsciiFormParams::validate_ascii_input() only reports the first few offending characters
+#[test]
+fn test_not_ascii_input_truncated() {
+    let input = AsciiFormParams {
+        ascii_input: "€".repeat(MAX_REPORTED_NON_ASCII + 3),
+        skip_normalization: false,
+        skip_soft_wrap_repair: false,
+        replace_unknown_symbols: false,
+        render_pixels: Some(false),
+        orientation: None,
+        title: None,
+        author: None,
+        license: None,
+        inline_result: false,
+    };
+
+    if let Err(AsciiInputError::NotAsciiInput { offenders, total }) = input.validate_ascii_input() {
+        assert_eq!(offenders.len(), MAX_REPORTED_NON_ASCII);
+        assert_eq!(total, MAX_REPORTED_NON_ASCII + 3);
+    } else {
+        panic!("Expected AsciiInputError::NotAsciiInput.");
+    }
+}
+
+// Verifies that NonAsciiCharacter::describe() includes the position and code points of the character
+#[test]
+fn test_describe_non_ascii_character() {
+    let character = NonAsciiCharacter {
+        grapheme: "\u{00A0}".to_string(),
+        line: 3,
+        column: 7,
+    };
+
+    assert_eq!(
+        character.describe(),
+        "Line 3, column 7: \"\u{00A0}\" (U+00A0)"
+    );
 }
 
 // Verifies that valid form input detected by AsciiFormParams::validate_ascii_input() and Ok(()) returned
@@ -78,8 +405,154 @@ fn test_not_ascii_input() {
 fn test_valid_ascii_input() {
     let input = AsciiFormParams {
         ascii_input: "Hello! <> 123 \n {};+=@".to_string(),
+        skip_normalization: false,
+        skip_soft_wrap_repair: false,
+        replace_unknown_symbols: false,
+        render_pixels: Some(false),
+        orientation: None,
+        title: None,
+        author: None,
+        license: None,
+        inline_result: false,
     };
     let result = input.validate_ascii_input();
 
     assert_eq!(result, Ok(()));
 }
+
+// Verifies that AsciiFormParams::normalize_ascii_input() strips a BOM and maps non-breaking spaces so the input validates
+#[test]
+fn test_normalize_ascii_input() {
+    let mut input = AsciiFormParams {
+        ascii_input: "\u{FEFF}@\u{00A0}@".to_string(),
+        skip_normalization: false,
+        skip_soft_wrap_repair: false,
+        replace_unknown_symbols: false,
+        render_pixels: Some(false),
+        orientation: None,
+        title: None,
+        author: None,
+        license: None,
+        inline_result: false,
+    };
+    let warnings = input.normalize_ascii_input();
+
+    assert_eq!(input.ascii_input, "@ @");
+    assert_eq!(
+        warnings,
+        vec![
+            ConversionWarning::InvisibleCharactersRemoved(1),
+            ConversionWarning::SpacesReplaced(1)
+        ]
+    );
+    assert_eq!(input.validate_ascii_input(), Ok(()));
+}
+
+// Verifies that AsciiFormParams::normalize_ascii_input() leaves the input alone when normalization is disabled
+#[test]
+fn test_skip_normalization() {
+    let mut input = AsciiFormParams {
+        ascii_input: "\u{FEFF}@\u{00A0}@".to_string(),
+        skip_normalization: true,
+        skip_soft_wrap_repair: false,
+        replace_unknown_symbols: false,
+        render_pixels: Some(false),
+        orientation: None,
+        title: None,
+        author: None,
+        license: None,
+        inline_result: false,
+    };
+    assert_eq!(input.normalize_ascii_input(), vec![]);
+
+    assert_eq!(input.ascii_input, "\u{FEFF}@\u{00A0}@");
+    assert!(matches!(
+        input.validate_ascii_input(),
+        Err(AsciiInputError::NotAsciiInput { total: 2, .. })
+    ));
+}
+
+// Verifies that AsciiFormParams::repair_soft_wrapped_input() rejoins soft-wrapped lines and reports the repair
+#[test]
+fn test_repair_soft_wrapped_input() {
+    let long = "@".repeat(40);
+    let mut input = AsciiFormParams {
+        ascii_input: format!("{long}\n..\n{long}\n.", long = long),
+        skip_normalization: false,
+        skip_soft_wrap_repair: false,
+        replace_unknown_symbols: false,
+        render_pixels: Some(false),
+        orientation: None,
+        title: None,
+        author: None,
+        license: None,
+        inline_result: false,
+    };
+
+    assert!(input.repair_soft_wrapped_input());
+    assert_eq!(input.ascii_input, format!("{long}..\n{long}.", long = long));
+
+    // nothing to repair the second time around
+    assert!(!input.repair_soft_wrapped_input());
+}
+
+// Verifies that AsciiFormParams::repair_soft_wrapped_input() leaves the input alone when the repair is disabled
+#[test]
+fn test_skip_soft_wrap_repair() {
+    let long = "@".repeat(40);
+    let mut input = AsciiFormParams {
+        ascii_input: format!("{long}\n..\n{long}\n.", long = long),
+        skip_normalization: false,
+        skip_soft_wrap_repair: true,
+        replace_unknown_symbols: false,
+        render_pixels: Some(false),
+        orientation: None,
+        title: None,
+        author: None,
+        license: None,
+        inline_result: false,
+    };
+
+    assert!(!input.repair_soft_wrapped_input());
+    assert_eq!(
+        input.ascii_input,
+        format!("{long}\n..\n{long}\n.", long = long)
+    );
+}
+
+// Verifies that the author and license are read by AsciiFormParams::validate_attribution(), and that an unsupported license is detected and error returned
+#[test]
+fn test_validate_attribution() {
+    let mut input = AsciiFormParams {
+        ascii_input: "$".to_string(),
+        skip_normalization: false,
+        skip_soft_wrap_repair: false,
+        replace_unknown_symbols: false,
+        render_pixels: None,
+        orientation: None,
+        title: None,
+        author: Some("Kelsey\n".to_string()),
+        license: Some("cc0-1.0".to_string()),
+        inline_result: false,
+    };
+
+    assert_eq!(
+        input.validate_attribution(),
+        Ok(Attribution {
+            author: Some("Kelsey".to_string()),
+            license: Some(License::Cc0),
+        })
+    );
+
+    input.author = None;
+    input.license = None;
+
+    assert_eq!(input.validate_attribution(), Ok(Attribution::default()));
+
+    input.license = Some("cc-by-nc-4.0".to_string());
+
+    assert_eq!(
+        input.validate_attribution(),
+        Err(AsciiInputError::UnsupportedLicense)
+    );
+}
@@ -0,0 +1,149 @@
+//! Module to store who made a conversion and how others may use it.
+//!
+//! Users can give the author of a conversion and pick a [License] for it on the forms. The [Attribution] is kept with
+//! the conversion in the gallery and in its [super::results::ResultRecord], is shown on the result pages, and is written
+//! into the text metadata of the PNG image when ASCII art is converted into an image.
+//!
+//! Robert Peterson and Kelsey Werner 2023
+
+use serde::{Deserialize, Serialize};
+
+/// The longest author name a conversion can be given, in characters.
+pub const MAX_AUTHOR_LENGTH: usize = 100;
+
+/// Enum to store the licenses a conversion can be published under.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, sqlx::Type)]
+pub enum License {
+    /// [License::AllRightsReserved] is used when the author keeps every right to the conversion.
+    #[serde(rename = "all-rights-reserved")]
+    #[sqlx(rename = "all-rights-reserved")]
+    AllRightsReserved,
+    /// [License::CcBy4] is used for the Creative Commons Attribution 4.0 license.
+    #[serde(rename = "cc-by-4.0")]
+    #[sqlx(rename = "cc-by-4.0")]
+    CcBy4,
+    /// [License::CcBySa4] is used for the Creative Commons Attribution-ShareAlike 4.0 license.
+    #[serde(rename = "cc-by-sa-4.0")]
+    #[sqlx(rename = "cc-by-sa-4.0")]
+    CcBySa4,
+    /// [License::Cc0] is used for the Creative Commons CC0 1.0 public domain dedication.
+    #[serde(rename = "cc0-1.0")]
+    #[sqlx(rename = "cc0-1.0")]
+    Cc0,
+}
+
+impl License {
+    /// Function to find the [License] picked on a form from its code, such as "cc-by-4.0".
+    ///
+    /// Returns [None] when the code is not the code of a [License].
+    pub fn from_code(code: &str) -> Option<License> {
+        match code {
+            "all-rights-reserved" => Some(License::AllRightsReserved),
+            "cc-by-4.0" => Some(License::CcBy4),
+            "cc-by-sa-4.0" => Some(License::CcBySa4),
+            "cc0-1.0" => Some(License::Cc0),
+            _ => None,
+        }
+    }
+
+    /// Function to get the name of the [License] that is shown to people.
+    pub fn name(&self) -> &'static str {
+        match self {
+            License::AllRightsReserved => "All rights reserved",
+            License::CcBy4 => "CC BY 4.0",
+            License::CcBySa4 => "CC BY-SA 4.0",
+            License::Cc0 => "CC0 1.0",
+        }
+    }
+}
+
+/// Struct to store the author and [License] of a conversion.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Attribution {
+    /// [Option] stores the name of the author of the conversion or [None] if no author was given.
+    pub author: Option<String>,
+    /// [Option] stores the [License] of the conversion or [None] if no license was picked.
+    pub license: Option<License>,
+}
+
+impl Attribution {
+    /// Function to get the PNG text metadata entries of the [Attribution], which are "Author" and "Copyright".
+    ///
+    /// Entries are only included for the parts of the [Attribution] that were given.
+    pub fn png_text_entries(&self) -> Vec<(&'static str, &str)> {
+        let mut entries = Vec::new();
+        if let Some(author) = &self.author {
+            entries.push(("Author", author.as_str()));
+        }
+        if let Some(license) = &self.license {
+            entries.push(("Copyright", license.name()));
+        }
+        entries
+    }
+}
+
+/// Function to clean up the author name a user gave a conversion.
+///
+/// Returns [None] when the name is blank, and otherwise the name without control characters or surrounding whitespace and
+/// cut to [MAX_AUTHOR_LENGTH] characters.
+pub fn clean_author(author: &str) -> Option<String> {
+    let author: String = author.chars().filter(|c| !c.is_control()).collect();
+    let author = author.trim();
+    if author.is_empty() {
+        None
+    } else {
+        Some(author.chars().take(MAX_AUTHOR_LENGTH).collect())
+    }
+}
+
+// Tests
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Verifies that clean_author() removes control characters, drops blank names and cuts long names
+    #[test]
+    fn test_clean_author() {
+        assert_eq!(
+            clean_author(" Kelsey\u{0}\r\n Werner "),
+            Some("Kelsey Werner".to_string())
+        );
+        assert_eq!(clean_author(" \t "), None);
+        assert_eq!(
+            clean_author(&"é".repeat(MAX_AUTHOR_LENGTH + 1)),
+            Some("é".repeat(MAX_AUTHOR_LENGTH))
+        );
+    }
+
+    // Verifies that licenses are found by their codes, which are also the names they are stored with
+    #[test]
+    fn test_license_codes() {
+        for license in [
+            License::AllRightsReserved,
+            License::CcBy4,
+            License::CcBySa4,
+            License::Cc0,
+        ] {
+            let code = serde_json::to_value(license).unwrap();
+
+            assert_eq!(License::from_code(code.as_str().unwrap()), Some(license));
+        }
+        assert_eq!(License::from_code("gpl-3.0"), None);
+    }
+
+    // Verifies that only the given parts of an attribution are written into the PNG text metadata
+    #[test]
+    fn test_png_text_entries() {
+        let attribution = Attribution {
+            author: Some("Robert Peterson".to_string()),
+            license: Some(License::CcBySa4),
+        };
+
+        assert_eq!(
+            attribution.png_text_entries(),
+            vec![("Author", "Robert Peterson"), ("Copyright", "CC BY-SA 4.0")]
+        );
+        assert!(Attribution::default().png_text_entries().is_empty());
+    }
+}
@@ -0,0 +1,436 @@
+//! Module to register users and sign them in and out.
+//!
+//! Accounts are optional and only turned on when the `accounts_enabled` of the [super::config::AppConfig] is set. A user
+//! registers with a name and a password on the GET /login page, and the password is only stored as an argon2 hash made by
+//! [hash_password]. Registering or signing in starts a session, whose random token is sent to the browser in the
+//! [SESSION_COOKIE] cookie. The cookie is only sent back to the website itself, can't be read by scripts, and is only sent
+//! over HTTPS when the website serves HTTPS.
+//!
+//! Handlers take a [SignedInUser] argument to only run for signed in users, which sends everyone else to the GET /login
+//! page before the body of their request is read.
+//!
+//! Robert Peterson and Kelsey Werner 2023
+
+use super::user::{User, UserStore};
+use actix_web::{
+    cookie::{time::Duration as CookieDuration, Cookie, SameSite},
+    dev::Payload,
+    http::{header, StatusCode},
+    web, FromRequest, HttpRequest, HttpResponse, ResponseError,
+};
+use argon2::{
+    password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, SaltString},
+    Argon2,
+};
+use serde::{Deserialize, Serialize};
+use std::{fmt, future::Future, pin::Pin};
+use uuid::Uuid;
+
+/// The cookie the token of the session of a signed in user is kept in.
+pub const SESSION_COOKIE: &str = "ascii_art_session";
+
+/// The fewest characters a user name can have.
+pub const MIN_USERNAME_LENGTH: usize = 3;
+
+/// The most characters a user name can have.
+pub const MAX_USERNAME_LENGTH: usize = 32;
+
+/// The fewest characters a password can have.
+pub const MIN_PASSWORD_LENGTH: usize = 8;
+
+/// The most characters a password can have, which keeps hashing a submitted password quick.
+pub const MAX_PASSWORD_LENGTH: usize = 128;
+
+/// A hash made by [hash_password] that no user has, which passwords are checked against when there is no user with the
+/// name, so signing in takes as long for names that are taken as for names that aren't.
+const DUMMY_PASSWORD_HASH: &str =
+    "$argon2id$v=19$m=19456,t=2,p=1$Kjz9+eA6S5GUVUHlVK1xeA$0A+iPlWKDzFWQdnuSiBi4VL5kIlhbBj2cz+fv/+JsPk";
+
+/// Struct to store the name and password a user registers or signs in with.
+///
+/// Actix Web populates [AccountFormParams] with the form data of the GET /login page.
+#[derive(Serialize, Deserialize)]
+pub struct AccountFormParams {
+    /// [String] to store the name of the user.
+    pub username: String,
+    /// [String] to store the password of the user.
+    pub password: String,
+}
+
+/// Enum to store the reasons a user can't register or sign in.
+///
+/// The different enum variants are used to identify the specific cause of an error.
+#[derive(PartialEq, Debug)]
+pub enum AccountError {
+    /// [AccountError::InvalidUsername] error is caused when the name is too short or too long, or has characters other than
+    /// letters, digits, `_`, `-` and `.`.
+    InvalidUsername,
+    /// [AccountError::InvalidPassword] error is caused when the password has fewer than [MIN_PASSWORD_LENGTH] or more than
+    /// [MAX_PASSWORD_LENGTH] characters.
+    InvalidPassword,
+    /// [AccountError::UsernameTaken] error is caused when another user already registered with the name.
+    UsernameTaken,
+    /// [AccountError::WrongPassword] error is caused when there is no user with the name, or the password is not theirs.
+    WrongPassword,
+    /// [AccountError::Unavailable] error is caused when the accounts can't be read or written.
+    Unavailable,
+}
+
+impl fmt::Display for AccountError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AccountError::InvalidUsername => write!(f, "It looks like that name can't be used! Pick a name of {} to {} letters, digits, underscores, dashes or dots.", MIN_USERNAME_LENGTH, MAX_USERNAME_LENGTH),
+            AccountError::InvalidPassword => write!(f, "It looks like that password can't be used! Pick a password of {} to {} characters.", MIN_PASSWORD_LENGTH, MAX_PASSWORD_LENGTH),
+            AccountError::UsernameTaken => write!(f, "It looks like someone already has that name! Pick another one, or sign in if it's yours."),
+            AccountError::WrongPassword => write!(f, "It looks like the name or password is wrong! Check them and try it one more time."),
+            AccountError::Unavailable => write!(f, "It looks like we ran into an issue with the accounts! Try it one more time in a few minutes."),
+        }
+    }
+}
+
+impl AccountError {
+    /// Function to get the status code of the page that shows the error.
+    pub fn status_code(&self) -> StatusCode {
+        match self {
+            AccountError::InvalidUsername | AccountError::InvalidPassword => {
+                StatusCode::BAD_REQUEST
+            }
+            AccountError::UsernameTaken => StatusCode::CONFLICT,
+            AccountError::WrongPassword => StatusCode::UNAUTHORIZED,
+            AccountError::Unavailable => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
+impl AccountFormParams {
+    /// Function to verify if the name and password can be registered with.
+    ///
+    /// Returns `Ok(&str)` with the name without surrounding whitespace, `Err(AccountError::InvalidUsername)` when the name
+    /// can't be used, and `Err(AccountError::InvalidPassword)` when the password can't be used.
+    pub fn validate(&self) -> Result<&str, AccountError> {
+        let username = self.username.trim();
+        let username_length = username.chars().count();
+        if !(MIN_USERNAME_LENGTH..=MAX_USERNAME_LENGTH).contains(&username_length)
+            || !username
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.'))
+        {
+            return Err(AccountError::InvalidUsername);
+        }
+        if !(MIN_PASSWORD_LENGTH..=MAX_PASSWORD_LENGTH).contains(&self.password.chars().count()) {
+            return Err(AccountError::InvalidPassword);
+        }
+
+        Ok(username)
+    }
+}
+
+/// Function to hash a password with argon2 and a random salt, in the PHC string format that keeps the settings with it.
+pub fn hash_password(password: &str) -> Result<String, argon2::password_hash::Error> {
+    let salt = SaltString::encode_b64(Uuid::new_v4().as_bytes())?;
+    Ok(Argon2::default()
+        .hash_password(password.as_bytes(), &salt)?
+        .to_string())
+}
+
+/// Function to check a password against a hash made by [hash_password].
+///
+/// Returns `false` when the password doesn't match, or the hash can't be read.
+pub fn verify_password(password: &str, password_hash: &str) -> bool {
+    PasswordHash::new(password_hash).is_ok_and(|password_hash| {
+        Argon2::default()
+            .verify_password(password.as_bytes(), &password_hash)
+            .is_ok()
+    })
+}
+
+/// Function to register a user with the name and password of a form.
+///
+/// The password is hashed on a blocking thread, since argon2 is slow on purpose. Returns the new [User], or the
+/// [AccountError] that says why they couldn't register.
+pub async fn register(users: &UserStore, form: &AccountFormParams) -> Result<User, AccountError> {
+    let username = form.validate()?;
+    let password = form.password.clone();
+    let password_hash = web::block(move || hash_password(&password))
+        .await
+        .map_err(|_| AccountError::Unavailable)?
+        .map_err(|_| AccountError::Unavailable)?;
+
+    match users.add_user(username, &password_hash).await {
+        Ok(Some(user)) => Ok(user),
+        Ok(None) => Err(AccountError::UsernameTaken),
+        Err(_) => Err(AccountError::Unavailable),
+    }
+}
+
+/// Function to find the user with the name and password of a form.
+///
+/// Returns [AccountError::WrongPassword] both when there is no user with the name and when the password is wrong, so the
+/// page doesn't tell which names are taken. The password is checked against [DUMMY_PASSWORD_HASH] when there is no user,
+/// so the time it takes doesn't tell either.
+pub async fn sign_in(users: &UserStore, form: &AccountFormParams) -> Result<User, AccountError> {
+    let user = users
+        .find_user(form.username.trim())
+        .await
+        .map_err(|_| AccountError::Unavailable)?;
+
+    let password = form.password.clone();
+    let password_hash = user.as_ref().map_or_else(
+        || DUMMY_PASSWORD_HASH.to_string(),
+        |user| user.password_hash.clone(),
+    );
+    let verified = web::block(move || verify_password(&password, &password_hash))
+        .await
+        .map_err(|_| AccountError::Unavailable)?;
+
+    match user {
+        Some(user) if verified => Ok(user),
+        _ => Err(AccountError::WrongPassword),
+    }
+}
+
+/// Function to start a session of a user and create the cookie that keeps its token in their browser.
+///
+/// The token is two random UUIDs, and the cookie is only sent over HTTPS when `secure` is set.
+pub async fn start_session(
+    users: &UserStore,
+    user: &User,
+    secure: bool,
+) -> Result<Cookie<'static>, AccountError> {
+    let token = format!("{}{}", Uuid::new_v4().simple(), Uuid::new_v4().simple());
+    users
+        .start_session(user.id, &token)
+        .await
+        .map_err(|_| AccountError::Unavailable)?;
+
+    Ok(Cookie::build(SESSION_COOKIE, token)
+        .path("/")
+        .http_only(true)
+        .secure(secure)
+        .same_site(SameSite::Lax)
+        .max_age(CookieDuration::days(i64::from(
+            super::user::SESSION_TTL_DAYS,
+        )))
+        .finish())
+}
+
+/// Function to create the cookie that removes the session token from the browser of a user that signs out.
+pub fn removal_cookie() -> Cookie<'static> {
+    let mut cookie = Cookie::build(SESSION_COOKIE, "").path("/").finish();
+    cookie.make_removal();
+    cookie
+}
+
+/// Enum to store the reasons a request doesn't come from a signed in user.
+#[derive(PartialEq, Debug)]
+pub enum SignInError {
+    /// [SignInError::SignedOut] error is caused when the request has no session cookie, or its session ended or expired.
+    SignedOut,
+    /// [SignInError::Disabled] error is caused when accounts are turned off.
+    Disabled,
+    /// [SignInError::Unavailable] error is caused when the sessions can't be read.
+    Unavailable,
+}
+
+impl fmt::Display for SignInError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SignInError::SignedOut => writeln!(f, "It looks like you're not signed in! Sign in to see your saved ASCII art."),
+            SignInError::Disabled => writeln!(f, "Accounts are turned off on this website."),
+            SignInError::Unavailable => writeln!(f, "It looks like we ran into an issue with the accounts! Try it one more time in a few minutes."),
+        }
+    }
+}
+
+impl ResponseError for SignInError {
+    /// Returns `303 See Other` to the sign in page for a signed out user, `404 Not Found` when accounts are turned off, and
+    /// `503 Service Unavailable` when the sessions can't be read.
+    fn status_code(&self) -> StatusCode {
+        match self {
+            SignInError::SignedOut => StatusCode::SEE_OTHER,
+            SignInError::Disabled => StatusCode::NOT_FOUND,
+            SignInError::Unavailable => StatusCode::SERVICE_UNAVAILABLE,
+        }
+    }
+
+    /// Returns the plain text error message, along with the `Location` of the sign in page for a signed out user.
+    fn error_response(&self) -> HttpResponse {
+        let mut response = HttpResponse::build(self.status_code());
+        if *self == SignInError::SignedOut {
+            response.insert_header((header::LOCATION, "/login"));
+        }
+        response
+            .content_type("text/plain; charset=utf-8")
+            .body(self.to_string())
+    }
+}
+
+/// Struct to store the [User] a request comes from.
+///
+/// Actix Web only creates a [SignedInUser] for requests whose session cookie belongs to a session that hasn't ended.
+pub struct SignedInUser(pub User);
+
+impl FromRequest for SignedInUser {
+    type Error = SignInError;
+    type Future = Pin<Box<dyn Future<Output = Result<SignedInUser, SignInError>>>>;
+
+    fn from_request(request: &HttpRequest, _: &mut Payload) -> Self::Future {
+        let users = request.app_data::<web::Data<UserStore>>().cloned();
+        let token = request
+            .cookie(SESSION_COOKIE)
+            .map(|cookie| cookie.value().to_string());
+
+        Box::pin(async move {
+            let users = users.ok_or(SignInError::Disabled)?;
+            let token = token.ok_or(SignInError::SignedOut)?;
+            match users.session_user(&token).await {
+                Ok(Some(user)) => Ok(SignedInUser(user)),
+                Ok(None) => Err(SignInError::SignedOut),
+                Err(_) => Err(SignInError::Unavailable),
+            }
+        })
+    }
+}
+
+// Tests
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::test::TestRequest;
+
+    // Opens a user store in a new database in a temporary directory
+    fn temporary_store() -> (tempfile::TempDir, UserStore) {
+        let directory = tempfile::tempdir().unwrap();
+        let url = format!("sqlite://{}", directory.path().join("users.db").display());
+        let store = UserStore::new(&url).unwrap();
+        (directory, store)
+    }
+
+    // Creates an account form with the given name and password
+    fn form(username: &str, password: &str) -> AccountFormParams {
+        AccountFormParams {
+            username: username.to_string(),
+            password: password.to_string(),
+        }
+    }
+
+    // Verifies that only names of letters, digits and a few symbols, and passwords of a sensible length, are accepted
+    #[test]
+    fn test_validate() {
+        assert_eq!(
+            form(" kelsey.w ", "correct horse").validate(),
+            Ok("kelsey.w")
+        );
+        assert_eq!(
+            form("ke", "correct horse").validate(),
+            Err(AccountError::InvalidUsername)
+        );
+        assert_eq!(
+            form("<script>", "correct horse").validate(),
+            Err(AccountError::InvalidUsername)
+        );
+        assert_eq!(
+            form(&"k".repeat(MAX_USERNAME_LENGTH + 1), "correct horse").validate(),
+            Err(AccountError::InvalidUsername)
+        );
+        assert_eq!(
+            form("kelsey", "short").validate(),
+            Err(AccountError::InvalidPassword)
+        );
+        assert_eq!(
+            form("kelsey", &"p".repeat(MAX_PASSWORD_LENGTH + 1)).validate(),
+            Err(AccountError::InvalidPassword)
+        );
+    }
+
+    // Verifies that passwords are hashed with a random salt and only match the password they were made from
+    #[test]
+    fn test_hash_password() {
+        let hash = hash_password("correct horse").unwrap();
+
+        assert!(hash.starts_with("$argon2id$"));
+        assert_ne!(hash, hash_password("correct horse").unwrap());
+        assert!(verify_password("correct horse", &hash));
+        assert!(!verify_password("battery staple", &hash));
+        assert!(!verify_password("correct horse", "not a hash"));
+    }
+
+    // Verifies that the dummy hash checked for unknown users is made with the same argon2 settings as the hashes of users,
+    // so checking a password against it takes as long
+    #[test]
+    fn test_dummy_password_hash() {
+        let hash = hash_password("correct horse").unwrap();
+        let settings = |hash: &str| hash.split('$').take(4).collect::<Vec<_>>().join("$");
+
+        assert_eq!(settings(DUMMY_PASSWORD_HASH), settings(&hash));
+        assert!(PasswordHash::new(DUMMY_PASSWORD_HASH).is_ok());
+        assert!(!verify_password("correct horse", DUMMY_PASSWORD_HASH));
+    }
+
+    // Verifies that a registered user can sign in with their password, and that the session cookie signs them in
+    #[actix_web::test]
+    async fn test_register_and_sign_in() {
+        let (_directory, store) = temporary_store();
+
+        let user = register(&store, &form("kelsey", "correct horse"))
+            .await
+            .unwrap();
+
+        assert_eq!(
+            register(&store, &form("Kelsey", "battery staple")).await,
+            Err(AccountError::UsernameTaken)
+        );
+        assert_eq!(
+            sign_in(&store, &form("KELSEY", "correct horse")).await,
+            Ok(user.clone())
+        );
+        assert_eq!(
+            sign_in(&store, &form("kelsey", "battery staple")).await,
+            Err(AccountError::WrongPassword)
+        );
+        assert_eq!(
+            sign_in(&store, &form("robert", "correct horse")).await,
+            Err(AccountError::WrongPassword)
+        );
+
+        let cookie = start_session(&store, &user, true).await.unwrap();
+
+        assert_eq!(cookie.name(), SESSION_COOKIE);
+        assert_eq!(cookie.http_only(), Some(true));
+        assert_eq!(cookie.secure(), Some(true));
+        assert_eq!(cookie.same_site(), Some(SameSite::Lax));
+
+        let store = web::Data::new(store);
+        let request = TestRequest::default()
+            .app_data(store.clone())
+            .cookie(cookie.clone())
+            .to_http_request();
+
+        assert_eq!(SignedInUser::extract(&request).await.unwrap().0, user);
+
+        store.end_session(cookie.value()).await.unwrap();
+        let request = TestRequest::default()
+            .app_data(store)
+            .cookie(cookie)
+            .to_http_request();
+        let error = SignedInUser::extract(&request).await.err().unwrap();
+
+        assert_eq!(error, SignInError::SignedOut);
+        assert_eq!(
+            error
+                .error_response()
+                .headers()
+                .get(header::LOCATION)
+                .unwrap(),
+            "/login"
+        );
+
+        let request = TestRequest::default().to_http_request();
+
+        assert_eq!(
+            SignedInUser::extract(&request).await.err(),
+            Some(SignInError::Disabled)
+        );
+    }
+}
@@ -0,0 +1,307 @@
+//! Module to store and sanitize batch image input.
+//!
+//! The images are provided in a multipart form to the POST /api/v1/batch/image-to-ascii endpoint, either as
+//! several `images` files or as a single ZIP archive of images, such as with
+//! `curl -F "images=@photos.zip" http://127.0.0.1:8080/api/v1/batch/image-to-ascii -o ascii.zip`.
+//!
+//! Robert Peterson and Kelsey Werner 2023
+
+use super::image_form_params::parse_ascii_width;
+use actix_multipart::form::{bytes::Bytes, text::Text, MultipartForm};
+use std::{
+    collections::HashSet,
+    io::{Cursor, Read},
+    path::Path,
+};
+use utoipa::ToSchema;
+use zip::ZipArchive;
+
+/// The most images that can be converted in one batch.
+pub const MAX_BATCH_IMAGES: usize = 100;
+
+/// The largest an image in a ZIP archive can be once it is extracted, in bytes.
+pub const MAX_BATCH_IMAGE_SIZE: u64 = 10_485_760;
+
+/// The name of the list of images that couldn't be converted, which no image of a batch is given.
+pub const ERRORS_NAME: &str = "errors";
+
+/// Struct to store a batch of images.
+///
+/// Actix Web populates [BatchFormParams] with user-submitted form data.
+#[derive(MultipartForm, ToSchema)]
+pub struct BatchFormParams {
+    /// [Vec] stores every submitted file as [Bytes], which are images or a ZIP archive of images.
+    #[schema(value_type = Vec<String>, format = Binary)]
+    pub images: Vec<Bytes>,
+    /// [Option] stores the selected ASCII art width as [Text] or [None] if no width submitted.
+    #[schema(value_type = Option<u32>, example = 80)]
+    pub ascii_width: Option<Text<String>>,
+}
+
+/// Struct to store an image of a batch along with the name its ASCII art is saved under.
+#[derive(PartialEq, Debug)]
+pub struct BatchImage {
+    /// The name of the image without its extension, which is unique within the batch.
+    pub name: String,
+    /// The contents of the image file.
+    pub data: Vec<u8>,
+}
+
+/// Enum to store the possible error states that can be detected when sanitizing batch input.
+///
+/// The different enum variants are used to identify the specific cause of an error.
+#[derive(PartialEq, Debug)]
+pub enum BatchInputError {
+    /// [BatchInputError::EmptyInput] error is caused when the form is submitted without any images.
+    EmptyInput,
+    /// [BatchInputError::InvalidArchive] error is caused when a ZIP archive can't be read.
+    InvalidArchive,
+    /// [BatchInputError::TooManyImages] error is caused when the form is submitted with more than [MAX_BATCH_IMAGES] images.
+    TooManyImages,
+    /// [BatchInputError::ImageTooLarge] error is caused when an image in a ZIP archive is larger than [MAX_BATCH_IMAGE_SIZE].
+    ImageTooLarge,
+    /// [BatchInputError::UnsupportedWidth] error is caused when the form is submitted with an ASCII art width that is not one of the [SUPPORTED_ASCII_WIDTHS](super::image_form_params::SUPPORTED_ASCII_WIDTHS).
+    UnsupportedWidth,
+}
+
+/// Function to check if a file is a ZIP archive by the signature at the start of its contents.
+fn is_zip_archive(data: &[u8]) -> bool {
+    data.starts_with(b"PK\x03\x04")
+}
+
+/// Function to pick a name for an image that no other image of the batch has.
+///
+/// The name is the file name of the image without its extension, or its position in the batch when it has no name,
+/// and a number is added to the end of names that were already taken.
+fn unique_name(file_name: Option<&str>, position: usize, taken: &mut HashSet<String>) -> String {
+    let name = file_name
+        .and_then(|file_name| Path::new(file_name).file_stem())
+        .map(|stem| stem.to_string_lossy().to_string())
+        .filter(|stem| !stem.is_empty())
+        .unwrap_or_else(|| format!("image-{}", position + 1));
+
+    let mut unique = name.clone();
+    let mut count = 1;
+    while !taken.insert(unique.clone()) {
+        count += 1;
+        unique = format!("{}-{}", name, count);
+    }
+    unique
+}
+
+impl BatchFormParams {
+    /// Function to verify if the batch form input is valid.
+    ///
+    /// When the input passes valiation, function returns `Ok(Vec<BatchImage>)` with every submitted image in order,
+    /// where the images of a ZIP archive take the place of the archive. Folders and hidden files in a ZIP archive are skipped.
+    /// Returns `Err(BatchInputError::EmptyInput)` when no images are submitted.
+    /// Returns `Err(BatchInputError::InvalidArchive)` when a ZIP archive can't be read.
+    /// Returns `Err(BatchInputError::TooManyImages)` when more than [MAX_BATCH_IMAGES] images are submitted.
+    /// Returns `Err(BatchInputError::ImageTooLarge)` when an image in a ZIP archive is larger than [MAX_BATCH_IMAGE_SIZE].
+    pub fn validate_images(&self) -> Result<Vec<BatchImage>, BatchInputError> {
+        let mut files: Vec<(Option<String>, Vec<u8>)> = Vec::new();
+
+        for file in self.images.iter().filter(|file| !file.data.is_empty()) {
+            if !is_zip_archive(&file.data) {
+                files.push((file.file_name.clone(), file.data.to_vec()));
+                continue;
+            }
+
+            let mut archive = ZipArchive::new(Cursor::new(&file.data[..]))
+                .map_err(|_| BatchInputError::InvalidArchive)?;
+            for i in 0..archive.len() {
+                let entry = archive
+                    .by_index(i)
+                    .map_err(|_| BatchInputError::InvalidArchive)?;
+                // folders and the hidden files that some archivers add aren't images
+                let hidden = entry.enclosed_name().is_none_or(|path| {
+                    path.iter()
+                        .any(|part| part.to_string_lossy().starts_with(['.', '_']))
+                });
+                if entry.is_dir() || hidden {
+                    continue;
+                }
+                if files.len() == MAX_BATCH_IMAGES {
+                    return Err(BatchInputError::TooManyImages);
+                }
+
+                // the size in the archive can't be trusted, so no more than the limit is extracted
+                let name = entry.name().to_string();
+                let mut data = Vec::new();
+                entry
+                    .take(MAX_BATCH_IMAGE_SIZE + 1)
+                    .read_to_end(&mut data)
+                    .map_err(|_| BatchInputError::InvalidArchive)?;
+                if data.len() as u64 > MAX_BATCH_IMAGE_SIZE {
+                    return Err(BatchInputError::ImageTooLarge);
+                }
+                files.push((Some(name), data));
+            }
+        }
+
+        if files.is_empty() {
+            return Err(BatchInputError::EmptyInput);
+        }
+        if files.len() > MAX_BATCH_IMAGES {
+            return Err(BatchInputError::TooManyImages);
+        }
+
+        let mut taken = HashSet::from([ERRORS_NAME.to_string()]);
+        Ok(files
+            .into_iter()
+            .enumerate()
+            .map(|(position, (file_name, data))| BatchImage {
+                name: unique_name(file_name.as_deref(), position, &mut taken),
+                data,
+            })
+            .collect())
+    }
+
+    /// Function to verify if the ASCII art width form input is valid, the same way as on the image to ASCII form.
+    ///
+    /// Returns `Err(BatchInputError::UnsupportedWidth)` when the width is rejected by [parse_ascii_width].
+    pub fn validate_ascii_width(&self) -> Result<Option<u32>, BatchInputError> {
+        parse_ascii_width(&self.ascii_width).map_err(|_| BatchInputError::UnsupportedWidth)
+    }
+}
+
+// Tests
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::web;
+    use std::io::Write;
+    use zip::{write::FileOptions, ZipWriter};
+
+    // Creates an uploaded file with the given name and contents
+    fn upload(file_name: &str, data: Vec<u8>) -> Bytes {
+        Bytes {
+            data: web::Bytes::from(data),
+            content_type: None,
+            file_name: Some(file_name.to_string()),
+        }
+    }
+
+    // Creates a ZIP archive that holds the given files
+    fn zip_archive(files: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut archive = ZipWriter::new(Cursor::new(Vec::new()));
+        for (name, data) in files {
+            archive.start_file(*name, FileOptions::default()).unwrap();
+            archive.write_all(data).unwrap();
+        }
+        archive.finish().unwrap().into_inner()
+    }
+
+    // Verifies that empty input accurately detected by BatchFormParams::validate_images() and error returned
+    #[test]
+    fn test_empty_input() {
+        let mut input = BatchFormParams {
+            images: Vec::new(),
+            ascii_width: None,
+        };
+
+        assert_eq!(
+            input.validate_images().unwrap_err(),
+            BatchInputError::EmptyInput
+        );
+
+        input.images = vec![
+            upload("empty.png", Vec::new()),
+            upload("folder.zip", zip_archive(&[("__MACOSX/a.png", b"a")])),
+        ];
+
+        assert_eq!(
+            input.validate_images().unwrap_err(),
+            BatchInputError::EmptyInput
+        );
+    }
+
+    // Verifies that the images of ZIP archives take the place of the archives and are given unique names
+    #[test]
+    fn test_validate_images() {
+        let archive = zip_archive(&[
+            ("photos/cat.png", b"cat"),
+            ("photos/.DS_Store", b"hidden"),
+            ("dog.jpeg", b"dog"),
+            ("errors.png", b"errors"),
+        ]);
+        let input = BatchFormParams {
+            images: vec![
+                upload("cat.png", b"first".to_vec()),
+                upload("photos.zip", archive),
+                upload("", b"unnamed".to_vec()),
+            ],
+            ascii_width: None,
+        };
+
+        let names_and_data: Vec<(String, Vec<u8>)> = input
+            .validate_images()
+            .unwrap()
+            .into_iter()
+            .map(|image| (image.name, image.data))
+            .collect();
+
+        assert_eq!(
+            names_and_data,
+            vec![
+                ("cat".to_string(), b"first".to_vec()),
+                ("cat-2".to_string(), b"cat".to_vec()),
+                ("dog".to_string(), b"dog".to_vec()),
+                ("errors-2".to_string(), b"errors".to_vec()),
+                ("image-5".to_string(), b"unnamed".to_vec()),
+            ]
+        );
+    }
+
+    // Verifies that archives that can't be read and batches over the limits are rejected by BatchFormParams::validate_images()
+    #[test]
+    fn test_validate_images_errors() {
+        let mut input = BatchFormParams {
+            images: vec![upload("broken.zip", b"PK\x03\x04broken".to_vec())],
+            ascii_width: None,
+        };
+
+        assert_eq!(
+            input.validate_images().unwrap_err(),
+            BatchInputError::InvalidArchive
+        );
+
+        input.images = (0..=MAX_BATCH_IMAGES)
+            .map(|i| upload(&format!("{}.png", i), vec![0]))
+            .collect();
+
+        assert_eq!(
+            input.validate_images().unwrap_err(),
+            BatchInputError::TooManyImages
+        );
+
+        let large_image = vec![0; MAX_BATCH_IMAGE_SIZE as usize + 1];
+        input.images = vec![upload(
+            "large.zip",
+            zip_archive(&[("large.png", &large_image)]),
+        )];
+
+        assert_eq!(
+            input.validate_images().unwrap_err(),
+            BatchInputError::ImageTooLarge
+        );
+    }
+
+    // Verifies that the ASCII art width is checked by BatchFormParams::validate_ascii_width()
+    #[test]
+    fn test_ascii_width() {
+        for (width, expected) in [
+            (None, Ok(None)),
+            (Some("40"), Ok(Some(40))),
+            (Some("wide"), Err(BatchInputError::UnsupportedWidth)),
+        ] {
+            let input = BatchFormParams {
+                images: Vec::new(),
+                ascii_width: width.map(|w| Text(w.to_string())),
+            };
+
+            assert_eq!(input.validate_ascii_width(), expected);
+        }
+    }
+}
@@ -0,0 +1,80 @@
+//! Module to remove old results of conversions so they don't pile up forever.
+//!
+//! A background task removes every result in the [ResultStore] that was stored longer ago than the time to live of the
+//! results, which is 7 days unless it is set in the [super::config::AppConfig]. Setting it to 0 hours keeps the results
+//! forever.
+//!
+//! Robert Peterson and Kelsey Werner 2023
+
+use super::result_store::ResultStore;
+use actix_web::{rt::time::interval, web};
+use std::{
+    io,
+    sync::Arc,
+    time::{Duration, SystemTime},
+};
+
+/// Function to remove every result that was stored longer than `ttl` before `now`.
+///
+/// Results that are removed by something else in the meantime are skipped. Returns the number of results that were removed.
+pub fn prune_results(
+    results: &dyn ResultStore,
+    ttl: Duration,
+    now: SystemTime,
+) -> io::Result<usize> {
+    let mut removed = 0;
+
+    for name in results.names()? {
+        let expired = match results.modified(&name)? {
+            Some(modified) => now.duration_since(modified).is_ok_and(|age| age > ttl),
+            None => false,
+        };
+        if expired && results.remove(&name)? {
+            removed += 1;
+        }
+    }
+
+    Ok(removed)
+}
+
+/// Function to remove old results every `period`, starting right away, for as long as the server runs.
+///
+/// The results are removed on a blocking thread so the server keeps answering requests meanwhile. A failed cleanup is
+/// tried again at the next interval.
+pub async fn run_cleanup(results: Arc<dyn ResultStore>, ttl: Duration, period: Duration) {
+    let mut ticks = interval(period);
+
+    loop {
+        ticks.tick().await;
+        let results = Arc::clone(&results);
+        let _ = web::block(move || prune_results(results.as_ref(), ttl, SystemTime::now())).await;
+    }
+}
+
+// Tests
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::website::result_store::DirectoryResultStore;
+
+    // Verifies that prune_results() only removes the results that are older than the time to live
+    #[test]
+    fn test_prune_results() {
+        let root = tempfile::tempdir().unwrap();
+        let results = DirectoryResultStore::new(root.path()).unwrap();
+        std::fs::write(root.path().join(".keep"), "").unwrap();
+        results.write("a.png", b"png").unwrap();
+        results.write("a.json", b"{}").unwrap();
+        let ttl = Duration::from_secs(60);
+
+        assert_eq!(prune_results(&results, ttl, SystemTime::now()).unwrap(), 0);
+        assert_eq!(results.names().unwrap(), vec!["a.json", "a.png"]);
+
+        let later = SystemTime::now() + ttl + Duration::from_secs(1);
+
+        assert_eq!(prune_results(&results, ttl, later).unwrap(), 2);
+        assert!(results.names().unwrap().is_empty());
+        assert!(root.path().join(".keep").exists());
+    }
+}
@@ -0,0 +1,268 @@
+//! Module to store and sanitize collage image input.
+//!
+//! The images are provided in a multipart form to the POST /api/v1/collage endpoint as two to four `images` files, such as with
+//! `curl -F "images=@cat.png" -F "images=@dog.jpeg" -F "layout=grid" http://127.0.0.1:8080/api/v1/collage`.
+//!
+//! Robert Peterson and Kelsey Werner 2023
+
+use super::image_form_params::parse_ascii_width;
+use actix_multipart::form::{bytes::Bytes, text::Text, MultipartForm};
+use ascii_art_converter::converter::{
+    collage::{Collage, CollageLayout},
+    image::SUPPORTED_FORMATS,
+};
+use utoipa::ToSchema;
+
+/// The fewest images that make a collage.
+pub const MIN_COLLAGE_IMAGES: usize = 2;
+
+/// The most images that can be put in one collage.
+pub const MAX_COLLAGE_IMAGES: usize = 4;
+
+/// The most characters a column separator can have.
+pub const MAX_SEPARATOR_LENGTH: usize = 5;
+
+/// Struct to store the images of a collage.
+///
+/// Actix Web populates [CollageFormParams] with user-submitted form data.
+#[derive(MultipartForm, ToSchema)]
+pub struct CollageFormParams {
+    /// [Vec] stores every submitted image file as [Bytes], in the order they are put in the collage.
+    #[schema(value_type = Vec<String>, format = Binary, min_items = 2, max_items = 4)]
+    pub images: Vec<Bytes>,
+    /// [Option] stores the name of the selected layout ("side-by-side" or "grid") as [Text] or [None] if no layout submitted.
+    #[schema(value_type = Option<String>, example = "grid")]
+    pub layout: Option<Text<String>>,
+    /// [Option] stores the text put between the images on the same lines as [Text] or [None] if no separator submitted.
+    #[schema(value_type = Option<String>, example = " | ")]
+    pub separator: Option<Text<String>>,
+    /// [Option] stores the character repeated between the rows of a grid as [Text] or [None] if no separator submitted.
+    #[schema(value_type = Option<String>, example = "-")]
+    pub row_separator: Option<Text<String>>,
+    /// [Option] stores the selected ASCII art width of each image as [Text] or [None] if no width submitted.
+    #[schema(value_type = Option<u32>, example = 80)]
+    pub ascii_width: Option<Text<String>>,
+}
+
+/// Enum to store the possible error states that can be detected when sanitizing collage input.
+///
+/// The different enum variants are used to identify the specific cause of an error.
+#[derive(PartialEq, Debug)]
+pub enum CollageInputError {
+    /// [CollageInputError::TooFewImages] error is caused when the form is submitted with fewer than [MIN_COLLAGE_IMAGES] images.
+    TooFewImages,
+    /// [CollageInputError::TooManyImages] error is caused when the form is submitted with more than [MAX_COLLAGE_IMAGES] images.
+    TooManyImages,
+    /// [CollageInputError::UnsupportedImageType] error is caused when the contents of a file are not a JPEG, PNG, WebP, BMP or TIFF image.
+    UnsupportedImageType,
+    /// [CollageInputError::UnsupportedLayout] error is caused when the form is submitted with a layout that is not "side-by-side" or "grid".
+    UnsupportedLayout,
+    /// [CollageInputError::UnsupportedSeparator] error is caused when the form is submitted with a separator that is longer than
+    /// [MAX_SEPARATOR_LENGTH], or a row separator that is not a single character, or either has characters that are not printable ASCII.
+    UnsupportedSeparator,
+    /// [CollageInputError::UnsupportedWidth] error is caused when the form is submitted with an ASCII art width that is not one of the [SUPPORTED_ASCII_WIDTHS](super::image_form_params::SUPPORTED_ASCII_WIDTHS).
+    UnsupportedWidth,
+}
+
+/// Function to check if a separator only has printable ASCII characters, which includes spaces but not line breaks or tabs.
+fn is_printable(separator: &str) -> bool {
+    separator.chars().all(|c| c == ' ' || c.is_ascii_graphic())
+}
+
+impl CollageFormParams {
+    /// Function to verify if the collage images are valid.
+    ///
+    /// The format of each image is sniffed from the magic bytes at the start of the file, just like on the image to ASCII form.
+    /// Empty files are skipped, as browsers submit one for a file input that was left empty.
+    ///
+    /// When the input passes valiation, function returns `Ok(Vec<&[u8]>)` with the contents of every image in order.
+    /// Returns `Err(CollageInputError::TooFewImages)` when fewer than [MIN_COLLAGE_IMAGES] images are submitted.
+    /// Returns `Err(CollageInputError::TooManyImages)` when more than [MAX_COLLAGE_IMAGES] images are submitted.
+    /// Returns `Err(CollageInputError::UnsupportedImageType)` when the contents of a file are not a JPEG, PNG, WebP, BMP or TIFF image.
+    pub fn validate_images(&self) -> Result<Vec<&[u8]>, CollageInputError> {
+        let images: Vec<&[u8]> = self
+            .images
+            .iter()
+            .filter(|file| !file.data.is_empty())
+            .map(|file| &file.data[..])
+            .collect();
+
+        if images.len() < MIN_COLLAGE_IMAGES {
+            return Err(CollageInputError::TooFewImages);
+        }
+        if images.len() > MAX_COLLAGE_IMAGES {
+            return Err(CollageInputError::TooManyImages);
+        }
+        let supported = images.iter().all(|image| {
+            image::guess_format(image).is_ok_and(|format| SUPPORTED_FORMATS.contains(&format))
+        });
+        if !supported {
+            return Err(CollageInputError::UnsupportedImageType);
+        }
+
+        Ok(images)
+    }
+
+    /// Function to verify if the layout and separator form input is valid.
+    ///
+    /// Returns `Ok(Collage)` that stitches the images with the submitted layout and separators, where a missing or empty input
+    /// is left as the default of [Collage::new], and a missing layout puts the images side by side.
+    /// Returns `Err(CollageInputError::UnsupportedLayout)` when a layout that is not "side-by-side" or "grid" is submitted.
+    /// Returns `Err(CollageInputError::UnsupportedSeparator)` when a separator is not printable ASCII or is too long.
+    pub fn validate_collage(&self) -> Result<Collage, CollageInputError> {
+        let layout = match self.layout.as_ref().map(|layout| layout.as_str()) {
+            None | Some("") | Some("side-by-side") => CollageLayout::SideBySide,
+            Some("grid") => CollageLayout::Grid,
+            Some(_) => return Err(CollageInputError::UnsupportedLayout),
+        };
+        let mut collage = Collage::new(layout);
+
+        match self.separator.as_ref().map(|separator| separator.as_str()) {
+            None | Some("") => {}
+            Some(separator)
+                if separator.len() <= MAX_SEPARATOR_LENGTH && is_printable(separator) =>
+            {
+                collage = collage.with_column_separator(separator);
+            }
+            Some(_) => return Err(CollageInputError::UnsupportedSeparator),
+        }
+
+        match self
+            .row_separator
+            .as_ref()
+            .map(|separator| separator.as_str())
+        {
+            None | Some("") => {}
+            Some(separator) if separator.len() == 1 && is_printable(separator) => {
+                collage = collage.with_row_separator(separator.chars().next());
+            }
+            Some(_) => return Err(CollageInputError::UnsupportedSeparator),
+        }
+
+        Ok(collage)
+    }
+
+    /// Function to verify if the ASCII art width form input is valid, the same way as on the image to ASCII form.
+    ///
+    /// Returns `Err(CollageInputError::UnsupportedWidth)` when the width is rejected by [parse_ascii_width].
+    pub fn validate_ascii_width(&self) -> Result<Option<u32>, CollageInputError> {
+        parse_ascii_width(&self.ascii_width).map_err(|_| CollageInputError::UnsupportedWidth)
+    }
+}
+
+// Tests
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::web;
+
+    // Creates an uploaded file with the given contents
+    fn upload(data: &[u8]) -> Bytes {
+        Bytes {
+            data: web::Bytes::copy_from_slice(data),
+            content_type: None,
+            file_name: None,
+        }
+    }
+
+    // Creates a form with the given images and text inputs
+    fn form(
+        images: &[&[u8]],
+        layout: Option<&str>,
+        separator: Option<&str>,
+        row_separator: Option<&str>,
+    ) -> CollageFormParams {
+        CollageFormParams {
+            images: images.iter().map(|image| upload(image)).collect(),
+            layout: layout.map(|layout| Text(layout.to_string())),
+            separator: separator.map(|separator| Text(separator.to_string())),
+            row_separator: row_separator.map(|separator| Text(separator.to_string())),
+            ascii_width: None,
+        }
+    }
+
+    const PNG: &[u8] = b"\x89PNG\r\n\x1a\n\0\0";
+    const JPEG: &[u8] = b"\xff\xd8\xff\0\0\0\0\0\0\0";
+
+    // Verifies that two to four images are accepted by CollageFormParams::validate_images(), skipping empty files
+    #[test]
+    fn test_validate_images() {
+        assert_eq!(
+            form(&[PNG, b"", JPEG], None, None, None).validate_images(),
+            Ok(vec![PNG, JPEG])
+        );
+        assert_eq!(
+            form(&[PNG, JPEG, PNG, JPEG], None, None, None)
+                .validate_images()
+                .unwrap()
+                .len(),
+            4
+        );
+
+        assert_eq!(
+            form(&[PNG, b""], None, None, None).validate_images(),
+            Err(CollageInputError::TooFewImages)
+        );
+        assert_eq!(
+            form(&[PNG; 5], None, None, None).validate_images(),
+            Err(CollageInputError::TooManyImages)
+        );
+        assert_eq!(
+            form(&[PNG, b"not an image"], None, None, None).validate_images(),
+            Err(CollageInputError::UnsupportedImageType)
+        );
+    }
+
+    // Verifies that the layout and separators are read by CollageFormParams::validate_collage()
+    #[test]
+    fn test_validate_collage() {
+        assert_eq!(
+            form(&[], None, None, None).validate_collage(),
+            Ok(Collage::new(CollageLayout::SideBySide))
+        );
+        assert_eq!(
+            form(&[], Some("grid"), Some(" | "), Some("-")).validate_collage(),
+            Ok(Collage::new(CollageLayout::Grid)
+                .with_column_separator(" | ")
+                .with_row_separator(Some('-')))
+        );
+        assert_eq!(
+            form(&[], Some("side-by-side"), Some(""), Some("")).validate_collage(),
+            Ok(Collage::new(CollageLayout::SideBySide))
+        );
+
+        assert_eq!(
+            form(&[], Some("diagonal"), None, None).validate_collage(),
+            Err(CollageInputError::UnsupportedLayout)
+        );
+        for (separator, row_separator) in [
+            (Some("\n"), None),
+            (Some("||||||"), None),
+            (Some("é"), None),
+            (None, Some("--")),
+            (None, Some("\t")),
+        ] {
+            assert_eq!(
+                form(&[], None, separator, row_separator).validate_collage(),
+                Err(CollageInputError::UnsupportedSeparator)
+            );
+        }
+    }
+
+    // Verifies that only the supported widths are accepted by CollageFormParams::validate_ascii_width()
+    #[test]
+    fn test_validate_ascii_width() {
+        let mut input = form(&[], None, None, None);
+        assert_eq!(input.validate_ascii_width(), Ok(None));
+
+        input.ascii_width = Some(Text("80".to_string()));
+        assert_eq!(input.validate_ascii_width(), Ok(Some(80)));
+
+        input.ascii_width = Some(Text("81".to_string()));
+        assert_eq!(
+            input.validate_ascii_width(),
+            Err(CollageInputError::UnsupportedWidth)
+        );
+    }
+}
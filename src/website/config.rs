@@ -0,0 +1,551 @@
+//! Module to store the settings of a deployment of the website.
+//!
+//! The [AppConfig] holds where the server listens, how large requests may be, where conversions and templates are
+//! stored, and how long results are kept. Every setting has a default, so the website runs without any configuration.
+//! Operators can put the settings in a TOML file whose path is set in the `ASCII_ART_CONFIG` environment variable, and
+//! any setting can also be set in its own environment variable, which takes precedence over the file:
+//!
+//! | Setting in the file        | Environment variable                 | Default                |
+//! |----------------------------|--------------------------------------|------------------------|
+//! | `bind_address`             | `ASCII_ART_BIND_ADDRESS`             | `127.0.0.1`            |
+//! | `port`                     | `ASCII_ART_PORT`                     | `8080`                 |
+//! | `payload_limit`            | `ASCII_ART_PAYLOAD_LIMIT`            | 1MB                    |
+//! | `upload_limit`             | `ASCII_ART_UPLOAD_LIMIT`             | 50MB                   |
+//! | `results_directory`        | `ASCII_ART_RESULTS_DIRECTORY`        | none, kept in memory   |
+//! | `art_directory`            | `ASCII_ART_ART_DIRECTORY`            | `./art_store/`         |
+//! | `gallery_database_url`     | `ASCII_ART_GALLERY_DATABASE_URL`     | `sqlite://gallery.db`  |
+//! | `template_directory`       | `ASCII_ART_TEMPLATE_DIRECTORY`       | `./static/templates`   |
+//! | `result_ttl_hours`         | `ASCII_ART_RESULT_TTL_HOURS`         | 168, 0 keeps forever   |
+//! | `cleanup_interval_minutes` | `ASCII_ART_CLEANUP_INTERVAL_MINUTES` | 60                     |
+//! | `rate_limit_per_minute`    | `ASCII_ART_RATE_LIMIT_PER_MINUTE`    | 30, 0 turns it off     |
+//! | `rate_limit_burst`         | `ASCII_ART_RATE_LIMIT_BURST`         | 10                     |
+//! | `require_self_test`        | `ASCII_ART_REQUIRE_SELF_TEST`        | `true`                 |
+//! | `job_threshold`            | `ASCII_ART_JOB_THRESHOLD`            | 2MB, 0 makes every job |
+//! | `conversion_workers`       | `ASCII_ART_CONVERSION_WORKERS`       | number of CPUs         |
+//! | `conversion_queue_limit`   | `ASCII_ART_CONVERSION_QUEUE_LIMIT`   | 64                     |
+//! | `locale`                   | `ASCII_ART_LOCALE`                   | `en`                   |
+//! | `shutdown_timeout_seconds` | `ASCII_ART_SHUTDOWN_TIMEOUT_SECONDS` | 30                     |
+//! | `tls_cert_path`            | `ASCII_ART_TLS_CERT_PATH`            | none, serves HTTP      |
+//! | `tls_key_path`             | `ASCII_ART_TLS_KEY_PATH`             | none, serves HTTP      |
+//! | `tls_port`                 | `ASCII_ART_TLS_PORT`                 | `8443`                 |
+//! | `content_security_policy`  | `ASCII_ART_CONTENT_SECURITY_POLICY`  | only the site itself   |
+//! | `frame_options`            | `ASCII_ART_FRAME_OPTIONS`            | `DENY`                 |
+//! | `referrer_policy`          | `ASCII_ART_REFERRER_POLICY`          | `same-origin`          |
+//! | `accounts_enabled`         | `ASCII_ART_ACCOUNTS_ENABLED`         | `false`                |
+//! | `api_keys_required`        | `ASCII_ART_API_KEYS_REQUIRED`        | `false`                |
+//! | `api_daily_quota`          | `ASCII_ART_API_DAILY_QUOTA`          | 1000                   |
+//!
+//! The security headers are left out when their setting is empty in the file.
+//! Environment variables with values that can't be used are ignored, while a file that can't be read stops the server.
+//!
+//! Robert Peterson and Kelsey Werner 2023
+
+use super::{
+    art_store::ART_STORE_DIRECTORY,
+    formatting::{NumberFormat, DEFAULT_LOCALE},
+    gallery::GALLERY_DATABASE_URL,
+    security_headers::{
+        DEFAULT_CONTENT_SECURITY_POLICY, DEFAULT_FRAME_OPTIONS, DEFAULT_REFERRER_POLICY,
+    },
+};
+use serde::Deserialize;
+use std::{
+    fmt, fs, io,
+    path::{Path, PathBuf},
+    str::FromStr,
+    thread,
+    time::Duration,
+};
+
+/// The environment variable the path of the TOML configuration file is read from.
+pub const CONFIG_FILE_VARIABLE: &str = "ASCII_ART_CONFIG";
+
+/// The environment variable the address the server listens on is read from.
+pub const BIND_ADDRESS_VARIABLE: &str = "ASCII_ART_BIND_ADDRESS";
+
+/// The environment variable the port the server listens on is read from.
+pub const PORT_VARIABLE: &str = "ASCII_ART_PORT";
+
+/// The environment variable the largest form or request body is read from, in bytes.
+pub const PAYLOAD_LIMIT_VARIABLE: &str = "ASCII_ART_PAYLOAD_LIMIT";
+
+/// The environment variable the largest multipart upload is read from, in bytes.
+pub const UPLOAD_LIMIT_VARIABLE: &str = "ASCII_ART_UPLOAD_LIMIT";
+
+/// The environment variable the directory the results of conversions are stored in is read from.
+pub const RESULTS_DIRECTORY_VARIABLE: &str = "ASCII_ART_RESULTS_DIRECTORY";
+
+/// The environment variable the directory published ASCII art is stored in is read from.
+pub const ART_DIRECTORY_VARIABLE: &str = "ASCII_ART_ART_DIRECTORY";
+
+/// The environment variable the url of the gallery database is read from.
+pub const GALLERY_DATABASE_URL_VARIABLE: &str = "ASCII_ART_GALLERY_DATABASE_URL";
+
+/// The environment variable the directory of the Handlebars templates is read from.
+pub const TEMPLATE_DIRECTORY_VARIABLE: &str = "ASCII_ART_TEMPLATE_DIRECTORY";
+
+/// The environment variable the time to live of the results is read from, in hours.
+pub const RESULT_TTL_VARIABLE: &str = "ASCII_ART_RESULT_TTL_HOURS";
+
+/// The environment variable how often old results are removed is read from, in minutes.
+pub const CLEANUP_INTERVAL_VARIABLE: &str = "ASCII_ART_CLEANUP_INTERVAL_MINUTES";
+
+/// The environment variable the number of conversions a client can make a minute is read from.
+pub const RATE_LIMIT_PER_MINUTE_VARIABLE: &str = "ASCII_ART_RATE_LIMIT_PER_MINUTE";
+
+/// The environment variable the number of conversions a client can make in a row is read from.
+pub const RATE_LIMIT_BURST_VARIABLE: &str = "ASCII_ART_RATE_LIMIT_BURST";
+
+/// The environment variable whether the server refuses to start when its self-test fails is read from, as `true` or `false`.
+pub const REQUIRE_SELF_TEST_VARIABLE: &str = "ASCII_ART_REQUIRE_SELF_TEST";
+
+/// The environment variable the size above which an uploaded image is converted as a background job is read from, in bytes.
+pub const JOB_THRESHOLD_VARIABLE: &str = "ASCII_ART_JOB_THRESHOLD";
+
+/// The environment variable the number of conversions that run at once is read from.
+pub const CONVERSION_WORKERS_VARIABLE: &str = "ASCII_ART_CONVERSION_WORKERS";
+
+/// The environment variable the number of conversions that can wait for a worker is read from.
+pub const CONVERSION_QUEUE_LIMIT_VARIABLE: &str = "ASCII_ART_CONVERSION_QUEUE_LIMIT";
+
+/// The environment variable the locale numbers are shown in is read from, such as `en` or `de-DE`.
+pub const LOCALE_VARIABLE: &str = "ASCII_ART_LOCALE";
+
+/// The environment variable the number of seconds conversions are given to finish when the server stops is read from.
+pub const SHUTDOWN_TIMEOUT_VARIABLE: &str = "ASCII_ART_SHUTDOWN_TIMEOUT_SECONDS";
+
+/// The environment variable the path of the PEM file with the TLS certificate chain is read from.
+pub const TLS_CERT_PATH_VARIABLE: &str = "ASCII_ART_TLS_CERT_PATH";
+
+/// The environment variable the path of the PEM file with the private key of the TLS certificate is read from.
+pub const TLS_KEY_PATH_VARIABLE: &str = "ASCII_ART_TLS_KEY_PATH";
+
+/// The environment variable the port HTTPS is served on is read from.
+pub const TLS_PORT_VARIABLE: &str = "ASCII_ART_TLS_PORT";
+
+/// The environment variable the Content-Security-Policy header of every response is read from.
+pub const CONTENT_SECURITY_POLICY_VARIABLE: &str = "ASCII_ART_CONTENT_SECURITY_POLICY";
+
+/// The environment variable the X-Frame-Options header of every response is read from.
+pub const FRAME_OPTIONS_VARIABLE: &str = "ASCII_ART_FRAME_OPTIONS";
+
+/// The environment variable the Referrer-Policy header of every response is read from.
+pub const REFERRER_POLICY_VARIABLE: &str = "ASCII_ART_REFERRER_POLICY";
+
+/// The environment variable whether users can register and save their ASCII art is read from.
+pub const ACCOUNTS_ENABLED_VARIABLE: &str = "ASCII_ART_ACCOUNTS_ENABLED";
+
+/// The environment variable whether the JSON API can only be used with an API key is read from.
+pub const API_KEYS_REQUIRED_VARIABLE: &str = "ASCII_ART_API_KEYS_REQUIRED";
+
+/// The environment variable the number of requests an API key can make a day, unless it was issued with its own, is read from.
+pub const API_DAILY_QUOTA_VARIABLE: &str = "ASCII_ART_API_DAILY_QUOTA";
+
+/// The number of hours results are kept for when none is set, which is 7 days.
+pub const DEFAULT_RESULT_TTL_HOURS: u64 = 7 * 24;
+
+/// Struct to store the settings of a deployment of the website.
+#[derive(Clone, PartialEq, Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AppConfig {
+    /// The address the server listens on.
+    pub bind_address: String,
+    /// The port the server listens on.
+    pub port: u16,
+    /// The largest form or request body the server accepts, in bytes.
+    pub payload_limit: usize,
+    /// The largest multipart upload the server accepts, which is kept in memory, in bytes.
+    pub upload_limit: usize,
+    /// [Option] stores the directory the results of conversions are stored in, or [None] to keep them in memory.
+    pub results_directory: Option<PathBuf>,
+    /// The directory published ASCII art is stored in.
+    pub art_directory: PathBuf,
+    /// The url of the SQLite database the gallery is stored in.
+    pub gallery_database_url: String,
+    /// The directory the Handlebars templates are registered from.
+    pub template_directory: PathBuf,
+    /// The number of hours results are kept for, where 0 keeps them forever.
+    pub result_ttl_hours: u64,
+    /// The number of minutes between the removals of old results.
+    pub cleanup_interval_minutes: u64,
+    /// The number of conversions a client can make a minute, where 0 turns the rate limiting off.
+    pub rate_limit_per_minute: u32,
+    /// The number of conversions a client can make in a row before it is held to [AppConfig::rate_limit_per_minute].
+    pub rate_limit_burst: u32,
+    /// [bool] that is `true` when the server refuses to start if its self-test fails, or `false` to only log the failures.
+    pub require_self_test: bool,
+    /// The size above which an uploaded image is converted as a background job instead of during the request, in bytes,
+    /// where 0 converts every upload as a job.
+    pub job_threshold: usize,
+    /// The number of conversions that run at once, each on its own blocking thread.
+    pub conversion_workers: usize,
+    /// The number of conversions that can wait for a worker before new ones are turned away.
+    pub conversion_queue_limit: usize,
+    /// The locale counts, sizes and durations are shown in on the pages of the website, such as `en` or `de-DE`.
+    pub locale: String,
+    /// The number of seconds requests and background jobs are given to finish once the server is asked to stop.
+    pub shutdown_timeout_seconds: u64,
+    /// [Option] stores the PEM file with the TLS certificate chain, or [None] to serve HTTP only.
+    pub tls_cert_path: Option<PathBuf>,
+    /// [Option] stores the PEM file with the private key of the TLS certificate, or [None] to serve HTTP only.
+    pub tls_key_path: Option<PathBuf>,
+    /// The port HTTPS is served on when a TLS certificate is set, while [AppConfig::port] redirects to it.
+    pub tls_port: u16,
+    /// The Content-Security-Policy header sent with every response, where an empty policy leaves it out.
+    pub content_security_policy: String,
+    /// The X-Frame-Options header sent with every response, where an empty value leaves it out.
+    pub frame_options: String,
+    /// The Referrer-Policy header sent with every response, where an empty policy leaves it out.
+    pub referrer_policy: String,
+    /// [bool] that is `true` when users can register, sign in and save their ASCII art, which is stored in the gallery
+    /// database, or `false` to turn accounts off.
+    pub accounts_enabled: bool,
+    /// [bool] that is `true` when the `/api/v1` endpoints can only be used with an API key issued by an operator, or
+    /// `false` to let anyone use them.
+    pub api_keys_required: bool,
+    /// The number of requests an API key can make to the `/api/v1` endpoints a day, unless it was issued with its own quota.
+    pub api_daily_quota: u32,
+}
+
+impl Default for AppConfig {
+    fn default() -> AppConfig {
+        AppConfig {
+            bind_address: "127.0.0.1".to_string(),
+            port: 8080,
+            payload_limit: 1_048_576,
+            upload_limit: 52_428_800,
+            results_directory: None,
+            art_directory: PathBuf::from(ART_STORE_DIRECTORY),
+            gallery_database_url: GALLERY_DATABASE_URL.to_string(),
+            template_directory: PathBuf::from("./static/templates"),
+            result_ttl_hours: DEFAULT_RESULT_TTL_HOURS,
+            cleanup_interval_minutes: 60,
+            rate_limit_per_minute: 30,
+            rate_limit_burst: 10,
+            require_self_test: true,
+            job_threshold: 2_097_152,
+            conversion_workers: thread::available_parallelism().map_or(1, |workers| workers.get()),
+            conversion_queue_limit: 64,
+            locale: DEFAULT_LOCALE.to_string(),
+            shutdown_timeout_seconds: 30,
+            tls_cert_path: None,
+            tls_key_path: None,
+            tls_port: 8443,
+            content_security_policy: DEFAULT_CONTENT_SECURITY_POLICY.to_string(),
+            frame_options: DEFAULT_FRAME_OPTIONS.to_string(),
+            referrer_policy: DEFAULT_REFERRER_POLICY.to_string(),
+            accounts_enabled: false,
+            api_keys_required: false,
+            api_daily_quota: 1000,
+        }
+    }
+}
+
+impl AppConfig {
+    /// Function to read the [AppConfig] from the file set in the [CONFIG_FILE_VARIABLE] environment variable, if any,
+    /// and then from the environment variables of each setting.
+    ///
+    /// Returns an error if the file can't be read or isn't a valid configuration.
+    pub fn from_env() -> Result<AppConfig, ConfigError> {
+        let variable = |name: &str| std::env::var(name).ok();
+        let config = match variable(CONFIG_FILE_VARIABLE) {
+            Some(path) if !path.trim().is_empty() => AppConfig::from_file(path.trim())?,
+            _ => AppConfig::default(),
+        };
+
+        Ok(config.with_variables(variable))
+    }
+
+    /// Function to read the [AppConfig] from a TOML file, where settings that are left out keep their defaults.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<AppConfig, ConfigError> {
+        let path = path.as_ref();
+        let contents = fs::read_to_string(path)
+            .map_err(|error| ConfigError::UnreadableFile(path.to_path_buf(), error))?;
+
+        toml::from_str(&contents)
+            .map_err(|error| ConfigError::InvalidFile(path.to_path_buf(), error.to_string()))
+    }
+
+    /// Function to override the settings with the values `variable` finds for their environment variables.
+    ///
+    /// Values that are blank or can't be used leave the setting as it was.
+    fn with_variables<F: Fn(&str) -> Option<String>>(mut self, variable: F) -> AppConfig {
+        let text = |name: &str| {
+            variable(name)
+                .map(|value| value.trim().to_string())
+                .filter(|value| !value.is_empty())
+        };
+
+        if let Some(bind_address) = text(BIND_ADDRESS_VARIABLE) {
+            self.bind_address = bind_address;
+        }
+        if let Some(port) = text(PORT_VARIABLE).and_then(|port| port.parse().ok()) {
+            self.port = port;
+        }
+        if let Some(payload_limit) = parse_number(text(PAYLOAD_LIMIT_VARIABLE)) {
+            self.payload_limit = payload_limit;
+        }
+        if let Some(upload_limit) = parse_number(text(UPLOAD_LIMIT_VARIABLE)) {
+            self.upload_limit = upload_limit;
+        }
+        if let Some(results_directory) = text(RESULTS_DIRECTORY_VARIABLE) {
+            self.results_directory = Some(PathBuf::from(results_directory));
+        }
+        if let Some(art_directory) = text(ART_DIRECTORY_VARIABLE) {
+            self.art_directory = PathBuf::from(art_directory);
+        }
+        if let Some(gallery_database_url) = text(GALLERY_DATABASE_URL_VARIABLE) {
+            self.gallery_database_url = gallery_database_url;
+        }
+        if let Some(template_directory) = text(TEMPLATE_DIRECTORY_VARIABLE) {
+            self.template_directory = PathBuf::from(template_directory);
+        }
+        if let Some(result_ttl_hours) =
+            text(RESULT_TTL_VARIABLE).and_then(|hours| hours.parse().ok())
+        {
+            self.result_ttl_hours = result_ttl_hours;
+        }
+        if let Some(cleanup_interval_minutes) = parse_number(text(CLEANUP_INTERVAL_VARIABLE)) {
+            self.cleanup_interval_minutes = cleanup_interval_minutes;
+        }
+        if let Some(rate_limit_per_minute) =
+            text(RATE_LIMIT_PER_MINUTE_VARIABLE).and_then(|limit| limit.parse().ok())
+        {
+            self.rate_limit_per_minute = rate_limit_per_minute;
+        }
+        if let Some(rate_limit_burst) = parse_number(text(RATE_LIMIT_BURST_VARIABLE)) {
+            self.rate_limit_burst = rate_limit_burst;
+        }
+        if let Some(require_self_test) =
+            text(REQUIRE_SELF_TEST_VARIABLE).and_then(|require| require.parse().ok())
+        {
+            self.require_self_test = require_self_test;
+        }
+        if let Some(job_threshold) =
+            text(JOB_THRESHOLD_VARIABLE).and_then(|threshold| threshold.parse().ok())
+        {
+            self.job_threshold = job_threshold;
+        }
+        if let Some(conversion_workers) = parse_number(text(CONVERSION_WORKERS_VARIABLE)) {
+            self.conversion_workers = conversion_workers;
+        }
+        if let Some(conversion_queue_limit) = parse_number(text(CONVERSION_QUEUE_LIMIT_VARIABLE)) {
+            self.conversion_queue_limit = conversion_queue_limit;
+        }
+        if let Some(locale) = text(LOCALE_VARIABLE) {
+            self.locale = locale;
+        }
+        if let Some(shutdown_timeout_seconds) = parse_number(text(SHUTDOWN_TIMEOUT_VARIABLE)) {
+            self.shutdown_timeout_seconds = shutdown_timeout_seconds;
+        }
+        if let Some(tls_cert_path) = text(TLS_CERT_PATH_VARIABLE) {
+            self.tls_cert_path = Some(PathBuf::from(tls_cert_path));
+        }
+        if let Some(tls_key_path) = text(TLS_KEY_PATH_VARIABLE) {
+            self.tls_key_path = Some(PathBuf::from(tls_key_path));
+        }
+        if let Some(tls_port) = text(TLS_PORT_VARIABLE).and_then(|port| port.parse().ok()) {
+            self.tls_port = tls_port;
+        }
+        if let Some(content_security_policy) = text(CONTENT_SECURITY_POLICY_VARIABLE) {
+            self.content_security_policy = content_security_policy;
+        }
+        if let Some(frame_options) = text(FRAME_OPTIONS_VARIABLE) {
+            self.frame_options = frame_options;
+        }
+        if let Some(referrer_policy) = text(REFERRER_POLICY_VARIABLE) {
+            self.referrer_policy = referrer_policy;
+        }
+        if let Some(accounts_enabled) =
+            text(ACCOUNTS_ENABLED_VARIABLE).and_then(|enabled| enabled.parse().ok())
+        {
+            self.accounts_enabled = accounts_enabled;
+        }
+        if let Some(api_keys_required) =
+            text(API_KEYS_REQUIRED_VARIABLE).and_then(|required| required.parse().ok())
+        {
+            self.api_keys_required = api_keys_required;
+        }
+        if let Some(api_daily_quota) = parse_number(text(API_DAILY_QUOTA_VARIABLE)) {
+            self.api_daily_quota = api_daily_quota;
+        }
+
+        self
+    }
+
+    /// Function to get how long results are kept for, or [None] when they are kept forever.
+    pub fn result_ttl(&self) -> Option<Duration> {
+        match self.result_ttl_hours {
+            0 => None,
+            hours => Some(Duration::from_secs(hours.saturating_mul(60 * 60))),
+        }
+    }
+
+    /// Function to get how often old results are removed, which is at least every minute.
+    pub fn cleanup_interval(&self) -> Duration {
+        Duration::from_secs(self.cleanup_interval_minutes.max(1).saturating_mul(60))
+    }
+
+    /// Function to get how long requests and background jobs are given to finish once the server is asked to stop.
+    pub fn shutdown_timeout(&self) -> Duration {
+        Duration::from_secs(self.shutdown_timeout_seconds)
+    }
+
+    /// Function to check whether the server serves HTTPS, which it does once either TLS path is set.
+    pub fn tls_enabled(&self) -> bool {
+        self.tls_cert_path.is_some() || self.tls_key_path.is_some()
+    }
+
+    /// Function to get how numbers are written in the [AppConfig::locale] of the deployment.
+    pub fn number_format(&self) -> NumberFormat {
+        NumberFormat::for_locale(&self.locale)
+    }
+}
+
+/// Function to parse a setting that must be a number above 0, such as a size limit.
+fn parse_number<T: FromStr + PartialOrd + Default>(value: Option<String>) -> Option<T> {
+    value
+        .and_then(|value| value.parse::<T>().ok())
+        .filter(|number| *number > T::default())
+}
+
+/// Enum to store the errors that can occur while reading the configuration file.
+///
+/// The different enum variants are used to identify the specific cause of an error.
+#[derive(Debug)]
+pub enum ConfigError {
+    /// [ConfigError::UnreadableFile] error is caused when the configuration file doesn't exist or can't be read.
+    UnreadableFile(PathBuf, io::Error),
+    /// [ConfigError::InvalidFile] error is caused when the configuration file isn't TOML or has a setting that can't be used.
+    InvalidFile(PathBuf, String),
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::UnreadableFile(path, error) => write!(
+                f,
+                "The configuration file {} can't be read: {}",
+                path.display(),
+                error
+            ),
+            ConfigError::InvalidFile(path, error) => write!(
+                f,
+                "The configuration file {} is not valid: {}",
+                path.display(),
+                error
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+// Tests
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    // Verifies that settings left out of the configuration file keep their defaults, and that unknown settings are rejected
+    #[test]
+    fn test_from_file() {
+        let root = tempfile::tempdir().unwrap();
+        let path = root.path().join("config.toml");
+        fs::write(
+            &path,
+            "bind_address = \"0.0.0.0\"\nport = 80\nresults_directory = \"/var/lib/ascii-art\"\nresult_ttl_hours = 0\n",
+        )
+        .unwrap();
+
+        let config = AppConfig::from_file(&path).unwrap();
+
+        assert_eq!(
+            config,
+            AppConfig {
+                bind_address: "0.0.0.0".to_string(),
+                port: 80,
+                results_directory: Some(PathBuf::from("/var/lib/ascii-art")),
+                result_ttl_hours: 0,
+                ..AppConfig::default()
+            }
+        );
+        assert_eq!(config.result_ttl(), None);
+
+        fs::write(&path, "prot = 80\n").unwrap();
+
+        assert!(matches!(
+            AppConfig::from_file(&path),
+            Err(ConfigError::InvalidFile(..))
+        ));
+        assert!(matches!(
+            AppConfig::from_file(root.path().join("missing.toml")),
+            Err(ConfigError::UnreadableFile(..))
+        ));
+    }
+
+    // Verifies that environment variables override the settings, and that values that can't be used are ignored
+    #[test]
+    fn test_with_variables() {
+        let variables = HashMap::from([
+            (PORT_VARIABLE, "3000"),
+            (PAYLOAD_LIMIT_VARIABLE, " 2048 "),
+            (UPLOAD_LIMIT_VARIABLE, "0"),
+            (TEMPLATE_DIRECTORY_VARIABLE, "/srv/templates"),
+            (RESULT_TTL_VARIABLE, "soon"),
+            (CLEANUP_INTERVAL_VARIABLE, "5"),
+            (RESULTS_DIRECTORY_VARIABLE, " "),
+            (RATE_LIMIT_PER_MINUTE_VARIABLE, "0"),
+            (RATE_LIMIT_BURST_VARIABLE, "0"),
+            (REQUIRE_SELF_TEST_VARIABLE, "false"),
+            (JOB_THRESHOLD_VARIABLE, "0"),
+            (CONVERSION_WORKERS_VARIABLE, "2"),
+            (CONVERSION_QUEUE_LIMIT_VARIABLE, "-1"),
+            (LOCALE_VARIABLE, "de-DE"),
+            (SHUTDOWN_TIMEOUT_VARIABLE, "10"),
+            (TLS_CERT_PATH_VARIABLE, "/etc/ascii-art/cert.pem"),
+            (TLS_KEY_PATH_VARIABLE, " "),
+            (TLS_PORT_VARIABLE, "443"),
+            (FRAME_OPTIONS_VARIABLE, "SAMEORIGIN"),
+            (REFERRER_POLICY_VARIABLE, ""),
+            (ACCOUNTS_ENABLED_VARIABLE, "true"),
+            (API_KEYS_REQUIRED_VARIABLE, "yes"),
+            (API_DAILY_QUOTA_VARIABLE, "250"),
+        ]);
+
+        let config = AppConfig::default()
+            .with_variables(|name| variables.get(name).map(|value| value.to_string()));
+
+        assert_eq!(
+            config,
+            AppConfig {
+                port: 3000,
+                payload_limit: 2048,
+                template_directory: PathBuf::from("/srv/templates"),
+                cleanup_interval_minutes: 5,
+                rate_limit_per_minute: 0,
+                require_self_test: false,
+                job_threshold: 0,
+                conversion_workers: 2,
+                locale: "de-DE".to_string(),
+                shutdown_timeout_seconds: 10,
+                tls_cert_path: Some(PathBuf::from("/etc/ascii-art/cert.pem")),
+                tls_port: 443,
+                frame_options: "SAMEORIGIN".to_string(),
+                accounts_enabled: true,
+                api_daily_quota: 250,
+                ..AppConfig::default()
+            }
+        );
+        assert_eq!(
+            config.result_ttl(),
+            Some(Duration::from_secs(DEFAULT_RESULT_TTL_HOURS * 60 * 60))
+        );
+        assert_eq!(config.cleanup_interval(), Duration::from_secs(5 * 60));
+        assert_eq!(config.shutdown_timeout(), Duration::from_secs(10));
+        assert!(config.tls_enabled());
+        assert!(!AppConfig::default().tls_enabled());
+        assert_eq!(config.number_format(), NumberFormat::for_locale("de"));
+    }
+}
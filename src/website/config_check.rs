@@ -0,0 +1,383 @@
+//! Module to check the settings of a deployment of the website without starting the server.
+//!
+//! Running the server with the [CHECK_CONFIG_FLAG] reads the [AppConfig] the same way the server does, and then
+//! [check_config] tries each setting on the machine it runs on: the address and port are listened on, the directories are
+//! written to, the TLS certificate and key are loaded when HTTPS is turned on, the gallery database is opened, the self-test is run with the templates and static files, and the
+//! environment variables of the operator token and the conversion defaults are read. Every check is run even after one
+//! fails, and the [ConfigReport] lists all of them, so that a bad deploy is found at once instead of by the first request.
+//!
+//! Robert Peterson and Kelsey Werner 2023
+
+use super::{
+    config::{AppConfig, ConfigError},
+    conversion_defaults::ConversionDefaults,
+    gallery::{Gallery, GallerySearch},
+    operator_auth::{OperatorToken, OPERATOR_TOKEN_VARIABLE},
+    self_test::run_self_test,
+    tls::load_tls_config,
+};
+use std::{fmt, fs, net::TcpListener, path::Path};
+use tempfile::NamedTempFile;
+
+/// The command-line flag that checks the configuration and exits instead of starting the server.
+pub const CHECK_CONFIG_FLAG: &str = "--check-config";
+
+/// Enum to store the outcome of a single check of the configuration.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum CheckStatus {
+    /// [CheckStatus::Passed] is used when the setting works.
+    Passed,
+    /// [CheckStatus::Warning] is used when the server can start with the setting, but probably not the way the operator meant.
+    Warning,
+    /// [CheckStatus::Failed] is used when the server would not start or would fail requests with the setting.
+    Failed,
+}
+
+/// Struct to store the outcome of a single check of the configuration.
+#[derive(PartialEq, Debug)]
+pub struct ConfigCheck {
+    /// The name of the setting that was checked.
+    pub setting: &'static str,
+    /// Whether the check passed.
+    pub status: CheckStatus,
+    /// A description of what was found, which explains how to fix the setting when the check didn't pass.
+    pub message: String,
+}
+
+impl ConfigCheck {
+    /// Function to create the [ConfigCheck] of a setting that works.
+    fn passed(setting: &'static str, message: String) -> ConfigCheck {
+        ConfigCheck {
+            setting,
+            status: CheckStatus::Passed,
+            message,
+        }
+    }
+
+    /// Function to create the [ConfigCheck] of a setting that works, but probably not the way the operator meant.
+    fn warning(setting: &'static str, message: String) -> ConfigCheck {
+        ConfigCheck {
+            setting,
+            status: CheckStatus::Warning,
+            message,
+        }
+    }
+
+    /// Function to create the [ConfigCheck] of a setting that doesn't work.
+    fn failed(setting: &'static str, message: String) -> ConfigCheck {
+        ConfigCheck {
+            setting,
+            status: CheckStatus::Failed,
+            message,
+        }
+    }
+}
+
+/// Struct to store the outcome of every check of the configuration.
+#[derive(PartialEq, Debug, Default)]
+pub struct ConfigReport {
+    /// The checks in the order they were run.
+    pub checks: Vec<ConfigCheck>,
+}
+
+impl ConfigReport {
+    /// Function to check if the server can start with the configuration, which it can when no check failed.
+    pub fn passed(&self) -> bool {
+        self.checks
+            .iter()
+            .all(|check| check.status != CheckStatus::Failed)
+    }
+
+    /// Function to count the checks with a [CheckStatus].
+    fn count(&self, status: CheckStatus) -> usize {
+        self.checks
+            .iter()
+            .filter(|check| check.status == status)
+            .count()
+    }
+}
+
+impl fmt::Display for ConfigReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for check in &self.checks {
+            let status = match check.status {
+                CheckStatus::Passed => "ok",
+                CheckStatus::Warning => "warning",
+                CheckStatus::Failed => "FAILED",
+            };
+            writeln!(f, "{:<8}{}: {}", status, check.setting, check.message)?;
+        }
+
+        writeln!(
+            f,
+            "\n{} passed, {} with warnings, {} failed.",
+            self.count(CheckStatus::Passed),
+            self.count(CheckStatus::Warning),
+            self.count(CheckStatus::Failed)
+        )
+    }
+}
+
+/// Function to check the configuration read by [AppConfig::from_env], using the templates of the configuration and the
+/// files in `static_directory` for the self-test, where `variable` finds the value of an environment variable.
+///
+/// A configuration that can't be read fails the only check, since none of its settings are known.
+pub async fn check_config<F: Fn(&str) -> Option<String>>(
+    config: Result<AppConfig, ConfigError>,
+    static_directory: &Path,
+    variable: F,
+) -> ConfigReport {
+    let config = match config {
+        Ok(config) => config,
+        Err(error) => {
+            return ConfigReport {
+                checks: vec![ConfigCheck::failed("configuration", error.to_string())],
+            }
+        }
+    };
+
+    let mut checks = vec![
+        ConfigCheck::passed("configuration", "the settings were read".to_string()),
+        check_address(&config),
+    ];
+
+    checks.push(match &config.results_directory {
+        Some(directory) => check_directory("results_directory", directory),
+        None => ConfigCheck::warning(
+            "results_directory",
+            "no directory is set, so the results of conversions are kept in memory and lost when the server restarts"
+                .to_string(),
+        ),
+    });
+    if config.tls_enabled() {
+        checks.push(match load_tls_config(&config) {
+            Ok(_) => ConfigCheck::passed(
+                "tls_cert_path",
+                format!("HTTPS can be served on port {}", config.tls_port),
+            ),
+            Err(error) => ConfigCheck::failed("tls_cert_path", error.to_string()),
+        });
+    }
+    checks.push(check_directory("art_directory", &config.art_directory));
+    checks.push(check_gallery(&config.gallery_database_url).await);
+
+    checks.push(
+        match run_self_test(&config.template_directory, static_directory) {
+            Ok(()) => ConfigCheck::passed(
+                "template_directory",
+                format!(
+                    "the self-test passed with the templates in {}",
+                    config.template_directory.display()
+                ),
+            ),
+            Err(errors) => ConfigCheck::failed(
+                "template_directory",
+                format!(
+                    "the self-test failed: {}",
+                    errors
+                        .iter()
+                        .map(|error| error.to_string())
+                        .collect::<Vec<_>>()
+                        .join("; ")
+                ),
+            ),
+        },
+    );
+
+    if config.job_threshold > config.upload_limit {
+        checks.push(ConfigCheck::warning(
+            "job_threshold",
+            format!(
+                "{} bytes is larger than the upload_limit of {} bytes, so no upload is converted as a background job",
+                config.job_threshold, config.upload_limit
+            ),
+        ));
+    }
+
+    checks.push(
+        if OperatorToken::new(variable(OPERATOR_TOKEN_VARIABLE)).is_enabled() {
+            ConfigCheck::passed(
+                "operator token",
+                format!(
+                    "{} is set, so the operator endpoints are turned on",
+                    OPERATOR_TOKEN_VARIABLE
+                ),
+            )
+        } else {
+            ConfigCheck::warning(
+                "operator token",
+                format!(
+                    "{} is not set, so the operator endpoints are turned off",
+                    OPERATOR_TOKEN_VARIABLE
+                ),
+            )
+        },
+    );
+
+    let ignored = ConversionDefaults::ignored_variables(&variable);
+    checks.push(if ignored.is_empty() {
+        ConfigCheck::passed("conversion defaults", "every value can be used".to_string())
+    } else {
+        ConfigCheck::warning(
+            "conversion defaults",
+            format!(
+                "the values of {} can't be used and are ignored",
+                ignored.join(", ")
+            ),
+        )
+    });
+
+    ConfigReport { checks }
+}
+
+/// Function to check that the server can listen on its address and port, which are given back before the check returns.
+fn check_address(config: &AppConfig) -> ConfigCheck {
+    let address = format!("{}:{}", config.bind_address, config.port);
+    match TcpListener::bind((config.bind_address.as_str(), config.port)) {
+        Ok(_) => ConfigCheck::passed("bind_address", format!("{} can be listened on", address)),
+        Err(error) => ConfigCheck::failed(
+            "bind_address",
+            format!("{} can't be listened on: {}", address, error),
+        ),
+    }
+}
+
+/// Function to check that a directory exists, or can be created, and that files can be written in it.
+fn check_directory(setting: &'static str, directory: &Path) -> ConfigCheck {
+    // the file that is written is removed when it is dropped
+    let writable = fs::create_dir_all(directory).and_then(|_| NamedTempFile::new_in(directory));
+    match writable {
+        Ok(_) => ConfigCheck::passed(
+            setting,
+            format!("{} can be written to", directory.display()),
+        ),
+        Err(error) => ConfigCheck::failed(
+            setting,
+            format!("{} can't be written to: {}", directory.display(), error),
+        ),
+    }
+}
+
+/// Function to check that the gallery database can be opened and searched, which creates it if it doesn't exist yet.
+async fn check_gallery(url: &str) -> ConfigCheck {
+    let searched = match Gallery::new(url) {
+        Ok(gallery) => gallery
+            .search(&GallerySearch::default(), 1)
+            .await
+            .map(|_| ()),
+        Err(error) => Err(error),
+    };
+
+    match searched {
+        Ok(()) => ConfigCheck::passed("gallery_database_url", format!("{} can be opened", url)),
+        Err(error) => ConfigCheck::failed(
+            "gallery_database_url",
+            format!("{} can't be opened: {}", url, error),
+        ),
+    }
+}
+
+// Tests
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::website::self_test::STATIC_DIRECTORY;
+
+    // Creates a configuration whose directories are in `root`, which listens on any free port
+    fn test_config(root: &Path) -> AppConfig {
+        AppConfig {
+            port: 0,
+            results_directory: Some(root.join("results")),
+            art_directory: root.join("art"),
+            gallery_database_url: "sqlite::memory:".to_string(),
+            ..Default::default()
+        }
+    }
+
+    // Verifies that a configuration that works passes, with warnings for settings that are probably not meant
+    #[actix_web::test]
+    async fn test_check_config() {
+        let root = tempfile::tempdir().unwrap();
+
+        let report = check_config(
+            Ok(test_config(root.path())),
+            Path::new(STATIC_DIRECTORY),
+            |_| None,
+        )
+        .await;
+
+        assert!(report.passed(), "{}", report);
+        assert!(root.path().join("results").is_dir());
+        assert_eq!(
+            report
+                .checks
+                .iter()
+                .filter(|check| check.status == CheckStatus::Warning)
+                .map(|check| check.setting)
+                .collect::<Vec<_>>(),
+            ["operator token"]
+        );
+
+        let report = check_config(
+            Ok(test_config(root.path())),
+            Path::new(STATIC_DIRECTORY),
+            |name| (name == OPERATOR_TOKEN_VARIABLE).then(|| "secret".to_string()),
+        )
+        .await;
+        assert!(report
+            .checks
+            .iter()
+            .all(|check| check.status == CheckStatus::Passed));
+        assert!(report
+            .to_string()
+            .ends_with("8 passed, 0 with warnings, 0 failed.\n"));
+    }
+
+    // Verifies that every setting that doesn't work is reported, and that a configuration that can't be read fails
+    #[actix_web::test]
+    async fn test_check_config_failures() {
+        let root = tempfile::tempdir().unwrap();
+        let file = root.path().join("file");
+        fs::write(&file, "not a directory").unwrap();
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+
+        let config = AppConfig {
+            port: listener.local_addr().unwrap().port(),
+            results_directory: Some(file.clone()),
+            art_directory: file.join("art"),
+            gallery_database_url: "postgres://localhost/gallery".to_string(),
+            template_directory: root.path().join("missing"),
+            tls_key_path: Some(root.path().join("key.pem")),
+            ..test_config(root.path())
+        };
+        let report = check_config(Ok(config), Path::new(STATIC_DIRECTORY), |_| None).await;
+
+        assert!(!report.passed());
+        assert_eq!(
+            report
+                .checks
+                .iter()
+                .filter(|check| check.status == CheckStatus::Failed)
+                .map(|check| check.setting)
+                .collect::<Vec<_>>(),
+            [
+                "bind_address",
+                "results_directory",
+                "tls_cert_path",
+                "art_directory",
+                "gallery_database_url",
+                "template_directory"
+            ]
+        );
+
+        let error = AppConfig::from_file(root.path().join("missing.toml")).unwrap_err();
+        let report = check_config(Err(error), Path::new(STATIC_DIRECTORY), |_| None).await;
+
+        assert!(!report.passed());
+        assert_eq!(report.checks.len(), 1);
+        assert!(report
+            .to_string()
+            .starts_with("FAILED  configuration: The configuration file"));
+    }
+}
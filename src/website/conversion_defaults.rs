@@ -0,0 +1,149 @@
+//! Module to store the conversion settings a deployment of the website uses when a request doesn't pick them.
+//!
+//! Operators can set the default ASCII art width, character set and theme in environment variables:
+//! `ASCII_ART_DEFAULT_WIDTH` must be one of the widths on the image to ASCII form, `ASCII_ART_DEFAULT_CHARSET` must be
+//! "ascii" or "braille", and `ASCII_ART_DEFAULT_THEME` must be "glyphs" to draw ASCII art with a font or "pixels" to draw
+//! each character as a gray pixel. The defaults are selected on the forms, and are used by the API endpoints when a
+//! request leaves a setting out. Values that can't be used are ignored.
+//!
+//! Robert Peterson and Kelsey Werner 2023
+
+use super::image_form_params::SUPPORTED_ASCII_WIDTHS;
+use ascii_art_converter::converter::options::{ImageRenderMode, RenderCharset};
+
+/// The environment variable the default ASCII art width is read from.
+pub const DEFAULT_WIDTH_VARIABLE: &str = "ASCII_ART_DEFAULT_WIDTH";
+
+/// The environment variable the default character set is read from.
+pub const DEFAULT_CHARSET_VARIABLE: &str = "ASCII_ART_DEFAULT_CHARSET";
+
+/// The environment variable the default theme of images made from ASCII art is read from.
+pub const DEFAULT_THEME_VARIABLE: &str = "ASCII_ART_DEFAULT_THEME";
+
+/// Struct to store the conversion settings used when a request doesn't pick them.
+#[derive(Clone, Copy, Default, PartialEq, Debug)]
+pub struct ConversionDefaults {
+    /// [Option] stores one of the [SUPPORTED_ASCII_WIDTHS] or [None] to let the converter pick a reasonable width.
+    pub width: Option<u32>,
+    /// The character set ASCII art is made with.
+    pub charset: RenderCharset,
+    /// The way ASCII art is drawn when it is converted into an image.
+    pub render_mode: ImageRenderMode,
+}
+
+impl ConversionDefaults {
+    /// Function to read the [ConversionDefaults] from the [DEFAULT_WIDTH_VARIABLE], [DEFAULT_CHARSET_VARIABLE] and
+    /// [DEFAULT_THEME_VARIABLE] environment variables.
+    pub fn from_env() -> ConversionDefaults {
+        let variable = |name| std::env::var(name).ok();
+        ConversionDefaults::parse(
+            variable(DEFAULT_WIDTH_VARIABLE).as_deref(),
+            variable(DEFAULT_CHARSET_VARIABLE).as_deref(),
+            variable(DEFAULT_THEME_VARIABLE).as_deref(),
+        )
+    }
+
+    /// Function to find the environment variables whose values can't be used and are ignored, where `variable` finds
+    /// the value of an environment variable.
+    pub fn ignored_variables<F: Fn(&str) -> Option<String>>(variable: F) -> Vec<&'static str> {
+        let width = variable(DEFAULT_WIDTH_VARIABLE);
+        let charset = variable(DEFAULT_CHARSET_VARIABLE);
+        let theme = variable(DEFAULT_THEME_VARIABLE);
+        let defaults =
+            ConversionDefaults::parse(width.as_deref(), charset.as_deref(), theme.as_deref());
+
+        let mut ignored = Vec::new();
+        if width.is_some() && defaults.width.is_none() {
+            ignored.push(DEFAULT_WIDTH_VARIABLE);
+        }
+        if charset.is_some_and(|charset| !matches!(charset.trim(), "ascii" | "braille")) {
+            ignored.push(DEFAULT_CHARSET_VARIABLE);
+        }
+        if theme.is_some_and(|theme| !matches!(theme.trim(), "glyphs" | "pixels")) {
+            ignored.push(DEFAULT_THEME_VARIABLE);
+        }
+        ignored
+    }
+
+    /// Function to create the [ConversionDefaults] from the values of the environment variables.
+    ///
+    /// A value that is missing or can't be used leaves the built-in default of that setting in place.
+    fn parse(
+        width: Option<&str>,
+        charset: Option<&str>,
+        theme: Option<&str>,
+    ) -> ConversionDefaults {
+        let width = width
+            .and_then(|width| width.trim().parse::<u32>().ok())
+            .filter(|width| SUPPORTED_ASCII_WIDTHS.contains(width));
+        let charset = match charset.map(str::trim) {
+            Some("braille") => RenderCharset::Braille,
+            _ => RenderCharset::Ascii,
+        };
+        let render_mode = match theme.map(str::trim) {
+            Some("pixels") => ImageRenderMode::Pixels,
+            _ => ImageRenderMode::Glyphs,
+        };
+
+        ConversionDefaults {
+            width,
+            charset,
+            render_mode,
+        }
+    }
+}
+
+// Tests
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Verifies that the defaults are read from the values of the environment variables, and that values that can't be used are ignored
+    #[test]
+    fn test_parse() {
+        assert_eq!(
+            ConversionDefaults::parse(Some("80"), Some("braille"), Some("pixels")),
+            ConversionDefaults {
+                width: Some(80),
+                charset: RenderCharset::Braille,
+                render_mode: ImageRenderMode::Pixels,
+            }
+        );
+        assert_eq!(
+            ConversionDefaults::parse(Some("81"), Some("emoji"), Some("neon")),
+            ConversionDefaults::default()
+        );
+        assert_eq!(
+            ConversionDefaults::parse(None, None, None),
+            ConversionDefaults::default()
+        );
+    }
+
+    // Verifies that the environment variables with values that can't be used are found
+    #[test]
+    fn test_ignored_variables() {
+        let variables = |width: &str, charset: &str, theme: &str| {
+            let values = [
+                (DEFAULT_WIDTH_VARIABLE, width.to_string()),
+                (DEFAULT_CHARSET_VARIABLE, charset.to_string()),
+                (DEFAULT_THEME_VARIABLE, theme.to_string()),
+            ];
+            move |name: &str| {
+                values
+                    .iter()
+                    .find(|(variable, _)| *variable == name)
+                    .map(|(_, value)| value.clone())
+            }
+        };
+
+        assert!(
+            ConversionDefaults::ignored_variables(variables("80", "ascii", "glyphs")).is_empty()
+        );
+        assert_eq!(
+            ConversionDefaults::ignored_variables(variables("81", "emoji", "pixels")),
+            [DEFAULT_WIDTH_VARIABLE, DEFAULT_CHARSET_VARIABLE]
+        );
+        assert!(ConversionDefaults::ignored_variables(|_| None).is_empty());
+    }
+}
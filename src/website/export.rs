@@ -0,0 +1,282 @@
+//! Module to export every stored result as a tar.gz archive for backups and migrations.
+//!
+//! The archive holds the results of conversions under `results/`, the published ASCII art under `art/`, and a
+//! `metadata.json` file at the end that lists the SHA-256 hash of every file along with the conversions of the gallery.
+//! The archive is written on a blocking thread while it is sent, so the export of a large store is never held in memory.
+//!
+//! Robert Peterson and Kelsey Werner 2023
+
+use super::{
+    art_store::{content_hash, ArtStore},
+    gallery::GalleryEntry,
+    result_store::ResultStore,
+};
+use actix_web::{
+    body::{BodySize, MessageBody},
+    rt::task::spawn_blocking,
+    web::Bytes,
+};
+use flate2::{write::GzEncoder, Compression};
+use serde::{Deserialize, Serialize};
+use std::{
+    io::{self, BufWriter, Write},
+    pin::Pin,
+    task::{Context, Poll},
+    time::SystemTime,
+};
+use tar::{Builder, Header};
+use tokio::sync::mpsc::{channel, Receiver, Sender};
+
+/// The version of the layout of the archive, which is written to `metadata.json`.
+pub const EXPORT_VERSION: u32 = 1;
+
+/// The name of the file in the archive that describes the rest of the archive.
+pub const METADATA_NAME: &str = "metadata.json";
+
+/// The folder of the archive that the results of conversions are stored in.
+pub const RESULTS_FOLDER: &str = "results";
+
+/// The folder of the archive that published ASCII art is stored in.
+pub const ART_FOLDER: &str = "art";
+
+/// The size of the chunks the archive is sent in, in bytes.
+const CHUNK_SIZE: usize = 65_536;
+
+/// The number of chunks that can wait to be sent before writing the archive is paused.
+const CHUNKS_IN_FLIGHT: usize = 4;
+
+/// Struct to store a file of the archive in `metadata.json`.
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+pub struct ExportedFile {
+    /// The path of the file in the archive, such as "results/{uuid}.png".
+    pub path: String,
+    /// The SHA-256 hash of the contents of the file.
+    pub sha256: String,
+}
+
+/// Struct to store the contents of `metadata.json`.
+#[derive(Serialize, Deserialize)]
+pub struct ExportMetadata {
+    /// The [EXPORT_VERSION] the archive was written with.
+    pub version: u32,
+    /// Every file of the archive other than `metadata.json`.
+    pub files: Vec<ExportedFile>,
+    /// Every conversion of the gallery, oldest first.
+    pub gallery: Vec<GalleryEntry>,
+}
+
+/// Function to add a file with the given contents to a tar archive.
+fn append_file<W: Write>(archive: &mut Builder<W>, path: &str, data: &[u8]) -> io::Result<()> {
+    let modified = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map_or(0, |since_epoch| since_epoch.as_secs());
+
+    let mut header = Header::new_gnu();
+    header.set_size(data.len() as u64);
+    header.set_mode(0o644);
+    header.set_mtime(modified);
+    archive.append_data(&mut header, path, data)
+}
+
+/// Function to write every stored result, the published ASCII art and the conversions of the gallery as a tar.gz archive.
+///
+/// Results and art that are removed while the archive is written are left out. Returns the writer once the whole archive is written.
+pub fn write_export<W: Write>(
+    results: &dyn ResultStore,
+    art_store: &ArtStore,
+    gallery: Vec<GalleryEntry>,
+    writer: W,
+) -> io::Result<W> {
+    let mut archive = Builder::new(GzEncoder::new(writer, Compression::default()));
+    let mut files = Vec::new();
+
+    for name in results.names()? {
+        if let Some(data) = results.read(&name)? {
+            let path = format!("{}/{}", RESULTS_FOLDER, name);
+            append_file(&mut archive, &path, &data)?;
+            files.push(ExportedFile {
+                path,
+                sha256: content_hash(&data),
+            });
+        }
+    }
+
+    for hash in art_store.hashes()? {
+        if let Some(ascii) = art_store.get(&hash)? {
+            let path = format!("{}/{}.txt", ART_FOLDER, hash);
+            append_file(&mut archive, &path, ascii.as_bytes())?;
+            files.push(ExportedFile { path, sha256: hash });
+        }
+    }
+
+    let metadata = ExportMetadata {
+        version: EXPORT_VERSION,
+        files,
+        gallery,
+    };
+    let metadata = serde_json::to_vec_pretty(&metadata)?;
+    append_file(&mut archive, METADATA_NAME, &metadata)?;
+
+    archive.into_inner()?.finish()
+}
+
+/// Struct to send the chunks written to it to an [ExportStream].
+pub struct ChunkWriter {
+    /// The sending half of the channel the [ExportStream] reads from.
+    sender: Sender<io::Result<Bytes>>,
+}
+
+impl Write for ChunkWriter {
+    /// Function to send a chunk, waiting while [CHUNKS_IN_FLIGHT] chunks have yet to be sent.
+    ///
+    /// Returns an error with [io::ErrorKind::BrokenPipe] once the client is gone, which stops the export.
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.sender
+            .blocking_send(Ok(Bytes::copy_from_slice(buf)))
+            .map_err(|_| io::Error::new(io::ErrorKind::BrokenPipe, "the export was cancelled"))?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Struct to stream an archive that is written on a blocking thread as the body of a response.
+pub struct ExportStream {
+    /// The receiving half of the channel the chunks of the archive are sent through.
+    receiver: Receiver<io::Result<Bytes>>,
+}
+
+impl ExportStream {
+    /// Function to create a new [ExportStream] that sends everything that `write` writes.
+    ///
+    /// `write` is run on a blocking thread and is given a [ChunkWriter] that is buffered into chunks of [CHUNK_SIZE] bytes.
+    /// If `write` returns an error, the error ends the stream.
+    pub fn new<F>(write: F) -> ExportStream
+    where
+        F: FnOnce(&mut BufWriter<ChunkWriter>) -> io::Result<()> + Send + 'static,
+    {
+        let (sender, receiver) = channel(CHUNKS_IN_FLIGHT);
+
+        spawn_blocking(move || {
+            let mut writer = BufWriter::with_capacity(
+                CHUNK_SIZE,
+                ChunkWriter {
+                    sender: sender.clone(),
+                },
+            );
+            if let Err(e) = write(&mut writer).and_then(|_| writer.flush()) {
+                let _ = sender.blocking_send(Err(e));
+            }
+        });
+
+        ExportStream { receiver }
+    }
+}
+
+impl MessageBody for ExportStream {
+    type Error = io::Error;
+
+    /// The size of the stream is not known ahead of time.
+    fn size(&self) -> BodySize {
+        BodySize::Stream
+    }
+
+    /// Function to send the next chunk of the archive once it is written.
+    fn poll_next(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Bytes, Self::Error>>> {
+        self.get_mut().receiver.poll_recv(cx)
+    }
+}
+
+// Tests
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::website::{gallery::ConversionKind, result_store::DirectoryResultStore};
+    use actix_web::body::to_bytes;
+    use flate2::read::GzDecoder;
+    use std::io::Read;
+    use tar::Archive;
+
+    // Reads every file of a tar.gz archive in order
+    fn read_archive(data: &[u8]) -> Vec<(String, Vec<u8>)> {
+        let mut archive = Archive::new(GzDecoder::new(data));
+        archive
+            .entries()
+            .unwrap()
+            .map(|entry| {
+                let mut entry = entry.unwrap();
+                let path = entry.path().unwrap().to_string_lossy().to_string();
+                let mut data = Vec::new();
+                entry.read_to_end(&mut data).unwrap();
+                (path, data)
+            })
+            .collect()
+    }
+
+    // Verifies that write_export() writes every result, every published ASCII art and the metadata to the archive
+    #[test]
+    fn test_write_export() {
+        let root = tempfile::tempdir().unwrap();
+        let results = DirectoryResultStore::new(root.path().join("results")).unwrap();
+        let art_store = ArtStore::new(root.path().join("art")).unwrap();
+        std::fs::write(root.path().join("results/castle.png"), "png").unwrap();
+        let (hash, _) = art_store.publish("$$\n").unwrap();
+        let gallery = vec![GalleryEntry {
+            id: 1,
+            kind: ConversionKind::AsciiToImage,
+            input_hash: content_hash("$$\n"),
+            output_path: "conversion_results/castle.png".to_string(),
+            title: None,
+            created_at: "2023-06-09 04:58:50".to_string(),
+            author: None,
+            license: None,
+            charset: None,
+            hidden: false,
+        }];
+
+        let archive = write_export(&results, &art_store, gallery, Vec::new()).unwrap();
+        let files = read_archive(&archive);
+
+        assert_eq!(
+            files[0],
+            ("results/castle.png".to_string(), b"png".to_vec())
+        );
+        assert_eq!(files[1], (format!("art/{}.txt", hash), b"$$\n".to_vec()));
+        assert_eq!(files[2].0, METADATA_NAME);
+
+        let metadata: serde_json::Value = serde_json::from_slice(&files[2].1).unwrap();
+
+        assert_eq!(metadata["version"], EXPORT_VERSION);
+        assert_eq!(
+            metadata["files"],
+            serde_json::json!([
+                { "path": "results/castle.png", "sha256": content_hash("png") },
+                { "path": format!("art/{}.txt", hash), "sha256": hash },
+            ])
+        );
+        assert_eq!(
+            metadata["gallery"][0]["output_path"],
+            "conversion_results/castle.png"
+        );
+    }
+
+    // Verifies that an ExportStream sends everything that was written, and ends with the error of a failed write
+    #[actix_web::test]
+    async fn test_export_stream() {
+        let data = vec![7; CHUNK_SIZE * 3 + 1];
+        let expected = data.clone();
+        let stream = ExportStream::new(move |writer| writer.write_all(&data));
+
+        assert_eq!(to_bytes(stream).await.unwrap(), expected);
+
+        let stream = ExportStream::new(|_| Err(io::Error::other("failed")));
+
+        assert!(to_bytes(stream).await.is_err());
+    }
+}
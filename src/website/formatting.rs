@@ -0,0 +1,280 @@
+//! Module to format numbers, sizes and durations the way they are shown to users.
+//!
+//! Counts such as the pixels of an image, byte sizes such as the memory a conversion used, and durations such as the
+//! time a client has to wait are written by [NumberFormat] in the locale of the deployment, which is set by
+//! [super::config::AppConfig::locale]. [register_format_helpers] registers them as Handlebars helpers, so every
+//! template formats its values the same way:
+//!
+//! ```handlebars
+//! {{format_count usage.pixels_processed}}     {{!-- 1,048,576 --}}
+//! {{format_size usage.peak_memory_bytes}}     {{!-- 4.5MB --}}
+//! {{format_duration usage.conversion_time}}   {{!-- 250 ms --}}
+//! {{format_count 1048576 locale="de"}}        {{!-- 1.048.576 --}}
+//! ```
+//!
+//! Durations can be given in seconds or as a serialized [Duration]. A template can pick another locale with the
+//! `locale` hash parameter.
+//!
+//! Robert Peterson and Kelsey Werner 2023
+
+use handlebars::{
+    Context, Handlebars, Helper, HelperResult, JsonValue, Output, RenderContext, RenderError,
+};
+use std::time::Duration;
+
+/// The locale numbers are formatted in when none is set.
+pub const DEFAULT_LOCALE: &str = "en";
+
+/// Struct to store how the numbers of a locale are written.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct NumberFormat {
+    /// The separator put between each group of three digits, such as "," in "1,000".
+    pub group_separator: char,
+    /// The separator put between the whole part of a number and its decimals, such as "." in "1.5".
+    pub decimal_separator: char,
+}
+
+impl Default for NumberFormat {
+    fn default() -> NumberFormat {
+        NumberFormat::for_locale(DEFAULT_LOCALE)
+    }
+}
+
+impl NumberFormat {
+    /// Function to find how numbers are written in a locale, such as "en", "de-DE" or "fr_CA".
+    ///
+    /// Only the language of the locale is used. Languages that are not known are written like English.
+    pub fn for_locale(locale: &str) -> NumberFormat {
+        let language = locale
+            .trim()
+            .split(['-', '_'])
+            .next()
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+
+        let (group_separator, decimal_separator) = match language.as_str() {
+            "de" | "es" | "it" | "nl" | "pt" | "da" | "id" | "tr" => ('.', ','),
+            // these languages group digits with a space that is kept on the same line as the number
+            "fr" | "cs" | "fi" | "nb" | "pl" | "ru" | "sv" | "uk" => ('\u{a0}', ','),
+            _ => (',', '.'),
+        };
+
+        NumberFormat {
+            group_separator,
+            decimal_separator,
+        }
+    }
+
+    /// Function to write a count with its digits grouped, such as "1,048,576".
+    pub fn count(&self, count: u64) -> String {
+        let digits = count.to_string();
+        let mut grouped = String::with_capacity(digits.len() + digits.len() / 3);
+        for (index, digit) in digits.chars().enumerate() {
+            if index > 0 && (digits.len() - index).is_multiple_of(3) {
+                grouped.push(self.group_separator);
+            }
+            grouped.push(digit);
+        }
+
+        grouped
+    }
+
+    /// Function to write a number with at most one decimal, leaving out a decimal of zero, such as "1.5" or "2".
+    fn decimal(&self, number: f64) -> String {
+        let tenths = (number * 10.0).round() as u64;
+        match tenths % 10 {
+            0 => self.count(tenths / 10),
+            tenth => format!(
+                "{}{}{}",
+                self.count(tenths / 10),
+                self.decimal_separator,
+                tenth
+            ),
+        }
+    }
+
+    /// Function to write a size in bytes in the largest unit it fills, such as "1MB", "1.5MB" or "512KB".
+    ///
+    /// Sizes are counted in multiples of 1024 and shown with at most one decimal.
+    pub fn size(&self, bytes: u64) -> String {
+        let (amount, unit) = match bytes {
+            0..=1023 => return format!("{} bytes", bytes),
+            1024..=1_048_575 => (bytes as f64 / 1024.0, "KB"),
+            1_048_576..=1_073_741_823 => (bytes as f64 / 1_048_576.0, "MB"),
+            _ => (bytes as f64 / 1_073_741_824.0, "GB"),
+        };
+
+        format!("{}{}", self.decimal(amount), unit)
+    }
+
+    /// Function to write a [Duration] in the units that suit its length, such as "250 ms", "2.5 s", "3 min 20 s" or
+    /// "2 h 5 min".
+    pub fn duration(&self, duration: Duration) -> String {
+        let seconds = duration.as_secs();
+        match seconds {
+            0 => format!("{} ms", self.count(duration.as_millis() as u64)),
+            1..=59 => format!("{} s", self.decimal(duration.as_secs_f64())),
+            60..=3599 if seconds.is_multiple_of(60) => format!("{} min", seconds / 60),
+            60..=3599 => format!("{} min {} s", seconds / 60, seconds % 60),
+            _ if seconds % 3600 < 60 => format!("{} h", self.count(seconds / 3600)),
+            _ => format!(
+                "{} h {} min",
+                self.count(seconds / 3600),
+                seconds % 3600 / 60
+            ),
+        }
+    }
+}
+
+/// Function to register the `format_count`, `format_size` and `format_duration` helpers with Handlebars.
+///
+/// The helpers write their first parameter with the [NumberFormat] of the deployment, or of the locale given in their
+/// `locale` hash parameter.
+pub fn register_format_helpers(handlebars: &mut Handlebars, number_format: NumberFormat) {
+    handlebars.register_helper(
+        "format_count",
+        Box::new(
+            move |h: &Helper,
+                  _: &Handlebars,
+                  _: &Context,
+                  _: &mut RenderContext,
+                  out: &mut dyn Output|
+                  -> HelperResult {
+                let count = param(h)?.as_u64().ok_or_else(|| {
+                    RenderError::new("format_count needs a whole number that is not negative")
+                })?;
+                out.write(&helper_format(h, number_format).count(count))?;
+                Ok(())
+            },
+        ),
+    );
+    handlebars.register_helper(
+        "format_size",
+        Box::new(
+            move |h: &Helper,
+                  _: &Handlebars,
+                  _: &Context,
+                  _: &mut RenderContext,
+                  out: &mut dyn Output|
+                  -> HelperResult {
+                let bytes = param(h)?.as_u64().ok_or_else(|| {
+                    RenderError::new("format_size needs a number of bytes that is not negative")
+                })?;
+                out.write(&helper_format(h, number_format).size(bytes))?;
+                Ok(())
+            },
+        ),
+    );
+    handlebars.register_helper(
+        "format_duration",
+        Box::new(
+            move |h: &Helper,
+                  _: &Handlebars,
+                  _: &Context,
+                  _: &mut RenderContext,
+                  out: &mut dyn Output|
+                  -> HelperResult {
+                let duration = parse_duration(param(h)?).ok_or_else(|| {
+                    RenderError::new("format_duration needs a number of seconds or a duration")
+                })?;
+                out.write(&helper_format(h, number_format).duration(duration))?;
+                Ok(())
+            },
+        ),
+    );
+}
+
+/// Function to get the value of the first parameter of a helper.
+fn param<'a>(h: &'a Helper) -> Result<&'a JsonValue, RenderError> {
+    h.param(0)
+        .map(|param| param.value())
+        .ok_or_else(|| RenderError::new(format!("{} needs a value to format", h.name())))
+}
+
+/// Function to get the [NumberFormat] of the `locale` hash parameter of a helper, or `number_format` when it has none.
+fn helper_format(h: &Helper, number_format: NumberFormat) -> NumberFormat {
+    h.hash_get("locale")
+        .and_then(|locale| locale.value().as_str())
+        .map_or(number_format, NumberFormat::for_locale)
+}
+
+/// Function to read a [Duration] from a number of seconds, or from the `{"secs": .., "nanos": ..}` object that a
+/// [Duration] is serialized as.
+fn parse_duration(value: &JsonValue) -> Option<Duration> {
+    match value {
+        JsonValue::Number(seconds) => seconds
+            .as_f64()
+            .and_then(|seconds| Duration::try_from_secs_f64(seconds).ok()),
+        JsonValue::Object(duration) => Some(Duration::new(
+            duration.get("secs")?.as_u64()?,
+            u32::try_from(duration.get("nanos")?.as_u64()?).ok()?,
+        )),
+        _ => None,
+    }
+}
+
+// Tests
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    // Verifies that the separators are picked by the language of the locale
+    #[test]
+    fn test_for_locale() {
+        assert_eq!(NumberFormat::for_locale("en-US"), NumberFormat::default());
+        assert_eq!(NumberFormat::for_locale("xx"), NumberFormat::default());
+        assert_eq!(NumberFormat::for_locale("DE_at").group_separator, '.');
+        assert_eq!(NumberFormat::for_locale("fr").decimal_separator, ',');
+    }
+
+    // Verifies that counts, sizes and durations are written in the units and separators of the locale
+    #[test]
+    fn test_number_format() {
+        let english = NumberFormat::default();
+        let german = NumberFormat::for_locale("de");
+
+        assert_eq!(english.count(0), "0");
+        assert_eq!(english.count(999), "999");
+        assert_eq!(english.count(1_048_576), "1,048,576");
+        assert_eq!(german.count(1_048_576), "1.048.576");
+
+        assert_eq!(english.size(500), "500 bytes");
+        assert_eq!(english.size(1_048_576), "1MB");
+        assert_eq!(english.size(52_428_800), "50MB");
+        assert_eq!(english.size(2048), "2KB");
+        assert_eq!(english.size(1_572_864), "1.5MB");
+        assert_eq!(german.size(1_572_864), "1,5MB");
+        assert_eq!(english.size(3 * 1_073_741_824), "3GB");
+
+        assert_eq!(english.duration(Duration::from_millis(250)), "250 ms");
+        assert_eq!(english.duration(Duration::from_millis(2_500)), "2.5 s");
+        assert_eq!(german.duration(Duration::from_millis(2_500)), "2,5 s");
+        assert_eq!(english.duration(Duration::from_secs(120)), "2 min");
+        assert_eq!(english.duration(Duration::from_secs(200)), "3 min 20 s");
+        assert_eq!(english.duration(Duration::from_secs(7_200)), "2 h");
+        assert_eq!(english.duration(Duration::from_secs(7_500)), "2 h 5 min");
+    }
+
+    // Verifies that the helpers format their values in the locale of the deployment or of their locale parameter
+    #[test]
+    fn test_register_format_helpers() {
+        let mut handlebars = Handlebars::new();
+        register_format_helpers(&mut handlebars, NumberFormat::default());
+        let data = json!({ "pixels": 1_048_576, "bytes": 4_718_592, "time": { "secs": 0, "nanos": 250_000_000 } });
+
+        let render = |template: &str| handlebars.render_template(template, &data);
+
+        assert_eq!(render("{{format_count pixels}}").unwrap(), "1,048,576");
+        assert_eq!(
+            render(r#"{{format_count pixels locale="de"}}"#).unwrap(),
+            "1.048.576"
+        );
+        assert_eq!(render("{{format_size bytes}}").unwrap(), "4.5MB");
+        assert_eq!(render("{{format_duration time}}").unwrap(), "250 ms");
+        assert_eq!(render("{{format_duration 12}}").unwrap(), "12 s");
+        assert!(render("{{format_count missing}}").is_err());
+        assert!(render("{{format_duration -1}}").is_err());
+    }
+}
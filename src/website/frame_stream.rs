@@ -0,0 +1,244 @@
+//! Module to stream the frames of an ASCII animation as server-sent events.
+//!
+//! Each frame is sent as a `frame` event as soon as the previous frame has been shown for its delay, so clients
+//! receive the animation at its own frame rate, and a final `end` event is sent once every frame has been shown.
+//! The frames are decoded and converted on a worker of the [JobQueue], which hands each one over to the [FrameStream]
+//! and waits while the client is still watching the frames before it.
+//! The format of the events is described here: <https://html.spec.whatwg.org/multipage/server-sent-events.html>
+//!
+//! Robert Peterson and Kelsey Werner 2023
+
+use super::jobs::{JobError, JobHandle, JobQueue};
+use actix_web::{
+    body::{BodySize, MessageBody},
+    rt::time::{sleep, Sleep},
+    web::Bytes,
+};
+use ascii_art_converter::converter::animation::{AsciiFrame, AsciiFrames};
+use std::{
+    convert::Infallible,
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+};
+use tokio::sync::mpsc;
+
+/// The number of converted frames that are kept while they wait to be shown, after which the conversion waits for the client.
+const FRAME_BUFFER: usize = 2;
+
+/// The message sent when a frame of the animation can't be decoded or converted.
+const FRAME_ERROR_MESSAGE: &str =
+    "It looks like we ran into an issue with parsing a frame of your animation!";
+
+/// The message sent when the conversion stopped without saying why, such as when it panicked.
+const STOPPED_MESSAGE: &str =
+    "It looks like we ran into an issue with converting your animation! Try it one more time.";
+
+/// Enum to store what the conversion hands over to the [FrameStream].
+#[derive(Debug, PartialEq)]
+enum FrameEvent {
+    /// [FrameEvent::Frame] is used for a converted frame of the animation.
+    Frame(AsciiFrame),
+    /// [FrameEvent::End] is used once every frame was converted.
+    End,
+    /// [FrameEvent::Error] is used when the conversion failed, and stores a plain text message that explains why.
+    Error(&'static str),
+}
+
+/// Function to put the conversion of an animation in the [JobQueue], and stream its frames as they are converted.
+///
+/// `convert` starts reading the frames of the animation, and returns a plain text message that explains why when it
+/// can't. The frames are read on the worker, since [AsciiFrames] can't be moved between threads. Returns
+/// [JobError::QueueFull] when too many conversions are already waiting. The conversion is never run if the
+/// [FrameStream] is dropped before a worker is free, and it stops once the [FrameStream] is dropped.
+pub fn stream_frames<F>(jobs: &JobQueue, convert: F) -> Result<FrameStream, JobError>
+where
+    F: FnOnce() -> Result<AsciiFrames<'static>, &'static str> + Send + 'static,
+{
+    let (sender, receiver) = mpsc::channel(FRAME_BUFFER);
+    let job = jobs.enqueue(move || {
+        let frames = match convert() {
+            Ok(frames) => frames,
+            Err(error_message) => {
+                let _ = sender.blocking_send(FrameEvent::Error(error_message));
+                return;
+            }
+        };
+        for frame in frames {
+            let frame = match frame {
+                Ok(frame) => frame,
+                Err(_) => {
+                    let _ = sender.blocking_send(FrameEvent::Error(FRAME_ERROR_MESSAGE));
+                    return;
+                }
+            };
+            // the client went away, so the rest of the frames are never watched
+            if sender.blocking_send(FrameEvent::Frame(frame)).is_err() {
+                return;
+            }
+        }
+        let _ = sender.blocking_send(FrameEvent::End);
+    })?;
+
+    Ok(FrameStream {
+        receiver,
+        next_id: 0,
+        delay: None,
+        finished: false,
+        _job: job,
+    })
+}
+
+/// Struct to stream the frames of an ASCII animation as the body of a `text/event-stream` response.
+pub struct FrameStream {
+    /// The receiver of the frames the conversion makes.
+    receiver: mpsc::Receiver<FrameEvent>,
+    /// The id of the next frame event.
+    next_id: u64,
+    /// The timer of the frame that is being shown, or [None] if the next event can be sent right away.
+    delay: Option<Pin<Box<Sleep>>>,
+    /// Whether the last event has been sent.
+    finished: bool,
+    /// The handle of the conversion, which is kept so the conversion isn't dropped from the queue while it waits.
+    _job: JobHandle<()>,
+}
+
+/// Function to format a frame of ASCII art as a `frame` event with one data field per line.
+fn frame_event(id: u64, ascii: &str) -> String {
+    let mut event = format!("id: {}\nevent: frame\n", id);
+    for line in ascii.lines() {
+        event.push_str("data: ");
+        event.push_str(line);
+        event.push('\n');
+    }
+    event.push('\n');
+    event
+}
+
+impl MessageBody for FrameStream {
+    type Error = Infallible;
+
+    /// The size of the stream is not known ahead of time.
+    fn size(&self) -> BodySize {
+        BodySize::Stream
+    }
+
+    /// Function to send the next event once the frame before it has been shown for its delay and the conversion has
+    /// made it.
+    fn poll_next(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Bytes, Self::Error>>> {
+        let this = self.get_mut();
+
+        if let Some(delay) = &mut this.delay {
+            if delay.as_mut().poll(cx).is_pending() {
+                return Poll::Pending;
+            }
+            this.delay = None;
+        }
+
+        if this.finished {
+            return Poll::Ready(None);
+        }
+
+        let event = match this.receiver.poll_recv(cx) {
+            Poll::Pending => return Poll::Pending,
+            Poll::Ready(Some(FrameEvent::Frame(frame))) => {
+                this.delay = Some(Box::pin(sleep(frame.delay)));
+                this.next_id += 1;
+                frame_event(this.next_id - 1, &frame.ascii)
+            }
+            Poll::Ready(Some(FrameEvent::End)) => {
+                this.finished = true;
+                "event: end\ndata: \n\n".to_string()
+            }
+            Poll::Ready(Some(FrameEvent::Error(error_message))) => {
+                this.finished = true;
+                format!("event: error\ndata: {}\n\n", error_message)
+            }
+            Poll::Ready(None) => {
+                this.finished = true;
+                format!("event: error\ndata: {}\n\n", STOPPED_MESSAGE)
+            }
+        };
+
+        Poll::Ready(Some(Ok(Bytes::from(event))))
+    }
+}
+
+// Tests
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::body::to_bytes;
+    use ascii_art_converter::converter::options::ConvertOptions;
+    use std::{
+        fs,
+        time::{Duration, Instant},
+    };
+
+    // Verifies that frame_event() sends each line of the frame as a data field
+    #[test]
+    fn test_frame_event() {
+        assert_eq!(
+            frame_event(3, "ab\ncd\n"),
+            "id: 3\nevent: frame\ndata: ab\ndata: cd\n\n"
+        );
+    }
+
+    // Verifies that a FrameStream sends every frame at the frame rate of the animation followed by an end event
+    #[actix_web::test]
+    async fn test_frame_stream() {
+        // The idea to use "CARGO_MANIFEST_DIR" comes from StackOverflow:
+        // https://stackoverflow.com/questions/30003921/how-can-i-locate-resources-for-testing-with-cargo
+        let gif_path = concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/test_assets/images/moving-bar.gif"
+        );
+        let options = ConvertOptions {
+            width: Some(4),
+            ..Default::default()
+        };
+        let gif = fs::read(gif_path).unwrap();
+        let jobs = JobQueue::new(1, 1, usize::MAX);
+        let frame_stream = stream_frames(&jobs, move || {
+            AsciiFrames::new(gif, options).map_err(|_| "It broke!")
+        })
+        .unwrap();
+
+        let start = Instant::now();
+        let body = to_bytes(frame_stream).await.unwrap();
+
+        // the moving-bar.gif has four frames that are each shown for 40ms
+        assert!(start.elapsed() >= Duration::from_millis(160));
+        let body = String::from_utf8(body.to_vec()).unwrap();
+        let events: Vec<&str> = body.split_terminator("\n\n").collect();
+        assert_eq!(events.len(), 5);
+        assert_eq!(events[0], "id: 0\nevent: frame\ndata: q:  \ndata: ;.  ");
+        assert_eq!(events[3], "id: 3\nevent: frame\ndata:   :q\ndata:   .;");
+        assert_eq!(events[4], "event: end\ndata: ");
+    }
+
+    // Verifies that an animation that can't be read ends the stream with an error event
+    #[actix_web::test]
+    async fn test_stream_frames_error() {
+        let jobs = JobQueue::new(1, 1, usize::MAX);
+        let frame_stream = stream_frames(&jobs, || Err("It broke!")).unwrap();
+        let body = to_bytes(frame_stream).await.unwrap();
+
+        assert_eq!(body, "event: error\ndata: It broke!\n\n");
+    }
+
+    // Verifies that the conversion isn't started when too many conversions are already waiting
+    #[actix_web::test]
+    async fn test_stream_frames_queue_full() {
+        let jobs = JobQueue::new(1, 0, usize::MAX);
+
+        assert_eq!(
+            stream_frames(&jobs, || Err("It broke!")).err(),
+            Some(JobError::QueueFull)
+        );
+    }
+}
@@ -0,0 +1,979 @@
+//! Module to store submitted conversions in a gallery.
+//!
+//! Every successful conversion is saved as a [GalleryEntry] in a SQLite database, so past conversions can still be browsed
+//! on the GET /gallery and GET /gallery/{id} pages after the server restarts. The table is created the first time the
+//! database is connected to, so a new deployment starts with an empty gallery, and the columns added since the table was
+//! first made are added to the tables of older databases.
+//!
+//! The titles and authors of the conversions are also indexed in a SQLite FTS5 table, so the gallery can be searched by
+//! words in them along with the character set and the dates the conversions were made with a [GallerySearch]. The index is
+//! kept up to date by triggers on the table of conversions, and is built from the existing conversions the first time an
+//! older database is connected to.
+//!
+//! Conversions can be reported, and the [super::moderation::Report]s are kept in a table next to the conversions until an
+//! operator hides or deletes the conversion or dismisses its reports. Hidden conversions are left out of the gallery pages
+//! and searches but are kept in the database.
+//!
+//! Robert Peterson and Kelsey Werner 2023
+
+use super::{
+    attribution::{Attribution, License},
+    moderation::{FlaggedEntry, ModerationAction, Report, ReportReason},
+};
+use ascii_art_converter::converter::options::RenderCharset;
+use serde::{Deserialize, Serialize};
+use sqlx::{
+    sqlite::{SqliteConnectOptions, SqlitePool, SqlitePoolOptions},
+    Executor,
+};
+use std::str::FromStr;
+
+/// The database the web application stores its gallery in.
+pub const GALLERY_DATABASE_URL: &str = "sqlite://gallery.db";
+
+/// The number of conversions shown on each page of the gallery.
+pub const GALLERY_PAGE_SIZE: u32 = 12;
+
+/// The longest title a conversion can be given, in characters.
+pub const MAX_TITLE_LENGTH: usize = 100;
+
+/// The SQL that creates the table of conversions when it doesn't exist yet.
+const GALLERY_SCHEMA: &str = "CREATE TABLE IF NOT EXISTS conversions (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    kind TEXT NOT NULL,
+    input_hash TEXT NOT NULL,
+    output_path TEXT NOT NULL,
+    title TEXT,
+    created_at TEXT NOT NULL DEFAULT (datetime('now')),
+    author TEXT,
+    license TEXT,
+    charset TEXT,
+    hidden INTEGER NOT NULL DEFAULT 0
+)";
+
+/// The columns that were added to the table of conversions after it was first made, with their types.
+const ADDED_COLUMNS: [(&str, &str); 4] = [
+    ("author", "TEXT"),
+    ("license", "TEXT"),
+    ("charset", "TEXT"),
+    ("hidden", "INTEGER NOT NULL DEFAULT 0"),
+];
+
+/// The SQL that creates the table of reports of conversions when it doesn't exist yet.
+const REPORTS_SCHEMA: &str = "CREATE TABLE IF NOT EXISTS reports (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    conversion_id INTEGER NOT NULL REFERENCES conversions (id) ON DELETE CASCADE,
+    reason TEXT NOT NULL,
+    details TEXT,
+    created_at TEXT NOT NULL DEFAULT (datetime('now'))
+)";
+
+/// The SQL that creates the full-text index of the titles and authors of the conversions, and the triggers that keep it up to date.
+const SEARCH_SCHEMA: [&str; 4] = [
+    "CREATE VIRTUAL TABLE IF NOT EXISTS conversions_search USING fts5(
+        title, author, content = 'conversions', content_rowid = 'id'
+    )",
+    "CREATE TRIGGER IF NOT EXISTS conversions_search_insert AFTER INSERT ON conversions BEGIN
+        INSERT INTO conversions_search (rowid, title, author) VALUES (new.id, new.title, new.author);
+    END",
+    "CREATE TRIGGER IF NOT EXISTS conversions_search_delete AFTER DELETE ON conversions BEGIN
+        INSERT INTO conversions_search (conversions_search, rowid, title, author) VALUES ('delete', old.id, old.title, old.author);
+    END",
+    "CREATE TRIGGER IF NOT EXISTS conversions_search_update AFTER UPDATE ON conversions BEGIN
+        INSERT INTO conversions_search (conversions_search, rowid, title, author) VALUES ('delete', old.id, old.title, old.author);
+        INSERT INTO conversions_search (rowid, title, author) VALUES (new.id, new.title, new.author);
+    END",
+];
+
+/// The longest search text that is used, in characters.
+pub const MAX_SEARCH_LENGTH: usize = 100;
+
+/// Enum to store the direction of a conversion.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, sqlx::Type)]
+#[serde(rename_all = "kebab-case")]
+#[sqlx(rename_all = "kebab-case")]
+pub enum ConversionKind {
+    /// [ConversionKind::AsciiToImage] is used for ASCII art that was converted into an image.
+    AsciiToImage,
+    /// [ConversionKind::ImageToAscii] is used for an image that was converted into ASCII art.
+    ImageToAscii,
+}
+
+/// Struct to store a conversion in the gallery.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, sqlx::FromRow)]
+pub struct GalleryEntry {
+    /// The id of the conversion, which is used in the address of its gallery page.
+    pub id: i64,
+    /// The direction of the conversion.
+    pub kind: ConversionKind,
+    /// The SHA-256 hash of the submitted image or ASCII art.
+    pub input_hash: String,
+    /// The route to the result of the conversion, such as "conversion/{uuid}.png".
+    pub output_path: String,
+    /// [Option] stores the title the user gave the conversion or [None] if it has no title.
+    pub title: Option<String>,
+    /// The time the conversion was made in UTC, such as "2023-06-09 04:58:50".
+    pub created_at: String,
+    /// [Option] stores the name of the author of the conversion or [None] if no author was given.
+    ///
+    /// Conversions exported before authors were kept have no author.
+    #[serde(default)]
+    pub author: Option<String>,
+    /// [Option] stores the [License] of the conversion or [None] if no license was picked.
+    #[serde(default)]
+    pub license: Option<License>,
+    /// [Option] stores the name of the [RenderCharset] of an image to ASCII art conversion, such as "braille", or [None]
+    /// for ASCII art to image conversions and conversions made before the character set was kept.
+    #[serde(default)]
+    pub charset: Option<String>,
+    /// [bool] that is `true` when an operator hid the conversion from the gallery.
+    #[serde(default)]
+    pub hidden: bool,
+}
+
+/// Struct to store a page of the gallery.
+#[derive(Debug, PartialEq)]
+pub struct GalleryPage {
+    /// The conversions on the page, newest first.
+    pub entries: Vec<GalleryEntry>,
+    /// [Option] stores the number of the page before this one or [None] if this is the first page.
+    pub previous_page: Option<u32>,
+    /// [Option] stores the number of the page after this one or [None] if this is the last page.
+    pub next_page: Option<u32>,
+}
+
+/// Struct to store the query of the gallery page.
+///
+/// Actix Web populates [GalleryQuery] with the query parameters of a link to a page of the gallery or of the search form on it.
+#[derive(Deserialize, Default)]
+pub struct GalleryQuery {
+    /// [Option] stores the number of the page to show or [None] to show the first page.
+    pub page: Option<u32>,
+    /// [Option] stores the words to search the titles and authors for or [None] if no words were submitted.
+    pub q: Option<String>,
+    /// [Option] stores the name of the character set to search for or [None] if no character set was picked.
+    pub charset: Option<String>,
+    /// [Option] stores the first day to search, such as "2023-06-09", or [None] if no day was picked.
+    pub from: Option<String>,
+    /// [Option] stores the last day to search, such as "2023-06-09", or [None] if no day was picked.
+    pub to: Option<String>,
+}
+
+impl GalleryQuery {
+    /// Function to get the [GallerySearch] of the query.
+    ///
+    /// Filters that are blank or can't be used, such as an unknown character set or a day that isn't written as
+    /// "YYYY-MM-DD", are left out of the search.
+    pub fn search(&self) -> GallerySearch {
+        let filter = |value: &Option<String>| {
+            value
+                .as_deref()
+                .map(str::trim)
+                .filter(|value| !value.is_empty())
+                .map(str::to_string)
+        };
+        let day = |value: &Option<String>| filter(value).filter(|day| is_day(day));
+
+        GallerySearch {
+            text: filter(&self.q).map(|text| text.chars().take(MAX_SEARCH_LENGTH).collect()),
+            charset: filter(&self.charset).and_then(|name| charset_from_name(&name)),
+            from: day(&self.from),
+            to: day(&self.to),
+        }
+    }
+}
+
+/// Struct to store the filters of a search of the gallery, where [None] leaves a filter out.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct GallerySearch {
+    /// [Option] stores the words that must each start a word of the title or author of a conversion.
+    pub text: Option<String>,
+    /// [Option] stores the [RenderCharset] a conversion must have been made with.
+    pub charset: Option<RenderCharset>,
+    /// [Option] stores the first day a conversion may have been made on in UTC, such as "2023-06-09".
+    pub from: Option<String>,
+    /// [Option] stores the last day a conversion may have been made on in UTC, such as "2023-06-09".
+    pub to: Option<String>,
+}
+
+impl GallerySearch {
+    /// Function to get the query parameters of the search, which links to other pages of the search put before the page number.
+    ///
+    /// Returns an empty [String] when the search is empty, and otherwise the parameters followed by "&".
+    pub fn to_query(&self) -> String {
+        let parameters: Vec<(&str, &str)> = [
+            ("q", self.text.as_deref()),
+            ("charset", self.charset.map(charset_name)),
+            ("from", self.from.as_deref()),
+            ("to", self.to.as_deref()),
+        ]
+        .into_iter()
+        .filter_map(|(name, value)| value.map(|value| (name, value)))
+        .collect();
+
+        if parameters.is_empty() {
+            String::new()
+        } else {
+            format!(
+                "{}&",
+                serde_urlencoded::to_string(parameters).unwrap_or_default()
+            )
+        }
+    }
+
+    /// Function to turn the search text into an FTS5 query where each word must start a word of the title or author.
+    ///
+    /// Every word is quoted so that characters with a meaning in FTS5 queries, such as `*` or `OR`, are searched for as text.
+    fn match_query(&self) -> Option<String> {
+        let words: Vec<String> = self
+            .text
+            .as_deref()?
+            .split_whitespace()
+            .map(|word| format!("\"{}\"*", word.replace('"', "\"\"")))
+            .collect();
+        (!words.is_empty()).then(|| words.join(" "))
+    }
+}
+
+/// Function to get the name a [RenderCharset] is stored and searched with, such as "braille".
+pub fn charset_name(charset: RenderCharset) -> &'static str {
+    match charset {
+        RenderCharset::Ascii => "ascii",
+        RenderCharset::Braille => "braille",
+        RenderCharset::HalfBlock => "half_block",
+    }
+}
+
+/// Function to find the [RenderCharset] with the given name, or [None] when no character set has the name.
+fn charset_from_name(name: &str) -> Option<RenderCharset> {
+    [
+        RenderCharset::Ascii,
+        RenderCharset::Braille,
+        RenderCharset::HalfBlock,
+    ]
+    .into_iter()
+    .find(|charset| charset_name(*charset) == name)
+}
+
+/// Function to check if a day is written as "YYYY-MM-DD", which is how dates are sent by the date inputs of forms.
+fn is_day(day: &str) -> bool {
+    day.len() == 10
+        && day.char_indices().all(|(i, c)| match i {
+            4 | 7 => c == '-',
+            _ => c.is_ascii_digit(),
+        })
+}
+
+/// Function to clean up the title a user gave a conversion.
+///
+/// Returns [None] when the title is blank, and otherwise the title without surrounding whitespace and cut to [MAX_TITLE_LENGTH] characters.
+pub fn clean_title(title: &str) -> Option<String> {
+    let title = title.trim();
+    if title.is_empty() {
+        None
+    } else {
+        Some(title.chars().take(MAX_TITLE_LENGTH).collect())
+    }
+}
+
+/// Struct to store conversions in a SQLite database.
+///
+/// The [Gallery] can be cloned cheaply, since every clone shares the same pool of connections.
+#[derive(Clone)]
+pub struct Gallery {
+    /// The pool of connections to the database.
+    pool: SqlitePool,
+}
+
+impl Gallery {
+    /// Function to open the gallery stored in the database at `url`, which is created if it doesn't exist yet.
+    ///
+    /// The connections are only made once the gallery is used, and the table of conversions is created on each new connection
+    /// if it doesn't exist yet, or given the [ADDED_COLUMNS] it is missing. The search index and the table of reports are
+    /// created the same way.
+    /// Returns an error if the url is not a SQLite url.
+    pub fn new(url: &str) -> Result<Gallery, sqlx::Error> {
+        // The code for setting up a pool of SQLite connections references the sqlx documentation:
+        // https://docs.rs/sqlx/0.7.4/sqlx/sqlite/struct.SqliteConnectOptions.html
+        let options = SqliteConnectOptions::from_str(url)?.create_if_missing(true);
+        let pool = SqlitePoolOptions::new()
+            .after_connect(|connection, _| {
+                Box::pin(async move {
+                    connection.execute(GALLERY_SCHEMA).await?;
+                    for (column, column_type) in ADDED_COLUMNS {
+                        let exists: bool = sqlx::query_scalar(
+                            "SELECT EXISTS (SELECT 1 FROM pragma_table_info('conversions') WHERE name = ?)",
+                        )
+                        .bind(column)
+                        .fetch_one(&mut *connection)
+                        .await?;
+                        if !exists {
+                            let alter = format!(
+                                "ALTER TABLE conversions ADD COLUMN {} {}",
+                                column, column_type
+                            );
+                            connection.execute(alter.as_str()).await?;
+                        }
+                    }
+
+                    let indexed: bool = sqlx::query_scalar(
+                        "SELECT EXISTS (SELECT 1 FROM sqlite_master WHERE name = 'conversions_search')",
+                    )
+                    .fetch_one(&mut *connection)
+                    .await?;
+                    for statement in SEARCH_SCHEMA {
+                        connection.execute(statement).await?;
+                    }
+                    if !indexed {
+                        // conversions made before the index existed are added to it
+                        connection
+                            .execute("INSERT INTO conversions_search (conversions_search) VALUES ('rebuild')")
+                            .await?;
+                    }
+                    connection.execute(REPORTS_SCHEMA).await?;
+                    Ok(())
+                })
+            })
+            .connect_lazy_with(options);
+
+        Ok(Gallery { pool })
+    }
+
+    /// Function to add a conversion to the gallery.
+    ///
+    /// The `charset` is the [RenderCharset] of an image to ASCII art conversion. Returns the id of the new [GalleryEntry].
+    pub async fn add(
+        &self,
+        kind: ConversionKind,
+        input_hash: &str,
+        output_path: &str,
+        title: Option<&str>,
+        attribution: &Attribution,
+        charset: Option<RenderCharset>,
+    ) -> Result<i64, sqlx::Error> {
+        let result = sqlx::query(
+            "INSERT INTO conversions (kind, input_hash, output_path, title, author, license, charset) VALUES (?, ?, ?, ?, ?, ?, ?)",
+        )
+        .bind(kind)
+        .bind(input_hash)
+        .bind(output_path)
+        .bind(title)
+        .bind(&attribution.author)
+        .bind(attribution.license)
+        .bind(charset.map(charset_name))
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.last_insert_rowid())
+    }
+
+    /// Function to add a conversion that was exported from another gallery, keeping its title, attribution, the time it was
+    /// made and whether it was hidden.
+    ///
+    /// The entry is given a new id. Returns `Ok(false)` without adding it when the gallery already has a conversion with the
+    /// same direction, input hash and output path.
+    pub async fn import(&self, entry: &GalleryEntry) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query(
+            "INSERT INTO conversions (kind, input_hash, output_path, title, created_at, author, license, charset, hidden)
+            SELECT ?, ?, ?, ?, ?, ?, ?, ?, ?
+            WHERE NOT EXISTS (
+                SELECT 1 FROM conversions WHERE kind = ? AND input_hash = ? AND output_path = ?
+            )",
+        )
+        .bind(entry.kind)
+        .bind(&entry.input_hash)
+        .bind(&entry.output_path)
+        .bind(&entry.title)
+        .bind(&entry.created_at)
+        .bind(&entry.author)
+        .bind(entry.license)
+        .bind(&entry.charset)
+        .bind(entry.hidden)
+        .bind(entry.kind)
+        .bind(&entry.input_hash)
+        .bind(&entry.output_path)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() == 1)
+    }
+
+    /// Function to get the conversion with the given id, which may be hidden.
+    ///
+    /// Returns `Ok(None)` when there is no conversion with the id.
+    pub async fn get(&self, id: i64) -> Result<Option<GalleryEntry>, sqlx::Error> {
+        sqlx::query_as("SELECT * FROM conversions WHERE id = ?")
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await
+    }
+
+    /// Function to get every conversion in the gallery, oldest first.
+    pub async fn all(&self) -> Result<Vec<GalleryEntry>, sqlx::Error> {
+        sqlx::query_as("SELECT * FROM conversions ORDER BY id")
+            .fetch_all(&self.pool)
+            .await
+    }
+
+    /// Function to get a page of the conversions that match a [GallerySearch], newest first, where the first page is page 1.
+    ///
+    /// An empty [GallerySearch] matches every conversion that isn't hidden. A page past the last page has no entries.
+    pub async fn search(
+        &self,
+        search: &GallerySearch,
+        page: u32,
+    ) -> Result<GalleryPage, sqlx::Error> {
+        let page = page.max(1);
+        let offset = i64::from(page - 1) * i64::from(GALLERY_PAGE_SIZE);
+
+        // one more conversion than fits on the page is read to find out if there is a next page
+        let mut entries: Vec<GalleryEntry> = sqlx::query_as(
+            "SELECT * FROM conversions
+            WHERE hidden = 0
+            AND (?1 IS NULL OR id IN (SELECT rowid FROM conversions_search WHERE conversions_search MATCH ?1))
+            AND (?2 IS NULL OR charset = ?2)
+            AND (?3 IS NULL OR created_at >= ?3)
+            AND (?4 IS NULL OR created_at < date(?4, '+1 day'))
+            ORDER BY id DESC LIMIT ?5 OFFSET ?6",
+        )
+        .bind(search.match_query())
+        .bind(search.charset.map(charset_name))
+        .bind(&search.from)
+        .bind(&search.to)
+        .bind(i64::from(GALLERY_PAGE_SIZE) + 1)
+        .bind(offset)
+        .fetch_all(&self.pool)
+        .await?;
+        let has_next_page = entries.len() > GALLERY_PAGE_SIZE as usize;
+        entries.truncate(GALLERY_PAGE_SIZE as usize);
+
+        Ok(GalleryPage {
+            entries,
+            previous_page: (page > 1).then(|| page - 1),
+            next_page: has_next_page.then(|| page + 1),
+        })
+    }
+
+    /// Function to report the conversion with the given id for a [ReportReason], with the details the user wrote about it.
+    ///
+    /// Returns `Ok(false)` without adding the report when there is no conversion with the id, or it is hidden.
+    pub async fn report(
+        &self,
+        id: i64,
+        reason: ReportReason,
+        details: Option<&str>,
+    ) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query(
+            "INSERT INTO reports (conversion_id, reason, details)
+            SELECT id, ?, ? FROM conversions WHERE id = ? AND hidden = 0",
+        )
+        .bind(reason)
+        .bind(details)
+        .bind(id)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() == 1)
+    }
+
+    /// Function to get the review queue, which has every conversion that was reported and isn't hidden along with its reports.
+    ///
+    /// The conversions with the most reports come first, and conversions with as many reports are in the order they were first reported.
+    pub async fn flagged(&self) -> Result<Vec<FlaggedEntry>, sqlx::Error> {
+        let entries: Vec<GalleryEntry> = sqlx::query_as(
+            "SELECT conversions.* FROM conversions
+            JOIN reports ON reports.conversion_id = conversions.id
+            WHERE conversions.hidden = 0
+            GROUP BY conversions.id
+            ORDER BY COUNT(*) DESC, MIN(reports.id)",
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        let reports: Vec<Report> = sqlx::query_as(
+            "SELECT reports.* FROM reports
+            JOIN conversions ON conversions.id = reports.conversion_id
+            WHERE conversions.hidden = 0
+            ORDER BY reports.id",
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(entries
+            .into_iter()
+            .map(|entry| FlaggedEntry {
+                reports: reports
+                    .iter()
+                    .filter(|report| report.conversion_id == entry.id)
+                    .cloned()
+                    .collect(),
+                entry,
+            })
+            .collect())
+    }
+
+    /// Function to take a [ModerationAction] on the conversion with the given id.
+    ///
+    /// Returns the conversion as it was before the action, so the result of a deleted conversion can be removed too,
+    /// or `Ok(None)` when there is no conversion with the id.
+    pub async fn moderate(
+        &self,
+        id: i64,
+        action: ModerationAction,
+    ) -> Result<Option<GalleryEntry>, sqlx::Error> {
+        let mut transaction = self.pool.begin().await?;
+        let entry: Option<GalleryEntry> = sqlx::query_as("SELECT * FROM conversions WHERE id = ?")
+            .bind(id)
+            .fetch_optional(&mut *transaction)
+            .await?;
+        if entry.is_none() {
+            return Ok(None);
+        }
+
+        let statements: &[&str] = match action {
+            ModerationAction::Hide => &["UPDATE conversions SET hidden = 1 WHERE id = ?"],
+            // the reports are deleted first since the foreign key may not be enforced
+            ModerationAction::Delete => &[
+                "DELETE FROM reports WHERE conversion_id = ?",
+                "DELETE FROM conversions WHERE id = ?",
+            ],
+            ModerationAction::Dismiss => &["DELETE FROM reports WHERE conversion_id = ?"],
+        };
+        for statement in statements {
+            sqlx::query(statement)
+                .bind(id)
+                .execute(&mut *transaction)
+                .await?;
+        }
+        transaction.commit().await?;
+
+        Ok(entry)
+    }
+}
+
+// Tests
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Opens a gallery in a new database in a temporary directory
+    fn temporary_gallery() -> (tempfile::TempDir, Gallery) {
+        let directory = tempfile::tempdir().unwrap();
+        let url = format!("sqlite://{}", directory.path().join("gallery.db").display());
+        let gallery = Gallery::new(&url).unwrap();
+        (directory, gallery)
+    }
+
+    // Verifies that clean_title() trims titles, drops blank titles and cuts long titles
+    #[test]
+    fn test_clean_title() {
+        assert_eq!(clean_title("  Castle \n"), Some("Castle".to_string()));
+        assert_eq!(clean_title("   "), None);
+        assert_eq!(
+            clean_title(&"é".repeat(MAX_TITLE_LENGTH + 1)),
+            Some("é".repeat(MAX_TITLE_LENGTH))
+        );
+    }
+
+    // Verifies that conversions added to the gallery can be read back by their id
+    #[actix_web::test]
+    async fn test_add_and_get() {
+        let (_directory, gallery) = temporary_gallery();
+
+        let id = gallery
+            .add(
+                ConversionKind::AsciiToImage,
+                "abc123",
+                "conversion_results/castle.png",
+                Some("Castle"),
+                &Attribution {
+                    author: Some("Kelsey".to_string()),
+                    license: Some(License::CcBy4),
+                },
+                None,
+            )
+            .await
+            .unwrap();
+        let entry = gallery.get(id).await.unwrap().unwrap();
+
+        assert_eq!(entry.id, id);
+        assert_eq!(entry.kind, ConversionKind::AsciiToImage);
+        assert_eq!(entry.input_hash, "abc123");
+        assert_eq!(entry.output_path, "conversion_results/castle.png");
+        assert_eq!(entry.title, Some("Castle".to_string()));
+        assert_eq!(entry.created_at.len(), "2023-06-09 04:58:50".len());
+        assert_eq!(entry.author, Some("Kelsey".to_string()));
+        assert_eq!(entry.license, Some(License::CcBy4));
+        assert_eq!(gallery.get(id + 1).await.unwrap(), None);
+    }
+
+    // Verifies that imported conversions keep their title, attribution and time, and are only imported once
+    #[actix_web::test]
+    async fn test_import() {
+        let (_directory, gallery) = temporary_gallery();
+        let entry = GalleryEntry {
+            id: 42,
+            kind: ConversionKind::ImageToAscii,
+            input_hash: "abc123".to_string(),
+            output_path: "conversion_results/art.txt".to_string(),
+            title: Some("Imported".to_string()),
+            created_at: "2023-06-09 04:58:50".to_string(),
+            author: Some("Robert".to_string()),
+            license: Some(License::Cc0),
+            charset: None,
+            hidden: true,
+        };
+
+        assert!(gallery.import(&entry).await.unwrap());
+        assert!(!gallery.import(&entry).await.unwrap());
+
+        let imported = gallery.all().await.unwrap();
+
+        assert_eq!(imported.len(), 1);
+        assert_eq!(imported[0], GalleryEntry { id: 1, ..entry });
+    }
+
+    // Verifies that the author, license, charset and hidden columns are added to the table of a database made before they existed
+    #[actix_web::test]
+    async fn test_added_columns() {
+        let directory = tempfile::tempdir().unwrap();
+        let url = format!("sqlite://{}", directory.path().join("gallery.db").display());
+        let options = SqliteConnectOptions::from_str(&url)
+            .unwrap()
+            .create_if_missing(true);
+        let pool = SqlitePool::connect_with(options).await.unwrap();
+        pool.execute(
+            "CREATE TABLE conversions (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                kind TEXT NOT NULL,
+                input_hash TEXT NOT NULL,
+                output_path TEXT NOT NULL,
+                title TEXT,
+                created_at TEXT NOT NULL DEFAULT (datetime('now'))
+            )",
+        )
+        .await
+        .unwrap();
+        pool.execute("INSERT INTO conversions (kind, input_hash, output_path, title) VALUES ('image-to-ascii', 'abc123', 'conversion_results/art.txt', 'Old castle')")
+            .await
+            .unwrap();
+        pool.close().await;
+
+        let gallery = Gallery::new(&url).unwrap();
+        let entry = gallery.get(1).await.unwrap().unwrap();
+
+        assert_eq!(entry.input_hash, "abc123");
+        assert_eq!(entry.author, None);
+        assert_eq!(entry.license, None);
+        assert_eq!(entry.charset, None);
+        assert!(!entry.hidden);
+
+        // the conversion made before the search index existed can be found
+        let search = GallerySearch {
+            text: Some("castle".to_string()),
+            ..GallerySearch::default()
+        };
+
+        assert_eq!(
+            gallery.search(&search, 1).await.unwrap().entries,
+            vec![entry]
+        );
+    }
+
+    // Verifies that the gallery can be searched by words in the titles and authors, character set and dates
+    #[actix_web::test]
+    async fn test_search() {
+        let (_directory, gallery) = temporary_gallery();
+        let conversions = [
+            (
+                "Castle at night",
+                Some("Kelsey Werner"),
+                Some(RenderCharset::Braille),
+            ),
+            (
+                "Castle",
+                Some("Robert Peterson"),
+                Some(RenderCharset::Ascii),
+            ),
+            ("Fish \"OR\" *", None, None),
+        ];
+        for (title, author, charset) in conversions {
+            let attribution = Attribution {
+                author: author.map(str::to_string),
+                license: None,
+            };
+            gallery
+                .add(
+                    ConversionKind::ImageToAscii,
+                    "abc123",
+                    "conversion/art.txt",
+                    Some(title),
+                    &attribution,
+                    charset,
+                )
+                .await
+                .unwrap();
+        }
+        let titles = |page: GalleryPage| -> Vec<String> {
+            page.entries
+                .into_iter()
+                .filter_map(|entry| entry.title)
+                .collect()
+        };
+        let search = |text: Option<&str>, charset: Option<RenderCharset>| GallerySearch {
+            text: text.map(str::to_string),
+            charset,
+            ..GallerySearch::default()
+        };
+
+        assert_eq!(
+            titles(
+                gallery
+                    .search(&search(Some("cast"), None), 1)
+                    .await
+                    .unwrap()
+            ),
+            vec!["Castle", "Castle at night"]
+        );
+        assert_eq!(
+            titles(
+                gallery
+                    .search(&search(Some("castle kel"), None), 1)
+                    .await
+                    .unwrap()
+            ),
+            vec!["Castle at night"]
+        );
+        assert_eq!(
+            titles(
+                gallery
+                    .search(&search(Some("castle"), Some(RenderCharset::Ascii)), 1)
+                    .await
+                    .unwrap()
+            ),
+            vec!["Castle"]
+        );
+        assert_eq!(
+            titles(
+                gallery
+                    .search(&search(Some("\"OR\" *"), None), 1)
+                    .await
+                    .unwrap()
+            ),
+            vec!["Fish \"OR\" *"]
+        );
+        assert_eq!(
+            titles(
+                gallery
+                    .search(&search(Some("dragon"), None), 1)
+                    .await
+                    .unwrap()
+            ),
+            Vec::<String>::new()
+        );
+
+        let today = gallery.get(1).await.unwrap().unwrap().created_at[..10].to_string();
+        let by_day = |from: &str, to: &str| GallerySearch {
+            from: Some(from.to_string()),
+            to: Some(to.to_string()),
+            ..GallerySearch::default()
+        };
+
+        assert_eq!(
+            gallery
+                .search(&by_day(&today, &today), 1)
+                .await
+                .unwrap()
+                .entries
+                .len(),
+            3
+        );
+        assert!(gallery
+            .search(&by_day("2023-06-01", "2023-06-09"), 1)
+            .await
+            .unwrap()
+            .entries
+            .is_empty());
+    }
+
+    // Verifies that reported conversions are in the review queue with the most reported first, and that hiding,
+    // deleting and dismissing take them out of it
+    #[actix_web::test]
+    async fn test_report_and_moderate() {
+        let (_directory, gallery) = temporary_gallery();
+        let mut ids = Vec::new();
+        for title in ["One", "Two", "Three"] {
+            let id = gallery
+                .add(
+                    ConversionKind::AsciiToImage,
+                    "abc123",
+                    "conversion/art.png",
+                    Some(title),
+                    &Attribution::default(),
+                    None,
+                )
+                .await
+                .unwrap();
+            ids.push(id);
+        }
+
+        assert!(gallery
+            .report(ids[0], ReportReason::Spam, None)
+            .await
+            .unwrap());
+        assert!(gallery
+            .report(ids[1], ReportReason::Copyright, Some("I drew this"))
+            .await
+            .unwrap());
+        assert!(gallery
+            .report(ids[1], ReportReason::Offensive, None)
+            .await
+            .unwrap());
+        assert!(!gallery
+            .report(ids[2] + 1, ReportReason::Spam, None)
+            .await
+            .unwrap());
+
+        let flagged = gallery.flagged().await.unwrap();
+
+        assert_eq!(flagged.len(), 2);
+        assert_eq!(flagged[0].entry.id, ids[1]);
+        assert_eq!(flagged[0].reports.len(), 2);
+        assert_eq!(flagged[0].reports[0].reason, ReportReason::Copyright);
+        assert_eq!(
+            flagged[0].reports[0].details,
+            Some("I drew this".to_string())
+        );
+        assert_eq!(flagged[1].entry.id, ids[0]);
+
+        // a hidden conversion is left out of the queue and the gallery, but is kept
+        let hidden = gallery
+            .moderate(ids[1], ModerationAction::Hide)
+            .await
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(hidden.title, Some("Two".to_string()));
+        assert!(gallery.get(ids[1]).await.unwrap().unwrap().hidden);
+        assert!(!gallery
+            .report(ids[1], ReportReason::Spam, None)
+            .await
+            .unwrap());
+
+        let titles = |page: GalleryPage| -> Vec<Option<String>> {
+            page.entries.into_iter().map(|entry| entry.title).collect()
+        };
+
+        assert_eq!(
+            titles(gallery.search(&GallerySearch::default(), 1).await.unwrap()),
+            vec![Some("Three".to_string()), Some("One".to_string())]
+        );
+
+        // dismissing keeps the conversion in the gallery
+        gallery
+            .moderate(ids[0], ModerationAction::Dismiss)
+            .await
+            .unwrap();
+
+        assert!(gallery.flagged().await.unwrap().is_empty());
+        assert!(gallery.get(ids[0]).await.unwrap().is_some());
+
+        gallery
+            .moderate(ids[1], ModerationAction::Delete)
+            .await
+            .unwrap();
+
+        assert_eq!(gallery.get(ids[1]).await.unwrap(), None);
+        assert_eq!(
+            gallery
+                .moderate(ids[1], ModerationAction::Delete)
+                .await
+                .unwrap(),
+            None
+        );
+    }
+
+    // Verifies that the query of the gallery page is turned into a search that leaves out blank and unusable filters,
+    // and that the search is turned back into query parameters for the links to other pages
+    #[test]
+    fn test_gallery_query_search() {
+        let query = GalleryQuery {
+            page: Some(2),
+            q: Some("  castle ".to_string()),
+            charset: Some("braille".to_string()),
+            from: Some("2023-06-09".to_string()),
+            to: Some("June 10".to_string()),
+        };
+        let search = query.search();
+
+        assert_eq!(
+            search,
+            GallerySearch {
+                text: Some("castle".to_string()),
+                charset: Some(RenderCharset::Braille),
+                from: Some("2023-06-09".to_string()),
+                to: None,
+            }
+        );
+        assert_eq!(
+            search.to_query(),
+            "q=castle&charset=braille&from=2023-06-09&"
+        );
+
+        let query = GalleryQuery {
+            q: Some(" ".to_string()),
+            charset: Some("emoji".to_string()),
+            ..GalleryQuery::default()
+        };
+
+        assert_eq!(query.search(), GallerySearch::default());
+        assert_eq!(GallerySearch::default().to_query(), "");
+    }
+
+    // Verifies that the gallery is split into pages of conversions, newest first
+    #[actix_web::test]
+    async fn test_page() {
+        let (_directory, gallery) = temporary_gallery();
+        for i in 0..GALLERY_PAGE_SIZE + 2 {
+            gallery
+                .add(
+                    ConversionKind::ImageToAscii,
+                    &i.to_string(),
+                    "conversion_results/art.txt",
+                    None,
+                    &Attribution::default(),
+                    None,
+                )
+                .await
+                .unwrap();
+        }
+
+        let first_page = gallery.search(&GallerySearch::default(), 1).await.unwrap();
+
+        assert_eq!(first_page.entries.len(), GALLERY_PAGE_SIZE as usize);
+        assert_eq!(
+            first_page.entries[0].input_hash,
+            (GALLERY_PAGE_SIZE + 1).to_string()
+        );
+        assert_eq!(first_page.previous_page, None);
+        assert_eq!(first_page.next_page, Some(2));
+
+        let second_page = gallery.search(&GallerySearch::default(), 2).await.unwrap();
+
+        assert_eq!(second_page.entries.len(), 2);
+        assert_eq!(second_page.entries[1].input_hash, "0");
+        assert_eq!(second_page.previous_page, Some(1));
+        assert_eq!(second_page.next_page, None);
+
+        assert!(gallery
+            .search(&GallerySearch::default(), 3)
+            .await
+            .unwrap()
+            .entries
+            .is_empty());
+        assert_eq!(
+            gallery.all().await.unwrap().len(),
+            GALLERY_PAGE_SIZE as usize + 2
+        );
+    }
+}
@@ -0,0 +1,53 @@
+//! Module to tell an orchestrator, such as Docker or Kubernetes, whether the website is alive and whether it should be
+//! sent new requests.
+//!
+//! GET /healthz answers for as long as the server is running, while GET /readyz stops answering with success once the
+//! server has been asked to stop. When the server gets SIGTERM or SIGINT, [wait_for_shutdown_signal] returns and the
+//! server is marked as draining with [start_draining]. The server then stops accepting connections, gives the requests
+//! that are being answered up to the `shutdown_timeout_seconds` of the [super::config::AppConfig] to finish, and waits as
+//! long again for the background conversion jobs in the [super::jobs::JobQueue], so that a restart doesn't lose the
+//! conversions that were already started.
+//!
+//! Robert Peterson and Kelsey Werner 2023
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Whether the server has been asked to stop and is finishing the conversions it already started.
+static DRAINING: AtomicBool = AtomicBool::new(false);
+
+/// Function to mark the server as draining, after which it no longer reports itself as ready.
+pub fn start_draining() {
+    DRAINING.store(true, Ordering::SeqCst);
+}
+
+/// Function to check whether the server has been asked to stop.
+pub fn is_draining() -> bool {
+    DRAINING.load(Ordering::SeqCst)
+}
+
+/// Function to wait until the process is asked to stop, which is SIGTERM or SIGINT on Unix and Ctrl-C elsewhere.
+pub async fn wait_for_shutdown_signal() {
+    #[cfg(unix)]
+    {
+        use actix_web::rt::signal::unix::{signal, SignalKind};
+        use std::{future::poll_fn, task::Poll};
+
+        if let (Ok(mut terminate), Ok(mut interrupt)) = (
+            signal(SignalKind::terminate()),
+            signal(SignalKind::interrupt()),
+        ) {
+            poll_fn(|cx| {
+                if terminate.poll_recv(cx).is_ready() || interrupt.poll_recv(cx).is_ready() {
+                    Poll::Ready(())
+                } else {
+                    Poll::Pending
+                }
+            })
+            .await;
+            return;
+        }
+    }
+
+    // without the Unix signals, only Ctrl-C can stop the server
+    let _ = actix_web::rt::signal::ctrl_c().await;
+}
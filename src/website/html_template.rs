@@ -3,10 +3,74 @@
 //! This module formats the dynamic data needed for each template,
 //! then uses the Handlebars templating engine to render the template.
 //!
+//! Every page fills in the `layout.html` template, which wraps the page in the shared `partials/header.html`,
+//! `partials/nav.html` and `partials/footer.html` partials, so the markup around each page is written once. Pages add
+//! to the head of the layout with an inline `head` partial, and show notices and warnings with `partials/flash.html`.
+//! The context that every page can use, such as the version of the website, is added to the data of each template
+//! as `site` when it is rendered.
+//!
 //! Robert Peterson and Kelsey Werner 2023
 
-use handlebars::{Handlebars, RenderError};
+use super::formatting::{register_format_helpers, NumberFormat};
+use super::{
+    admin::DiskUsage,
+    attribution::Attribution,
+    gallery::{GalleryEntry, GallerySearch},
+    metrics_store::ConversionSummary,
+    user::Artwork,
+};
+use ascii_art_converter::converter::{options::RenderCharset, usage::ResourceUsage};
+use handlebars::{Handlebars, RenderError, TemplateError};
 use serde_json::{json, Value};
+use std::path::Path;
+
+/// The version of the website, which is shown in the footer of every page.
+pub const SITE_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Function to register the templates and partials in `template_directory` and the helpers they use with Handlebars.
+///
+/// Partials such as `partials/nav.html` are registered by their path in the directory without the extension, such as
+/// `partials/nav`. Counts, sizes and durations are formatted with `number_format`. Returns an error if a template
+/// can't be read or parsed.
+pub fn register_templates(
+    handlebars: &mut Handlebars,
+    template_directory: &Path,
+    number_format: NumberFormat,
+) -> Result<(), Box<TemplateError>> {
+    handlebars.register_templates_directory(".html", template_directory)?;
+    register_format_helpers(handlebars, number_format);
+
+    Ok(())
+}
+
+/// Function to escape ASCII art so it is shown verbatim inside a `<pre>` element of a page.
+///
+/// The characters HTML gives a meaning to, `&`, `<`, `>`, `"` and `'`, are written as character references, so ASCII art
+/// such as `<script>` is shown instead of run. Only line feeds, tabs and printable characters are kept as they are:
+/// carriage returns are dropped, and every other control character, such as the escape that starts a terminal color,
+/// is replaced with U+FFFD so it can't confuse the browser or the clipboard.
+pub fn escape_ascii_art(ascii: &str) -> String {
+    let mut escaped = String::with_capacity(ascii.len());
+    for character in ascii.chars() {
+        match character {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#x27;"),
+            '\n' | '\t' => escaped.push(character),
+            '\r' => {}
+            _ if character.is_control() => escaped.push(char::REPLACEMENT_CHARACTER),
+            _ => escaped.push(character),
+        }
+    }
+    escaped
+}
+
+/// Function to create the context that every page can use, which is added to the data of each template as `site`.
+fn site_context() -> Value {
+    json!({ "version": SITE_VERSION })
+}
 
 /// Enum to store the possible HTML templates that can be displayed.
 ///
@@ -19,12 +83,144 @@ pub enum HtmlTemplate<'a> {
     // book on page 235.
     /// [HtmlTemplate::AsciiToImageResult] is the template used to display an image that has been generated from ASCII text.
     ///
-    /// This variant stores a [String] that contains the route to the image being displayed.
-    AsciiToImageResult { image_result: String },
+    /// This variant stores a [String] that contains the route to the image being displayed, a [String] that contains
+    /// the route to the same image drawn twice as large for high-DPI screens, a [String] that contains
+    /// the route to an SVG version of the image that can be downloaded, an optional notice that tells the user
+    /// about any repairs made to their ASCII art, a [Vec] of [String] fields that each describe a character that was
+    /// removed or replaced while normalizing the ASCII art, an [Option] that contains the route to the page of the result that can be shared,
+    /// or [None] when the result couldn't be stored and the routes of the images are data URLs, and the [Attribution] the user gave the result.
+    AsciiToImageResult {
+        image_result: String,
+        image_result_2x: String,
+        svg_result: String,
+        notice: Option<&'a str>,
+        warnings: Vec<String>,
+        permalink: Option<String>,
+        attribution: Attribution,
+    },
     /// [HtmlTemplate::ImageToAsciiResult] is the template used to display ASCII art that has been generated from an image.
     ///
-    /// This variant stores a [String] that contains the text characters of the ASCII art being displayed.
-    ImageToAsciiResult { ascii_result: String },
+    /// This variant stores a [String] that contains the text characters of the ASCII art being displayed, a [String] that
+    /// contains the route to a text file of the ASCII art that can be downloaded, a [Vec] of [String] fields that each describe a transformation that was applied to the image, and a [String] that contains
+    /// the JSON pipeline of those transformations so that they can be replayed, a [String] that contains the JSON
+    /// manifest of every setting used for the conversion, a [String] that contains a link to the image to ASCII
+    /// form with those settings filled in so that they can be shared, a [Vec] of [String] fields that each describe how
+    /// the image was scaled without being asked to, an [Option] that contains the route to the page
+    /// of the result that can be shared, or [None] when the result couldn't be stored and the route of the text file is a
+    /// data URL, and the [Attribution] the user gave the result.
+    ImageToAsciiResult {
+        ascii_result: String,
+        text_result: String,
+        applied_stages: Vec<String>,
+        pipeline: String,
+        manifest: String,
+        share_link: String,
+        warnings: Vec<String>,
+        permalink: Option<String>,
+        attribution: Attribution,
+    },
+    /// [HtmlTemplate::ImageToAsciiForm] is the template used to display the form to submit an image.
+    ///
+    /// This variant stores a [String] that contains the JSON manifest of shared settings to fill in, which is empty
+    /// when no settings were shared, and the ASCII art width and [RenderCharset] that are selected on the form to start with.
+    ImageToAsciiForm {
+        manifest: String,
+        width: Option<u32>,
+        charset: RenderCharset,
+    },
+    /// [HtmlTemplate::AsciiToImageForm] is the template used to display the form to submit ASCII art.
+    ///
+    /// This variant stores a [bool] that is `true` when drawing each character as a gray pixel is selected on the form to start with.
+    AsciiToImageForm { render_pixels: bool },
+    /// [HtmlTemplate::TextToAsciiResult] is the template used to display a banner of large letters that has been generated from text.
+    ///
+    /// This variant stores a [String] that contains the text characters of the banner being displayed.
+    TextToAsciiResult { ascii_result: String },
+    /// [HtmlTemplate::TextToAsciiForm] is the template used to display the form to submit the text of a banner.
+    ///
+    /// This variant stores the [RenderCharset] that is selected on the form to start with.
+    TextToAsciiForm { charset: RenderCharset },
+    /// [HtmlTemplate::QrResult] is the template used to display a QR code that has been drawn with block characters.
+    ///
+    /// This variant stores a [String] that contains the block characters of the QR code, and a [String] that contains the
+    /// data URL of the QR code rasterized into a PNG image that can be scanned or downloaded.
+    #[cfg(feature = "qr")]
+    QrResult {
+        ascii_result: String,
+        image_result: String,
+    },
+    /// [HtmlTemplate::QrForm] is the template used to display the forms to submit a link, or a QR code drawn with block characters.
+    #[cfg(feature = "qr")]
+    QrForm,
+    /// [HtmlTemplate::Gallery] is the template used to display a page of past conversions.
+    ///
+    /// This variant stores a [Vec] of the [GalleryEntry] conversions on the page, the numbers of the pages before
+    /// and after it, which are [None] on the first and last page, and the [GallerySearch] the conversions match.
+    Gallery {
+        entries: Vec<GalleryEntry>,
+        previous_page: Option<u32>,
+        next_page: Option<u32>,
+        search: GallerySearch,
+    },
+    /// [HtmlTemplate::GalleryDetail] is the template used to display a single past conversion.
+    ///
+    /// This variant stores the [GalleryEntry] of the conversion, and an [Option] that stores the text of the ASCII art
+    /// when the result of the conversion is ASCII art or [None] when the result is an image.
+    GalleryDetail {
+        entry: GalleryEntry,
+        ascii_result: Option<String>,
+    },
+    /// [HtmlTemplate::SavedResult] is the template used to display the result of a conversion from its shared link.
+    ///
+    /// This variant stores a [String] that contains the route to the page itself, the [Option] routes to the PNG and SVG
+    /// images when the result is an image along with the PNG drawn twice as large when the result has one, the [Option] route to the text file along with the text of the ASCII art
+    /// when the result is ASCII art, a [String] that contains the route to the ZIP archive of every file of the result, and the
+    /// [Attribution] the user gave the result, along with the [ResourceUsage] of an image to ASCII art conversion when it was
+    /// measured.
+    SavedResult {
+        permalink: String,
+        image_result: Option<String>,
+        image_result_2x: Option<String>,
+        svg_result: Option<String>,
+        text_result: Option<String>,
+        ascii_result: Option<String>,
+        bundle_link: String,
+        attribution: Attribution,
+        usage: Option<ResourceUsage>,
+    },
+    /// [HtmlTemplate::ReportReceived] is the template used to thank a user for reporting a conversion.
+    ///
+    /// This variant stores the id of the conversion that was reported, so the page can link back to it.
+    ReportReceived { entry_id: i64 },
+    /// [HtmlTemplate::ConversionJob] is the template used to show the progress of a conversion job of a large upload.
+    ///
+    /// This variant stores the id of the job, so the page can refresh itself, and the percent of the job that is done.
+    ConversionJob { job_id: String, progress: u8 },
+    /// [HtmlTemplate::Account] is the template used to display the forms to sign in and to register.
+    ///
+    /// This variant stores an [Option] that contains the message of why the user couldn't sign in or register, or [None]
+    /// when the page is first shown.
+    Account { error_message: Option<String> },
+    /// [HtmlTemplate::MyArt] is the template used to display the ASCII art a signed in user saved, with the forms to change it.
+    ///
+    /// This variant stores the name of the user, a [Vec] of the [Artwork]s they saved, the most recently changed first, and
+    /// an [Option] that contains the message of why their art couldn't be saved, or [None] when it was.
+    MyArt {
+        username: String,
+        artworks: Vec<Artwork>,
+        error_message: Option<String>,
+    },
+    /// [HtmlTemplate::Admin] is the template used to display the dashboard of the operators, with the form to purge old results.
+    ///
+    /// This variant stores the [ConversionSummary] of every conversion, the [DiskUsage] of the stored results, the time to
+    /// live of the results in hours, which the purge form starts with, and an [Option] that contains the number of results
+    /// the last purge removed, or [None] if the page wasn't opened after one.
+    Admin {
+        summary: ConversionSummary,
+        disk_usage: DiskUsage,
+        result_ttl_hours: u64,
+        purged: Option<u64>,
+    },
     /// [HtmlTemplate::Error] is the template used to display an error with a single error message.
     ///
     /// This variant stores a [String] that contains the error message and
@@ -34,6 +230,11 @@ pub enum HtmlTemplate<'a> {
         try_again_link: &'a str,
     },
 
+    /// [HtmlTemplate::TooManyRequests] is the template used to ask a client that made too many conversions to slow down.
+    ///
+    /// This variant stores the number of seconds until the client may make another conversion.
+    TooManyRequests { retry_after: u64 },
+
     /// [HtmlTemplate::Error] is the template used to display an error with separate sections of an error message.
     ///
     /// This variant stores two [String] fields that contain the separate sections of the error message and
@@ -43,6 +244,29 @@ pub enum HtmlTemplate<'a> {
         error_message2: &'a str,
         try_again_link: &'a str,
     },
+
+    /// [HtmlTemplate::ErrorWithDetails] is the template used to display an error with a list of details about the error.
+    ///
+    /// This variant stores a [String] that contains the error message, a [Vec] of [String] fields that each contain
+    /// a detail about the error, and a [String] that contains a route to another page of the site to retry the failed operation.
+    ErrorWithDetails {
+        error_message: String,
+        error_details: Vec<String>,
+        try_again_link: &'a str,
+    },
+
+    /// [HtmlTemplate::ErrorWithLocation] is the template used to display an error that was caused by a single character of some text.
+    ///
+    /// This variant stores a [String] that contains the error message, the line and column of the character counted from 1,
+    /// a [String] that contains the line of text the character is on, and a [String] that contains a route to another page
+    /// of the site to retry the failed operation.
+    ErrorWithLocation {
+        error_message: String,
+        line: u32,
+        column: u32,
+        excerpt: String,
+        try_again_link: &'a str,
+    },
 }
 
 impl HtmlTemplate<'_> {
@@ -55,18 +279,116 @@ impl HtmlTemplate<'_> {
         // The syntax for pattern matching enums with struct variants was found in the "Programming in Rust"
         // book on page 243.
         match self {
-            HtmlTemplate::AsciiToImageResult { image_result } => {
-                json!({ "image_result": image_result })
+            HtmlTemplate::AsciiToImageResult {
+                image_result,
+                image_result_2x,
+                svg_result,
+                notice,
+                warnings,
+                permalink,
+                attribution,
+            } => {
+                json!({ "image_result": image_result, "image_result_2x": image_result_2x, "svg_result": svg_result, "notice": notice, "warnings": warnings, "permalink": permalink, "attribution": attribution })
+            }
+            HtmlTemplate::ImageToAsciiResult {
+                ascii_result,
+                text_result,
+                applied_stages,
+                pipeline,
+                manifest,
+                share_link,
+                warnings,
+                permalink,
+                attribution,
+            } => {
+                // the ASCII art is escaped here and rendered as is by the template, so no markup in it reaches the page
+                json!({ "ascii_result": escape_ascii_art(ascii_result), "text_result": text_result, "applied_stages": applied_stages, "pipeline": pipeline, "manifest": manifest, "share_link": share_link, "warnings": warnings, "permalink": permalink, "attribution": attribution })
+            }
+            HtmlTemplate::ImageToAsciiForm {
+                manifest,
+                width,
+                charset,
+            } => {
+                json!({ "manifest": manifest, "width": width, "charset": charset })
+            }
+            HtmlTemplate::AsciiToImageForm { render_pixels } => {
+                json!({ "render_pixels": render_pixels })
             }
-            HtmlTemplate::ImageToAsciiResult { ascii_result } => {
+            HtmlTemplate::TextToAsciiResult { ascii_result } => {
                 json!({ "ascii_result": ascii_result })
             }
+            HtmlTemplate::TextToAsciiForm { charset } => {
+                json!({ "charset": charset })
+            }
+            #[cfg(feature = "qr")]
+            HtmlTemplate::QrResult {
+                ascii_result,
+                image_result,
+            } => {
+                json!({ "ascii_result": ascii_result, "image_result": image_result })
+            }
+            #[cfg(feature = "qr")]
+            HtmlTemplate::QrForm => json!({}),
+            HtmlTemplate::Gallery {
+                entries,
+                previous_page,
+                next_page,
+                search,
+            } => {
+                json!({ "entries": entries, "previous_page": previous_page, "next_page": next_page, "search": search, "search_query": search.to_query() })
+            }
+            HtmlTemplate::GalleryDetail {
+                entry,
+                ascii_result,
+            } => {
+                json!({ "entry": entry, "ascii_result": ascii_result })
+            }
+            HtmlTemplate::SavedResult {
+                permalink,
+                image_result,
+                image_result_2x,
+                svg_result,
+                text_result,
+                ascii_result,
+                bundle_link,
+                attribution,
+                usage,
+            } => {
+                json!({ "permalink": permalink, "image_result": image_result, "image_result_2x": image_result_2x, "svg_result": svg_result, "text_result": text_result, "ascii_result": ascii_result, "bundle_link": bundle_link, "attribution": attribution, "usage": usage })
+            }
+            HtmlTemplate::ReportReceived { entry_id } => {
+                json!({ "entry_id": entry_id })
+            }
+            HtmlTemplate::ConversionJob { job_id, progress } => {
+                json!({ "job_id": job_id, "progress": progress })
+            }
+            HtmlTemplate::Account { error_message } => {
+                json!({ "error_message": error_message })
+            }
+            HtmlTemplate::MyArt {
+                username,
+                artworks,
+                error_message,
+            } => {
+                json!({ "username": username, "artworks": artworks, "error_message": error_message })
+            }
+            HtmlTemplate::Admin {
+                summary,
+                disk_usage,
+                result_ttl_hours,
+                purged,
+            } => {
+                json!({ "summary": summary, "disk_usage": disk_usage, "result_ttl_hours": result_ttl_hours, "purged": purged })
+            }
             HtmlTemplate::Error {
                 error_message,
                 try_again_link,
             } => {
                 json!({ "error_message": error_message, "try_again_link": try_again_link })
             }
+            HtmlTemplate::TooManyRequests { retry_after } => {
+                json!({ "retry_after": retry_after })
+            }
             HtmlTemplate::ErrorMultiLine {
                 error_message,
                 error_message2,
@@ -74,6 +396,24 @@ impl HtmlTemplate<'_> {
             } => {
                 json!({ "error_message": error_message, "error_message2": error_message2, "try_again_link": try_again_link })
             }
+            HtmlTemplate::ErrorWithDetails {
+                error_message,
+                error_details,
+                try_again_link,
+            } => {
+                json!({ "error_message": error_message, "error_details": error_details, "try_again_link": try_again_link })
+            }
+            HtmlTemplate::ErrorWithLocation {
+                error_message,
+                line,
+                column,
+                excerpt,
+                try_again_link,
+            } => {
+                // the marker points at the character from under the excerpt
+                let marker = format!("{}^", " ".repeat(column.saturating_sub(1) as usize));
+                json!({ "error_message": error_message, "error_location": { "line": line, "column": column, "excerpt": excerpt, "marker": marker }, "try_again_link": try_again_link })
+            }
         }
     }
 
@@ -81,11 +421,31 @@ impl HtmlTemplate<'_> {
     ///
     /// Handlebars uses the [str] reference that is returned from this function to identify the
     /// specific HTML template file to render.
-    fn get_template_name(&self) -> &str {
+    pub fn get_template_name(&self) -> &str {
         match self {
             HtmlTemplate::AsciiToImageResult { .. } => "ascii-to-image-result",
             HtmlTemplate::ImageToAsciiResult { .. } => "image-to-ascii-result",
-            HtmlTemplate::Error { .. } | HtmlTemplate::ErrorMultiLine { .. } => "error",
+            HtmlTemplate::ImageToAsciiForm { .. } => "image-to-ascii",
+            HtmlTemplate::AsciiToImageForm { .. } => "ascii-to-image",
+            HtmlTemplate::TextToAsciiResult { .. } => "text-to-ascii-result",
+            HtmlTemplate::TextToAsciiForm { .. } => "text-to-ascii",
+            #[cfg(feature = "qr")]
+            HtmlTemplate::QrResult { .. } => "qr-result",
+            #[cfg(feature = "qr")]
+            HtmlTemplate::QrForm => "qr",
+            HtmlTemplate::Gallery { .. } => "gallery",
+            HtmlTemplate::GalleryDetail { .. } => "gallery-detail",
+            HtmlTemplate::SavedResult { .. } => "result",
+            HtmlTemplate::ReportReceived { .. } => "report-received",
+            HtmlTemplate::ConversionJob { .. } => "conversion-job",
+            HtmlTemplate::Account { .. } => "account",
+            HtmlTemplate::MyArt { .. } => "my-art",
+            HtmlTemplate::Admin { .. } => "admin",
+            HtmlTemplate::TooManyRequests { .. } => "too-many-requests",
+            HtmlTemplate::Error { .. }
+            | HtmlTemplate::ErrorMultiLine { .. }
+            | HtmlTemplate::ErrorWithDetails { .. }
+            | HtmlTemplate::ErrorWithLocation { .. } => "error",
         }
     }
 
@@ -96,10 +456,27 @@ impl HtmlTemplate<'_> {
     /// called within endpoints in the web app.
     pub fn is_error_template(&self) -> bool {
         match self {
-            HtmlTemplate::AsciiToImageResult { .. } | HtmlTemplate::ImageToAsciiResult { .. } => {
-                false
-            }
-            HtmlTemplate::Error { .. } | HtmlTemplate::ErrorMultiLine { .. } => true,
+            HtmlTemplate::AsciiToImageResult { .. }
+            | HtmlTemplate::ImageToAsciiResult { .. }
+            | HtmlTemplate::ImageToAsciiForm { .. }
+            | HtmlTemplate::AsciiToImageForm { .. }
+            | HtmlTemplate::TextToAsciiResult { .. }
+            | HtmlTemplate::TextToAsciiForm { .. }
+            | HtmlTemplate::Gallery { .. }
+            | HtmlTemplate::GalleryDetail { .. }
+            | HtmlTemplate::SavedResult { .. }
+            | HtmlTemplate::ReportReceived { .. }
+            | HtmlTemplate::ConversionJob { .. }
+            | HtmlTemplate::Account { .. }
+            | HtmlTemplate::MyArt { .. }
+            | HtmlTemplate::Admin { .. } => false,
+            #[cfg(feature = "qr")]
+            HtmlTemplate::QrResult { .. } | HtmlTemplate::QrForm => false,
+            HtmlTemplate::TooManyRequests { .. }
+            | HtmlTemplate::Error { .. }
+            | HtmlTemplate::ErrorMultiLine { .. }
+            | HtmlTemplate::ErrorWithDetails { .. }
+            | HtmlTemplate::ErrorWithLocation { .. } => true,
         }
     }
 
@@ -109,28 +486,231 @@ impl HtmlTemplate<'_> {
     /// to render the data provided in the [HtmlTemplate] variants within the corresponding HTML template file.
     /// Returns `Ok(String)` that contains the response body that will be serverd by web app endpoints.
     pub fn render_template(&self, hb: &Handlebars) -> Result<String, RenderError> {
-        hb.render(self.get_template_name(), &self.format_template_data())
+        let mut data = self.format_template_data();
+        data["site"] = site_context();
+        hb.render(self.get_template_name(), &data)
     }
 }
 
 // Tests
 
+// Creates a conversion as it would be stored in the gallery
+#[cfg(test)]
+fn test_gallery_entry(kind: super::gallery::ConversionKind, output_path: &str) -> GalleryEntry {
+    GalleryEntry {
+        id: 7,
+        kind,
+        input_hash: "abc123".to_string(),
+        output_path: output_path.to_string(),
+        title: Some("Castle".to_string()),
+        created_at: "2023-06-09 04:58:50".to_string(),
+        author: None,
+        license: None,
+        charset: None,
+        hidden: false,
+    }
+}
+
+// Creates the attribution of a conversion that was given an author and a license
+#[cfg(test)]
+fn test_attribution() -> Attribution {
+    Attribution {
+        author: Some("Kelsey".to_string()),
+        license: Some(super::attribution::License::CcBySa4),
+    }
+}
+
+// Creates the totals of a few conversions, one of which failed
+#[cfg(test)]
+fn test_summary() -> ConversionSummary {
+    ConversionSummary {
+        conversions: 3,
+        ascii_to_image: 1,
+        image_to_ascii: 2,
+        failures: 1,
+        errors: vec![super::metrics_store::ErrorCount {
+            error: "TooLarge".to_string(),
+            count: 1,
+        }],
+    }
+}
+
+// Creates ASCII art as it would be saved by a user
+#[cfg(test)]
+fn test_artwork() -> Artwork {
+    Artwork {
+        id: 3,
+        user_id: 1,
+        name: "Castle".to_string(),
+        ascii: "/\\\n||".to_string(),
+        created_at: "2023-06-09 04:58:50".to_string(),
+        updated_at: "2023-06-10 04:58:50".to_string(),
+    }
+}
+
 // Verifies format_template_data() function creates correctly formatted data for each HtmlTemplate variant
 #[test]
 fn test_format_template_data() {
     let mut html_template = HtmlTemplate::AsciiToImageResult {
         image_result: "conversion_results/image_file_name.png".to_string(),
+        image_result_2x: "conversion_results/image_file_name.2x.png".to_string(),
+        svg_result: "conversion_results/image_file_name.svg".to_string(),
+        notice: None,
+        warnings: vec![],
+        permalink: Some("/result/abc".to_string()),
+        attribution: Attribution::default(),
     };
     let mut result = html_template.format_template_data();
-    let mut expected_result = json!({ "image_result": "conversion_results/image_file_name.png" });
+    let mut expected_result = json!({ "image_result": "conversion_results/image_file_name.png", "image_result_2x": "conversion_results/image_file_name.2x.png", "svg_result": "conversion_results/image_file_name.svg", "notice": null, "warnings": [], "permalink": "/result/abc", "attribution": { "author": null, "license": null } });
 
     assert_eq!(result, expected_result);
 
     html_template = HtmlTemplate::ImageToAsciiResult {
         ascii_result: "><(((('>".to_string(),
+        text_result: "conversion_results/ascii_file_name.txt".to_string(),
+        applied_stages: vec!["Rotate 180°".to_string()],
+        pipeline: r#"[{"rotate":"clockwise180"}]"#.to_string(),
+        manifest: r#"{"version":1}"#.to_string(),
+        share_link: "/image-to-ascii?manifest=%7B%22version%22%3A1%7D".to_string(),
+        warnings: vec![],
+        permalink: Some("/result/abc".to_string()),
+        attribution: Attribution::default(),
+    };
+    result = html_template.format_template_data();
+    expected_result = json!({ "ascii_result": "&gt;&lt;((((&#x27;&gt;", "text_result": "conversion_results/ascii_file_name.txt", "applied_stages": ["Rotate 180°"], "pipeline": r#"[{"rotate":"clockwise180"}]"#, "manifest": r#"{"version":1}"#, "share_link": "/image-to-ascii?manifest=%7B%22version%22%3A1%7D", "warnings": [], "permalink": "/result/abc", "attribution": { "author": null, "license": null } });
+
+    assert_eq!(result, expected_result);
+
+    html_template = HtmlTemplate::ImageToAsciiForm {
+        manifest: r#"{"version":1}"#.to_string(),
+        width: Some(80),
+        charset: RenderCharset::Braille,
+    };
+    result = html_template.format_template_data();
+    expected_result = json!({ "manifest": r#"{"version":1}"#, "width": 80, "charset": "braille" });
+
+    assert_eq!(result, expected_result);
+
+    html_template = HtmlTemplate::AsciiToImageForm {
+        render_pixels: true,
+    };
+    result = html_template.format_template_data();
+    expected_result = json!({ "render_pixels": true });
+
+    assert_eq!(result, expected_result);
+
+    html_template = HtmlTemplate::TextToAsciiResult {
+        ascii_result: "$$".to_string(),
+    };
+    result = html_template.format_template_data();
+    expected_result = json!({ "ascii_result": "$$" });
+
+    assert_eq!(result, expected_result);
+
+    html_template = HtmlTemplate::TextToAsciiForm {
+        charset: RenderCharset::Braille,
+    };
+    result = html_template.format_template_data();
+    expected_result = json!({ "charset": "braille" });
+
+    assert_eq!(result, expected_result);
+
+    html_template = HtmlTemplate::Gallery {
+        entries: vec![test_gallery_entry(
+            super::gallery::ConversionKind::AsciiToImage,
+            "conversion_results/image_file_name.png",
+        )],
+        previous_page: None,
+        next_page: Some(2),
+        search: GallerySearch {
+            text: Some("Castle".to_string()),
+            charset: Some(RenderCharset::Braille),
+            ..GallerySearch::default()
+        },
     };
     result = html_template.format_template_data();
-    expected_result = json!({ "ascii_result": "><(((('>" });
+    expected_result = json!({ "entries": [{ "id": 7, "kind": "ascii-to-image", "input_hash": "abc123", "output_path": "conversion_results/image_file_name.png", "title": "Castle", "created_at": "2023-06-09 04:58:50", "author": null, "license": null, "charset": null, "hidden": false }], "previous_page": null, "next_page": 2, "search": { "text": "Castle", "charset": "braille", "from": null, "to": null }, "search_query": "q=Castle&charset=braille&" });
+
+    assert_eq!(result, expected_result);
+
+    html_template = HtmlTemplate::GalleryDetail {
+        entry: test_gallery_entry(
+            super::gallery::ConversionKind::ImageToAscii,
+            "conversion_results/ascii_file_name.txt",
+        ),
+        ascii_result: Some("><(((('>".to_string()),
+    };
+    result = html_template.format_template_data();
+    expected_result = json!({ "entry": { "id": 7, "kind": "image-to-ascii", "input_hash": "abc123", "output_path": "conversion_results/ascii_file_name.txt", "title": "Castle", "created_at": "2023-06-09 04:58:50", "author": null, "license": null, "charset": null, "hidden": false }, "ascii_result": "><(((('>" });
+
+    assert_eq!(result, expected_result);
+
+    html_template = HtmlTemplate::SavedResult {
+        permalink: "/result/abc".to_string(),
+        image_result: None,
+        image_result_2x: None,
+        svg_result: None,
+        text_result: Some("conversion_results/abc.txt".to_string()),
+        ascii_result: Some("><(((('>".to_string()),
+        bundle_link: "/r/abc/bundle.zip".to_string(),
+        attribution: test_attribution(),
+        usage: None,
+    };
+    result = html_template.format_template_data();
+    expected_result = json!({ "permalink": "/result/abc", "image_result": null, "image_result_2x": null, "svg_result": null, "text_result": "conversion_results/abc.txt", "ascii_result": "><(((('>", "bundle_link": "/r/abc/bundle.zip", "attribution": { "author": "Kelsey", "license": "cc-by-sa-4.0" }, "usage": null });
+
+    assert_eq!(result, expected_result);
+
+    html_template = HtmlTemplate::ReportReceived { entry_id: 7 };
+    result = html_template.format_template_data();
+    expected_result = json!({ "entry_id": 7 });
+
+    assert_eq!(result, expected_result);
+
+    html_template = HtmlTemplate::ConversionJob {
+        job_id: "abc".to_string(),
+        progress: 42,
+    };
+    result = html_template.format_template_data();
+    expected_result = json!({ "job_id": "abc", "progress": 42 });
+
+    assert_eq!(result, expected_result);
+
+    html_template = HtmlTemplate::Account {
+        error_message: Some("Wrong password".to_string()),
+    };
+    result = html_template.format_template_data();
+    expected_result = json!({ "error_message": "Wrong password" });
+
+    assert_eq!(result, expected_result);
+
+    html_template = HtmlTemplate::MyArt {
+        username: "kelsey".to_string(),
+        artworks: vec![test_artwork()],
+        error_message: None,
+    };
+    result = html_template.format_template_data();
+    expected_result = json!({ "username": "kelsey", "artworks": [{ "id": 3, "user_id": 1, "name": "Castle", "ascii": "/\\\n||", "created_at": "2023-06-09 04:58:50", "updated_at": "2023-06-10 04:58:50" }], "error_message": null });
+
+    assert_eq!(result, expected_result);
+
+    html_template = HtmlTemplate::Admin {
+        summary: test_summary(),
+        disk_usage: DiskUsage {
+            files: 2,
+            bytes: 2048,
+        },
+        result_ttl_hours: 168,
+        purged: Some(4),
+    };
+    result = html_template.format_template_data();
+    expected_result = json!({ "summary": { "conversions": 3, "ascii_to_image": 1, "image_to_ascii": 2, "failures": 1, "errors": [{ "error": "TooLarge", "count": 1 }] }, "disk_usage": { "files": 2, "bytes": 2048 }, "result_ttl_hours": 168, "purged": 4 });
+
+    assert_eq!(result, expected_result);
+
+    html_template = HtmlTemplate::TooManyRequests { retry_after: 12 };
+    result = html_template.format_template_data();
+    expected_result = json!({ "retry_after": 12 });
 
     assert_eq!(result, expected_result);
 
@@ -153,6 +733,28 @@ fn test_format_template_data() {
     expected_result = json!({ "error_message": "This is a test error message.", "error_message2": "This is a test error message part two.", "try_again_link": "/try_again" });
 
     assert_eq!(result, expected_result);
+
+    html_template = HtmlTemplate::ErrorWithDetails {
+        error_message: "This is a test error message.".to_string(),
+        error_details: vec!["First detail.".to_string(), "Second detail.".to_string()],
+        try_again_link: "/try_again",
+    };
+    result = html_template.format_template_data();
+    expected_result = json!({ "error_message": "This is a test error message.", "error_details": ["First detail.", "Second detail."], "try_again_link": "/try_again" });
+
+    assert_eq!(result, expected_result);
+
+    html_template = HtmlTemplate::ErrorWithLocation {
+        error_message: "This is a test error message.".to_string(),
+        line: 2,
+        column: 4,
+        excerpt: "@@@=@@".to_string(),
+        try_again_link: "/try_again",
+    };
+    result = html_template.format_template_data();
+    expected_result = json!({ "error_message": "This is a test error message.", "error_location": { "line": 2, "column": 4, "excerpt": "@@@=@@", "marker": "   ^" }, "try_again_link": "/try_again" });
+
+    assert_eq!(result, expected_result);
 }
 
 // Verifies get_template_name() function identifies the correct name of the HTML template associated with each HtmlTemplate variant
@@ -160,6 +762,12 @@ fn test_format_template_data() {
 fn test_get_template_name() {
     let mut html_template = HtmlTemplate::AsciiToImageResult {
         image_result: "conversion_results/image_file_name.png".to_string(),
+        image_result_2x: "conversion_results/image_file_name.2x.png".to_string(),
+        svg_result: "conversion_results/image_file_name.svg".to_string(),
+        notice: None,
+        warnings: vec![],
+        permalink: Some("/result/abc".to_string()),
+        attribution: Attribution::default(),
     };
     let mut result = html_template.get_template_name();
 
@@ -167,11 +775,129 @@ fn test_get_template_name() {
 
     html_template = HtmlTemplate::ImageToAsciiResult {
         ascii_result: "><(((('>".to_string(),
+        text_result: "conversion_results/ascii_file_name.txt".to_string(),
+        applied_stages: vec!["Rotate 180°".to_string()],
+        pipeline: r#"[{"rotate":"clockwise180"}]"#.to_string(),
+        manifest: r#"{"version":1}"#.to_string(),
+        share_link: "/image-to-ascii?manifest=%7B%22version%22%3A1%7D".to_string(),
+        warnings: vec![],
+        permalink: Some("/result/abc".to_string()),
+        attribution: Attribution::default(),
     };
     result = html_template.get_template_name();
 
     assert_eq!(result, "image-to-ascii-result");
 
+    html_template = HtmlTemplate::ImageToAsciiForm {
+        manifest: String::new(),
+        width: None,
+        charset: RenderCharset::Ascii,
+    };
+    result = html_template.get_template_name();
+
+    assert_eq!(result, "image-to-ascii");
+
+    html_template = HtmlTemplate::AsciiToImageForm {
+        render_pixels: false,
+    };
+    result = html_template.get_template_name();
+
+    assert_eq!(result, "ascii-to-image");
+
+    html_template = HtmlTemplate::TextToAsciiResult {
+        ascii_result: "$$".to_string(),
+    };
+    result = html_template.get_template_name();
+
+    assert_eq!(result, "text-to-ascii-result");
+
+    html_template = HtmlTemplate::TextToAsciiForm {
+        charset: RenderCharset::Ascii,
+    };
+    result = html_template.get_template_name();
+
+    assert_eq!(result, "text-to-ascii");
+
+    html_template = HtmlTemplate::Gallery {
+        entries: Vec::new(),
+        previous_page: None,
+        next_page: None,
+        search: GallerySearch::default(),
+    };
+    result = html_template.get_template_name();
+
+    assert_eq!(result, "gallery");
+
+    html_template = HtmlTemplate::GalleryDetail {
+        entry: test_gallery_entry(
+            super::gallery::ConversionKind::AsciiToImage,
+            "conversion_results/image_file_name.png",
+        ),
+        ascii_result: None,
+    };
+    result = html_template.get_template_name();
+
+    assert_eq!(result, "gallery-detail");
+
+    html_template = HtmlTemplate::SavedResult {
+        permalink: "/result/abc".to_string(),
+        image_result: Some("conversion_results/abc.png".to_string()),
+        image_result_2x: Some("conversion_results/abc.2x.png".to_string()),
+        svg_result: Some("conversion_results/abc.svg".to_string()),
+        text_result: None,
+        ascii_result: None,
+        bundle_link: "/r/abc/bundle.zip".to_string(),
+        attribution: Attribution::default(),
+        usage: None,
+    };
+    result = html_template.get_template_name();
+
+    assert_eq!(result, "result");
+
+    html_template = HtmlTemplate::ReportReceived { entry_id: 7 };
+    result = html_template.get_template_name();
+
+    assert_eq!(result, "report-received");
+
+    html_template = HtmlTemplate::ConversionJob {
+        job_id: "abc".to_string(),
+        progress: 42,
+    };
+    result = html_template.get_template_name();
+
+    assert_eq!(result, "conversion-job");
+
+    html_template = HtmlTemplate::Account {
+        error_message: None,
+    };
+    result = html_template.get_template_name();
+
+    assert_eq!(result, "account");
+
+    html_template = HtmlTemplate::MyArt {
+        username: "kelsey".to_string(),
+        artworks: vec![],
+        error_message: None,
+    };
+    result = html_template.get_template_name();
+
+    assert_eq!(result, "my-art");
+
+    html_template = HtmlTemplate::Admin {
+        summary: ConversionSummary::default(),
+        disk_usage: DiskUsage::default(),
+        result_ttl_hours: 168,
+        purged: None,
+    };
+    result = html_template.get_template_name();
+
+    assert_eq!(result, "admin");
+
+    html_template = HtmlTemplate::TooManyRequests { retry_after: 12 };
+    result = html_template.get_template_name();
+
+    assert_eq!(result, "too-many-requests");
+
     html_template = HtmlTemplate::Error {
         error_message: "This is a test error message.",
         try_again_link: "/try_again",
@@ -188,6 +914,26 @@ fn test_get_template_name() {
     result = html_template.get_template_name();
 
     assert_eq!(result, "error");
+
+    html_template = HtmlTemplate::ErrorWithDetails {
+        error_message: "This is a test error message.".to_string(),
+        error_details: vec!["First detail.".to_string()],
+        try_again_link: "/try_again",
+    };
+    result = html_template.get_template_name();
+
+    assert_eq!(result, "error");
+
+    html_template = HtmlTemplate::ErrorWithLocation {
+        error_message: "This is a test error message.".to_string(),
+        line: 1,
+        column: 1,
+        excerpt: "=".to_string(),
+        try_again_link: "/try_again",
+    };
+    result = html_template.get_template_name();
+
+    assert_eq!(result, "error");
 }
 
 // Verifies that is_error_template() function is correctly able to identify whether each HtmlTemplate variant
@@ -196,6 +942,12 @@ fn test_get_template_name() {
 fn test_is_error_template() {
     let mut html_template = HtmlTemplate::AsciiToImageResult {
         image_result: "conversion_results/image_file_name.png".to_string(),
+        image_result_2x: "conversion_results/image_file_name.2x.png".to_string(),
+        svg_result: "conversion_results/image_file_name.svg".to_string(),
+        notice: None,
+        warnings: vec![],
+        permalink: Some("/result/abc".to_string()),
+        attribution: Attribution::default(),
     };
     let mut result = html_template.is_error_template();
 
@@ -203,11 +955,129 @@ fn test_is_error_template() {
 
     html_template = HtmlTemplate::ImageToAsciiResult {
         ascii_result: "><(((('>".to_string(),
+        text_result: "conversion_results/ascii_file_name.txt".to_string(),
+        applied_stages: vec!["Rotate 180°".to_string()],
+        pipeline: r#"[{"rotate":"clockwise180"}]"#.to_string(),
+        manifest: r#"{"version":1}"#.to_string(),
+        share_link: "/image-to-ascii?manifest=%7B%22version%22%3A1%7D".to_string(),
+        warnings: vec![],
+        permalink: Some("/result/abc".to_string()),
+        attribution: Attribution::default(),
+    };
+    result = html_template.is_error_template();
+
+    assert!(!result);
+
+    html_template = HtmlTemplate::ImageToAsciiForm {
+        manifest: String::new(),
+        width: None,
+        charset: RenderCharset::Ascii,
+    };
+    result = html_template.is_error_template();
+
+    assert!(!result);
+
+    html_template = HtmlTemplate::AsciiToImageForm {
+        render_pixels: false,
+    };
+    result = html_template.is_error_template();
+
+    assert!(!result);
+
+    html_template = HtmlTemplate::TextToAsciiResult {
+        ascii_result: "$$".to_string(),
+    };
+    result = html_template.is_error_template();
+
+    assert!(!result);
+
+    html_template = HtmlTemplate::TextToAsciiForm {
+        charset: RenderCharset::Ascii,
+    };
+    result = html_template.is_error_template();
+
+    assert!(!result);
+
+    html_template = HtmlTemplate::Gallery {
+        entries: Vec::new(),
+        previous_page: None,
+        next_page: None,
+        search: GallerySearch::default(),
+    };
+    result = html_template.is_error_template();
+
+    assert!(!result);
+
+    html_template = HtmlTemplate::GalleryDetail {
+        entry: test_gallery_entry(
+            super::gallery::ConversionKind::AsciiToImage,
+            "conversion_results/image_file_name.png",
+        ),
+        ascii_result: None,
+    };
+    result = html_template.is_error_template();
+
+    assert!(!result);
+
+    html_template = HtmlTemplate::SavedResult {
+        permalink: "/result/abc".to_string(),
+        image_result: Some("conversion_results/abc.png".to_string()),
+        image_result_2x: Some("conversion_results/abc.2x.png".to_string()),
+        svg_result: Some("conversion_results/abc.svg".to_string()),
+        text_result: None,
+        ascii_result: None,
+        bundle_link: "/r/abc/bundle.zip".to_string(),
+        attribution: Attribution::default(),
+        usage: None,
+    };
+    result = html_template.is_error_template();
+
+    assert!(!result);
+
+    html_template = HtmlTemplate::ReportReceived { entry_id: 7 };
+    result = html_template.is_error_template();
+
+    assert!(!result);
+
+    html_template = HtmlTemplate::ConversionJob {
+        job_id: "abc".to_string(),
+        progress: 42,
+    };
+    result = html_template.is_error_template();
+
+    assert!(!result);
+
+    html_template = HtmlTemplate::Account {
+        error_message: Some("Wrong password".to_string()),
+    };
+    result = html_template.is_error_template();
+
+    assert!(!result);
+
+    html_template = HtmlTemplate::MyArt {
+        username: "kelsey".to_string(),
+        artworks: vec![],
+        error_message: None,
+    };
+    result = html_template.is_error_template();
+
+    assert!(!result);
+
+    html_template = HtmlTemplate::Admin {
+        summary: ConversionSummary::default(),
+        disk_usage: DiskUsage::default(),
+        result_ttl_hours: 168,
+        purged: None,
     };
     result = html_template.is_error_template();
 
     assert!(!result);
 
+    html_template = HtmlTemplate::TooManyRequests { retry_after: 12 };
+    result = html_template.is_error_template();
+
+    assert!(result);
+
     html_template = HtmlTemplate::Error {
         error_message: "This is a test error message.",
         try_again_link: "/try_again",
@@ -224,6 +1094,26 @@ fn test_is_error_template() {
     result = html_template.is_error_template();
 
     assert!(result);
+
+    html_template = HtmlTemplate::ErrorWithDetails {
+        error_message: "This is a test error message.".to_string(),
+        error_details: vec!["First detail.".to_string()],
+        try_again_link: "/try_again",
+    };
+    result = html_template.is_error_template();
+
+    assert!(result);
+
+    html_template = HtmlTemplate::ErrorWithLocation {
+        error_message: "This is a test error message.".to_string(),
+        line: 1,
+        column: 1,
+        excerpt: "=".to_string(),
+        try_again_link: "/try_again",
+    };
+    result = html_template.is_error_template();
+
+    assert!(result);
 }
 
 // Verifies that the render_template() function renders the correct Handlebars HTML template for each HtmlTemplate variant
@@ -233,39 +1123,262 @@ fn test_render_template() {
     // https://stackoverflow.com/questions/30003921/how-can-i-locate-resources-for-testing-with-cargo
     let file_path = concat!(env!("CARGO_MANIFEST_DIR"), "/static/templates");
     let mut handlebars = Handlebars::new();
-    handlebars
-        .register_templates_directory(".html", file_path)
-        .unwrap();
+    register_templates(
+        &mut handlebars,
+        Path::new(file_path),
+        NumberFormat::default(),
+    )
+    .unwrap();
 
     let mut html_template = HtmlTemplate::AsciiToImageResult {
         image_result: "conversion_results/image_file_name.png".to_string(),
+        image_result_2x: "conversion_results/image_file_name.2x.png".to_string(),
+        svg_result: "conversion_results/image_file_name.svg".to_string(),
+        notice: None,
+        warnings: vec![],
+        permalink: Some("/result/abc".to_string()),
+        attribution: Attribution::default(),
     };
     let mut result = html_template.render_template(&handlebars).unwrap();
-    let mut expected_data = json!({ "image_result": "conversion_results/image_file_name.png" });
+    let mut expected_data = json!({ "image_result": "conversion_results/image_file_name.png", "image_result_2x": "conversion_results/image_file_name.2x.png", "svg_result": "conversion_results/image_file_name.svg", "notice": null, "warnings": [], "permalink": "/result/abc", "attribution": { "author": null, "license": null }, "site": site_context() });
     let mut expected_result = handlebars
         .render("ascii-to-image-result", &expected_data)
         .unwrap();
 
     assert_eq!(result, expected_result);
 
+    html_template = HtmlTemplate::AsciiToImageResult {
+        image_result: "conversion_results/image_file_name.png".to_string(),
+        image_result_2x: "conversion_results/image_file_name.2x.png".to_string(),
+        svg_result: "conversion_results/image_file_name.svg".to_string(),
+        notice: Some("This is a test notice."),
+        warnings: vec!["1 invisible character removed".to_string()],
+        permalink: Some("/result/abc".to_string()),
+        attribution: Attribution::default(),
+    };
+    result = html_template.render_template(&handlebars).unwrap();
+
+    assert!(result.contains("This is a test notice."));
+    assert!(result.contains("<li>1 invisible character removed</li>"));
+    assert!(result.contains("conversion_results/image_file_name.svg"));
+    assert!(result.contains(
+        r#"srcset="conversion_results/image_file_name.png 1x, conversion_results/image_file_name.2x.png 2x""#
+    ));
+
     html_template = HtmlTemplate::ImageToAsciiResult {
         ascii_result: "><(((('>".to_string(),
+        text_result: "conversion_results/ascii_file_name.txt".to_string(),
+        applied_stages: vec!["Rotate 180°".to_string()],
+        pipeline: r#"[{"rotate":"clockwise180"}]"#.to_string(),
+        manifest: r#"{"version":1}"#.to_string(),
+        share_link: "/image-to-ascii?manifest=%7B%22version%22%3A1%7D".to_string(),
+        warnings: vec![],
+        permalink: Some("/result/abc".to_string()),
+        attribution: Attribution::default(),
     };
     result = html_template.render_template(&handlebars).unwrap();
-    expected_data = json!({ "ascii_result": "><(((('>" });
+    expected_data = json!({ "ascii_result": "&gt;&lt;((((&#x27;&gt;", "text_result": "conversion_results/ascii_file_name.txt", "applied_stages": ["Rotate 180°"], "pipeline": r#"[{"rotate":"clockwise180"}]"#, "manifest": r#"{"version":1}"#, "share_link": "/image-to-ascii?manifest=%7B%22version%22%3A1%7D", "warnings": [], "permalink": "/result/abc", "attribution": { "author": null, "license": null }, "site": site_context() });
     expected_result = handlebars
         .render("image-to-ascii-result", &expected_data)
         .unwrap();
 
     assert_eq!(result, expected_result);
+    assert!(result.contains(r#"href="/image-to-ascii?manifest&#x3D;%7B%22version%22%3A1%7D""#));
+
+    html_template = HtmlTemplate::ImageToAsciiForm {
+        manifest: r#"{"width":40}"#.to_string(),
+        width: None,
+        charset: RenderCharset::Ascii,
+    };
+    result = html_template.render_template(&handlebars).unwrap();
+
+    assert!(result.contains(r#"name="manifest""#));
+    assert!(result.contains("{&quot;width&quot;:40}"));
+    assert!(result.contains(r#"<option value="" selected>"#));
+    assert!(result.contains(r#"<option value="ascii" selected>"#));
+
+    html_template = HtmlTemplate::ImageToAsciiForm {
+        manifest: String::new(),
+        width: Some(120),
+        charset: RenderCharset::Braille,
+    };
+    result = html_template.render_template(&handlebars).unwrap();
+
+    assert!(result.contains(r#"<option value="120" selected>"#));
+    assert!(result.contains(r#"<option value="braille" selected>"#));
+    assert!(!result.contains(r#"<option value="" selected>"#));
+
+    html_template = HtmlTemplate::AsciiToImageForm {
+        render_pixels: true,
+    };
+    result = html_template.render_template(&handlebars).unwrap();
+
+    assert!(result.contains(r#"name="ascii_input""#));
+    assert!(result.contains(r#"<option value="true" selected>"#));
+
+    html_template = HtmlTemplate::TextToAsciiForm {
+        charset: RenderCharset::Braille,
+    };
+    result = html_template.render_template(&handlebars).unwrap();
+
+    assert!(result.contains(r#"name="text_input""#));
+    assert!(result.contains(r#"<option value="braille" selected>"#));
+
+    html_template = HtmlTemplate::TextToAsciiResult {
+        ascii_result: "$$ & $$".to_string(),
+    };
+    result = html_template.render_template(&handlebars).unwrap();
+
+    assert!(result.contains("$$ &amp; $$"));
+
+    html_template = HtmlTemplate::Gallery {
+        entries: vec![test_gallery_entry(
+            super::gallery::ConversionKind::AsciiToImage,
+            "conversion_results/image_file_name.png",
+        )],
+        previous_page: Some(1),
+        next_page: None,
+        search: GallerySearch::default(),
+    };
+    result = html_template.render_template(&handlebars).unwrap();
+
+    assert!(result.contains(r#"href="/gallery/7""#));
+    assert!(result.contains("Castle"));
+    assert!(result.contains(r#"href="/gallery?page=1""#));
+    assert!(!result.contains("Older"));
+
+    html_template = HtmlTemplate::GalleryDetail {
+        entry: test_gallery_entry(
+            super::gallery::ConversionKind::AsciiToImage,
+            "conversion_results/image_file_name.png",
+        ),
+        ascii_result: None,
+    };
+    result = html_template.render_template(&handlebars).unwrap();
+
+    assert!(result.contains(r#"src="/conversion_results/image_file_name.png""#));
+
+    html_template = HtmlTemplate::GalleryDetail {
+        entry: test_gallery_entry(
+            super::gallery::ConversionKind::ImageToAscii,
+            "conversion_results/ascii_file_name.txt",
+        ),
+        ascii_result: Some("><(((('>".to_string()),
+    };
+    result = html_template.render_template(&handlebars).unwrap();
+
+    assert!(result.contains("&gt;&lt;((((&#x27;&gt;"));
+    assert!(result.contains(r#"href="/conversion_results/ascii_file_name.txt""#));
+    assert!(result.contains(r#"action="/r/7/report""#));
+
+    html_template = HtmlTemplate::SavedResult {
+        permalink: "/result/abc".to_string(),
+        image_result: Some("conversion_results/abc.png".to_string()),
+        image_result_2x: Some("conversion_results/abc.2x.png".to_string()),
+        svg_result: Some("conversion_results/abc.svg".to_string()),
+        text_result: None,
+        ascii_result: None,
+        bundle_link: "/r/abc/bundle.zip".to_string(),
+        attribution: Attribution::default(),
+        usage: None,
+    };
+    result = html_template.render_template(&handlebars).unwrap();
+
+    assert!(result.contains(r#"src="/conversion_results/abc.png""#));
+    assert!(result
+        .contains(r#"srcset="/conversion_results/abc.png 1x, /conversion_results/abc.2x.png 2x""#));
+    assert!(result.contains(r#"href="/conversion_results/abc.svg""#));
+    assert!(result.contains(r#"value="/result/abc""#));
+
+    html_template = HtmlTemplate::SavedResult {
+        permalink: "/result/abc".to_string(),
+        image_result: None,
+        image_result_2x: None,
+        svg_result: None,
+        text_result: Some("/result/abc.txt".to_string()),
+        ascii_result: Some("><(((('>".to_string()),
+        bundle_link: "/r/abc/bundle.zip".to_string(),
+        attribution: test_attribution(),
+        usage: Some(ResourceUsage {
+            pixels_processed: 1_048_576,
+            peak_memory_bytes: 4_718_592,
+            conversion_time: std::time::Duration::from_millis(250),
+        }),
+    };
+    result = html_template.render_template(&handlebars).unwrap();
+
+    assert!(result.contains("&gt;&lt;((((&#x27;&gt;"));
+    assert!(result.contains(r#"href="/result/abc.txt""#));
+    assert!(result.contains(r#"<pre id="ascii_art""#));
+    assert!(result.contains("Copy to Clipboard"));
+    assert!(result.contains("Kelsey"));
+    assert!(result.contains("Converted 1,048,576 pixels in 250 ms using at most 4.5MB of memory."));
+    assert!(result.contains(r#"href="https://creativecommons.org/licenses/by-sa/4.0/""#));
+
+    html_template = HtmlTemplate::ReportReceived { entry_id: 7 };
+    result = html_template.render_template(&handlebars).unwrap();
+
+    assert!(result.contains(r#"href="/gallery/7""#));
+
+    html_template = HtmlTemplate::ConversionJob {
+        job_id: "abc".to_string(),
+        progress: 42,
+    };
+    result = html_template.render_template(&handlebars).unwrap();
+
+    assert!(result.contains(r#"content="2; url=/job/abc""#));
+    assert!(result.contains("42%"));
+
+    html_template = HtmlTemplate::Account {
+        error_message: Some("Wrong password".to_string()),
+    };
+    result = html_template.render_template(&handlebars).unwrap();
+
+    assert!(result.contains(r#"<div class="alert alert-danger col-8">Wrong password</div>"#));
+    assert!(result.contains(r#"<form action="/register" method="post""#));
+
+    html_template = HtmlTemplate::MyArt {
+        username: "kelsey".to_string(),
+        artworks: vec![Artwork {
+            ascii: "</textarea><b>".to_string(),
+            ..test_artwork()
+        }],
+        error_message: None,
+    };
+    result = html_template.render_template(&handlebars).unwrap();
+
+    assert!(result.contains("kelsey's ASCII Art"));
+    assert!(result.contains(r#"<form action="/my-art/3" method="post""#));
+    assert!(result.contains(r#"value="Castle""#));
+    assert!(result.contains("&lt;/textarea&gt;&lt;b&gt;</textarea>"));
+
+    html_template = HtmlTemplate::Admin {
+        summary: test_summary(),
+        disk_usage: DiskUsage {
+            files: 2,
+            bytes: 2048,
+        },
+        result_ttl_hours: 168,
+        purged: Some(4),
+    };
+    result = html_template.render_template(&handlebars).unwrap();
+
+    assert!(result.contains("<td>TooLarge</td>"));
+    assert!(result.contains(r#"<form action="/admin/purge" method="post""#));
+    assert!(result.contains(r#"value="168""#));
+    assert!(result.contains("Removed 4 result files."));
+
+    html_template = HtmlTemplate::TooManyRequests { retry_after: 12 };
+    result = html_template.render_template(&handlebars).unwrap();
+
+    assert!(result.contains("try again in 12 s."));
 
     html_template = HtmlTemplate::Error {
         error_message: "This is a test error message.",
         try_again_link: "/try_again",
     };
     result = html_template.render_template(&handlebars).unwrap();
-    expected_data =
-        json!({ "error_message": "This is a test error message.", "try_again_link": "/try_again" });
+    expected_data = json!({ "error_message": "This is a test error message.", "try_again_link": "/try_again", "site": site_context() });
     expected_result = handlebars.render("error", &expected_data).unwrap();
 
     assert_eq!(result, expected_result);
@@ -276,8 +1389,174 @@ fn test_render_template() {
         try_again_link: "/try_again",
     };
     result = html_template.render_template(&handlebars).unwrap();
-    expected_data = json!({ "error_message": "This is a test error message.", "error_message2": "This is a test error message part two.", "try_again_link": "/try_again" });
+    expected_data = json!({ "error_message": "This is a test error message.", "error_message2": "This is a test error message part two.", "try_again_link": "/try_again", "site": site_context() });
     expected_result = handlebars.render("error", &expected_data).unwrap();
 
     assert_eq!(result, expected_result);
+
+    html_template = HtmlTemplate::ErrorWithDetails {
+        error_message: "This is a test error message.".to_string(),
+        error_details: vec!["First detail.".to_string(), "Second detail.".to_string()],
+        try_again_link: "/try_again",
+    };
+    result = html_template.render_template(&handlebars).unwrap();
+    expected_data = json!({ "error_message": "This is a test error message.", "error_details": ["First detail.", "Second detail."], "try_again_link": "/try_again", "site": site_context() });
+    expected_result = handlebars.render("error", &expected_data).unwrap();
+
+    assert_eq!(result, expected_result);
+    assert!(result.contains("<li>First detail.</li>"));
+
+    html_template = HtmlTemplate::ErrorWithLocation {
+        error_message: "This is a test error message.".to_string(),
+        line: 2,
+        column: 4,
+        excerpt: "@@@=@@".to_string(),
+        try_again_link: "/try_again",
+    };
+    result = html_template.render_template(&handlebars).unwrap();
+
+    assert!(result.contains("Line 2, column 4"));
+    assert!(result.contains("@@@&#x3D;@@\n   ^</pre>"));
+}
+
+// Verifies that the QR code variants are formatted, named and rendered as successful pages
+#[cfg(feature = "qr")]
+#[test]
+fn test_qr_templates() {
+    let file_path = concat!(env!("CARGO_MANIFEST_DIR"), "/static/templates");
+    let mut handlebars = Handlebars::new();
+    register_templates(
+        &mut handlebars,
+        Path::new(file_path),
+        NumberFormat::default(),
+    )
+    .unwrap();
+
+    let html_template = HtmlTemplate::QrResult {
+        ascii_result: " ▄▄ \n █▀ ".to_string(),
+        image_result: "data:image/png;base64,iVBORw0KGgo=".to_string(),
+    };
+    assert_eq!(
+        html_template.format_template_data(),
+        json!({ "ascii_result": " ▄▄ \n █▀ ", "image_result": "data:image/png;base64,iVBORw0KGgo=" })
+    );
+    assert_eq!(html_template.get_template_name(), "qr-result");
+    assert!(!html_template.is_error_template());
+
+    let result = html_template.render_template(&handlebars).unwrap();
+    assert!(result.contains(" ▄▄ \n █▀ "));
+    assert!(result.contains(r#"src="data:image/png;base64,iVBORw0KGgo"#));
+
+    let html_template = HtmlTemplate::QrForm;
+    assert_eq!(html_template.format_template_data(), json!({}));
+    assert_eq!(html_template.get_template_name(), "qr");
+    assert!(!html_template.is_error_template());
+
+    let result = html_template.render_template(&handlebars).unwrap();
+    assert!(result.contains(r#"<form action="/submit-qr" method="post""#));
+    assert!(result.contains(r#"<form action="/submit-qr-ascii" method="post""#));
+}
+
+// Verifies that every page is wrapped in the layout with the shared partials and the site context, that pages can add
+// to the head of the layout, and that the layout doesn't indent the ASCII art of a page
+#[test]
+fn test_render_template_layout() {
+    let file_path = concat!(env!("CARGO_MANIFEST_DIR"), "/static/templates");
+    let mut handlebars = Handlebars::new();
+    register_templates(
+        &mut handlebars,
+        Path::new(file_path),
+        NumberFormat::default(),
+    )
+    .unwrap();
+
+    let html_template = HtmlTemplate::ConversionJob {
+        job_id: "abc".to_string(),
+        progress: 42,
+    };
+    let result = html_template.render_template(&handlebars).unwrap();
+
+    assert!(result.starts_with("<!DOCTYPE html>"));
+    assert!(result.contains(r#"<a href="/gallery" class="nav-link">Gallery</a>"#));
+    assert!(result.contains(&format!("ASCII Art Converter {} by", SITE_VERSION)));
+    let head = &result[..result.find("</head>").unwrap()];
+    assert!(head.contains(r#"<meta http-equiv="refresh" content="2; url=/job/abc" />"#));
+
+    let html_template = HtmlTemplate::ImageToAsciiResult {
+        ascii_result: "@@@\n...".to_string(),
+        text_result: "conversion_results/ascii_file_name.txt".to_string(),
+        applied_stages: vec![],
+        pipeline: "[]".to_string(),
+        manifest: "{}".to_string(),
+        share_link: "/image-to-ascii".to_string(),
+        warnings: vec!["image downscaled".to_string()],
+        permalink: None,
+        attribution: Attribution::default(),
+    };
+    let result = html_template.render_template(&handlebars).unwrap();
+
+    assert!(result.contains("\n@@@\n...\n"));
+    assert!(result.contains("We changed a few things about your image before converting it:"));
+    assert!(result.contains("<li>image downscaled</li>"));
+    assert!(!result.contains("alert-info"));
+}
+
+// Verifies that ASCII art is escaped so that only line feeds, tabs and printable characters reach the page as they are
+#[test]
+fn test_escape_ascii_art() {
+    assert_eq!(escape_ascii_art("@@@\n. .\t#"), "@@@\n. .\t#");
+    assert_eq!(
+        escape_ascii_art(r#"<b>&amp;"'</b>"#),
+        "&lt;b&gt;&amp;amp;&quot;&#x27;&lt;/b&gt;"
+    );
+    assert_eq!(
+        escape_ascii_art("ab\r\n\x1b[31m\0é"),
+        "ab\n\u{fffd}[31m\u{fffd}é"
+    );
+}
+
+// Verifies that hostile ASCII art is shown verbatim inside the <pre> of the result page without adding markup to it
+#[test]
+fn test_render_hostile_ascii_art() {
+    let mut handlebars = Handlebars::new();
+    register_templates(
+        &mut handlebars,
+        Path::new("./static/templates"),
+        NumberFormat::default(),
+    )
+    .unwrap();
+
+    let hostile = [
+        "<script>alert('hi')</script>",
+        "</pre><img src=x onerror=alert(1)>",
+        "{{ascii_result}} & &amp; <!-- -->",
+        "\"><svg onload=alert(1)>`=",
+    ];
+    for ascii_result in hostile {
+        let html_template = HtmlTemplate::ImageToAsciiResult {
+            ascii_result: ascii_result.to_string(),
+            text_result: "conversion_results/ascii_file_name.txt".to_string(),
+            applied_stages: vec![],
+            pipeline: "[]".to_string(),
+            manifest: "{}".to_string(),
+            share_link: "/image-to-ascii".to_string(),
+            warnings: vec![],
+            permalink: None,
+            attribution: Attribution::default(),
+        };
+        let result = html_template.render_template(&handlebars).unwrap();
+
+        let start = result.find("<pre id=\"ascii_art\"").unwrap();
+        let pre = &result[start..];
+        let pre = &pre[pre.find('>').unwrap() + 1..pre.find("</pre>").unwrap()];
+        assert_eq!(
+            pre.trim(),
+            escape_ascii_art(ascii_result),
+            "{}",
+            ascii_result
+        );
+        assert!(!pre.contains('<'));
+        assert_eq!(result.matches("<pre").count(), 1);
+        assert_eq!(result.matches("</pre>").count(), 1);
+    }
 }
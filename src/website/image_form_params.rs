@@ -4,15 +4,114 @@
 //!
 //! Robert Peterson and Kelsey Werner 2023
 
-use actix_multipart::form::{tempfile::TempFile, MultipartForm};
+use super::attribution::{clean_author, Attribution, License};
+use actix_multipart::form::{bytes::Bytes, text::Text, MultipartForm};
+use ascii_art_converter::converter::{
+    dimension::Rect,
+    image::SUPPORTED_FORMATS,
+    manifest::Manifest,
+    options::{ConvertOptions, RenderCharset, MAX_BRIGHTNESS, MAX_CONTRAST_ADJUSTMENT},
+    pipeline::{Flip, Pipeline, Rotation, Stage},
+    symbol_map::SymbolSet,
+};
+use image::ImageFormat;
+use serde::Deserialize;
+
+/// The ASCII art widths (in characters) that can be selected on the image to ASCII form.
+pub const SUPPORTED_ASCII_WIDTHS: [u32; 4] = [40, 80, 120, 200];
+
+/// The orientations that can be selected on the image to ASCII and ASCII to image forms, by name along with the
+/// rotate or flip [Stage] that turns the image that way.
+pub const SUPPORTED_ORIENTATIONS: [(&str, Stage); 5] = [
+    ("rotate90", Stage::Rotate(Rotation::Clockwise90)),
+    ("rotate180", Stage::Rotate(Rotation::Clockwise180)),
+    ("rotate270", Stage::Rotate(Rotation::Clockwise270)),
+    ("flip_horizontal", Stage::Flip(Flip::Horizontal)),
+    ("flip_vertical", Stage::Flip(Flip::Vertical)),
+];
+
+/// Function to find the rotate or flip [Stage] of an orientation name of the [SUPPORTED_ORIENTATIONS].
+///
+/// Returns `Some(None)` for a blank name so that the image is left as it is, and [None] for a name that is not supported.
+pub fn parse_orientation(name: &str) -> Option<Option<Stage>> {
+    if name.is_empty() {
+        return Some(None);
+    }
+
+    SUPPORTED_ORIENTATIONS
+        .iter()
+        .find(|(supported, _)| *supported == name)
+        .map(|(_, stage)| Some(*stage))
+}
+
+/// Struct to store the error of an ASCII art width that is not one of the [SUPPORTED_ASCII_WIDTHS].
+///
+/// Each form with a width maps it into the unsupported width error of its own input error enum.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct InvalidWidth;
+
+/// Function to parse the ASCII art width form input of the forms that convert images into ASCII art.
+///
+/// Returns `Ok(Some(u32))` when one of the [SUPPORTED_ASCII_WIDTHS] is submitted.
+/// Returns `Ok(None)` when no width is submitted so that the converter can pick a reasonable width.
+/// Returns `Err(InvalidWidth)` when any other width is submitted.
+pub fn parse_ascii_width(width: &Option<Text<String>>) -> Result<Option<u32>, InvalidWidth> {
+    match width {
+        Some(width) if width.is_empty() => Ok(None),
+        Some(width) => match width.parse::<u32>() {
+            Ok(width) if SUPPORTED_ASCII_WIDTHS.contains(&width) => Ok(Some(width)),
+            _ => Err(InvalidWidth),
+        },
+        None => Ok(None),
+    }
+}
 
 /// Struct to store an image.
 ///
 /// Actix Web populates [ImageFormParams] with user-submitted form data.
 #[derive(MultipartForm)]
 pub struct ImageFormParams {
-    /// [Option] stores a PNG or JPEG as [TempFile] or [None] if no image submitted.
-    pub image_input: Option<TempFile>,
+    /// [Option] stores a JPEG, PNG, WebP, BMP or TIFF as [Bytes] or [None] if no image submitted.
+    ///
+    /// The image is kept in memory so that it can be converted without a temporary file.
+    pub image_input: Option<Bytes>,
+    /// [Option] stores the selected ASCII art width as [Text] or [None] if no width submitted.
+    pub ascii_width: Option<Text<String>>,
+    /// [Option] stores the selected character set ("ascii" or "braille") as [Text] or [None] if no character set submitted.
+    pub render_charset: Option<Text<String>>,
+    /// [Option] stores the character used for the empty parts of the ASCII art as [Text] or [None] if no character submitted.
+    pub background_char: Option<Text<String>>,
+    /// [Option] stores the name of the selected [SymbolSet] (such as "minimal10") as [Text] or [None] if no symbol set submitted.
+    pub symbol_set: Option<Text<String>>,
+    /// [Option] stores the region of the image to convert as "x,y,width,height" [Text] in pixels or [None] if no region selected.
+    pub crop: Option<Text<String>>,
+    /// [Option] stores the name of the selected rotation or flip (such as "rotate90") as [Text] or [None] if no orientation submitted.
+    pub orientation: Option<Text<String>>,
+    /// [Option] stores the amount the brightness of the image is raised or lowered by as [Text] or [None] if no brightness submitted.
+    pub brightness: Option<Text<String>>,
+    /// [Option] stores the percent the contrast of the image is raised or lowered by as [Text] or [None] if no contrast submitted.
+    pub contrast: Option<Text<String>>,
+    /// [Option] stores whether line numbers and column rulers were requested as [Text] or [None] if the checkbox was not checked.
+    pub show_rulers: Option<Text<bool>>,
+    /// [Option] stores the JSON pipeline of transformations to replay as [Text] or [None] if no pipeline submitted.
+    pub pipeline: Option<Text<String>>,
+    /// [Option] stores the JSON manifest of shared settings as [Text] or [None] if no settings submitted.
+    pub manifest: Option<Text<String>>,
+    /// [Option] stores the title the conversion is shown with in the gallery as [Text] or [None] if no title submitted.
+    pub title: Option<Text<String>>,
+    /// [Option] stores the name of the author of the conversion as [Text] or [None] if no author submitted.
+    pub author: Option<Text<String>>,
+    /// [Option] stores the code of the selected [License] as [Text] or [None] if no license submitted.
+    pub license: Option<Text<String>>,
+}
+
+/// Struct to store the query of the image to ASCII form.
+///
+/// Actix Web populates [ImageFormQuery] with the query parameters of a link that shares settings.
+#[derive(Deserialize)]
+pub struct ImageFormQuery {
+    /// [Option] stores the JSON manifest of shared settings to fill in on the form or [None] if no settings were shared.
+    pub manifest: Option<String>,
 }
 
 /// Enum to store the possible error states that can be detected when sanitizing image input.
@@ -22,29 +121,287 @@ pub struct ImageFormParams {
 pub enum ImageInputError {
     /// [ImageInputError::EmptyInput] error is caused when the form is submitted without being populated with an image.
     EmptyInput,
-    /// [ImageInputError::UnsupportedImageType] error is caused when the form is submitted with an image that is not a JPEG or PNG.
+    /// [ImageInputError::UnsupportedImageType] error is caused when the form is submitted with a file whose contents are not a JPEG, PNG, WebP, BMP or TIFF image.
     UnsupportedImageType,
+    /// [ImageInputError::ImageTypeMismatch] error is caused when the form is submitted with an image whose contents are a different format than its type says.
+    ImageTypeMismatch,
+    /// [ImageInputError::UnsupportedWidth] error is caused when the form is submitted with an ASCII art width that is not one of the [SUPPORTED_ASCII_WIDTHS].
+    UnsupportedWidth,
+    /// [ImageInputError::UnsupportedCharset] error is caused when the form is submitted with a character set that is not "ascii" or "braille".
+    UnsupportedCharset,
+    /// [ImageInputError::UnsupportedBackground] error is caused when the form is submitted with a background that is not a single visible ASCII character.
+    UnsupportedBackground,
+    /// [ImageInputError::UnsupportedSymbolSet] error is caused when the form is submitted with a symbol set that is not the name of a [SymbolSet].
+    UnsupportedSymbolSet,
+    /// [ImageInputError::InvalidCrop] error is caused when the form is submitted with a region of the image that is not four
+    /// whole numbers or that has no pixels.
+    InvalidCrop,
+    /// [ImageInputError::UnsupportedOrientation] error is caused when the form is submitted with an orientation that is not one of the
+    /// [SUPPORTED_ORIENTATIONS].
+    UnsupportedOrientation,
+    /// [ImageInputError::UnsupportedAdjustment] error is caused when the form is submitted with a brightness that is not a whole
+    /// number from -[MAX_BRIGHTNESS] to [MAX_BRIGHTNESS], or a contrast that is not a number from -[MAX_CONTRAST_ADJUSTMENT] to
+    /// [MAX_CONTRAST_ADJUSTMENT].
+    UnsupportedAdjustment,
+    /// [ImageInputError::InvalidPipeline] error is caused when the form is submitted with a pipeline that is not valid JSON or whose stages are out of order.
+    InvalidPipeline,
+    /// [ImageInputError::InvalidManifest] error is caused when the form is submitted with shared settings that can't be read or can't be used on the site.
+    InvalidManifest,
+    /// [ImageInputError::UnsupportedLicense] error is caused when the form is submitted with a license that is not one of the [License] codes.
+    UnsupportedLicense,
 }
 
 impl ImageFormParams {
     /// Function to verify if image form input is valid.
     ///
-    /// When the input image passes valiation, function returns `Ok(&TempFile)` where [TempFile] is the input image file.
+    /// The format of the image is sniffed from the magic bytes at the start of the file rather than trusted from the
+    /// type the browser sent, so a renamed program or an unsupported image never reaches the converter.
+    ///
+    /// When the input image passes valiation, function returns `Ok(&Bytes)` where [Bytes] is the input image file.
     /// Returns `Err(ImageInputError::EmptyInput)` when an empty form is submitted.
-    /// Returns `Err(ImageInputError::UnsupportedImageType)` when an image that is not a JPEG or PNG is submitted.
-    pub fn validate_image_input(&self) -> Result<&TempFile, ImageInputError> {
-        match &self.image_input {
-            Some(image_file) if image_file.size == 0 => Err(ImageInputError::EmptyInput),
-            Some(image_file) => match &image_file.content_type {
-                Some(mime_type)
-                    if *mime_type == mime::IMAGE_JPEG || *mime_type == mime::IMAGE_PNG =>
-                {
-                    Ok(image_file)
-                }
-                _ => Err(ImageInputError::UnsupportedImageType),
-            },
-            None => Err(ImageInputError::EmptyInput),
+    /// Returns `Err(ImageInputError::UnsupportedImageType)` when the contents of the file are not a JPEG, PNG, WebP, BMP or TIFF image.
+    /// Returns `Err(ImageInputError::ImageTypeMismatch)` when the file has an image type that is a different format than its contents.
+    pub fn validate_image_input(&self) -> Result<&Bytes, ImageInputError> {
+        let image_file = match &self.image_input {
+            Some(image_file) if image_file.data.is_empty() => {
+                return Err(ImageInputError::EmptyInput)
+            }
+            Some(image_file) => image_file,
+            None => return Err(ImageInputError::EmptyInput),
+        };
+
+        let sniffed_format = image::guess_format(&image_file.data)
+            .ok()
+            .filter(|format| SUPPORTED_FORMATS.contains(format))
+            .ok_or(ImageInputError::UnsupportedImageType)?;
+
+        // browsers send a generic type such as application/octet-stream for extensions they don't know, so only an image
+        // type is compared with the contents
+        let declared_format = image_file
+            .content_type
+            .as_ref()
+            .and_then(|mime_type| ImageFormat::from_mime_type(mime_type.essence_str()));
+        match declared_format {
+            Some(format) if format != sniffed_format => Err(ImageInputError::ImageTypeMismatch),
+            _ => Ok(image_file),
+        }
+    }
+
+    /// Function to verify if the ASCII art width form input is valid.
+    ///
+    /// The width is parsed with [parse_ascii_width], and `Err(ImageInputError::UnsupportedWidth)` is returned when it
+    /// is not one of the [SUPPORTED_ASCII_WIDTHS].
+    pub fn validate_ascii_width(&self) -> Result<Option<u32>, ImageInputError> {
+        parse_ascii_width(&self.ascii_width).map_err(|_| ImageInputError::UnsupportedWidth)
+    }
+
+    /// Function to verify if the character set form input is valid.
+    ///
+    /// Returns `Ok(None)` when no character set is submitted so that the default character set of the website is used.
+    /// Returns `Ok(Some(RenderCharset::Ascii))` when "ascii" is submitted.
+    /// Returns `Ok(Some(RenderCharset::Braille))` when "braille" is submitted.
+    /// Returns `Err(ImageInputError::UnsupportedCharset)` when any other character set is submitted.
+    pub fn validate_render_charset(&self) -> Result<Option<RenderCharset>, ImageInputError> {
+        match self.render_charset.as_ref().map(|charset| charset.as_str()) {
+            None | Some("") => Ok(None),
+            Some("ascii") => Ok(Some(RenderCharset::Ascii)),
+            Some("braille") => Ok(Some(RenderCharset::Braille)),
+            Some(_) => Err(ImageInputError::UnsupportedCharset),
+        }
+    }
+
+    /// Function to check if line numbers and column rulers were requested.
+    ///
+    /// Returns `true` only when the rulers checkbox is checked.
+    pub fn rulers_requested(&self) -> bool {
+        self.show_rulers.as_ref().is_some_and(|show| **show)
+    }
+
+    /// Function to verify if the background character form input is valid.
+    ///
+    /// Returns `Ok(None)` when a space or no character is submitted so that the default background is used.
+    /// Returns `Ok(Some(char))` when a single visible ASCII character (such as ".") is submitted.
+    /// Returns `Err(ImageInputError::UnsupportedBackground)` when anything else is submitted.
+    pub fn validate_background_char(&self) -> Result<Option<char>, ImageInputError> {
+        let background = match &self.background_char {
+            Some(background) => background.as_str(),
+            None => return Ok(None),
+        };
+
+        let mut chars = background.chars();
+        match (chars.next(), chars.next()) {
+            (None, _) | (Some(' '), None) => Ok(None),
+            (Some(c), None) if c.is_ascii_graphic() => Ok(Some(c)),
+            _ => Err(ImageInputError::UnsupportedBackground),
+        }
+    }
+
+    /// Function to verify if the symbol set form input is valid.
+    ///
+    /// Returns `Ok(None)` when no symbol set is submitted so that the default symbols of the website are used.
+    /// Returns `Ok(Some(SymbolSet))` when the name of a [SymbolSet] (such as "blocks") is submitted.
+    /// Returns `Err(ImageInputError::UnsupportedSymbolSet)` when any other symbol set is submitted.
+    pub fn validate_symbol_set(&self) -> Result<Option<SymbolSet>, ImageInputError> {
+        match self.symbol_set.as_ref().map(|set| set.as_str()) {
+            None | Some("") => Ok(None),
+            Some(name) => SymbolSet::from_name(name)
+                .map(Some)
+                .ok_or(ImageInputError::UnsupportedSymbolSet),
+        }
+    }
+
+    /// Function to verify if the region of the image form input is valid.
+    ///
+    /// Returns `Ok(None)` when a blank or no region is submitted so that the whole image is converted.
+    /// Returns `Ok(Some(Rect))` when a region such as "10,20,300,200" is submitted, which is its left and top edges
+    /// followed by its width and height in pixels.
+    /// Returns `Err(ImageInputError::InvalidCrop)` when the region is not four whole numbers or has no pixels.
+    /// Whether the region fits inside of the image is checked by the converter.
+    pub fn validate_crop(&self) -> Result<Option<Rect>, ImageInputError> {
+        let region = match &self.crop {
+            Some(region) if !region.trim().is_empty() => region.as_str(),
+            _ => return Ok(None),
+        };
+
+        let numbers = region
+            .split(',')
+            .map(|number| number.trim().parse::<u32>())
+            .collect::<Result<Vec<u32>, _>>()
+            .map_err(|_| ImageInputError::InvalidCrop)?;
+
+        match numbers[..] {
+            [x, y, width, height] if width > 0 && height > 0 => {
+                Ok(Some(Rect::new(x, y, width, height)))
+            }
+            _ => Err(ImageInputError::InvalidCrop),
+        }
+    }
+
+    /// Function to verify if the orientation form input is valid.
+    ///
+    /// Returns `Ok(None)` when no orientation is submitted so that the image is converted as it is.
+    /// Returns `Ok(Some(Stage))` with the rotate or flip stage of one of the [SUPPORTED_ORIENTATIONS] (such as "rotate90").
+    /// Returns `Err(ImageInputError::UnsupportedOrientation)` when any other orientation is submitted.
+    pub fn validate_orientation(&self) -> Result<Option<Stage>, ImageInputError> {
+        match &self.orientation {
+            None => Ok(None),
+            Some(name) => parse_orientation(name).ok_or(ImageInputError::UnsupportedOrientation),
+        }
+    }
+
+    /// Function to verify if the brightness form input is valid.
+    ///
+    /// Returns `Ok(0)` when a blank or no brightness is submitted so that the brightness is kept as it is.
+    /// Returns `Ok(i16)` when a whole number from -[MAX_BRIGHTNESS] to [MAX_BRIGHTNESS] is submitted.
+    /// Returns `Err(ImageInputError::UnsupportedAdjustment)` when any other brightness is submitted.
+    pub fn validate_brightness(&self) -> Result<i16, ImageInputError> {
+        let brightness =
+            match &self.brightness {
+                Some(brightness) if !brightness.trim().is_empty() => brightness
+                    .trim()
+                    .parse::<i16>()
+                    .map_err(|_| ImageInputError::UnsupportedAdjustment)?,
+                _ => return Ok(0),
+            };
+
+        if (-MAX_BRIGHTNESS..=MAX_BRIGHTNESS).contains(&brightness) {
+            Ok(brightness)
+        } else {
+            Err(ImageInputError::UnsupportedAdjustment)
+        }
+    }
+
+    /// Function to verify if the contrast form input is valid.
+    ///
+    /// Returns `Ok(0.0)` when a blank or no contrast is submitted so that the contrast is kept as it is.
+    /// Returns `Ok(f32)` when a number from -[MAX_CONTRAST_ADJUSTMENT] to [MAX_CONTRAST_ADJUSTMENT] percent is submitted.
+    /// Returns `Err(ImageInputError::UnsupportedAdjustment)` when any other contrast is submitted.
+    pub fn validate_contrast(&self) -> Result<f32, ImageInputError> {
+        let contrast = match &self.contrast {
+            Some(contrast) if !contrast.trim().is_empty() => contrast
+                .trim()
+                .parse::<f32>()
+                .map_err(|_| ImageInputError::UnsupportedAdjustment)?,
+            _ => return Ok(0.0),
+        };
+
+        // a contrast of "NaN" parses, but is not in the range
+        if (-MAX_CONTRAST_ADJUSTMENT..=MAX_CONTRAST_ADJUSTMENT).contains(&contrast) {
+            Ok(contrast)
+        } else {
+            Err(ImageInputError::UnsupportedAdjustment)
+        }
+    }
+
+    /// Function to verify if the pipeline form input is valid.
+    ///
+    /// Returns `Ok(Pipeline)` with no stages when a blank or no pipeline is submitted.
+    /// Returns `Ok(Pipeline)` when a JSON pipeline, such as the one shown on the image to ASCII result page, is submitted.
+    /// Returns `Err(ImageInputError::InvalidPipeline)` when the pipeline can't be parsed or its stages can't be applied in order.
+    pub fn validate_pipeline(&self) -> Result<Pipeline, ImageInputError> {
+        let json = match &self.pipeline {
+            Some(json) if !json.trim().is_empty() => json.as_str(),
+            _ => return Ok(Pipeline::new()),
+        };
+
+        let pipeline: Pipeline =
+            serde_json::from_str(json).map_err(|_| ImageInputError::InvalidPipeline)?;
+        pipeline
+            .validate()
+            .map_err(|_| ImageInputError::InvalidPipeline)?;
+
+        Ok(pipeline)
+    }
+
+    /// Function to verify if the shared settings form input is valid.
+    ///
+    /// Returns `Ok(None)` when blank or no settings are submitted so that the other form inputs are used.
+    /// Returns `Ok(Some(ConvertOptions))` when a JSON manifest, such as the one shown on the image to ASCII result page, is submitted.
+    /// Returns `Err(ImageInputError::InvalidManifest)` when the manifest can't be read, or asks for a width or character set that
+    /// can't be selected on the form or for a pipeline whose stages can't be applied in order.
+    pub fn validate_manifest(&self) -> Result<Option<ConvertOptions>, ImageInputError> {
+        let json = match &self.manifest {
+            Some(json) if !json.trim().is_empty() => json.as_str(),
+            _ => return Ok(None),
+        };
+
+        let options = Manifest::from_json(json)
+            .and_then(|manifest| manifest.to_options())
+            .map_err(|_| ImageInputError::InvalidManifest)?;
+
+        let supported_width = options
+            .width
+            .is_none_or(|width| SUPPORTED_ASCII_WIDTHS.contains(&width));
+        let supported_charset = matches!(
+            options.charset,
+            RenderCharset::Ascii | RenderCharset::Braille
+        );
+        if !supported_width || !supported_charset || options.pipeline.validate().is_err() {
+            return Err(ImageInputError::InvalidManifest);
         }
+
+        Ok(Some(options))
+    }
+
+    /// Function to verify if the author and license form input is valid.
+    ///
+    /// Returns `Ok(Attribution)` with the cleaned up author name and the selected [License], which are [None] when left blank.
+    /// Returns `Err(ImageInputError::UnsupportedLicense)` when a license that is not one of the [License] codes is submitted.
+    pub fn validate_attribution(&self) -> Result<Attribution, ImageInputError> {
+        let license = match self.license.as_ref().map(|license| license.as_str()) {
+            None | Some("") => None,
+            Some(code) => {
+                Some(License::from_code(code).ok_or(ImageInputError::UnsupportedLicense)?)
+            }
+        };
+
+        Ok(Attribution {
+            author: self
+                .author
+                .as_deref()
+                .and_then(|author| clean_author(author)),
+            license,
+        })
     }
 }
 
@@ -53,24 +410,55 @@ impl ImageFormParams {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use tempfile::NamedTempFile;
+    use actix_web::web;
+    use ascii_art_converter::converter::{options::ContrastMode, pipeline::Rotation};
+    use std::{fs, io::Cursor};
 
     // Verifies that empty input accurately detected by ImageFormParams::validate_image_input() and error returned
     #[test]
     fn test_empty_input() {
-        let mut input = ImageFormParams { image_input: None };
+        let mut input = ImageFormParams {
+            image_input: None,
+            ascii_width: None,
+            render_charset: None,
+            background_char: None,
+            symbol_set: None,
+            crop: None,
+            orientation: None,
+            brightness: None,
+            contrast: None,
+            show_rulers: None,
+            pipeline: None,
+            manifest: None,
+            title: None,
+            author: None,
+            license: None,
+        };
         let mut result = input.validate_image_input();
 
         assert_eq!(result.unwrap_err(), ImageInputError::EmptyInput);
 
-        let temp_file = TempFile {
-            file: NamedTempFile::new().unwrap(),
+        let image_bytes = Bytes {
+            data: web::Bytes::new(),
             content_type: Some(mime::IMAGE_PNG),
             file_name: Some("test_file.png".to_string()),
-            size: 0,
         };
         input = ImageFormParams {
-            image_input: Some(temp_file),
+            image_input: Some(image_bytes),
+            ascii_width: None,
+            render_charset: None,
+            background_char: None,
+            symbol_set: None,
+            crop: None,
+            orientation: None,
+            brightness: None,
+            contrast: None,
+            show_rulers: None,
+            pipeline: None,
+            manifest: None,
+            title: None,
+            author: None,
+            license: None,
         };
         result = input.validate_image_input();
 
@@ -80,14 +468,27 @@ mod tests {
     // Verifies that input image with unsupported mime type accurately detected by ImageFormParams::validate_image_input() and error returned
     #[test]
     fn test_unsupported_mime_type() {
-        let temp_file = TempFile {
-            file: NamedTempFile::new().unwrap(),
+        let image_bytes = Bytes {
+            data: web::Bytes::from(vec![0; 10]),
             content_type: Some(mime::IMAGE_GIF),
             file_name: Some("test_file.gif".to_string()),
-            size: 10,
         };
         let input = ImageFormParams {
-            image_input: Some(temp_file),
+            image_input: Some(image_bytes),
+            ascii_width: None,
+            render_charset: None,
+            background_char: None,
+            symbol_set: None,
+            crop: None,
+            orientation: None,
+            brightness: None,
+            contrast: None,
+            show_rulers: None,
+            pipeline: None,
+            manifest: None,
+            title: None,
+            author: None,
+            license: None,
         };
         let result = input.validate_image_input();
 
@@ -97,48 +498,986 @@ mod tests {
     // Verifies that valid JPEG form input detected by ImageFormParams::validate_image_input() and Ok(image_input) returned
     #[test]
     fn test_jpeg_input() {
-        let temp_file = TempFile {
-            file: NamedTempFile::new().unwrap(),
+        let image_bytes = Bytes {
+            data: web::Bytes::from_static(b"\xff\xd8\xff\0\0\0\0\0\0\0"),
             content_type: Some(mime::IMAGE_JPEG),
             file_name: Some("test_file.jpeg".to_string()),
-            size: 10,
         };
         let input = ImageFormParams {
-            image_input: Some(temp_file),
+            image_input: Some(image_bytes),
+            ascii_width: None,
+            render_charset: None,
+            background_char: None,
+            symbol_set: None,
+            crop: None,
+            orientation: None,
+            brightness: None,
+            contrast: None,
+            show_rulers: None,
+            pipeline: None,
+            manifest: None,
+            title: None,
+            author: None,
+            license: None,
         };
         let result = input.validate_image_input();
 
         assert!(&result.is_ok());
 
-        // I used the TempFile source code to reference how to validate the individual fields of the TempFile struct:
-        // https://docs.rs/actix-multipart/latest/src/actix_multipart/form/tempfile.rs.html#186
+        // I used the Bytes source code to reference how to validate the individual fields of the Bytes struct:
+        // https://docs.rs/actix-multipart/latest/src/actix_multipart/form/bytes.rs.html
         let result = result.unwrap();
         assert_eq!(result.file_name, Some("test_file.jpeg".to_string()));
         assert_eq!(result.content_type, Some(mime::IMAGE_JPEG));
-        assert_eq!(result.size, 10);
+        assert_eq!(result.data.len(), 10);
     }
 
     // Verifies that valid PNG form input detected by ImageFormParams::validate_image_input() and Ok(image_input) returned
     #[test]
     fn test_png_input() {
-        let temp_file = TempFile {
-            file: NamedTempFile::new().unwrap(),
+        let image_bytes = Bytes {
+            data: web::Bytes::from_static(b"\x89PNG\r\n\x1a\n\0\0"),
             content_type: Some(mime::IMAGE_PNG),
             file_name: Some("test_file.png".to_string()),
-            size: 10,
         };
         let input = ImageFormParams {
-            image_input: Some(temp_file),
+            image_input: Some(image_bytes),
+            ascii_width: None,
+            render_charset: None,
+            background_char: None,
+            symbol_set: None,
+            crop: None,
+            orientation: None,
+            brightness: None,
+            contrast: None,
+            show_rulers: None,
+            pipeline: None,
+            manifest: None,
+            title: None,
+            author: None,
+            license: None,
         };
         let result = input.validate_image_input();
 
         assert!(&result.is_ok());
 
-        // I used the TempFile source code to reference how to validate the individual fields of the TempFile struct:
-        // https://docs.rs/actix-multipart/latest/src/actix_multipart/form/tempfile.rs.html#186
+        // I used the Bytes source code to reference how to validate the individual fields of the Bytes struct:
+        // https://docs.rs/actix-multipart/latest/src/actix_multipart/form/bytes.rs.html
         let result = result.unwrap();
         assert_eq!(result.file_name, Some("test_file.png".to_string()));
         assert_eq!(result.content_type, Some(mime::IMAGE_PNG));
-        assert_eq!(result.size, 10);
+        assert_eq!(result.data.len(), 10);
+    }
+
+    // Creates form Bytes with the contents of `bytes` and the given mime type
+    fn form_bytes(bytes: Vec<u8>, mime_type: &str, file_name: &str) -> Bytes {
+        Bytes {
+            data: web::Bytes::from(bytes),
+            content_type: Some(mime_type.parse().unwrap()),
+            file_name: Some(file_name.to_string()),
+        }
+    }
+
+    // Encodes the small freakazoid test image in the given format
+    fn encode_test_image(format: ImageFormat) -> Vec<u8> {
+        // The idea to use "CARGO_MANIFEST_DIR" comes from StackOverflow:
+        // https://stackoverflow.com/questions/30003921/how-can-i-locate-resources-for-testing-with-cargo
+        let img_path = concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/test_assets/images/freakazoid-small.png"
+        );
+        let mut bytes = Cursor::new(Vec::new());
+        image::open(img_path)
+            .unwrap()
+            .write_to(&mut bytes, format)
+            .unwrap();
+
+        bytes.into_inner()
+    }
+
+    // Verifies that valid WebP form input detected by ImageFormParams::validate_image_input() and Ok(image_input) returned
+    #[test]
+    fn test_webp_input() {
+        let img_path = concat!(env!("CARGO_MANIFEST_DIR"), "/test_assets/images/pixel.webp");
+        let input = ImageFormParams {
+            image_input: Some(form_bytes(
+                fs::read(img_path).unwrap(),
+                "image/webp",
+                "test_file.webp",
+            )),
+            ascii_width: None,
+            render_charset: None,
+            background_char: None,
+            symbol_set: None,
+            crop: None,
+            orientation: None,
+            brightness: None,
+            contrast: None,
+            show_rulers: None,
+            pipeline: None,
+            manifest: None,
+            title: None,
+            author: None,
+            license: None,
+        };
+        let result = input.validate_image_input();
+
+        assert!(&result.is_ok());
+        assert_eq!(
+            result.unwrap().content_type,
+            Some("image/webp".parse().unwrap())
+        );
+    }
+
+    // Verifies that valid BMP form input detected by ImageFormParams::validate_image_input() and Ok(image_input) returned
+    #[test]
+    fn test_bmp_input() {
+        let input = ImageFormParams {
+            image_input: Some(form_bytes(
+                encode_test_image(ImageFormat::Bmp),
+                "image/bmp",
+                "test_file.bmp",
+            )),
+            ascii_width: None,
+            render_charset: None,
+            background_char: None,
+            symbol_set: None,
+            crop: None,
+            orientation: None,
+            brightness: None,
+            contrast: None,
+            show_rulers: None,
+            pipeline: None,
+            manifest: None,
+            title: None,
+            author: None,
+            license: None,
+        };
+        let result = input.validate_image_input();
+
+        assert!(&result.is_ok());
+        assert_eq!(result.unwrap().content_type, Some(mime::IMAGE_BMP));
+    }
+
+    // Verifies that valid TIFF form input detected by ImageFormParams::validate_image_input() and Ok(image_input) returned
+    #[test]
+    fn test_tiff_input() {
+        let input = ImageFormParams {
+            image_input: Some(form_bytes(
+                encode_test_image(ImageFormat::Tiff),
+                "image/tiff",
+                "test_file.tiff",
+            )),
+            ascii_width: None,
+            render_charset: None,
+            background_char: None,
+            symbol_set: None,
+            crop: None,
+            orientation: None,
+            brightness: None,
+            contrast: None,
+            show_rulers: None,
+            pipeline: None,
+            manifest: None,
+            title: None,
+            author: None,
+            license: None,
+        };
+        let result = input.validate_image_input();
+
+        assert!(&result.is_ok());
+        assert_eq!(
+            result.unwrap().content_type,
+            Some("image/tiff".parse().unwrap())
+        );
+    }
+
+    // Verifies that an image whose contents don't match its mime type is detected by ImageFormParams::validate_image_input() and error returned
+    #[test]
+    fn test_image_type_mismatch() {
+        let input = ImageFormParams {
+            image_input: Some(form_bytes(
+                encode_test_image(ImageFormat::Bmp),
+                "image/png",
+                "test_file.png",
+            )),
+            ascii_width: None,
+            render_charset: None,
+            background_char: None,
+            symbol_set: None,
+            crop: None,
+            orientation: None,
+            brightness: None,
+            contrast: None,
+            show_rulers: None,
+            pipeline: None,
+            manifest: None,
+            title: None,
+            author: None,
+            license: None,
+        };
+        let result = input.validate_image_input();
+
+        assert_eq!(result.unwrap_err(), ImageInputError::ImageTypeMismatch);
+    }
+
+    // Verifies that the format of the image is sniffed from its contents by ImageFormParams::validate_image_input(), so files
+    // that only claim to be a supported image are rejected and images sent with a generic type are accepted
+    #[test]
+    fn test_sniffed_image_type() {
+        let renamed_program = b"MZ\x90\0\x03\0\0\0\x04\0".to_vec();
+        for (bytes, mime_type, file_name, expected) in [
+            (
+                renamed_program,
+                "image/png",
+                "test_file.png",
+                Err(ImageInputError::UnsupportedImageType),
+            ),
+            (
+                encode_test_image(ImageFormat::Gif),
+                "image/png",
+                "test_file.png",
+                Err(ImageInputError::UnsupportedImageType),
+            ),
+            (
+                encode_test_image(ImageFormat::Png),
+                "application/octet-stream",
+                "test_file.png",
+                Ok(()),
+            ),
+            (
+                encode_test_image(ImageFormat::Jpeg),
+                "text/plain",
+                "test_file.jpeg",
+                Ok(()),
+            ),
+        ] {
+            let input = ImageFormParams {
+                image_input: Some(form_bytes(bytes, mime_type, file_name)),
+                ascii_width: None,
+                render_charset: None,
+                background_char: None,
+                symbol_set: None,
+                crop: None,
+                orientation: None,
+                brightness: None,
+                contrast: None,
+                show_rulers: None,
+                pipeline: None,
+                manifest: None,
+                title: None,
+                author: None,
+                license: None,
+            };
+
+            assert_eq!(input.validate_image_input().map(|_| ()), expected);
+        }
+    }
+
+    // Verifies that a missing or empty width is accepted by ImageFormParams::validate_ascii_width() and Ok(None) returned
+    #[test]
+    fn test_default_ascii_width() {
+        let mut input = ImageFormParams {
+            image_input: None,
+            ascii_width: None,
+            render_charset: None,
+            background_char: None,
+            symbol_set: None,
+            crop: None,
+            orientation: None,
+            brightness: None,
+            contrast: None,
+            show_rulers: None,
+            pipeline: None,
+            manifest: None,
+            title: None,
+            author: None,
+            license: None,
+        };
+
+        assert_eq!(input.validate_ascii_width(), Ok(None));
+
+        input = ImageFormParams {
+            image_input: None,
+            ascii_width: Some(Text(String::new())),
+            render_charset: None,
+            background_char: None,
+            symbol_set: None,
+            crop: None,
+            orientation: None,
+            brightness: None,
+            contrast: None,
+            show_rulers: None,
+            pipeline: None,
+            manifest: None,
+            title: None,
+            author: None,
+            license: None,
+        };
+
+        assert_eq!(input.validate_ascii_width(), Ok(None));
+    }
+
+    // Verifies that each supported width is accepted by ImageFormParams::validate_ascii_width() and Ok(Some(width)) returned
+    #[test]
+    fn test_supported_ascii_width() {
+        for width in SUPPORTED_ASCII_WIDTHS {
+            let input = ImageFormParams {
+                image_input: None,
+                ascii_width: Some(Text(width.to_string())),
+                render_charset: None,
+                background_char: None,
+                symbol_set: None,
+                crop: None,
+                orientation: None,
+                brightness: None,
+                contrast: None,
+                show_rulers: None,
+                pipeline: None,
+                manifest: None,
+                title: None,
+                author: None,
+                license: None,
+            };
+
+            assert_eq!(input.validate_ascii_width(), Ok(Some(width)));
+        }
+    }
+
+    // Verifies that an unsupported width is detected by ImageFormParams::validate_ascii_width() and error returned
+    #[test]
+    fn test_unsupported_ascii_width() {
+        for width in ["0", "41", "-80", "wide"] {
+            let input = ImageFormParams {
+                image_input: None,
+                ascii_width: Some(Text(width.to_string())),
+                render_charset: None,
+                background_char: None,
+                symbol_set: None,
+                crop: None,
+                orientation: None,
+                brightness: None,
+                contrast: None,
+                show_rulers: None,
+                pipeline: None,
+                manifest: None,
+                title: None,
+                author: None,
+                license: None,
+            };
+
+            assert_eq!(
+                input.validate_ascii_width(),
+                Err(ImageInputError::UnsupportedWidth)
+            );
+        }
+    }
+
+    // Verifies that parse_ascii_width() accepts a missing, empty or supported width, and rejects any other width
+    #[test]
+    fn test_parse_ascii_width() {
+        for (width, expected) in [
+            (None, Ok(None)),
+            (Some(""), Ok(None)),
+            (Some("120"), Ok(Some(120))),
+            (Some("41"), Err(InvalidWidth)),
+            (Some("wide"), Err(InvalidWidth)),
+        ] {
+            assert_eq!(
+                parse_ascii_width(&width.map(|width| Text(width.to_string()))),
+                expected
+            );
+        }
+    }
+
+    // Verifies that supported character sets are detected by ImageFormParams::validate_render_charset() and the matching RenderCharset returned
+    #[test]
+    fn test_supported_render_charset() {
+        let mut input = ImageFormParams {
+            image_input: None,
+            ascii_width: None,
+            render_charset: None,
+            background_char: None,
+            symbol_set: None,
+            crop: None,
+            orientation: None,
+            brightness: None,
+            contrast: None,
+            show_rulers: None,
+            pipeline: None,
+            manifest: None,
+            title: None,
+            author: None,
+            license: None,
+        };
+
+        assert_eq!(input.validate_render_charset(), Ok(None));
+
+        input = ImageFormParams {
+            image_input: None,
+            ascii_width: None,
+            render_charset: Some(Text("ascii".to_string())),
+            background_char: None,
+            symbol_set: None,
+            crop: None,
+            orientation: None,
+            brightness: None,
+            contrast: None,
+            show_rulers: None,
+            pipeline: None,
+            manifest: None,
+            title: None,
+            author: None,
+            license: None,
+        };
+
+        assert_eq!(
+            input.validate_render_charset(),
+            Ok(Some(RenderCharset::Ascii))
+        );
+
+        input = ImageFormParams {
+            image_input: None,
+            ascii_width: None,
+            render_charset: Some(Text("braille".to_string())),
+            background_char: None,
+            symbol_set: None,
+            crop: None,
+            orientation: None,
+            brightness: None,
+            contrast: None,
+            show_rulers: None,
+            pipeline: None,
+            manifest: None,
+            title: None,
+            author: None,
+            license: None,
+        };
+
+        assert_eq!(
+            input.validate_render_charset(),
+            Ok(Some(RenderCharset::Braille))
+        );
+    }
+
+    // Verifies that an unsupported character set is detected by ImageFormParams::validate_render_charset() and error returned
+    #[test]
+    fn test_unsupported_render_charset() {
+        let input = ImageFormParams {
+            image_input: None,
+            ascii_width: None,
+            render_charset: Some(Text("emoji".to_string())),
+            background_char: None,
+            symbol_set: None,
+            crop: None,
+            orientation: None,
+            brightness: None,
+            contrast: None,
+            show_rulers: None,
+            pipeline: None,
+            manifest: None,
+            title: None,
+            author: None,
+            license: None,
+        };
+
+        assert_eq!(
+            input.validate_render_charset(),
+            Err(ImageInputError::UnsupportedCharset)
+        );
+    }
+
+    // Verifies that the name of each symbol set is accepted by ImageFormParams::validate_symbol_set() and any other name is rejected
+    #[test]
+    fn test_validate_symbol_set() {
+        for (symbol_set, expected) in [
+            (None, Ok(None)),
+            (Some(""), Ok(None)),
+            (Some("standard70"), Ok(Some(SymbolSet::Standard70))),
+            (Some("minimal10"), Ok(Some(SymbolSet::Minimal10))),
+            (Some("blocks"), Ok(Some(SymbolSet::Blocks))),
+            (Some("binary"), Ok(Some(SymbolSet::Binary))),
+            (Some("emoji"), Err(ImageInputError::UnsupportedSymbolSet)),
+        ] {
+            let input = ImageFormParams {
+                image_input: None,
+                ascii_width: None,
+                render_charset: None,
+                background_char: None,
+                symbol_set: symbol_set.map(|name| Text(name.to_string())),
+                crop: None,
+                orientation: None,
+                brightness: None,
+                contrast: None,
+                show_rulers: None,
+                pipeline: None,
+                manifest: None,
+                title: None,
+                author: None,
+                license: None,
+            };
+
+            assert_eq!(input.validate_symbol_set(), expected);
+        }
+    }
+
+    // Verifies that the region of the image is accurately validated by ImageFormParams::validate_crop()
+    #[test]
+    fn test_validate_crop() {
+        for (crop, expected) in [
+            (None, Ok(None)),
+            (Some(" "), Ok(None)),
+            (Some("10,20,300,200"), Ok(Some(Rect::new(10, 20, 300, 200)))),
+            (Some("0, 0, 1, 1"), Ok(Some(Rect::new(0, 0, 1, 1)))),
+            (Some("10,20,0,200"), Err(ImageInputError::InvalidCrop)),
+            (Some("10,20,300"), Err(ImageInputError::InvalidCrop)),
+            (Some("-10,20,300,200"), Err(ImageInputError::InvalidCrop)),
+            (Some("10.5,20,300,200"), Err(ImageInputError::InvalidCrop)),
+        ] {
+            let input = ImageFormParams {
+                image_input: None,
+                ascii_width: None,
+                render_charset: None,
+                background_char: None,
+                symbol_set: None,
+                crop: crop.map(|region| Text(region.to_string())),
+                orientation: None,
+                brightness: None,
+                contrast: None,
+                show_rulers: None,
+                pipeline: None,
+                manifest: None,
+                title: None,
+                author: None,
+                license: None,
+            };
+
+            assert_eq!(input.validate_crop(), expected);
+        }
+    }
+
+    // Verifies that the orientation is accurately validated by ImageFormParams::validate_orientation()
+    #[test]
+    fn test_validate_orientation() {
+        for (orientation, expected) in [
+            (None, Ok(None)),
+            (Some(""), Ok(None)),
+            (
+                Some("rotate90"),
+                Ok(Some(Stage::Rotate(Rotation::Clockwise90))),
+            ),
+            (Some("flip_vertical"), Ok(Some(Stage::Flip(Flip::Vertical)))),
+            (
+                Some("rotate45"),
+                Err(ImageInputError::UnsupportedOrientation),
+            ),
+        ] {
+            let input = ImageFormParams {
+                image_input: None,
+                ascii_width: None,
+                render_charset: None,
+                background_char: None,
+                symbol_set: None,
+                crop: None,
+                orientation: orientation.map(|name| Text(name.to_string())),
+                brightness: None,
+                contrast: None,
+                show_rulers: None,
+                pipeline: None,
+                manifest: None,
+                title: None,
+                author: None,
+                license: None,
+            };
+
+            assert_eq!(input.validate_orientation(), expected);
+        }
+    }
+
+    // Verifies that the brightness and contrast are accurately validated by ImageFormParams::validate_brightness() and
+    // ImageFormParams::validate_contrast()
+    #[test]
+    fn test_validate_adjustments() {
+        for (brightness, contrast, expected) in [
+            (None, None, Ok((0, 0.0))),
+            (Some(""), Some(""), Ok((0, 0.0))),
+            (Some("-40"), Some("25.5"), Ok((-40, 25.5))),
+            (Some("255"), Some("-100"), Ok((255, -100.0))),
+            (
+                Some("256"),
+                None,
+                Err(ImageInputError::UnsupportedAdjustment),
+            ),
+            (
+                Some("1.5"),
+                None,
+                Err(ImageInputError::UnsupportedAdjustment),
+            ),
+            (
+                None,
+                Some("101"),
+                Err(ImageInputError::UnsupportedAdjustment),
+            ),
+            (
+                None,
+                Some("NaN"),
+                Err(ImageInputError::UnsupportedAdjustment),
+            ),
+            (
+                None,
+                Some("more"),
+                Err(ImageInputError::UnsupportedAdjustment),
+            ),
+        ] {
+            let input = ImageFormParams {
+                image_input: None,
+                ascii_width: None,
+                render_charset: None,
+                background_char: None,
+                symbol_set: None,
+                crop: None,
+                orientation: None,
+                brightness: brightness.map(|value| Text(value.to_string())),
+                contrast: contrast.map(|value| Text(value.to_string())),
+                show_rulers: None,
+                pipeline: None,
+                manifest: None,
+                title: None,
+                author: None,
+                license: None,
+            };
+
+            assert_eq!(
+                input
+                    .validate_brightness()
+                    .and_then(|brightness| Ok((brightness, input.validate_contrast()?))),
+                expected
+            );
+        }
+    }
+
+    // Verifies that ImageFormParams::rulers_requested() is only true when the rulers checkbox is checked
+    #[test]
+    fn test_rulers_requested() {
+        for (show_rulers, expected) in [(None, false), (Some(false), false), (Some(true), true)] {
+            let input = ImageFormParams {
+                image_input: None,
+                ascii_width: None,
+                render_charset: None,
+                background_char: None,
+                symbol_set: None,
+                crop: None,
+                orientation: None,
+                brightness: None,
+                contrast: None,
+                show_rulers: show_rulers.map(Text),
+                pipeline: None,
+                manifest: None,
+                title: None,
+                author: None,
+                license: None,
+            };
+
+            assert_eq!(input.rulers_requested(), expected);
+        }
+    }
+
+    // Verifies that a missing, empty or space background is accepted by ImageFormParams::validate_background_char() and Ok(None) returned
+    #[test]
+    fn test_default_background_char() {
+        for background in [None, Some(""), Some(" ")] {
+            let input = ImageFormParams {
+                image_input: None,
+                ascii_width: None,
+                render_charset: None,
+                background_char: background.map(|b| Text(b.to_string())),
+                symbol_set: None,
+                crop: None,
+                orientation: None,
+                brightness: None,
+                contrast: None,
+                show_rulers: None,
+                pipeline: None,
+                manifest: None,
+                title: None,
+                author: None,
+                license: None,
+            };
+
+            assert_eq!(input.validate_background_char(), Ok(None));
+        }
+    }
+
+    // Verifies that a visible ASCII background is accepted by ImageFormParams::validate_background_char() and Ok(Some(char)) returned
+    #[test]
+    fn test_supported_background_char() {
+        for background in ['.', '_', '#'] {
+            let input = ImageFormParams {
+                image_input: None,
+                ascii_width: None,
+                render_charset: None,
+                background_char: Some(Text(background.to_string())),
+                symbol_set: None,
+                crop: None,
+                orientation: None,
+                brightness: None,
+                contrast: None,
+                show_rulers: None,
+                pipeline: None,
+                manifest: None,
+                title: None,
+                author: None,
+                license: None,
+            };
+
+            assert_eq!(input.validate_background_char(), Ok(Some(background)));
+        }
+    }
+
+    // Verifies that an unsupported background is detected by ImageFormParams::validate_background_char() and error returned
+    #[test]
+    fn test_unsupported_background_char() {
+        for background in ["..", "\t", "é", "\n"] {
+            let input = ImageFormParams {
+                image_input: None,
+                ascii_width: None,
+                render_charset: None,
+                background_char: Some(Text(background.to_string())),
+                symbol_set: None,
+                crop: None,
+                orientation: None,
+                brightness: None,
+                contrast: None,
+                show_rulers: None,
+                pipeline: None,
+                manifest: None,
+                title: None,
+                author: None,
+                license: None,
+            };
+
+            assert_eq!(
+                input.validate_background_char(),
+                Err(ImageInputError::UnsupportedBackground)
+            );
+        }
+    }
+
+    // Verifies that a missing or blank pipeline is accepted by ImageFormParams::validate_pipeline() and an empty Pipeline returned
+    #[test]
+    fn test_default_pipeline() {
+        for pipeline in [None, Some(""), Some("  ")] {
+            let input = ImageFormParams {
+                image_input: None,
+                ascii_width: None,
+                render_charset: None,
+                background_char: None,
+                symbol_set: None,
+                crop: None,
+                orientation: None,
+                brightness: None,
+                contrast: None,
+                show_rulers: None,
+                pipeline: pipeline.map(|p| Text(p.to_string())),
+                manifest: None,
+                title: None,
+                author: None,
+                license: None,
+            };
+
+            assert_eq!(input.validate_pipeline(), Ok(Pipeline::new()));
+        }
+    }
+
+    // Verifies that a JSON pipeline is parsed by ImageFormParams::validate_pipeline() and Ok(Pipeline) returned
+    #[test]
+    fn test_supported_pipeline() {
+        let input = ImageFormParams {
+            image_input: None,
+            ascii_width: None,
+            render_charset: None,
+            background_char: None,
+            symbol_set: None,
+            crop: None,
+            orientation: None,
+            brightness: None,
+            contrast: None,
+            show_rulers: None,
+            pipeline: Some(Text(
+                r#"[{"rotate":"clockwise90"},{"contrast":"equalize"}]"#.to_string(),
+            )),
+            manifest: None,
+            title: None,
+            author: None,
+            license: None,
+        };
+
+        assert_eq!(
+            input.validate_pipeline(),
+            Ok(Pipeline::new()
+                .rotate(Rotation::Clockwise90)
+                .contrast(ContrastMode::Equalize))
+        );
+    }
+
+    // Verifies that an unreadable or out of order pipeline is detected by ImageFormParams::validate_pipeline() and error returned
+    #[test]
+    fn test_invalid_pipeline() {
+        for pipeline in [
+            "rotate",
+            r#"[{"rotate":"clockwise45"}]"#,
+            r#"[{"dither":"bayer"},{"rotate":"clockwise90"}]"#,
+        ] {
+            let input = ImageFormParams {
+                image_input: None,
+                ascii_width: None,
+                render_charset: None,
+                background_char: None,
+                symbol_set: None,
+                crop: None,
+                orientation: None,
+                brightness: None,
+                contrast: None,
+                show_rulers: None,
+                pipeline: Some(Text(pipeline.to_string())),
+                manifest: None,
+                title: None,
+                author: None,
+                license: None,
+            };
+
+            assert_eq!(
+                input.validate_pipeline(),
+                Err(ImageInputError::InvalidPipeline)
+            );
+        }
+    }
+
+    // Verifies that a missing or blank manifest is accepted by ImageFormParams::validate_manifest() and None returned
+    #[test]
+    fn test_default_manifest() {
+        for manifest in [None, Some(""), Some("  ")] {
+            let input = ImageFormParams {
+                image_input: None,
+                ascii_width: None,
+                render_charset: None,
+                background_char: None,
+                symbol_set: None,
+                crop: None,
+                orientation: None,
+                brightness: None,
+                contrast: None,
+                show_rulers: None,
+                pipeline: None,
+                manifest: manifest.map(|m| Text(m.to_string())),
+                title: None,
+                author: None,
+                license: None,
+            };
+
+            assert_eq!(input.validate_manifest(), Ok(None));
+        }
+    }
+
+    // Verifies that a JSON manifest is parsed by ImageFormParams::validate_manifest() and Ok(Some(ConvertOptions)) returned
+    #[test]
+    fn test_supported_manifest() {
+        let input = ImageFormParams {
+            image_input: None,
+            ascii_width: None,
+            render_charset: None,
+            background_char: None,
+            symbol_set: None,
+            crop: None,
+            orientation: None,
+            brightness: None,
+            contrast: None,
+            show_rulers: None,
+            pipeline: None,
+            manifest: Some(Text(
+                r#"{"width":80,"charset":"braille","rulers":true}"#.to_string(),
+            )),
+            title: None,
+            author: None,
+            license: None,
+        };
+
+        let options = input.validate_manifest().unwrap().unwrap();
+        assert_eq!(options.width, Some(80));
+        assert_eq!(options.charset, RenderCharset::Braille);
+        assert!(options.rulers);
+    }
+
+    // Verifies that an unreadable manifest, or one with settings the form can't use, is detected by ImageFormParams::validate_manifest() and error returned
+    #[test]
+    fn test_invalid_manifest() {
+        for manifest in [
+            "width",
+            r#"{"width":81}"#,
+            r#"{"charset":"half_block"}"#,
+            r#"{"version":99}"#,
+            r#"{"pipeline":[{"dither":"bayer"},{"rotate":"clockwise90"}]}"#,
+        ] {
+            let input = ImageFormParams {
+                image_input: None,
+                ascii_width: None,
+                render_charset: None,
+                background_char: None,
+                symbol_set: None,
+                crop: None,
+                orientation: None,
+                brightness: None,
+                contrast: None,
+                show_rulers: None,
+                pipeline: None,
+                manifest: Some(Text(manifest.to_string())),
+                title: None,
+                author: None,
+                license: None,
+            };
+
+            assert_eq!(
+                input.validate_manifest(),
+                Err(ImageInputError::InvalidManifest)
+            );
+        }
+    }
+
+    // Verifies that the author and license are read by ImageFormParams::validate_attribution(), and that an unsupported license is detected and error returned
+    #[test]
+    fn test_validate_attribution() {
+        let input = |author: Option<&str>, license: Option<&str>| ImageFormParams {
+            image_input: None,
+            ascii_width: None,
+            render_charset: None,
+            background_char: None,
+            symbol_set: None,
+            crop: None,
+            orientation: None,
+            brightness: None,
+            contrast: None,
+            show_rulers: None,
+            pipeline: None,
+            manifest: None,
+            title: None,
+            author: author.map(|author| Text(author.to_string())),
+            license: license.map(|license| Text(license.to_string())),
+        };
+
+        assert_eq!(
+            input(Some(" Robert "), Some("cc-by-sa-4.0")).validate_attribution(),
+            Ok(Attribution {
+                author: Some("Robert".to_string()),
+                license: Some(License::CcBySa4),
+            })
+        );
+        assert_eq!(
+            input(Some(""), Some("")).validate_attribution(),
+            Ok(Attribution::default())
+        );
+        assert_eq!(
+            input(None, Some("mit")).validate_attribution(),
+            Err(ImageInputError::UnsupportedLicense)
+        );
     }
 }
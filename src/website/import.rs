@@ -0,0 +1,400 @@
+//! Module to import an archive made by the operator export into the stores of this server.
+//!
+//! Every file of the archive must be listed in its `metadata.json` file with the SHA-256 hash of its contents, so files that
+//! were damaged on the way are rejected instead of stored. Results and ASCII art that are already stored are skipped, so
+//! an archive can be imported again after an import was interrupted.
+//!
+//! Robert Peterson and Kelsey Werner 2023
+
+use super::{
+    art_store::{content_hash, is_content_hash, ArtStore},
+    export::{ExportMetadata, ART_FOLDER, EXPORT_VERSION, METADATA_NAME, RESULTS_FOLDER},
+    gallery::GalleryEntry,
+    result_store::{is_result_name, ResultStore},
+    results::file_name_from_route,
+};
+use flate2::read::GzDecoder;
+use std::{
+    collections::{HashMap, HashSet},
+    io::{self, Read},
+};
+use tar::Archive;
+
+/// The largest archive that can be uploaded to be imported, in bytes.
+pub const MAX_IMPORT_SIZE: usize = 104_857_600;
+
+/// The most bytes the files of an archive can hold once they are extracted.
+pub const MAX_EXTRACTED_SIZE: u64 = 524_288_000;
+
+/// Enum to store the possible error states that stop an archive from being imported.
+///
+/// The different enum variants are used to identify the specific cause of an error.
+#[derive(Debug)]
+pub enum ImportError {
+    /// [ImportError::InvalidArchive] error is caused when the upload is not a tar.gz archive.
+    InvalidArchive,
+    /// [ImportError::ArchiveTooLarge] error is caused when the files of the archive hold more than [MAX_EXTRACTED_SIZE] bytes.
+    ArchiveTooLarge,
+    /// [ImportError::InvalidMetadata] error is caused when the archive has no `metadata.json` file or it can't be read.
+    InvalidMetadata,
+    /// [ImportError::UnsupportedVersion] error is caused when the archive was made by a newer [EXPORT_VERSION].
+    UnsupportedVersion,
+    /// [ImportError::StorageError] error is caused when a file can't be stored.
+    StorageError,
+}
+
+/// Struct to store what happened to the files of an imported archive.
+#[derive(Default, PartialEq, Debug)]
+pub struct ImportSummary {
+    /// The number of results and ASCII art that were stored.
+    pub imported_files: usize,
+    /// The number of results and ASCII art that were already stored.
+    pub duplicate_files: usize,
+    /// The number of conversions that were added to the gallery.
+    pub imported_conversions: usize,
+    /// The number of conversions that the gallery already had.
+    pub duplicate_conversions: usize,
+    /// The files and conversions that were left out, along with why.
+    pub rejected: Vec<String>,
+}
+
+impl ImportSummary {
+    /// Function to describe the import in plain text for the operator.
+    pub fn report(&self) -> String {
+        let mut report = format!(
+            "Imported {} files and {} gallery conversions.\nSkipped {} files and {} gallery conversions that were already stored.\n",
+            self.imported_files,
+            self.imported_conversions,
+            self.duplicate_files,
+            self.duplicate_conversions
+        );
+        if !self.rejected.is_empty() {
+            report.push_str(&format!("Rejected {}:\n", self.rejected.len()));
+            for rejected in &self.rejected {
+                report.push_str(rejected);
+                report.push('\n');
+            }
+        }
+        report
+    }
+}
+
+/// Function to read every file of a tar.gz archive.
+fn read_archive<R: Read>(reader: R) -> Result<Vec<(String, Vec<u8>)>, ImportError> {
+    let mut archive = Archive::new(GzDecoder::new(reader));
+    let mut files = Vec::new();
+    let mut extracted_size = 0;
+
+    for entry in archive.entries().map_err(|_| ImportError::InvalidArchive)? {
+        let entry = entry.map_err(|_| ImportError::InvalidArchive)?;
+        if !entry.header().entry_type().is_file() {
+            continue;
+        }
+        let path = entry
+            .path()
+            .map_err(|_| ImportError::InvalidArchive)?
+            .to_string_lossy()
+            .to_string();
+
+        // the size in the archive can't be trusted, so no more than the limit is extracted
+        let mut data = Vec::new();
+        entry
+            .take(MAX_EXTRACTED_SIZE - extracted_size + 1)
+            .read_to_end(&mut data)
+            .map_err(|_| ImportError::InvalidArchive)?;
+        extracted_size += data.len() as u64;
+        if extracted_size > MAX_EXTRACTED_SIZE {
+            return Err(ImportError::ArchiveTooLarge);
+        }
+        files.push((path, data));
+    }
+
+    Ok(files)
+}
+
+/// Function to store a file of the archive in the [ResultStore] or the [ArtStore].
+///
+/// Returns `Ok(Ok(true))` when the file is stored, `Ok(Ok(false))` when it was already stored,
+/// and `Ok(Err(reason))` when the file can't be stored in either.
+fn import_file(
+    path: &str,
+    data: Vec<u8>,
+    results: &dyn ResultStore,
+    art_store: &ArtStore,
+) -> io::Result<Result<bool, &'static str>> {
+    if let Some(name) = path
+        .strip_prefix(RESULTS_FOLDER)
+        .and_then(|rest| rest.strip_prefix('/'))
+    {
+        if !is_result_name(name) {
+            return Ok(Err("its name can't be used for a result"));
+        }
+        return match results.read(name)? {
+            Some(stored) if stored == data => Ok(Ok(false)),
+            Some(_) => Ok(Err("a different result is already stored under its name")),
+            None => results.write(name, &data).map(|_| Ok(true)),
+        };
+    }
+
+    if let Some(hash) = path
+        .strip_prefix(ART_FOLDER)
+        .and_then(|rest| rest.strip_prefix('/'))
+        .and_then(|rest| rest.strip_suffix(".txt"))
+    {
+        if !is_content_hash(hash) || content_hash(&data) != hash {
+            return Ok(Err("its name is not the SHA-256 hash of its ASCII art"));
+        }
+        return match String::from_utf8(data) {
+            Ok(ascii) => art_store.publish(&ascii).map(|(_, created)| Ok(created)),
+            Err(_) => Ok(Err("its ASCII art is not valid text")),
+        };
+    }
+
+    Ok(Err("it is not a result or published ASCII art"))
+}
+
+/// Function to store the results and ASCII art of an archive made by the operator export.
+///
+/// Each file is only stored when its SHA-256 hash matches the hash in `metadata.json`, and is skipped when it is already stored.
+/// Returns the [ImportSummary] of the files along with the conversions of the gallery whose results are now stored,
+/// which are left for the caller to add to the gallery.
+pub fn import_files<R: Read>(
+    reader: R,
+    results: &dyn ResultStore,
+    art_store: &ArtStore,
+) -> Result<(ImportSummary, Vec<GalleryEntry>), ImportError> {
+    let mut files = read_archive(reader)?;
+
+    let metadata_position = files
+        .iter()
+        .position(|(path, _)| path == METADATA_NAME)
+        .ok_or(ImportError::InvalidMetadata)?;
+    let (_, metadata) = files.remove(metadata_position);
+    let metadata: ExportMetadata =
+        serde_json::from_slice(&metadata).map_err(|_| ImportError::InvalidMetadata)?;
+    if metadata.version > EXPORT_VERSION {
+        return Err(ImportError::UnsupportedVersion);
+    }
+
+    let mut expected_hashes: HashMap<String, String> = metadata
+        .files
+        .into_iter()
+        .map(|file| (file.path, file.sha256))
+        .collect();
+    let mut summary = ImportSummary::default();
+
+    for (path, data) in files {
+        let outcome = match expected_hashes.remove(&path) {
+            None => Err("it is not listed in metadata.json"),
+            Some(hash) if hash != content_hash(&data) => {
+                Err("its SHA-256 hash doesn't match metadata.json")
+            }
+            Some(_) => import_file(&path, data, results, art_store)
+                .map_err(|_| ImportError::StorageError)?,
+        };
+        match outcome {
+            Ok(true) => summary.imported_files += 1,
+            Ok(false) => summary.duplicate_files += 1,
+            Err(reason) => summary.rejected.push(format!("{}: {}", path, reason)),
+        }
+    }
+
+    let mut missing: Vec<String> = expected_hashes.into_keys().collect();
+    missing.sort_unstable();
+    for path in missing {
+        summary
+            .rejected
+            .push(format!("{}: it is missing from the archive", path));
+    }
+
+    // conversions are only added to the gallery when their result can be shown
+    let stored: HashSet<String> = results
+        .names()
+        .map_err(|_| ImportError::StorageError)?
+        .into_iter()
+        .collect();
+    let mut conversions = Vec::new();
+    for entry in metadata.gallery {
+        let has_result =
+            file_name_from_route(&entry.output_path).is_some_and(|name| stored.contains(name));
+        if has_result {
+            conversions.push(entry);
+        } else {
+            summary.rejected.push(format!(
+                "gallery conversion {}: its result {} is not stored",
+                entry.id, entry.output_path
+            ));
+        }
+    }
+
+    Ok((summary, conversions))
+}
+
+// Tests
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::website::{
+        export::write_export, gallery::ConversionKind, result_store::DirectoryResultStore,
+    };
+    use flate2::{write::GzEncoder, Compression};
+    use tar::{Builder, Header};
+
+    // Creates a tar.gz archive that holds the given files
+    fn tar_gz(files: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut archive = Builder::new(GzEncoder::new(Vec::new(), Compression::default()));
+        for (path, data) in files {
+            let mut header = Header::new_gnu();
+            header.set_size(data.len() as u64);
+            archive.append_data(&mut header, path, *data).unwrap();
+        }
+        archive.into_inner().unwrap().finish().unwrap()
+    }
+
+    // Verifies that an export can be imported into empty stores, and that importing it again only skips duplicates
+    #[test]
+    fn test_import_files() {
+        let root = tempfile::tempdir().unwrap();
+        let exported_results = DirectoryResultStore::new(root.path().join("old-results")).unwrap();
+        let exported_art = ArtStore::new(root.path().join("old-art")).unwrap();
+        exported_results.write("castle.txt", b"$$\n").unwrap();
+        exported_art.publish("..\n").unwrap();
+        let gallery = vec![
+            GalleryEntry {
+                id: 1,
+                kind: ConversionKind::ImageToAscii,
+                input_hash: content_hash("image"),
+                output_path: "conversion_results/castle.txt".to_string(),
+                title: Some("Castle".to_string()),
+                created_at: "2023-06-09 04:58:50".to_string(),
+                author: None,
+                license: None,
+                charset: None,
+                hidden: false,
+            },
+            GalleryEntry {
+                id: 2,
+                kind: ConversionKind::AsciiToImage,
+                input_hash: content_hash("ascii"),
+                output_path: "conversion_results/removed.png".to_string(),
+                title: None,
+                created_at: "2023-06-09 04:58:51".to_string(),
+                author: None,
+                license: None,
+                charset: None,
+                hidden: false,
+            },
+        ];
+        let archive = write_export(&exported_results, &exported_art, gallery, Vec::new()).unwrap();
+
+        let results = DirectoryResultStore::new(root.path().join("results")).unwrap();
+        let art_store = ArtStore::new(root.path().join("art")).unwrap();
+        let (summary, conversions) = import_files(&archive[..], &results, &art_store).unwrap();
+
+        assert_eq!(summary.imported_files, 2);
+        assert_eq!(summary.duplicate_files, 0);
+        assert_eq!(
+            summary.rejected,
+            vec!["gallery conversion 2: its result conversion_results/removed.png is not stored"]
+        );
+        assert_eq!(conversions.len(), 1);
+        assert_eq!(conversions[0].title, Some("Castle".to_string()));
+        assert_eq!(results.read("castle.txt").unwrap(), Some(b"$$\n".to_vec()));
+        assert_eq!(
+            art_store.get(&content_hash("..\n")).unwrap(),
+            Some("..\n".to_string())
+        );
+
+        let (summary, _) = import_files(&archive[..], &results, &art_store).unwrap();
+
+        assert_eq!(summary.imported_files, 0);
+        assert_eq!(summary.duplicate_files, 2);
+    }
+
+    // Verifies that files with the wrong hash, unlisted files and missing files are rejected without being stored
+    #[test]
+    fn test_import_files_rejected() {
+        let root = tempfile::tempdir().unwrap();
+        let results = DirectoryResultStore::new(root.path().join("results")).unwrap();
+        let art_store = ArtStore::new(root.path().join("art")).unwrap();
+        let metadata = serde_json::json!({
+            "version": EXPORT_VERSION,
+            "files": [
+                { "path": "results/damaged.txt", "sha256": content_hash("$$\n") },
+                { "path": "results/missing.txt", "sha256": content_hash("..\n") },
+            ],
+            "gallery": [],
+        })
+        .to_string();
+        let archive = tar_gz(&[
+            ("results/damaged.txt", b"$.\n"),
+            ("results/extra.txt", b"extra"),
+            (METADATA_NAME, metadata.as_bytes()),
+        ]);
+
+        let (summary, _) = import_files(&archive[..], &results, &art_store).unwrap();
+
+        assert_eq!(summary.imported_files, 0);
+        assert_eq!(
+            summary.rejected,
+            vec![
+                "results/damaged.txt: its SHA-256 hash doesn't match metadata.json",
+                "results/extra.txt: it is not listed in metadata.json",
+                "results/missing.txt: it is missing from the archive",
+            ]
+        );
+        assert!(results.names().unwrap().is_empty());
+    }
+
+    // Verifies that uploads that aren't archives of the export are rejected as a whole
+    #[test]
+    fn test_import_files_errors() {
+        let root = tempfile::tempdir().unwrap();
+        let results = DirectoryResultStore::new(root.path().join("results")).unwrap();
+        let art_store = ArtStore::new(root.path().join("art")).unwrap();
+
+        assert!(matches!(
+            import_files(&b"not an archive"[..], &results, &art_store),
+            Err(ImportError::InvalidArchive)
+        ));
+        assert!(matches!(
+            import_files(
+                &tar_gz(&[("results/a.txt", b"a")])[..],
+                &results,
+                &art_store
+            ),
+            Err(ImportError::InvalidMetadata)
+        ));
+
+        let metadata = format!(
+            r#"{{"version":{},"files":[],"gallery":[]}}"#,
+            EXPORT_VERSION + 1
+        );
+        assert!(matches!(
+            import_files(
+                &tar_gz(&[(METADATA_NAME, metadata.as_bytes())])[..],
+                &results,
+                &art_store
+            ),
+            Err(ImportError::UnsupportedVersion)
+        ));
+    }
+
+    // Verifies that ImportSummary::report() lists the rejected files after the counts
+    #[test]
+    fn test_report() {
+        let summary = ImportSummary {
+            imported_files: 3,
+            duplicate_files: 1,
+            imported_conversions: 2,
+            duplicate_conversions: 0,
+            rejected: vec!["results/a.txt: it is missing from the archive".to_string()],
+        };
+
+        assert_eq!(
+            summary.report(),
+            "Imported 3 files and 2 gallery conversions.\nSkipped 1 files and 0 gallery conversions that were already stored.\nRejected 1:\nresults/a.txt: it is missing from the archive\n"
+        );
+    }
+}
@@ -8,46 +8,194 @@
 //! Robert Peterson and Kelsey Werner 2023
 
 use super::{
+    accounting::record_usage,
+    animation_form_params::{AnimationFormParams, AnimationInputError},
+    art_store::{content_hash, ArtStore},
     ascii_form_params::{AsciiFormParams, AsciiInputError},
+    attribution::Attribution,
+    batch_form_params::{BatchFormParams, BatchInputError, ERRORS_NAME},
+    collage_form_params::{CollageFormParams, CollageInputError},
+    conversion_defaults::ConversionDefaults,
+    export::{write_export, ExportStream},
+    frame_stream::{stream_frames, FrameStream},
+    gallery::{ConversionKind, Gallery, GallerySearch},
     html_template::HtmlTemplate,
     image_form_params::{ImageFormParams, ImageInputError},
+    import::{import_files, ImportError},
+    jobs::{JobError, JobQueue},
+    line_stream::{stream_lines, LineStream},
+    metrics_store::record_conversion,
+    moderation::{clean_details, ModerationAction, ReportFormParams},
+    result_store::{count_write_failure, ResultStore},
+    results::{
+        file_content_type, file_name_from_route, file_route, remove_result, ResultRecord,
+        HIGH_DPI_EXTENSION, HIGH_DPI_SCALE, MANIFEST_EXTENSION,
+    },
+    text_form_params::{TextFormParams, TextInputError},
 };
+use actix_web::web::{self, Bytes};
 use ascii_art_converter::{
-    ascii_to_image,
-    converter::ConvertError::{UnknownASCIISymbol, WriteError},
-    image_to_ascii,
+    conversion_warnings,
+    converter::{
+        animation::{AsciiFrame, AsciiFrames},
+        asciicast::frames_to_cast,
+        banner::text_to_banner,
+        image::{ProgressCallback, SUPPORTED_FORMATS},
+        manifest::Manifest,
+        options::{ConvertOptions, ImageRenderMode, UnknownSymbolPolicy},
+        pipeline::Pipeline,
+        png_text::add_text_chunks,
+        service::{Converter, DEFAULT_CHARSET},
+        symbol_map::SymbolMap,
+        ConvertError,
+        ConvertError::{
+            EmptyInput, InvalidCrop, InvalidGamma, InvalidPipeline, TooLarge, UnknownASCIISymbol,
+            WriteError,
+        },
+    },
+    inspect,
 };
+use base64::{engine::general_purpose::STANDARD, Engine};
 use std::{
-    fs::File,
-    io::{BufReader, Write},
+    io::{self, Cursor, Write},
+    sync::Arc,
+};
+use zip::{result::ZipResult, write::FileOptions, CompressionMethod, ZipWriter};
+
+#[cfg(feature = "qr")]
+use super::qr_form_params::{QrAsciiFormParams, QrFormParams, QrInputError};
+#[cfg(feature = "qr")]
+use ascii_art_converter::converter::{
+    qr::{qr_ascii_to_png, text_to_qr_ascii},
+    ConvertError::{InvalidQrCode, QrDataTooLong},
 };
-use uuid::Uuid;
 
-/// Function to take a buffer of `Vec<u86>` and output the content buffer into a PNG image file.
+/// The message of a stream of ASCII art whose client went away before the conversion finished.
+const STREAM_CLOSED_ERROR: &str = "The stream was closed before the image was converted.";
+
+/// The message shown when the frames of an animation can't be converted.
+const ANIMATION_PARSE_ERROR: &str = "It looks like we ran into an issue with parsing your animation! Try it one more time, but if that doesn't work, try a different GIF.\n";
+
+/// Function to take a buffer of bytes and output the content buffer into an image file with the given extension (such as "png" or "svg"),
+/// or into a text file of ASCII art with the "txt" extension.
+///
+/// The image file is stored in the [ResultStore], which serves it from the route given by [file_route].
+/// The name of the image file is the id of the [ResultRecord] of the conversion, which is generated using the uuid crate to
+/// ensure that the file will always have a unique name, and the file is added to the record.
+/// This dyamically generated image name is returned as a [String], or the error of the store if the file can't be stored.
+fn create_image_file(
+    results: &dyn ResultStore,
+    record: &mut ResultRecord,
+    buffer: &[u8],
+    extension: &str,
+) -> io::Result<String> {
+    let file_name = record.add_file(extension);
+    results.write(&file_name, buffer)?;
+    Ok(file_name)
+}
+
+/// Function to store the files of a result, each given with its extension, and then its [ResultRecord] in the [ResultStore].
 ///
-/// The image file is stored in ./static/conversion_results/.
-/// The name of the PNG file is dynamically generated using the uuid crate to ensure that the file will always have a unique name.
-/// This dyamically generated image name is returned as a [String].
-fn create_image_file(buffer: Vec<u8>) -> String {
-    let file_name: String = format!("{}.png", Uuid::new_v4());
-    let file_path: String = format!("./static/conversion_results/{}", file_name);
+/// Returns the routes of the files in the same order, along with the permalink of the result.
+/// When the store fails, for example because the disk is full, the failure is counted by [count_write_failure] and the files
+/// that were already stored are removed. Each route is then a data URL with the content of the file instead, so the result
+/// can still be shown and downloaded, and there is no permalink since the result can't be shared.
+fn store_result_files(
+    results: &dyn ResultStore,
+    record: &mut ResultRecord,
+    files: &[(&[u8], &str)],
+) -> (Vec<String>, Option<String>) {
+    let stored = files
+        .iter()
+        .map(|(buffer, extension)| create_image_file(results, record, buffer, extension))
+        .collect::<io::Result<Vec<String>>>()
+        .and_then(|file_names| record.save(results).map(|_| file_names));
 
-    let mut file = File::create(file_path)
-        .expect("Failed to create image file after converting from ASCII art.");
-    file.write_all(&buffer)
-        .expect("Failed to populate file after creating image from ASCII art.");
+    match stored {
+        Ok(file_names) => (
+            file_names.iter().map(|name| file_route(name)).collect(),
+            Some(record.permalink()),
+        ),
+        Err(error) => {
+            count_write_failure(&error);
+            for file_name in &record.files {
+                let _ = results.remove(file_name);
+            }
+
+            (data_urls(record, files), None)
+        }
+    }
+}
+
+/// Function to put the files of a result, each given with its extension, into data URLs with the content of the file,
+/// so the result can be shown and downloaded without storing it in the [ResultStore].
+fn data_urls(record: &ResultRecord, files: &[(&[u8], &str)]) -> Vec<String> {
+    files
+        .iter()
+        .map(|(buffer, extension)| {
+            let content_type = file_content_type(&format!("{}.{}", record.id, extension))
+                .unwrap_or("application/octet-stream");
+            format!(
+                "data:{};base64,{}",
+                content_type.replace(' ', ""),
+                STANDARD.encode(buffer)
+            )
+        })
+        .collect()
+}
 
-    file_name
+/// Function to get the default [SymbolMap] from the charsets of the shared [Converter].
+fn default_symbol_map(converter: &Converter) -> SymbolMap {
+    converter
+        .charset(DEFAULT_CHARSET)
+        .cloned()
+        .unwrap_or_default()
 }
 
 /// Function to transform ASCII text into a PNG image in an HTML template.
 ///
-/// This function uses the [super::ascii_form_params] module to validate and sanitize the ASCII text.
-/// Then if there are no errors, the text is passed to the [ascii_art_converter::ascii_to_image] function which does the actual work
-/// of transforming the ASCII text into a PNG image.
+/// This function uses the [super::ascii_form_params] module to normalize, repair, validate, and sanitize the ASCII text.
+/// Then if there are no errors, the text is passed to the shared [Converter] which does the actual work
+/// of transforming the ASCII text into a PNG image in the [ImageRenderMode] the user picked (or the one of the [ConversionDefaults]), and into an SVG so that the image
+/// can also be downloaded as an SVG. The author and license the user gave are written into the text metadata of the PNG image.
+/// Both images are saved in the [ResultStore] along with a [ResultRecord] so the result can be shared, unless the user asked
+/// for the result inline, in which case the images are returned as data URLs and nothing is stored.
 /// An instance of a [HtmlTemplate] variant populated with valid data is returned for both error and success states.
-pub fn generate_ascii_to_image_result<'a>(params: AsciiFormParams) -> HtmlTemplate<'a> {
-    match params.validate_ascii_input() {
+pub fn generate_ascii_to_image_result<'a>(
+    mut params: AsciiFormParams,
+    converter: &Converter,
+    results: &dyn ResultStore,
+    defaults: &ConversionDefaults,
+) -> HtmlTemplate<'a> {
+    let warnings: Vec<String> = params
+        .normalize_ascii_input()
+        .iter()
+        .map(|warning| warning.to_string())
+        .collect();
+    let notice = if params.repair_soft_wrapped_input() {
+        Some("It looks like your ASCII art was soft-wrapped when it was copied, so we rejoined the wrapped lines before converting it.")
+    } else {
+        None
+    };
+
+    let symbol_map = default_symbol_map(converter);
+    let render_mode = match params.render_pixels {
+        Some(true) => ImageRenderMode::Pixels,
+        Some(false) => ImageRenderMode::Glyphs,
+        None => defaults.render_mode,
+    };
+    let policy = if params.replace_unknown_symbols {
+        UnknownSymbolPolicy::NearestMatch
+    } else {
+        UnknownSymbolPolicy::Error
+    };
+
+    match params
+        .validate_ascii_input()
+        .and_then(|_| params.validate_orientation())
+        // the PNG is turned once it is drawn, so the orientation is kept as a pipeline of at most one stage
+        .and_then(|orientation| Ok((params.validate_attribution()?, orientation.into_iter().fold(Pipeline::new(), Pipeline::stage))))
+    {
         // Display err/or page to user if submitted form is empty
         Err(AsciiInputError::EmptyInput) => {
             HtmlTemplate::Error {
@@ -55,28 +203,108 @@ pub fn generate_ascii_to_image_result<'a>(params: AsciiFormParams) -> HtmlTempla
                 try_again_link: "/ascii-to-image"
             }
         }
-        // Display error page to user if submitted form contains non-ASCII characters
-        Err(AsciiInputError::NotAsciiInput) => {
+        // Display error page to user if submitted form contains non-ASCII characters,
+        // listing where the first few of them are so the user can find them
+        Err(AsciiInputError::NotAsciiInput { offenders, total }) => {
+            let mut error_details: Vec<String> =
+                offenders.iter().map(|offender| offender.describe()).collect();
+            if total > offenders.len() {
+                error_details.push(format!(
+                    "...and {} more.",
+                    total - offenders.len()
+                ));
+            }
+
+            HtmlTemplate::ErrorWithDetails {
+                error_message: format!(
+                    "This form only accepts ASCII characters! We found {} non-ASCII character(s) in the pasted text:",
+                    total
+                ),
+                error_details,
+                try_again_link: "/ascii-to-image",
+            }
+        }
+        Err(AsciiInputError::UnsupportedLicense) => {
+            HtmlTemplate::Error {
+                error_message: "It looks like you picked an unsupported license! Be sure to pick one of the licenses listed on the form.",
+                try_again_link: "/ascii-to-image"
+            }
+        }
+        Err(AsciiInputError::UnsupportedOrientation) => {
             HtmlTemplate::Error {
-                error_message: "This form only accepts ASCII characters! Be sure to double check that all pasted text is valid ASCII.",
+                error_message: "It looks like you picked an unsupported rotation! Be sure to pick one of the orientations listed on the form.",
                 try_again_link: "/ascii-to-image"
             }
         }
-        Ok(_) => match ascii_to_image(&params.ascii_input) {
-            Ok(image) => {
-                let file_name = create_image_file(image.into_inner());
+        // the conversion is counted by how it ended, for the dashboard of the operators
+        Ok((attribution, orientation)) => match record_conversion(ConversionKind::AsciiToImage, converter.ascii_to_oriented_image(&params.ascii_input, &symbol_map, render_mode, policy, 1, &orientation)
+            .and_then(|image| add_text_chunks(&image.into_inner(), &attribution.png_text_entries()))
+            // a copy twice as large is drawn for high-DPI screens, which the result page offers in a srcset
+            .and_then(|image| Ok((image, converter.ascii_to_oriented_image(&params.ascii_input, &symbol_map, render_mode, policy, HIGH_DPI_SCALE, &orientation)?)))
+            .and_then(|(image, image_2x)| Ok((image, add_text_chunks(&image_2x.into_inner(), &attribution.png_text_entries())?)))
+            .and_then(|(image, image_2x)| Ok((image, image_2x, converter.ascii_to_svg(&params.ascii_input, &symbol_map, policy)?))))
+        {
+            Ok((image, image_2x, svg)) => {
+                let mut record = ResultRecord::new(ConversionKind::AsciiToImage);
+                record.attribution = attribution.clone();
+                // the ASCII art is kept too so it can be downloaded with the images in the bundle of the result
+                let files: [(&[u8], &str); 4] = [
+                    (&image, "png"),
+                    (&image_2x, HIGH_DPI_EXTENSION),
+                    (svg.as_bytes(), "svg"),
+                    (params.ascii_input.as_bytes(), "txt"),
+                ];
+                // inline results are never written to the store, so they have nothing to share
+                let (routes, permalink) = if params.inline_result {
+                    (data_urls(&record, &files), None)
+                } else {
+                    store_result_files(results, &mut record, &files)
+                };
 
                 HtmlTemplate::AsciiToImageResult {
-                    image_result: format!("conversion_results/{}", file_name),
+                    image_result: routes[0].clone(),
+                    image_result_2x: routes[1].clone(),
+                    svg_result: routes[2].clone(),
+                    notice,
+                    warnings,
+                    permalink,
+                    attribution,
                 }
             }
-            Err(WriteError) => {
+            Err(WriteError(_)) => {
                 HtmlTemplate::Error {
                         error_message: "It looks like we ran into an issue with parsing your ASCII art! Wait a few minutes, and try it one more time. But if that doesn't work, try a different piece of ASCII art.",
                         try_again_link: "/ascii-to-image"
                     }
             }
-            Err(UnknownASCIISymbol(symbol)) => {
+            // the form isn't empty, but the text has nothing to draw once its line breaks are left out
+            Err(EmptyInput) => {
+                HtmlTemplate::Error {
+                    error_message: "It looks like the ASCII art you submitted only has blank lines! Be sure to paste the whole piece of ASCII art into the text box of the form.",
+                    try_again_link: "/ascii-to-image"
+                }
+            }
+            // the ASCII has more lines or columns than the converter draws
+            Err(TooLarge { .. }) => {
+                HtmlTemplate::Error {
+                    error_message: "It looks like the ASCII art you submitted is too large for us to draw! Be sure the ASCII art has no more than a few thousand lines and columns, or try a smaller piece of ASCII art.",
+                    try_again_link: "/ascii-to-image"
+                }
+            }
+            // Display the line the unsupported character is on when its position is known, so the user can find it
+            Err(UnknownASCIISymbol(symbol, Some(position))) => {
+                HtmlTemplate::ErrorWithLocation {
+                    error_message: format!(
+                        "The ASCII art you submitted contains an unsupported character: {}. Please try again with a piece of ASCII art that only contains supported symbols.",
+                        symbol
+                    ),
+                    line: position.y + 1,
+                    column: position.x + 1,
+                    excerpt: params.ascii_input.lines().nth(position.y as usize).unwrap_or_default().to_string(),
+                    try_again_link: "/ascii-to-image"
+                }
+            }
+            Err(UnknownASCIISymbol(symbol, None)) => {
                 HtmlTemplate::ErrorMultiLine {
                         error_message: format!(
                             "The ASCII art you submitted contains an unsupported character: {}",
@@ -96,281 +324,3083 @@ pub fn generate_ascii_to_image_result<'a>(params: AsciiFormParams) -> HtmlTempla
     }
 }
 
+/// Function to validate the image to ASCII form, and read the [ConvertOptions] it picks.
+///
+/// The ASCII art width and the character set come from the [ConversionDefaults] when they aren't picked, and shared
+/// settings replace every other choice on the form. Returns the contents of the image file along with the options and the attribution of
+/// the conversion, or the [ImageInputError] of the first choice that isn't valid.
+fn image_form_options<'a>(
+    form: &'a ImageFormParams,
+    converter: &Converter,
+    defaults: &ConversionDefaults,
+) -> Result<(&'a [u8], ConvertOptions, Attribution), ImageInputError> {
+    let image_file = &form.validate_image_input()?.data[..];
+    let attribution = form.validate_attribution()?;
+    // shared settings replace all of the other choices on the form
+    if let Some(options) = form.validate_manifest()? {
+        return Ok((image_file, options, attribution));
+    }
+
+    let symbol_map = match form.validate_symbol_set()? {
+        Some(symbol_set) => SymbolMap::from(symbol_set),
+        None => default_symbol_map(converter),
+    };
+    let symbol_map = match form.validate_background_char()? {
+        Some(background) => symbol_map.with_background(background),
+        None => symbol_map,
+    };
+    let options = ConvertOptions {
+        width: form.validate_ascii_width()?.or(defaults.width),
+        charset: form.validate_render_charset()?.unwrap_or(defaults.charset),
+        symbol_map,
+        brightness: form.validate_brightness()?,
+        contrast_adjustment: form.validate_contrast()?,
+        rulers: form.rulers_requested(),
+        crop: form.validate_crop()?,
+        // the image is turned before the transformations, so that they work on the image as it is seen
+        pipeline: match form.validate_orientation()? {
+            Some(stage) => form
+                .validate_pipeline()?
+                .stages()
+                .iter()
+                .fold(Pipeline::new().stage(stage), |pipeline, stage| {
+                    pipeline.stage(*stage)
+                }),
+            None => form.validate_pipeline()?,
+        },
+        ..Default::default()
+    };
+    Ok((image_file, options, attribution))
+}
+
+/// Function to get the message that explains an [ImageInputError] to the user.
+fn image_input_error_message(error: ImageInputError) -> &'static str {
+    match error {
+        ImageInputError::EmptyInput => "It looks like you submitted an empty form! Be sure to upload an image to the form before submitting.",
+        ImageInputError::UnsupportedImageType => "It looks like you submitted an unsupported image type! Be sure to upload a JPEG, PNG, WebP, BMP or TIFF image only.",
+        ImageInputError::ImageTypeMismatch => "It looks like the contents of your image don't match its file type! Try saving the image again with the right file extension before uploading it.",
+        ImageInputError::UnsupportedWidth => "It looks like you selected an unsupported ASCII art width! Be sure to pick one of the widths listed on the form.",
+        ImageInputError::UnsupportedCharset => "It looks like you selected an unsupported character set! Be sure to pick one of the character sets listed on the form.",
+        ImageInputError::UnsupportedBackground => "It looks like you entered an unsupported background character! Be sure to enter a single ASCII character, or leave it blank to use spaces.",
+        ImageInputError::UnsupportedSymbolSet => "It looks like you selected an unsupported set of symbols! Be sure to pick one of the symbol sets listed on the form.",
+        ImageInputError::InvalidCrop => "It looks like the part of the image you selected couldn't be read! Be sure to select the part to convert by dragging over your image, or clear the selection.",
+        ImageInputError::UnsupportedOrientation => "It looks like you picked an unsupported rotation! Be sure to pick one of the orientations listed on the form.",
+        ImageInputError::UnsupportedAdjustment => "It looks like the brightness or contrast you picked is out of range! Be sure to use the sliders on the form to pick them.",
+        ImageInputError::InvalidPipeline => "It looks like you entered transformations we couldn't read! Be sure to paste the pipeline exactly as it was shown with your last ASCII art, or leave it blank.",
+        ImageInputError::InvalidManifest => "It looks like the shared settings you entered can't be used! Be sure to paste the settings exactly as they were shown with your last ASCII art, or leave them blank.",
+        ImageInputError::UnsupportedLicense => "It looks like you picked an unsupported license! Be sure to pick one of the licenses listed on the form.",
+    }
+}
+
+/// Function to get the message that explains why an image couldn't be converted into ASCII art to the user.
+fn image_conversion_error_message(error: ConvertError) -> &'static str {
+    match error {
+        InvalidCrop => "It looks like the part of the image you selected isn't inside of your image! Be sure to select the part to convert after choosing the image, or clear the selection.",
+        InvalidPipeline | InvalidGamma => "It looks like the transformations you entered can't be applied to your image! Be sure every crop fits inside of the image.",
+        // the header of the image asks for more pixels than the converter decodes, so it is never decoded
+        TooLarge { .. } => "It looks like your image is too large for us to convert! Be sure the image has no more than 100 megapixels, or try a smaller copy of it.",
+        _ => "It looks like we ran into an issue with parsing your image! There could be a problem with your image or with our parser, so try it one more time. But if that doesn't work, try a different image.",
+    }
+}
+
 /// Function to transform a JPEG or PNG image into ASCII art text in an HTML template.
 ///
-/// This function uses the [super::image_form_params] module to validate and sanitize the given image.
-/// Then if there are no errors, the image is passed to the [ascii_art_converter::image_to_ascii] function which does the actual work
-/// of transforming the image into ASCII text.
+/// This function uses the [super::image_form_params] module to validate and sanitize the given image, ASCII art width,
+/// character set and pipeline of transformations, where the width and character set that aren't picked come from the
+/// [ConversionDefaults]. Then if there are no errors, the image is passed to the shared [Converter]
+/// which does the actual work of transforming the image into ASCII (or braille) text. The text is saved in the [ResultStore]
+/// along with a [ResultRecord] so the result can be shared.
 /// An instance of a [HtmlTemplate] variant populated with valid data is returned for both error and success states.
-pub fn generate_image_to_ascii_result<'a>(form: ImageFormParams) -> HtmlTemplate<'a> {
-    match form.validate_image_input() {
-        Ok(image_file) => match image_to_ascii(&mut BufReader::new(&image_file.file)) {
-            Ok(ascii_art) => {
-                HtmlTemplate::ImageToAsciiResult {
-                    ascii_result: ascii_art,
-                }
-            }
-            Err(_) => {
-                HtmlTemplate::Error {
-                    error_message: "It looks like we ran into an issue with parsing your image! There could be a problem with your image or with our parser, so try it one more time. But if that doesn't work, try a different image.",
-                    try_again_link: "/image-to-ascii"
+pub fn generate_image_to_ascii_result<'a>(
+    form: ImageFormParams,
+    converter: &Converter,
+    results: &dyn ResultStore,
+    defaults: &ConversionDefaults,
+) -> HtmlTemplate<'a> {
+    generate_image_to_ascii_result_with_progress(form, converter, results, defaults, &|_, _| {})
+}
+
+/// Function to transform a JPEG or PNG image into ASCII art text in an HTML template, reporting how far along the
+/// conversion is.
+///
+/// This works just like [generate_image_to_ascii_result], but `progress` is called with the number of lines of ASCII art
+/// converted so far and the total number of lines, which is used by the conversion jobs of large uploads.
+pub fn generate_image_to_ascii_result_with_progress<'a>(
+    form: ImageFormParams,
+    converter: &Converter,
+    results: &dyn ResultStore,
+    defaults: &ConversionDefaults,
+    progress: ProgressCallback,
+) -> HtmlTemplate<'a> {
+    let validated_input = image_form_options(&form, converter, defaults);
+
+    match validated_input {
+        Ok((image_file, options, attribution)) => {
+            // the stages are listed on the result page so they can be replayed on another image
+            let applied_stages = options.applied_stages();
+            let pipeline = applied_stages
+                .iter()
+                .fold(Pipeline::new(), |pipeline, stage| pipeline.stage(*stage));
+            // the full set of options is shared so the same conversion can be run again
+            let manifest = Manifest::from(&options).to_json();
+            let share_link = format!(
+                "/image-to-ascii?{}",
+                serde_urlencoded::to_string([("manifest", &manifest)]).unwrap_or_default()
+            );
+
+            // only the header of the image is read, and the warnings are not worth failing the conversion over
+            let warnings = conversion_warnings(image_file, options.clone())
+                .unwrap_or_default()
+                .iter()
+                .map(|warning| warning.to_string())
+                .collect();
+
+            // the conversion is counted by how it ended, for the dashboard of the operators
+            match record_conversion(
+                ConversionKind::ImageToAscii,
+                converter.image_to_ascii_with_usage(image_file, options, progress),
+            ) {
+                Ok((ascii_art, usage)) => {
+                    record_usage(&usage);
+
+                    // the ASCII art is saved so it can be downloaded and shown again in the gallery
+                    let mut record = ResultRecord::new(ConversionKind::ImageToAscii);
+                    record.attribution = attribution.clone();
+                    record.usage = Some(usage);
+                    let (routes, permalink) = store_result_files(
+                        results,
+                        &mut record,
+                        &[
+                            (ascii_art.as_bytes(), "txt"),
+                            (manifest.as_bytes(), MANIFEST_EXTENSION),
+                        ],
+                    );
+
+                    // the text is downloaded from its own route when it was saved, and from a data URL otherwise
+                    let text_result = match permalink {
+                        Some(_) => record.text_link(),
+                        None => routes[0].clone(),
+                    };
+
+                    HtmlTemplate::ImageToAsciiResult {
+                        ascii_result: ascii_art,
+                        text_result,
+                        applied_stages: applied_stages
+                            .iter()
+                            .map(|stage| stage.to_string())
+                            .collect(),
+                        pipeline: serde_json::to_string(&pipeline).unwrap_or_default(),
+                        manifest,
+                        share_link,
+                        warnings,
+                        permalink,
+                        attribution,
+                    }
                 }
-            }
-        },
-        Err(ImageInputError::EmptyInput) => {
-            HtmlTemplate::Error {
-                error_message: "It looks like you submitted an empty form! Be sure to upload an image to the form before submitting.",
-                try_again_link: "/image-to-ascii"
-            }
-        }
-        Err(ImageInputError::UnsupportedImageType) => {
-            HtmlTemplate::Error {
-                error_message: "It looks like you submitted an unsupported image type! Be sure to upload either a JPEG or a PNG image only.",
-                try_again_link: "/image-to-ascii"
+                Err(error) => HtmlTemplate::Error {
+                    error_message: image_conversion_error_message(error),
+                    try_again_link: "/image-to-ascii",
+                },
             }
         }
+        Err(error) => HtmlTemplate::Error {
+            error_message: image_input_error_message(error),
+            try_again_link: "/image-to-ascii",
+        },
     }
 }
 
-// Tests
+/// Enum to store the outcome of starting to stream ASCII art, where `S` is the stream of the [LineStream] lines of an
+/// image or the [FrameStream] frames of an animation.
+pub enum StreamResult<S> {
+    /// [StreamResult::Streaming] is used when the conversion was started, and stores the stream of its ASCII art.
+    Streaming(S),
+    /// [StreamResult::Rejected] is used when the form is not valid, and stores a plain text message that explains why.
+    Rejected(&'static str),
+    /// [StreamResult::Busy] is used when too many conversions are already waiting for a worker of the [JobQueue].
+    Busy,
+}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use actix_multipart::form::tempfile::TempFile;
-    use regex::Regex;
-    use std::{
-        fs::{read, read_to_string, remove_file},
-        io::{Seek, SeekFrom::Start, Write},
+/// Function to transform a JPEG or PNG image into ASCII art text that is streamed line by line as it is converted.
+///
+/// The form is validated the same way as [generate_image_to_ascii_result], and then the image is converted by a worker
+/// of the [JobQueue], which writes each line to the returned [LineStream] as soon as it is ready. PNG images are also
+/// decoded a few rows at a time, so the first lines are sent before the whole image is read. Braille, half blocks, rulers
+/// and metadata headers need the whole text first, so their lines are all sent at the end.
+/// Since the lines are read by a script, plain text error messages are returned instead of an [HtmlTemplate], and the
+/// ASCII art is not saved.
+pub fn generate_image_to_ascii_stream(
+    form: ImageFormParams,
+    converter: web::Data<Converter>,
+    defaults: &ConversionDefaults,
+    jobs: &JobQueue,
+) -> StreamResult<LineStream> {
+    let (image, options) = match image_form_options(&form, converter.get_ref(), defaults) {
+        Ok((image_file, options, _)) => (image_file.to_vec(), options),
+        Err(error) => return StreamResult::Rejected(image_input_error_message(error)),
     };
-    use tempfile::NamedTempFile;
 
-    // Tests for create_image_file() function
+    let streamed = stream_lines(jobs, move |writer| {
+        // the conversion is counted by how it ended, for the dashboard of the operators
+        record_conversion(
+            ConversionKind::ImageToAscii,
+            converter.image_to_ascii_writer(image, options, writer),
+        )
+        .map_err(|error| match error {
+            // the client went away, so the message is never read
+            WriteError(_) => STREAM_CLOSED_ERROR,
+            error => image_conversion_error_message(error),
+        })
+    });
 
-    // Verifies that create_image_file() function correctly names and stores an image file with the expected content
-    #[test]
-    fn test_create_image_file() {
-        // Verify file created with correct name format
-        let file_contents: Vec<u8> = vec![1, 2, 3];
-        let result_file_name = create_image_file(file_contents.clone());
-        // Used https://regexr.com/ to help create regex
-        let expected_format = Regex::new(r"^\w{8}-\w{4}-\w{4}-\w{4}-\w{12}\.png$").unwrap();
+    match streamed {
+        Ok(line_stream) => StreamResult::Streaming(line_stream),
+        Err(JobError::QueueFull | JobError::Canceled) => StreamResult::Busy,
+    }
+}
 
-        assert!(expected_format.is_match(&result_file_name));
+/// Function to pack files into a ZIP archive, where each file is a name and its contents.
+fn create_zip_archive(files: &[(String, Vec<u8>)]) -> ZipResult<Vec<u8>> {
+    let mut archive = ZipWriter::new(Cursor::new(Vec::new()));
+    let options = FileOptions::default().compression_method(CompressionMethod::Deflated);
 
-        // Verify that file created in correct directory
-        // The idea to use "CARGO_MANIFEST_DIR" comes from StackOverflow:
-        // https://stackoverflow.com/questions/30003921/how-can-i-locate-resources-for-testing-with-cargo
-        let dir_path = concat!(env!("CARGO_MANIFEST_DIR"), "/static/conversion_results/");
-        // Found method for verifying if file exists on this website:
-        // https://programming-idioms.org/idiom/144/check-if-file-exists/1988/rust
-        let file_path = format!("{}{}", dir_path, result_file_name);
-        let does_file_exist = std::path::Path::new(&file_path).exists();
+    for (name, contents) in files {
+        archive.start_file(name.as_str(), options)?;
+        archive.write_all(contents)?;
+    }
+
+    Ok(archive.finish()?.into_inner())
+}
 
-        assert!(does_file_exist);
+/// Function to transform a batch of images into a ZIP archive of ASCII art.
+///
+/// This function uses the [super::batch_form_params] module to validate the images and the ASCII art width, which along with
+/// the character set comes from the [ConversionDefaults] when it isn't picked. Then if there are no errors, the JPEG, PNG, WebP, BMP and TIFF images are passed to the shared [Converter], which converts
+/// them at the same time. The ASCII art of each image is saved in the archive as a `.txt` file with the name of the image,
+/// and the images that couldn't be converted are listed in an `errors.txt` file along with the reason.
+/// Since the archive is downloaded by other tools, a plain text error message is returned instead of an [HtmlTemplate].
+pub fn generate_batch_result(
+    form: BatchFormParams,
+    converter: &Converter,
+    defaults: &ConversionDefaults,
+) -> Result<Vec<u8>, &'static str> {
+    let validated_input = form.validate_images().and_then(|images| {
+        let options = ConvertOptions {
+            width: form.validate_ascii_width()?.or(defaults.width),
+            charset: defaults.charset,
+            symbol_map: default_symbol_map(converter),
+            ..Default::default()
+        };
+        Ok((images, options))
+    });
 
-        // Verify that file has correct contents
-        let result_file = read(&file_path).unwrap();
+    let (images, options) = match validated_input {
+        Ok(validated_input) => validated_input,
+        Err(BatchInputError::EmptyInput) => {
+            return Err("It looks like you submitted an empty form! Be sure to upload images or a ZIP archive of images as images before submitting.\n")
+        }
+        Err(BatchInputError::InvalidArchive) => {
+            return Err("It looks like we couldn't open your ZIP archive! Try creating the archive again before uploading it.\n")
+        }
+        Err(BatchInputError::TooManyImages) => {
+            return Err("It looks like you submitted too many images! Be sure to convert no more than 100 images at a time.\n")
+        }
+        Err(BatchInputError::ImageTooLarge) => {
+            return Err("It looks like your ZIP archive has an image that is too large! Be sure every image is smaller than 10MB.\n")
+        }
+        Err(BatchInputError::UnsupportedWidth) => {
+            return Err("It looks like you selected an unsupported ASCII art width! Be sure to pick one of the widths listed on the image to ASCII form.\n")
+        }
+    };
 
-        assert_eq!(file_contents, result_file);
+    // only the image types the site supports are converted, just like on the image to ASCII form
+    let supported: Vec<bool> = images
+        .iter()
+        .map(|image| {
+            inspect(&image.data[..]).is_ok_and(|info| SUPPORTED_FORMATS.contains(&info.format))
+        })
+        .collect();
+    let mut results = converter
+        .convert_batch(
+            images
+                .iter()
+                .zip(&supported)
+                .filter(|(_, supported)| **supported)
+                .map(|(image, _)| &image.data[..]),
+            &options,
+        )
+        .into_iter();
 
-        // Clean up file created for test
-        remove_file(file_path).unwrap();
+    let mut files = Vec::new();
+    let mut errors = String::new();
+    for (image, supported) in images.iter().zip(supported) {
+        let result = if supported { results.next() } else { None };
+        match result {
+            Some(Ok(ascii_art)) => {
+                files.push((format!("{}.txt", image.name), ascii_art.into_bytes()))
+            }
+            Some(Err(_)) => errors.push_str(&format!(
+                "{}: the image couldn't be converted\n",
+                image.name
+            )),
+            None => errors.push_str(&format!(
+                "{}: the image is not a JPEG, PNG, WebP, BMP or TIFF\n",
+                image.name
+            )),
+        }
     }
 
-    // Tests for generate_image_to_ascii_result() function
+    if files.is_empty() {
+        return Err("It looks like none of your images could be converted! Be sure to upload JPEG, PNG, WebP, BMP or TIFF images only.\n");
+    }
+    if !errors.is_empty() {
+        files.push((format!("{}.txt", ERRORS_NAME), errors.into_bytes()));
+    }
 
-    // Verifies that the generate_ascii_to_image_result() function generates the correct file in the expected directory
-    // and returns the correctly poplated HtmlTemplate variant when there are no errors
-    #[test]
-    fn test_generate_ascii_to_image_result() {
-        // The idea to use "CARGO_MANIFEST_DIR" comes from StackOverflow:
-        // https://stackoverflow.com/questions/30003921/how-can-i-locate-resources-for-testing-with-cargo
-        let ascii_path = concat!(env!("CARGO_MANIFEST_DIR"), "/test_assets/ascii/castle.txt");
-        let ascii_text = read_to_string(ascii_path).unwrap();
+    create_zip_archive(&files).map_err(|_| {
+        "It looks like we ran into an issue with creating your ZIP archive! Try it one more time.\n"
+    })
+}
 
-        let params = AsciiFormParams {
-            ascii_input: ascii_text,
+/// Function to transform two to four images into a collage of their ASCII art.
+///
+/// This function uses the [super::collage_form_params] module to validate the images, the layout, the separators and the ASCII art
+/// width, which along with the character set comes from the [ConversionDefaults] when it isn't picked. Then if there are no errors,
+/// the images are passed to the shared [Converter], which converts them at the same time, and their ASCII art is stitched side by
+/// side or in a grid by a [Collage](ascii_art_converter::converter::collage::Collage).
+/// Since the collage is downloaded by other tools, a plain text error message is returned instead of an [HtmlTemplate].
+pub fn generate_collage_result(
+    form: CollageFormParams,
+    converter: &Converter,
+    defaults: &ConversionDefaults,
+) -> Result<String, &'static str> {
+    let validated_input = form.validate_images().and_then(|images| {
+        let options = ConvertOptions {
+            width: form.validate_ascii_width()?.or(defaults.width),
+            charset: defaults.charset,
+            symbol_map: default_symbol_map(converter),
+            ..Default::default()
         };
-        let result = generate_ascii_to_image_result(params);
+        Ok((images, form.validate_collage()?, options))
+    });
 
-        if let HtmlTemplate::AsciiToImageResult { image_result } = result {
-            // Verify file has correct format
-            // Used https://regexr.com/ to help create regex
-            let expected_format =
-                Regex::new(r"^conversion_results/\w{8}-\w{4}-\w{4}-\w{4}-\w{12}\.png$").unwrap();
+    let (images, collage, options) = match validated_input {
+        Ok(validated_input) => validated_input,
+        Err(CollageInputError::TooFewImages) => {
+            return Err("It looks like you submitted too few images! Be sure to upload at least 2 images as images before submitting.\n")
+        }
+        Err(CollageInputError::TooManyImages) => {
+            return Err("It looks like you submitted too many images! Be sure to put no more than 4 images in a collage.\n")
+        }
+        Err(CollageInputError::UnsupportedImageType) => {
+            return Err("It looks like you submitted an unsupported image type! Be sure to upload JPEG, PNG, WebP, BMP or TIFF images only.\n")
+        }
+        Err(CollageInputError::UnsupportedLayout) => {
+            return Err("It looks like you selected an unsupported layout! Be sure to pick side-by-side or grid as the layout.\n")
+        }
+        Err(CollageInputError::UnsupportedSeparator) => {
+            return Err("It looks like you submitted an unsupported separator! Be sure the separator has no more than 5 printable ASCII characters, and the row separator only one.\n")
+        }
+        Err(CollageInputError::UnsupportedWidth) => {
+            return Err("It looks like you selected an unsupported ASCII art width! Be sure to pick one of the widths listed on the image to ASCII form.\n")
+        }
+    };
 
-            assert!(expected_format.is_match(&image_result));
+    let blocks = converter
+        .convert_batch(images, &options)
+        .into_iter()
+        .collect::<Result<Vec<String>, _>>();
+    match blocks.and_then(|blocks| collage.stitch(&blocks)) {
+        Ok(collage) => Ok(collage),
+        Err(TooLarge { .. }) => Err("It looks like one of your images is too large for us to convert! Be sure every image has no more than 100 megapixels.\n"),
+        Err(_) => Err("It looks like we ran into an issue with parsing one of your images! Try it one more time, but if that doesn't work, try different images.\n"),
+    }
+}
 
-            // Verify that file created in correct directory
-            let image_name = image_result.split('/').collect::<Vec<_>>()[1];
-            // The idea to use "CARGO_MANIFEST_DIR" comes from StackOverflow:
-            // https://stackoverflow.com/questions/30003921/how-can-i-locate-resources-for-testing-with-cargo
-            let dir_path = concat!(env!("CARGO_MANIFEST_DIR"), "/static/conversion_results/");
-            // Found method for verifying if file exists on this website:
-            // https://programming-idioms.org/idiom/144/check-if-file-exists/1988/rust
-            let file_path = format!("{}{}", dir_path, image_name);
-            let does_file_exist = std::path::Path::new(&file_path).exists();
+/// Function to transform short text into a banner of large letters made of ASCII art in an HTML template.
+///
+/// This function uses the [super::text_form_params] module to validate the text, the font size and the character set, which comes
+/// from the [ConversionDefaults] when it isn't picked. Then if there are no errors, the text is drawn with the font of the converter
+/// and converted with the symbols of the shared [Converter], so the letters are made of the same symbols as converted images.
+/// Banners are not stored or added to the gallery, since they are quick to make again.
+/// An instance of a [HtmlTemplate] variant populated with valid data is returned for both error and success states.
+pub fn generate_text_to_ascii_result<'a>(
+    params: TextFormParams,
+    converter: &Converter,
+    defaults: &ConversionDefaults,
+) -> HtmlTemplate<'a> {
+    let validated_input = params.validate_text_input().and_then(|text| {
+        let options = ConvertOptions {
+            charset: params
+                .validate_render_charset()?
+                .unwrap_or(defaults.charset),
+            symbol_map: default_symbol_map(converter),
+            ..Default::default()
+        };
+        Ok((text, params.validate_font_size()?, options))
+    });
 
-            assert!(does_file_exist);
+    match validated_input {
+        Err(TextInputError::EmptyInput) => HtmlTemplate::Error {
+            error_message: "It looks like you submitted an empty form! Be sure to type some text into the text box of the form.",
+            try_again_link: "/text-to-ascii",
+        },
+        Err(TextInputError::TooLong) => HtmlTemplate::Error {
+            error_message: "It looks like your text is too long for a banner! Be sure the text has no more than 40 characters.",
+            try_again_link: "/text-to-ascii",
+        },
+        Err(TextInputError::UnsupportedFontSize) => HtmlTemplate::Error {
+            error_message: "It looks like you selected an unsupported letter size! Be sure to pick one of the sizes listed on the form.",
+            try_again_link: "/text-to-ascii",
+        },
+        Err(TextInputError::UnsupportedCharset) => HtmlTemplate::Error {
+            error_message: "It looks like you selected an unsupported character set! Be sure to pick one of the character sets listed on the form.",
+            try_again_link: "/text-to-ascii",
+        },
+        Ok((text, font_size, options)) => match text_to_banner(&text, font_size, &options) {
+            Ok(ascii_result) => HtmlTemplate::TextToAsciiResult { ascii_result },
+            Err(_) => HtmlTemplate::Error {
+                error_message: "It looks like we ran into an issue with drawing your text! Try it one more time, but if that doesn't work, try different text.",
+                try_again_link: "/text-to-ascii",
+            },
+        },
+    }
+}
 
-            // Verify that image created correctly
-            // The idea to use "CARGO_MANIFEST_DIR" comes from StackOverflow:
-            // https://stackoverflow.com/questions/30003921/how-can-i-locate-resources-for-testing-with-cargo
-            let expected_image_file_path = concat!(
-                env!("CARGO_MANIFEST_DIR"),
-                "/test_assets/converted_images/castle.png"
-            );
-            let expected_image_contents = read(expected_image_file_path).unwrap();
-            let actual_image_contents = read(&file_path).unwrap();
+/// Function to put a QR code and the PNG image it was rasterized into in an HTML template, with the PNG as a data URL.
+#[cfg(feature = "qr")]
+fn qr_result<'a>(ascii_result: String, png: &[u8]) -> HtmlTemplate<'a> {
+    HtmlTemplate::QrResult {
+        ascii_result,
+        image_result: format!("data:image/png;base64,{}", STANDARD.encode(png)),
+    }
+}
 
-            assert_eq!(expected_image_contents, actual_image_contents);
+/// Function to draw a link as a QR code made of block characters in an HTML template.
+///
+/// This function uses the [super::qr_form_params] module to validate the link. Then if there are no errors, the link is drawn as
+/// a QR code with block characters, which is also rasterized into a PNG image so that it can be downloaded.
+/// QR codes are not stored or added to the gallery, since they are quick to make again.
+/// An instance of a [HtmlTemplate] variant populated with valid data is returned for both error and success states.
+#[cfg(feature = "qr")]
+pub fn generate_qr_result<'a>(params: QrFormParams) -> HtmlTemplate<'a> {
+    let too_long = HtmlTemplate::Error {
+        error_message: "It looks like your link is too long for a QR code! Be sure the link has no more than 1024 characters.",
+        try_again_link: "/qr",
+    };
 
-            // Clean up file created for test
-            remove_file(file_path).unwrap();
-        } else {
-            assert!(false);
-        }
+    match params.validate_url() {
+        Err(QrInputError::EmptyInput) => HtmlTemplate::Error {
+            error_message: "It looks like you submitted an empty form! Be sure to type a link into the text box of the form.",
+            try_again_link: "/qr",
+        },
+        Err(QrInputError::NotAUrl) => HtmlTemplate::Error {
+            error_message: "It looks like that isn't a link! Be sure the link starts with http:// or https:// and has no spaces.",
+            try_again_link: "/qr",
+        },
+        Err(QrInputError::TooLong) => too_long,
+        Ok(url) => match text_to_qr_ascii(url)
+            .and_then(|ascii| Ok((qr_ascii_to_png(&ascii)?, ascii)))
+        {
+            Ok((png, ascii_result)) => qr_result(ascii_result, &png),
+            Err(QrDataTooLong) => too_long,
+            Err(_) => HtmlTemplate::Error {
+                error_message: "It looks like we ran into an issue with drawing your QR code! Try it one more time, but if that doesn't work, try a different link.",
+                try_again_link: "/qr",
+            },
+        },
     }
+}
 
-    // Verifies that the generate_ascii_to_image_result() function returns the correctly poplated HtmlTemplate variant
-    // when there is an empty input error
-    #[test]
-    fn test_generate_ascii_to_image_result_empty_input() {
-        let params = AsciiFormParams {
-            ascii_input: "".to_string(),
-        };
-        let result = generate_ascii_to_image_result(params);
-
-        let expected_result = HtmlTemplate::Error {
-            error_message: "It looks like you submitted an empty form! Be sure to paste your ASCII text into the text box of the form.",
-            try_again_link: "/ascii-to-image"
-        };
+/// Function to rasterize a QR code made of block characters into a PNG image in an HTML template.
+///
+/// This function uses the [super::qr_form_params] module to validate the QR code. Then if there are no errors, the QR code is
+/// rasterized into a PNG image that can be scanned, with a quiet zone around it no matter how much of it was pasted.
+/// An instance of a [HtmlTemplate] variant populated with valid data is returned for both error and success states.
+#[cfg(feature = "qr")]
+pub fn generate_qr_image_result<'a>(params: QrAsciiFormParams) -> HtmlTemplate<'a> {
+    let qr_ascii = match params.validate_qr_ascii() {
+        Ok(qr_ascii) => qr_ascii.replace("\r\n", "\n"),
+        Err(_) => {
+            return HtmlTemplate::Error {
+                error_message: "It looks like you submitted an empty form! Be sure to paste your QR code into the text box of the form.",
+                try_again_link: "/qr",
+            }
+        }
+    };
 
-        assert_eq!(result, expected_result);
+    match qr_ascii_to_png(&qr_ascii) {
+        Ok(png) => qr_result(qr_ascii, &png),
+        Err(UnknownASCIISymbol(..)) => HtmlTemplate::Error {
+            error_message: "It looks like your QR code has characters other than blocks and spaces! Be sure to paste a QR code drawn with the █, ▀ and ▄ characters.",
+            try_again_link: "/qr",
+        },
+        Err(EmptyInput | InvalidQrCode) => HtmlTemplate::Error {
+            error_message: "It looks like that isn't a whole QR code! Be sure to copy every line of the QR code, and that the lines weren't wrapped.",
+            try_again_link: "/qr",
+        },
+        Err(_) => HtmlTemplate::Error {
+            error_message: "It looks like we ran into an issue with drawing your QR code! Try it one more time, but if that doesn't work, try a different QR code.",
+            try_again_link: "/qr",
+        },
     }
+}
 
-    // Verifies that the generate_ascii_to_image_result() function returns the correctly poplated HtmlTemplate variant
-    // when there is an error due to invalid ASCII input
-    #[test]
-    fn test_generate_ascii_to_image_result_not_ascii_input() {
-        let mut input = AsciiFormParams {
-            ascii_input: "😄".to_string(),
-        };
-        let mut result = generate_ascii_to_image_result(input);
+/// Enum to store the outcome of publishing ASCII art to the [ArtStore].
+#[derive(PartialEq, Debug)]
+pub enum PublishResult {
+    /// [PublishResult::Created] is used when the ASCII art is stored for the first time.
+    Created,
+    /// [PublishResult::AlreadyStored] is used when the same ASCII art was published before.
+    AlreadyStored,
+    /// [PublishResult::Rejected] is used when the ASCII art can't be published, and stores a plain text message that explains why.
+    Rejected(String),
+    /// [PublishResult::Failed] is used when the ASCII art couldn't be saved.
+    Failed,
+}
 
-        let expected_result = HtmlTemplate::Error {
-            error_message: "This form only accepts ASCII characters! Be sure to double check that all pasted text is valid ASCII.",
-            try_again_link: "/ascii-to-image"
-        };
+/// Function to publish ASCII art to the [ArtStore] under the hash it was sent to.
+///
+/// This function uses the [super::ascii_form_params] module to validate the ASCII art text, which is stored exactly as it was sent
+/// so that its hash doesn't change. The hash must be the SHA-256 hash of the text, which lets the client know the address
+/// of the art before it is published and lets the server check that the art arrived intact.
+pub fn generate_publish_result(hash: &str, ascii: String, store: &ArtStore) -> PublishResult {
+    let params = AsciiFormParams {
+        ascii_input: ascii,
+        skip_normalization: true,
+        skip_soft_wrap_repair: true,
+        replace_unknown_symbols: false,
+        render_pixels: Some(false),
+        orientation: None,
+        title: None,
+        author: None,
+        license: None,
+        inline_result: false,
+    };
 
-        assert_eq!(result, expected_result);
+    match params.validate_ascii_input() {
+        Err(AsciiInputError::EmptyInput) => {
+            PublishResult::Rejected("It looks like you sent empty ASCII art! Be sure to send the ASCII art text as the body of the request.\n".to_string())
+        }
+        Err(AsciiInputError::NotAsciiInput { offenders, .. }) => {
+            PublishResult::Rejected(format!(
+                "Only ASCII characters can be published! We found a non-ASCII character at {}\n",
+                offenders.first().map(|offender| offender.describe()).unwrap_or_default()
+            ))
+        }
+        // published art isn't given a license or orientation, so this is only here to cover every error
+        Err(AsciiInputError::UnsupportedLicense | AsciiInputError::UnsupportedOrientation) => {
+            PublishResult::Rejected("It looks like you picked an unsupported license!\n".to_string())
+        }
+        Ok(_) if content_hash(&params.ascii_input) != hash => {
+            PublishResult::Rejected(format!(
+                "The hash in the address doesn't match the ASCII art! The SHA-256 hash of the ASCII art you sent is {}\n",
+                content_hash(&params.ascii_input)
+            ))
+        }
+        Ok(_) => match store.publish(&params.ascii_input) {
+            Ok((_, true)) => PublishResult::Created,
+            Ok((_, false)) => PublishResult::AlreadyStored,
+            Err(_) => PublishResult::Failed,
+        },
+    }
+}
 
-        input = AsciiFormParams {
-            ascii_input: "£¥€¢abc".to_string(),
-        };
-        result = generate_ascii_to_image_result(input);
+/// Function to transform an animated GIF into a stream of ASCII frames.
+///
+/// This function uses the [super::animation_form_params] module to validate the animation and the ASCII art width, which along
+/// with the character set comes from the [ConversionDefaults] when it isn't picked. Then if there are no errors, the GIF is passed to the shared [Converter] on a
+/// worker of the [JobQueue], and its frames are converted into ASCII while they are sent by the returned [FrameStream].
+/// Since the stream is watched from a terminal, plain text error messages are returned instead of an [HtmlTemplate], and a GIF
+/// that can't be read ends the stream with an `error` event.
+pub fn generate_ascii_animation_stream(
+    form: AnimationFormParams,
+    converter: web::Data<Converter>,
+    defaults: &ConversionDefaults,
+    jobs: &JobQueue,
+) -> StreamResult<FrameStream> {
+    let (animation, options) = match animation_options(&form, converter.get_ref(), defaults) {
+        Ok((animation_file, options)) => (animation_file.data.clone(), options),
+        Err(error_message) => return StreamResult::Rejected(error_message),
+    };
 
-        assert_eq!(result, expected_result);
+    let streamed = stream_frames(jobs, move || {
+        converter
+            .animation_to_ascii(animation.to_vec(), options)
+            .map_err(|_| ANIMATION_PARSE_ERROR)
+    });
+
+    match streamed {
+        Ok(frame_stream) => StreamResult::Streaming(frame_stream),
+        Err(JobError::QueueFull | JobError::Canceled) => StreamResult::Busy,
     }
+}
 
-    // Verifies that the generate_ascii_to_image_result() function returns the correctly poplated HtmlTemplate variant
-    // when there is an error due to the submitted ASCII art containing a character that is unsupported by the ascii_art_converter library crate
-    #[test]
-    fn test_generate_ascii_to_image_result_unknown_ascii_symbol() {
-        let mut input = AsciiFormParams {
-            ascii_input: "V".to_string(),
-        };
-        let mut result = generate_ascii_to_image_result(input);
+/// Function to transform an animated GIF into an asciinema cast of its ASCII frames, which can be downloaded and played back in a
+/// terminal with `asciinema play`.
+///
+/// The animation is validated and converted the same way as [generate_ascii_animation_stream], but every frame is converted before
+/// the cast is returned, and each frame is shown for its delay in the GIF.
+/// Like the stream, a plain text error message is returned instead of an [HtmlTemplate].
+pub fn generate_ascii_animation_cast(
+    form: AnimationFormParams,
+    converter: &Converter,
+    defaults: &ConversionDefaults,
+) -> Result<String, &'static str> {
+    let frames: Vec<AsciiFrame> = convert_animation(form, converter, defaults)?
+        .collect::<Result<_, _>>()
+        .map_err(|_| ANIMATION_PARSE_ERROR)?;
 
-        let mut expected_result = HtmlTemplate::ErrorMultiLine {
-            error_message: "The ASCII art you submitted contains an unsupported character: V"
-                .to_string(),
-            error_message2:
-                "Please try again with a piece of ASCII art that only contains supported symbols.",
-            try_again_link: "/ascii-to-image",
-        };
+    // the frames of a GIF keep their own delays
+    Ok(frames_to_cast(&frames, None))
+}
 
-        assert_eq!(result, expected_result);
+/// Function to validate an animation form and start converting the frames of its GIF into ASCII.
+///
+/// The frames are converted one at a time as the returned [AsciiFrames] are read, and a plain text error message is returned
+/// when the form is not valid or the GIF can't be read.
+fn convert_animation(
+    form: AnimationFormParams,
+    converter: &Converter,
+    defaults: &ConversionDefaults,
+) -> Result<AsciiFrames<'static>, &'static str> {
+    let (animation_file, options) = animation_options(&form, converter, defaults)?;
 
-        input = AsciiFormParams {
-            ascii_input: "=".to_string(),
-        };
-        result = generate_ascii_to_image_result(input);
+    converter
+        .animation_to_ascii(animation_file.data.to_vec(), options)
+        .map_err(|_| ANIMATION_PARSE_ERROR)
+}
 
-        expected_result = HtmlTemplate::ErrorMultiLine {
-            error_message: "The ASCII art you submitted contains an unsupported character: ="
-                .to_string(),
-            error_message2:
-                "Please try again with a piece of ASCII art that only contains supported symbols.",
-            try_again_link: "/ascii-to-image",
+/// Function to validate an animation form, and return its GIF along with the [ConvertOptions] to convert it with.
+///
+/// A plain text error message is returned when the form is not valid.
+fn animation_options<'a>(
+    form: &'a AnimationFormParams,
+    converter: &Converter,
+    defaults: &ConversionDefaults,
+) -> Result<(&'a actix_multipart::form::bytes::Bytes, ConvertOptions), &'static str> {
+    let validated_input = form.validate_animation_input().and_then(|animation_file| {
+        let options = ConvertOptions {
+            width: form.validate_ascii_width()?.or(defaults.width),
+            charset: defaults.charset,
+            symbol_map: default_symbol_map(converter),
+            ..Default::default()
         };
+        Ok((animation_file, options))
+    });
 
-        assert_eq!(result, expected_result);
+    match validated_input {
+        Ok(validated_input) => Ok(validated_input),
+        Err(AnimationInputError::EmptyInput) => {
+            Err("It looks like you submitted an empty form! Be sure to upload a GIF as animation_input before submitting.\n")
+        }
+        Err(AnimationInputError::UnsupportedAnimationType) => {
+            Err("It looks like you submitted an unsupported animation type! Be sure to upload a GIF only.\n")
+        }
+        Err(AnimationInputError::UnsupportedWidth) => {
+            Err("It looks like you selected an unsupported ASCII art width! Be sure to pick one of the widths listed on the image to ASCII form.\n")
+        }
     }
+}
 
-    // Tests for generate_image_to_ascii_result() function
+/// Function to add a successful conversion to the [Gallery].
+///
+/// The `html` is the [HtmlTemplate] that shows the result of the conversion, and error templates are not added.
+/// The conversion is added with the [super::attribution::Attribution] shown on the result, and the character set of
+/// ASCII art is read from the manifest of its settings so the gallery can be searched by it.
+/// The conversion is still shown to the user when it can't be added, so an error from the [Gallery] is ignored.
+pub async fn add_to_gallery(
+    gallery: &Gallery,
+    html: &HtmlTemplate<'_>,
+    input_hash: &str,
+    title: Option<&str>,
+) {
+    let (kind, output_path, attribution, charset) = match html {
+        // a result that couldn't be stored has nothing for the gallery to link to
+        HtmlTemplate::AsciiToImageResult {
+            permalink: None, ..
+        }
+        | HtmlTemplate::ImageToAsciiResult {
+            permalink: None, ..
+        } => return,
+        HtmlTemplate::AsciiToImageResult {
+            image_result,
+            attribution,
+            ..
+        } => (
+            ConversionKind::AsciiToImage,
+            image_result,
+            attribution,
+            None,
+        ),
+        HtmlTemplate::ImageToAsciiResult {
+            text_result,
+            manifest,
+            attribution,
+            ..
+        } => (
+            ConversionKind::ImageToAscii,
+            text_result,
+            attribution,
+            Manifest::from_json(manifest)
+                .ok()
+                .map(|manifest| manifest.charset),
+        ),
+        _ => return,
+    };
 
-    // Verifies that the generate_image_to_ascii_result() function generates the correct ASCII text
-    // and returns the correctly poplated HtmlTemplate variant when there are no errors
-    #[test]
-    fn test_generate_image_to_ascii_result() {
-        // The idea to use "CARGO_MANIFEST_DIR" comes from StackOverflow:
-        // https://stackoverflow.com/questions/30003921/how-can-i-locate-resources-for-testing-with-cargo
-        let image_path = concat!(
-            env!("CARGO_MANIFEST_DIR"),
+    let _ = gallery
+        .add(kind, input_hash, output_path, title, attribution, charset)
+        .await;
+}
+
+/// Function to read a page of the past conversions that match a [GallerySearch] from the [Gallery] into an HTML template.
+///
+/// Pages are numbered from 1, and a page past the last page shows no conversions. An empty search shows every conversion.
+/// An instance of a [HtmlTemplate] variant populated with valid data is returned for both error and success states.
+pub async fn generate_gallery_result<'a>(
+    gallery: &Gallery,
+    search: GallerySearch,
+    page: u32,
+) -> HtmlTemplate<'a> {
+    match gallery.search(&search, page).await {
+        Ok(page) => HtmlTemplate::Gallery {
+            entries: page.entries,
+            previous_page: page.previous_page,
+            next_page: page.next_page,
+            search,
+        },
+        Err(_) => HtmlTemplate::Error {
+            error_message:
+                "It looks like we ran into an issue with loading the gallery! Try it one more time.",
+            try_again_link: "/gallery",
+        },
+    }
+}
+
+/// Function to read a past conversion from the [Gallery] into an HTML template.
+///
+/// The ASCII art of an image to ASCII art conversion is read from its text file in the [ResultStore] to be shown on the page.
+/// Returns [None] when there is no conversion with the id or it was hidden by an operator, and otherwise an instance of a
+/// [HtmlTemplate] variant populated with valid data for both error and success states.
+pub async fn generate_gallery_detail_result<'a>(
+    gallery: &Gallery,
+    results: &dyn ResultStore,
+    id: i64,
+) -> Option<HtmlTemplate<'a>> {
+    match gallery.get(id).await {
+        Ok(Some(entry)) if !entry.hidden => {
+            let ascii_result = match entry.kind {
+                ConversionKind::ImageToAscii => file_name_from_route(&entry.output_path)
+                    .and_then(|file_name| results.read(file_name).ok().flatten())
+                    .and_then(|text| String::from_utf8(text).ok()),
+                ConversionKind::AsciiToImage => None,
+            };
+            Some(HtmlTemplate::GalleryDetail {
+                entry,
+                ascii_result,
+            })
+        }
+        Ok(_) => None,
+        Err(_) => Some(HtmlTemplate::Error {
+            error_message: "It looks like we ran into an issue with loading this conversion! Try it one more time.",
+            try_again_link: "/gallery",
+        }),
+    }
+}
+
+/// Function to read the result of a conversion from its [ResultRecord] into an HTML template.
+///
+/// The ASCII art of an image to ASCII art conversion is read from its text file in the [ResultStore] to be shown on the page.
+/// Returns [None] when there is no result with the id, and otherwise an instance of a [HtmlTemplate] variant
+/// populated with valid data for both error and success states.
+pub fn generate_saved_result<'a>(results: &dyn ResultStore, id: &str) -> Option<HtmlTemplate<'a>> {
+    match ResultRecord::load(results, id) {
+        Ok(Some(record)) => {
+            let route = |extension| record.file(extension).map(file_route);
+            let ascii_result = record
+                .file("txt")
+                .and_then(|file_name| results.read(file_name).ok().flatten())
+                .and_then(|text| String::from_utf8(text).ok());
+
+            Some(HtmlTemplate::SavedResult {
+                permalink: record.permalink(),
+                image_result: route("png"),
+                image_result_2x: route(HIGH_DPI_EXTENSION),
+                svg_result: route("svg"),
+                text_result: record.file("txt").map(|_| record.text_link()),
+                ascii_result,
+                bundle_link: record.bundle_link(),
+                attribution: record.attribution.clone(),
+                usage: record.usage,
+            })
+        }
+        Ok(None) => None,
+        Err(_) => Some(HtmlTemplate::Error {
+            error_message:
+                "It looks like we ran into an issue with loading this result! Try it one more time.",
+            try_again_link: "/",
+        }),
+    }
+}
+
+/// Function to package the ASCII art, the image and the manifest of a saved result into a ZIP archive.
+///
+/// The files are read from the [ResultStore] through the [ResultRecord] of the conversion, and are saved in the archive as
+/// `ascii-art.txt`, `ascii-art.png` and `manifest.json`. An image to ASCII art conversion has no image of its own, so its ASCII art
+/// is drawn into one by the shared [Converter] with the symbols recorded in its manifest. Files that a result doesn't have, such as
+/// the manifest of ASCII art that was converted into an image, or files of results saved before they were kept, are left out.
+/// Returns [None] when there is no result with the id. Since the archive is downloaded by other tools, a plain text error
+/// message is returned instead of an [HtmlTemplate].
+pub fn generate_result_bundle(
+    results: &dyn ResultStore,
+    converter: &Converter,
+    id: &str,
+) -> Option<Result<Vec<u8>, &'static str>> {
+    let load_error =
+        "It looks like we ran into an issue with loading this result! Try it one more time.\n";
+    let record = match ResultRecord::load(results, id) {
+        Ok(Some(record)) => record,
+        Ok(None) => return None,
+        Err(_) => return Some(Err(load_error)),
+    };
+    let read = |extension| match record.file(extension) {
+        Some(file_name) => results.read(file_name),
+        None => Ok(None),
+    };
+
+    let (ascii, image, manifest) = match (read("txt"), read("png"), read(MANIFEST_EXTENSION)) {
+        (Ok(ascii), Ok(image), Ok(manifest)) => (ascii, image, manifest),
+        _ => return Some(Err(load_error)),
+    };
+    let image = image.or_else(|| {
+        let ascii = String::from_utf8(ascii.clone()?).ok()?;
+        let symbol_map = manifest
+            .as_deref()
+            .and_then(|manifest| std::str::from_utf8(manifest).ok())
+            .and_then(|manifest| Manifest::from_json(manifest).ok())
+            .and_then(|manifest| manifest.to_options().ok())
+            .map_or_else(
+                || default_symbol_map(converter),
+                |options| options.symbol_map,
+            );
+        // braille and the numbers of rulers are not in the symbol map, so they are drawn as the symbols that look like them
+        converter
+            .ascii_to_image(
+                &ascii,
+                &symbol_map,
+                ImageRenderMode::Glyphs,
+                UnknownSymbolPolicy::NearestMatch,
+            )
+            .ok()
+            .map(Cursor::into_inner)
+    });
+
+    let files: Vec<(String, Vec<u8>)> = [
+        ("ascii-art.txt", ascii),
+        ("ascii-art.png", image),
+        ("manifest.json", manifest),
+    ]
+    .into_iter()
+    .filter_map(|(name, contents)| Some((name.to_string(), contents?)))
+    .collect();
+
+    Some(create_zip_archive(&files).map_err(|_| {
+        "It looks like we ran into an issue with creating your ZIP archive! Try it one more time.\n"
+    }))
+}
+
+/// Function to read the ASCII art of a saved result, so it can be downloaded as a text file.
+///
+/// The text is read from the [ResultStore] through the [ResultRecord] of the conversion. Returns [None] when there is no result
+/// with the id, or the result has no ASCII art, such as an image made from ASCII art. Since the text is downloaded, a plain
+/// text error message is returned instead of an [HtmlTemplate].
+pub fn generate_result_text(
+    results: &dyn ResultStore,
+    id: &str,
+) -> Option<Result<Vec<u8>, &'static str>> {
+    let load_error =
+        "It looks like we ran into an issue with loading this result! Try it one more time.\n";
+    let record = match ResultRecord::load(results, id) {
+        Ok(Some(record)) => record,
+        Ok(None) => return None,
+        Err(_) => return Some(Err(load_error)),
+    };
+
+    match results.read(record.file("txt")?) {
+        Ok(text) => text.map(Ok),
+        Err(_) => Some(Err(load_error)),
+    }
+}
+
+/// Function to export every stored result as a tar.gz archive.
+///
+/// The conversions of the [Gallery] are read first, and then the archive is written by the [super::export] module from the
+/// [ResultStore] and the [ArtStore] while it is sent by the returned [ExportStream].
+/// Since the export is downloaded by operators from a terminal, a plain text error message is returned instead of an [HtmlTemplate].
+pub async fn generate_export_stream(
+    results: Arc<dyn ResultStore>,
+    art_store: Arc<ArtStore>,
+    gallery: &Gallery,
+) -> Result<ExportStream, &'static str> {
+    let entries = gallery.all().await.map_err(|_| {
+        "It looks like we ran into an issue with reading the gallery! Try it one more time.\n"
+    })?;
+
+    Ok(ExportStream::new(move |writer| {
+        write_export(results.as_ref(), &art_store, entries, writer).map(|_| ())
+    }))
+}
+
+/// Enum to store the outcome of importing an archive made by the operator export.
+#[derive(PartialEq, Debug)]
+pub enum ImportResult {
+    /// [ImportResult::Imported] is used when the archive was imported, and stores a plain text report of what was imported.
+    Imported(String),
+    /// [ImportResult::Rejected] is used when the archive can't be imported, and stores a plain text message that explains why.
+    Rejected(&'static str),
+    /// [ImportResult::Failed] is used when the files or conversions couldn't be saved.
+    Failed,
+}
+
+/// Function to import an archive made by the operator export into the [ResultStore], the [ArtStore] and the [Gallery].
+///
+/// The files of the archive are checked against their hashes and stored by the [super::import] module on a blocking thread,
+/// and then the conversions whose results are stored are added to the [Gallery]. Files and conversions that are already stored
+/// are skipped, so an import that failed part of the way through can be run again.
+pub async fn generate_import_result(
+    archive: Bytes,
+    results: Arc<dyn ResultStore>,
+    art_store: Arc<ArtStore>,
+    gallery: &Gallery,
+) -> ImportResult {
+    let imported =
+        web::block(move || import_files(&archive[..], results.as_ref(), &art_store)).await;
+
+    let (mut summary, conversions) = match imported {
+        Ok(Ok(imported)) => imported,
+        Ok(Err(ImportError::InvalidArchive)) => {
+            return ImportResult::Rejected("It looks like you didn't upload a tar.gz archive! Be sure to upload an archive made by the operator export.\n")
+        }
+        Ok(Err(ImportError::ArchiveTooLarge)) => {
+            return ImportResult::Rejected("The files of the archive are larger than 500MB once they are extracted, which is more than can be imported at once.\n")
+        }
+        Ok(Err(ImportError::InvalidMetadata)) => {
+            return ImportResult::Rejected("It looks like the archive has no metadata.json file that can be read! Be sure to upload an archive made by the operator export.\n")
+        }
+        Ok(Err(ImportError::UnsupportedVersion)) => {
+            return ImportResult::Rejected("The archive was made by a newer version of the website! Update this server before importing it.\n")
+        }
+        Ok(Err(ImportError::StorageError)) | Err(_) => return ImportResult::Failed,
+    };
+
+    for conversion in conversions {
+        match gallery.import(&conversion).await {
+            Ok(true) => summary.imported_conversions += 1,
+            Ok(false) => summary.duplicate_conversions += 1,
+            Err(_) => return ImportResult::Failed,
+        }
+    }
+
+    ImportResult::Imported(summary.report())
+}
+
+/// Function to report a conversion in the [Gallery] from the data of the report form.
+///
+/// Returns [None] when there is no conversion with the id or it is hidden, and otherwise an instance of a [HtmlTemplate]
+/// variant populated with valid data for both error and success states.
+pub async fn generate_report_result<'a>(
+    gallery: &Gallery,
+    id: i64,
+    params: ReportFormParams,
+) -> Option<HtmlTemplate<'a>> {
+    let details = params.details.as_deref().and_then(clean_details);
+
+    match gallery.report(id, params.reason, details.as_deref()).await {
+        Ok(true) => Some(HtmlTemplate::ReportReceived { entry_id: id }),
+        Ok(false) => None,
+        Err(_) => Some(HtmlTemplate::Error {
+            error_message:
+                "It looks like we ran into an issue with saving your report! Try it one more time.",
+            try_again_link: "/gallery",
+        }),
+    }
+}
+
+/// Enum to store the outcome of an operator taking a [ModerationAction] on a conversion.
+#[derive(PartialEq, Debug)]
+pub enum ModerationResult {
+    /// [ModerationResult::Moderated] is used when the action was taken, and stores a plain text message that says what was done.
+    Moderated(String),
+    /// [ModerationResult::NotFound] is used when there is no conversion with the id.
+    NotFound,
+    /// [ModerationResult::Failed] is used when the gallery or the result couldn't be changed.
+    Failed,
+}
+
+/// Function to take a [ModerationAction] on a conversion in the [Gallery].
+///
+/// When the conversion is deleted, its result is removed from the [ResultStore] as well, so it can't be reached from its
+/// shared link either.
+pub async fn generate_moderation_result(
+    gallery: &Gallery,
+    results: &dyn ResultStore,
+    id: i64,
+    action: ModerationAction,
+) -> ModerationResult {
+    let entry = match gallery.moderate(id, action).await {
+        Ok(Some(entry)) => entry,
+        Ok(None) => return ModerationResult::NotFound,
+        Err(_) => return ModerationResult::Failed,
+    };
+
+    let message = match action {
+        ModerationAction::Hide => format!("Conversion {} is hidden from the gallery.\n", id),
+        ModerationAction::Delete => {
+            let removed = file_name_from_route(&entry.output_path)
+                .map_or(Ok(()), |file_name| remove_result(results, file_name));
+            if removed.is_err() {
+                return ModerationResult::Failed;
+            }
+            format!("Conversion {} and its result are deleted.\n", id)
+        }
+        ModerationAction::Dismiss => {
+            format!("The reports of conversion {} are dismissed.\n", id)
+        }
+    };
+
+    ModerationResult::Moderated(message)
+}
+
+// Tests
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::website::{
+        attribution::{Attribution, License},
+        moderation::ReportReason,
+        result_store::{write_failures, DirectoryResultStore, MemoryResultStore},
+    };
+    use actix_multipart::form::{bytes::Bytes, text::Text};
+    use actix_web::web;
+    use ascii_art_converter::{
+        compare_images,
+        converter::{compare::Tolerance, options::RenderCharset},
+    };
+    use regex::Regex;
+    use std::fs::{read, read_to_string};
+
+    // Creates an empty store for the results of conversions, which is kept in memory
+    fn test_result_store() -> MemoryResultStore {
+        MemoryResultStore::new()
+    }
+
+    // A store that runs out of space after a number of writes, like a disk that fills up
+    struct FullResultStore {
+        results: MemoryResultStore,
+        writes_left: std::sync::atomic::AtomicUsize,
+    }
+
+    impl FullResultStore {
+        fn new(writes_left: usize) -> FullResultStore {
+            FullResultStore {
+                results: MemoryResultStore::new(),
+                writes_left: writes_left.into(),
+            }
+        }
+    }
+
+    impl ResultStore for FullResultStore {
+        fn names(&self) -> io::Result<Vec<String>> {
+            self.results.names()
+        }
+
+        fn read(&self, name: &str) -> io::Result<Option<Vec<u8>>> {
+            self.results.read(name)
+        }
+
+        fn write(&self, name: &str, data: &[u8]) -> io::Result<()> {
+            let writes_left = &self.writes_left;
+            match writes_left.fetch_update(
+                std::sync::atomic::Ordering::Relaxed,
+                std::sync::atomic::Ordering::Relaxed,
+                |left| left.checked_sub(1),
+            ) {
+                Ok(_) => self.results.write(name, data),
+                Err(_) => Err(io::Error::other("No space left on device")),
+            }
+        }
+
+        fn modified(&self, name: &str) -> io::Result<Option<std::time::SystemTime>> {
+            self.results.modified(name)
+        }
+
+        fn remove(&self, name: &str) -> io::Result<bool> {
+            self.results.remove(name)
+        }
+    }
+
+    // Reads the file of a result from the route it is served from
+    fn read_result_file(results: &dyn ResultStore, route: &str) -> Vec<u8> {
+        results
+            .read(file_name_from_route(route).unwrap())
+            .unwrap()
+            .unwrap()
+    }
+
+    // Tests for create_image_file() function
+
+    // Verifies that create_image_file() function correctly names and stores an image file with the expected content
+    #[test]
+    fn test_create_image_file() {
+        // Verify file created with correct name format
+        let file_contents: Vec<u8> = vec![1, 2, 3];
+        let mut record = ResultRecord::new(ConversionKind::AsciiToImage);
+        let results = test_result_store();
+        let result_file_name =
+            create_image_file(&results, &mut record, &file_contents, "png").unwrap();
+        // Used https://regexr.com/ to help create regex
+        let expected_format = Regex::new(r"^\w{8}-\w{4}-\w{4}-\w{4}-\w{12}\.png$").unwrap();
+
+        assert!(expected_format.is_match(&result_file_name));
+        assert_eq!(record.files, vec![result_file_name.clone()]);
+
+        // Verify that file was saved in the store with the correct contents
+        let result_file = results.read(&result_file_name).unwrap();
+
+        assert_eq!(result_file, Some(file_contents));
+    }
+
+    // Verifies that the files of a result are put in data URLs when the store fails, and that the files stored before
+    // the failure are removed and the failure is counted
+    #[test]
+    fn test_store_result_files_when_store_fails() {
+        let results = FullResultStore::new(1);
+        let mut record = ResultRecord::new(ConversionKind::AsciiToImage);
+        let failures = write_failures();
+
+        let (routes, permalink) = store_result_files(
+            &results,
+            &mut record,
+            &[(&[1, 2, 3], "png"), (b"@@", "txt")],
+        );
+
+        assert_eq!(
+            routes,
+            [
+                "data:image/png;base64,AQID",
+                "data:text/plain;charset=utf-8;base64,QEA="
+            ]
+        );
+        assert_eq!(permalink, None);
+        assert!(results.names().unwrap().is_empty());
+        assert!(write_failures() > failures);
+
+        let results = FullResultStore::new(3);
+        let (routes, permalink) = store_result_files(
+            &results,
+            &mut record,
+            &[(&[1, 2, 3], "png"), (b"@@", "txt")],
+        );
+
+        assert_eq!(routes[0], format!("conversion/{}.png", record.id));
+        assert_eq!(permalink, Some(record.permalink()));
+    }
+
+    // Verifies that a conversion still shows its result when the store is full, and that the result is not added to
+    // the gallery since it has nothing to link to
+    #[actix_web::test]
+    async fn test_generate_ascii_to_image_result_when_store_fails() {
+        let params = AsciiFormParams {
+            ascii_input: "$$\n$$".to_string(),
+            skip_normalization: false,
+            skip_soft_wrap_repair: false,
+            replace_unknown_symbols: false,
+            render_pixels: None,
+            orientation: None,
+            title: None,
+            author: None,
+            license: None,
+            inline_result: false,
+        };
+        let results = FullResultStore::new(0);
+        let result = generate_ascii_to_image_result(
+            params,
+            &Converter::new().unwrap(),
+            &results,
+            &ConversionDefaults::default(),
+        );
+
+        if let HtmlTemplate::AsciiToImageResult {
+            image_result,
+            svg_result,
+            permalink,
+            ..
+        } = &result
+        {
+            assert!(image_result.starts_with("data:image/png;base64,"));
+            assert!(svg_result.starts_with("data:image/svg+xml;base64,"));
+            assert_eq!(permalink, &None);
+        } else {
+            panic!("The ASCII art was not converted: {:?}", result);
+        }
+
+        let gallery = Gallery::new("sqlite::memory:").unwrap();
+        add_to_gallery(&gallery, &result, "abc123", None).await;
+
+        assert!(gallery
+            .search(&GallerySearch::default(), 1)
+            .await
+            .unwrap()
+            .entries
+            .is_empty());
+    }
+
+    // Verifies that an inline result is returned as data URLs without storing anything, and that it is not added to
+    // the gallery since it has nothing to link to
+    #[actix_web::test]
+    async fn test_generate_ascii_to_image_result_inline() {
+        let params = AsciiFormParams {
+            ascii_input: "$$\n$$".to_string(),
+            skip_normalization: false,
+            skip_soft_wrap_repair: false,
+            replace_unknown_symbols: false,
+            render_pixels: None,
+            orientation: None,
+            title: None,
+            author: None,
+            license: None,
+            inline_result: true,
+        };
+        let results = test_result_store();
+        let result = generate_ascii_to_image_result(
+            params,
+            &Converter::new().unwrap(),
+            &results,
+            &ConversionDefaults::default(),
+        );
+
+        if let HtmlTemplate::AsciiToImageResult {
+            image_result,
+            image_result_2x,
+            svg_result,
+            permalink,
+            ..
+        } = &result
+        {
+            assert_eq!(
+                image_result,
+                &ascii_art_converter::ascii_to_image_data_uri("$$\n$$").unwrap()
+            );
+            assert!(image_result_2x.starts_with("data:image/png;base64,"));
+            assert!(svg_result.starts_with("data:image/svg+xml;base64,"));
+            assert_eq!(permalink, &None);
+        } else {
+            panic!("The ASCII art was not converted: {:?}", result);
+        }
+        assert!(results.names().unwrap().is_empty());
+
+        let gallery = Gallery::new("sqlite::memory:").unwrap();
+        add_to_gallery(&gallery, &result, "abc123", None).await;
+
+        assert!(gallery
+            .search(&GallerySearch::default(), 1)
+            .await
+            .unwrap()
+            .entries
+            .is_empty());
+    }
+
+    // Tests for generate_image_to_ascii_result() function
+
+    // Verifies that the generate_ascii_to_image_result() function generates the correct file in the expected directory
+    // and returns the correctly poplated HtmlTemplate variant when there are no errors
+    #[test]
+    fn test_generate_ascii_to_image_result() {
+        // The idea to use "CARGO_MANIFEST_DIR" comes from StackOverflow:
+        // https://stackoverflow.com/questions/30003921/how-can-i-locate-resources-for-testing-with-cargo
+        let ascii_path = concat!(env!("CARGO_MANIFEST_DIR"), "/test_assets/ascii/castle.txt");
+        let ascii_text = read_to_string(ascii_path).unwrap();
+
+        // Gray pixels are rendered so that the result can be compared to the expected image
+        let params = AsciiFormParams {
+            ascii_input: ascii_text,
+            skip_normalization: false,
+            skip_soft_wrap_repair: false,
+            replace_unknown_symbols: false,
+            render_pixels: Some(true),
+            orientation: None,
+            title: None,
+            author: None,
+            license: None,
+            inline_result: false,
+        };
+        let results = test_result_store();
+        let result = generate_ascii_to_image_result(
+            params,
+            &Converter::new().unwrap(),
+            &results,
+            &ConversionDefaults::default(),
+        );
+
+        if let HtmlTemplate::AsciiToImageResult {
+            image_result,
+            image_result_2x,
+            svg_result,
+            permalink,
+            ..
+        } = result
+        {
+            // Verify file has correct format
+            // Used https://regexr.com/ to help create regex
+            let expected_format =
+                Regex::new(r"^conversion/\w{8}-\w{4}-\w{4}-\w{4}-\w{12}\.png$").unwrap();
+
+            assert!(expected_format.is_match(&image_result));
+
+            // Verify that image created correctly
+            // The idea to use "CARGO_MANIFEST_DIR" comes from StackOverflow:
+            // https://stackoverflow.com/questions/30003921/how-can-i-locate-resources-for-testing-with-cargo
+            let expected_image_file_path = concat!(
+                env!("CARGO_MANIFEST_DIR"),
+                "/test_assets/converted_images/castle.png"
+            );
+            let comparison = compare_images(
+                std::path::Path::new(expected_image_file_path),
+                read_result_file(&results, &image_result),
+                &Tolerance::exact(),
+            )
+            .unwrap();
+
+            assert!(comparison.is_similar(&Tolerance::exact()));
+
+            // Verify that the SVG version of the image created correctly
+            let expected_format =
+                Regex::new(r"^conversion/\w{8}-\w{4}-\w{4}-\w{4}-\w{12}\.svg$").unwrap();
+
+            assert!(expected_format.is_match(&svg_result));
+
+            let actual_svg_contents = read_result_file(&results, &svg_result);
+
+            assert!(actual_svg_contents.starts_with(b"<svg "));
+
+            // Verify that the result can be shared by the id of its record
+            let image_name = file_name_from_route(&image_result).unwrap();
+            let id = image_name.trim_end_matches(".png");
+
+            assert_eq!(permalink, Some(format!("/result/{}", id)));
+
+            // Verify that the high-DPI copy of the image is twice as large and named after the same id
+            assert_eq!(image_result_2x, format!("conversion/{}.2x.png", id));
+
+            let image =
+                image::load_from_memory(&read_result_file(&results, &image_result)).unwrap();
+            let image_2x =
+                image::load_from_memory(&read_result_file(&results, &image_result_2x)).unwrap();
+
+            assert_eq!(
+                (image_2x.width(), image_2x.height()),
+                (image.width() * 2, image.height() * 2)
+            );
+        } else {
+            panic!("Expected HtmlTemplate::AsciiToImageResult.");
+        }
+    }
+
+    // Verifies that the generate_ascii_to_image_result() function returns the correctly poplated HtmlTemplate variant
+    // when there is an empty input error
+    #[test]
+    fn test_generate_ascii_to_image_result_empty_input() {
+        let params = AsciiFormParams {
+            ascii_input: "".to_string(),
+            skip_normalization: false,
+            skip_soft_wrap_repair: false,
+            replace_unknown_symbols: false,
+            render_pixels: Some(false),
+            orientation: None,
+            title: None,
+            author: None,
+            license: None,
+            inline_result: false,
+        };
+        let result = generate_ascii_to_image_result(
+            params,
+            &Converter::new().unwrap(),
+            &test_result_store(),
+            &ConversionDefaults::default(),
+        );
+
+        let expected_result = HtmlTemplate::Error {
+            error_message: "It looks like you submitted an empty form! Be sure to paste your ASCII text into the text box of the form.",
+            try_again_link: "/ascii-to-image"
+        };
+
+        assert_eq!(result, expected_result);
+    }
+
+    // Verifies that the generate_ascii_to_image_result() function returns the correctly poplated HtmlTemplate variant
+    // when the input only has line breaks
+    #[test]
+    fn test_generate_ascii_to_image_result_whitespace_input() {
+        for ascii_input in ["\n\n\n", "\r\n\r\n"] {
+            let params = AsciiFormParams {
+                ascii_input: ascii_input.to_string(),
+                skip_normalization: false,
+                skip_soft_wrap_repair: false,
+                replace_unknown_symbols: false,
+                render_pixels: Some(false),
+                orientation: None,
+                title: None,
+                author: None,
+                license: None,
+                inline_result: false,
+            };
+            let result = generate_ascii_to_image_result(
+                params,
+                &Converter::new().unwrap(),
+                &test_result_store(),
+                &ConversionDefaults::default(),
+            );
+
+            let expected_result = HtmlTemplate::Error {
+                error_message: "It looks like the ASCII art you submitted only has blank lines! Be sure to paste the whole piece of ASCII art into the text box of the form.",
+                try_again_link: "/ascii-to-image"
+            };
+
+            assert_eq!(result, expected_result);
+        }
+    }
+
+    // Verifies that the generate_ascii_to_image_result() function returns the correctly populated HtmlTemplate variant
+    // when the ASCII has more lines than the converter draws
+    #[test]
+    fn test_generate_ascii_to_image_result_too_large() {
+        let params = AsciiFormParams {
+            ascii_input: ".\n".repeat(5000),
+            skip_normalization: false,
+            skip_soft_wrap_repair: false,
+            replace_unknown_symbols: false,
+            render_pixels: Some(true),
+            orientation: None,
+            title: None,
+            author: None,
+            license: None,
+            inline_result: false,
+        };
+        let result = generate_ascii_to_image_result(
+            params,
+            &Converter::new().unwrap(),
+            &test_result_store(),
+            &ConversionDefaults::default(),
+        );
+
+        let expected_result = HtmlTemplate::Error {
+            error_message: "It looks like the ASCII art you submitted is too large for us to draw! Be sure the ASCII art has no more than a few thousand lines and columns, or try a smaller piece of ASCII art.",
+            try_again_link: "/ascii-to-image"
+        };
+
+        assert_eq!(result, expected_result);
+    }
+
+    // Verifies that the generate_ascii_to_image_result() function returns the correctly poplated HtmlTemplate variant
+    // when there is an error due to invalid ASCII input
+    #[test]
+    fn test_generate_ascii_to_image_result_not_ascii_input() {
+        let mut input = AsciiFormParams {
+            ascii_input: "😄".to_string(),
+            skip_normalization: false,
+            skip_soft_wrap_repair: false,
+            replace_unknown_symbols: false,
+            render_pixels: Some(false),
+            orientation: None,
+            title: None,
+            author: None,
+            license: None,
+            inline_result: false,
+        };
+        let mut result = generate_ascii_to_image_result(
+            input,
+            &Converter::new().unwrap(),
+            &test_result_store(),
+            &ConversionDefaults::default(),
+        );
+
+        let mut expected_result = HtmlTemplate::ErrorWithDetails {
+            error_message: "This form only accepts ASCII characters! We found 1 non-ASCII character(s) in the pasted text:".to_string(),
+            error_details: vec!["Line 1, column 1: \"😄\" (U+1F604)".to_string()],
+            try_again_link: "/ascii-to-image",
+        };
+
+        assert_eq!(result, expected_result);
+
+        input = AsciiFormParams {
+            ascii_input: "£¥€¢abc\n¡!".to_string(),
+            skip_normalization: false,
+            skip_soft_wrap_repair: false,
+            replace_unknown_symbols: false,
+            render_pixels: Some(false),
+            orientation: None,
+            title: None,
+            author: None,
+            license: None,
+            inline_result: false,
+        };
+        result = generate_ascii_to_image_result(
+            input,
+            &Converter::new().unwrap(),
+            &test_result_store(),
+            &ConversionDefaults::default(),
+        );
+
+        expected_result = HtmlTemplate::ErrorWithDetails {
+            error_message: "This form only accepts ASCII characters! We found 5 non-ASCII character(s) in the pasted text:".to_string(),
+            error_details: vec![
+                "Line 1, column 1: \"£\" (U+00A3)".to_string(),
+                "Line 1, column 2: \"¥\" (U+00A5)".to_string(),
+                "Line 1, column 3: \"€\" (U+20AC)".to_string(),
+                "Line 1, column 4: \"¢\" (U+00A2)".to_string(),
+                "Line 2, column 1: \"¡\" (U+00A1)".to_string(),
+            ],
+            try_again_link: "/ascii-to-image",
+        };
+
+        assert_eq!(result, expected_result);
+
+        input = AsciiFormParams {
+            ascii_input: "€€€€€€€".to_string(),
+            skip_normalization: false,
+            skip_soft_wrap_repair: false,
+            replace_unknown_symbols: false,
+            render_pixels: Some(false),
+            orientation: None,
+            title: None,
+            author: None,
+            license: None,
+            inline_result: false,
+        };
+        result = generate_ascii_to_image_result(
+            input,
+            &Converter::new().unwrap(),
+            &test_result_store(),
+            &ConversionDefaults::default(),
+        );
+
+        if let HtmlTemplate::ErrorWithDetails { error_details, .. } = result {
+            assert_eq!(error_details.len(), 6);
+            assert_eq!(error_details[5], "...and 2 more.");
+        } else {
+            panic!("Expected HtmlTemplate::ErrorWithDetails.");
+        }
+    }
+
+    // Verifies that the generate_ascii_to_image_result() function repairs soft-wrapped ASCII art and adds a notice about the repair
+    #[test]
+    fn test_generate_ascii_to_image_result_soft_wrapped_input() {
+        let long = "@".repeat(40);
+        let params = AsciiFormParams {
+            ascii_input: format!("{long}\n..\n{long}\n.", long = long),
+            skip_normalization: false,
+            skip_soft_wrap_repair: false,
+            replace_unknown_symbols: false,
+            render_pixels: Some(false),
+            orientation: None,
+            title: None,
+            author: None,
+            license: None,
+            inline_result: false,
+        };
+        let result = generate_ascii_to_image_result(
+            params,
+            &Converter::new().unwrap(),
+            &test_result_store(),
+            &ConversionDefaults::default(),
+        );
+
+        if let HtmlTemplate::AsciiToImageResult { notice, .. } = result {
+            assert_eq!(notice, Some("It looks like your ASCII art was soft-wrapped when it was copied, so we rejoined the wrapped lines before converting it."));
+        } else {
+            panic!("Expected HtmlTemplate::AsciiToImageResult.");
+        }
+    }
+
+    // Verifies that the generate_ascii_to_image_result() function lists the characters it removed or replaced as warnings
+    #[test]
+    fn test_generate_ascii_to_image_result_warnings() {
+        let params = AsciiFormParams {
+            ascii_input: "\u{FEFF}@\u{00A0}@\u{00A0}@".to_string(),
+            skip_normalization: false,
+            skip_soft_wrap_repair: false,
+            replace_unknown_symbols: false,
+            render_pixels: Some(false),
+            orientation: None,
+            title: None,
+            author: None,
+            license: None,
+            inline_result: false,
+        };
+        let result = generate_ascii_to_image_result(
+            params,
+            &Converter::new().unwrap(),
+            &test_result_store(),
+            &ConversionDefaults::default(),
+        );
+
+        if let HtmlTemplate::AsciiToImageResult { warnings, .. } = result {
+            assert_eq!(
+                warnings,
+                vec![
+                    "1 invisible character removed",
+                    "2 Unicode spaces replaced with ASCII spaces"
+                ]
+            );
+        } else {
+            panic!("Expected HtmlTemplate::AsciiToImageResult.");
+        }
+    }
+
+    // Verifies that the generate_ascii_to_image_result() function draws the characters with a font by default
+    #[test]
+    fn test_generate_ascii_to_image_result_glyphs() {
+        // The idea to use "CARGO_MANIFEST_DIR" comes from StackOverflow:
+        // https://stackoverflow.com/questions/30003921/how-can-i-locate-resources-for-testing-with-cargo
+        let ascii_path = concat!(env!("CARGO_MANIFEST_DIR"), "/test_assets/ascii/castle.txt");
+        let ascii_text = read_to_string(ascii_path).unwrap();
+
+        let params = AsciiFormParams {
+            ascii_input: ascii_text,
+            skip_normalization: false,
+            skip_soft_wrap_repair: false,
+            replace_unknown_symbols: false,
+            render_pixels: Some(false),
+            orientation: None,
+            title: None,
+            author: None,
+            license: None,
+            inline_result: false,
+        };
+        let results = test_result_store();
+        let result = generate_ascii_to_image_result(
+            params,
+            &Converter::new().unwrap(),
+            &results,
+            &ConversionDefaults::default(),
+        );
+
+        if let HtmlTemplate::AsciiToImageResult { image_result, .. } = result {
+            // Verify that the image is a PNG that is different from the gray pixel version
+            let actual_image_contents = read_result_file(&results, &image_result);
+            let pixel_image_file_path = concat!(
+                env!("CARGO_MANIFEST_DIR"),
+                "/test_assets/converted_images/castle.png"
+            );
+
+            assert!(image::load_from_memory(&actual_image_contents).is_ok());
+            assert_ne!(read(pixel_image_file_path).unwrap(), actual_image_contents);
+        } else {
+            panic!("Expected HtmlTemplate::AsciiToImageResult.");
+        }
+    }
+
+    // Verifies that the generate_ascii_to_image_result() function writes the author and license into the PNG image,
+    // shows them with the result and keeps them in the record of the result
+    #[test]
+    fn test_generate_ascii_to_image_result_attribution() {
+        let params = AsciiFormParams {
+            ascii_input: "$$$\n$ $\n".to_string(),
+            skip_normalization: false,
+            skip_soft_wrap_repair: false,
+            replace_unknown_symbols: false,
+            render_pixels: Some(true),
+            orientation: None,
+            title: None,
+            author: Some("  Kelsey Werner ".to_string()),
+            license: Some("cc-by-4.0".to_string()),
+            inline_result: false,
+        };
+        let results = test_result_store();
+        let result = generate_ascii_to_image_result(
+            params,
+            &Converter::new().unwrap(),
+            &results,
+            &ConversionDefaults::default(),
+        );
+
+        if let HtmlTemplate::AsciiToImageResult {
+            image_result,
+            permalink,
+            attribution,
+            ..
+        } = result
+        {
+            let expected_attribution = Attribution {
+                author: Some("Kelsey Werner".to_string()),
+                license: Some(License::CcBy4),
+            };
+            let id = permalink.as_deref().unwrap().trim_start_matches("/result/");
+            let image_contents = read_result_file(&results, &image_result);
+
+            assert_eq!(attribution, expected_attribution);
+            assert!(image_contents
+                .windows(24)
+                .any(|chunk| chunk == b"tEXtAuthor\0Kelsey Werner"));
+            assert!(image_contents
+                .windows(23)
+                .any(|chunk| chunk == b"tEXtCopyright\0CC BY 4.0"));
+            assert!(image::load_from_memory(&image_contents).is_ok());
+            assert_eq!(
+                ResultRecord::load(&results, id)
+                    .unwrap()
+                    .unwrap()
+                    .attribution,
+                expected_attribution
+            );
+        } else {
+            panic!("Expected HtmlTemplate::AsciiToImageResult.");
+        }
+    }
+
+    // Verifies that the generate_ascii_to_image_result() function returns the correctly poplated HtmlTemplate variant
+    // when there is error caused by the submission of an unsupported license
+    #[test]
+    fn test_generate_ascii_to_image_result_unsupported_license() {
+        let params = AsciiFormParams {
+            ascii_input: "$$$".to_string(),
+            skip_normalization: false,
+            skip_soft_wrap_repair: false,
+            replace_unknown_symbols: false,
+            render_pixels: Some(true),
+            orientation: None,
+            title: None,
+            author: None,
+            license: Some("gpl-3.0".to_string()),
+            inline_result: false,
+        };
+        let result = generate_ascii_to_image_result(
+            params,
+            &Converter::new().unwrap(),
+            &test_result_store(),
+            &ConversionDefaults::default(),
+        );
+
+        let expected_result = HtmlTemplate::Error {
+            error_message: "It looks like you picked an unsupported license! Be sure to pick one of the licenses listed on the form.",
+            try_again_link: "/ascii-to-image"
+        };
+
+        assert_eq!(result, expected_result);
+    }
+
+    // Verifies that the generate_ascii_to_image_result() function returns the correctly poplated HtmlTemplate variant
+    // for a supported orientation and for an unsupported one
+    #[test]
+    fn test_generate_ascii_to_image_result_orientation() {
+        let convert = |orientation: &str| {
+            let params = AsciiFormParams {
+                ascii_input: "$$$\n...".to_string(),
+                skip_normalization: false,
+                skip_soft_wrap_repair: false,
+                replace_unknown_symbols: false,
+                render_pixels: Some(true),
+                orientation: Some(orientation.to_string()),
+                title: None,
+                author: None,
+                license: None,
+                inline_result: false,
+            };
+            generate_ascii_to_image_result(
+                params,
+                &Converter::new().unwrap(),
+                &test_result_store(),
+                &ConversionDefaults::default(),
+            )
+        };
+
+        assert!(matches!(
+            convert("rotate270"),
+            HtmlTemplate::AsciiToImageResult { .. }
+        ));
+        assert_eq!(
+            convert("sideways"),
+            HtmlTemplate::Error {
+                error_message: "It looks like you picked an unsupported rotation! Be sure to pick one of the orientations listed on the form.",
+                try_again_link: "/ascii-to-image"
+            }
+        );
+    }
+
+    // Verifies that the generate_ascii_to_image_result() function accepts ASCII art with a BOM and non-breaking spaces
+    #[test]
+    fn test_generate_ascii_to_image_result_normalized_input() {
+        let params = AsciiFormParams {
+            ascii_input: "\u{FEFF}@\u{00A0}@".to_string(),
+            skip_normalization: false,
+            skip_soft_wrap_repair: false,
+            replace_unknown_symbols: false,
+            render_pixels: Some(false),
+            orientation: None,
+            title: None,
+            author: None,
+            license: None,
+            inline_result: false,
+        };
+        let result = generate_ascii_to_image_result(
+            params,
+            &Converter::new().unwrap(),
+            &test_result_store(),
+            &ConversionDefaults::default(),
+        );
+
+        assert!(matches!(result, HtmlTemplate::AsciiToImageResult { .. }));
+    }
+
+    // Verifies that the generate_ascii_to_image_result() function returns the correctly poplated HtmlTemplate variant
+    // when there is an error due to the submitted ASCII art containing a character that is unsupported by the ascii_art_converter library crate
+    #[test]
+    fn test_generate_ascii_to_image_result_unknown_ascii_symbol() {
+        let mut input = AsciiFormParams {
+            ascii_input: "V".to_string(),
+            skip_normalization: false,
+            skip_soft_wrap_repair: false,
+            replace_unknown_symbols: false,
+            render_pixels: Some(false),
+            orientation: None,
+            title: None,
+            author: None,
+            license: None,
+            inline_result: false,
+        };
+        let mut result = generate_ascii_to_image_result(
+            input,
+            &Converter::new().unwrap(),
+            &test_result_store(),
+            &ConversionDefaults::default(),
+        );
+
+        let mut expected_result = HtmlTemplate::ErrorWithLocation {
+            error_message: "The ASCII art you submitted contains an unsupported character: V. Please try again with a piece of ASCII art that only contains supported symbols."
+                .to_string(),
+            line: 1,
+            column: 1,
+            excerpt: "V".to_string(),
+            try_again_link: "/ascii-to-image",
+        };
+
+        assert_eq!(result, expected_result);
+
+        input = AsciiFormParams {
+            ascii_input: "@@@\n.=.\n@@@".to_string(),
+            skip_normalization: false,
+            skip_soft_wrap_repair: false,
+            replace_unknown_symbols: false,
+            render_pixels: Some(true),
+            orientation: None,
+            title: None,
+            author: None,
+            license: None,
+            inline_result: false,
+        };
+        result = generate_ascii_to_image_result(
+            input,
+            &Converter::new().unwrap(),
+            &test_result_store(),
+            &ConversionDefaults::default(),
+        );
+
+        expected_result = HtmlTemplate::ErrorWithLocation {
+            error_message: "The ASCII art you submitted contains an unsupported character: =. Please try again with a piece of ASCII art that only contains supported symbols."
+                .to_string(),
+            line: 2,
+            column: 2,
+            excerpt: ".=.".to_string(),
+            try_again_link: "/ascii-to-image",
+        };
+
+        assert_eq!(result, expected_result);
+
+        input = AsciiFormParams {
+            ascii_input: "@@@\n.=.\n@@@".to_string(),
+            skip_normalization: false,
+            skip_soft_wrap_repair: false,
+            replace_unknown_symbols: true,
+            render_pixels: Some(true),
+            orientation: None,
+            title: None,
+            author: None,
+            license: None,
+            inline_result: false,
+        };
+        result = generate_ascii_to_image_result(
+            input,
+            &Converter::new().unwrap(),
+            &test_result_store(),
+            &ConversionDefaults::default(),
+        );
+
+        assert!(matches!(result, HtmlTemplate::AsciiToImageResult { .. }));
+    }
+
+    // Tests for generate_image_to_ascii_result() function
+
+    // Verifies that the generate_image_to_ascii_result() function generates the correct ASCII text
+    // and returns the correctly poplated HtmlTemplate variant when there are no errors
+    #[test]
+    fn test_generate_image_to_ascii_result() {
+        // The idea to use "CARGO_MANIFEST_DIR" comes from StackOverflow:
+        // https://stackoverflow.com/questions/30003921/how-can-i-locate-resources-for-testing-with-cargo
+        let image_path = concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/test_assets/images/freakazoid-small.png"
+        );
+        let image_file = read(image_path).unwrap();
+        let image_bytes = Bytes {
+            data: web::Bytes::from(image_file),
+            content_type: Some(mime::IMAGE_PNG),
+            file_name: Some("freakazoid-small.png".to_string()),
+        };
+        let params = ImageFormParams {
+            image_input: Some(image_bytes),
+            ascii_width: None,
+            render_charset: None,
+            background_char: None,
+            symbol_set: None,
+            crop: None,
+            orientation: None,
+            brightness: None,
+            contrast: None,
+            show_rulers: None,
+            pipeline: None,
+            manifest: None,
+            title: None,
+            author: None,
+            license: None,
+        };
+        let results = test_result_store();
+        let result = generate_image_to_ascii_result(
+            params,
+            &Converter::new().unwrap(),
+            &results,
+            &ConversionDefaults::default(),
+        );
+
+        // The idea to use "CARGO_MANIFEST_DIR" comes from StackOverflow:
+        // https://stackoverflow.com/questions/30003921/how-can-i-locate-resources-for-testing-with-cargo
+        let ascii_path = concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/test_assets/ascii/freakazoid-small.txt"
+        );
+        let ascii_text = read_to_string(ascii_path).unwrap();
+        let manifest = Manifest::from(&ConvertOptions::default()).to_json();
+        let share_link = format!(
+            "/image-to-ascii?{}",
+            serde_urlencoded::to_string([("manifest", &manifest)]).unwrap()
+        );
+        if let HtmlTemplate::ImageToAsciiResult {
+            text_result,
+            permalink,
+            ..
+        } = &result
+        {
+            let expected_format =
+                Regex::new(r"^/result/(\w{8}-\w{4}-\w{4}-\w{4}-\w{12})\.txt$").unwrap();
+
+            let id = &expected_format.captures(text_result).unwrap()[1];
+            assert_eq!(
+                generate_result_text(&results, id),
+                Some(Ok(ascii_text.as_bytes().to_vec()))
+            );
+
+            let expected_result = HtmlTemplate::ImageToAsciiResult {
+                ascii_result: ascii_text,
+                text_result: text_result.clone(),
+                applied_stages: vec![],
+                pipeline: "[]".to_string(),
+                manifest,
+                share_link,
+                warnings: vec![],
+                permalink: permalink.clone(),
+                attribution: Attribution::default(),
+            };
+
+            // the resources the conversion used are kept in the record of the result
+            let id = permalink.as_deref().unwrap().trim_start_matches("/result/");
+            let usage = ResultRecord::load(&results, id)
+                .unwrap()
+                .unwrap()
+                .usage
+                .unwrap();
+            assert!(usage.pixels_processed > 0);
+
+            assert_eq!(result, expected_result);
+        } else {
+            panic!("Expected HtmlTemplate::ImageToAsciiResult.");
+        }
+    }
+
+    // Verifies that the generate_image_to_ascii_result() function warns that a large image was scaled down when no width was picked
+    #[test]
+    fn test_generate_image_to_ascii_result_warnings() {
+        // The idea to use "CARGO_MANIFEST_DIR" comes from StackOverflow:
+        // https://stackoverflow.com/questions/30003921/how-can-i-locate-resources-for-testing-with-cargo
+        let image_path = concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/test_assets/images/freakazoid-large.png"
+        );
+        let image_file = read(image_path).unwrap();
+        let image_bytes = Bytes {
+            data: web::Bytes::from(image_file),
+            content_type: Some(mime::IMAGE_PNG),
+            file_name: Some("freakazoid-large.png".to_string()),
+        };
+        let params = ImageFormParams {
+            image_input: Some(image_bytes),
+            ascii_width: None,
+            render_charset: None,
+            background_char: None,
+            symbol_set: None,
+            crop: None,
+            orientation: None,
+            brightness: None,
+            contrast: None,
+            show_rulers: None,
+            pipeline: None,
+            manifest: None,
+            title: None,
+            author: None,
+            license: None,
+        };
+        let result = generate_image_to_ascii_result(
+            params,
+            &Converter::new().unwrap(),
+            &test_result_store(),
+            &ConversionDefaults::default(),
+        );
+
+        if let HtmlTemplate::ImageToAsciiResult {
+            ascii_result,
+            warnings,
+            ..
+        } = result
+        {
+            let width = ascii_result.lines().next().unwrap().len();
+            assert_eq!(
+                warnings,
+                vec![format!(
+                    "image downscaled from 580px to {} characters wide",
+                    width
+                )]
+            );
+        } else {
+            panic!("Expected HtmlTemplate::ImageToAsciiResult.");
+        }
+    }
+
+    // Verifies that the generate_image_to_ascii_result_error() function returns the correctly poplated HtmlTemplate variant
+    // when there is an error due to problems parsing the given image in the ascii_art_converter library crate
+    #[test]
+    fn test_generate_image_to_ascii_result_error() {
+        let image_bytes = Bytes {
+            data: web::Bytes::from_static(b"\xff\xd8\xff\0\0\0\0\0\0\0"),
+            content_type: Some(mime::IMAGE_JPEG),
+            file_name: Some("test_file.png".to_string()),
+        };
+        let params = ImageFormParams {
+            image_input: Some(image_bytes),
+            ascii_width: None,
+            render_charset: None,
+            background_char: None,
+            symbol_set: None,
+            crop: None,
+            orientation: None,
+            brightness: None,
+            contrast: None,
+            show_rulers: None,
+            pipeline: None,
+            manifest: None,
+            title: None,
+            author: None,
+            license: None,
+        };
+        let result = generate_image_to_ascii_result(
+            params,
+            &Converter::new().unwrap(),
+            &test_result_store(),
+            &ConversionDefaults::default(),
+        );
+
+        let expected_result = HtmlTemplate::Error {
+            error_message: "It looks like we ran into an issue with parsing your image! There could be a problem with your image or with our parser, so try it one more time. But if that doesn't work, try a different image.",
+            try_again_link: "/image-to-ascii"
+        };
+
+        assert_eq!(result, expected_result);
+    }
+
+    // Verifies that the generate_image_to_ascii_result() function returns the correctly poplated HtmlTemplate variant
+    // when there is an empty input error
+    #[test]
+    fn test_generate_image_to_ascii_result_empty_input() {
+        let params = ImageFormParams {
+            image_input: None,
+            ascii_width: None,
+            render_charset: None,
+            background_char: None,
+            symbol_set: None,
+            crop: None,
+            orientation: None,
+            brightness: None,
+            contrast: None,
+            show_rulers: None,
+            pipeline: None,
+            manifest: None,
+            title: None,
+            author: None,
+            license: None,
+        };
+        let result = generate_image_to_ascii_result(
+            params,
+            &Converter::new().unwrap(),
+            &test_result_store(),
+            &ConversionDefaults::default(),
+        );
+
+        let expected_result = HtmlTemplate::Error {
+            error_message: "It looks like you submitted an empty form! Be sure to upload an image to the form before submitting.",
+            try_again_link: "/image-to-ascii"
+        };
+
+        assert_eq!(result, expected_result);
+    }
+
+    // Verifies that the generate_image_to_ascii_result() function returns the correctly poplated HtmlTemplate variant
+    // when there is error caused by the submission of an unsupported image type
+    #[test]
+    fn test_generate_image_to_ascii_result_unsupported_image_type() {
+        let image_bytes = Bytes {
+            data: web::Bytes::from(vec![0; 10]),
+            content_type: Some(mime::IMAGE_GIF),
+            file_name: Some("test_file.gif".to_string()),
+        };
+        let params = ImageFormParams {
+            image_input: Some(image_bytes),
+            ascii_width: None,
+            render_charset: None,
+            background_char: None,
+            symbol_set: None,
+            crop: None,
+            orientation: None,
+            brightness: None,
+            contrast: None,
+            show_rulers: None,
+            pipeline: None,
+            manifest: None,
+            title: None,
+            author: None,
+            license: None,
+        };
+        let result = generate_image_to_ascii_result(
+            params,
+            &Converter::new().unwrap(),
+            &test_result_store(),
+            &ConversionDefaults::default(),
+        );
+
+        let expected_result = HtmlTemplate::Error {
+            error_message: "It looks like you submitted an unsupported image type! Be sure to upload a JPEG, PNG, WebP, BMP or TIFF image only.",
+            try_again_link: "/image-to-ascii"
+        };
+
+        assert_eq!(result, expected_result);
+    }
+
+    // Verifies that the generate_image_to_ascii_result() function returns the correctly poplated HtmlTemplate variant
+    // when the contents of the submitted image don't match its image type
+    #[test]
+    fn test_generate_image_to_ascii_result_image_type_mismatch() {
+        // The idea to use "CARGO_MANIFEST_DIR" comes from StackOverflow:
+        // https://stackoverflow.com/questions/30003921/how-can-i-locate-resources-for-testing-with-cargo
+        let image_path = concat!(
+            env!("CARGO_MANIFEST_DIR"),
             "/test_assets/images/freakazoid-small.png"
         );
         let image_file = read(image_path).unwrap();
-        let mut named_temp_file = NamedTempFile::new().unwrap();
+        let image_bytes = Bytes {
+            data: web::Bytes::from(image_file),
+            content_type: Some(mime::IMAGE_JPEG),
+            file_name: Some("freakazoid-small.jpeg".to_string()),
+        };
+        let params = ImageFormParams {
+            image_input: Some(image_bytes),
+            ascii_width: None,
+            render_charset: None,
+            background_char: None,
+            symbol_set: None,
+            crop: None,
+            orientation: None,
+            brightness: None,
+            contrast: None,
+            show_rulers: None,
+            pipeline: None,
+            manifest: None,
+            title: None,
+            author: None,
+            license: None,
+        };
+        let result = generate_image_to_ascii_result(
+            params,
+            &Converter::new().unwrap(),
+            &test_result_store(),
+            &ConversionDefaults::default(),
+        );
 
-        // I figured out how to write to a NamedTempFile using the tests from the NamedTempFile source code:
-        // https://github.com/Stebalien/tempfile/blob/master/tests/namedtempfile.rs#L100
-        named_temp_file.write_all(&image_file).unwrap();
-        named_temp_file.seek(Start(0)).unwrap();
+        let expected_result = HtmlTemplate::Error {
+            error_message: "It looks like the contents of your image don't match its file type! Try saving the image again with the right file extension before uploading it.",
+            try_again_link: "/image-to-ascii"
+        };
 
-        let temp_file = TempFile {
-            file: named_temp_file,
-            content_type: Some(mime::IMAGE_JPEG),
+        assert_eq!(result, expected_result);
+    }
+
+    // Verifies that the generate_image_to_ascii_result() function generates ASCII text of the selected width
+    #[test]
+    fn test_generate_image_to_ascii_result_with_width() {
+        // The idea to use "CARGO_MANIFEST_DIR" comes from StackOverflow:
+        // https://stackoverflow.com/questions/30003921/how-can-i-locate-resources-for-testing-with-cargo
+        let image_path = concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/test_assets/images/freakazoid-small.png"
+        );
+        let image_file = read(image_path).unwrap();
+        let image_bytes = Bytes {
+            data: web::Bytes::from(image_file),
+            content_type: Some(mime::IMAGE_PNG),
+            file_name: Some("freakazoid-small.png".to_string()),
+        };
+        let params = ImageFormParams {
+            image_input: Some(image_bytes),
+            ascii_width: Some(Text("40".to_string())),
+            render_charset: None,
+            background_char: None,
+            symbol_set: None,
+            crop: None,
+            orientation: None,
+            brightness: None,
+            contrast: None,
+            show_rulers: None,
+            pipeline: None,
+            manifest: None,
+            title: None,
+            author: None,
+            license: None,
+        };
+        let result = generate_image_to_ascii_result(
+            params,
+            &Converter::new().unwrap(),
+            &test_result_store(),
+            &ConversionDefaults::default(),
+        );
+
+        if let HtmlTemplate::ImageToAsciiResult { ascii_result, .. } = result {
+            assert!(ascii_result.lines().all(|l| l.len() == 40));
+        } else {
+            panic!("Expected HtmlTemplate::ImageToAsciiResult.");
+        }
+    }
+
+    // Verifies that the generate_image_to_ascii_result() function uses the default width and character set of the website
+    // when none are selected, and the selected ones otherwise
+    #[test]
+    fn test_generate_image_to_ascii_result_with_defaults() {
+        // The idea to use "CARGO_MANIFEST_DIR" comes from StackOverflow:
+        // https://stackoverflow.com/questions/30003921/how-can-i-locate-resources-for-testing-with-cargo
+        let image_path = concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/test_assets/images/freakazoid-small.png"
+        );
+        let image_file = read(image_path).unwrap();
+        let defaults = ConversionDefaults {
+            width: Some(40),
+            charset: RenderCharset::Braille,
+            ..Default::default()
+        };
+
+        for (ascii_width, render_charset, expected_width, expected_braille) in [
+            (None, None, 40, true),
+            (Some("80"), Some("ascii"), 80, false),
+        ] {
+            let params = ImageFormParams {
+                image_input: Some(Bytes {
+                    data: web::Bytes::from(image_file.clone()),
+                    content_type: Some(mime::IMAGE_PNG),
+                    file_name: Some("freakazoid-small.png".to_string()),
+                }),
+                ascii_width: ascii_width.map(|width| Text(width.to_string())),
+                render_charset: render_charset.map(|charset| Text(charset.to_string())),
+                background_char: None,
+                symbol_set: None,
+                crop: None,
+                orientation: None,
+                brightness: None,
+                contrast: None,
+                show_rulers: None,
+                pipeline: None,
+                manifest: None,
+                title: None,
+                author: None,
+                license: None,
+            };
+            let result = generate_image_to_ascii_result(
+                params,
+                &Converter::new().unwrap(),
+                &test_result_store(),
+                &defaults,
+            );
+
+            if let HtmlTemplate::ImageToAsciiResult { ascii_result, .. } = result {
+                assert!(ascii_result
+                    .lines()
+                    .all(|l| l.chars().count() == expected_width));
+                assert_eq!(
+                    ascii_result
+                        .chars()
+                        .any(|c| ('\u{2800}'..='\u{28FF}').contains(&c)),
+                    expected_braille
+                );
+            } else {
+                panic!("Expected HtmlTemplate::ImageToAsciiResult.");
+            }
+        }
+    }
+
+    // Verifies that the generate_image_to_ascii_result() function replays the submitted pipeline and lists its stages
+    #[test]
+    fn test_generate_image_to_ascii_result_with_pipeline() {
+        // The idea to use "CARGO_MANIFEST_DIR" comes from StackOverflow:
+        // https://stackoverflow.com/questions/30003921/how-can-i-locate-resources-for-testing-with-cargo
+        let image_path = concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/test_assets/images/freakazoid-small.png"
+        );
+        let image_file = read(image_path).unwrap();
+        let submitted_pipeline = r#"[{"crop":{"x":0,"y":0,"width":40,"height":20}},{"rotate":"clockwise180"},{"dither":"bayer"}]"#;
+        let image_bytes = Bytes {
+            data: web::Bytes::from(image_file),
+            content_type: Some(mime::IMAGE_PNG),
+            file_name: Some("freakazoid-small.png".to_string()),
+        };
+        let params = ImageFormParams {
+            image_input: Some(image_bytes),
+            ascii_width: None,
+            render_charset: None,
+            background_char: None,
+            symbol_set: None,
+            crop: None,
+            orientation: None,
+            brightness: None,
+            contrast: None,
+            show_rulers: None,
+            pipeline: Some(Text(submitted_pipeline.to_string())),
+            manifest: None,
+            title: None,
+            author: None,
+            license: None,
+        };
+        let result = generate_image_to_ascii_result(
+            params,
+            &Converter::new().unwrap(),
+            &test_result_store(),
+            &ConversionDefaults::default(),
+        );
+
+        if let HtmlTemplate::ImageToAsciiResult {
+            ascii_result,
+            applied_stages,
+            pipeline,
+            ..
+        } = result
+        {
+            assert_eq!(ascii_result.lines().count(), 10);
+            assert!(ascii_result.lines().all(|l| l.len() == 40));
+            assert_eq!(
+                applied_stages,
+                vec![
+                    "Crop to 40x20 pixels at (0, 0)",
+                    "Rotate 180°",
+                    "Dither with a Bayer matrix"
+                ]
+            );
+            assert_eq!(pipeline, submitted_pipeline);
+        } else {
+            panic!("Expected HtmlTemplate::ImageToAsciiResult.");
+        }
+    }
+
+    // Verifies that the generate_image_to_ascii_result() function runs the conversion from submitted shared settings
+    // in place of the other form inputs and shares the same settings again
+    #[test]
+    fn test_generate_image_to_ascii_result_with_manifest() {
+        // The idea to use "CARGO_MANIFEST_DIR" comes from StackOverflow:
+        // https://stackoverflow.com/questions/30003921/how-can-i-locate-resources-for-testing-with-cargo
+        let image_path = concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/test_assets/images/freakazoid-small.png"
+        );
+        let image_file = read(image_path).unwrap();
+        let submitted_manifest = r#"{"version":1,"width":40,"charset":"ascii","symbols":"@. ","contrast":"linear","dither":"none","rulers":false,"pipeline":[{"rotate":"clockwise180"}]}"#;
+        let image_bytes = Bytes {
+            data: web::Bytes::from(image_file),
+            content_type: Some(mime::IMAGE_PNG),
+            file_name: Some("freakazoid-small.png".to_string()),
+        };
+        let params = ImageFormParams {
+            image_input: Some(image_bytes),
+            ascii_width: Some(Text("200".to_string())),
+            render_charset: None,
+            background_char: None,
+            symbol_set: None,
+            crop: None,
+            orientation: None,
+            brightness: None,
+            contrast: None,
+            show_rulers: Some(Text(true)),
+            pipeline: None,
+            manifest: Some(Text(submitted_manifest.to_string())),
+            title: None,
+            author: None,
+            license: None,
+        };
+        let result = generate_image_to_ascii_result(
+            params,
+            &Converter::new().unwrap(),
+            &test_result_store(),
+            &ConversionDefaults::default(),
+        );
+
+        if let HtmlTemplate::ImageToAsciiResult {
+            ascii_result,
+            applied_stages,
+            manifest,
+            share_link,
+            ..
+        } = result
+        {
+            assert!(ascii_result.lines().all(|l| l.len() == 40));
+            assert!(ascii_result.chars().all(|c| "@. \n".contains(c)));
+            assert_eq!(applied_stages, vec!["Rotate 180°"]);
+            assert_eq!(manifest, submitted_manifest);
+            assert!(share_link.starts_with("/image-to-ascii?manifest=%7B%22version%22%3A1%2C"));
+        } else {
+            panic!("Expected HtmlTemplate::ImageToAsciiResult.");
+        }
+    }
+
+    // Verifies that the generate_image_to_ascii_result() function returns the correctly poplated HtmlTemplate variant
+    // when the submitted shared settings can't be read
+    #[test]
+    fn test_generate_image_to_ascii_result_invalid_manifest() {
+        let image_bytes = Bytes {
+            data: web::Bytes::from_static(b"\x89PNG\r\n\x1a\n\0\0"),
+            content_type: Some(mime::IMAGE_PNG),
+            file_name: Some("test_file.png".to_string()),
+        };
+        let params = ImageFormParams {
+            image_input: Some(image_bytes),
+            ascii_width: None,
+            render_charset: None,
+            background_char: None,
+            symbol_set: None,
+            crop: None,
+            orientation: None,
+            brightness: None,
+            contrast: None,
+            show_rulers: None,
+            pipeline: None,
+            manifest: Some(Text(r#"{"width":41}"#.to_string())),
+            title: None,
+            author: None,
+            license: None,
+        };
+        let result = generate_image_to_ascii_result(
+            params,
+            &Converter::new().unwrap(),
+            &test_result_store(),
+            &ConversionDefaults::default(),
+        );
+        let expected_result = HtmlTemplate::Error {
+            error_message: "It looks like the shared settings you entered can't be used! Be sure to paste the settings exactly as they were shown with your last ASCII art, or leave them blank.",
+            try_again_link: "/image-to-ascii",
+        };
+
+        assert_eq!(result, expected_result);
+    }
+
+    // Verifies that the generate_image_to_ascii_result() function returns the correctly poplated HtmlTemplate variant
+    // when the submitted pipeline can't be read or can't be applied to the image
+    #[test]
+    fn test_generate_image_to_ascii_result_invalid_pipeline() {
+        // The idea to use "CARGO_MANIFEST_DIR" comes from StackOverflow:
+        // https://stackoverflow.com/questions/30003921/how-can-i-locate-resources-for-testing-with-cargo
+        let image_path = concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/test_assets/images/freakazoid-small.png"
+        );
+        let image_file = read(image_path).unwrap();
+
+        let expected_results = [
+            ("[{\"rotate\"", HtmlTemplate::Error {
+                error_message: "It looks like you entered transformations we couldn't read! Be sure to paste the pipeline exactly as it was shown with your last ASCII art, or leave it blank.",
+                try_again_link: "/image-to-ascii"
+            }),
+            (r#"[{"crop":{"x":0,"y":0,"width":100000,"height":1}}]"#, HtmlTemplate::Error {
+                error_message: "It looks like the transformations you entered can't be applied to your image! Be sure every crop fits inside of the image.",
+                try_again_link: "/image-to-ascii"
+            }),
+        ];
+
+        for (pipeline, expected_result) in expected_results {
+            let image_bytes = Bytes {
+                data: web::Bytes::from(image_file.clone()),
+                content_type: Some(mime::IMAGE_PNG),
+                file_name: Some("freakazoid-small.png".to_string()),
+            };
+            let params = ImageFormParams {
+                image_input: Some(image_bytes),
+                ascii_width: None,
+                render_charset: None,
+                background_char: None,
+                symbol_set: None,
+                crop: None,
+                orientation: None,
+                brightness: None,
+                contrast: None,
+                show_rulers: None,
+                pipeline: Some(Text(pipeline.to_string())),
+                manifest: None,
+                title: None,
+                author: None,
+                license: None,
+            };
+
+            assert_eq!(
+                generate_image_to_ascii_result(
+                    params,
+                    &Converter::new().unwrap(),
+                    &test_result_store(),
+                    &ConversionDefaults::default()
+                ),
+                expected_result
+            );
+        }
+    }
+
+    // Verifies that the generate_image_to_ascii_result() function generates braille text when the braille character set is selected
+    #[test]
+    fn test_generate_image_to_ascii_result_braille() {
+        // The idea to use "CARGO_MANIFEST_DIR" comes from StackOverflow:
+        // https://stackoverflow.com/questions/30003921/how-can-i-locate-resources-for-testing-with-cargo
+        let image_path = concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/test_assets/images/freakazoid-small.png"
+        );
+        let image_file = read(image_path).unwrap();
+        let image_bytes = Bytes {
+            data: web::Bytes::from(image_file),
+            content_type: Some(mime::IMAGE_PNG),
+            file_name: Some("freakazoid-small.png".to_string()),
+        };
+        let params = ImageFormParams {
+            image_input: Some(image_bytes),
+            ascii_width: Some(Text("40".to_string())),
+            render_charset: Some(Text("braille".to_string())),
+            background_char: None,
+            symbol_set: None,
+            crop: None,
+            orientation: None,
+            brightness: None,
+            contrast: None,
+            show_rulers: None,
+            pipeline: None,
+            manifest: None,
+            title: None,
+            author: None,
+            license: None,
+        };
+        let result = generate_image_to_ascii_result(
+            params,
+            &Converter::new().unwrap(),
+            &test_result_store(),
+            &ConversionDefaults::default(),
+        );
+
+        if let HtmlTemplate::ImageToAsciiResult { ascii_result, .. } = result {
+            assert!(ascii_result.lines().all(|l| l.chars().count() == 40
+                && l.chars().all(|c| ('\u{2800}'..='\u{28FF}').contains(&c))));
+        } else {
+            panic!("Expected HtmlTemplate::ImageToAsciiResult.");
+        }
+    }
+
+    // Verifies that the generate_image_to_ascii_result() function returns the correctly poplated HtmlTemplate variant
+    // when there is error caused by the submission of an unsupported character set
+    #[test]
+    fn test_generate_image_to_ascii_result_unsupported_charset() {
+        let image_bytes = Bytes {
+            data: web::Bytes::from_static(b"\x89PNG\r\n\x1a\n\0\0"),
+            content_type: Some(mime::IMAGE_PNG),
+            file_name: Some("test_file.png".to_string()),
+        };
+        let params = ImageFormParams {
+            image_input: Some(image_bytes),
+            ascii_width: None,
+            render_charset: Some(Text("emoji".to_string())),
+            background_char: None,
+            symbol_set: None,
+            crop: None,
+            orientation: None,
+            brightness: None,
+            contrast: None,
+            show_rulers: None,
+            pipeline: None,
+            manifest: None,
+            title: None,
+            author: None,
+            license: None,
+        };
+        let result = generate_image_to_ascii_result(
+            params,
+            &Converter::new().unwrap(),
+            &test_result_store(),
+            &ConversionDefaults::default(),
+        );
+
+        let expected_result = HtmlTemplate::Error {
+            error_message: "It looks like you selected an unsupported character set! Be sure to pick one of the character sets listed on the form.",
+            try_again_link: "/image-to-ascii"
+        };
+
+        assert_eq!(result, expected_result);
+    }
+
+    // Verifies that the generate_image_to_ascii_result() function returns the correctly poplated HtmlTemplate variant
+    // when there is error caused by the submission of an unsupported license
+    #[test]
+    fn test_generate_image_to_ascii_result_unsupported_license() {
+        let image_bytes = Bytes {
+            data: web::Bytes::from_static(b"\x89PNG\r\n\x1a\n\0\0"),
+            content_type: Some(mime::IMAGE_PNG),
+            file_name: Some("test_file.png".to_string()),
+        };
+        let params = ImageFormParams {
+            image_input: Some(image_bytes),
+            ascii_width: None,
+            render_charset: None,
+            background_char: None,
+            symbol_set: None,
+            crop: None,
+            orientation: None,
+            brightness: None,
+            contrast: None,
+            show_rulers: None,
+            pipeline: None,
+            manifest: None,
+            title: None,
+            author: None,
+            license: Some(Text("gpl-3.0".to_string())),
+        };
+        let result = generate_image_to_ascii_result(
+            params,
+            &Converter::new().unwrap(),
+            &test_result_store(),
+            &ConversionDefaults::default(),
+        );
+
+        let expected_result = HtmlTemplate::Error {
+            error_message: "It looks like you picked an unsupported license! Be sure to pick one of the licenses listed on the form.",
+            try_again_link: "/image-to-ascii"
+        };
+
+        assert_eq!(result, expected_result);
+    }
+
+    // Verifies that the generate_image_to_ascii_result() function uses the submitted background character for empty space
+    #[test]
+    fn test_generate_image_to_ascii_result_background_char() {
+        // The idea to use "CARGO_MANIFEST_DIR" comes from StackOverflow:
+        // https://stackoverflow.com/questions/30003921/how-can-i-locate-resources-for-testing-with-cargo
+        let image_path = concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/test_assets/images/freakazoid-small.png"
+        );
+        let image_file = read(image_path).unwrap();
+        let image_bytes = Bytes {
+            data: web::Bytes::from(image_file),
+            content_type: Some(mime::IMAGE_PNG),
             file_name: Some("freakazoid-small.png".to_string()),
-            size: image_file.len(),
         };
-        let params = ImageFormParams {
-            image_input: Some(temp_file),
+        let params = ImageFormParams {
+            image_input: Some(image_bytes),
+            ascii_width: None,
+            render_charset: None,
+            background_char: Some(Text(".".to_string())),
+            symbol_set: None,
+            crop: None,
+            orientation: None,
+            brightness: None,
+            contrast: None,
+            show_rulers: None,
+            pipeline: None,
+            manifest: None,
+            title: None,
+            author: None,
+            license: None,
+        };
+        let result = generate_image_to_ascii_result(
+            params,
+            &Converter::new().unwrap(),
+            &test_result_store(),
+            &ConversionDefaults::default(),
+        );
+
+        if let HtmlTemplate::ImageToAsciiResult { ascii_result, .. } = result {
+            assert!(!ascii_result.contains(' '));
+            assert!(ascii_result.contains('.'));
+        } else {
+            panic!("Expected HtmlTemplate::ImageToAsciiResult.");
+        }
+    }
+
+    // Verifies that the generate_image_to_ascii_result() function only uses the symbols of the submitted symbol set, and
+    // the submitted background character along with them
+    #[test]
+    fn test_generate_image_to_ascii_result_symbol_set() {
+        // The idea to use "CARGO_MANIFEST_DIR" comes from StackOverflow:
+        // https://stackoverflow.com/questions/30003921/how-can-i-locate-resources-for-testing-with-cargo
+        let image_path = concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/test_assets/images/freakazoid-small.png"
+        );
+        let image_file = read(image_path).unwrap();
+
+        for (symbol_set, background_char, symbols) in [
+            ("minimal10", None, "@%#*+=-:. \n"),
+            ("blocks", None, "\u{2588}\u{2593}\u{2592}\u{2591} \n"),
+            ("binary", Some("."), "#.\n"),
+        ] {
+            let params = ImageFormParams {
+                image_input: Some(Bytes {
+                    data: web::Bytes::from(image_file.clone()),
+                    content_type: Some(mime::IMAGE_PNG),
+                    file_name: Some("freakazoid-small.png".to_string()),
+                }),
+                ascii_width: Some(Text("40".to_string())),
+                render_charset: None,
+                background_char: background_char.map(|c: &str| Text(c.to_string())),
+                symbol_set: Some(Text(symbol_set.to_string())),
+                crop: None,
+                orientation: None,
+                brightness: None,
+                contrast: None,
+                show_rulers: None,
+                pipeline: None,
+                manifest: None,
+                title: None,
+                author: None,
+                license: None,
+            };
+            let result = generate_image_to_ascii_result(
+                params,
+                &Converter::new().unwrap(),
+                &test_result_store(),
+                &ConversionDefaults::default(),
+            );
+
+            if let HtmlTemplate::ImageToAsciiResult { ascii_result, .. } = result {
+                assert!(
+                    ascii_result.chars().all(|c| symbols.contains(c)),
+                    "{} used other symbols: {}",
+                    symbol_set,
+                    ascii_result
+                );
+                assert!(ascii_result.contains(symbols.chars().next().unwrap()));
+            } else {
+                panic!("Expected HtmlTemplate::ImageToAsciiResult.");
+            }
+        }
+    }
+
+    // Verifies that the generate_image_to_ascii_result() function only converts the selected region of the image, and returns
+    // an error when the region isn't inside of the image
+    #[test]
+    fn test_generate_image_to_ascii_result_crop() {
+        // The idea to use "CARGO_MANIFEST_DIR" comes from StackOverflow:
+        // https://stackoverflow.com/questions/30003921/how-can-i-locate-resources-for-testing-with-cargo
+        let image_path = concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/test_assets/images/freakazoid-small.png"
+        );
+        let image_file = read(image_path).unwrap();
+        let (width, height) = image::image_dimensions(image_path).unwrap();
+
+        let convert = |crop: Option<String>| {
+            let params = ImageFormParams {
+                image_input: Some(Bytes {
+                    data: web::Bytes::from(image_file.clone()),
+                    content_type: Some(mime::IMAGE_PNG),
+                    file_name: Some("freakazoid-small.png".to_string()),
+                }),
+                ascii_width: Some(Text("40".to_string())),
+                render_charset: None,
+                background_char: None,
+                symbol_set: None,
+                crop: crop.map(Text),
+                orientation: None,
+                brightness: None,
+                contrast: None,
+                show_rulers: None,
+                pipeline: None,
+                manifest: None,
+                title: None,
+                author: None,
+                license: None,
+            };
+            generate_image_to_ascii_result(
+                params,
+                &Converter::new().unwrap(),
+                &test_result_store(),
+                &ConversionDefaults::default(),
+            )
+        };
+
+        let rows = |result: HtmlTemplate| match result {
+            HtmlTemplate::ImageToAsciiResult {
+                ascii_result,
+                manifest,
+                ..
+            } => (ascii_result.lines().count(), manifest),
+            _ => panic!("Expected HtmlTemplate::ImageToAsciiResult."),
+        };
+        let (whole_rows, _) = rows(convert(None));
+        let (left_half_rows, manifest) =
+            rows(convert(Some(format!("0,0,{},{}", width / 2, height))));
+
+        // the left half is as wide as the whole image, so it is twice as tall
+        assert!(left_half_rows.abs_diff(whole_rows * 2) <= 1);
+        assert!(manifest.contains(r#""crop":{"x":0,"y":0"#));
+
+        let result = convert(Some(format!("0,0,{},{}", width + 1, height)));
+        assert_eq!(
+            result,
+            HtmlTemplate::Error {
+                error_message: "It looks like the part of the image you selected isn't inside of your image! Be sure to select the part to convert after choosing the image, or clear the selection.",
+                try_again_link: "/image-to-ascii"
+            }
+        );
+    }
+
+    // Verifies that the generate_image_to_ascii_result() function turns the image before the transformations of the pipeline
+    #[test]
+    fn test_generate_image_to_ascii_result_orientation() {
+        // The idea to use "CARGO_MANIFEST_DIR" comes from StackOverflow:
+        // https://stackoverflow.com/questions/30003921/how-can-i-locate-resources-for-testing-with-cargo
+        let image_path = concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/test_assets/images/freakazoid-small.png"
+        );
+        let image_file = read(image_path).unwrap();
+
+        let convert = |orientation: &str| {
+            let params = ImageFormParams {
+                image_input: Some(Bytes {
+                    data: web::Bytes::from(image_file.clone()),
+                    content_type: Some(mime::IMAGE_PNG),
+                    file_name: Some("freakazoid-small.png".to_string()),
+                }),
+                ascii_width: Some(Text("40".to_string())),
+                render_charset: None,
+                background_char: None,
+                symbol_set: None,
+                crop: None,
+                orientation: Some(Text(orientation.to_string())),
+                brightness: None,
+                contrast: None,
+                show_rulers: None,
+                pipeline: Some(Text(r#"[{"dither":"floyd_steinberg"}]"#.to_string())),
+                manifest: None,
+                title: None,
+                author: None,
+                license: None,
+            };
+            generate_image_to_ascii_result(
+                params,
+                &Converter::new().unwrap(),
+                &test_result_store(),
+                &ConversionDefaults::default(),
+            )
+        };
+
+        match convert("flip_horizontal") {
+            HtmlTemplate::ImageToAsciiResult { manifest, .. } => {
+                assert!(
+                    manifest.contains(r#"[{"flip":"horizontal"},{"dither":"floyd_steinberg"}]"#)
+                );
+            }
+            _ => panic!("Expected HtmlTemplate::ImageToAsciiResult."),
+        }
+        assert_eq!(
+            convert("upside_down"),
+            HtmlTemplate::Error {
+                error_message: "It looks like you picked an unsupported rotation! Be sure to pick one of the orientations listed on the form.",
+                try_again_link: "/image-to-ascii"
+            }
+        );
+    }
+
+    // Verifies that the generate_image_to_ascii_result() function adjusts the brightness and contrast picked on the sliders
+    #[test]
+    fn test_generate_image_to_ascii_result_adjustments() {
+        // The idea to use "CARGO_MANIFEST_DIR" comes from StackOverflow:
+        // https://stackoverflow.com/questions/30003921/how-can-i-locate-resources-for-testing-with-cargo
+        let image_path = concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/test_assets/images/freakazoid-small.png"
+        );
+        let image_file = read(image_path).unwrap();
+
+        let convert = |brightness: &str, contrast: &str| {
+            let params = ImageFormParams {
+                image_input: Some(Bytes {
+                    data: web::Bytes::from(image_file.clone()),
+                    content_type: Some(mime::IMAGE_PNG),
+                    file_name: Some("freakazoid-small.png".to_string()),
+                }),
+                ascii_width: Some(Text("40".to_string())),
+                render_charset: None,
+                background_char: None,
+                symbol_set: None,
+                crop: None,
+                orientation: None,
+                brightness: Some(Text(brightness.to_string())),
+                contrast: Some(Text(contrast.to_string())),
+                show_rulers: None,
+                pipeline: None,
+                manifest: None,
+                title: None,
+                author: None,
+                license: None,
+            };
+            generate_image_to_ascii_result(
+                params,
+                &Converter::new().unwrap(),
+                &test_result_store(),
+                &ConversionDefaults::default(),
+            )
+        };
+
+        let ascii = |result| match result {
+            HtmlTemplate::ImageToAsciiResult {
+                ascii_result,
+                manifest,
+                ..
+            } => (ascii_result, manifest),
+            _ => panic!("Expected HtmlTemplate::ImageToAsciiResult."),
         };
-        let result = generate_image_to_ascii_result(params);
 
+        let (unchanged, _) = ascii(convert("0", "0"));
+        let (adjusted, manifest) = ascii(convert("60", "-20"));
+        assert_ne!(unchanged, adjusted);
+        assert!(manifest.contains(r#""brightness":60,"contrast_adjustment":-20.0"#));
+        assert_eq!(
+            convert("300", "0"),
+            HtmlTemplate::Error {
+                error_message: "It looks like the brightness or contrast you picked is out of range! Be sure to use the sliders on the form to pick them.",
+                try_again_link: "/image-to-ascii"
+            }
+        );
+    }
+
+    // Verifies that the generate_image_to_ascii_result() function adds line numbers and column rulers when they are requested
+    #[test]
+    fn test_generate_image_to_ascii_result_rulers() {
         // The idea to use "CARGO_MANIFEST_DIR" comes from StackOverflow:
         // https://stackoverflow.com/questions/30003921/how-can-i-locate-resources-for-testing-with-cargo
-        let ascii_path = concat!(
+        let image_path = concat!(
             env!("CARGO_MANIFEST_DIR"),
-            "/test_assets/ascii/freakazoid-small.txt"
+            "/test_assets/images/freakazoid-small.png"
         );
-        let ascii_text = read_to_string(ascii_path).unwrap();
-        let expected_result = HtmlTemplate::ImageToAsciiResult {
-            ascii_result: ascii_text,
+        let image_file = read(image_path).unwrap();
+        let image_bytes = Bytes {
+            data: web::Bytes::from(image_file),
+            content_type: Some(mime::IMAGE_PNG),
+            file_name: Some("freakazoid-small.png".to_string()),
+        };
+        let params = ImageFormParams {
+            image_input: Some(image_bytes),
+            ascii_width: Some(Text("40".to_string())),
+            render_charset: None,
+            background_char: None,
+            symbol_set: None,
+            crop: None,
+            orientation: None,
+            brightness: None,
+            contrast: None,
+            show_rulers: Some(Text(true)),
+            pipeline: None,
+            manifest: None,
+            title: None,
+            author: None,
+            license: None,
         };
+        let result = generate_image_to_ascii_result(
+            params,
+            &Converter::new().unwrap(),
+            &test_result_store(),
+            &ConversionDefaults::default(),
+        );
 
-        assert_eq!(result, expected_result);
+        if let HtmlTemplate::ImageToAsciiResult { ascii_result, .. } = result {
+            let lines: Vec<&str> = ascii_result.lines().collect();
+            assert!(lines[1].ends_with("1234567890123456789012345678901234567890"));
+            assert!(lines[3].starts_with(" 1 | "));
+        } else {
+            panic!("Expected HtmlTemplate::ImageToAsciiResult.");
+        }
     }
 
-    // Verifies that the generate_image_to_ascii_result_error() function returns the correctly poplated HtmlTemplate variant
-    // when there is an error due to problems parsing the given image in the ascii_art_converter library crate
+    // Verifies that the generate_image_to_ascii_result() function returns the correctly poplated HtmlTemplate variant
+    // when there is error caused by the submission of an unsupported background character
     #[test]
-    fn test_generate_image_to_ascii_result_error() {
-        let temp_file = TempFile {
-            file: NamedTempFile::new().unwrap(),
-            content_type: Some(mime::IMAGE_JPEG),
+    fn test_generate_image_to_ascii_result_unsupported_background_char() {
+        let image_bytes = Bytes {
+            data: web::Bytes::from_static(b"\x89PNG\r\n\x1a\n\0\0"),
+            content_type: Some(mime::IMAGE_PNG),
             file_name: Some("test_file.png".to_string()),
-            size: 10,
         };
         let params = ImageFormParams {
-            image_input: Some(temp_file),
+            image_input: Some(image_bytes),
+            ascii_width: None,
+            render_charset: None,
+            background_char: Some(Text("ab".to_string())),
+            symbol_set: None,
+            crop: None,
+            orientation: None,
+            brightness: None,
+            contrast: None,
+            show_rulers: None,
+            pipeline: None,
+            manifest: None,
+            title: None,
+            author: None,
+            license: None,
         };
-        let result = generate_image_to_ascii_result(params);
+        let result = generate_image_to_ascii_result(
+            params,
+            &Converter::new().unwrap(),
+            &test_result_store(),
+            &ConversionDefaults::default(),
+        );
 
         let expected_result = HtmlTemplate::Error {
-            error_message: "It looks like we ran into an issue with parsing your image! There could be a problem with your image or with our parser, so try it one more time. But if that doesn't work, try a different image.",
+            error_message: "It looks like you entered an unsupported background character! Be sure to enter a single ASCII character, or leave it blank to use spaces.",
             try_again_link: "/image-to-ascii"
         };
 
@@ -378,40 +3408,842 @@ mod tests {
     }
 
     // Verifies that the generate_image_to_ascii_result() function returns the correctly poplated HtmlTemplate variant
-    // when there is an empty input error
+    // when there is error caused by the submission of an unsupported ASCII art width
     #[test]
-    fn test_generate_image_to_ascii_result_empty_input() {
-        let params = ImageFormParams { image_input: None };
-        let result = generate_image_to_ascii_result(params);
+    fn test_generate_image_to_ascii_result_unsupported_width() {
+        let image_bytes = Bytes {
+            data: web::Bytes::from_static(b"\x89PNG\r\n\x1a\n\0\0"),
+            content_type: Some(mime::IMAGE_PNG),
+            file_name: Some("test_file.png".to_string()),
+        };
+        let params = ImageFormParams {
+            image_input: Some(image_bytes),
+            ascii_width: Some(Text("9000".to_string())),
+            render_charset: None,
+            background_char: None,
+            symbol_set: None,
+            crop: None,
+            orientation: None,
+            brightness: None,
+            contrast: None,
+            show_rulers: None,
+            pipeline: None,
+            manifest: None,
+            title: None,
+            author: None,
+            license: None,
+        };
+        let result = generate_image_to_ascii_result(
+            params,
+            &Converter::new().unwrap(),
+            &test_result_store(),
+            &ConversionDefaults::default(),
+        );
 
         let expected_result = HtmlTemplate::Error {
-            error_message: "It looks like you submitted an empty form! Be sure to upload an image to the form before submitting.",
+            error_message: "It looks like you selected an unsupported ASCII art width! Be sure to pick one of the widths listed on the form.",
             try_again_link: "/image-to-ascii"
         };
 
         assert_eq!(result, expected_result);
     }
 
-    // Verifies that the generate_image_to_ascii_result() function returns the correctly poplated HtmlTemplate variant
-    // when there is error caused by the submission of an unsupported image type
+    // Verifies that the generate_image_to_ascii_result() function returns the correctly populated HtmlTemplate variant
+    // when the header of the image asks for more pixels than the converter decodes
     #[test]
-    fn test_generate_image_to_ascii_result_unsupported_image_type() {
-        let temp_file = TempFile {
-            file: NamedTempFile::new().unwrap(),
-            content_type: Some(mime::IMAGE_GIF),
-            file_name: Some("test_file.gif".to_string()),
-            size: 10,
+    fn test_generate_image_to_ascii_result_too_large() {
+        // a 1x1 BMP whose header claims it is 20000x20000 pixels
+        let mut bmp = Cursor::new(Vec::new());
+        image::DynamicImage::new_rgb8(1, 1)
+            .write_to(&mut bmp, image::ImageOutputFormat::Bmp)
+            .unwrap();
+        let mut image_file = bmp.into_inner();
+        image_file[18..22].copy_from_slice(&20000_i32.to_le_bytes());
+        image_file[22..26].copy_from_slice(&20000_i32.to_le_bytes());
+
+        let image_bytes = Bytes {
+            data: web::Bytes::from(image_file),
+            content_type: Some(mime::IMAGE_BMP),
+            file_name: Some("bomb.bmp".to_string()),
         };
         let params = ImageFormParams {
-            image_input: Some(temp_file),
+            image_input: Some(image_bytes),
+            ascii_width: None,
+            render_charset: None,
+            background_char: None,
+            symbol_set: None,
+            crop: None,
+            orientation: None,
+            brightness: None,
+            contrast: None,
+            show_rulers: None,
+            pipeline: None,
+            manifest: None,
+            title: None,
+            author: None,
+            license: None,
         };
-        let result = generate_image_to_ascii_result(params);
+        let result = generate_image_to_ascii_result(
+            params,
+            &Converter::new().unwrap(),
+            &test_result_store(),
+            &ConversionDefaults::default(),
+        );
 
         let expected_result = HtmlTemplate::Error {
-            error_message: "It looks like you submitted an unsupported image type! Be sure to upload either a JPEG or a PNG image only.",
+            error_message: "It looks like your image is too large for us to convert! Be sure the image has no more than 100 megapixels, or try a smaller copy of it.",
             try_again_link: "/image-to-ascii"
         };
 
         assert_eq!(result, expected_result);
     }
+
+    // Tests for generate_ascii_animation_stream() function
+
+    // Verifies that the generate_ascii_animation_stream() function returns a stream of the frames of a GIF
+    #[actix_web::test]
+    async fn test_generate_ascii_animation_stream() {
+        // The idea to use "CARGO_MANIFEST_DIR" comes from StackOverflow:
+        // https://stackoverflow.com/questions/30003921/how-can-i-locate-resources-for-testing-with-cargo
+        let gif_path = concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/test_assets/images/moving-bar.gif"
+        );
+        let form = AnimationFormParams {
+            animation_input: Some(Bytes {
+                data: web::Bytes::from(read(gif_path).unwrap()),
+                content_type: Some(mime::IMAGE_GIF),
+                file_name: Some("moving-bar.gif".to_string()),
+            }),
+            ascii_width: None,
+        };
+
+        let stream = match generate_ascii_animation_stream(
+            form,
+            web::Data::new(Converter::new().unwrap()),
+            &ConversionDefaults::default(),
+            &JobQueue::new(1, 1, usize::MAX),
+        ) {
+            StreamResult::Streaming(frame_stream) => frame_stream,
+            _ => panic!("the animation wasn't streamed"),
+        };
+        let body = actix_web::body::to_bytes(stream).await.unwrap();
+        let body = String::from_utf8(body.to_vec()).unwrap();
+
+        assert_eq!(body.matches("event: frame\n").count(), 4);
+        assert!(body.ends_with("event: end\ndata: \n\n"));
+    }
+
+    // Verifies that the generate_ascii_animation_stream() function returns an error message for invalid input, and
+    // doesn't start converting when the queue is full
+    #[actix_web::test]
+    async fn test_generate_ascii_animation_stream_errors() {
+        // The idea to use "CARGO_MANIFEST_DIR" comes from StackOverflow:
+        // https://stackoverflow.com/questions/30003921/how-can-i-locate-resources-for-testing-with-cargo
+        let gif_path = concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/test_assets/images/moving-bar.gif"
+        );
+        let converter = web::Data::new(Converter::new().unwrap());
+        let jobs = JobQueue::new(1, 0, usize::MAX);
+        let gif = Bytes {
+            data: web::Bytes::from(read(gif_path).unwrap()),
+            content_type: Some(mime::IMAGE_GIF),
+            file_name: Some("test_file.gif".to_string()),
+        };
+        let form = AnimationFormParams {
+            animation_input: None,
+            ascii_width: None,
+        };
+
+        assert!(matches!(
+            generate_ascii_animation_stream(
+                form,
+                converter.clone(),
+                &ConversionDefaults::default(),
+                &jobs
+            ),
+            StreamResult::Rejected("It looks like you submitted an empty form! Be sure to upload a GIF as animation_input before submitting.\n")
+        ));
+
+        let form = AnimationFormParams {
+            animation_input: Some(gif),
+            ascii_width: Some(Text("9000".to_string())),
+        };
+
+        assert!(matches!(
+            generate_ascii_animation_stream(
+                form,
+                converter.clone(),
+                &ConversionDefaults::default(),
+                &jobs
+            ),
+            StreamResult::Rejected("It looks like you selected an unsupported ASCII art width! Be sure to pick one of the widths listed on the image to ASCII form.\n")
+        ));
+
+        let form = AnimationFormParams {
+            animation_input: Some(Bytes {
+                data: web::Bytes::from(read(gif_path).unwrap()),
+                content_type: Some(mime::IMAGE_GIF),
+                file_name: Some("test_file.gif".to_string()),
+            }),
+            ascii_width: None,
+        };
+
+        assert!(matches!(
+            generate_ascii_animation_stream(form, converter, &ConversionDefaults::default(), &jobs),
+            StreamResult::Busy
+        ));
+    }
+
+    // Tests for generate_batch_result() function
+
+    // Verifies that the generate_batch_result() function returns a ZIP archive with the ASCII art of every image
+    // and a list of the images that couldn't be converted
+    #[test]
+    fn test_generate_batch_result() {
+        // The idea to use "CARGO_MANIFEST_DIR" comes from StackOverflow:
+        // https://stackoverflow.com/questions/30003921/how-can-i-locate-resources-for-testing-with-cargo
+        let image_path = concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/test_assets/images/freakazoid-small.png"
+        );
+        let ascii_path = concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/test_assets/ascii/freakazoid-small.txt"
+        );
+        let gif_path = concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/test_assets/images/moving-bar.gif"
+        );
+        let upload = |file_name: &str, data: Vec<u8>| Bytes {
+            data: web::Bytes::from(data),
+            content_type: None,
+            file_name: Some(file_name.to_string()),
+        };
+        let form = BatchFormParams {
+            images: vec![
+                upload("freakazoid.png", read(image_path).unwrap()),
+                upload("animation.gif", read(gif_path).unwrap()),
+                upload("freakazoid.png", read(image_path).unwrap()),
+            ],
+            ascii_width: None,
+        };
+
+        let archive = generate_batch_result(
+            form,
+            &Converter::new().unwrap(),
+            &ConversionDefaults::default(),
+        )
+        .unwrap();
+        let mut archive = zip::ZipArchive::new(std::io::Cursor::new(archive)).unwrap();
+
+        assert_eq!(
+            archive
+                .file_names()
+                .collect::<std::collections::BTreeSet<_>>(),
+            ["errors.txt", "freakazoid-2.txt", "freakazoid.txt"].into()
+        );
+        for (name, expected) in [
+            ("freakazoid.txt", read_to_string(ascii_path).unwrap()),
+            ("freakazoid-2.txt", read_to_string(ascii_path).unwrap()),
+            (
+                "errors.txt",
+                "animation: the image is not a JPEG, PNG, WebP, BMP or TIFF\n".to_string(),
+            ),
+        ] {
+            let mut contents = String::new();
+            std::io::Read::read_to_string(&mut archive.by_name(name).unwrap(), &mut contents)
+                .unwrap();
+            assert_eq!(contents, expected);
+        }
+    }
+
+    // Verifies that the generate_batch_result() function returns an error message when no image can be converted
+    #[test]
+    fn test_generate_batch_result_errors() {
+        let converter = Converter::new().unwrap();
+        let form = BatchFormParams {
+            images: Vec::new(),
+            ascii_width: None,
+        };
+
+        assert_eq!(
+            generate_batch_result(form, &converter, &ConversionDefaults::default()),
+            Err("It looks like you submitted an empty form! Be sure to upload images or a ZIP archive of images as images before submitting.\n")
+        );
+
+        let form = BatchFormParams {
+            images: vec![Bytes {
+                data: web::Bytes::from_static(b"not an image"),
+                content_type: None,
+                file_name: Some("notes.txt".to_string()),
+            }],
+            ascii_width: None,
+        };
+
+        assert_eq!(
+            generate_batch_result(form, &converter, &ConversionDefaults::default()),
+            Err("It looks like none of your images could be converted! Be sure to upload JPEG, PNG, WebP, BMP or TIFF images only.\n")
+        );
+    }
+
+    // Verifies that the generate_collage_result() function stitches the ASCII art of the images with the separators
+    #[test]
+    fn test_generate_collage_result() {
+        // The idea to use "CARGO_MANIFEST_DIR" comes from StackOverflow:
+        // https://stackoverflow.com/questions/30003921/how-can-i-locate-resources-for-testing-with-cargo
+        let image_path = concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/test_assets/images/freakazoid-small.png"
+        );
+        let upload = |data: Vec<u8>| Bytes {
+            data: web::Bytes::from(data),
+            content_type: None,
+            file_name: None,
+        };
+        let form = CollageFormParams {
+            images: vec![
+                upload(read(image_path).unwrap()),
+                upload(read(image_path).unwrap()),
+            ],
+            layout: None,
+            separator: Some(Text(" | ".to_string())),
+            row_separator: None,
+            ascii_width: Some(Text("40".to_string())),
+        };
+        let converter = Converter::new().unwrap();
+
+        let collage =
+            generate_collage_result(form, &converter, &ConversionDefaults::default()).unwrap();
+        let ascii = converter
+            .image_to_ascii(
+                &read(image_path).unwrap()[..],
+                ConvertOptions {
+                    width: Some(40),
+                    symbol_map: default_symbol_map(&converter),
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+
+        assert_eq!(collage.lines().count(), ascii.lines().count());
+        assert!(collage
+            .lines()
+            .zip(ascii.lines())
+            .all(|(collage, line)| collage == format!("{} | {}", line, line)));
+    }
+
+    // Verifies that the generate_collage_result() function returns a plain text error message for invalid input
+    #[test]
+    fn test_generate_collage_result_errors() {
+        let converter = Converter::new().unwrap();
+        let form = |images: &[&'static [u8]], layout: &str| CollageFormParams {
+            images: images
+                .iter()
+                .map(|image| Bytes {
+                    data: web::Bytes::from_static(image),
+                    content_type: None,
+                    file_name: None,
+                })
+                .collect(),
+            layout: Some(Text(layout.to_string())),
+            separator: None,
+            row_separator: None,
+            ascii_width: None,
+        };
+        let png: &'static [u8] = b"\x89PNG\r\n\x1a\n\0\0";
+
+        assert_eq!(
+            generate_collage_result(form(&[png], "grid"), &converter, &ConversionDefaults::default()),
+            Err("It looks like you submitted too few images! Be sure to upload at least 2 images as images before submitting.\n")
+        );
+        assert_eq!(
+            generate_collage_result(form(&[png, png], "diagonal"), &converter, &ConversionDefaults::default()),
+            Err("It looks like you selected an unsupported layout! Be sure to pick side-by-side or grid as the layout.\n")
+        );
+        // the images look like PNGs, but can't be decoded
+        assert_eq!(
+            generate_collage_result(form(&[png, png], "grid"), &converter, &ConversionDefaults::default()),
+            Err("It looks like we ran into an issue with parsing one of your images! Try it one more time, but if that doesn't work, try different images.\n")
+        );
+    }
+
+    // Verifies that the generate_text_to_ascii_result() function draws the text in letters as tall as the font size
+    #[test]
+    fn test_generate_text_to_ascii_result() {
+        let converter = Converter::new().unwrap();
+        let params = TextFormParams {
+            text_input: "Hi".to_string(),
+            font_size: Some("12".to_string()),
+            render_charset: Some("ascii".to_string()),
+        };
+
+        let result =
+            generate_text_to_ascii_result(params, &converter, &ConversionDefaults::default());
+
+        if let HtmlTemplate::TextToAsciiResult { ascii_result } = &result {
+            assert_eq!(ascii_result.lines().count(), 12);
+            assert!(!ascii_result.trim().is_empty());
+        } else {
+            panic!("The text was not drawn: {:?}", result);
+        }
+    }
+
+    // Verifies that the generate_text_to_ascii_result() function returns the correctly populated error template for invalid text
+    #[test]
+    fn test_generate_text_to_ascii_result_errors() {
+        let converter = Converter::new().unwrap();
+        let params = |text_input: &str, font_size: &str| TextFormParams {
+            text_input: text_input.to_string(),
+            font_size: Some(font_size.to_string()),
+            render_charset: None,
+        };
+
+        assert_eq!(
+            generate_text_to_ascii_result(params(" ", "8"), &converter, &ConversionDefaults::default()),
+            HtmlTemplate::Error {
+                error_message: "It looks like you submitted an empty form! Be sure to type some text into the text box of the form.",
+                try_again_link: "/text-to-ascii",
+            }
+        );
+        assert_eq!(
+            generate_text_to_ascii_result(params(&"a".repeat(41), "8"), &converter, &ConversionDefaults::default()),
+            HtmlTemplate::Error {
+                error_message: "It looks like your text is too long for a banner! Be sure the text has no more than 40 characters.",
+                try_again_link: "/text-to-ascii",
+            }
+        );
+        assert_eq!(
+            generate_text_to_ascii_result(params("Hi", "7"), &converter, &ConversionDefaults::default()),
+            HtmlTemplate::Error {
+                error_message: "It looks like you selected an unsupported letter size! Be sure to pick one of the sizes listed on the form.",
+                try_again_link: "/text-to-ascii",
+            }
+        );
+    }
+
+    // Verifies that the generate_qr_result() function draws a link as a QR code, and that the PNG of the code can be drawn from it again
+    #[cfg(feature = "qr")]
+    #[test]
+    fn test_generate_qr_result() {
+        let result = generate_qr_result(QrFormParams {
+            url: " https://example.com/gallery\n".to_string(),
+        });
+
+        let HtmlTemplate::QrResult {
+            ascii_result,
+            image_result,
+        } = result
+        else {
+            panic!("The link was not drawn: {:?}", result);
+        };
+        assert!(ascii_result.contains('█'));
+        assert!(image_result.starts_with("data:image/png;base64,"));
+
+        let redrawn = generate_qr_image_result(QrAsciiFormParams {
+            qr_ascii: ascii_result.replace('\n', "\r\n"),
+        });
+        assert_eq!(
+            redrawn,
+            HtmlTemplate::QrResult {
+                ascii_result,
+                image_result,
+            }
+        );
+    }
+
+    // Verifies that the generate_qr_result() and generate_qr_image_result() functions return the correctly populated error
+    // template for invalid input
+    #[cfg(feature = "qr")]
+    #[test]
+    fn test_generate_qr_result_errors() {
+        let link = |url: &str| {
+            generate_qr_result(QrFormParams {
+                url: url.to_string(),
+            })
+        };
+        let drawn = |qr_ascii: &str| {
+            generate_qr_image_result(QrAsciiFormParams {
+                qr_ascii: qr_ascii.to_string(),
+            })
+        };
+
+        assert_eq!(
+            link(""),
+            HtmlTemplate::Error {
+                error_message: "It looks like you submitted an empty form! Be sure to type a link into the text box of the form.",
+                try_again_link: "/qr",
+            }
+        );
+        assert_eq!(
+            link("example.com"),
+            HtmlTemplate::Error {
+                error_message: "It looks like that isn't a link! Be sure the link starts with http:// or https:// and has no spaces.",
+                try_again_link: "/qr",
+            }
+        );
+        assert_eq!(
+            drawn("\n \n"),
+            HtmlTemplate::Error {
+                error_message: "It looks like you submitted an empty form! Be sure to paste your QR code into the text box of the form.",
+                try_again_link: "/qr",
+            }
+        );
+        assert_eq!(
+            drawn("##\n##"),
+            HtmlTemplate::Error {
+                error_message: "It looks like your QR code has characters other than blocks and spaces! Be sure to paste a QR code drawn with the █, ▀ and ▄ characters.",
+                try_again_link: "/qr",
+            }
+        );
+        assert_eq!(
+            drawn("██\n██"),
+            HtmlTemplate::Error {
+                error_message: "It looks like that isn't a whole QR code! Be sure to copy every line of the QR code, and that the lines weren't wrapped.",
+                try_again_link: "/qr",
+            }
+        );
+    }
+
+    // Tests for generate_publish_result() function
+
+    // Verifies that the generate_publish_result() function stores ASCII art under its hash only once
+    #[test]
+    fn test_generate_publish_result() {
+        let root = tempfile::tempdir().unwrap();
+        let store = ArtStore::new(root.path()).unwrap();
+        let ascii = "$$\n..\n".to_string();
+        let hash = content_hash(&ascii);
+
+        assert_eq!(
+            generate_publish_result(&hash, ascii.clone(), &store),
+            PublishResult::Created
+        );
+        assert_eq!(
+            generate_publish_result(&hash, ascii.clone(), &store),
+            PublishResult::AlreadyStored
+        );
+        assert_eq!(store.get(&hash).unwrap(), Some(ascii));
+    }
+
+    // Verifies that the generate_publish_result() function rejects empty art, non-ASCII art and art that doesn't match its hash
+    #[test]
+    fn test_generate_publish_result_rejected() {
+        let root = tempfile::tempdir().unwrap();
+        let store = ArtStore::new(root.path()).unwrap();
+
+        assert_eq!(
+            generate_publish_result(&content_hash(""), String::new(), &store),
+            PublishResult::Rejected("It looks like you sent empty ASCII art! Be sure to send the ASCII art text as the body of the request.\n".to_string())
+        );
+        assert_eq!(
+            generate_publish_result(&content_hash("$é"), "$é".to_string(), &store),
+            PublishResult::Rejected("Only ASCII characters can be published! We found a non-ASCII character at Line 1, column 2: \"é\" (U+00E9)\n".to_string())
+        );
+        assert_eq!(
+            generate_publish_result(&content_hash("$$"), "$".to_string(), &store),
+            PublishResult::Rejected(format!("The hash in the address doesn't match the ASCII art! The SHA-256 hash of the ASCII art you sent is {}\n", content_hash("$")))
+        );
+        assert_eq!(store.get(&content_hash("$")).unwrap(), None);
+    }
+
+    // Tests for the gallery functions
+
+    // Verifies that add_to_gallery() only adds successful conversions, which are then shown by generate_gallery_result()
+    #[actix_web::test]
+    async fn test_add_to_gallery() {
+        let root = tempfile::tempdir().unwrap();
+        let url = format!("sqlite://{}", root.path().join("gallery.db").display());
+        let gallery = Gallery::new(&url).unwrap();
+
+        let html = HtmlTemplate::AsciiToImageResult {
+            image_result: "conversion/castle.png".to_string(),
+            image_result_2x: "conversion/castle.2x.png".to_string(),
+            svg_result: "conversion/castle.svg".to_string(),
+            notice: None,
+            warnings: vec![],
+            permalink: Some("/result/castle".to_string()),
+            attribution: Attribution {
+                author: Some("Kelsey".to_string()),
+                license: Some(License::Cc0),
+            },
+        };
+        add_to_gallery(&gallery, &html, "abc123", Some("Castle")).await;
+        let error = HtmlTemplate::Error {
+            error_message: "This is a test error message.",
+            try_again_link: "/try_again",
+        };
+        add_to_gallery(&gallery, &error, "def456", None).await;
+
+        if let HtmlTemplate::Gallery {
+            entries,
+            previous_page,
+            next_page,
+            ..
+        } = generate_gallery_result(&gallery, GallerySearch::default(), 1).await
+        {
+            assert_eq!(entries.len(), 1);
+            assert_eq!(entries[0].kind, ConversionKind::AsciiToImage);
+            assert_eq!(entries[0].input_hash, "abc123");
+            assert_eq!(entries[0].output_path, "conversion/castle.png");
+            assert_eq!(entries[0].title, Some("Castle".to_string()));
+            assert_eq!(entries[0].author, Some("Kelsey".to_string()));
+            assert_eq!(entries[0].license, Some(License::Cc0));
+            assert_eq!(previous_page, None);
+            assert_eq!(next_page, None);
+        } else {
+            panic!("Expected HtmlTemplate::Gallery.");
+        }
+    }
+
+    // Verifies that the generate_gallery_detail_result() function shows the ASCII art of a past conversion,
+    // and returns None when there is no conversion with the id
+    #[actix_web::test]
+    async fn test_generate_gallery_detail_result() {
+        let root = tempfile::tempdir().unwrap();
+        let url = format!("sqlite://{}", root.path().join("gallery.db").display());
+        let gallery = Gallery::new(&url).unwrap();
+        let results = test_result_store();
+        let mut record = ResultRecord::new(ConversionKind::ImageToAscii);
+        let text_file_name = create_image_file(&results, &mut record, b"><(((('>", "txt").unwrap();
+        let output_path = file_route(&text_file_name);
+        let id = gallery
+            .add(
+                ConversionKind::ImageToAscii,
+                "abc123",
+                &output_path,
+                None,
+                &Attribution::default(),
+                None,
+            )
+            .await
+            .unwrap();
+
+        let result = generate_gallery_detail_result(&gallery, &results, id).await;
+
+        if let Some(HtmlTemplate::GalleryDetail {
+            entry,
+            ascii_result,
+        }) = result
+        {
+            assert_eq!(entry.id, id);
+            assert_eq!(entry.output_path, output_path);
+            assert_eq!(ascii_result, Some("><(((('>".to_string()));
+        } else {
+            panic!("Expected HtmlTemplate::GalleryDetail.");
+        }
+
+        assert_eq!(
+            generate_gallery_detail_result(&gallery, &results, id + 1).await,
+            None
+        );
+    }
+
+    // Verifies that conversions can be reported, that hidden conversions can't be seen or reported,
+    // and that deleting a conversion removes its result
+    #[actix_web::test]
+    async fn test_report_and_moderate() {
+        let root = tempfile::tempdir().unwrap();
+        let url = format!("sqlite://{}", root.path().join("gallery.db").display());
+        let gallery = Gallery::new(&url).unwrap();
+        let results = test_result_store();
+        let mut record = ResultRecord::new(ConversionKind::ImageToAscii);
+        let text_file_name = create_image_file(&results, &mut record, b"><(((('>", "txt").unwrap();
+        record.save(&results).unwrap();
+        let id = gallery
+            .add(
+                ConversionKind::ImageToAscii,
+                "abc123",
+                &file_route(&text_file_name),
+                None,
+                &Attribution::default(),
+                None,
+            )
+            .await
+            .unwrap();
+        let report = || ReportFormParams {
+            reason: ReportReason::Other,
+            details: Some("  \u{0} ".to_string()),
+        };
+
+        assert_eq!(
+            generate_report_result(&gallery, id, report()).await,
+            Some(HtmlTemplate::ReportReceived { entry_id: id })
+        );
+        assert_eq!(
+            generate_report_result(&gallery, id + 1, report()).await,
+            None
+        );
+
+        let flagged = gallery.flagged().await.unwrap();
+
+        assert_eq!(flagged.len(), 1);
+        assert_eq!(flagged[0].reports[0].details, None);
+
+        assert_eq!(
+            generate_moderation_result(&gallery, &results, id, ModerationAction::Hide).await,
+            ModerationResult::Moderated(format!("Conversion {} is hidden from the gallery.\n", id))
+        );
+        assert_eq!(
+            generate_gallery_detail_result(&gallery, &results, id).await,
+            None
+        );
+        assert_eq!(generate_report_result(&gallery, id, report()).await, None);
+
+        assert_eq!(
+            generate_moderation_result(&gallery, &results, id, ModerationAction::Delete).await,
+            ModerationResult::Moderated(format!("Conversion {} and its result are deleted.\n", id))
+        );
+        assert_eq!(results.names().unwrap(), Vec::<String>::new());
+        assert_eq!(
+            generate_moderation_result(&gallery, &results, id, ModerationAction::Dismiss).await,
+            ModerationResult::NotFound
+        );
+    }
+
+    // Tests for generate_saved_result() function
+
+    // Verifies that the generate_saved_result() function shows a saved result by the id of its record,
+    // and returns None when there is no result with the id
+    #[test]
+    fn test_generate_saved_result() {
+        let root = tempfile::tempdir().unwrap();
+        let results = DirectoryResultStore::new(root.path()).unwrap();
+        let mut record = ResultRecord::new(ConversionKind::ImageToAscii);
+        create_image_file(&results, &mut record, b"><(((('>", "txt").unwrap();
+        record.save(&results).unwrap();
+
+        let result = generate_saved_result(&results, &record.id);
+        let expected_result = Some(HtmlTemplate::SavedResult {
+            permalink: format!("/result/{}", record.id),
+            image_result: None,
+            image_result_2x: None,
+            svg_result: None,
+            text_result: Some(format!("/result/{}.txt", record.id)),
+            ascii_result: Some("><(((('>".to_string()),
+            bundle_link: format!("/r/{}/bundle.zip", record.id),
+            attribution: Attribution::default(),
+            usage: None,
+        });
+
+        assert_eq!(result, expected_result);
+
+        let mut record = ResultRecord::new(ConversionKind::AsciiToImage);
+        create_image_file(&results, &mut record, &[1, 2, 3], "png").unwrap();
+        create_image_file(&results, &mut record, &[4, 5, 6], HIGH_DPI_EXTENSION).unwrap();
+        create_image_file(&results, &mut record, b"<svg />", "svg").unwrap();
+        record.save(&results).unwrap();
+
+        if let Some(HtmlTemplate::SavedResult {
+            image_result,
+            image_result_2x,
+            svg_result,
+            ascii_result,
+            ..
+        }) = generate_saved_result(&results, &record.id)
+        {
+            assert_eq!(image_result, Some(format!("conversion/{}.png", record.id)));
+            assert_eq!(
+                image_result_2x,
+                Some(format!("conversion/{}.2x.png", record.id))
+            );
+            assert_eq!(svg_result, Some(format!("conversion/{}.svg", record.id)));
+            assert_eq!(ascii_result, None);
+        } else {
+            panic!("Expected HtmlTemplate::SavedResult.");
+        }
+
+        assert_eq!(
+            generate_saved_result(
+                &results,
+                &ResultRecord::new(ConversionKind::AsciiToImage).id
+            ),
+            None
+        );
+        assert_eq!(generate_saved_result(&results, "not-an-id"), None);
+    }
+
+    // Tests for generate_result_bundle() function
+
+    // Verifies that the generate_result_bundle() function packages the files of a result, draws an image for ASCII art,
+    // and returns None when there is no result with the id
+    #[test]
+    fn test_generate_result_bundle() {
+        let results = test_result_store();
+        let converter = Converter::new().unwrap();
+        let file_names = |archive: Vec<u8>| {
+            let archive = zip::ZipArchive::new(Cursor::new(archive)).unwrap();
+            let mut names: Vec<String> = archive.file_names().map(str::to_string).collect();
+            names.sort();
+            names
+        };
+
+        let mut record = ResultRecord::new(ConversionKind::ImageToAscii);
+        create_image_file(&results, &mut record, b"@@..\n..@@", "txt").unwrap();
+        let manifest = Manifest::from(&ConvertOptions::default()).to_json();
+        create_image_file(
+            &results,
+            &mut record,
+            manifest.as_bytes(),
+            MANIFEST_EXTENSION,
+        )
+        .unwrap();
+        record.save(&results).unwrap();
+
+        let archive = generate_result_bundle(&results, &converter, &record.id)
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            file_names(archive.clone()),
+            ["ascii-art.png", "ascii-art.txt", "manifest.json"]
+        );
+        let mut archive = zip::ZipArchive::new(Cursor::new(archive)).unwrap();
+        let mut image = Vec::new();
+        std::io::Read::read_to_end(&mut archive.by_name("ascii-art.png").unwrap(), &mut image)
+            .unwrap();
+        assert!(image::load_from_memory(&image).is_ok());
+
+        let mut record = ResultRecord::new(ConversionKind::AsciiToImage);
+        create_image_file(&results, &mut record, &[1, 2, 3], "png").unwrap();
+        record.save(&results).unwrap();
+
+        let archive = generate_result_bundle(&results, &converter, &record.id)
+            .unwrap()
+            .unwrap();
+        assert_eq!(file_names(archive), ["ascii-art.png"]);
+
+        assert_eq!(
+            generate_result_bundle(
+                &results,
+                &converter,
+                &ResultRecord::new(ConversionKind::AsciiToImage).id
+            ),
+            None
+        );
+    }
+
+    // Tests for generate_result_text() function
+
+    // Verifies that the generate_result_text() function reads the ASCII art of a result, and returns None when there is no
+    // result with the id or the result has no ASCII art
+    #[test]
+    fn test_generate_result_text() {
+        let results = test_result_store();
+
+        let mut record = ResultRecord::new(ConversionKind::ImageToAscii);
+        create_image_file(&results, &mut record, b"@@..\n..@@", "txt").unwrap();
+        record.save(&results).unwrap();
+        assert_eq!(
+            generate_result_text(&results, &record.id),
+            Some(Ok(b"@@..\n..@@".to_vec()))
+        );
+
+        let mut record = ResultRecord::new(ConversionKind::AsciiToImage);
+        create_image_file(&results, &mut record, &[1, 2, 3], "png").unwrap();
+        record.save(&results).unwrap();
+        assert_eq!(generate_result_text(&results, &record.id), None);
+
+        assert_eq!(generate_result_text(&results, "not-a-result"), None);
+    }
 }
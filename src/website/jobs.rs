@@ -0,0 +1,514 @@
+//! Module to run conversions on a bounded pool of workers, and to convert large uploads in the background while the user
+//! watches their progress.
+//!
+//! Conversions are never run on the threads that answer requests. Every conversion is put in the [JobQueue] with
+//! [JobQueue::enqueue], which returns a [JobHandle] that is awaited for the result. At most `conversion_workers` of the
+//! [super::config::AppConfig] conversions run at once on blocking threads, and the rest wait for a free worker, so one
+//! enormous upload only takes up one worker while the other users' requests keep being answered. When
+//! `conversion_queue_limit` conversions are already waiting, [JobError::QueueFull] is returned instead of making the
+//! queue longer.
+//!
+//! Converting a large image can take long enough that a browser or proxy gives up on the request, so uploads larger than
+//! the `job_threshold` of the [super::config::AppConfig] are turned into conversion jobs. The POST /submit-image request
+//! is answered right away with the id of the job, and [run_image_job] converts the image on a blocking thread. Every line
+//! of ASCII art that is converted moves the progress of the job forward, which can be read as JSON from GET
+//! /job/{id}/status or watched on the GET /job/{id} page, which refreshes itself until the job is done and then sends the
+//! user to the saved result.
+//!
+//! Robert Peterson and Kelsey Werner 2023
+
+use super::{
+    conversion_defaults::ConversionDefaults,
+    gallery::Gallery,
+    html_template::HtmlTemplate,
+    image_form_params::ImageFormParams,
+    input_processors::{add_to_gallery, generate_image_to_ascii_result_with_progress},
+    result_store::ResultStore,
+};
+use actix_web::{rt, web};
+use ascii_art_converter::converter::service::Converter;
+use serde::Serialize;
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex, PoisonError,
+    },
+    time::{Duration, Instant},
+};
+use tokio::sync::{oneshot, Semaphore};
+use uuid::Uuid;
+
+/// How long a finished job can still be looked up, which is 1 hour.
+pub const FINISHED_JOB_TTL: Duration = Duration::from_secs(60 * 60);
+
+/// The error shown when a job fails without an error page of its own.
+const JOB_FAILED_MESSAGE: &str =
+    "It looks like we ran into an issue while converting your image! Please try it one more time.";
+
+/// The error shown when a job converted its image, but the result couldn't be stored for the page of the job to link to.
+const RESULT_NOT_STORED_MESSAGE: &str =
+    "We converted your image, but we couldn't save the result! Wait a few minutes, and try it one more time.";
+
+/// How often [JobQueue::wait_until_idle] checks whether the conversions have finished.
+const IDLE_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// The message of a background job that couldn't be queued because too many conversions were waiting.
+const QUEUE_FULL_MESSAGE: &str = "The converter is busy with a lot of other conversions right now! Wait a minute, and try it one more time.";
+
+/// Enum to store the errors that can occur while a conversion is run by the [JobQueue].
+///
+/// The different enum variants are used to identify the specific cause of an error.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum JobError {
+    /// [JobError::QueueFull] error is caused when the most conversions the queue holds are already waiting for a worker.
+    QueueFull,
+    /// [JobError::Canceled] error is caused when the conversion stopped without a result, such as when it panicked.
+    Canceled,
+}
+
+/// Struct to store the result of a conversion that was put in the [JobQueue], which is awaited with [JobHandle::wait].
+pub struct JobHandle<T> {
+    /// The receiver the worker sends the result of the conversion to.
+    receiver: oneshot::Receiver<T>,
+}
+
+impl<T> JobHandle<T> {
+    /// Function to wait until a worker has run the conversion, and return its result.
+    ///
+    /// Returns [JobError::Canceled] if the conversion stopped without a result.
+    pub async fn wait(self) -> Result<T, JobError> {
+        self.receiver.await.map_err(|_| JobError::Canceled)
+    }
+}
+
+/// Enum to store the states a conversion job goes through.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum JobState {
+    /// [JobState::Queued] is used for a job that is waiting for a thread to convert it.
+    Queued,
+    /// [JobState::Running] is used for a job whose image is being converted.
+    Running,
+    /// [JobState::Done] is used for a job whose ASCII art was saved.
+    Done,
+    /// [JobState::Failed] is used for a job whose image couldn't be converted.
+    Failed,
+}
+
+/// Struct to store the status of a conversion job, which is sent as the JSON body of GET /job/{id}/status.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct JobStatus {
+    /// The [JobState] of the job.
+    pub state: JobState,
+    /// The percent of the lines of ASCII art that were converted, from 0 to 100.
+    pub progress: u8,
+    /// [Option] stores the permalink of the saved result once the job is done, or [None] until then.
+    pub result: Option<String>,
+    /// [Option] stores the error message when the job failed, or [None] otherwise.
+    pub error: Option<String>,
+    /// The warnings about how the image was changed to convert it, which are only known once the job is done.
+    pub warnings: Vec<String>,
+}
+
+/// Struct to store a conversion job along with when it finished.
+struct Job {
+    /// The [JobStatus] of the job.
+    status: JobStatus,
+    /// [Option] stores the time the job finished, or [None] while it is still queued or running.
+    finished: Option<Instant>,
+}
+
+/// Struct to store the pool of workers that run conversions, along with the background jobs of large uploads by their id.
+///
+/// The [JobQueue] is shared by every worker of the server, so the workers are shared by every request and the status of
+/// a job can be read from any of them.
+pub struct JobQueue {
+    /// The permits of the workers, where each running conversion holds one.
+    workers: Arc<Semaphore>,
+    /// The number of workers, which is the number of permits when no conversion is running.
+    worker_count: usize,
+    /// The number of conversions that are waiting for a worker.
+    waiting: Arc<AtomicUsize>,
+    /// The most conversions that can wait for a worker.
+    queue_limit: usize,
+    /// The size above which an uploaded image is converted as a job, in bytes.
+    threshold: usize,
+    /// The jobs by their id.
+    jobs: Mutex<HashMap<String, Job>>,
+}
+
+impl JobQueue {
+    /// Function to create an empty [JobQueue] that runs `workers` conversions at once, lets `queue_limit` more wait for a
+    /// worker, and converts uploads larger than `threshold` bytes as background jobs.
+    ///
+    /// There is always at least one worker.
+    pub fn new(workers: usize, queue_limit: usize, threshold: usize) -> JobQueue {
+        JobQueue {
+            workers: Arc::new(Semaphore::new(workers.max(1))),
+            worker_count: workers.max(1),
+            waiting: Arc::new(AtomicUsize::new(0)),
+            queue_limit,
+            threshold,
+            jobs: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Function to put a conversion in the queue, which is run on a blocking thread once a worker is free.
+    ///
+    /// Returns a [JobHandle] to await the result of the conversion, or [JobError::QueueFull] when too many conversions
+    /// are already waiting. A conversion whose [JobHandle] is dropped before a worker is free is never run.
+    pub fn enqueue<F, T>(&self, work: F) -> Result<JobHandle<T>, JobError>
+    where
+        F: FnOnce() -> T + Send + 'static,
+        T: Send + 'static,
+    {
+        if self.waiting.fetch_add(1, Ordering::SeqCst) >= self.queue_limit {
+            self.waiting.fetch_sub(1, Ordering::SeqCst);
+            return Err(JobError::QueueFull);
+        }
+
+        let workers = Arc::clone(&self.workers);
+        let waiting = Arc::clone(&self.waiting);
+        let (sender, receiver) = oneshot::channel();
+        rt::spawn(async move {
+            let permit = workers.acquire_owned().await;
+            waiting.fetch_sub(1, Ordering::SeqCst);
+            // the request that wanted the result may have gone away while the conversion waited
+            if permit.is_err() || sender.is_closed() {
+                return;
+            }
+            if let Ok(result) = web::block(work).await {
+                let _ = sender.send(result);
+            }
+        });
+
+        Ok(JobHandle { receiver })
+    }
+
+    /// Function to put a conversion in the queue and wait for its result.
+    ///
+    /// See [JobQueue::enqueue] and [JobHandle::wait] for the errors that can be returned.
+    pub async fn run<F, T>(&self, work: F) -> Result<T, JobError>
+    where
+        F: FnOnce() -> T + Send + 'static,
+        T: Send + 'static,
+    {
+        self.enqueue(work)?.wait().await
+    }
+
+    /// Function to check whether no conversion is running or waiting for a worker.
+    pub fn is_idle(&self) -> bool {
+        self.waiting.load(Ordering::SeqCst) == 0
+            && self.workers.available_permits() == self.worker_count
+    }
+
+    /// Function to wait until every conversion in the queue has finished, such as when the server shuts down and the
+    /// background jobs should still be saved.
+    pub async fn wait_until_idle(&self) {
+        while !self.is_idle() {
+            rt::time::sleep(IDLE_POLL_INTERVAL).await;
+        }
+    }
+
+    /// Function to check whether an upload of `size` bytes is large enough to be converted as a job.
+    pub fn is_large_upload(&self, size: usize) -> bool {
+        size > self.threshold
+    }
+
+    /// Function to add a queued job at the time `now` and return its id.
+    ///
+    /// Jobs that finished more than [FINISHED_JOB_TTL] before `now` are forgotten.
+    pub fn create(&self, now: Instant) -> String {
+        let mut jobs = self.jobs.lock().unwrap_or_else(PoisonError::into_inner);
+        jobs.retain(|_, job| {
+            job.finished
+                .is_none_or(|finished| now.duration_since(finished) <= FINISHED_JOB_TTL)
+        });
+
+        let id = Uuid::new_v4().to_string();
+        jobs.insert(
+            id.clone(),
+            Job {
+                status: JobStatus {
+                    state: JobState::Queued,
+                    progress: 0,
+                    result: None,
+                    error: None,
+                    warnings: Vec::new(),
+                },
+                finished: None,
+            },
+        );
+        id
+    }
+
+    /// Function to mark the job `id` as running and set its progress from the `done` lines out of `total`.
+    ///
+    /// A job that already finished is left as it was.
+    pub fn report_progress(&self, id: &str, done: u32, total: u32) {
+        self.update(id, |job| {
+            if job.finished.is_none() {
+                job.status.state = JobState::Running;
+                job.status.progress = progress_percent(done, total);
+            }
+        });
+    }
+
+    /// Function to finish the job `id` at the time `now` with the permalink and warnings of its result or the error it failed with.
+    pub fn finish(&self, id: &str, outcome: Result<(String, Vec<String>), String>, now: Instant) {
+        self.update(id, |job| {
+            match outcome {
+                Ok((permalink, warnings)) => {
+                    job.status.state = JobState::Done;
+                    job.status.progress = 100;
+                    job.status.result = Some(permalink);
+                    job.status.warnings = warnings;
+                }
+                Err(error) => {
+                    job.status.state = JobState::Failed;
+                    job.status.error = Some(error);
+                }
+            }
+            job.finished = Some(now);
+        });
+    }
+
+    /// Function to get the [JobStatus] of the job `id`, or [None] if there is no such job.
+    pub fn status(&self, id: &str) -> Option<JobStatus> {
+        let jobs = self.jobs.lock().unwrap_or_else(PoisonError::into_inner);
+        jobs.get(id).map(|job| job.status.clone())
+    }
+
+    /// Function to change the job `id` with `change`, if there is such a job.
+    fn update<F: FnOnce(&mut Job)>(&self, id: &str, change: F) {
+        let mut jobs = self.jobs.lock().unwrap_or_else(PoisonError::into_inner);
+        if let Some(job) = jobs.get_mut(id) {
+            change(job);
+        }
+    }
+}
+
+/// Function to get the percent of the `done` lines out of `total`, where a conversion without lines is 100 percent done.
+pub fn progress_percent(done: u32, total: u32) -> u8 {
+    if total == 0 {
+        return 100;
+    }
+    (u64::from(done.min(total)) * 100 / u64::from(total)) as u8
+}
+
+/// Function to get the outcome of a job from the [HtmlTemplate] its conversion made.
+///
+/// Returns the permalink and warnings of the result when the conversion worked, and otherwise the error message of the page.
+fn job_outcome(html: &HtmlTemplate) -> Result<(String, Vec<String>), String> {
+    match html {
+        HtmlTemplate::ImageToAsciiResult {
+            permalink: Some(permalink),
+            warnings,
+            ..
+        } => Ok((permalink.clone(), warnings.clone())),
+        // the page of a job can only send the user on to a result that was stored
+        HtmlTemplate::ImageToAsciiResult {
+            permalink: None, ..
+        } => Err(RESULT_NOT_STORED_MESSAGE.to_string()),
+        HtmlTemplate::Error { error_message, .. } => Err(error_message.to_string()),
+        _ => Err(JOB_FAILED_MESSAGE.to_string()),
+    }
+}
+
+/// Struct to store everything a conversion job needs to convert an image and add it to the gallery.
+pub struct ImageJob {
+    /// The id of the job in the [JobQueue].
+    pub id: String,
+    /// The form the image was uploaded with.
+    pub form: ImageFormParams,
+    /// The hash of the uploaded image, which is stored in the gallery.
+    pub input_hash: String,
+    /// [Option] stores the cleaned title of the conversion, or [None] when it has none.
+    pub title: Option<String>,
+}
+
+/// Function to convert the image of an [ImageJob] on a worker of the [JobQueue], reporting its progress to the queue.
+///
+/// The result is saved in the [ResultStore] and added to the [Gallery] just like a conversion that isn't a job, and then
+/// the job is finished with the permalink of the result or the error message the conversion failed with.
+pub async fn run_image_job(
+    job: ImageJob,
+    jobs: Arc<JobQueue>,
+    converter: Arc<Converter>,
+    results: Arc<dyn ResultStore>,
+    defaults: Arc<ConversionDefaults>,
+    gallery: Arc<Gallery>,
+) {
+    let ImageJob {
+        id,
+        form,
+        input_hash,
+        title,
+    } = job;
+
+    let job_id = id.clone();
+    let job_queue = Arc::clone(&jobs);
+    let html = jobs
+        .run(move || {
+            job_queue.report_progress(&job_id, 0, 1);
+            generate_image_to_ascii_result_with_progress(
+                form,
+                converter.as_ref(),
+                results.as_ref(),
+                defaults.as_ref(),
+                &|done, total| job_queue.report_progress(&job_id, done, total),
+            )
+        })
+        .await;
+
+    let outcome = match html {
+        Ok(html) => {
+            add_to_gallery(gallery.as_ref(), &html, &input_hash, title.as_deref()).await;
+            job_outcome(&html)
+        }
+        Err(JobError::QueueFull) => Err(QUEUE_FULL_MESSAGE.to_string()),
+        Err(JobError::Canceled) => Err(JOB_FAILED_MESSAGE.to_string()),
+    };
+    jobs.finish(&id, outcome, Instant::now());
+}
+
+// Tests
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Verifies that a job moves from queued to running to done, and that finished jobs are forgotten after a while
+    #[test]
+    fn test_job_queue() {
+        let jobs = JobQueue::new(1, 1, 0);
+        let start = Instant::now();
+        let id = jobs.create(start);
+
+        assert_eq!(
+            jobs.status(&id),
+            Some(JobStatus {
+                state: JobState::Queued,
+                progress: 0,
+                result: None,
+                error: None,
+                warnings: vec![],
+            })
+        );
+
+        jobs.report_progress(&id, 25, 100);
+
+        assert_eq!(jobs.status(&id).unwrap().state, JobState::Running);
+        assert_eq!(jobs.status(&id).unwrap().progress, 25);
+
+        jobs.finish(
+            &id,
+            Ok((
+                "/result/abc".to_string(),
+                vec!["image downscaled from 580px to 183 characters wide".to_string()],
+            )),
+            start,
+        );
+        jobs.report_progress(&id, 50, 100);
+
+        assert_eq!(
+            jobs.status(&id),
+            Some(JobStatus {
+                state: JobState::Done,
+                progress: 100,
+                result: Some("/result/abc".to_string()),
+                error: None,
+                warnings: vec!["image downscaled from 580px to 183 characters wide".to_string()],
+            })
+        );
+
+        let failed = jobs.create(start);
+        jobs.finish(&failed, Err("No good".to_string()), start);
+
+        assert_eq!(jobs.status(&failed).unwrap().state, JobState::Failed);
+        assert_eq!(
+            jobs.status(&failed).unwrap().error,
+            Some("No good".to_string())
+        );
+
+        let running = jobs.create(start);
+        jobs.create(start + FINISHED_JOB_TTL + Duration::from_secs(1));
+
+        assert_eq!(jobs.status(&id), None);
+        assert_eq!(jobs.status(&failed), None);
+        assert!(jobs.status(&running).is_some());
+        assert_eq!(jobs.status("missing"), None);
+        assert!(jobs.is_large_upload(1));
+        assert!(!jobs.is_large_upload(0));
+    }
+
+    // Verifies that conversions wait for a free worker, and that the queue turns conversions away once it is full
+    #[actix_web::test]
+    async fn test_enqueue() {
+        let jobs = JobQueue::new(1, 1, 0);
+        let started = Arc::new(AtomicUsize::new(0));
+        let (release, released) = std::sync::mpsc::channel::<()>();
+
+        let first_started = Arc::clone(&started);
+        let first = jobs
+            .enqueue(move || {
+                first_started.fetch_add(1, Ordering::SeqCst);
+                released.recv().unwrap();
+                "first"
+            })
+            .unwrap();
+        while started.load(Ordering::SeqCst) == 0 {
+            rt::time::sleep(Duration::from_millis(10)).await;
+        }
+
+        let second_started = Arc::clone(&started);
+        let second = jobs
+            .enqueue(move || {
+                second_started.fetch_add(1, Ordering::SeqCst);
+                "second"
+            })
+            .unwrap();
+
+        assert_eq!(jobs.enqueue(|| "third").err(), Some(JobError::QueueFull));
+
+        rt::time::sleep(Duration::from_millis(50)).await;
+
+        assert_eq!(started.load(Ordering::SeqCst), 1);
+
+        release.send(()).unwrap();
+
+        assert_eq!(first.wait().await, Ok("first"));
+        assert_eq!(second.wait().await, Ok("second"));
+        assert_eq!(jobs.run(|| "fourth").await, Ok("fourth"));
+    }
+
+    // Verifies that the queue is idle only once every conversion in it has finished
+    #[actix_web::test]
+    async fn test_wait_until_idle() {
+        let jobs = JobQueue::new(2, 1, 0);
+        assert!(jobs.is_idle());
+
+        let (release, released) = std::sync::mpsc::channel::<()>();
+        let handle = jobs.enqueue(move || released.recv().unwrap()).unwrap();
+        assert!(!jobs.is_idle());
+
+        release.send(()).unwrap();
+        rt::time::timeout(Duration::from_secs(5), jobs.wait_until_idle())
+            .await
+            .unwrap();
+
+        assert!(jobs.is_idle());
+        assert_eq!(handle.wait().await, Ok(()));
+    }
+
+    // Verifies that progress is counted in whole percents of the lines that were converted
+    #[test]
+    fn test_progress_percent() {
+        assert_eq!(progress_percent(0, 200), 0);
+        assert_eq!(progress_percent(1, 3), 33);
+        assert_eq!(progress_percent(200, 200), 100);
+        assert_eq!(progress_percent(300, 200), 100);
+        assert_eq!(progress_percent(0, 0), 100);
+    }
+}
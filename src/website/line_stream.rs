@@ -0,0 +1,251 @@
+//! Module to stream the lines of ASCII art as server-sent events while an image is converted.
+//!
+//! Large images take a while to convert, so instead of waiting for the whole result, the converter writes each line to a
+//! [LineWriter] as soon as it is ready, which hands it over to the [LineStream] that sends it as a `line` event. A final
+//! `end` event is sent once every line has been sent, or an `error` event if the conversion failed part of the way.
+//! The conversion runs on a worker of the [JobQueue], and waits for the client when it has read too few of the lines.
+//! The format of the events is described here: <https://html.spec.whatwg.org/multipage/server-sent-events.html>
+//!
+//! Robert Peterson and Kelsey Werner 2023
+
+use super::jobs::{JobError, JobHandle, JobQueue};
+use actix_web::{
+    body::{BodySize, MessageBody},
+    web::Bytes,
+};
+use std::{
+    convert::Infallible,
+    io,
+    pin::Pin,
+    task::{Context, Poll},
+};
+use tokio::sync::mpsc;
+
+/// The number of lines that are kept while they wait to be sent, after which the conversion waits for the client.
+const LINE_BUFFER: usize = 64;
+
+/// The message sent when the conversion stopped without saying why, such as when it panicked.
+const STOPPED_MESSAGE: &str =
+    "It looks like we ran into an issue with converting your image! Try it one more time.";
+
+/// Enum to store what the conversion hands over to the [LineStream].
+#[derive(Debug, PartialEq)]
+enum LineEvent {
+    /// [LineEvent::Line] is used for a line of ASCII art, without its line break.
+    Line(String),
+    /// [LineEvent::End] is used once every line was converted.
+    End,
+    /// [LineEvent::Error] is used when the conversion failed, and stores a plain text message that explains why.
+    Error(&'static str),
+}
+
+/// Struct to store the writer the converter writes ASCII art to, which sends each whole line to the [LineStream].
+pub struct LineWriter {
+    /// The sender of the lines to the [LineStream].
+    sender: mpsc::Sender<LineEvent>,
+    /// The text written after the last line break, which is sent once the rest of its line is written.
+    partial: Vec<u8>,
+}
+
+impl LineWriter {
+    /// Function to send a line, waiting while the [LineStream] has too many lines to send.
+    ///
+    /// Returns a [io::ErrorKind::BrokenPipe] error when the client went away, so the conversion stops.
+    fn send(&self, event: LineEvent) -> io::Result<()> {
+        self.sender
+            .blocking_send(event)
+            .map_err(|_| io::Error::from(io::ErrorKind::BrokenPipe))
+    }
+}
+
+impl io::Write for LineWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.partial.extend_from_slice(buf);
+        while let Some(end) = self.partial.iter().position(|byte| *byte == b'\n') {
+            let line: Vec<u8> = self.partial.drain(..=end).collect();
+            self.send(LineEvent::Line(
+                String::from_utf8_lossy(&line[..end]).into_owned(),
+            ))?;
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Function to put a conversion that writes lines of ASCII art in the [JobQueue], and stream the lines as it goes.
+///
+/// `convert` writes the lines to the [LineWriter] it is given, and returns a plain text message that explains why when
+/// it fails. A last line without a line break is sent too. Returns [JobError::QueueFull] when too many conversions are
+/// already waiting. The conversion is never run if the [LineStream] is dropped before a worker is free.
+pub fn stream_lines<F>(jobs: &JobQueue, convert: F) -> Result<LineStream, JobError>
+where
+    F: FnOnce(&mut LineWriter) -> Result<(), &'static str> + Send + 'static,
+{
+    let (sender, receiver) = mpsc::channel(LINE_BUFFER);
+    let job = jobs.enqueue(move || {
+        let mut writer = LineWriter {
+            sender,
+            partial: Vec::new(),
+        };
+        let last_event = match convert(&mut writer) {
+            Ok(()) if writer.partial.is_empty() => Ok(LineEvent::End),
+            Ok(()) => {
+                let line = String::from_utf8_lossy(&writer.partial).into_owned();
+                writer.send(LineEvent::Line(line)).map(|_| LineEvent::End)
+            }
+            Err(error_message) => Ok(LineEvent::Error(error_message)),
+        };
+        // the client may have gone away, and then there is no one to tell
+        if let Ok(last_event) = last_event {
+            let _ = writer.send(last_event);
+        }
+    })?;
+
+    Ok(LineStream {
+        receiver,
+        next_id: 0,
+        finished: false,
+        _job: job,
+    })
+}
+
+/// Struct to stream the lines of ASCII art as the body of a `text/event-stream` response.
+pub struct LineStream {
+    /// The receiver of the lines the conversion writes.
+    receiver: mpsc::Receiver<LineEvent>,
+    /// The id of the next line event.
+    next_id: u64,
+    /// Whether the last event has been sent.
+    finished: bool,
+    /// The handle of the conversion, which is kept so the conversion isn't dropped from the queue while it waits.
+    _job: JobHandle<()>,
+}
+
+/// Function to format a line of ASCII art as a `line` event.
+///
+/// The space after `data:` is left out by the client, so the spaces at the start of the line are kept.
+fn line_event(id: u64, line: &str) -> String {
+    format!("id: {}\nevent: line\ndata: {}\n\n", id, line)
+}
+
+impl MessageBody for LineStream {
+    type Error = Infallible;
+
+    /// The size of the stream is not known ahead of time.
+    fn size(&self) -> BodySize {
+        BodySize::Stream
+    }
+
+    /// Function to send the next line once the conversion has written it.
+    fn poll_next(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Bytes, Self::Error>>> {
+        let this = self.get_mut();
+
+        if this.finished {
+            return Poll::Ready(None);
+        }
+
+        let event = match this.receiver.poll_recv(cx) {
+            Poll::Pending => return Poll::Pending,
+            Poll::Ready(Some(LineEvent::Line(line))) => {
+                this.next_id += 1;
+                line_event(this.next_id - 1, &line)
+            }
+            Poll::Ready(Some(LineEvent::End)) => {
+                this.finished = true;
+                "event: end\ndata: \n\n".to_string()
+            }
+            Poll::Ready(Some(LineEvent::Error(error_message))) => {
+                this.finished = true;
+                format!("event: error\ndata: {}\n\n", error_message)
+            }
+            Poll::Ready(None) => {
+                this.finished = true;
+                format!("event: error\ndata: {}\n\n", STOPPED_MESSAGE)
+            }
+        };
+
+        Poll::Ready(Some(Ok(Bytes::from(event))))
+    }
+}
+
+// Tests
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::body::to_bytes;
+    use std::io::Write;
+
+    // Verifies that line_event() keeps the spaces at the start of the line
+    #[test]
+    fn test_line_event() {
+        assert_eq!(line_event(3, "  $$"), "id: 3\nevent: line\ndata:   $$\n\n");
+    }
+
+    // Verifies that each line written is sent as its own event, however the writes split the lines, followed by an end event
+    #[actix_web::test]
+    async fn test_stream_lines() {
+        let jobs = JobQueue::new(1, 1, usize::MAX);
+        let line_stream = stream_lines(&jobs, |writer| {
+            writer.write_all(b"$$\n  ").unwrap();
+            writer.write_all(b"..\n@").unwrap();
+            Ok(())
+        })
+        .unwrap();
+        let body = to_bytes(line_stream).await.unwrap();
+
+        assert_eq!(
+            body,
+            "id: 0\nevent: line\ndata: $$\n\n\
+             id: 1\nevent: line\ndata:   ..\n\n\
+             id: 2\nevent: line\ndata: @\n\n\
+             event: end\ndata: \n\n"
+        );
+    }
+
+    // Verifies that a conversion that fails part of the way ends the stream with an error event after the lines it wrote
+    #[actix_web::test]
+    async fn test_stream_lines_error() {
+        let jobs = JobQueue::new(1, 1, usize::MAX);
+        let line_stream = stream_lines(&jobs, |writer| {
+            writer.write_all(b"$$\n").unwrap();
+            Err("It broke!")
+        })
+        .unwrap();
+        let body = to_bytes(line_stream).await.unwrap();
+
+        assert_eq!(
+            body,
+            "id: 0\nevent: line\ndata: $$\n\nevent: error\ndata: It broke!\n\n"
+        );
+    }
+
+    // Verifies that the conversion stops writing once the stream is dropped, such as when the client went away
+    #[actix_web::test]
+    async fn test_stream_lines_dropped() {
+        let jobs = JobQueue::new(1, 1, usize::MAX);
+        let (sender, receiver) = tokio::sync::oneshot::channel();
+        let line_stream = stream_lines(&jobs, move |writer| {
+            let written = (0..LINE_BUFFER * 4).try_for_each(|_| writer.write_all(b"$\n"));
+            let _ = sender.send(written.map_err(|error| error.kind()));
+            Ok(())
+        })
+        .unwrap();
+        let mut line_stream = Box::pin(line_stream);
+
+        // the first line is read so the conversion is running before the stream is dropped
+        std::future::poll_fn(|cx| line_stream.as_mut().poll_next(cx))
+            .await
+            .unwrap()
+            .unwrap();
+        drop(line_stream);
+
+        assert_eq!(receiver.await.unwrap(), Err(io::ErrorKind::BrokenPipe));
+    }
+}
@@ -0,0 +1,286 @@
+//! Module to keep statistics of the conversions the website makes, so operators can see how it is used.
+//!
+//! Every conversion that reaches the [Converter](ascii_art_converter::converter::service::Converter) is counted with
+//! [record_conversion], along with the name of the [ConvertError] it failed with, if any. Conversions are made on
+//! blocking threads, so they are only kept in memory at first and written to the `conversion_stats` table of the
+//! gallery database by [MetricsStore::flush], which [run_metrics_flush] does every few seconds. The totals and the
+//! errors by kind are read back with [MetricsStore::summary] for the dashboard at GET /admin, and they are kept between
+//! restarts of the server.
+//!
+//! Robert Peterson and Kelsey Werner 2023
+
+use super::gallery::ConversionKind;
+use actix_web::rt::time::interval;
+use ascii_art_converter::converter::ConvertError;
+use serde::Serialize;
+use sqlx::{
+    sqlite::{SqliteConnectOptions, SqlitePool, SqlitePoolOptions},
+    Executor,
+};
+use std::{
+    str::FromStr,
+    sync::{Mutex, MutexGuard, PoisonError},
+    time::Duration,
+};
+
+/// How often the statistics kept in memory are written to the database.
+pub const FLUSH_INTERVAL: Duration = Duration::from_secs(10);
+
+/// The most statistics kept in memory while they can't be written, after which new conversions aren't counted.
+const MAX_PENDING_STATS: usize = 100_000;
+
+/// The SQL that creates the table of statistics when it doesn't exist yet.
+const STATS_SCHEMA: &str = "CREATE TABLE IF NOT EXISTS conversion_stats (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    kind TEXT NOT NULL,
+    error TEXT,
+    created_at TEXT NOT NULL DEFAULT (datetime('now'))
+)";
+
+/// The statistics of the conversions that haven't been written to the database yet.
+static PENDING: Mutex<Vec<ConversionStat>> = Mutex::new(Vec::new());
+
+/// Struct to store the statistics of a single conversion.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConversionStat {
+    /// The direction of the conversion.
+    pub kind: ConversionKind,
+    /// [Option] stores the name of the [ConvertError] the conversion failed with, or [None] if it succeeded.
+    pub error: Option<&'static str>,
+}
+
+/// Struct to store how many conversions failed with one kind of [ConvertError].
+#[derive(Debug, Clone, PartialEq, Serialize, sqlx::FromRow)]
+pub struct ErrorCount {
+    /// The name of the [ConvertError], such as "TooLarge".
+    pub error: String,
+    /// The number of conversions that failed with it.
+    pub count: i64,
+}
+
+/// Struct to store the totals of every conversion that was counted.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct ConversionSummary {
+    /// The number of conversions, whether they succeeded or not.
+    pub conversions: i64,
+    /// The number of conversions of ASCII art into images.
+    pub ascii_to_image: i64,
+    /// The number of conversions of images into ASCII art.
+    pub image_to_ascii: i64,
+    /// The number of conversions that failed.
+    pub failures: i64,
+    /// The failed conversions by the kind of [ConvertError], the most common first.
+    pub errors: Vec<ErrorCount>,
+}
+
+/// Function to count a conversion of `kind` by its `result`, which is returned as it is.
+///
+/// The conversion is kept in memory until it is written to the database by [MetricsStore::flush].
+pub fn record_conversion<T>(
+    kind: ConversionKind,
+    result: Result<T, ConvertError>,
+) -> Result<T, ConvertError> {
+    let mut pending = pending();
+    if pending.len() < MAX_PENDING_STATS {
+        pending.push(ConversionStat {
+            kind,
+            error: result.as_ref().err().map(ConvertError::name),
+        });
+    }
+    result
+}
+
+/// Function to lock the statistics that haven't been written yet.
+///
+/// A thread that panicked while it held the lock can only have left whole statistics behind, so the lock is used anyway.
+fn pending() -> MutexGuard<'static, Vec<ConversionStat>> {
+    PENDING.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Struct to store the statistics of conversions in a SQLite database.
+///
+/// The [MetricsStore] can be cloned cheaply, since every clone shares the same pool of connections.
+#[derive(Clone)]
+pub struct MetricsStore {
+    /// The pool of connections to the database.
+    pool: SqlitePool,
+}
+
+impl MetricsStore {
+    /// Function to open the statistics stored in the database at `url`, which is created if it doesn't exist yet.
+    ///
+    /// The connections are only made once the store is used, and the table is created on each new connection if it doesn't
+    /// exist yet. Returns an error if the url is not a SQLite url.
+    pub fn new(url: &str) -> Result<MetricsStore, sqlx::Error> {
+        let options = SqliteConnectOptions::from_str(url)?.create_if_missing(true);
+        let pool = SqlitePoolOptions::new()
+            .after_connect(|connection, _| {
+                Box::pin(async move {
+                    connection.execute(STATS_SCHEMA).await?;
+                    Ok(())
+                })
+            })
+            .connect_lazy_with(options);
+
+        Ok(MetricsStore { pool })
+    }
+
+    /// Function to add the statistics of conversions, which are all added or none are.
+    pub async fn add(&self, stats: &[ConversionStat]) -> Result<(), sqlx::Error> {
+        let mut transaction = self.pool.begin().await?;
+        for stat in stats {
+            sqlx::query("INSERT INTO conversion_stats (kind, error) VALUES (?, ?)")
+                .bind(stat.kind)
+                .bind(stat.error)
+                .execute(&mut *transaction)
+                .await?;
+        }
+        transaction.commit().await
+    }
+
+    /// Function to write the statistics kept in memory by [record_conversion] to the database.
+    ///
+    /// Returns the number of conversions that were written. When they can't be written, they are kept in memory to be
+    /// written the next time.
+    pub async fn flush(&self) -> Result<usize, sqlx::Error> {
+        let stats = std::mem::take(&mut *pending());
+        if stats.is_empty() {
+            return Ok(0);
+        }
+
+        match self.add(&stats).await {
+            Ok(()) => Ok(stats.len()),
+            Err(error) => {
+                let mut pending = pending();
+                let newer = std::mem::replace(&mut *pending, stats);
+                pending.extend(newer);
+                Err(error)
+            }
+        }
+    }
+
+    /// Function to get the totals of every conversion in the database.
+    pub async fn summary(&self) -> Result<ConversionSummary, sqlx::Error> {
+        let (conversions, ascii_to_image, image_to_ascii, failures): (i64, i64, i64, i64) =
+            sqlx::query_as(
+                "SELECT COUNT(*),
+                COALESCE(SUM(kind = 'ascii-to-image'), 0),
+                COALESCE(SUM(kind = 'image-to-ascii'), 0),
+                COALESCE(SUM(error IS NOT NULL), 0)
+                FROM conversion_stats",
+            )
+            .fetch_one(&self.pool)
+            .await?;
+        let errors = sqlx::query_as(
+            "SELECT error, COUNT(*) AS count FROM conversion_stats
+            WHERE error IS NOT NULL
+            GROUP BY error
+            ORDER BY count DESC, error",
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(ConversionSummary {
+            conversions,
+            ascii_to_image,
+            image_to_ascii,
+            failures,
+            errors,
+        })
+    }
+}
+
+/// Function to write the statistics kept in memory to the database every `period`, for as long as the server runs.
+///
+/// Statistics that can't be written are logged and tried again at the next interval.
+pub async fn run_metrics_flush(store: MetricsStore, period: Duration) {
+    let mut ticks = interval(period);
+
+    loop {
+        ticks.tick().await;
+        if let Err(error) = store.flush().await {
+            log::warn!("Writing the statistics of conversions failed: {}", error);
+        }
+    }
+}
+
+// Tests
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Opens a metrics store in a new database in a temporary directory
+    fn temporary_store() -> (tempfile::TempDir, MetricsStore) {
+        let directory = tempfile::tempdir().unwrap();
+        let url = format!("sqlite://{}", directory.path().join("metrics.db").display());
+        let store = MetricsStore::new(&url).unwrap();
+        (directory, store)
+    }
+
+    // Verifies that record_conversion() returns the result it counts, and counts it by the name of its error
+    #[actix_web::test]
+    async fn test_record_conversion() {
+        let (_directory, store) = temporary_store();
+
+        assert_eq!(
+            record_conversion(ConversionKind::AsciiToImage, Ok::<_, ConvertError>(1)),
+            Ok(1)
+        );
+        assert_eq!(
+            record_conversion::<()>(ConversionKind::ImageToAscii, Err(ConvertError::InvalidCrop)),
+            Err(ConvertError::InvalidCrop)
+        );
+
+        // other tests may convert at the same time, so there are at least the two conversions counted here
+        assert!(store.flush().await.unwrap() >= 2);
+
+        let summary = store.summary().await.unwrap();
+
+        assert!(summary.conversions >= 2);
+        assert!(summary
+            .errors
+            .iter()
+            .any(|count| count.error == "InvalidCrop"));
+    }
+
+    // Verifies that summary() totals the conversions by kind, and the failures by error with the most common first
+    #[actix_web::test]
+    async fn test_summary() {
+        let (_directory, store) = temporary_store();
+
+        assert_eq!(store.summary().await.unwrap(), ConversionSummary::default());
+
+        let stat = |kind, error| ConversionStat { kind, error };
+        store
+            .add(&[
+                stat(ConversionKind::AsciiToImage, None),
+                stat(ConversionKind::AsciiToImage, Some("EmptyInput")),
+                stat(ConversionKind::ImageToAscii, Some("TooLarge")),
+                stat(ConversionKind::ImageToAscii, Some("TooLarge")),
+                stat(ConversionKind::ImageToAscii, None),
+            ])
+            .await
+            .unwrap();
+
+        assert_eq!(
+            store.summary().await.unwrap(),
+            ConversionSummary {
+                conversions: 5,
+                ascii_to_image: 2,
+                image_to_ascii: 3,
+                failures: 3,
+                errors: vec![
+                    ErrorCount {
+                        error: "TooLarge".to_string(),
+                        count: 2
+                    },
+                    ErrorCount {
+                        error: "EmptyInput".to_string(),
+                        count: 1
+                    },
+                ],
+            }
+        );
+    }
+}
@@ -0,0 +1,137 @@
+//! Module to store the reports of conversions in the gallery and the actions operators take on them.
+//!
+//! Anyone can report a conversion from its gallery page with the POST /r/{id}/report form, picking a [ReportReason] and
+//! adding some details. Conversions with reports are listed in the review queue of the operators at GET
+//! /api/operator/reports, where each [FlaggedEntry] has the conversion and its reports. An operator then takes a
+//! [ModerationAction] on the conversion with POST /api/operator/reports/{id}, which hides it from the gallery, deletes it
+//! along with its result, or dismisses its reports.
+//!
+//! Robert Peterson and Kelsey Werner 2023
+
+use super::gallery::GalleryEntry;
+use serde::{Deserialize, Serialize};
+
+/// The longest details a report can be given, in characters.
+pub const MAX_REPORT_DETAILS_LENGTH: usize = 500;
+
+/// Enum to store the reasons a conversion can be reported for.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, sqlx::Type)]
+#[serde(rename_all = "kebab-case")]
+#[sqlx(rename_all = "kebab-case")]
+pub enum ReportReason {
+    /// [ReportReason::Spam] is used for conversions that advertise something or flood the gallery.
+    Spam,
+    /// [ReportReason::Offensive] is used for conversions that are hateful, violent or sexual.
+    Offensive,
+    /// [ReportReason::Copyright] is used for conversions of art the submitter has no right to share.
+    Copyright,
+    /// [ReportReason::Other] is used for any other reason, which should be explained in the details.
+    Other,
+}
+
+/// Struct to store the data from the form to report a conversion.
+///
+/// Actix Web populates [ReportFormParams] with the form data of a POST /r/{id}/report request.
+#[derive(Deserialize)]
+pub struct ReportFormParams {
+    /// The [ReportReason] picked on the form.
+    pub reason: ReportReason,
+    /// [Option] stores the details the user wrote about the report or [None] if the field was not sent.
+    pub details: Option<String>,
+}
+
+/// Struct to store a report of a conversion.
+#[derive(Debug, Clone, PartialEq, Serialize, sqlx::FromRow)]
+pub struct Report {
+    /// The id of the report.
+    pub id: i64,
+    /// The id of the [GalleryEntry] that was reported.
+    pub conversion_id: i64,
+    /// The [ReportReason] the conversion was reported for.
+    pub reason: ReportReason,
+    /// [Option] stores the details the user wrote about the report or [None] if no details were given.
+    pub details: Option<String>,
+    /// The time the report was made in UTC, such as "2023-06-09 04:58:50".
+    pub created_at: String,
+}
+
+/// Struct to store a conversion in the review queue along with its reports.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FlaggedEntry {
+    /// The conversion that was reported.
+    pub entry: GalleryEntry,
+    /// The reports of the conversion, oldest first.
+    pub reports: Vec<Report>,
+}
+
+/// Enum to store the actions an operator can take on a reported conversion.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ModerationAction {
+    /// [ModerationAction::Hide] is used to take the conversion out of the gallery while keeping it and its reports.
+    Hide,
+    /// [ModerationAction::Delete] is used to remove the conversion, its reports and its result for good.
+    Delete,
+    /// [ModerationAction::Dismiss] is used to remove the reports of a conversion that was reported by mistake.
+    Dismiss,
+}
+
+/// Struct to store the JSON body of a POST /api/operator/reports/{id} request, such as `{"action": "hide"}`.
+#[derive(Deserialize)]
+pub struct ModerationRequest {
+    /// The [ModerationAction] to take on the conversion.
+    pub action: ModerationAction,
+}
+
+/// Function to clean up the details a user wrote about a report.
+///
+/// Returns [None] when the details are blank, and otherwise the details without control characters other than line
+/// breaks or surrounding whitespace and cut to [MAX_REPORT_DETAILS_LENGTH] characters.
+pub fn clean_details(details: &str) -> Option<String> {
+    let details: String = details
+        .chars()
+        .filter(|c| *c == '\n' || !c.is_control())
+        .collect();
+    let details = details.trim();
+    if details.is_empty() {
+        None
+    } else {
+        Some(details.chars().take(MAX_REPORT_DETAILS_LENGTH).collect())
+    }
+}
+
+// Tests
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Verifies that clean_details() removes control characters but keeps line breaks, drops blank details and cuts long details
+    #[test]
+    fn test_clean_details() {
+        assert_eq!(
+            clean_details(" Copied from\r\nmy\u{0} site "),
+            Some("Copied from\nmy site".to_string())
+        );
+        assert_eq!(clean_details(" \n\t "), None);
+        assert_eq!(
+            clean_details(&"é".repeat(MAX_REPORT_DETAILS_LENGTH + 1)),
+            Some("é".repeat(MAX_REPORT_DETAILS_LENGTH))
+        );
+    }
+
+    // Verifies that the actions and reasons are read from the names operators and forms send
+    #[test]
+    fn test_names() {
+        let request: ModerationRequest = serde_json::from_str(r#"{"action": "dismiss"}"#).unwrap();
+
+        assert_eq!(request.action, ModerationAction::Dismiss);
+        assert!(serde_json::from_str::<ModerationRequest>(r#"{"action": "ban"}"#).is_err());
+
+        let params: ReportFormParams =
+            serde_urlencoded::from_str("reason=copyright&details=mine").unwrap();
+
+        assert_eq!(params.reason, ReportReason::Copyright);
+        assert_eq!(params.details, Some("mine".to_string()));
+    }
+}
@@ -0,0 +1,138 @@
+//! Module to store and sanitize QR code input.
+//!
+//! The link of a QR code is provided by the user in an HTML form to the POST /submit-qr endpoint, and is drawn as a QR code
+//! made of block characters. A QR code drawn that way is provided in an HTML form to the POST /submit-qr-ascii endpoint, and
+//! is rasterized back into an image that can be scanned.
+//!
+//! Robert Peterson and Kelsey Werner 2023
+
+use serde::{Deserialize, Serialize};
+
+/// The most characters the link of a QR code can have.
+pub const MAX_QR_URL_LENGTH: usize = 1024;
+
+/// Struct to store the link of a QR code.
+///
+/// Actix Web populates [QrFormParams] with user-submitted form data.
+#[derive(Serialize, Deserialize)]
+pub struct QrFormParams {
+    /// [String] to store the link that is drawn as a QR code.
+    pub url: String,
+}
+
+/// Struct to store a QR code drawn with block characters.
+///
+/// Actix Web populates [QrAsciiFormParams] with user-submitted form data.
+#[derive(Serialize, Deserialize)]
+pub struct QrAsciiFormParams {
+    /// [String] to store the block characters of the QR code.
+    pub qr_ascii: String,
+}
+
+/// Enum to store the possible error states that can be detected when sanitizing QR code input.
+///
+/// The different enum variants are used to identify the specific cause of an error.
+#[derive(PartialEq, Debug)]
+pub enum QrInputError {
+    /// [QrInputError::EmptyInput] error is caused when the form is submitted without any text, or with only whitespace.
+    EmptyInput,
+    /// [QrInputError::NotAUrl] error is caused when the link doesn't start with "http://" or "https://", or has whitespace in it.
+    NotAUrl,
+    /// [QrInputError::TooLong] error is caused when the link has more than [MAX_QR_URL_LENGTH] characters.
+    TooLong,
+}
+
+impl QrFormParams {
+    /// Function to verify if the link input is valid.
+    ///
+    /// Whitespace around the link is removed before the link is checked, since it is often copied along with the link.
+    /// When the link passes valiation, function returns `Ok(&str)` with the trimmed link.
+    /// Returns `Err(QrInputError::EmptyInput)` when the link is empty or only has whitespace.
+    /// Returns `Err(QrInputError::NotAUrl)` when the link is not a web address.
+    /// Returns `Err(QrInputError::TooLong)` when the link has more than [MAX_QR_URL_LENGTH] characters.
+    pub fn validate_url(&self) -> Result<&str, QrInputError> {
+        let url = self.url.trim();
+
+        if url.is_empty() {
+            return Err(QrInputError::EmptyInput);
+        }
+        let scheme = url
+            .split_once("://")
+            .map(|(scheme, _)| scheme.to_lowercase());
+        if !matches!(scheme.as_deref(), Some("http" | "https")) || url.contains(char::is_whitespace)
+        {
+            return Err(QrInputError::NotAUrl);
+        }
+        if url.chars().count() > MAX_QR_URL_LENGTH {
+            return Err(QrInputError::TooLong);
+        }
+
+        Ok(url)
+    }
+}
+
+impl QrAsciiFormParams {
+    /// Function to verify if the QR code input is valid.
+    ///
+    /// Whether the characters are a QR code is checked when the code is rasterized, so only empty input is rejected here.
+    /// Returns `Ok(&str)` with the QR code when it has any characters other than whitespace.
+    /// Returns `Err(QrInputError::EmptyInput)` when the QR code is empty or only has whitespace.
+    pub fn validate_qr_ascii(&self) -> Result<&str, QrInputError> {
+        if self.qr_ascii.trim().is_empty() {
+            return Err(QrInputError::EmptyInput);
+        }
+
+        Ok(&self.qr_ascii)
+    }
+}
+
+// Tests
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Creates a form with the given link
+    fn form(url: &str) -> QrFormParams {
+        QrFormParams {
+            url: url.to_string(),
+        }
+    }
+
+    // Verifies that QrFormParams::validate_url() trims the link and only accepts web addresses
+    #[test]
+    fn test_validate_url() {
+        assert_eq!(
+            form(" https://example.com/gallery?page=2\r\n").validate_url(),
+            Ok("https://example.com/gallery?page=2")
+        );
+        assert_eq!(
+            form("HTTP://example.com").validate_url(),
+            Ok("HTTP://example.com")
+        );
+
+        assert_eq!(form(" ").validate_url(), Err(QrInputError::EmptyInput));
+        for url in [
+            "example.com",
+            "ftp://example.com",
+            "javascript:alert(1)",
+            "https://example.com/a b",
+        ] {
+            assert_eq!(form(url).validate_url(), Err(QrInputError::NotAUrl));
+        }
+        let long_url = format!("https://example.com/{}", "a".repeat(MAX_QR_URL_LENGTH));
+        assert_eq!(form(&long_url).validate_url(), Err(QrInputError::TooLong));
+    }
+
+    // Verifies that QrAsciiFormParams::validate_qr_ascii() rejects empty QR codes
+    #[test]
+    fn test_validate_qr_ascii() {
+        let mut input = QrAsciiFormParams {
+            qr_ascii: " ▄▄▄ \n █ █ ".to_string(),
+        };
+        assert_eq!(input.validate_qr_ascii(), Ok(" ▄▄▄ \n █ █ "));
+
+        input.qr_ascii = " \r\n \n".to_string();
+        assert_eq!(input.validate_qr_ascii(), Err(QrInputError::EmptyInput));
+    }
+}
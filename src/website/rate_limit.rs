@@ -0,0 +1,414 @@
+//! Module to limit how often a single client can run conversions.
+//!
+//! Every IP address gets a token bucket that holds up to `rate_limit_burst` tokens and is refilled with
+//! `rate_limit_per_minute` tokens a minute, both set in the [super::config::AppConfig]. Each conversion request takes a
+//! token, so a client can make a few conversions in a row but can't keep the converter busy by itself. The [RateLimit]
+//! middleware wraps the conversion endpoints, and answers a request from a client whose bucket is empty with a
+//! `429 Too Many Requests` page that says how many seconds to wait, or with the JSON error object of the JSON API on its
+//! endpoints. Only the clients that were seen most recently are remembered, so the buckets can't fill the memory.
+//!
+//! Robert Peterson and Kelsey Werner 2023
+
+use super::{api_keys::ApiErrorBody, html_template::HtmlTemplate};
+use actix_web::{
+    body::{EitherBody, MessageBody},
+    dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform},
+    http::header,
+    web, Error, HttpResponse,
+};
+use handlebars::Handlebars;
+use std::{
+    collections::{BTreeSet, HashMap},
+    future::{ready, Future, Ready},
+    net::IpAddr,
+    pin::Pin,
+    rc::Rc,
+    sync::{Arc, Mutex, PoisonError},
+    time::{Duration, Instant},
+};
+
+/// The number of clients whose buckets are kept, after which the client that was seen longest ago is forgotten.
+const MAX_TRACKED_CLIENTS: usize = 10_000;
+
+/// Struct to store the token bucket of a client.
+struct Bucket {
+    /// The number of requests the client can still make, which may be part of a token.
+    tokens: f64,
+    /// The time the tokens were last counted.
+    updated: Instant,
+}
+
+/// Struct to store the buckets of the clients, along with the order they were last seen in.
+#[derive(Default)]
+struct Buckets {
+    /// The bucket of each client by its IP address.
+    by_client: HashMap<IpAddr, Bucket>,
+    /// The time each client was last seen along with its IP address, so the client seen longest ago comes first.
+    by_last_seen: BTreeSet<(Instant, IpAddr)>,
+}
+
+/// Struct to store the token buckets of the clients that made conversions.
+///
+/// The [RateLimiter] is shared by every worker of the server, so a client can't get more requests by reaching another worker.
+pub struct RateLimiter {
+    /// The most tokens a bucket can hold, which is the number of requests a client can make in a row.
+    burst: f64,
+    /// The number of tokens added to a bucket every second.
+    refill_rate: f64,
+    /// The [Buckets] of the clients that were seen most recently.
+    buckets: Mutex<Buckets>,
+}
+
+impl RateLimiter {
+    /// Function to create a [RateLimiter] that lets a client make `burst` requests in a row and `per_minute` requests a minute after that.
+    ///
+    /// Returns [None] when either number is 0, which turns the rate limiting off.
+    pub fn new(per_minute: u32, burst: u32) -> Option<RateLimiter> {
+        (per_minute > 0 && burst > 0).then(|| RateLimiter {
+            burst: f64::from(burst),
+            refill_rate: f64::from(per_minute) / 60.0,
+            buckets: Mutex::new(Buckets::default()),
+        })
+    }
+
+    /// Function to take a token from the bucket of the client at `client` at the time `now`.
+    ///
+    /// Returns `Ok(())` when the client may make the request, and otherwise `Err` with how long until its next token.
+    /// A new client makes the client that was seen longest ago be forgotten once [MAX_TRACKED_CLIENTS] are remembered.
+    pub fn check(&self, client: IpAddr, now: Instant) -> Result<(), Duration> {
+        let mut buckets = self.buckets.lock().unwrap_or_else(PoisonError::into_inner);
+        let Buckets {
+            by_client,
+            by_last_seen,
+        } = &mut *buckets;
+
+        if by_client.len() >= MAX_TRACKED_CLIENTS && !by_client.contains_key(&client) {
+            if let Some((_, oldest)) = by_last_seen.pop_first() {
+                by_client.remove(&oldest);
+            }
+        }
+
+        let bucket = by_client.entry(client).or_insert(Bucket {
+            tokens: self.burst,
+            updated: now,
+        });
+        by_last_seen.remove(&(bucket.updated, client));
+        bucket.tokens = self.tokens(bucket, now);
+        bucket.updated = now;
+        by_last_seen.insert((now, client));
+
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(Duration::from_secs_f64(
+                (1.0 - bucket.tokens) / self.refill_rate,
+            ))
+        }
+    }
+
+    /// Function to count the tokens of a bucket at the time `now`, after it was refilled since it was last counted.
+    fn tokens(&self, bucket: &Bucket, now: Instant) -> f64 {
+        let elapsed = now.saturating_duration_since(bucket.updated).as_secs_f64();
+        (bucket.tokens + elapsed * self.refill_rate).min(self.burst)
+    }
+}
+
+/// Struct to store the middleware that limits how often a client can make the requests it wraps.
+///
+/// Every resource wrapped with clones of the same [RateLimit] takes tokens from the same buckets. Requests that don't
+/// come from a known IP address are not limited.
+#[derive(Clone)]
+pub struct RateLimit {
+    /// [Option] stores the shared [RateLimiter] or [None] when rate limiting is turned off.
+    limiter: Option<Arc<RateLimiter>>,
+    /// Whether limited requests are answered with an [ApiErrorBody] instead of a page.
+    json_errors: bool,
+}
+
+impl RateLimit {
+    /// Function to create the middleware from a [RateLimiter], where [None] lets every request through.
+    pub fn new(limiter: Option<RateLimiter>) -> RateLimit {
+        RateLimit {
+            limiter: limiter.map(Arc::new),
+            json_errors: false,
+        }
+    }
+
+    /// Function to answer limited requests with the JSON error object of the JSON API instead of a page, for the
+    /// endpoints of the JSON API. The buckets are still shared with the [RateLimit] this was made from.
+    pub fn with_json_errors(self) -> RateLimit {
+        RateLimit {
+            json_errors: true,
+            ..self
+        }
+    }
+}
+
+// The middleware follows the example in the actix-web middleware documentation:
+// https://actix.rs/docs/middleware/
+impl<S, B> Transform<S, ServiceRequest> for RateLimit
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: MessageBody + 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Transform = RateLimitMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(RateLimitMiddleware {
+            service: Rc::new(service),
+            limiter: self.limiter.clone(),
+            json_errors: self.json_errors,
+        }))
+    }
+}
+
+/// Struct to store the service the [RateLimit] middleware wraps.
+pub struct RateLimitMiddleware<S> {
+    /// The wrapped service, which handles the requests that are let through.
+    service: Rc<S>,
+    /// [Option] stores the shared [RateLimiter] or [None] when rate limiting is turned off.
+    limiter: Option<Arc<RateLimiter>>,
+    /// Whether limited requests are answered with an [ApiErrorBody] instead of a page.
+    json_errors: bool,
+}
+
+impl<S, B> Service<ServiceRequest> for RateLimitMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: MessageBody + 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>>>>;
+
+    forward_ready!(service);
+
+    fn call(&self, request: ServiceRequest) -> Self::Future {
+        let client = request.peer_addr().map(|address| address.ip());
+        let checked = match (&self.limiter, client) {
+            (Some(limiter), Some(client)) => limiter.check(client, Instant::now()),
+            _ => Ok(()),
+        };
+
+        match checked {
+            Ok(()) => {
+                let service = Rc::clone(&self.service);
+                Box::pin(async move {
+                    service
+                        .call(request)
+                        .await
+                        .map(ServiceResponse::map_into_left_body)
+                })
+            }
+            Err(wait) => {
+                let response = too_many_requests_response(&request, wait, self.json_errors);
+                Box::pin(ready(Ok(request
+                    .into_response(response)
+                    .map_into_right_body())))
+            }
+        }
+    }
+}
+
+/// Function to create the `429 Too Many Requests` response for a client that has to wait `wait` for its next token.
+///
+/// The response has a `Retry-After` header. With `json_errors` it is an [ApiErrorBody], and otherwise it is the
+/// too-many-requests.html template when Handlebars is registered with the app or plain text.
+fn too_many_requests_response(
+    request: &ServiceRequest,
+    wait: Duration,
+    json_errors: bool,
+) -> HttpResponse {
+    // the wait is rounded up so a client that waits as long as it is told always gets a token
+    let retry_after = wait.as_secs() + u64::from(wait.subsec_nanos() > 0);
+    let html = HtmlTemplate::TooManyRequests { retry_after };
+    let mut response = HttpResponse::TooManyRequests();
+    response.insert_header((header::RETRY_AFTER, retry_after));
+
+    if json_errors {
+        return response.json(ApiErrorBody {
+            error: "rate_limited".to_string(),
+            message: format!(
+                "You've made a lot of requests in a short time! Please try again in {} seconds.",
+                retry_after
+            ),
+        });
+    }
+
+    match request
+        .app_data::<web::Data<Handlebars>>()
+        .and_then(|hb| html.render_template(hb).ok())
+    {
+        Some(res_body) => response
+            .content_type("text/html; charset=utf-8")
+            .body(res_body),
+        None => response
+            .content_type("text/plain; charset=utf-8")
+            .body(format!(
+            "You've made a lot of conversions in a short time! Please try again in {} seconds.\n",
+            retry_after
+        )),
+    }
+}
+
+// Tests
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::{
+        http::StatusCode,
+        test::{call_service, init_service, read_body, TestRequest},
+        App,
+    };
+    use std::net::{Ipv4Addr, SocketAddr};
+
+    // Verifies that a client can make its burst of requests in a row, then has to wait for its bucket to be refilled,
+    // and that other clients have their own buckets
+    #[test]
+    fn test_check() {
+        let limiter = RateLimiter::new(6, 2).unwrap();
+        let client = IpAddr::V4(Ipv4Addr::new(192, 0, 2, 1));
+        let other_client = IpAddr::V4(Ipv4Addr::new(192, 0, 2, 2));
+        let now = Instant::now();
+
+        assert_eq!(limiter.check(client, now), Ok(()));
+        assert_eq!(limiter.check(client, now), Ok(()));
+        assert_eq!(limiter.check(client, now), Err(Duration::from_secs(10)));
+        assert_eq!(limiter.check(other_client, now), Ok(()));
+
+        let later = now + Duration::from_secs(5);
+
+        assert_eq!(limiter.check(client, later), Err(Duration::from_secs(5)));
+
+        let much_later = now + Duration::from_secs(60 * 60);
+
+        assert_eq!(limiter.check(client, much_later), Ok(()));
+        assert_eq!(limiter.check(client, much_later), Ok(()));
+        assert!(limiter.check(client, much_later).is_err());
+    }
+
+    // Verifies that rate limiting is turned off when either limit is 0
+    #[test]
+    fn test_new_turned_off() {
+        assert!(RateLimiter::new(0, 10).is_none());
+        assert!(RateLimiter::new(30, 0).is_none());
+    }
+
+    // Verifies that the middleware answers a client that made too many requests with a 429 response that says how long to wait
+    #[actix_web::test]
+    async fn test_rate_limit_middleware() {
+        let app = init_service(
+            App::new().service(
+                web::resource("/convert")
+                    .wrap(RateLimit::new(RateLimiter::new(1, 1)))
+                    .to(HttpResponse::Ok),
+            ),
+        )
+        .await;
+        let client = SocketAddr::from(([192, 0, 2, 1], 8080));
+
+        let request = TestRequest::get()
+            .uri("/convert")
+            .peer_addr(client)
+            .to_request();
+        let response = call_service(&app, request).await;
+
+        assert!(response.status().is_success());
+
+        let request = TestRequest::get()
+            .uri("/convert")
+            .peer_addr(client)
+            .to_request();
+        let response = call_service(&app, request).await;
+
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(response.headers().get(header::RETRY_AFTER).unwrap(), "60");
+
+        let response_body = String::from_utf8(read_body(response).await.to_vec()).unwrap();
+
+        assert!(response_body.contains("try again in 60 seconds"));
+
+        // requests without an address are not limited
+        let request = TestRequest::get().uri("/convert").to_request();
+        let response = call_service(&app, request).await;
+
+        assert!(response.status().is_success());
+    }
+
+    // Verifies that the JSON API is answered with its JSON error object when a client made too many requests, and that it
+    // shares the buckets of the pages
+    #[actix_web::test]
+    async fn test_rate_limit_middleware_json_errors() {
+        let rate_limit = RateLimit::new(RateLimiter::new(1, 1));
+        let app = init_service(
+            App::new()
+                .service(
+                    web::resource("/convert")
+                        .wrap(rate_limit.clone())
+                        .to(HttpResponse::Ok),
+                )
+                .service(
+                    web::resource("/api/v1/convert")
+                        .wrap(rate_limit.with_json_errors())
+                        .to(HttpResponse::Ok),
+                ),
+        )
+        .await;
+        let client = SocketAddr::from(([192, 0, 2, 1], 8080));
+
+        let request = TestRequest::get()
+            .uri("/convert")
+            .peer_addr(client)
+            .to_request();
+        let response = call_service(&app, request).await;
+
+        assert!(response.status().is_success());
+
+        let request = TestRequest::get()
+            .uri("/api/v1/convert")
+            .peer_addr(client)
+            .to_request();
+        let response = call_service(&app, request).await;
+
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(response.headers().get(header::RETRY_AFTER).unwrap(), "60");
+        assert_eq!(
+            response.headers().get(header::CONTENT_TYPE).unwrap(),
+            "application/json"
+        );
+
+        let response_body: ApiErrorBody =
+            serde_json::from_slice(&read_body(response).await).unwrap();
+
+        assert_eq!(response_body.error, "rate_limited");
+        assert!(response_body.message.contains("try again in 60 seconds"));
+    }
+
+    // Verifies that only the clients that were seen most recently are remembered, however many clients make requests
+    #[test]
+    fn test_check_forgets_oldest_clients() {
+        let limiter = RateLimiter::new(1, 1).unwrap();
+        let now = Instant::now();
+        let client = |number: usize| IpAddr::V4(Ipv4Addr::from(number as u32));
+
+        for number in 0..MAX_TRACKED_CLIENTS + 100 {
+            let _ = limiter.check(client(number), now + Duration::from_millis(number as u64));
+        }
+
+        let buckets = limiter.buckets.lock().unwrap();
+
+        assert_eq!(buckets.by_client.len(), MAX_TRACKED_CLIENTS);
+        assert_eq!(buckets.by_last_seen.len(), MAX_TRACKED_CLIENTS);
+        assert!(!buckets.by_client.contains_key(&client(99)));
+        assert!(buckets.by_client.contains_key(&client(100)));
+        assert!(buckets
+            .by_client
+            .contains_key(&client(MAX_TRACKED_CLIENTS + 99)));
+    }
+}
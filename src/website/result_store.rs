@@ -0,0 +1,325 @@
+//! Module to store the results of conversions.
+//!
+//! The images and ASCII art created by the website are kept in a [MemoryResultStore], so converting doesn't write to the
+//! disk. Operators who want the results to be kept between restarts can set a `results_directory` in the
+//! [super::config::AppConfig] to store them as files with a [DirectoryResultStore] instead. Everything that works with the results, such as the GET /conversion/{name} route that
+//! serves them, the operator export and import, or the cleanup of old results, goes through the [ResultStore] trait so
+//! it doesn't depend on where they are stored.
+//!
+//! A store can fail to write, for example when the disk is full. The conversions then still show their results, and each
+//! failure is counted by [count_write_failure] so the operator can see it in the metrics of the website.
+//!
+//! Robert Peterson and Kelsey Werner 2023
+
+use std::{
+    collections::BTreeMap,
+    fs, io,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, PoisonError, RwLock, RwLockReadGuard, RwLockWriteGuard,
+    },
+    time::SystemTime,
+};
+use tempfile::NamedTempFile;
+
+/// Function to open the [ResultStore] the website keeps the results of conversions in.
+///
+/// Returns a [DirectoryResultStore] in `directory`, or a [MemoryResultStore] when no directory is given. Returns an
+/// error if the directory can't be created.
+pub fn open_result_store(directory: Option<&Path>) -> io::Result<Arc<dyn ResultStore>> {
+    match directory {
+        Some(directory) => Ok(Arc::new(DirectoryResultStore::new(directory)?)),
+        None => Ok(Arc::new(MemoryResultStore::new())),
+    }
+}
+
+/// The number of results that couldn't be stored since the server started.
+static WRITE_FAILURES: AtomicU64 = AtomicU64::new(0);
+
+/// Function to count a result that couldn't be stored because of `error`, which is also logged.
+pub fn count_write_failure(error: &io::Error) {
+    log::error!("Failed to store the result of a conversion: {}", error);
+    WRITE_FAILURES.fetch_add(1, Ordering::Relaxed);
+}
+
+/// Function to get the number of results that couldn't be stored since the server started.
+pub fn write_failures() -> u64 {
+    WRITE_FAILURES.load(Ordering::Relaxed)
+}
+
+/// Function to check if a name can be used for a stored result.
+///
+/// A name must be a plain file name, such as "{uuid}.png", so that it can't point outside of the store or to a hidden file.
+pub fn is_result_name(name: &str) -> bool {
+    !name.is_empty()
+        && !name.starts_with('.')
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '.')
+}
+
+/// Trait for the places the results of conversions can be stored in.
+///
+/// A [ResultStore] is shared by every worker of the server, so it must be [Send] and [Sync].
+pub trait ResultStore: Send + Sync {
+    /// Function to list the names of every stored result, sorted by name.
+    fn names(&self) -> io::Result<Vec<String>>;
+
+    /// Function to read the stored result with a name.
+    ///
+    /// Returns `Ok(None)` when nothing is stored under the name, or when the name is not allowed by [is_result_name].
+    fn read(&self, name: &str) -> io::Result<Option<Vec<u8>>>;
+
+    /// Function to store a result under a name, replacing a result that was stored under the name before.
+    ///
+    /// Returns an error with [io::ErrorKind::InvalidInput] when the name is not allowed by [is_result_name].
+    fn write(&self, name: &str, data: &[u8]) -> io::Result<()>;
+
+    /// Function to get the time the result with a name was last stored.
+    ///
+    /// Returns `Ok(None)` when nothing is stored under the name, or when the name is not allowed by [is_result_name].
+    fn modified(&self, name: &str) -> io::Result<Option<SystemTime>>;
+
+    /// Function to remove the stored result with a name.
+    ///
+    /// Returns `Ok(false)` when nothing is stored under the name, or when the name is not allowed by [is_result_name].
+    fn remove(&self, name: &str) -> io::Result<bool>;
+
+    /// Function to get the number of bytes of the stored result with a name.
+    ///
+    /// Returns `Ok(None)` when nothing is stored under the name. The result is read to measure it, unless the store can
+    /// tell its size without reading it.
+    fn size(&self, name: &str) -> io::Result<Option<u64>> {
+        Ok(self.read(name)?.map(|data| data.len() as u64))
+    }
+}
+
+/// Struct to store the results of conversions as files in a directory.
+pub struct DirectoryResultStore {
+    /// The directory the result files are stored in.
+    root: PathBuf,
+}
+
+impl DirectoryResultStore {
+    /// Function to open the store in a directory, which is created if it doesn't exist yet.
+    pub fn new<P: Into<PathBuf>>(root: P) -> io::Result<DirectoryResultStore> {
+        let root = root.into();
+        fs::create_dir_all(&root)?;
+        Ok(DirectoryResultStore { root })
+    }
+}
+
+impl ResultStore for DirectoryResultStore {
+    fn names(&self) -> io::Result<Vec<String>> {
+        let mut names = Vec::new();
+        for entry in fs::read_dir(&self.root)? {
+            let entry = entry?;
+            let name = entry.file_name().to_string_lossy().to_string();
+            // the .keep file that holds the directory in git is not a result
+            if entry.file_type()?.is_file() && is_result_name(&name) {
+                names.push(name);
+            }
+        }
+        names.sort_unstable();
+        Ok(names)
+    }
+
+    fn read(&self, name: &str) -> io::Result<Option<Vec<u8>>> {
+        if !is_result_name(name) {
+            return Ok(None);
+        }
+
+        match fs::read(self.root.join(name)) {
+            Ok(data) => Ok(Some(data)),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    fn write(&self, name: &str, data: &[u8]) -> io::Result<()> {
+        if !is_result_name(name) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("{:?} can't be used as the name of a result", name),
+            ));
+        }
+
+        // the file is written under a temporary name and then renamed, so a result is never read while it is half written
+        let mut file = NamedTempFile::new_in(&self.root)?;
+        io::Write::write_all(&mut file, data)?;
+        file.persist(self.root.join(name)).map_err(|e| e.error)?;
+        Ok(())
+    }
+
+    fn modified(&self, name: &str) -> io::Result<Option<SystemTime>> {
+        if !is_result_name(name) {
+            return Ok(None);
+        }
+
+        match fs::metadata(self.root.join(name)) {
+            Ok(metadata) => Ok(Some(metadata.modified()?)),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    fn size(&self, name: &str) -> io::Result<Option<u64>> {
+        if !is_result_name(name) {
+            return Ok(None);
+        }
+
+        match fs::metadata(self.root.join(name)) {
+            Ok(metadata) => Ok(Some(metadata.len())),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    fn remove(&self, name: &str) -> io::Result<bool> {
+        if !is_result_name(name) {
+            return Ok(false);
+        }
+
+        match fs::remove_file(self.root.join(name)) {
+            Ok(()) => Ok(true),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+}
+
+/// Struct to store the results of conversions in memory.
+///
+/// The results are lost when the server stops, and are removed by the cleanup of old results like any other results.
+#[derive(Default)]
+pub struct MemoryResultStore {
+    /// The data of each result and the time it was stored, by name.
+    results: RwLock<BTreeMap<String, (Vec<u8>, SystemTime)>>,
+}
+
+impl MemoryResultStore {
+    /// Function to create a store with no results.
+    pub fn new() -> MemoryResultStore {
+        MemoryResultStore::default()
+    }
+
+    /// Function to lock the results so they can be read while no other thread changes them.
+    ///
+    /// A thread that panicked while it held the lock can only have left whole results behind, so the lock is used anyway.
+    fn results(&self) -> RwLockReadGuard<'_, BTreeMap<String, (Vec<u8>, SystemTime)>> {
+        self.results.read().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Function to lock the results so they can be changed while no other thread reads or changes them.
+    fn results_mut(&self) -> RwLockWriteGuard<'_, BTreeMap<String, (Vec<u8>, SystemTime)>> {
+        self.results.write().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl ResultStore for MemoryResultStore {
+    fn names(&self) -> io::Result<Vec<String>> {
+        Ok(self.results().keys().cloned().collect())
+    }
+
+    fn read(&self, name: &str) -> io::Result<Option<Vec<u8>>> {
+        Ok(self.results().get(name).map(|(data, _)| data.clone()))
+    }
+
+    fn write(&self, name: &str, data: &[u8]) -> io::Result<()> {
+        if !is_result_name(name) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("{:?} can't be used as the name of a result", name),
+            ));
+        }
+
+        self.results_mut()
+            .insert(name.to_string(), (data.to_vec(), SystemTime::now()));
+        Ok(())
+    }
+
+    fn modified(&self, name: &str) -> io::Result<Option<SystemTime>> {
+        Ok(self.results().get(name).map(|(_, modified)| *modified))
+    }
+
+    fn remove(&self, name: &str) -> io::Result<bool> {
+        Ok(self.results_mut().remove(name).is_some())
+    }
+}
+
+// Tests
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Verifies that only plain file names can be used for results
+    #[test]
+    fn test_is_result_name() {
+        assert!(is_result_name("0b7f2a4e-5d1c-4c8e-9a43-3f0e2c1d7b66.png"));
+        assert!(!is_result_name(""));
+        assert!(!is_result_name(".keep"));
+        assert!(!is_result_name("../gallery.db"));
+        assert!(!is_result_name("nested/result.txt"));
+    }
+
+    // Verifies that results written to a DirectoryResultStore can be listed and read back
+    #[test]
+    fn test_directory_result_store() {
+        let root = tempfile::tempdir().unwrap();
+        let store = DirectoryResultStore::new(root.path()).unwrap();
+        fs::write(root.path().join(".keep"), "").unwrap();
+
+        store.write("b.txt", b"$$").unwrap();
+        store.write("a.png", b"png").unwrap();
+
+        assert_eq!(store.names().unwrap(), vec!["a.png", "b.txt"]);
+        assert_eq!(store.read("b.txt").unwrap(), Some(b"$$".to_vec()));
+        assert_eq!(store.read("missing.txt").unwrap(), None);
+        assert_eq!(store.read("../b.txt").unwrap(), None);
+        assert_eq!(store.size("a.png").unwrap(), Some(3));
+        assert_eq!(store.size("missing.txt").unwrap(), None);
+        assert_eq!(
+            store.write("../escape.txt", b"$").unwrap_err().kind(),
+            io::ErrorKind::InvalidInput
+        );
+    }
+
+    // Verifies that results written to a MemoryResultStore can be listed, read back and removed
+    #[test]
+    fn test_memory_result_store() {
+        let store = MemoryResultStore::new();
+
+        store.write("b.txt", b"$$").unwrap();
+        store.write("a.png", b"png").unwrap();
+
+        assert_eq!(store.names().unwrap(), vec!["a.png", "b.txt"]);
+        assert_eq!(store.read("b.txt").unwrap(), Some(b"$$".to_vec()));
+        assert_eq!(store.read("missing.txt").unwrap(), None);
+        assert_eq!(
+            store.write("../escape.txt", b"$").unwrap_err().kind(),
+            io::ErrorKind::InvalidInput
+        );
+        assert!(store.modified("a.png").unwrap().is_some());
+        assert_eq!(store.size("b.txt").unwrap(), Some(2));
+        assert!(store.remove("a.png").unwrap());
+        assert!(!store.remove("a.png").unwrap());
+        assert_eq!(store.modified("a.png").unwrap(), None);
+        assert_eq!(store.names().unwrap(), vec!["b.txt"]);
+    }
+
+    // Verifies that results can be removed from a DirectoryResultStore, and that the time they were stored is known until then
+    #[test]
+    fn test_directory_result_store_remove() {
+        let root = tempfile::tempdir().unwrap();
+        let store = DirectoryResultStore::new(root.path()).unwrap();
+        store.write("a.png", b"png").unwrap();
+
+        assert!(store.modified("a.png").unwrap().is_some());
+        assert!(store.remove("a.png").unwrap());
+        assert!(!store.remove("a.png").unwrap());
+        assert_eq!(store.modified("a.png").unwrap(), None);
+        assert!(!store.remove("../a.png").unwrap());
+    }
+}
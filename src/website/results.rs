@@ -0,0 +1,246 @@
+//! Module to keep a record of the result of each conversion so it can be shared.
+//!
+//! Every file of a conversion is named after the id of the conversion, such as "{id}.png", "{id}.2x.png" and "{id}.svg", and a
+//! "{id}.json" [ResultRecord] that lists the files is saved next to them in the [ResultStore]. The GET /result/{id} page
+//! reads the record to show the result again, which gives each conversion a link that can be shared. The files themselves
+//! are served from the [ResultStore] by the GET /conversion/{name} route, or all at once as a ZIP archive by the
+//! GET /r/{id}/bundle.zip route. The ASCII art of a result is also downloaded as a text file by the GET /result/{id}.txt route.
+//!
+//! Robert Peterson and Kelsey Werner 2023
+
+use super::{attribution::Attribution, gallery::ConversionKind, result_store::ResultStore};
+use ascii_art_converter::converter::usage::ResourceUsage;
+use serde::{Deserialize, Serialize};
+use std::{io, time::SystemTime};
+use uuid::Uuid;
+
+/// The route the files of results are served from, relative to the root of the website.
+pub const RESULT_FILES_ROUTE: &str = "conversion/";
+
+/// The route the files of results were served from before they were kept in the [ResultStore], which older links and
+/// gallery entries still use.
+pub const LEGACY_RESULT_FILES_ROUTE: &str = "conversion_results/";
+
+/// How many times larger than normal the high-DPI copy of an image made from ASCII art is drawn.
+pub const HIGH_DPI_SCALE: u32 = 2;
+
+/// The extension of the high-DPI copy of an image made from ASCII art, which is named "{id}.2x.png".
+pub const HIGH_DPI_EXTENSION: &str = "2x.png";
+
+/// The extension of the JSON manifest of the settings of an image to ASCII art conversion, which is named "{id}.manifest.json".
+///
+/// The manifest is not served on its own, but is packaged with the other files of the result by the GET /r/{id}/bundle.zip route.
+pub const MANIFEST_EXTENSION: &str = "manifest.json";
+
+/// Function to get the route a file of a result is served from, such as "conversion/{id}.png".
+pub fn file_route(name: &str) -> String {
+    format!("{}{}", RESULT_FILES_ROUTE, name)
+}
+
+/// Function to get the name of a file of a result in the [ResultStore] from the route it is served from.
+///
+/// Routes from before the results were kept in the [ResultStore] are understood too. Returns [None] for any other route.
+pub fn file_name_from_route(route: &str) -> Option<&str> {
+    route
+        .strip_prefix(RESULT_FILES_ROUTE)
+        .or_else(|| route.strip_prefix(LEGACY_RESULT_FILES_ROUTE))
+}
+
+/// Function to get the content type a file of a result is served with from its extension.
+///
+/// Returns [None] for files that are not served, such as the "{id}.json" records.
+pub fn file_content_type(name: &str) -> Option<&'static str> {
+    match name.rsplit_once('.').map(|(_, extension)| extension) {
+        Some("png") => Some("image/png"),
+        Some("svg") => Some("image/svg+xml"),
+        Some("txt") => Some("text/plain; charset=utf-8"),
+        _ => None,
+    }
+}
+
+/// Struct to store the record of the result of a conversion.
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+pub struct ResultRecord {
+    /// The id of the conversion, which is a UUID.
+    pub id: String,
+    /// The direction of the conversion.
+    pub kind: ConversionKind,
+    /// The names of the files of the result in the [ResultStore].
+    pub files: Vec<String>,
+    /// The time the conversion was made, in seconds since the Unix epoch.
+    pub created_at: u64,
+    /// The author and license the user gave the result, which records saved before they were kept don't have.
+    #[serde(default)]
+    pub attribution: Attribution,
+    /// The resources the conversion used, which are only measured when images are converted into ASCII art.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub usage: Option<ResourceUsage>,
+}
+
+impl ResultRecord {
+    /// Function to create the record of a new conversion with a new id, no files and no attribution.
+    pub fn new(kind: ConversionKind) -> ResultRecord {
+        ResultRecord {
+            id: Uuid::new_v4().to_string(),
+            kind,
+            files: Vec::new(),
+            created_at: SystemTime::now()
+                .duration_since(SystemTime::UNIX_EPOCH)
+                .map_or(0, |since_epoch| since_epoch.as_secs()),
+            attribution: Attribution::default(),
+            usage: None,
+        }
+    }
+
+    /// Function to name a file of the result with the given extension (such as "png" or "txt") and add it to the record.
+    ///
+    /// Returns the name of the file, which is the id of the conversion followed by the extension.
+    pub fn add_file(&mut self, extension: &str) -> String {
+        let name = format!("{}.{}", self.id, extension);
+        self.files.push(name.clone());
+        name
+    }
+
+    /// Function to find the name of the file of the result with the given extension.
+    pub fn file(&self, extension: &str) -> Option<&str> {
+        let name = format!("{}.{}", self.id, extension);
+        self.files
+            .iter()
+            .find(|file| **file == name)
+            .map(String::as_str)
+    }
+
+    /// Function to get the route of the page that shows the result.
+    pub fn permalink(&self) -> String {
+        format!("/result/{}", self.id)
+    }
+
+    /// Function to get the route the ASCII art of the result is downloaded from as a text file.
+    pub fn text_link(&self) -> String {
+        format!("/result/{}.txt", self.id)
+    }
+
+    /// Function to get the route the files of the result are downloaded from as a single ZIP archive.
+    pub fn bundle_link(&self) -> String {
+        format!("/r/{}/bundle.zip", self.id)
+    }
+
+    /// Function to save the record in the [ResultStore] as "{id}.json".
+    pub fn save(&self, results: &dyn ResultStore) -> io::Result<()> {
+        results.write(&format!("{}.json", self.id), &serde_json::to_vec(self)?)
+    }
+
+    /// Function to load the record of the conversion with the given id from the [ResultStore].
+    ///
+    /// Returns `Ok(None)` when there is no record with the id, or the id is not a UUID.
+    pub fn load(results: &dyn ResultStore, id: &str) -> io::Result<Option<ResultRecord>> {
+        if Uuid::parse_str(id).is_err() {
+            return Ok(None);
+        }
+
+        match results.read(&format!("{}.json", id))? {
+            Some(record) => Ok(Some(serde_json::from_slice(&record)?)),
+            None => Ok(None),
+        }
+    }
+}
+
+/// Function to remove the result a file belongs to from the [ResultStore], along with every other file of the result and its record.
+///
+/// Files from before records were kept, or whose record is gone, are removed on their own.
+pub fn remove_result(results: &dyn ResultStore, file_name: &str) -> io::Result<()> {
+    let id = file_name.split_once('.').map_or(file_name, |(id, _)| id);
+
+    match ResultRecord::load(results, id)? {
+        Some(record) => {
+            for file in &record.files {
+                results.remove(file)?;
+            }
+            results.remove(&format!("{}.json", record.id))?;
+        }
+        None => {
+            results.remove(file_name)?;
+        }
+    }
+
+    Ok(())
+}
+
+// Tests
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::website::result_store::DirectoryResultStore;
+
+    // Verifies that the files of a result are named after its id and can be found by their extension
+    #[test]
+    fn test_add_file() {
+        let mut record = ResultRecord::new(ConversionKind::AsciiToImage);
+
+        let png = record.add_file("png");
+        let svg = record.add_file("svg");
+
+        assert_eq!(png, format!("{}.png", record.id));
+        assert_eq!(record.file("svg"), Some(svg.as_str()));
+        assert_eq!(record.file("txt"), None);
+        assert_eq!(record.permalink(), format!("/result/{}", record.id));
+        assert_eq!(record.bundle_link(), format!("/r/{}/bundle.zip", record.id));
+        assert_eq!(record.text_link(), format!("/result/{}.txt", record.id));
+    }
+
+    // Verifies that the files of results are served from their own route, which is also read back for older routes
+    #[test]
+    fn test_file_route() {
+        assert_eq!(file_route("abc.png"), "conversion/abc.png");
+        assert_eq!(file_name_from_route("conversion/abc.png"), Some("abc.png"));
+        assert_eq!(
+            file_name_from_route("conversion_results/abc.txt"),
+            Some("abc.txt")
+        );
+        assert_eq!(file_name_from_route("images/favicon.svg"), None);
+        assert_eq!(file_content_type("abc.svg"), Some("image/svg+xml"));
+        assert_eq!(file_content_type("abc.json"), None);
+    }
+
+    // Verifies that a saved record can be loaded by its id, and that ids that aren't UUIDs are not loaded
+    #[test]
+    fn test_save_and_load() {
+        let root = tempfile::tempdir().unwrap();
+        let results = DirectoryResultStore::new(root.path()).unwrap();
+        let mut record = ResultRecord::new(ConversionKind::ImageToAscii);
+        record.add_file("txt");
+
+        record.save(&results).unwrap();
+
+        assert_eq!(
+            ResultRecord::load(&results, &record.id).unwrap(),
+            Some(record)
+        );
+        assert_eq!(
+            ResultRecord::load(&results, &Uuid::new_v4().to_string()).unwrap(),
+            None
+        );
+        assert_eq!(ResultRecord::load(&results, "../gallery").unwrap(), None);
+    }
+
+    // Verifies that removing a result removes all of its files and its record, and that files without a record are removed too
+    #[test]
+    fn test_remove_result() {
+        let root = tempfile::tempdir().unwrap();
+        let results = DirectoryResultStore::new(root.path()).unwrap();
+        let mut record = ResultRecord::new(ConversionKind::AsciiToImage);
+        let png = record.add_file("png");
+        let svg = record.add_file("svg");
+        results.write(&png, b"png").unwrap();
+        results.write(&svg, b"svg").unwrap();
+        record.save(&results).unwrap();
+        results.write("legacy.txt", b"text").unwrap();
+        results.write("other.txt", b"text").unwrap();
+
+        remove_result(&results, &png).unwrap();
+        remove_result(&results, "legacy.txt").unwrap();
+
+        assert_eq!(results.names().unwrap(), vec!["other.txt"]);
+    }
+}
@@ -0,0 +1,139 @@
+//! Module to set the security headers of every response of the website.
+//!
+//! The pages of the website echo ASCII art and titles that users typed, and show images the converter generated from
+//! their uploads, so the browser is told to only run and load what the website itself serves. The middleware made by
+//! [security_headers] adds a `Content-Security-Policy`, `X-Content-Type-Options: nosniff`, `X-Frame-Options` and
+//! `Referrer-Policy` to every response that doesn't set them itself. The policy, frame options and referrer policy are
+//! set with the `content_security_policy`, `frame_options` and `referrer_policy` of the [AppConfig], where an empty value
+//! leaves the header out.
+//!
+//! Robert Peterson and Kelsey Werner 2023
+
+use super::config::AppConfig;
+use actix_web::{
+    http::header::{self, HeaderName, HeaderValue},
+    middleware::DefaultHeaders,
+};
+
+/// The Content-Security-Policy used when none is set.
+///
+/// Results are shown as `data:` images and a cropped upload is previewed from a `blob:` url, so those are allowed while
+/// everything else, scripts and styles included, must come from the files the website itself serves.
+pub const DEFAULT_CONTENT_SECURITY_POLICY: &str =
+    "default-src 'self'; img-src 'self' data: blob:; \
+     style-src 'self'; script-src 'self'; object-src 'none'; base-uri 'self'; form-action 'self'; \
+     frame-ancestors 'none'";
+
+/// The X-Frame-Options used when none is set, which keeps other websites from framing the pages.
+pub const DEFAULT_FRAME_OPTIONS: &str = "DENY";
+
+/// The Referrer-Policy used when none is set, which keeps the urls of results from being sent to other websites.
+pub const DEFAULT_REFERRER_POLICY: &str = "same-origin";
+
+/// Function to create the middleware that adds the security headers of an [AppConfig] to every response.
+///
+/// Headers whose setting is empty are left out, and so are values that can't be sent in a header, which are logged.
+pub fn security_headers(config: &AppConfig) -> DefaultHeaders {
+    let configured = [
+        (
+            header::CONTENT_SECURITY_POLICY,
+            &config.content_security_policy,
+        ),
+        (header::X_FRAME_OPTIONS, &config.frame_options),
+        (header::REFERRER_POLICY, &config.referrer_policy),
+    ];
+
+    configured.into_iter().fold(
+        DefaultHeaders::new().add((header::X_CONTENT_TYPE_OPTIONS, "nosniff")),
+        |headers, (name, value)| match header_value(&name, value) {
+            Some(value) => headers.add((name, value)),
+            None => headers,
+        },
+    )
+}
+
+/// Function to turn a setting into the value of a header, or [None] when it is empty or can't be sent.
+fn header_value(name: &HeaderName, value: &str) -> Option<HeaderValue> {
+    if value.trim().is_empty() {
+        return None;
+    }
+    match HeaderValue::from_str(value.trim()) {
+        Ok(value) => Some(value),
+        Err(_) => {
+            log::warn!(
+                "The {} header was left out, since its value can't be sent.",
+                name
+            );
+            None
+        }
+    }
+}
+
+// Tests
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::{
+        test::{call_service, init_service, TestRequest},
+        web, App, HttpResponse,
+    };
+
+    // Verifies that the default policy doesn't let inline scripts or styles run
+    #[test]
+    fn test_default_content_security_policy() {
+        assert!(!DEFAULT_CONTENT_SECURITY_POLICY.contains("'unsafe-inline'"));
+        assert!(DEFAULT_CONTENT_SECURITY_POLICY.contains("script-src 'self';"));
+        assert!(DEFAULT_CONTENT_SECURITY_POLICY.contains("style-src 'self';"));
+    }
+
+    // Verifies that every response gets the security headers, leaving out the ones that are turned off
+    #[actix_web::test]
+    async fn test_security_headers() {
+        let app = init_service(
+            App::new()
+                .wrap(security_headers(&AppConfig::default()))
+                .route("/", web::get().to(HttpResponse::Ok)),
+        )
+        .await;
+        let response = call_service(&app, TestRequest::get().uri("/").to_request()).await;
+        let headers = response.headers();
+
+        assert_eq!(
+            headers.get(header::CONTENT_SECURITY_POLICY).unwrap(),
+            DEFAULT_CONTENT_SECURITY_POLICY
+        );
+        assert_eq!(
+            headers.get(header::X_CONTENT_TYPE_OPTIONS).unwrap(),
+            "nosniff"
+        );
+        assert_eq!(headers.get(header::X_FRAME_OPTIONS).unwrap(), "DENY");
+        assert_eq!(headers.get(header::REFERRER_POLICY).unwrap(), "same-origin");
+
+        let config = AppConfig {
+            content_security_policy: "default-src 'none'".to_string(),
+            frame_options: String::new(),
+            referrer_policy: "no\nreferrer".to_string(),
+            ..AppConfig::default()
+        };
+        let app = init_service(
+            App::new()
+                .wrap(security_headers(&config))
+                .route("/", web::get().to(HttpResponse::NotFound)),
+        )
+        .await;
+        let response = call_service(&app, TestRequest::get().uri("/").to_request()).await;
+        let headers = response.headers();
+
+        assert_eq!(
+            headers.get(header::CONTENT_SECURITY_POLICY).unwrap(),
+            "default-src 'none'"
+        );
+        assert_eq!(
+            headers.get(header::X_CONTENT_TYPE_OPTIONS).unwrap(),
+            "nosniff"
+        );
+        assert!(headers.get(header::X_FRAME_OPTIONS).is_none());
+        assert!(headers.get(header::REFERRER_POLICY).is_none());
+    }
+}
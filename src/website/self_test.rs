@@ -0,0 +1,360 @@
+//! Module to check that the website works before it starts taking requests.
+//!
+//! When the server starts, [run_self_test] converts a tiny piece of ASCII art into a PNG and an SVG, converts the PNG back
+//! into ASCII art, and converts the image again in every one of the [SUPPORTED_FORMATS] that can be written, so a build
+//! that is missing a codec is found before the first upload. Every [HtmlTemplate] is then rendered with the results, and
+//! the files in the static directory that the pages link to are looked for. This also loads the font and templates once
+//! before the first conversion. The server refuses to start when the self-test fails, unless `require_self_test` is
+//! turned off in the [super::config::AppConfig], in which case the failures are only logged.
+//!
+//! Robert Peterson and Kelsey Werner 2023
+
+use super::{
+    admin::DiskUsage,
+    attribution::Attribution,
+    formatting::NumberFormat,
+    gallery::{ConversionKind, GalleryEntry, GallerySearch},
+    html_template::{register_templates, HtmlTemplate},
+    metrics_store::{ConversionSummary, ErrorCount},
+    user::Artwork,
+};
+use ascii_art_converter::converter::{
+    image::SUPPORTED_FORMATS,
+    options::{ConvertOptions, ImageRenderMode, RenderCharset, UnknownSymbolPolicy},
+    service::{Converter, DEFAULT_CHARSET},
+    ConvertError, ErrorSource,
+};
+use handlebars::Handlebars;
+use image::{DynamicImage, ImageError, ImageFormat};
+use std::{
+    fmt,
+    io::Cursor,
+    path::{Path, PathBuf},
+};
+
+/// The directory the home page, stylesheets and images of the website are served from.
+pub const STATIC_DIRECTORY: &str = "./static";
+
+/// The files in the [STATIC_DIRECTORY] that every page needs.
+pub const STATIC_ASSETS: [&str; 4] = [
+    "index.html",
+    "css/bootstrap.min.css",
+    "css/site.css",
+    "images/favicon.svg",
+];
+
+/// The ASCII art that is converted by the self-test.
+const SELF_TEST_ASCII: &str = "@@@@\n@::@\n@@@@";
+
+/// Enum to store the problems the self-test can find.
+///
+/// The different enum variants are used to identify the specific cause of an error.
+#[derive(Debug, PartialEq)]
+pub enum SelfTestError {
+    /// [SelfTestError::Conversion] error is caused when a step of the conversions fails, such as drawing the ASCII art
+    /// with the font. The variant stores the step and the [ConvertError].
+    Conversion(&'static str, ConvertError),
+    /// [SelfTestError::Codec] error is caused when an image in one of the [SUPPORTED_FORMATS] can't be converted into
+    /// ASCII art.
+    Codec(ImageFormat, ConvertError),
+    /// [SelfTestError::Template] error is caused when the templates can't be registered or a template can't be rendered.
+    /// The variant stores the name of the template or directory and the error from Handlebars.
+    Template(String, String),
+    /// [SelfTestError::MissingAsset] error is caused when a file in the [STATIC_ASSETS] doesn't exist.
+    MissingAsset(PathBuf),
+}
+
+impl fmt::Display for SelfTestError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SelfTestError::Conversion(step, error) => write!(f, "{} failed: {}", step, error),
+            SelfTestError::Codec(format, error) => write!(
+                f,
+                "a {:?} image can't be converted into ASCII art: {}",
+                format, error
+            ),
+            SelfTestError::Template(name, error) => {
+                write!(f, "the {} template can't be rendered: {}", name, error)
+            }
+            SelfTestError::MissingAsset(path) => {
+                write!(f, "the static file {} doesn't exist", path.display())
+            }
+        }
+    }
+}
+
+/// Function to run the self-test with the templates in `template_directory` and the files in `static_directory`.
+///
+/// Every check is run even after one fails. Returns `Ok(())` when everything works, and otherwise every [SelfTestError] found.
+pub fn run_self_test(
+    template_directory: &Path,
+    static_directory: &Path,
+) -> Result<(), Vec<SelfTestError>> {
+    let mut errors = Vec::new();
+
+    let ascii_result = check_conversions(&mut errors);
+    check_templates(
+        template_directory,
+        ascii_result.as_deref().unwrap_or(SELF_TEST_ASCII),
+        &mut errors,
+    );
+    for asset in STATIC_ASSETS {
+        let path = static_directory.join(asset);
+        if !path.is_file() {
+            errors.push(SelfTestError::MissingAsset(path));
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+/// Function to convert the [SELF_TEST_ASCII] into an image and back, and an image in each of the [SUPPORTED_FORMATS] into ASCII art.
+///
+/// Formats that can't be written without a codec that the website doesn't need, such as WebP, are left out. Returns the
+/// ASCII art of the PNG image, or [None] when it couldn't be made.
+fn check_conversions(errors: &mut Vec<SelfTestError>) -> Option<String> {
+    let converter = match Converter::new() {
+        Ok(converter) => converter,
+        Err(error) => {
+            errors.push(SelfTestError::Conversion("Loading the font", error));
+            return None;
+        }
+    };
+    let symbol_map = converter.charset(DEFAULT_CHARSET)?;
+
+    if let Err(error) =
+        converter.ascii_to_svg(SELF_TEST_ASCII, symbol_map, UnknownSymbolPolicy::Error)
+    {
+        errors.push(SelfTestError::Conversion(
+            "Converting ASCII art into an SVG",
+            error,
+        ));
+    }
+    let png = match converter.ascii_to_image(
+        SELF_TEST_ASCII,
+        symbol_map,
+        ImageRenderMode::Glyphs,
+        UnknownSymbolPolicy::Error,
+    ) {
+        Ok(png) => png.into_inner(),
+        Err(error) => {
+            errors.push(SelfTestError::Conversion(
+                "Converting ASCII art into a PNG",
+                error,
+            ));
+            return None;
+        }
+    };
+    let ascii_result = match converter.image_to_ascii(&png[..], ConvertOptions::default()) {
+        Ok(ascii_result) => ascii_result,
+        Err(error) => {
+            errors.push(SelfTestError::Conversion(
+                "Converting a PNG into ASCII art",
+                error,
+            ));
+            return None;
+        }
+    };
+
+    // the image is written in every format it can be, since only formats with a codec can be read
+    let image = match image::load_from_memory(&png) {
+        Ok(image) => DynamicImage::ImageRgb8(image.to_rgb8()),
+        Err(error) => {
+            errors.push(SelfTestError::Codec(
+                ImageFormat::Png,
+                ConvertError::DecodeError(ErrorSource::new(error)),
+            ));
+            return Some(ascii_result);
+        }
+    };
+    for format in SUPPORTED_FORMATS {
+        let mut encoded = Cursor::new(Vec::new());
+        match image.write_to(&mut encoded, format) {
+            Ok(()) => {
+                if let Err(error) =
+                    converter.image_to_ascii(encoded.into_inner(), ConvertOptions::default())
+                {
+                    errors.push(SelfTestError::Codec(format, error));
+                }
+            }
+            Err(ImageError::Unsupported(_)) => {}
+            Err(error) => errors.push(SelfTestError::Codec(
+                format,
+                ConvertError::WriteError(ErrorSource::new(error)),
+            )),
+        }
+    }
+
+    Some(ascii_result)
+}
+
+/// Function to register the templates in `template_directory` and render every [HtmlTemplate] with the ASCII art of the self-test.
+fn check_templates(template_directory: &Path, ascii_result: &str, errors: &mut Vec<SelfTestError>) {
+    let mut handlebars = Handlebars::new();
+    if let Err(error) =
+        register_templates(&mut handlebars, template_directory, NumberFormat::default())
+    {
+        errors.push(SelfTestError::Template(
+            template_directory.display().to_string(),
+            error.to_string(),
+        ));
+        return;
+    }
+
+    for html in sample_templates(ascii_result) {
+        if let Err(error) = html.render_template(&handlebars) {
+            errors.push(SelfTestError::Template(
+                html.get_template_name().to_string(),
+                error.to_string(),
+            ));
+        }
+    }
+}
+
+/// Function to create an [HtmlTemplate] of every kind of page, filled in with the ASCII art of the self-test.
+fn sample_templates(ascii_result: &str) -> Vec<HtmlTemplate<'static>> {
+    let entry = GalleryEntry {
+        id: 1,
+        kind: ConversionKind::ImageToAscii,
+        input_hash: String::new(),
+        output_path: "conversion/self-test.txt".to_string(),
+        title: Some("Self-test".to_string()),
+        created_at: "2023-06-09 04:58:50".to_string(),
+        author: None,
+        license: None,
+        charset: None,
+        hidden: false,
+    };
+
+    vec![
+        HtmlTemplate::AsciiToImageResult {
+            image_result: "conversion/self-test.png".to_string(),
+            image_result_2x: "conversion/self-test.2x.png".to_string(),
+            svg_result: "conversion/self-test.svg".to_string(),
+            notice: None,
+            warnings: vec!["1 invisible character removed".to_string()],
+            permalink: Some("/result/self-test".to_string()),
+            attribution: Attribution::default(),
+        },
+        HtmlTemplate::ImageToAsciiResult {
+            ascii_result: ascii_result.to_string(),
+            text_result: "conversion/self-test.txt".to_string(),
+            applied_stages: Vec::new(),
+            pipeline: "[]".to_string(),
+            manifest: "{}".to_string(),
+            share_link: "/image-to-ascii".to_string(),
+            warnings: vec!["image downscaled from 580px to 183 characters wide".to_string()],
+            permalink: Some("/result/self-test".to_string()),
+            attribution: Attribution::default(),
+        },
+        HtmlTemplate::ImageToAsciiForm {
+            manifest: String::new(),
+            width: None,
+            charset: RenderCharset::Ascii,
+        },
+        HtmlTemplate::AsciiToImageForm {
+            render_pixels: false,
+        },
+        HtmlTemplate::Gallery {
+            entries: vec![entry.clone()],
+            previous_page: None,
+            next_page: None,
+            search: GallerySearch::default(),
+        },
+        HtmlTemplate::GalleryDetail {
+            entry,
+            ascii_result: Some(ascii_result.to_string()),
+        },
+        HtmlTemplate::SavedResult {
+            permalink: "/result/self-test".to_string(),
+            image_result: None,
+            image_result_2x: None,
+            svg_result: None,
+            text_result: Some("conversion/self-test.txt".to_string()),
+            ascii_result: Some(ascii_result.to_string()),
+            bundle_link: "/r/self-test/bundle.zip".to_string(),
+            attribution: Attribution::default(),
+            usage: None,
+        },
+        HtmlTemplate::ReportReceived { entry_id: 1 },
+        HtmlTemplate::ConversionJob {
+            job_id: "self-test".to_string(),
+            progress: 50,
+        },
+        HtmlTemplate::Account {
+            error_message: Some("Self-test".to_string()),
+        },
+        HtmlTemplate::MyArt {
+            username: "self-test".to_string(),
+            artworks: vec![Artwork {
+                id: 1,
+                user_id: 1,
+                name: "Self-test".to_string(),
+                ascii: ascii_result.to_string(),
+                created_at: "2023-06-09 04:58:50".to_string(),
+                updated_at: "2023-06-09 04:58:50".to_string(),
+            }],
+            error_message: None,
+        },
+        HtmlTemplate::Admin {
+            summary: ConversionSummary {
+                conversions: 1,
+                ascii_to_image: 1,
+                image_to_ascii: 0,
+                failures: 1,
+                errors: vec![ErrorCount {
+                    error: "Self-test".to_string(),
+                    count: 1,
+                }],
+            },
+            disk_usage: DiskUsage { files: 1, bytes: 1 },
+            result_ttl_hours: 1,
+            purged: Some(1),
+        },
+        HtmlTemplate::TooManyRequests { retry_after: 1 },
+        HtmlTemplate::Error {
+            error_message: "Self-test",
+            try_again_link: "/",
+        },
+    ]
+}
+
+// Tests
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Verifies that the self-test passes with the templates and static files of the website
+    #[test]
+    fn test_run_self_test() {
+        assert_eq!(
+            run_self_test(Path::new("./static/templates"), Path::new(STATIC_DIRECTORY)),
+            Ok(())
+        );
+    }
+
+    // Verifies that the self-test finds every missing template and static file
+    #[test]
+    fn test_run_self_test_missing_files() {
+        let directory = tempfile::tempdir().unwrap();
+
+        let errors = run_self_test(directory.path(), directory.path()).unwrap_err();
+
+        assert!(errors.contains(&SelfTestError::Template(
+            "gallery".to_string(),
+            "Template not found: gallery".to_string()
+        )));
+        assert!(errors.contains(&SelfTestError::MissingAsset(
+            directory.path().join("css/bootstrap.min.css")
+        )));
+        assert!(!errors.iter().any(|error| matches!(
+            error,
+            SelfTestError::Conversion(..) | SelfTestError::Codec(..)
+        )));
+    }
+}
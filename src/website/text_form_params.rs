@@ -0,0 +1,176 @@
+//! Module to store and sanitize the text of a banner.
+//!
+//! The text input is provided by the user in an HTML form to the POST /submit-text endpoint, and is drawn in large letters
+//! made of ASCII symbols.
+//!
+//! Robert Peterson and Kelsey Werner 2023
+
+use ascii_art_converter::converter::{banner::DEFAULT_FONT_SIZE, options::RenderCharset};
+use serde::{Deserialize, Serialize};
+
+/// The most characters the text of a banner can have, counting line breaks.
+pub const MAX_BANNER_LENGTH: usize = 40;
+
+/// The font sizes, in lines of ASCII, that the text of a banner can be drawn at.
+pub const SUPPORTED_FONT_SIZES: [u32; 4] = [4, DEFAULT_FONT_SIZE, 12, 16];
+
+/// Struct to store the text of a banner.
+///
+/// Actix Web populates [TextFormParams] with user-submitted form data.
+#[derive(Serialize, Deserialize)]
+pub struct TextFormParams {
+    /// [String] to store the text that is drawn.
+    pub text_input: String,
+    /// [Option] stores the selected font size or [None] if no font size submitted.
+    #[serde(default)]
+    pub font_size: Option<String>,
+    /// [Option] stores the name of the selected character set (such as "braille") or [None] if no character set submitted.
+    #[serde(default)]
+    pub render_charset: Option<String>,
+}
+
+/// Enum to store the possible error states that can be detected when sanitizing the text of a banner.
+///
+/// The different enum variants are used to identify the specific cause of an error.
+#[derive(PartialEq, Debug)]
+pub enum TextInputError {
+    /// [TextInputError::EmptyInput] error is caused when the form is submitted without any text, or with only whitespace.
+    EmptyInput,
+    /// [TextInputError::TooLong] error is caused when the form is submitted with more than [MAX_BANNER_LENGTH] characters.
+    TooLong,
+    /// [TextInputError::UnsupportedFontSize] error is caused when the form is submitted with a font size that is not one of the
+    /// [SUPPORTED_FONT_SIZES].
+    UnsupportedFontSize,
+    /// [TextInputError::UnsupportedCharset] error is caused when the form is submitted with a character set that is not "ascii" or "braille".
+    UnsupportedCharset,
+}
+
+impl TextFormParams {
+    /// Function to verify if the text input is valid.
+    ///
+    /// Line endings are normalized to line feeds before the text is checked, so pasted Windows line endings aren't counted twice.
+    /// When the text passes valiation, function returns `Ok(String)` with the normalized text.
+    /// Returns `Err(TextInputError::EmptyInput)` when the text is empty or only has whitespace.
+    /// Returns `Err(TextInputError::TooLong)` when the text has more than [MAX_BANNER_LENGTH] characters.
+    pub fn validate_text_input(&self) -> Result<String, TextInputError> {
+        let text = self.text_input.replace("\r\n", "\n");
+
+        if text.trim().is_empty() {
+            return Err(TextInputError::EmptyInput);
+        }
+        if text.chars().count() > MAX_BANNER_LENGTH {
+            return Err(TextInputError::TooLong);
+        }
+
+        Ok(text)
+    }
+
+    /// Function to verify if the font size form input is valid.
+    ///
+    /// Returns `Ok(u32)` when one of the [SUPPORTED_FONT_SIZES] is submitted, or the [DEFAULT_FONT_SIZE] when no font size is submitted.
+    /// Returns `Err(TextInputError::UnsupportedFontSize)` when any other font size is submitted.
+    pub fn validate_font_size(&self) -> Result<u32, TextInputError> {
+        match self.font_size.as_deref() {
+            None | Some("") => Ok(DEFAULT_FONT_SIZE),
+            Some(font_size) => match font_size.parse::<u32>() {
+                Ok(font_size) if SUPPORTED_FONT_SIZES.contains(&font_size) => Ok(font_size),
+                _ => Err(TextInputError::UnsupportedFontSize),
+            },
+        }
+    }
+
+    /// Function to verify if the character set form input is valid.
+    ///
+    /// Returns `Ok(None)` when no character set is submitted so that the default character set of the website is used.
+    /// Returns `Ok(Some(RenderCharset))` when "ascii" or "braille" is submitted.
+    /// Returns `Err(TextInputError::UnsupportedCharset)` when any other character set is submitted.
+    pub fn validate_render_charset(&self) -> Result<Option<RenderCharset>, TextInputError> {
+        match self.render_charset.as_deref() {
+            None | Some("") => Ok(None),
+            Some("ascii") => Ok(Some(RenderCharset::Ascii)),
+            Some("braille") => Ok(Some(RenderCharset::Braille)),
+            Some(_) => Err(TextInputError::UnsupportedCharset),
+        }
+    }
+}
+
+// Tests
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Creates a form with the given text and no other choices
+    fn form(text_input: &str) -> TextFormParams {
+        TextFormParams {
+            text_input: text_input.to_string(),
+            font_size: None,
+            render_charset: None,
+        }
+    }
+
+    // Verifies that TextFormParams::validate_text_input() normalizes line endings and rejects empty or long text
+    #[test]
+    fn test_validate_text_input() {
+        assert_eq!(
+            form("Hi\r\nthere").validate_text_input(),
+            Ok("Hi\nthere".to_string())
+        );
+        assert_eq!(
+            form(&"a".repeat(MAX_BANNER_LENGTH)).validate_text_input(),
+            Ok("a".repeat(MAX_BANNER_LENGTH))
+        );
+
+        assert_eq!(
+            form("").validate_text_input(),
+            Err(TextInputError::EmptyInput)
+        );
+        assert_eq!(
+            form(" \r\n ").validate_text_input(),
+            Err(TextInputError::EmptyInput)
+        );
+        assert_eq!(
+            form(&"a".repeat(MAX_BANNER_LENGTH + 1)).validate_text_input(),
+            Err(TextInputError::TooLong)
+        );
+    }
+
+    // Verifies that only the supported font sizes are accepted by TextFormParams::validate_font_size()
+    #[test]
+    fn test_validate_font_size() {
+        let mut input = form("Hi");
+        assert_eq!(input.validate_font_size(), Ok(DEFAULT_FONT_SIZE));
+
+        for font_size in SUPPORTED_FONT_SIZES {
+            input.font_size = Some(font_size.to_string());
+            assert_eq!(input.validate_font_size(), Ok(font_size));
+        }
+
+        for font_size in ["0", "5", "big"] {
+            input.font_size = Some(font_size.to_string());
+            assert_eq!(
+                input.validate_font_size(),
+                Err(TextInputError::UnsupportedFontSize)
+            );
+        }
+    }
+
+    // Verifies that only the supported character sets are accepted by TextFormParams::validate_render_charset()
+    #[test]
+    fn test_validate_render_charset() {
+        let mut input = form("Hi");
+        assert_eq!(input.validate_render_charset(), Ok(None));
+
+        input.render_charset = Some("braille".to_string());
+        assert_eq!(
+            input.validate_render_charset(),
+            Ok(Some(RenderCharset::Braille))
+        );
+
+        input.render_charset = Some("half-block".to_string());
+        assert_eq!(
+            input.validate_render_charset(),
+            Err(TextInputError::UnsupportedCharset)
+        );
+    }
+}
@@ -0,0 +1,192 @@
+//! Module to serve the website over HTTPS without a reverse proxy in front of it.
+//!
+//! When the `tls_cert_path` and `tls_key_path` of the [AppConfig] are set, [load_tls_config] reads the certificate chain
+//! and its private key from PEM files into the rustls configuration the server listens on the `tls_port` with. The plain
+//! `port` then only answers the health endpoints and redirects every other request to the same path over HTTPS, using
+//! the address [https_url] builds.
+//!
+//! Robert Peterson and Kelsey Werner 2023
+
+use super::config::AppConfig;
+use rustls::{Certificate, PrivateKey, ServerConfig};
+use rustls_pemfile::Item;
+use std::{
+    fmt, fs,
+    io::{self, BufReader},
+    path::{Path, PathBuf},
+};
+
+/// Function to read the TLS certificate and key of an [AppConfig] into the configuration HTTPS is served with.
+///
+/// Returns [None] when neither path is set, so the server only serves HTTP.
+/// If only one of the paths is set, or the files can't be read or used, then a [TlsError] is returned instead.
+pub fn load_tls_config(config: &AppConfig) -> Result<Option<ServerConfig>, TlsError> {
+    let (cert_path, key_path) = match (&config.tls_cert_path, &config.tls_key_path) {
+        (None, None) => return Ok(None),
+        (Some(_), None) => return Err(TlsError::MissingPath("tls_key_path")),
+        (None, Some(_)) => return Err(TlsError::MissingPath("tls_cert_path")),
+        (Some(cert_path), Some(key_path)) => (cert_path, key_path),
+    };
+
+    let certificates: Vec<Certificate> = read_pem(cert_path)?
+        .into_iter()
+        .filter_map(|item| match item {
+            Item::X509Certificate(certificate) => Some(Certificate(certificate)),
+            _ => None,
+        })
+        .collect();
+    if certificates.is_empty() {
+        return Err(TlsError::NoCertificate(cert_path.clone()));
+    }
+
+    let key = read_pem(key_path)?
+        .into_iter()
+        .find_map(|item| match item {
+            Item::RSAKey(key) | Item::PKCS8Key(key) | Item::ECKey(key) => Some(PrivateKey(key)),
+            _ => None,
+        })
+        .ok_or_else(|| TlsError::NoKey(key_path.clone()))?;
+
+    ServerConfig::builder()
+        .with_safe_defaults()
+        .with_no_client_auth()
+        .with_single_cert(certificates, key)
+        .map(Some)
+        .map_err(|error| TlsError::Rejected(error.to_string()))
+}
+
+/// Function to read every item of a PEM file.
+fn read_pem(path: &Path) -> Result<Vec<Item>, TlsError> {
+    let file = fs::File::open(path)
+        .map_err(|error| TlsError::UnreadableFile(path.to_path_buf(), error))?;
+    rustls_pemfile::read_all(&mut BufReader::new(file))
+        .map_err(|error| TlsError::UnreadableFile(path.to_path_buf(), error))
+}
+
+/// Function to build the HTTPS address of a request that was sent over HTTP to `host`, served on `tls_port`.
+///
+/// The port the request was sent to is replaced with `tls_port`, which is left out when it is the default port of HTTPS.
+pub fn https_url(host: &str, path_and_query: &str, tls_port: u16) -> String {
+    // an IPv6 address is in brackets, since it has colons of its own
+    let hostname = match host.find(']') {
+        Some(end) if host.starts_with('[') => &host[..=end],
+        _ => host.split(':').next().unwrap_or(host),
+    };
+    match tls_port {
+        443 => format!("https://{}{}", hostname, path_and_query),
+        port => format!("https://{}:{}{}", hostname, port, path_and_query),
+    }
+}
+
+/// Enum to store the errors that can occur while reading the TLS certificate and key.
+///
+/// The different enum variants are used to identify the specific cause of an error.
+#[derive(Debug)]
+pub enum TlsError {
+    /// [TlsError::MissingPath] error is caused when only one of the TLS paths is set, and names the one that is missing.
+    MissingPath(&'static str),
+    /// [TlsError::UnreadableFile] error is caused when the certificate or key file doesn't exist or can't be read.
+    UnreadableFile(PathBuf, io::Error),
+    /// [TlsError::NoCertificate] error is caused when the certificate file has no PEM certificates.
+    NoCertificate(PathBuf),
+    /// [TlsError::NoKey] error is caused when the key file has no PEM private key.
+    NoKey(PathBuf),
+    /// [TlsError::Rejected] error is caused when rustls can't use the certificate with the key, such as when they don't
+    /// belong together.
+    Rejected(String),
+}
+
+impl fmt::Display for TlsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TlsError::MissingPath(setting) => write!(
+                f,
+                "HTTPS needs both a certificate and a key, but {} is not set",
+                setting
+            ),
+            TlsError::UnreadableFile(path, error) => {
+                write!(
+                    f,
+                    "The TLS file {} can't be read: {}",
+                    path.display(),
+                    error
+                )
+            }
+            TlsError::NoCertificate(path) => {
+                write!(f, "The TLS file {} has no certificate", path.display())
+            }
+            TlsError::NoKey(path) => {
+                write!(f, "The TLS file {} has no private key", path.display())
+            }
+            TlsError::Rejected(error) => {
+                write!(f, "The TLS certificate and key can't be used: {}", error)
+            }
+        }
+    }
+}
+
+impl std::error::Error for TlsError {}
+
+// Tests
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // The self-signed certificate for localhost the tests serve HTTPS with
+    const TEST_CERT: &str = "./test_assets/tls/cert.pem";
+
+    // The private key of the test certificate
+    const TEST_KEY: &str = "./test_assets/tls/key.pem";
+
+    // Creates a configuration with the TLS paths
+    fn tls_config(cert_path: Option<&str>, key_path: Option<&str>) -> AppConfig {
+        AppConfig {
+            tls_cert_path: cert_path.map(PathBuf::from),
+            tls_key_path: key_path.map(PathBuf::from),
+            ..AppConfig::default()
+        }
+    }
+
+    // Verifies that the certificate and key are only loaded when both are set and can be used
+    #[test]
+    fn test_load_tls_config() {
+        assert!(load_tls_config(&tls_config(None, None)).unwrap().is_none());
+        assert!(
+            load_tls_config(&tls_config(Some(TEST_CERT), Some(TEST_KEY)))
+                .unwrap()
+                .is_some()
+        );
+
+        assert!(matches!(
+            load_tls_config(&tls_config(Some(TEST_CERT), None)),
+            Err(TlsError::MissingPath("tls_key_path"))
+        ));
+        assert!(matches!(
+            load_tls_config(&tls_config(Some("./missing.pem"), Some(TEST_KEY))),
+            Err(TlsError::UnreadableFile(_, _))
+        ));
+        assert!(matches!(
+            load_tls_config(&tls_config(Some(TEST_KEY), Some(TEST_KEY))),
+            Err(TlsError::NoCertificate(_))
+        ));
+        assert!(matches!(
+            load_tls_config(&tls_config(Some(TEST_CERT), Some(TEST_CERT))),
+            Err(TlsError::NoKey(_))
+        ));
+    }
+
+    // Verifies that requests are redirected to the same host and path on the HTTPS port
+    #[test]
+    fn test_https_url() {
+        assert_eq!(
+            https_url("example.com:8080", "/gallery?page=2", 8443),
+            "https://example.com:8443/gallery?page=2"
+        );
+        assert_eq!(https_url("example.com", "/", 443), "https://example.com/");
+        assert_eq!(
+            https_url("[::1]:80", "/readyz", 443),
+            "https://[::1]/readyz"
+        );
+    }
+}
@@ -0,0 +1,400 @@
+//! Module to store user accounts, their sessions and the ASCII art they saved.
+//!
+//! Accounts are kept in the same SQLite database as the gallery, in tables that are created the first time the database is
+//! connected to. Each [User] has a unique name and the argon2 hash of their password made by [super::auth], and signing in
+//! adds a session whose token is kept in a cookie by the browser. Only the SHA-256 hash of the token is stored, so a copy of
+//! the database can't be used to sign in. Sessions expire [SESSION_TTL_DAYS] days after they are made.
+//!
+//! A signed in user can save ASCII art as an [Artwork] with a name, and later rename it, edit it or delete it on the GET
+//! /my-art page. Every query of the artworks is limited to the user that owns them, so one user can never read or change the
+//! art of another.
+//!
+//! Robert Peterson and Kelsey Werner 2023
+
+use super::{art_store::content_hash, gallery::MAX_TITLE_LENGTH};
+use serde::{Deserialize, Serialize};
+use sqlx::{
+    sqlite::{SqliteConnectOptions, SqlitePool, SqlitePoolOptions},
+    Executor,
+};
+use std::{fmt, str::FromStr};
+
+/// The number of days a session lasts before the user has to sign in again.
+pub const SESSION_TTL_DAYS: u32 = 30;
+
+/// The most characters the ASCII art of an [Artwork] can have.
+pub const MAX_ARTWORK_LENGTH: usize = 100_000;
+
+/// The SQL that creates the tables of users, sessions and artworks when they don't exist yet.
+const USER_SCHEMA: [&str; 3] = [
+    "CREATE TABLE IF NOT EXISTS users (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        username TEXT NOT NULL UNIQUE COLLATE NOCASE,
+        password_hash TEXT NOT NULL,
+        created_at TEXT NOT NULL DEFAULT (datetime('now'))
+    )",
+    "CREATE TABLE IF NOT EXISTS sessions (
+        token_hash TEXT PRIMARY KEY,
+        user_id INTEGER NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+        expires_at TEXT NOT NULL
+    )",
+    "CREATE TABLE IF NOT EXISTS artworks (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        user_id INTEGER NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+        name TEXT NOT NULL,
+        ascii TEXT NOT NULL,
+        created_at TEXT NOT NULL DEFAULT (datetime('now')),
+        updated_at TEXT NOT NULL DEFAULT (datetime('now'))
+    )",
+];
+
+/// Struct to store a user account.
+#[derive(Debug, Clone, PartialEq, Serialize, sqlx::FromRow)]
+pub struct User {
+    /// The id of the user.
+    pub id: i64,
+    /// The name the user signs in with, which is unique whatever its case.
+    pub username: String,
+    /// The argon2 hash of the password of the user, which is never shown.
+    #[serde(skip)]
+    pub password_hash: String,
+}
+
+/// Struct to store ASCII art a user saved.
+#[derive(Debug, Clone, PartialEq, Serialize, sqlx::FromRow)]
+pub struct Artwork {
+    /// The id of the artwork, which is used in the addresses that change it.
+    pub id: i64,
+    /// The id of the [User] that saved the artwork.
+    pub user_id: i64,
+    /// The name the user gave the artwork.
+    pub name: String,
+    /// The text of the ASCII art.
+    pub ascii: String,
+    /// The time the artwork was saved in UTC, such as "2023-06-09 04:58:50".
+    pub created_at: String,
+    /// The time the artwork was last changed in UTC.
+    pub updated_at: String,
+}
+
+/// Struct to store the name and ASCII art of an artwork.
+///
+/// Actix Web populates [ArtworkFormParams] with the form data of the GET /my-art page.
+#[derive(Serialize, Deserialize)]
+pub struct ArtworkFormParams {
+    /// [String] to store the name of the artwork.
+    pub name: String,
+    /// [String] to store the ASCII art.
+    pub ascii: String,
+}
+
+/// Enum to store the possible error states that can be detected when sanitizing an artwork.
+///
+/// The different enum variants are used to identify the specific cause of an error.
+#[derive(PartialEq, Debug)]
+pub enum ArtworkInputError {
+    /// [ArtworkInputError::EmptyName] error is caused when the form is submitted without a name, or with only whitespace.
+    EmptyName,
+    /// [ArtworkInputError::EmptyArt] error is caused when the form is submitted without ASCII art, or with only whitespace.
+    EmptyArt,
+    /// [ArtworkInputError::TooLong] error is caused when the ASCII art has more than [MAX_ARTWORK_LENGTH] characters.
+    TooLong,
+}
+
+impl fmt::Display for ArtworkInputError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ArtworkInputError::EmptyName => write!(f, "It looks like your art has no name! Give it a name so you can find it later."),
+            ArtworkInputError::EmptyArt => write!(f, "It looks like there is no ASCII art to save! Paste or type your art and try it one more time."),
+            ArtworkInputError::TooLong => write!(f, "It looks like your art is too long! Saved art can have at most {} characters.", MAX_ARTWORK_LENGTH),
+        }
+    }
+}
+
+impl ArtworkFormParams {
+    /// Function to verify if the artwork is valid.
+    ///
+    /// The name is trimmed and cut to [MAX_TITLE_LENGTH] characters, and line endings of the ASCII art are normalized to line
+    /// feeds. When the artwork passes validation, function returns `Ok((String, String))` with the name and the ASCII art.
+    /// Returns `Err(ArtworkInputError::EmptyName)` when the name is blank, `Err(ArtworkInputError::EmptyArt)` when the ASCII
+    /// art is blank, and `Err(ArtworkInputError::TooLong)` when it has more than [MAX_ARTWORK_LENGTH] characters.
+    pub fn validate(&self) -> Result<(String, String), ArtworkInputError> {
+        let name: String = self.name.trim().chars().take(MAX_TITLE_LENGTH).collect();
+        let ascii = self.ascii.replace("\r\n", "\n");
+
+        if name.is_empty() {
+            return Err(ArtworkInputError::EmptyName);
+        }
+        if ascii.trim().is_empty() {
+            return Err(ArtworkInputError::EmptyArt);
+        }
+        if ascii.chars().count() > MAX_ARTWORK_LENGTH {
+            return Err(ArtworkInputError::TooLong);
+        }
+
+        Ok((name, ascii))
+    }
+}
+
+/// Struct to store user accounts, sessions and artworks in a SQLite database.
+///
+/// The [UserStore] can be cloned cheaply, since every clone shares the same pool of connections.
+#[derive(Clone)]
+pub struct UserStore {
+    /// The pool of connections to the database.
+    pool: SqlitePool,
+}
+
+impl UserStore {
+    /// Function to open the accounts stored in the database at `url`, which is created if it doesn't exist yet.
+    ///
+    /// The connections are only made once the store is used, and the tables are created on each new connection if they
+    /// don't exist yet. Returns an error if the url is not a SQLite url.
+    pub fn new(url: &str) -> Result<UserStore, sqlx::Error> {
+        let options = SqliteConnectOptions::from_str(url)?
+            .create_if_missing(true)
+            .foreign_keys(true);
+        let pool = SqlitePoolOptions::new()
+            .after_connect(|connection, _| {
+                Box::pin(async move {
+                    for statement in USER_SCHEMA {
+                        connection.execute(statement).await?;
+                    }
+                    Ok(())
+                })
+            })
+            .connect_lazy_with(options);
+
+        Ok(UserStore { pool })
+    }
+
+    /// Function to add a user with the hash of their password.
+    ///
+    /// Returns `Ok(None)` without adding the user when another user already has the name, whatever its case.
+    pub async fn add_user(
+        &self,
+        username: &str,
+        password_hash: &str,
+    ) -> Result<Option<User>, sqlx::Error> {
+        let result = sqlx::query(
+            "INSERT INTO users (username, password_hash) VALUES (?, ?) ON CONFLICT (username) DO NOTHING",
+        )
+        .bind(username)
+        .bind(password_hash)
+        .execute(&self.pool)
+        .await?;
+
+        Ok((result.rows_affected() == 1).then(|| User {
+            id: result.last_insert_rowid(),
+            username: username.to_string(),
+            password_hash: password_hash.to_string(),
+        }))
+    }
+
+    /// Function to find the user with a name, whatever its case, or `Ok(None)` when there is no such user.
+    pub async fn find_user(&self, username: &str) -> Result<Option<User>, sqlx::Error> {
+        sqlx::query_as("SELECT id, username, password_hash FROM users WHERE username = ?")
+            .bind(username)
+            .fetch_optional(&self.pool)
+            .await
+    }
+
+    /// Function to start a session of a user with a token that was sent to their browser.
+    ///
+    /// The session expires [SESSION_TTL_DAYS] days from now, and sessions that already expired are removed.
+    pub async fn start_session(&self, user_id: i64, token: &str) -> Result<(), sqlx::Error> {
+        sqlx::query("DELETE FROM sessions WHERE expires_at <= datetime('now')")
+            .execute(&self.pool)
+            .await?;
+        sqlx::query("INSERT INTO sessions (token_hash, user_id, expires_at) VALUES (?, ?, datetime('now', ?))")
+            .bind(content_hash(token))
+            .bind(user_id)
+            .bind(format!("+{} days", SESSION_TTL_DAYS))
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Function to find the user whose session has the token, or `Ok(None)` when there is no such session or it expired.
+    pub async fn session_user(&self, token: &str) -> Result<Option<User>, sqlx::Error> {
+        sqlx::query_as(
+            "SELECT users.id, users.username, users.password_hash FROM sessions
+            JOIN users ON users.id = sessions.user_id
+            WHERE sessions.token_hash = ? AND sessions.expires_at > datetime('now')",
+        )
+        .bind(content_hash(token))
+        .fetch_optional(&self.pool)
+        .await
+    }
+
+    /// Function to end the session with the token, such as when the user signs out.
+    pub async fn end_session(&self, token: &str) -> Result<(), sqlx::Error> {
+        sqlx::query("DELETE FROM sessions WHERE token_hash = ?")
+            .bind(content_hash(token))
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Function to save ASCII art with a name for a user. Returns the id of the new [Artwork].
+    pub async fn add_artwork(
+        &self,
+        user_id: i64,
+        name: &str,
+        ascii: &str,
+    ) -> Result<i64, sqlx::Error> {
+        let result = sqlx::query("INSERT INTO artworks (user_id, name, ascii) VALUES (?, ?, ?)")
+            .bind(user_id)
+            .bind(name)
+            .bind(ascii)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.last_insert_rowid())
+    }
+
+    /// Function to get every artwork of a user, the most recently changed first.
+    pub async fn artworks(&self, user_id: i64) -> Result<Vec<Artwork>, sqlx::Error> {
+        sqlx::query_as("SELECT * FROM artworks WHERE user_id = ? ORDER BY updated_at DESC, id DESC")
+            .bind(user_id)
+            .fetch_all(&self.pool)
+            .await
+    }
+
+    /// Function to change the name and ASCII art of an artwork of a user.
+    ///
+    /// Returns `Ok(false)` when the user has no artwork with the id.
+    pub async fn update_artwork(
+        &self,
+        user_id: i64,
+        id: i64,
+        name: &str,
+        ascii: &str,
+    ) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query(
+            "UPDATE artworks SET name = ?, ascii = ?, updated_at = datetime('now') WHERE id = ? AND user_id = ?",
+        )
+        .bind(name)
+        .bind(ascii)
+        .bind(id)
+        .bind(user_id)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() == 1)
+    }
+
+    /// Function to delete an artwork of a user.
+    ///
+    /// Returns `Ok(false)` when the user has no artwork with the id.
+    pub async fn delete_artwork(&self, user_id: i64, id: i64) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query("DELETE FROM artworks WHERE id = ? AND user_id = ?")
+            .bind(id)
+            .bind(user_id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected() == 1)
+    }
+}
+
+// Tests
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Opens a user store in a new database in a temporary directory
+    fn temporary_store() -> (tempfile::TempDir, UserStore) {
+        let directory = tempfile::tempdir().unwrap();
+        let url = format!("sqlite://{}", directory.path().join("users.db").display());
+        let store = UserStore::new(&url).unwrap();
+        (directory, store)
+    }
+
+    // Creates an artwork form with the given name and ASCII art
+    fn form(name: &str, ascii: &str) -> ArtworkFormParams {
+        ArtworkFormParams {
+            name: name.to_string(),
+            ascii: ascii.to_string(),
+        }
+    }
+
+    // Verifies that ArtworkFormParams::validate() trims the name, normalizes line endings and rejects blank or long art
+    #[test]
+    fn test_validate_artwork() {
+        assert_eq!(
+            form("  Castle ", "/\\\r\n||").validate(),
+            Ok(("Castle".to_string(), "/\\\n||".to_string()))
+        );
+        assert_eq!(
+            form(" ", "$$").validate(),
+            Err(ArtworkInputError::EmptyName)
+        );
+        assert_eq!(
+            form("Castle", " \n ").validate(),
+            Err(ArtworkInputError::EmptyArt)
+        );
+        assert_eq!(
+            form("Castle", &"$".repeat(MAX_ARTWORK_LENGTH + 1)).validate(),
+            Err(ArtworkInputError::TooLong)
+        );
+    }
+
+    // Verifies that user names are unique whatever their case, and that sessions find their user until they end
+    #[actix_web::test]
+    async fn test_users_and_sessions() {
+        let (_directory, store) = temporary_store();
+
+        let user = store.add_user("Kelsey", "hash").await.unwrap().unwrap();
+
+        assert_eq!(store.add_user("kelsey", "other").await.unwrap(), None);
+        assert_eq!(store.find_user("KELSEY").await.unwrap(), Some(user.clone()));
+        assert_eq!(store.find_user("robert").await.unwrap(), None);
+
+        store.start_session(user.id, "token").await.unwrap();
+
+        assert_eq!(store.session_user("token").await.unwrap(), Some(user));
+        assert_eq!(store.session_user("wrong").await.unwrap(), None);
+
+        store.end_session("token").await.unwrap();
+
+        assert_eq!(store.session_user("token").await.unwrap(), None);
+    }
+
+    // Verifies that artworks can be saved, changed and deleted, but only by the user that owns them
+    #[actix_web::test]
+    async fn test_artworks() {
+        let (_directory, store) = temporary_store();
+        let kelsey = store.add_user("kelsey", "hash").await.unwrap().unwrap();
+        let robert = store.add_user("robert", "hash").await.unwrap().unwrap();
+
+        let id = store
+            .add_artwork(kelsey.id, "Castle", "/\\\n||")
+            .await
+            .unwrap();
+        store.add_artwork(robert.id, "Fish", "><>").await.unwrap();
+
+        let artworks = store.artworks(kelsey.id).await.unwrap();
+        assert_eq!(artworks.len(), 1);
+        assert_eq!(artworks[0].name, "Castle");
+        assert_eq!(artworks[0].ascii, "/\\\n||");
+
+        assert!(!store
+            .update_artwork(robert.id, id, "Mine", "$")
+            .await
+            .unwrap());
+        assert!(!store.delete_artwork(robert.id, id).await.unwrap());
+        assert!(store
+            .update_artwork(kelsey.id, id, "Tower", "|")
+            .await
+            .unwrap());
+        assert_eq!(store.artworks(kelsey.id).await.unwrap()[0].name, "Tower");
+
+        assert!(store.delete_artwork(kelsey.id, id).await.unwrap());
+        assert!(store.artworks(kelsey.id).await.unwrap().is_empty());
+        assert_eq!(store.artworks(robert.id).await.unwrap().len(), 1);
+    }
+}
@@ -0,0 +1,7 @@
+// the text of the pre element ends with the indent of its closing tag, which is not part of the art
+document.getElementById("copy_ascii").addEventListener("click", (event) => {
+    const ascii = document.getElementById("ascii_art").textContent.replace(/\n[ ]*$/, "\n");
+    navigator.clipboard.writeText(ascii).then(() => {
+        event.target.textContent = "Copied!";
+    });
+});
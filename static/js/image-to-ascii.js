@@ -0,0 +1,150 @@
+// the cropper posts the selected part of the image as "x,y,width,height" in the pixels of the image
+const imageInput = document.getElementById("image_input");
+const cropRow = document.getElementById("crop_row");
+const preview = document.getElementById("crop_preview");
+const selection = document.getElementById("crop_selection");
+const crop = document.getElementById("crop");
+let start = null;
+
+function clearSelection() {
+    crop.value = "";
+    selection.hidden = true;
+}
+
+// the point of the pointer event inside of the preview, in the pixels the preview is shown with
+function pointOf(event) {
+    const box = preview.getBoundingClientRect();
+    return {
+        x: Math.min(Math.max(event.clientX - box.left, 0), box.width),
+        y: Math.min(Math.max(event.clientY - box.top, 0), box.height),
+    };
+}
+
+function showSelection(from, to) {
+    selection.style.left = Math.min(from.x, to.x) + "px";
+    selection.style.top = Math.min(from.y, to.y) + "px";
+    selection.style.width = Math.abs(to.x - from.x) + "px";
+    selection.style.height = Math.abs(to.y - from.y) + "px";
+    selection.hidden = false;
+}
+
+function selectRegion(from, to) {
+    const scale = preview.naturalWidth / preview.getBoundingClientRect().width;
+    const x = Math.round(Math.min(from.x, to.x) * scale);
+    const y = Math.round(Math.min(from.y, to.y) * scale);
+    const width = Math.min(Math.round(Math.abs(to.x - from.x) * scale), preview.naturalWidth - x);
+    const height = Math.min(Math.round(Math.abs(to.y - from.y) * scale), preview.naturalHeight - y);
+    if (width < 1 || height < 1) {
+        clearSelection();
+        return;
+    }
+    crop.value = [x, y, width, height].join(",");
+}
+
+imageInput.addEventListener("change", () => {
+    clearSelection();
+    const file = imageInput.files[0];
+    cropRow.hidden = !file;
+    if (file) {
+        preview.src = URL.createObjectURL(file);
+    }
+});
+// images the browser can't show, such as TIFF, are converted whole
+preview.addEventListener("error", () => {
+    cropRow.hidden = true;
+});
+preview.addEventListener("pointerdown", (event) => {
+    start = pointOf(event);
+    preview.setPointerCapture(event.pointerId);
+    clearSelection();
+});
+preview.addEventListener("pointermove", (event) => {
+    if (start) {
+        showSelection(start, pointOf(event));
+    }
+});
+preview.addEventListener("pointerup", (event) => {
+    if (start) {
+        selectRegion(start, pointOf(event));
+        start = null;
+    }
+});
+document.getElementById("crop_clear").addEventListener("click", clearSelection);
+
+// the sliders show the amount they adjust the image by
+for (const name of ["brightness", "contrast"]) {
+    const slider = document.getElementById(name);
+    const value = document.getElementById(name + "_value");
+    slider.addEventListener("input", () => {
+        value.textContent = slider.value;
+    });
+}
+
+// the preview posts the form to the stream of server-sent events, and shows each line event as it arrives
+const streamButton = document.getElementById("stream_button");
+const streamRow = document.getElementById("stream_row");
+const streamStatus = document.getElementById("stream_status");
+const streamOutput = document.getElementById("stream_output");
+
+function showStreamStatus(message, kind) {
+    streamStatus.textContent = message;
+    streamStatus.className = "alert alert-" + kind;
+}
+
+// an event is a block of "field: value" lines, and only one space after the colon belongs to the field
+function handleStreamEvent(block) {
+    let name = "message";
+    const data = [];
+    for (const line of block.split("\n")) {
+        const colon = line.indexOf(":");
+        const field = colon < 0 ? line : line.slice(0, colon);
+        const value = colon < 0 ? "" : line.slice(colon + 1).replace(/^ /, "");
+        if (field === "event") {
+            name = value;
+        } else if (field === "data") {
+            data.push(value);
+        }
+    }
+    if (name === "line") {
+        streamOutput.textContent += data.join("\n") + "\n";
+    } else if (name === "end") {
+        showStreamStatus("Your ASCII art is done! Submit the form to save it.", "success");
+    } else if (name === "error") {
+        showStreamStatus(data.join(" "), "danger");
+    }
+}
+
+streamButton.addEventListener("click", async () => {
+    streamButton.disabled = true;
+    streamRow.hidden = false;
+    streamOutput.textContent = "";
+    showStreamStatus("Converting your image...", "info");
+    try {
+        const response = await fetch("/stream-image-to-ascii", {
+            method: "POST",
+            body: new FormData(streamButton.form),
+        });
+        if (!response.ok) {
+            showStreamStatus(await response.text(), "danger");
+            return;
+        }
+        const reader = response.body.pipeThrough(new TextDecoderStream()).getReader();
+        let buffered = "";
+        for (;;) {
+            const { value, done } = await reader.read();
+            if (done) {
+                break;
+            }
+            buffered += value;
+            let end;
+            while ((end = buffered.indexOf("\n\n")) >= 0) {
+                handleStreamEvent(buffered.slice(0, end));
+                buffered = buffered.slice(end + 2);
+            }
+        }
+    } catch (error) {
+        showStreamStatus("It looks like we lost the connection while converting your image! Try it one more time.", "danger");
+    } finally {
+        streamButton.disabled = false;
+    }
+});